@@ -8,7 +8,7 @@ use stwo::core::{
     vcs::poseidon31_merkle::Poseidon31MerkleHasher,
 };
 
-use crate::CairoFiatShamirHints;
+use crate::{CairoFiatShamirHints, VerificationError};
 
 pub struct AnswerHints {
     pub answers_log_sizes: Vec<Vec<SecureField>>,
@@ -20,6 +20,13 @@ impl AnswerHints {
         fiat_shamir_hints: &CairoFiatShamirHints,
         proof: &CairoProof<Poseidon31MerkleHasher>,
     ) -> Self {
+        Self::try_new(fiat_shamir_hints, proof).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_new(
+        fiat_shamir_hints: &CairoFiatShamirHints,
+        proof: &CairoProof<Poseidon31MerkleHasher>,
+    ) -> Result<Self, VerificationError> {
         let samples = fiat_shamir_hints
             .sample_points
             .clone()
@@ -62,7 +69,7 @@ impl AnswerHints {
             proof.stark_proof.queried_values.clone(),
             n_columns_per_log_size,
         )
-        .unwrap();
+        .map_err(|_| VerificationError::QueriedValues)?;
 
         let max_query = fiat_shamir_hints
             .query_positions_per_log_size
@@ -90,16 +97,17 @@ impl AnswerHints {
             queries_answers.push(map);
         }
 
-        Self {
+        Ok(Self {
             answers_log_sizes,
             queries_answers,
-        }
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::CairoVerifierParams;
     use cairo_air::utils::{deserialize_proof_from_file, ProofFormat};
     use std::path::PathBuf;
 
@@ -113,7 +121,8 @@ mod tests {
             .join("recursive_proof.bin.bz");
 
         let proof = deserialize_proof_from_file(&data_path, ProofFormat::Binary).unwrap();
-        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof);
+        let params = CairoVerifierParams::from_proof(&proof);
+        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof, &params);
         let answer_hints = AnswerHints::new(&fiat_shamir_hints, &proof);
 
         fiat_shamir_hints
@@ -5,7 +5,7 @@ use stwo::core::{
     vcs::poseidon31_merkle::Poseidon31MerkleHasher,
 };
 
-use crate::CairoFiatShamirHints;
+use crate::{CairoFiatShamirHints, VerificationError};
 
 pub struct CairoCompositionHints {}
 
@@ -14,6 +14,32 @@ impl CairoCompositionHints {
         fiat_shamir_hints: &CairoFiatShamirHints,
         proof: &CairoProof<Poseidon31MerkleHasher>,
     ) -> Self {
+        Self::try_new(fiat_shamir_hints, proof).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Evaluate the constraints at the OODS point, and return an error when the sampled values
+    /// do not match the mask points or the composition polynomial.
+    pub fn try_new(
+        fiat_shamir_hints: &CairoFiatShamirHints,
+        proof: &CairoProof<Poseidon31MerkleHasher>,
+    ) -> Result<Self, VerificationError> {
+        let sampled_values = &proof.stark_proof.sampled_values;
+        let sample_points = &fiat_shamir_hints.sample_points;
+        if sampled_values.len() != sample_points.len()
+            || sampled_values
+                .iter()
+                .zip(sample_points.iter())
+                .any(|(values, points)| {
+                    values.len() != points.len()
+                        || values
+                            .iter()
+                            .zip(points.iter())
+                            .any(|(values, points)| values.len() != points.len())
+                })
+        {
+            return Err(VerificationError::SampledValuesShape);
+        }
+
         let oods_point = fiat_shamir_hints.oods_point;
         let random_coeff = &fiat_shamir_hints.random_coeff;
         let component_generator = &fiat_shamir_hints.component_generator;
@@ -21,92 +47,146 @@ impl CairoCompositionHints {
         let mut evaluation_accumulator = PointEvaluationAccumulator::new(*random_coeff);
 
         // opcodes
-        component_generator.opcodes.add[0].evaluate_constraint_quotients_at_point(
-            oods_point,
-            &proof.stark_proof.sampled_values,
-            &mut evaluation_accumulator,
-        );
-        component_generator.opcodes.add_small[0].evaluate_constraint_quotients_at_point(
-            oods_point,
-            &proof.stark_proof.sampled_values,
-            &mut evaluation_accumulator,
-        );
-        component_generator.opcodes.add_ap[0].evaluate_constraint_quotients_at_point(
-            oods_point,
-            &proof.stark_proof.sampled_values,
-            &mut evaluation_accumulator,
-        );
-        component_generator.opcodes.assert_eq[0].evaluate_constraint_quotients_at_point(
-            oods_point,
-            &proof.stark_proof.sampled_values,
-            &mut evaluation_accumulator,
-        );
-        component_generator.opcodes.assert_eq_imm[0].evaluate_constraint_quotients_at_point(
-            oods_point,
-            &proof.stark_proof.sampled_values,
-            &mut evaluation_accumulator,
-        );
-        component_generator.opcodes.assert_eq_double_deref[0]
-            .evaluate_constraint_quotients_at_point(
+        for component in component_generator.opcodes.add.iter() {
+            component.evaluate_constraint_quotients_at_point(
                 oods_point,
                 &proof.stark_proof.sampled_values,
                 &mut evaluation_accumulator,
             );
-        component_generator.opcodes.blake[0].evaluate_constraint_quotients_at_point(
-            oods_point,
-            &proof.stark_proof.sampled_values,
-            &mut evaluation_accumulator,
-        );
-        component_generator.opcodes.call[0].evaluate_constraint_quotients_at_point(
-            oods_point,
-            &proof.stark_proof.sampled_values,
-            &mut evaluation_accumulator,
-        );
-        component_generator.opcodes.call_rel_imm[0].evaluate_constraint_quotients_at_point(
-            oods_point,
-            &proof.stark_proof.sampled_values,
-            &mut evaluation_accumulator,
-        );
-        component_generator.opcodes.jnz[0].evaluate_constraint_quotients_at_point(
-            oods_point,
-            &proof.stark_proof.sampled_values,
-            &mut evaluation_accumulator,
-        );
-        component_generator.opcodes.jnz_taken[0].evaluate_constraint_quotients_at_point(
-            oods_point,
-            &proof.stark_proof.sampled_values,
-            &mut evaluation_accumulator,
-        );
-        component_generator.opcodes.jump_rel[0].evaluate_constraint_quotients_at_point(
-            oods_point,
-            &proof.stark_proof.sampled_values,
-            &mut evaluation_accumulator,
-        );
-        component_generator.opcodes.jump_rel_imm[0].evaluate_constraint_quotients_at_point(
-            oods_point,
-            &proof.stark_proof.sampled_values,
-            &mut evaluation_accumulator,
-        );
-        component_generator.opcodes.mul[0].evaluate_constraint_quotients_at_point(
-            oods_point,
-            &proof.stark_proof.sampled_values,
-            &mut evaluation_accumulator,
-        );
-        component_generator.opcodes.mul_small[0].evaluate_constraint_quotients_at_point(
-            oods_point,
-            &proof.stark_proof.sampled_values,
-            &mut evaluation_accumulator,
-        );
-        component_generator.opcodes.qm31[0].evaluate_constraint_quotients_at_point(
-            oods_point,
-            &proof.stark_proof.sampled_values,
-            &mut evaluation_accumulator,
-        );
-        component_generator.opcodes.ret[0].evaluate_constraint_quotients_at_point(
-            oods_point,
-            &proof.stark_proof.sampled_values,
-            &mut evaluation_accumulator,
-        );
+        }
+        for component in component_generator.opcodes.add_small.iter() {
+            component.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
+        for component in component_generator.opcodes.add_ap.iter() {
+            component.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
+        for component in component_generator.opcodes.assert_eq.iter() {
+            component.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
+        for component in component_generator.opcodes.assert_eq_imm.iter() {
+            component.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
+        for component in component_generator.opcodes.assert_eq_double_deref.iter() {
+            component.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
+        for component in component_generator.opcodes.blake.iter() {
+            component.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
+        for component in component_generator.opcodes.call.iter() {
+            component.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
+        for component in component_generator.opcodes.call_rel_imm.iter() {
+            component.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
+        for component in component_generator.opcodes.generic.iter() {
+            component.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
+        for component in component_generator.opcodes.jnz.iter() {
+            component.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
+        for component in component_generator.opcodes.jnz_taken.iter() {
+            component.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
+        for component in component_generator.opcodes.jump.iter() {
+            component.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
+        for component in component_generator.opcodes.jump_double_deref.iter() {
+            component.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
+        for component in component_generator.opcodes.jump_rel.iter() {
+            component.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
+        for component in component_generator.opcodes.jump_rel_imm.iter() {
+            component.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
+        for component in component_generator.opcodes.mul.iter() {
+            component.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
+        for component in component_generator.opcodes.mul_small.iter() {
+            component.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
+        for component in component_generator.opcodes.qm31.iter() {
+            component.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
+        for component in component_generator.opcodes.ret.iter() {
+            component.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
 
         // verify_instruction
         component_generator
@@ -160,6 +240,41 @@ impl CairoCompositionHints {
             );
 
         // builtins
+        if let Some(add_mod_builtin) = &component_generator.builtins.add_mod_builtin {
+            add_mod_builtin.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
+        if let Some(bitwise_builtin) = &component_generator.builtins.bitwise_builtin {
+            bitwise_builtin.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
+        if let Some(mul_mod_builtin) = &component_generator.builtins.mul_mod_builtin {
+            mul_mod_builtin.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
+        if let Some(poseidon_builtin) = &component_generator.builtins.poseidon_builtin {
+            poseidon_builtin.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
+        if let Some(range_check_96_builtin) = &component_generator.builtins.range_check_96_builtin {
+            range_check_96_builtin.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
         let range_check_128_builtin = &component_generator
             .builtins
             .range_check_128_builtin
@@ -171,6 +286,53 @@ impl CairoCompositionHints {
             &mut evaluation_accumulator,
         );
 
+        // poseidon_context
+        if let Some(poseidon_context_components) = &component_generator.poseidon_context.components
+        {
+            poseidon_context_components
+                .poseidon_aggregator
+                .evaluate_constraint_quotients_at_point(
+                    oods_point,
+                    &proof.stark_proof.sampled_values,
+                    &mut evaluation_accumulator,
+                );
+            poseidon_context_components
+                .poseidon_3_partial_rounds_chain
+                .evaluate_constraint_quotients_at_point(
+                    oods_point,
+                    &proof.stark_proof.sampled_values,
+                    &mut evaluation_accumulator,
+                );
+            poseidon_context_components
+                .poseidon_full_round_chain
+                .evaluate_constraint_quotients_at_point(
+                    oods_point,
+                    &proof.stark_proof.sampled_values,
+                    &mut evaluation_accumulator,
+                );
+            poseidon_context_components
+                .cube_252
+                .evaluate_constraint_quotients_at_point(
+                    oods_point,
+                    &proof.stark_proof.sampled_values,
+                    &mut evaluation_accumulator,
+                );
+            poseidon_context_components
+                .poseidon_round_keys
+                .evaluate_constraint_quotients_at_point(
+                    oods_point,
+                    &proof.stark_proof.sampled_values,
+                    &mut evaluation_accumulator,
+                );
+            poseidon_context_components
+                .range_check_252_width_27
+                .evaluate_constraint_quotients_at_point(
+                    oods_point,
+                    &proof.stark_proof.sampled_values,
+                    &mut evaluation_accumulator,
+                );
+        }
+
         // memory_address_to_id
         component_generator
             .memory_address_to_id
@@ -181,11 +343,13 @@ impl CairoCompositionHints {
             );
 
         // memory_id_to_value
-        component_generator.memory_id_to_value.0[0].evaluate_constraint_quotients_at_point(
-            oods_point,
-            &proof.stark_proof.sampled_values,
-            &mut evaluation_accumulator,
-        );
+        for component in component_generator.memory_id_to_value.0.iter() {
+            component.evaluate_constraint_quotients_at_point(
+                oods_point,
+                &proof.stark_proof.sampled_values,
+                &mut evaluation_accumulator,
+            );
+        }
         component_generator
             .memory_id_to_value
             .1
@@ -505,23 +669,25 @@ impl CairoCompositionHints {
                     .x
                     * right_eval
         };
-        assert_eq!(
-            composition_oods_eval,
-            components.eval_composition_polynomial_at_point(
+        if composition_oods_eval
+            != components.eval_composition_polynomial_at_point(
                 oods_point,
                 &proof.stark_proof.sampled_values,
                 *random_coeff,
             )
-        );
-        assert_eq!(res, composition_oods_eval);
+            || res != composition_oods_eval
+        {
+            return Err(VerificationError::CompositionEval);
+        }
 
-        Self {}
+        Ok(Self {})
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::CairoVerifierParams;
     use cairo_air::utils::{deserialize_proof_from_file, ProofFormat};
     use std::path::PathBuf;
 
@@ -535,7 +701,8 @@ mod tests {
             .join("recursive_proof.bin.bz");
 
         let proof = deserialize_proof_from_file(&data_path, ProofFormat::Binary).unwrap();
-        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof);
+        let params = CairoVerifierParams::from_proof(&proof);
+        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof, &params);
         let _ = CairoCompositionHints::new(&fiat_shamir_hints, &proof);
     }
 }
@@ -29,7 +29,7 @@ use stwo::core::{
     },
 };
 
-use crate::CairoFiatShamirHints;
+use crate::{CairoFiatShamirHints, VerificationError};
 
 #[derive(Debug, Clone)]
 pub struct QueryDecommitmentProof {
@@ -63,14 +63,18 @@ impl QueryDecommitmentNode {
 }
 
 impl QueryDecommitmentProof {
+    /// Split the Merkle decommitment of a tree into one proof per query, and return an error when
+    /// the decommitment is malformed or does not lead to the root.
     pub fn from_stwo_proof(
+        tree: &'static str,
         merkle_verifier: &MerkleVerifier<Poseidon31MerkleHasher>,
         raw_queries: Vec<usize>,
         queries_per_log_size: &BTreeMap<u32, Vec<usize>>,
         queried_values: Vec<BaseField>,
         decommitment: MerkleDecommitment<Poseidon31MerkleHasher>,
         log_blowup_factor: u32,
-    ) -> Vec<QueryDecommitmentProof> {
+    ) -> Result<Vec<QueryDecommitmentProof>, VerificationError> {
+        let invalid = VerificationError::Decommitment { tree };
         let mut layers = IndexMap::new();
 
         let max_tree_log_size = *merkle_verifier.column_log_sizes.iter().max().unwrap();
@@ -110,22 +114,27 @@ impl QueryDecommitmentProof {
                     .peek_take_while(|q| q / 2 == node_index)
                     .for_each(drop);
 
-                let node_hashes = prev_layer_hashes.as_mut().map(|prev_layer_hashes| {
-                    {
+                let node_hashes = match prev_layer_hashes.as_mut() {
+                    Some(prev_layer_hashes) => {
                         // If the left child was not computed, read it from the witness.
-                        let left_hash = prev_layer_hashes
+                        let left_hash = match prev_layer_hashes
                             .next_if(|(index, _)| *index == 2 * node_index)
-                            .map(|(_, hash)| *hash)
-                            .unwrap_or_else(|| hash_witness.next().unwrap());
+                        {
+                            Some((_, hash)) => *hash,
+                            None => hash_witness.next().ok_or(invalid)?,
+                        };
 
                         // If the right child was not computed, read it to from the witness.
-                        let right_hash = prev_layer_hashes
+                        let right_hash = match prev_layer_hashes
                             .next_if(|(index, _)| *index == 2 * node_index + 1)
-                            .map(|(_, hash)| *hash)
-                            .unwrap_or_else(|| hash_witness.next().unwrap());
-                        (left_hash, right_hash)
+                        {
+                            Some((_, hash)) => *hash,
+                            None => hash_witness.next().ok_or(invalid)?,
+                        };
+                        Some((left_hash, right_hash))
                     }
-                });
+                    None => None,
+                };
 
                 // If the column values were queried, read them from `queried_value`.
                 let node_values_iter = match layer_column_queries.next_if_eq(&node_index) {
@@ -134,7 +143,9 @@ impl QueryDecommitmentProof {
                 };
 
                 let node_values = node_values_iter.take(n_columns_in_layer).collect_vec();
-                assert_eq!(node_values.len(), n_columns_in_layer);
+                if node_values.len() != n_columns_in_layer {
+                    return Err(invalid);
+                }
 
                 layer.insert(
                     node_index,
@@ -155,19 +166,18 @@ impl QueryDecommitmentProof {
         }
 
         // Check that all witnesses and values have been consumed.
-        if hash_witness.next().is_some() {
-            panic!("hash witness not consumed");
-        }
-        if queried_values.next().is_some() {
-            panic!("queried values not consumed");
-        }
-        if column_witness.next().is_some() {
-            panic!("column witness not consumed");
+        if hash_witness.next().is_some()
+            || queried_values.next().is_some()
+            || column_witness.next().is_some()
+        {
+            return Err(invalid);
         }
 
-        let [(_, computed_root)] = last_layer_hashes.unwrap().try_into().unwrap();
+        let Ok([(_, computed_root)]) = <[_; 1]>::try_from(last_layer_hashes.unwrap()) else {
+            return Err(invalid);
+        };
         if computed_root != merkle_verifier.root {
-            panic!("computed root mismatch");
+            return Err(invalid);
         }
 
         let mut proofs = vec![];
@@ -207,7 +217,19 @@ impl QueryDecommitmentProof {
             };
             proofs.push(proof);
         }
-        proofs
+        Ok(proofs)
+    }
+
+    /// Check that the values at each layer of the proof hash to the column hashes computed from
+    /// the padded query results, keyed by the log size of the columns.
+    pub fn verify_column_hashes(&self, column_hashes: &IndexMap<usize, Poseidon31Hash>) -> bool {
+        self.intermediate_layers.iter().all(|(idx, node)| {
+            node.value.is_empty()
+                || column_hashes.get(&(idx - self.log_blowup_factor as usize))
+                    == Some(&Poseidon31MerkleHasher::hash_column_get_capacity(
+                        &node.value,
+                    ))
+        })
     }
 }
 
@@ -293,84 +315,57 @@ impl CairoDecommitmentHints {
         fiat_shamir_hints: &CairoFiatShamirHints,
         proof: &CairoProof<Poseidon31MerkleHasher>,
     ) -> Self {
-        let preprocessed_trace = read_preprocessed_trace(fiat_shamir_hints, proof);
-        let trace = read_trace(fiat_shamir_hints, proof);
-        let interaction = read_interaction(fiat_shamir_hints, proof);
+        Self::try_new(fiat_shamir_hints, proof).unwrap_or_else(|err| panic!("{err}"))
+    }
 
+    /// Read the queried values of each tree into the padded query results, after checking that
+    /// the decommitment of the tree leads to its commitment.
+    pub fn try_new(
+        fiat_shamir_hints: &CairoFiatShamirHints,
+        proof: &CairoProof<Poseidon31MerkleHasher>,
+    ) -> Result<Self, VerificationError> {
         let log_blowup_factor = fiat_shamir_hints.pcs_config.fri_config.log_blowup_factor;
-
-        let column_log_sizes = fiat_shamir_hints.log_sizes[0]
-            .iter()
-            .map(|log_size| log_size + log_blowup_factor)
-            .collect_vec();
-        let merkle_verifier =
-            MerkleVerifier::new(proof.stark_proof.commitments[0], column_log_sizes);
-        let preprocessed_trace_decommitment_proofs = QueryDecommitmentProof::from_stwo_proof(
-            &merkle_verifier,
-            fiat_shamir_hints.raw_queries.clone(),
-            &fiat_shamir_hints.query_positions_per_log_size,
-            proof.stark_proof.queried_values[0].clone(),
-            proof.stark_proof.decommitments[0].clone(),
-            log_blowup_factor,
-        );
-
-        let column_log_sizes = fiat_shamir_hints.log_sizes[1]
-            .iter()
-            .map(|log_size| log_size + fiat_shamir_hints.pcs_config.fri_config.log_blowup_factor)
-            .collect_vec();
-        let merkle_verifier =
-            MerkleVerifier::new(proof.stark_proof.commitments[1], column_log_sizes);
-        let trace_decommitment_proofs = QueryDecommitmentProof::from_stwo_proof(
-            &merkle_verifier,
-            fiat_shamir_hints.raw_queries.clone(),
-            &fiat_shamir_hints.query_positions_per_log_size,
-            proof.stark_proof.queried_values[1].clone(),
-            proof.stark_proof.decommitments[1].clone(),
-            log_blowup_factor,
-        );
-
-        let column_log_sizes = fiat_shamir_hints.log_sizes[2]
-            .iter()
-            .map(|log_size| log_size + fiat_shamir_hints.pcs_config.fri_config.log_blowup_factor)
-            .collect_vec();
-        let merkle_verifier =
-            MerkleVerifier::new(proof.stark_proof.commitments[2], column_log_sizes);
-        let interaction_decommitment_proofs = QueryDecommitmentProof::from_stwo_proof(
-            &merkle_verifier,
-            fiat_shamir_hints.raw_queries.clone(),
-            &fiat_shamir_hints.query_positions_per_log_size,
-            proof.stark_proof.queried_values[2].clone(),
-            proof.stark_proof.decommitments[2].clone(),
-            log_blowup_factor,
-        );
-
-        let column_log_sizes = [fiat_shamir_hints.composition_log_size - 1;
-            2 * SECURE_EXTENSION_DEGREE]
-            .iter()
-            .map(|log_size| log_size + fiat_shamir_hints.pcs_config.fri_config.log_blowup_factor)
-            .collect_vec();
-        let merkle_verifier =
-            MerkleVerifier::new(proof.stark_proof.commitments[3], column_log_sizes);
-        let composition = read_composition(fiat_shamir_hints, proof);
-        let composition_decommitment_proofs = QueryDecommitmentProof::from_stwo_proof(
-            &merkle_verifier,
-            fiat_shamir_hints.raw_queries.clone(),
-            &fiat_shamir_hints.query_positions_per_log_size,
-            proof.stark_proof.queried_values[3].clone(),
-            proof.stark_proof.decommitments[3].clone(),
-            log_blowup_factor,
-        );
-
-        Self {
-            preprocessed_trace,
+        let decommitment_proofs = |tree: &'static str, tree_idx: usize, log_sizes: &[u32]| {
+            let column_log_sizes = log_sizes
+                .iter()
+                .map(|log_size| log_size + log_blowup_factor)
+                .collect_vec();
+            let merkle_verifier =
+                MerkleVerifier::new(proof.stark_proof.commitments[tree_idx], column_log_sizes);
+            QueryDecommitmentProof::from_stwo_proof(
+                tree,
+                &merkle_verifier,
+                fiat_shamir_hints.raw_queries.clone(),
+                &fiat_shamir_hints.query_positions_per_log_size,
+                proof.stark_proof.queried_values[tree_idx].clone(),
+                proof.stark_proof.decommitments[tree_idx].clone(),
+                log_blowup_factor,
+            )
+        };
+
+        // the queried values are only read once the decommitments account for all of them
+        let preprocessed_trace_decommitment_proofs =
+            decommitment_proofs("preprocessed trace", 0, &fiat_shamir_hints.log_sizes[0])?;
+        let trace_decommitment_proofs =
+            decommitment_proofs("trace", 1, &fiat_shamir_hints.log_sizes[1])?;
+        let interaction_decommitment_proofs =
+            decommitment_proofs("interaction", 2, &fiat_shamir_hints.log_sizes[2])?;
+        let composition_decommitment_proofs = decommitment_proofs(
+            "composition",
+            3,
+            &[fiat_shamir_hints.composition_log_size - 1; 2 * SECURE_EXTENSION_DEGREE],
+        )?;
+
+        Ok(Self {
+            preprocessed_trace: read_preprocessed_trace(fiat_shamir_hints, proof),
             preprocessed_trace_decommitment_proofs,
-            trace,
+            trace: read_trace(fiat_shamir_hints, proof),
             trace_decommitment_proofs,
-            interaction,
+            interaction: read_interaction(fiat_shamir_hints, proof),
             interaction_decommitment_proofs,
-            composition,
+            composition: read_composition(fiat_shamir_hints, proof),
             composition_decommitment_proofs,
-        }
+        })
     }
 }
 
@@ -381,6 +376,7 @@ mod tests {
     use std::path::PathBuf;
 
     use super::*;
+    use crate::CairoVerifierParams;
 
     #[test]
     fn test_decommitment_hints() {
@@ -392,7 +388,8 @@ mod tests {
             .join("recursive_proof.bin.bz");
 
         let proof = deserialize_proof_from_file(&data_path, ProofFormat::Binary).unwrap();
-        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof);
+        let params = CairoVerifierParams::from_proof(&proof);
+        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof, &params);
         let decommitment_hints = CairoDecommitmentHints::new(&fiat_shamir_hints, &proof);
 
         let decommitment_proof =
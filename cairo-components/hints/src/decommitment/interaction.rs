@@ -1,7 +1,9 @@
 use cairo_air::{
-    air::CairoClaim, blake::air::BlakeContextClaim, opcodes_air::OpcodeClaim, CairoProof,
+    air::CairoClaim, blake::air::BlakeContextClaim, opcodes_air::OpcodeClaim,
+    poseidon::air::PoseidonContextClaim, CairoProof,
 };
 use indexmap::IndexMap;
+use itertools::Itertools;
 use stwo::core::{
     fields::{cm31::CM31, m31::M31, qm31::QM31},
     vcs::{poseidon31_hash::Poseidon31Hash, poseidon31_merkle::Poseidon31MerkleHasher},
@@ -9,36 +11,83 @@ use stwo::core::{
 
 use crate::{decommitment::utils::ColumnsHasherQM31, CairoFiatShamirHints};
 
+/// The number of interaction columns of a component with `$n_own_lookups` lookups of its own on
+/// top of the ones listed in its relation uses. An opcode component, for example, consumes and
+/// emits a state in the opcodes relation. The lookups are batched in pairs, one QM31 column per
+/// batch.
+macro_rules! n_interaction_columns {
+    ($component:ident, $n_own_lookups:expr) => {{
+        let relation_uses = &cairo_air::components::$component::RELATION_USES_PER_ROW;
+        let mut n_lookups = $n_own_lookups;
+        let mut i = 0;
+        while i < relation_uses.len() {
+            n_lookups += relation_uses[i].uses as usize;
+            i += 1;
+        }
+        n_lookups.div_ceil(2)
+    }};
+}
+
+pub const GENERIC_OPCODE_N_INTERACTION_COLUMNS: usize = n_interaction_columns!(generic_opcode, 2);
+pub const JUMP_OPCODE_ABS_N_INTERACTION_COLUMNS: usize = n_interaction_columns!(jump_opcode_abs, 2);
+pub const JUMP_OPCODE_DOUBLE_DEREF_N_INTERACTION_COLUMNS: usize =
+    n_interaction_columns!(jump_opcode_double_deref, 2);
+pub const ADD_MOD_BUILTIN_N_INTERACTION_COLUMNS: usize = n_interaction_columns!(add_mod_builtin, 0);
+pub const BITWISE_BUILTIN_N_INTERACTION_COLUMNS: usize = n_interaction_columns!(bitwise_builtin, 0);
+pub const MUL_MOD_BUILTIN_N_INTERACTION_COLUMNS: usize = n_interaction_columns!(mul_mod_builtin, 0);
+pub const POSEIDON_BUILTIN_N_INTERACTION_COLUMNS: usize =
+    n_interaction_columns!(poseidon_builtin, 0);
+pub const RANGE_CHECK_96_BUILTIN_N_INTERACTION_COLUMNS: usize =
+    n_interaction_columns!(range_check_builtin_bits_96, 0);
+pub const POSEIDON_AGGREGATOR_N_INTERACTION_COLUMNS: usize =
+    n_interaction_columns!(poseidon_aggregator, 1);
+pub const POSEIDON_3_PARTIAL_ROUNDS_CHAIN_N_INTERACTION_COLUMNS: usize =
+    n_interaction_columns!(poseidon_3_partial_rounds_chain, 2);
+pub const POSEIDON_FULL_ROUND_CHAIN_N_INTERACTION_COLUMNS: usize =
+    n_interaction_columns!(poseidon_full_round_chain, 2);
+pub const CUBE_252_N_INTERACTION_COLUMNS: usize = n_interaction_columns!(cube_252, 1);
+pub const RANGE_CHECK_252_WIDTH_27_N_INTERACTION_COLUMNS: usize =
+    n_interaction_columns!(range_check_252_width_27, 1);
+
 pub struct InteractionQueryResult {
     pub opcodes: OpcodesInteractionQueryResult,
     pub verify_instruction: [QM31; 3],
     pub blake: BlakeInteractionQueryResult,
+    pub add_mod_builtin: Option<[QM31; ADD_MOD_BUILTIN_N_INTERACTION_COLUMNS]>,
+    pub bitwise_builtin: Option<[QM31; BITWISE_BUILTIN_N_INTERACTION_COLUMNS]>,
+    pub mul_mod_builtin: Option<[QM31; MUL_MOD_BUILTIN_N_INTERACTION_COLUMNS]>,
+    pub poseidon_builtin: Option<[QM31; POSEIDON_BUILTIN_N_INTERACTION_COLUMNS]>,
+    pub range_check_96_builtin: Option<[QM31; RANGE_CHECK_96_BUILTIN_N_INTERACTION_COLUMNS]>,
     pub range_check_128_builtin: [QM31; 1],
+    pub poseidon: Option<PoseidonInteractionQueryResult>,
     pub memory_address_to_id: [QM31; 8],
-    pub memory_id_to_big_big: [QM31; 8],
+    pub memory_id_to_big_big: Vec<[QM31; 8]>,
     pub memory_id_to_big_small: [QM31; 3],
     pub range_checks: RangeChecksInteractionQueryResult,
     pub verify_bitwise: VerifyBitwiseInteractionQueryResult,
 }
 
 pub struct OpcodesInteractionQueryResult {
-    pub add: [QM31; 5],
-    pub add_small: [QM31; 5],
-    pub add_ap: [QM31; 4],
-    pub assert_eq: [QM31; 3],
-    pub assert_eq_imm: [QM31; 3],
-    pub assert_eq_double_deref: [QM31; 4],
-    pub blake: [QM31; 37],
-    pub call: [QM31; 5],
-    pub call_rel_imm: [QM31; 5],
-    pub jnz: [QM31; 3],
-    pub jnz_taken: [QM31; 4],
-    pub jump_rel: [QM31; 3],
-    pub jump_rel_imm: [QM31; 3],
-    pub mul: [QM31; 19],
-    pub mul_small: [QM31; 6],
-    pub qm31: [QM31; 6],
-    pub ret: [QM31; 4],
+    pub add: Vec<[QM31; 5]>,
+    pub add_small: Vec<[QM31; 5]>,
+    pub add_ap: Vec<[QM31; 4]>,
+    pub assert_eq: Vec<[QM31; 3]>,
+    pub assert_eq_imm: Vec<[QM31; 3]>,
+    pub assert_eq_double_deref: Vec<[QM31; 4]>,
+    pub blake: Vec<[QM31; 37]>,
+    pub call: Vec<[QM31; 5]>,
+    pub call_rel_imm: Vec<[QM31; 5]>,
+    pub generic: Vec<[QM31; GENERIC_OPCODE_N_INTERACTION_COLUMNS]>,
+    pub jnz: Vec<[QM31; 3]>,
+    pub jnz_taken: Vec<[QM31; 4]>,
+    pub jump: Vec<[QM31; JUMP_OPCODE_ABS_N_INTERACTION_COLUMNS]>,
+    pub jump_double_deref: Vec<[QM31; JUMP_OPCODE_DOUBLE_DEREF_N_INTERACTION_COLUMNS]>,
+    pub jump_rel: Vec<[QM31; 3]>,
+    pub jump_rel_imm: Vec<[QM31; 3]>,
+    pub mul: Vec<[QM31; 19]>,
+    pub mul_small: Vec<[QM31; 6]>,
+    pub qm31: Vec<[QM31; 6]>,
+    pub ret: Vec<[QM31; 4]>,
 }
 
 pub struct BlakeInteractionQueryResult {
@@ -49,6 +98,15 @@ pub struct BlakeInteractionQueryResult {
     pub verify_bitwise_xor_12: [QM31; 8],
 }
 
+pub struct PoseidonInteractionQueryResult {
+    pub aggregator: [QM31; POSEIDON_AGGREGATOR_N_INTERACTION_COLUMNS],
+    pub partial_rounds_chain: [QM31; POSEIDON_3_PARTIAL_ROUNDS_CHAIN_N_INTERACTION_COLUMNS],
+    pub full_round_chain: [QM31; POSEIDON_FULL_ROUND_CHAIN_N_INTERACTION_COLUMNS],
+    pub cube_252: [QM31; CUBE_252_N_INTERACTION_COLUMNS],
+    pub round_keys: [QM31; 1],
+    pub range_check_252_width_27: [QM31; RANGE_CHECK_252_WIDTH_27_N_INTERACTION_COLUMNS],
+}
+
 pub struct RangeChecksInteractionQueryResult {
     pub range_check_6: [QM31; 1],
     pub range_check_8: [QM31; 1],
@@ -98,18 +156,55 @@ impl InteractionQueryResult {
         columns_hasher.update(claim.verify_instruction.log_size, &self.verify_instruction);
         self.blake
             .update_hashes(&mut columns_hasher, &claim.blake_context);
+        if let Some(add_mod_builtin) = &self.add_mod_builtin {
+            columns_hasher.update(
+                claim.builtins.add_mod_builtin.unwrap().log_size,
+                add_mod_builtin,
+            );
+        }
+        if let Some(bitwise_builtin) = &self.bitwise_builtin {
+            columns_hasher.update(
+                claim.builtins.bitwise_builtin.unwrap().log_size,
+                bitwise_builtin,
+            );
+        }
+        if let Some(mul_mod_builtin) = &self.mul_mod_builtin {
+            columns_hasher.update(
+                claim.builtins.mul_mod_builtin.unwrap().log_size,
+                mul_mod_builtin,
+            );
+        }
+        if let Some(poseidon_builtin) = &self.poseidon_builtin {
+            columns_hasher.update(
+                claim.builtins.poseidon_builtin.unwrap().log_size,
+                poseidon_builtin,
+            );
+        }
+        if let Some(range_check_96_builtin) = &self.range_check_96_builtin {
+            columns_hasher.update(
+                claim.builtins.range_check_96_builtin.unwrap().log_size,
+                range_check_96_builtin,
+            );
+        }
         columns_hasher.update(
             claim.builtins.range_check_128_builtin.unwrap().log_size,
             &self.range_check_128_builtin,
         );
+        if let Some(poseidon) = &self.poseidon {
+            poseidon.update_hashes(&mut columns_hasher, &claim.poseidon_context);
+        }
         columns_hasher.update(
             claim.memory_address_to_id.log_size,
             &self.memory_address_to_id,
         );
-        columns_hasher.update(
-            claim.memory_id_to_value.big_log_sizes[0],
-            &self.memory_id_to_big_big,
-        );
+        for (log_size, columns) in claim
+            .memory_id_to_value
+            .big_log_sizes
+            .iter()
+            .zip_eq(self.memory_id_to_big_big.iter())
+        {
+            columns_hasher.update(*log_size, columns);
+        }
         columns_hasher.update(
             claim.memory_id_to_value.small_log_size,
             &self.memory_id_to_big_small,
@@ -127,26 +222,34 @@ impl InteractionQueryResult {
 
 impl OpcodesInteractionQueryResult {
     pub fn update_hashes(&self, columns_hasher: &mut ColumnsHasherQM31, trace_claim: &OpcodeClaim) {
-        columns_hasher.update(trace_claim.add[0].log_size, &self.add);
-        columns_hasher.update(trace_claim.add_small[0].log_size, &self.add_small);
-        columns_hasher.update(trace_claim.add_ap[0].log_size, &self.add_ap);
-        columns_hasher.update(trace_claim.assert_eq[0].log_size, &self.assert_eq);
-        columns_hasher.update(trace_claim.assert_eq_imm[0].log_size, &self.assert_eq_imm);
-        columns_hasher.update(
-            trace_claim.assert_eq_double_deref[0].log_size,
-            &self.assert_eq_double_deref,
-        );
-        columns_hasher.update(trace_claim.blake[0].log_size, &self.blake);
-        columns_hasher.update(trace_claim.call[0].log_size, &self.call);
-        columns_hasher.update(trace_claim.call_rel_imm[0].log_size, &self.call_rel_imm);
-        columns_hasher.update(trace_claim.jnz[0].log_size, &self.jnz);
-        columns_hasher.update(trace_claim.jnz_taken[0].log_size, &self.jnz_taken);
-        columns_hasher.update(trace_claim.jump_rel[0].log_size, &self.jump_rel);
-        columns_hasher.update(trace_claim.jump_rel_imm[0].log_size, &self.jump_rel_imm);
-        columns_hasher.update(trace_claim.mul[0].log_size, &self.mul);
-        columns_hasher.update(trace_claim.mul_small[0].log_size, &self.mul_small);
-        columns_hasher.update(trace_claim.qm31[0].log_size, &self.qm31);
-        columns_hasher.update(trace_claim.ret[0].log_size, &self.ret);
+        macro_rules! update_instances {
+            ($name:ident) => {
+                for (instance, columns) in trace_claim.$name.iter().zip_eq(self.$name.iter()) {
+                    columns_hasher.update(instance.log_size, columns);
+                }
+            };
+        }
+
+        update_instances!(add);
+        update_instances!(add_small);
+        update_instances!(add_ap);
+        update_instances!(assert_eq);
+        update_instances!(assert_eq_imm);
+        update_instances!(assert_eq_double_deref);
+        update_instances!(blake);
+        update_instances!(call);
+        update_instances!(call_rel_imm);
+        update_instances!(generic);
+        update_instances!(jnz);
+        update_instances!(jnz_taken);
+        update_instances!(jump);
+        update_instances!(jump_double_deref);
+        update_instances!(jump_rel);
+        update_instances!(jump_rel_imm);
+        update_instances!(mul);
+        update_instances!(mul_small);
+        update_instances!(qm31);
+        update_instances!(ret);
     }
 }
 
@@ -171,6 +274,34 @@ impl BlakeInteractionQueryResult {
     }
 }
 
+impl PoseidonInteractionQueryResult {
+    pub fn update_hashes(
+        &self,
+        columns_hasher: &mut ColumnsHasherQM31,
+        trace_claim: &PoseidonContextClaim,
+    ) {
+        let claim = &trace_claim.claim.as_ref().unwrap();
+        columns_hasher.update(claim.poseidon_aggregator.log_size, &self.aggregator);
+        columns_hasher.update(
+            claim.poseidon_3_partial_rounds_chain.log_size,
+            &self.partial_rounds_chain,
+        );
+        columns_hasher.update(
+            claim.poseidon_full_round_chain.log_size,
+            &self.full_round_chain,
+        );
+        columns_hasher.update(claim.cube_252.log_size, &self.cube_252);
+        columns_hasher.update(
+            cairo_air::components::poseidon_round_keys::LOG_SIZE,
+            &self.round_keys,
+        );
+        columns_hasher.update(
+            claim.range_check_252_width_27.log_size,
+            &self.range_check_252_width_27,
+        );
+    }
+}
+
 impl RangeChecksInteractionQueryResult {
     pub fn update_hashes(&self, columns_hasher: &mut ColumnsHasherQM31) {
         columns_hasher.update(
@@ -337,26 +468,61 @@ fn extract_qm31_array<const N: usize>(slice: &[QM31], offset: &mut usize) -> [QM
     arr
 }
 
-/// Allocate OpcodesInteractionQueryResult from QM31 slice
-fn allocate_opcodes(slice: &[QM31], offset: &mut usize) -> OpcodesInteractionQueryResult {
+/// Helper function to extract one fixed-size array of QM31 per instance of a component
+fn extract_qm31_arrays<const N: usize>(
+    slice: &[QM31],
+    offset: &mut usize,
+    n_instances: usize,
+) -> Vec<[QM31; N]> {
+    (0..n_instances)
+        .map(|_| extract_qm31_array::<N>(slice, offset))
+        .collect()
+}
+
+/// Allocate OpcodesInteractionQueryResult from QM31 slice, with one entry per instance of each
+/// opcode in the claim
+fn allocate_opcodes(
+    slice: &[QM31],
+    offset: &mut usize,
+    claim: &OpcodeClaim,
+) -> OpcodesInteractionQueryResult {
     OpcodesInteractionQueryResult {
-        add: extract_qm31_array::<5>(slice, offset),
-        add_small: extract_qm31_array::<5>(slice, offset),
-        add_ap: extract_qm31_array::<4>(slice, offset),
-        assert_eq: extract_qm31_array::<3>(slice, offset),
-        assert_eq_imm: extract_qm31_array::<3>(slice, offset),
-        assert_eq_double_deref: extract_qm31_array::<4>(slice, offset),
-        blake: extract_qm31_array::<37>(slice, offset),
-        call: extract_qm31_array::<5>(slice, offset),
-        call_rel_imm: extract_qm31_array::<5>(slice, offset),
-        jnz: extract_qm31_array::<3>(slice, offset),
-        jnz_taken: extract_qm31_array::<4>(slice, offset),
-        jump_rel: extract_qm31_array::<3>(slice, offset),
-        jump_rel_imm: extract_qm31_array::<3>(slice, offset),
-        mul: extract_qm31_array::<19>(slice, offset),
-        mul_small: extract_qm31_array::<6>(slice, offset),
-        qm31: extract_qm31_array::<6>(slice, offset),
-        ret: extract_qm31_array::<4>(slice, offset),
+        add: extract_qm31_arrays::<5>(slice, offset, claim.add.len()),
+        add_small: extract_qm31_arrays::<5>(slice, offset, claim.add_small.len()),
+        add_ap: extract_qm31_arrays::<4>(slice, offset, claim.add_ap.len()),
+        assert_eq: extract_qm31_arrays::<3>(slice, offset, claim.assert_eq.len()),
+        assert_eq_imm: extract_qm31_arrays::<3>(slice, offset, claim.assert_eq_imm.len()),
+        assert_eq_double_deref: extract_qm31_arrays::<4>(
+            slice,
+            offset,
+            claim.assert_eq_double_deref.len(),
+        ),
+        blake: extract_qm31_arrays::<37>(slice, offset, claim.blake.len()),
+        call: extract_qm31_arrays::<5>(slice, offset, claim.call.len()),
+        call_rel_imm: extract_qm31_arrays::<5>(slice, offset, claim.call_rel_imm.len()),
+        generic: extract_qm31_arrays::<GENERIC_OPCODE_N_INTERACTION_COLUMNS>(
+            slice,
+            offset,
+            claim.generic.len(),
+        ),
+        jnz: extract_qm31_arrays::<3>(slice, offset, claim.jnz.len()),
+        jnz_taken: extract_qm31_arrays::<4>(slice, offset, claim.jnz_taken.len()),
+        jump: extract_qm31_arrays::<JUMP_OPCODE_ABS_N_INTERACTION_COLUMNS>(
+            slice,
+            offset,
+            claim.jump.len(),
+        ),
+        jump_double_deref: extract_qm31_arrays::<JUMP_OPCODE_DOUBLE_DEREF_N_INTERACTION_COLUMNS>(
+            slice,
+            offset,
+            claim.jump_double_deref.len(),
+        ),
+        jump_rel: extract_qm31_arrays::<3>(slice, offset, claim.jump_rel.len()),
+        jump_rel_imm: extract_qm31_arrays::<3>(slice, offset, claim.jump_rel_imm.len()),
+        mul: extract_qm31_arrays::<19>(slice, offset, claim.mul.len()),
+        mul_small: extract_qm31_arrays::<6>(slice, offset, claim.mul_small.len()),
+        qm31: extract_qm31_arrays::<6>(slice, offset, claim.qm31.len()),
+        ret: extract_qm31_arrays::<4>(slice, offset, claim.ret.len()),
     }
 }
 
@@ -371,6 +537,24 @@ fn allocate_blake(slice: &[QM31], offset: &mut usize) -> BlakeInteractionQueryRe
     }
 }
 
+/// Allocate PoseidonInteractionQueryResult from QM31 slice
+fn allocate_poseidon(slice: &[QM31], offset: &mut usize) -> PoseidonInteractionQueryResult {
+    PoseidonInteractionQueryResult {
+        aggregator: extract_qm31_array::<POSEIDON_AGGREGATOR_N_INTERACTION_COLUMNS>(slice, offset),
+        partial_rounds_chain: extract_qm31_array::<
+            POSEIDON_3_PARTIAL_ROUNDS_CHAIN_N_INTERACTION_COLUMNS,
+        >(slice, offset),
+        full_round_chain: extract_qm31_array::<POSEIDON_FULL_ROUND_CHAIN_N_INTERACTION_COLUMNS>(
+            slice, offset,
+        ),
+        cube_252: extract_qm31_array::<CUBE_252_N_INTERACTION_COLUMNS>(slice, offset),
+        round_keys: extract_qm31_array::<1>(slice, offset),
+        range_check_252_width_27: extract_qm31_array::<
+            RANGE_CHECK_252_WIDTH_27_N_INTERACTION_COLUMNS,
+        >(slice, offset),
+    }
+}
+
 /// Allocate RangeChecksInteractionQueryResult from QM31 slice
 fn allocate_range_checks(slice: &[QM31], offset: &mut usize) -> RangeChecksInteractionQueryResult {
     RangeChecksInteractionQueryResult {
@@ -421,16 +605,44 @@ fn allocate_verify_bitwise(
 }
 
 /// Allocate InteractionQueryResult from QM31 slice following the exact field order
-fn allocate_interaction_query_result(slice: &[QM31]) -> InteractionQueryResult {
+fn allocate_interaction_query_result(slice: &[QM31], claim: &CairoClaim) -> InteractionQueryResult {
     let mut offset = 0;
 
     // Allocate in the exact order as defined in InteractionQueryResult
-    let opcodes = allocate_opcodes(slice, &mut offset);
+    let opcodes = allocate_opcodes(slice, &mut offset, &claim.opcodes);
     let verify_instruction = extract_qm31_array::<3>(slice, &mut offset);
     let blake = allocate_blake(slice, &mut offset);
+    let add_mod_builtin = claim
+        .builtins
+        .add_mod_builtin
+        .map(|_| extract_qm31_array::<ADD_MOD_BUILTIN_N_INTERACTION_COLUMNS>(slice, &mut offset));
+    let bitwise_builtin = claim
+        .builtins
+        .bitwise_builtin
+        .map(|_| extract_qm31_array::<BITWISE_BUILTIN_N_INTERACTION_COLUMNS>(slice, &mut offset));
+    let mul_mod_builtin = claim
+        .builtins
+        .mul_mod_builtin
+        .map(|_| extract_qm31_array::<MUL_MOD_BUILTIN_N_INTERACTION_COLUMNS>(slice, &mut offset));
+    let poseidon_builtin = claim
+        .builtins
+        .poseidon_builtin
+        .map(|_| extract_qm31_array::<POSEIDON_BUILTIN_N_INTERACTION_COLUMNS>(slice, &mut offset));
+    let range_check_96_builtin = claim.builtins.range_check_96_builtin.map(|_| {
+        extract_qm31_array::<RANGE_CHECK_96_BUILTIN_N_INTERACTION_COLUMNS>(slice, &mut offset)
+    });
     let range_check_128_builtin = extract_qm31_array::<1>(slice, &mut offset);
+    let poseidon = claim
+        .poseidon_context
+        .claim
+        .as_ref()
+        .map(|_| allocate_poseidon(slice, &mut offset));
     let memory_address_to_id = extract_qm31_array::<8>(slice, &mut offset);
-    let memory_id_to_big_big = extract_qm31_array::<8>(slice, &mut offset);
+    let memory_id_to_big_big = extract_qm31_arrays::<8>(
+        slice,
+        &mut offset,
+        claim.memory_id_to_value.big_log_sizes.len(),
+    );
     let memory_id_to_big_small = extract_qm31_array::<3>(slice, &mut offset);
     let range_checks = allocate_range_checks(slice, &mut offset);
     let verify_bitwise = allocate_verify_bitwise(slice, &mut offset);
@@ -441,7 +653,13 @@ fn allocate_interaction_query_result(slice: &[QM31]) -> InteractionQueryResult {
         opcodes,
         verify_instruction,
         blake,
+        add_mod_builtin,
+        bitwise_builtin,
+        mul_mod_builtin,
+        poseidon_builtin,
+        range_check_96_builtin,
         range_check_128_builtin,
+        poseidon,
         memory_address_to_id,
         memory_id_to_big_big,
         memory_id_to_big_small,
@@ -477,7 +695,7 @@ pub fn read_interaction(
     {
         // Convert M31 slice to QM31 slice (4 M31 -> 1 QM31)
         let qm31_slice = convert_m31_to_qm31(m31_slice);
-        let interaction_query_result = allocate_interaction_query_result(&qm31_slice);
+        let interaction_query_result = allocate_interaction_query_result(&qm31_slice, &proof.claim);
         results.push(interaction_query_result);
     }
 
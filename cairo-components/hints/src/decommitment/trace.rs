@@ -1,7 +1,9 @@
 use cairo_air::{
-    air::CairoClaim, blake::air::BlakeContextClaim, opcodes_air::OpcodeClaim, CairoProof,
+    air::CairoClaim, blake::air::BlakeContextClaim, opcodes_air::OpcodeClaim,
+    poseidon::air::PoseidonContextClaim, CairoProof,
 };
 use indexmap::IndexMap;
+use itertools::Itertools;
 use stwo::core::{
     fields::m31::M31,
     vcs::{poseidon31_hash::Poseidon31Hash, poseidon31_merkle::Poseidon31MerkleHasher},
@@ -13,34 +15,45 @@ pub struct TraceQueryResult {
     pub opcodes: OpcodesTraceQueryResult,
     pub verify_instruction: [M31; cairo_air::components::verify_instruction::N_TRACE_COLUMNS],
     pub blake: BlakeTraceQueryResult,
+    pub add_mod_builtin: Option<[M31; cairo_air::components::add_mod_builtin::N_TRACE_COLUMNS]>,
+    pub bitwise_builtin: Option<[M31; cairo_air::components::bitwise_builtin::N_TRACE_COLUMNS]>,
+    pub mul_mod_builtin: Option<[M31; cairo_air::components::mul_mod_builtin::N_TRACE_COLUMNS]>,
+    pub poseidon_builtin: Option<[M31; cairo_air::components::poseidon_builtin::N_TRACE_COLUMNS]>,
+    pub range_check_96_builtin:
+        Option<[M31; cairo_air::components::range_check_builtin_bits_96::N_TRACE_COLUMNS]>,
     pub range_check_128_builtin:
         [M31; cairo_air::components::range_check_builtin_bits_128::N_TRACE_COLUMNS],
+    pub poseidon: Option<PoseidonTraceQueryResult>,
     pub memory_address_to_id: [M31; cairo_air::components::memory_address_to_id::N_TRACE_COLUMNS],
-    pub memory_id_to_big_big: [M31; cairo_air::components::memory_id_to_big::BIG_N_COLUMNS],
+    pub memory_id_to_big_big: Vec<[M31; cairo_air::components::memory_id_to_big::BIG_N_COLUMNS]>,
     pub memory_id_to_big_small: [M31; cairo_air::components::memory_id_to_big::SMALL_N_COLUMNS],
     pub range_checks: RangeChecksTraceQueryResult,
     pub verify_bitwise: VerifyBitwiseTraceQueryResult,
 }
 
 pub struct OpcodesTraceQueryResult {
-    pub add: [M31; cairo_air::components::add_opcode::N_TRACE_COLUMNS],
-    pub add_small: [M31; cairo_air::components::add_opcode_small::N_TRACE_COLUMNS],
-    pub add_ap: [M31; cairo_air::components::add_ap_opcode::N_TRACE_COLUMNS],
-    pub assert_eq: [M31; cairo_air::components::assert_eq_opcode::N_TRACE_COLUMNS],
-    pub assert_eq_imm: [M31; cairo_air::components::assert_eq_opcode_imm::N_TRACE_COLUMNS],
+    pub add: Vec<[M31; cairo_air::components::add_opcode::N_TRACE_COLUMNS]>,
+    pub add_small: Vec<[M31; cairo_air::components::add_opcode_small::N_TRACE_COLUMNS]>,
+    pub add_ap: Vec<[M31; cairo_air::components::add_ap_opcode::N_TRACE_COLUMNS]>,
+    pub assert_eq: Vec<[M31; cairo_air::components::assert_eq_opcode::N_TRACE_COLUMNS]>,
+    pub assert_eq_imm: Vec<[M31; cairo_air::components::assert_eq_opcode_imm::N_TRACE_COLUMNS]>,
     pub assert_eq_double_deref:
-        [M31; cairo_air::components::assert_eq_opcode_double_deref::N_TRACE_COLUMNS],
-    pub blake: [M31; cairo_air::components::blake_compress_opcode::N_TRACE_COLUMNS],
-    pub call: [M31; cairo_air::components::call_opcode_abs::N_TRACE_COLUMNS],
-    pub call_rel_imm: [M31; cairo_air::components::call_opcode_rel_imm::N_TRACE_COLUMNS],
-    pub jnz: [M31; cairo_air::components::jnz_opcode_non_taken::N_TRACE_COLUMNS],
-    pub jnz_taken: [M31; cairo_air::components::jnz_opcode_taken::N_TRACE_COLUMNS],
-    pub jump_rel: [M31; cairo_air::components::jump_opcode_rel::N_TRACE_COLUMNS],
-    pub jump_rel_imm: [M31; cairo_air::components::jump_opcode_rel_imm::N_TRACE_COLUMNS],
-    pub mul: [M31; cairo_air::components::mul_opcode::N_TRACE_COLUMNS],
-    pub mul_small: [M31; cairo_air::components::mul_opcode_small::N_TRACE_COLUMNS],
-    pub qm31: [M31; cairo_air::components::qm_31_add_mul_opcode::N_TRACE_COLUMNS],
-    pub ret: [M31; cairo_air::components::ret_opcode::N_TRACE_COLUMNS],
+        Vec<[M31; cairo_air::components::assert_eq_opcode_double_deref::N_TRACE_COLUMNS]>,
+    pub blake: Vec<[M31; cairo_air::components::blake_compress_opcode::N_TRACE_COLUMNS]>,
+    pub call: Vec<[M31; cairo_air::components::call_opcode_abs::N_TRACE_COLUMNS]>,
+    pub call_rel_imm: Vec<[M31; cairo_air::components::call_opcode_rel_imm::N_TRACE_COLUMNS]>,
+    pub generic: Vec<[M31; cairo_air::components::generic_opcode::N_TRACE_COLUMNS]>,
+    pub jnz: Vec<[M31; cairo_air::components::jnz_opcode_non_taken::N_TRACE_COLUMNS]>,
+    pub jnz_taken: Vec<[M31; cairo_air::components::jnz_opcode_taken::N_TRACE_COLUMNS]>,
+    pub jump: Vec<[M31; cairo_air::components::jump_opcode_abs::N_TRACE_COLUMNS]>,
+    pub jump_double_deref:
+        Vec<[M31; cairo_air::components::jump_opcode_double_deref::N_TRACE_COLUMNS]>,
+    pub jump_rel: Vec<[M31; cairo_air::components::jump_opcode_rel::N_TRACE_COLUMNS]>,
+    pub jump_rel_imm: Vec<[M31; cairo_air::components::jump_opcode_rel_imm::N_TRACE_COLUMNS]>,
+    pub mul: Vec<[M31; cairo_air::components::mul_opcode::N_TRACE_COLUMNS]>,
+    pub mul_small: Vec<[M31; cairo_air::components::mul_opcode_small::N_TRACE_COLUMNS]>,
+    pub qm31: Vec<[M31; cairo_air::components::qm_31_add_mul_opcode::N_TRACE_COLUMNS]>,
+    pub ret: Vec<[M31; cairo_air::components::ret_opcode::N_TRACE_COLUMNS]>,
 }
 
 pub struct BlakeTraceQueryResult {
@@ -51,6 +64,17 @@ pub struct BlakeTraceQueryResult {
     pub verify_bitwise_xor_12: [M31; cairo_air::components::verify_bitwise_xor_12::N_TRACE_COLUMNS],
 }
 
+pub struct PoseidonTraceQueryResult {
+    pub aggregator: [M31; cairo_air::components::poseidon_aggregator::N_TRACE_COLUMNS],
+    pub partial_rounds_chain:
+        [M31; cairo_air::components::poseidon_3_partial_rounds_chain::N_TRACE_COLUMNS],
+    pub full_round_chain: [M31; cairo_air::components::poseidon_full_round_chain::N_TRACE_COLUMNS],
+    pub cube_252: [M31; cairo_air::components::cube_252::N_TRACE_COLUMNS],
+    pub round_keys: [M31; cairo_air::components::poseidon_round_keys::N_TRACE_COLUMNS],
+    pub range_check_252_width_27:
+        [M31; cairo_air::components::range_check_252_width_27::N_TRACE_COLUMNS],
+}
+
 pub struct RangeChecksTraceQueryResult {
     pub range_check_6: [M31; cairo_air::components::range_check_6::N_TRACE_COLUMNS],
     pub range_check_8: [M31; cairo_air::components::range_check_8::N_TRACE_COLUMNS],
@@ -101,18 +125,55 @@ impl TraceQueryResult {
         columns_hasher.update(claim.verify_instruction.log_size, &self.verify_instruction);
         self.blake
             .update_hashes(&mut columns_hasher, &claim.blake_context);
+        if let Some(add_mod_builtin) = &self.add_mod_builtin {
+            columns_hasher.update(
+                claim.builtins.add_mod_builtin.unwrap().log_size,
+                add_mod_builtin,
+            );
+        }
+        if let Some(bitwise_builtin) = &self.bitwise_builtin {
+            columns_hasher.update(
+                claim.builtins.bitwise_builtin.unwrap().log_size,
+                bitwise_builtin,
+            );
+        }
+        if let Some(mul_mod_builtin) = &self.mul_mod_builtin {
+            columns_hasher.update(
+                claim.builtins.mul_mod_builtin.unwrap().log_size,
+                mul_mod_builtin,
+            );
+        }
+        if let Some(poseidon_builtin) = &self.poseidon_builtin {
+            columns_hasher.update(
+                claim.builtins.poseidon_builtin.unwrap().log_size,
+                poseidon_builtin,
+            );
+        }
+        if let Some(range_check_96_builtin) = &self.range_check_96_builtin {
+            columns_hasher.update(
+                claim.builtins.range_check_96_builtin.unwrap().log_size,
+                range_check_96_builtin,
+            );
+        }
         columns_hasher.update(
             claim.builtins.range_check_128_builtin.unwrap().log_size,
             &self.range_check_128_builtin,
         );
+        if let Some(poseidon) = &self.poseidon {
+            poseidon.update_hashes(&mut columns_hasher, &claim.poseidon_context);
+        }
         columns_hasher.update(
             claim.memory_address_to_id.log_size,
             &self.memory_address_to_id,
         );
-        columns_hasher.update(
-            claim.memory_id_to_value.big_log_sizes[0],
-            &self.memory_id_to_big_big,
-        );
+        for (log_size, columns) in claim
+            .memory_id_to_value
+            .big_log_sizes
+            .iter()
+            .zip_eq(self.memory_id_to_big_big.iter())
+        {
+            columns_hasher.update(*log_size, columns);
+        }
         columns_hasher.update(
             claim.memory_id_to_value.small_log_size,
             &self.memory_id_to_big_small,
@@ -130,26 +191,34 @@ impl TraceQueryResult {
 
 impl OpcodesTraceQueryResult {
     pub fn update_hashes(&self, columns_hasher: &mut ColumnsHasher, claim: &OpcodeClaim) {
-        columns_hasher.update(claim.add[0].log_size, &self.add);
-        columns_hasher.update(claim.add_small[0].log_size, &self.add_small);
-        columns_hasher.update(claim.add_ap[0].log_size, &self.add_ap);
-        columns_hasher.update(claim.assert_eq[0].log_size, &self.assert_eq);
-        columns_hasher.update(claim.assert_eq_imm[0].log_size, &self.assert_eq_imm);
-        columns_hasher.update(
-            claim.assert_eq_double_deref[0].log_size,
-            &self.assert_eq_double_deref,
-        );
-        columns_hasher.update(claim.blake[0].log_size, &self.blake);
-        columns_hasher.update(claim.call[0].log_size, &self.call);
-        columns_hasher.update(claim.call_rel_imm[0].log_size, &self.call_rel_imm);
-        columns_hasher.update(claim.jnz[0].log_size, &self.jnz);
-        columns_hasher.update(claim.jnz_taken[0].log_size, &self.jnz_taken);
-        columns_hasher.update(claim.jump_rel[0].log_size, &self.jump_rel);
-        columns_hasher.update(claim.jump_rel_imm[0].log_size, &self.jump_rel_imm);
-        columns_hasher.update(claim.mul[0].log_size, &self.mul);
-        columns_hasher.update(claim.mul_small[0].log_size, &self.mul_small);
-        columns_hasher.update(claim.qm31[0].log_size, &self.qm31);
-        columns_hasher.update(claim.ret[0].log_size, &self.ret);
+        macro_rules! update_instances {
+            ($name:ident) => {
+                for (instance, columns) in claim.$name.iter().zip_eq(self.$name.iter()) {
+                    columns_hasher.update(instance.log_size, columns);
+                }
+            };
+        }
+
+        update_instances!(add);
+        update_instances!(add_small);
+        update_instances!(add_ap);
+        update_instances!(assert_eq);
+        update_instances!(assert_eq_imm);
+        update_instances!(assert_eq_double_deref);
+        update_instances!(blake);
+        update_instances!(call);
+        update_instances!(call_rel_imm);
+        update_instances!(generic);
+        update_instances!(jnz);
+        update_instances!(jnz_taken);
+        update_instances!(jump);
+        update_instances!(jump_double_deref);
+        update_instances!(jump_rel);
+        update_instances!(jump_rel_imm);
+        update_instances!(mul);
+        update_instances!(mul_small);
+        update_instances!(qm31);
+        update_instances!(ret);
     }
 }
 
@@ -170,6 +239,30 @@ impl BlakeTraceQueryResult {
     }
 }
 
+impl PoseidonTraceQueryResult {
+    pub fn update_hashes(&self, columns_hasher: &mut ColumnsHasher, claim: &PoseidonContextClaim) {
+        let claim = &claim.claim.as_ref().unwrap();
+        columns_hasher.update(claim.poseidon_aggregator.log_size, &self.aggregator);
+        columns_hasher.update(
+            claim.poseidon_3_partial_rounds_chain.log_size,
+            &self.partial_rounds_chain,
+        );
+        columns_hasher.update(
+            claim.poseidon_full_round_chain.log_size,
+            &self.full_round_chain,
+        );
+        columns_hasher.update(claim.cube_252.log_size, &self.cube_252);
+        columns_hasher.update(
+            cairo_air::components::poseidon_round_keys::LOG_SIZE,
+            &self.round_keys,
+        );
+        columns_hasher.update(
+            claim.range_check_252_width_27.log_size,
+            &self.range_check_252_width_27,
+        );
+    }
+}
+
 impl RangeChecksTraceQueryResult {
     pub fn update_hashes(&self, columns_hasher: &mut ColumnsHasher) {
         columns_hasher.update(
@@ -324,54 +417,115 @@ fn extract_array<const N: usize>(slice: &[M31], offset: &mut usize) -> [M31; N]
     arr
 }
 
-/// Allocate OpcodesTraceQueryResult from pad slice
-fn allocate_opcodes(slice: &[M31], offset: &mut usize) -> OpcodesTraceQueryResult {
+/// Helper function to extract one fixed-size array per instance of a component from a slice
+fn extract_arrays<const N: usize>(
+    slice: &[M31],
+    offset: &mut usize,
+    n_instances: usize,
+) -> Vec<[M31; N]> {
+    (0..n_instances)
+        .map(|_| extract_array::<N>(slice, offset))
+        .collect()
+}
+
+/// Allocate OpcodesTraceQueryResult from pad slice, with one entry per instance of each opcode
+/// in the claim
+fn allocate_opcodes(
+    slice: &[M31],
+    offset: &mut usize,
+    claim: &OpcodeClaim,
+) -> OpcodesTraceQueryResult {
     OpcodesTraceQueryResult {
-        add: extract_array::<{ cairo_air::components::add_opcode::N_TRACE_COLUMNS }>(slice, offset),
-        add_small: extract_array::<{ cairo_air::components::add_opcode_small::N_TRACE_COLUMNS }>(
-            slice, offset,
+        add: extract_arrays::<{ cairo_air::components::add_opcode::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.add.len(),
         ),
-        add_ap: extract_array::<{ cairo_air::components::add_ap_opcode::N_TRACE_COLUMNS }>(
-            slice, offset,
+        add_small: extract_arrays::<{ cairo_air::components::add_opcode_small::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.add_small.len(),
         ),
-        assert_eq: extract_array::<{ cairo_air::components::assert_eq_opcode::N_TRACE_COLUMNS }>(
-            slice, offset,
+        add_ap: extract_arrays::<{ cairo_air::components::add_ap_opcode::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.add_ap.len(),
         ),
-        assert_eq_imm: extract_array::<
+        assert_eq: extract_arrays::<{ cairo_air::components::assert_eq_opcode::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.assert_eq.len(),
+        ),
+        assert_eq_imm: extract_arrays::<
             { cairo_air::components::assert_eq_opcode_imm::N_TRACE_COLUMNS },
-        >(slice, offset),
-        assert_eq_double_deref: extract_array::<
+        >(slice, offset, claim.assert_eq_imm.len()),
+        assert_eq_double_deref: extract_arrays::<
             { cairo_air::components::assert_eq_opcode_double_deref::N_TRACE_COLUMNS },
-        >(slice, offset),
-        blake: extract_array::<{ cairo_air::components::blake_compress_opcode::N_TRACE_COLUMNS }>(
-            slice, offset,
+        >(slice, offset, claim.assert_eq_double_deref.len()),
+        blake: extract_arrays::<{ cairo_air::components::blake_compress_opcode::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.blake.len(),
         ),
-        call: extract_array::<{ cairo_air::components::call_opcode_abs::N_TRACE_COLUMNS }>(
-            slice, offset,
+        call: extract_arrays::<{ cairo_air::components::call_opcode_abs::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.call.len(),
         ),
-        call_rel_imm: extract_array::<
+        call_rel_imm: extract_arrays::<
             { cairo_air::components::call_opcode_rel_imm::N_TRACE_COLUMNS },
-        >(slice, offset),
-        jnz: extract_array::<{ cairo_air::components::jnz_opcode_non_taken::N_TRACE_COLUMNS }>(
-            slice, offset,
+        >(slice, offset, claim.call_rel_imm.len()),
+        generic: extract_arrays::<{ cairo_air::components::generic_opcode::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.generic.len(),
         ),
-        jnz_taken: extract_array::<{ cairo_air::components::jnz_opcode_taken::N_TRACE_COLUMNS }>(
-            slice, offset,
+        jnz: extract_arrays::<{ cairo_air::components::jnz_opcode_non_taken::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.jnz.len(),
         ),
-        jump_rel: extract_array::<{ cairo_air::components::jump_opcode_rel::N_TRACE_COLUMNS }>(
-            slice, offset,
+        jnz_taken: extract_arrays::<{ cairo_air::components::jnz_opcode_taken::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.jnz_taken.len(),
+        ),
+        jump: extract_arrays::<{ cairo_air::components::jump_opcode_abs::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.jump.len(),
         ),
-        jump_rel_imm: extract_array::<
+        jump_double_deref: extract_arrays::<
+            { cairo_air::components::jump_opcode_double_deref::N_TRACE_COLUMNS },
+        >(slice, offset, claim.jump_double_deref.len()),
+        jump_rel: extract_arrays::<{ cairo_air::components::jump_opcode_rel::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.jump_rel.len(),
+        ),
+        jump_rel_imm: extract_arrays::<
             { cairo_air::components::jump_opcode_rel_imm::N_TRACE_COLUMNS },
-        >(slice, offset),
-        mul: extract_array::<{ cairo_air::components::mul_opcode::N_TRACE_COLUMNS }>(slice, offset),
-        mul_small: extract_array::<{ cairo_air::components::mul_opcode_small::N_TRACE_COLUMNS }>(
-            slice, offset,
+        >(slice, offset, claim.jump_rel_imm.len()),
+        mul: extract_arrays::<{ cairo_air::components::mul_opcode::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.mul.len(),
         ),
-        qm31: extract_array::<{ cairo_air::components::qm_31_add_mul_opcode::N_TRACE_COLUMNS }>(
-            slice, offset,
+        mul_small: extract_arrays::<{ cairo_air::components::mul_opcode_small::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.mul_small.len(),
+        ),
+        qm31: extract_arrays::<{ cairo_air::components::qm_31_add_mul_opcode::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.qm31.len(),
+        ),
+        ret: extract_arrays::<{ cairo_air::components::ret_opcode::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.ret.len(),
         ),
-        ret: extract_array::<{ cairo_air::components::ret_opcode::N_TRACE_COLUMNS }>(slice, offset),
     }
 }
 
@@ -394,6 +548,30 @@ fn allocate_blake(slice: &[M31], offset: &mut usize) -> BlakeTraceQueryResult {
     }
 }
 
+/// Allocate PoseidonTraceQueryResult from pad slice
+fn allocate_poseidon(slice: &[M31], offset: &mut usize) -> PoseidonTraceQueryResult {
+    PoseidonTraceQueryResult {
+        aggregator: extract_array::<{ cairo_air::components::poseidon_aggregator::N_TRACE_COLUMNS }>(
+            slice, offset,
+        ),
+        partial_rounds_chain: extract_array::<
+            { cairo_air::components::poseidon_3_partial_rounds_chain::N_TRACE_COLUMNS },
+        >(slice, offset),
+        full_round_chain: extract_array::<
+            { cairo_air::components::poseidon_full_round_chain::N_TRACE_COLUMNS },
+        >(slice, offset),
+        cube_252: extract_array::<{ cairo_air::components::cube_252::N_TRACE_COLUMNS }>(
+            slice, offset,
+        ),
+        round_keys: extract_array::<{ cairo_air::components::poseidon_round_keys::N_TRACE_COLUMNS }>(
+            slice, offset,
+        ),
+        range_check_252_width_27: extract_array::<
+            { cairo_air::components::range_check_252_width_27::N_TRACE_COLUMNS },
+        >(slice, offset),
+    }
+}
+
 /// Allocate RangeChecksTraceQueryResult from pad slice
 fn allocate_range_checks(slice: &[M31], offset: &mut usize) -> RangeChecksTraceQueryResult {
     RangeChecksTraceQueryResult {
@@ -509,24 +687,62 @@ fn allocate_verify_bitwise(slice: &[M31], offset: &mut usize) -> VerifyBitwiseTr
 }
 
 /// Allocate TraceQueryResult from pad slice following the exact field order
-fn allocate_trace_query_result(slice: &[M31]) -> TraceQueryResult {
+fn allocate_trace_query_result(slice: &[M31], claim: &CairoClaim) -> TraceQueryResult {
     let mut offset = 0;
 
     // Allocate in the exact order as defined in TraceQueryResult
-    let opcodes = allocate_opcodes(slice, &mut offset);
+    let opcodes = allocate_opcodes(slice, &mut offset, &claim.opcodes);
     let verify_instruction = extract_array::<
         { cairo_air::components::verify_instruction::N_TRACE_COLUMNS },
     >(slice, &mut offset);
     let blake = allocate_blake(slice, &mut offset);
+    let add_mod_builtin = claim.builtins.add_mod_builtin.map(|_| {
+        extract_array::<{ cairo_air::components::add_mod_builtin::N_TRACE_COLUMNS }>(
+            slice,
+            &mut offset,
+        )
+    });
+    let bitwise_builtin = claim.builtins.bitwise_builtin.map(|_| {
+        extract_array::<{ cairo_air::components::bitwise_builtin::N_TRACE_COLUMNS }>(
+            slice,
+            &mut offset,
+        )
+    });
+    let mul_mod_builtin = claim.builtins.mul_mod_builtin.map(|_| {
+        extract_array::<{ cairo_air::components::mul_mod_builtin::N_TRACE_COLUMNS }>(
+            slice,
+            &mut offset,
+        )
+    });
+    let poseidon_builtin = claim.builtins.poseidon_builtin.map(|_| {
+        extract_array::<{ cairo_air::components::poseidon_builtin::N_TRACE_COLUMNS }>(
+            slice,
+            &mut offset,
+        )
+    });
+    let range_check_96_builtin = claim.builtins.range_check_96_builtin.map(|_| {
+        extract_array::<{ cairo_air::components::range_check_builtin_bits_96::N_TRACE_COLUMNS }>(
+            slice,
+            &mut offset,
+        )
+    });
     let range_check_128_builtin = extract_array::<
         { cairo_air::components::range_check_builtin_bits_128::N_TRACE_COLUMNS },
     >(slice, &mut offset);
+    let poseidon = claim
+        .poseidon_context
+        .claim
+        .as_ref()
+        .map(|_| allocate_poseidon(slice, &mut offset));
     let memory_address_to_id = extract_array::<
         { cairo_air::components::memory_address_to_id::N_TRACE_COLUMNS },
     >(slice, &mut offset);
-    let memory_id_to_big_big = extract_array::<
-        { cairo_air::components::memory_id_to_big::BIG_N_COLUMNS },
-    >(slice, &mut offset);
+    let memory_id_to_big_big =
+        extract_arrays::<{ cairo_air::components::memory_id_to_big::BIG_N_COLUMNS }>(
+            slice,
+            &mut offset,
+            claim.memory_id_to_value.big_log_sizes.len(),
+        );
     let memory_id_to_big_small = extract_array::<
         { cairo_air::components::memory_id_to_big::SMALL_N_COLUMNS },
     >(slice, &mut offset);
@@ -539,7 +755,13 @@ fn allocate_trace_query_result(slice: &[M31]) -> TraceQueryResult {
         opcodes,
         verify_instruction,
         blake,
+        add_mod_builtin,
+        bitwise_builtin,
+        mul_mod_builtin,
+        poseidon_builtin,
+        range_check_96_builtin,
         range_check_128_builtin,
+        poseidon,
         memory_address_to_id,
         memory_id_to_big_big,
         memory_id_to_big_small,
@@ -573,7 +795,7 @@ pub fn read_trace(
         .iter()
         .take(proof.stark_proof.config.fri_config.n_queries)
     {
-        let trace_query_result = allocate_trace_query_result(c);
+        let trace_query_result = allocate_trace_query_result(c, &proof.claim);
         results.push(trace_query_result);
     }
 
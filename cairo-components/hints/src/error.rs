@@ -0,0 +1,110 @@
+use crate::Assumption;
+use std::fmt;
+
+/// A check of the Cairo verifier that a proof fails while the hints are computed. The circuit
+/// enforces the same checks, so such a proof cannot be verified in the circuit either.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationError {
+    /// The logup sums of the claim do not add up to zero.
+    LogupSum,
+    /// The nonce before drawing the interaction elements does not meet `INTERACTION_POW_BITS`.
+    InteractionProofOfWork,
+    /// The nonce before drawing the queries does not meet the `pow_bits` of the config.
+    ProofOfWork,
+    /// The sampled values do not match the mask points of the components.
+    SampledValuesShape,
+    /// The composition polynomial does not match the constraints at the OODS point.
+    CompositionEval,
+    /// The queried values do not match the columns of the trees.
+    QueriedValues,
+    /// The decommitment of a trace tree, or of a FRI layer, is malformed or does not lead to the
+    /// commitment.
+    Decommitment { tree: &'static str },
+    /// The FRI proof does not have the layers implied by the degree bounds.
+    FriLayers,
+    /// The folded evaluations do not match the last layer polynomial.
+    LastLayer,
+    /// The claim, or the public input, does not match the verifier params.
+    Params(ParamsMismatch),
+    /// The proof violates an assumption of the circuit. `validate_cairo_proof_for_recursion`
+    /// explains all the violations.
+    Incompatible(Assumption),
+}
+
+impl fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LogupSum => write!(f, "Invalid logup sum"),
+            Self::InteractionProofOfWork => write!(f, "Interaction proof of work failed"),
+            Self::ProofOfWork => write!(f, "Proof of work failed"),
+            Self::SampledValuesShape => {
+                write!(f, "The sampled values do not match the mask points")
+            }
+            Self::CompositionEval => {
+                write!(
+                    f,
+                    "The composition polynomial does not match the constraints"
+                )
+            }
+            Self::QueriedValues => write!(f, "The queried values do not match the columns"),
+            Self::Decommitment { tree } => write!(f, "Invalid decommitment of the {tree} tree"),
+            Self::FriLayers => write!(f, "The FRI layers do not match the degree bounds"),
+            Self::LastLayer => write!(f, "The folded values do not match the last layer"),
+            Self::Params(mismatch) => write!(f, "{mismatch}"),
+            Self::Incompatible(assumption) => write!(
+                f,
+                "The proof violates the {assumption:?} assumption of the verifier"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for VerificationError {}
+
+/// The part of the verifier params that a proof does not match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamsMismatch {
+    /// The params do not list all the builtins of the layout.
+    Builtins,
+    /// The public segments of the proof are not the builtins of the params.
+    SegmentLayout,
+    /// The program of the proof is not the one of the params.
+    Program,
+    /// The program of the proof does not have the length of the one of the params.
+    ProgramLength,
+    /// A program hash is given in the hardcoded program mode, or missing in the hashed one.
+    ProgramHashMode,
+    /// The proof was made with another PCS config than the one of the params.
+    PcsConfig,
+}
+
+impl fmt::Display for ParamsMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Builtins => write!(
+                f,
+                "The verifier requires a program with all the builtins of its layout"
+            ),
+            Self::SegmentLayout => write!(
+                f,
+                "The public segments of the proof do not match the builtins of the verifier params"
+            ),
+            Self::Program => write!(
+                f,
+                "The program of the proof does not match the verifier params"
+            ),
+            Self::ProgramLength => write!(
+                f,
+                "The program length of the proof does not match the verifier params"
+            ),
+            Self::ProgramHashMode => write!(
+                f,
+                "The program hash is a public input only in the hashed program mode"
+            ),
+            Self::PcsConfig => write!(
+                f,
+                "The PCS config of the proof does not match the verifier params"
+            ),
+        }
+    }
+}
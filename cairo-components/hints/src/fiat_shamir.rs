@@ -1,17 +1,22 @@
+use crate::{
+    validate_cairo_proof_for_recursion, CairoPadding, CairoVerifierParams, ParamsMismatch,
+    ProgramMode, VerificationError,
+};
 use cairo_air::{
-    air::{lookup_sum, CairoClaim, CairoComponents, CairoInteractionElements},
+    air::{lookup_sum, CairoComponents, CairoInteractionElements, MemorySection},
     verifier::INTERACTION_POW_BITS,
     CairoProof, PreProcessedTraceVariant,
 };
+use circle_plonk_dsl_primitives::{LoggedPoseidon31Channel, TranscriptLog};
 use itertools::Itertools;
-use num_traits::{One, Zero};
-use std::collections::{BTreeMap, BTreeSet, HashMap};
+use num_traits::Zero;
+use std::collections::{BTreeMap, BTreeSet};
 use stwo::core::{
     air::Components,
     channel::{Channel, Poseidon31Channel},
     circle::CirclePoint,
     fields::{
-        m31::{BaseField, M31},
+        m31::M31,
         qm31::{SecureField, QM31, SECURE_EXTENSION_DEGREE},
     },
     fri::{CirclePolyDegreeBound, FriVerifier},
@@ -23,13 +28,23 @@ use stwo::core::{
     },
     ColumnVec,
 };
-use stwo_cairo_common::{
-    builtins::RANGE_CHECK_MEMORY_CELLS, memory::LARGE_MEMORY_VALUE_ID_BASE,
-    prover_types::cpu::PRIME,
-};
 use stwo_constraint_framework::PREPROCESSED_TRACE_IDX;
 
+/// The maximal number of instances of a single opcode component, and of big memory tables,
+/// accepted by the recursive verifier. The circuit pads each of them to this number of instance
+/// slots, so that proofs with different numbers of instances share the same circuit.
+pub const MAX_COMPONENT_INSTANCES: usize = 8;
+
+/// The maximal length of the output memory section accepted by the recursive verifier. The
+/// circuit pads the output section to this length, so that programs with different output
+/// lengths share the same circuit.
+pub const MAX_OUTPUT_LEN: usize = 64;
+
 pub struct CairoFiatShamirHints {
+    pub params: CairoVerifierParams,
+    /// The program memory section of the proof, which is the witness of the hashed program mode.
+    /// In the hardcoded mode, the circuit takes the program from `params` instead.
+    pub program: MemorySection,
     pub initial_channel: [M31; 8],
 
     pub pcs_config: PcsConfig,
@@ -44,6 +59,8 @@ pub struct CairoFiatShamirHints {
     pub random_coeff: SecureField,
 
     pub component_generator: CairoComponents,
+    /// The layout of the trace padded to the instance slots of the circuit.
+    pub padding: CairoPadding,
     pub composition_log_size: u32,
     pub n_preprocessed_columns: usize,
 
@@ -56,154 +73,43 @@ pub struct CairoFiatShamirHints {
     pub commitment_scheme_verifier: CommitmentSchemeVerifier<Poseidon31MerkleChannel>,
     pub fri_verifier: FriVerifier<Poseidon31MerkleChannel>,
     pub after_sampled_values_random_coeff: QM31,
+
+    /// The channel right before the interaction commitment is mixed, where the transcript log of
+    /// the verifier circuit starts.
+    pub channel_before_interaction_commitment: Poseidon31Channel,
 }
 
 impl CairoFiatShamirHints {
-    fn verify_claim(claim: &CairoClaim) {
-        let segment_ranges = &claim.public_data.public_memory.public_segments;
-
-        // the only builtin we implemented is range_check_128
-        assert!(segment_ranges.range_check_128.is_some());
-        assert!(!segment_ranges.range_check_128.as_ref().unwrap().is_empty());
-
-        assert!(segment_ranges.pedersen.is_some());
-        assert!(segment_ranges.ecdsa.is_some());
-        assert!(segment_ranges.bitwise.is_some());
-        assert!(segment_ranges.ec_op.is_some());
-        assert!(segment_ranges.keccak.is_some());
-        assert!(segment_ranges.poseidon.is_some());
-        assert!(segment_ranges.range_check_96.is_some());
-        assert!(segment_ranges.add_mod.is_some());
-        assert!(segment_ranges.mul_mod.is_some());
-
-        assert!(segment_ranges.pedersen.as_ref().unwrap().is_empty());
-        assert!(segment_ranges.ecdsa.as_ref().unwrap().is_empty());
-        assert!(segment_ranges.bitwise.as_ref().unwrap().is_empty());
-        assert!(segment_ranges.ec_op.as_ref().unwrap().is_empty());
-        assert!(segment_ranges.keccak.as_ref().unwrap().is_empty());
-        assert!(segment_ranges.poseidon.as_ref().unwrap().is_empty());
-        assert!(segment_ranges.range_check_96.as_ref().unwrap().is_empty());
-        assert!(segment_ranges.add_mod.as_ref().unwrap().is_empty());
-        assert!(segment_ranges.mul_mod.as_ref().unwrap().is_empty());
-
-        // check output builtin
-        assert!(segment_ranges.output.stop_ptr.value < 1 << 31);
-        assert!(segment_ranges.output.start_ptr.value <= segment_ranges.output.stop_ptr.value);
-
-        // find the claim for range_check_128
-        // check the well-formedness of the range_check_128 builtin
-        assert!(claim.builtins.range_check_128_builtin.is_some());
-        {
-            let range_check_128_claim = claim.builtins.range_check_128_builtin.as_ref().unwrap();
-            let segment_range = segment_ranges.range_check_128.as_ref().unwrap();
-
-            let segment_start = range_check_128_claim.range_check_builtin_segment_start;
-            let log_size = range_check_128_claim.log_size;
-
-            let segment_end = segment_start + (1 << log_size) * RANGE_CHECK_MEMORY_CELLS as u32;
-            let start_ptr = segment_range.start_ptr.value;
-            let stop_ptr = segment_range.stop_ptr.value;
-
-            assert_eq!(start_ptr, segment_start);
-            assert!(start_ptr <= stop_ptr);
-            assert!(stop_ptr <= segment_end);
-            assert!(segment_end < 1 << 31);
-        }
-
-        assert!(claim.builtins.pedersen_builtin.is_none());
-        assert!(claim.builtins.bitwise_builtin.is_none());
-        assert!(claim.builtins.poseidon_builtin.is_none());
-        assert!(claim.builtins.range_check_96_builtin.is_none());
-        assert!(claim.builtins.add_mod_builtin.is_none());
-        assert!(claim.builtins.mul_mod_builtin.is_none());
-
-        let program = &claim.public_data.public_memory.program;
-        let n_builtins = claim
-            .public_data
-            .public_memory
-            .public_segments
-            .present_segments()
-            .len() as u32;
-
-        // First instruction: add_app_immediate (n_builtins).
-        assert_eq!(program[0].1, [0x7fff7fff, 0x4078001, 0, 0, 0, 0, 0, 0]); // add_ap_imm.
-        assert_eq!(program[1].1, [n_builtins, 0, 0, 0, 0, 0, 0, 0]); // Imm.
-
-        // Safe call.
-        assert_eq!(program[2].1, [0x80018000, 0x11048001, 0, 0, 0, 0, 0, 0]); // Instruction: call rel ?
-        assert_eq!(program[4].1, [0x7fff7fff, 0x1078001, 0, 0, 0, 0, 0, 0]); // Instruction: jmp rel 0.
-        assert_eq!(program[5].1, [0, 0, 0, 0, 0, 0, 0, 0]); // Imm of last instruction (jmp rel 0).
-
-        let initial_pc = &claim.public_data.initial_state.pc;
-        let initial_ap = &claim.public_data.initial_state.ap;
-        let initial_fp = &claim.public_data.initial_state.fp;
-        let final_fp = &claim.public_data.final_state.fp;
-        let final_pc = &claim.public_data.final_state.pc;
-        let final_ap = &claim.public_data.final_state.ap;
-
-        assert_eq!(*initial_pc, BaseField::one());
-        assert!(
-            *initial_pc + BaseField::from(2) < *initial_ap,
-            "Initial pc + 2 must be less than initial ap, but got initial_pc: {initial_pc}, initial_ap: {initial_ap}"
-        );
-        assert_eq!(initial_fp, final_fp);
-        assert_eq!(initial_fp, initial_ap);
-        assert_eq!(*final_pc, BaseField::from(5));
-        assert!(initial_ap <= final_ap);
-
-        let mut relation_uses = HashMap::<&'static str, u64>::new();
-        claim.accumulate_relation_uses(&mut relation_uses);
-        relation_uses.iter().for_each(|(_, count)| {
-            assert!(*count < PRIME as u64);
-        });
-
-        // Large value IDs reside in [LARGE_MEMORY_VALUE_ID_BASE..P).
-        // Check that IDs in (ID -> Value) do not overflow P.
-        let largest_id = claim
-            .memory_id_to_value
-            .big_log_sizes
-            .iter()
-            .map(|log_size| 1 << log_size)
-            .sum::<u32>()
-            - 1
-            + LARGE_MEMORY_VALUE_ID_BASE;
-        assert!(largest_id < PRIME);
+    pub fn new(proof: &CairoProof<Poseidon31MerkleHasher>, params: &CairoVerifierParams) -> Self {
+        Self::try_new(proof, params).unwrap_or_else(|err| panic!("{err}"))
     }
 
-    pub fn new(proof: &CairoProof<Poseidon31MerkleHasher>) -> Self {
-        assert_eq!(
-            proof.stark_proof.fri_proof.last_layer_poly.coeffs.len(),
-            1,
-            "The recursive verifier only works for last layer poly of a single constant"
-        );
-        assert_eq!(
-            proof
-                .stark_proof
-                .config
-                .fri_config
-                .log_last_layer_degree_bound,
-            0,
-            "The recursive verifier only works for last layer poly of a single constant"
-        );
-        assert_eq!(
-            proof.stark_proof.config.fri_config.log_blowup_factor, 1,
-            "The recursive verifier only works for log_blowup_factor of 1"
-        );
-        assert_eq!(
-            proof.stark_proof.config.pow_bits, 26,
-            "The recursive verifier assumes pow_bits of 26"
-        );
+    /// Replay the channel of the verifier, and return an error when the proof does not have the
+    /// shape of the circuit, the claim does not match the params, or the logup sum, a proof of
+    /// work, or the shape of the FRI proof is invalid.
+    pub fn try_new(
+        proof: &CairoProof<Poseidon31MerkleHasher>,
+        params: &CairoVerifierParams,
+    ) -> Result<Self, VerificationError> {
+        // the circuit is generated for the config of the params, not for the one of the proof
+        if proof.stark_proof.config != params.pcs_config {
+            return Err(VerificationError::Params(ParamsMismatch::PcsConfig));
+        }
+        // the shape of the proof that the circuit is generated for
+        if let Err(incompatibilities) = validate_cairo_proof_for_recursion(proof) {
+            return Err(VerificationError::Incompatible(
+                incompatibilities[0].assumption,
+            ));
+        }
 
         let claim = &proof.claim;
         let stark_proof = &proof.stark_proof;
-        let channel_salt = proof.channel_salt;
 
-        Self::verify_claim(claim);
+        params.check_claim(claim)?;
 
         let channel = &mut Poseidon31Channel::default();
-        assert!(channel_salt.is_none());
 
-        let pcs_config = stark_proof.config;
+        let pcs_config = params.pcs_config;
         pcs_config.mix_into(channel);
 
         let mut commitment_scheme_verifier =
@@ -224,6 +130,7 @@ impl CairoFiatShamirHints {
         {
             {
                 let program = &claim.public_data.public_memory.program;
+                let before_program_channel = channel.digest();
                 let public_segments = &claim.public_data.public_memory.public_segments;
                 let output = &claim.public_data.public_memory.output;
                 let safe_call_ids = &claim.public_data.public_memory.safe_call_ids;
@@ -233,7 +140,11 @@ impl CairoFiatShamirHints {
                 channel.mix_u32s(&program.iter().map(|(id, _)| *id).collect_vec());
                 channel.mix_u32s(&program.iter().flat_map(|(_, value)| *value).collect_vec());
 
-                initial_channel = channel.digest();
+                // In the hashed mode, the program is a witness that the circuit mixes on its own.
+                initial_channel = match params.program_mode {
+                    ProgramMode::Hardcoded => channel.digest(),
+                    ProgramMode::Hashed => before_program_channel,
+                };
 
                 // Mix public segments.
                 public_segments.mix_into(channel);
@@ -252,36 +163,12 @@ impl CairoFiatShamirHints {
             claim.public_data.initial_state.mix_into(channel);
             claim.public_data.final_state.mix_into(channel);
 
-            // Force a specific shape of the opcodes
-            assert_eq!(claim.opcodes.add.len(), 1);
-            assert_eq!(claim.opcodes.add_small.len(), 1);
-            assert_eq!(claim.opcodes.add_ap.len(), 1);
-            assert_eq!(claim.opcodes.assert_eq.len(), 1);
-            assert_eq!(claim.opcodes.assert_eq_imm.len(), 1);
-            assert_eq!(claim.opcodes.assert_eq_double_deref.len(), 1);
-            assert_eq!(claim.opcodes.blake.len(), 1);
-            assert_eq!(claim.opcodes.call.len(), 1);
-            assert_eq!(claim.opcodes.call_rel_imm.len(), 1);
-            assert_eq!(claim.opcodes.generic.len(), 0);
-            assert_eq!(claim.opcodes.jnz.len(), 1);
-            assert_eq!(claim.opcodes.jnz_taken.len(), 1);
-            assert_eq!(claim.opcodes.jump.len(), 0);
-            assert_eq!(claim.opcodes.jump_double_deref.len(), 0);
-            assert_eq!(claim.opcodes.jump_rel.len(), 1);
-            assert_eq!(claim.opcodes.jump_rel_imm.len(), 1);
-            assert_eq!(claim.opcodes.mul.len(), 1);
-            assert_eq!(claim.opcodes.mul_small.len(), 1);
-            assert_eq!(claim.opcodes.qm31.len(), 1);
-            assert_eq!(claim.opcodes.ret.len(), 1);
-
             claim.opcodes.mix_into(channel);
             claim.verify_instruction.mix_into(channel);
             claim.blake_context.mix_into(channel);
             claim.builtins.mix_into(channel);
-            assert!(claim.pedersen_context.claim.is_none());
-            assert!(claim.poseidon_context.claim.is_none());
+            claim.poseidon_context.mix_into(channel);
             claim.memory_address_to_id.mix_into(channel);
-            assert_eq!(claim.memory_id_to_value.big_log_sizes.len(), 1);
             claim.memory_id_to_value.mix_into(channel);
             claim.verify_bitwise_xor_4.mix_into(channel);
             claim.verify_bitwise_xor_7.mix_into(channel);
@@ -299,47 +186,18 @@ impl CairoFiatShamirHints {
 
         // Proof of work.
         if !channel.verify_pow_nonce(INTERACTION_POW_BITS, proof.interaction_pow) {
-            panic!("Proof of work failed");
+            return Err(VerificationError::InteractionProofOfWork);
         }
         channel.mix_u64(proof.interaction_pow);
         let interaction_elements = CairoInteractionElements::draw(channel);
 
-        assert!(proof.interaction_claim.opcodes.generic.is_empty());
-        assert!(proof.interaction_claim.opcodes.jump.is_empty());
-        assert!(proof.interaction_claim.opcodes.jump_double_deref.is_empty());
-        assert!(proof.interaction_claim.blake_context.claim.is_some());
-        assert!(proof.interaction_claim.builtins.add_mod_builtin.is_none());
-        assert!(proof.interaction_claim.builtins.bitwise_builtin.is_none());
-        assert!(proof.interaction_claim.builtins.mul_mod_builtin.is_none());
-        assert!(proof.interaction_claim.builtins.pedersen_builtin.is_none());
-        assert!(proof.interaction_claim.builtins.poseidon_builtin.is_none());
-        assert!(proof
-            .interaction_claim
-            .builtins
-            .range_check_96_builtin
-            .is_none());
-        assert!(proof
-            .interaction_claim
-            .builtins
-            .range_check_128_builtin
-            .is_some());
-        assert!(proof.interaction_claim.pedersen_context.claim.is_none());
-        assert!(proof.interaction_claim.poseidon_context.claim.is_none());
-        assert_eq!(
-            proof
-                .interaction_claim
-                .memory_id_to_value
-                .big_claimed_sums
-                .len(),
-            1
-        );
-
         // Verify lookup argument.
         if lookup_sum(claim, &interaction_elements, &proof.interaction_claim) != SecureField::zero()
         {
-            panic!("Invalid logup sum");
+            return Err(VerificationError::LogupSum);
         }
         proof.interaction_claim.mix_into(channel);
+        let channel_before_interaction_commitment = channel.clone();
         commitment_scheme_verifier.commit(stark_proof.commitments[2], &log_sizes[2], channel);
 
         let component_generator = CairoComponents::new(
@@ -349,6 +207,8 @@ impl CairoFiatShamirHints {
             &preprocessed_trace.ids(),
         );
 
+        let padding = CairoPadding::new(claim, &proof.interaction_claim);
+
         let components = component_generator.components();
 
         let n_preprocessed_columns = commitment_scheme_verifier.trees[PREPROCESSED_TRACE_IDX]
@@ -412,7 +272,7 @@ impl CairoFiatShamirHints {
             proof.stark_proof.fri_proof.clone(),
             bounds,
         )
-        .unwrap();
+        .map_err(|_| VerificationError::FriLayers)?;
 
         let mut fri_alphas = vec![];
         fri_alphas.push(fri_verifier.first_layer.folding_alpha);
@@ -425,7 +285,7 @@ impl CairoFiatShamirHints {
             commitment_scheme_verifier.config.pow_bits,
             proof.stark_proof.proof_of_work,
         ) {
-            panic!("Proof of work failed");
+            return Err(VerificationError::ProofOfWork);
         }
         channel.mix_u64(proof.stark_proof.proof_of_work);
 
@@ -471,7 +331,9 @@ impl CairoFiatShamirHints {
         // Get FRI query positions.
         let query_positions_per_log_size = fri_verifier.sample_query_positions(channel);
 
-        Self {
+        Ok(Self {
+            params: params.clone(),
+            program: claim.public_data.public_memory.program.clone(),
             initial_channel,
             pcs_config,
             log_sizes,
@@ -484,6 +346,7 @@ impl CairoFiatShamirHints {
             sample_points,
             random_coeff,
             component_generator,
+            padding,
             composition_log_size,
             n_preprocessed_columns,
             raw_queries,
@@ -496,13 +359,54 @@ impl CairoFiatShamirHints {
             commitment_scheme_verifier,
             fri_verifier,
             after_sampled_values_random_coeff,
+            channel_before_interaction_commitment,
+        })
+    }
+
+    /// Replay the channel operations of the verifier circuit from the interaction commitment on
+    /// a logged native channel, so that the transcript can be compared with the one of the
+    /// circuit when a proof fails to verify.
+    pub fn transcript(&self, proof: &CairoProof<Poseidon31MerkleHasher>) -> TranscriptLog {
+        let mut channel =
+            LoggedPoseidon31Channel::new(self.channel_before_interaction_commitment.clone());
+
+        channel.mix_root(self.interaction_commitment);
+        assert_eq!(channel.draw_secure_felt(), self.random_coeff);
+        channel.mix_root(self.composition_commitment);
+
+        // Draw OODS point.
+        let _ = channel.draw_secure_felt();
+
+        channel.mix_felts(&proof.stark_proof.sampled_values.clone().flatten_cols());
+        assert_eq!(
+            channel.draw_secure_felt(),
+            self.after_sampled_values_random_coeff
+        );
+
+        // the skipped inner layers are not part of the proof, and the circuit does not log them
+        let fri_proof = &proof.stark_proof.fri_proof;
+        let layer_commitments = std::iter::once(fri_proof.first_layer.commitment)
+            .chain(fri_proof.inner_layers.iter().map(|layer| layer.commitment));
+        for (commitment, fri_alpha) in layer_commitments.zip_eq(self.fri_alphas.iter()) {
+            channel.mix_root(commitment);
+            assert_eq!(channel.draw_secure_felt(), *fri_alpha);
+        }
+        channel.mix_felts(&fri_proof.last_layer_poly.coeffs);
+
+        channel.mix_u64(proof.stark_proof.proof_of_work);
+
+        for _ in 0..self.pcs_config.fri_config.n_queries.div_ceil(4) {
+            let _ = channel.draw_u32s();
         }
+
+        channel.log
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Assumption;
     use cairo_air::utils::{deserialize_proof_from_file, ProofFormat};
     use std::path::PathBuf;
 
@@ -516,6 +420,44 @@ mod tests {
             .join("recursive_proof.bin.bz");
 
         let proof = deserialize_proof_from_file(&data_path, ProofFormat::Binary).unwrap();
-        let _ = CairoFiatShamirHints::new(&proof);
+        let params = CairoVerifierParams::from_proof(&proof);
+        let _ = CairoFiatShamirHints::new(&proof, &params);
+
+        // a proof made with another config than the one of the verifier is rejected
+        let mut params = params;
+        params.pcs_config.fri_config.log_blowup_factor += 1;
+        assert!(matches!(
+            CairoFiatShamirHints::try_new(&proof, &params),
+            Err(VerificationError::Params(ParamsMismatch::PcsConfig))
+        ));
+    }
+
+    #[test]
+    fn test_fiat_shamir_hints_too_many_instances() {
+        let manifest_dir = env!("CARGO_MANIFEST_DIR");
+        let data_path = PathBuf::from(manifest_dir)
+            .parent()
+            .unwrap()
+            .join("test_data")
+            .join("recursive_proof.bin.bz");
+
+        let mut proof = deserialize_proof_from_file(&data_path, ProofFormat::Binary).unwrap();
+        let params = CairoVerifierParams::from_proof(&proof);
+
+        // one more instance of the ret opcode, which every program uses, than the circuit has
+        // room for
+        let instance = proof.claim.opcodes.ret[0].clone();
+        let claimed_sum = proof.interaction_claim.opcodes.ret[0].clone();
+        proof.claim.opcodes.ret = vec![instance; MAX_COMPONENT_INSTANCES + 1];
+        proof.interaction_claim.opcodes.ret = vec![claimed_sum; MAX_COMPONENT_INSTANCES + 1];
+
+        assert!(matches!(
+            CairoFiatShamirHints::try_new(&proof, &params),
+            Err(VerificationError::Incompatible(_))
+        ));
+        let incompatibilities = validate_cairo_proof_for_recursion(&proof).unwrap_err();
+        assert!(incompatibilities
+            .iter()
+            .any(|incompatibility| incompatibility.assumption == Assumption::ComponentInstances));
     }
 }
@@ -22,7 +22,7 @@ use stwo::core::{
     },
 };
 
-use crate::{AnswerHints, CairoFiatShamirHints};
+use crate::{AnswerHints, CairoFiatShamirHints, VerificationError};
 
 #[derive(Clone)]
 pub struct SinglePairMerkleProof {
@@ -39,6 +39,10 @@ pub struct SinglePairMerkleProof {
 
 impl SinglePairMerkleProof {
     pub fn verify(&self) {
+        assert_eq!(self.compute_root(), self.root);
+    }
+
+    pub fn compute_root(&self) -> Poseidon31Hash {
         let mut self_hash = Poseidon31MerkleHasher::hash_node(
             None,
             &self
@@ -95,9 +99,11 @@ impl SinglePairMerkleProof {
                 };
             }
         }
-        assert_eq!(self_hash, self.root);
+        self_hash
     }
 
+    /// Split the Merkle decommitment of a FRI layer into one proof per query, and return an error
+    /// when the decommitment is malformed or does not lead to the root.
     pub fn from_stwo_proof(
         log_sizes_with_data: &BTreeSet<u32>,
         root: Poseidon31Hash,
@@ -105,10 +111,14 @@ impl SinglePairMerkleProof {
         values: &[M31],
         decommitment: &MerkleDecommitment<Poseidon31MerkleHasher>,
         log_blowup_factor: u32,
-    ) -> Vec<SinglePairMerkleProof> {
+    ) -> Result<Vec<SinglePairMerkleProof>, VerificationError> {
+        let invalid = VerificationError::Decommitment { tree: "FRI layer" };
+
         // require the column witness to be empty
         // (all the values are provided)
-        assert_eq!(decommitment.column_witness.len(), 0);
+        if !decommitment.column_witness.is_empty() {
+            return Err(invalid);
+        }
 
         // get the max log_size
         let max_log_size = *log_sizes_with_data.iter().max().unwrap();
@@ -138,12 +148,10 @@ impl SinglePairMerkleProof {
 
                 let mut queries_values = BTreeMap::new();
                 for k in self_and_siblings.iter() {
-                    let v = [
-                        *values_iter.next().unwrap(),
-                        *values_iter.next().unwrap(),
-                        *values_iter.next().unwrap(),
-                        *values_iter.next().unwrap(),
-                    ];
+                    let mut v = [M31::zero(); SECURE_EXTENSION_DEGREE];
+                    for limb in v.iter_mut() {
+                        *limb = *values_iter.next().ok_or(invalid)?;
+                    }
                     queries_values.insert(*k, v);
                 }
 
@@ -159,7 +167,7 @@ impl SinglePairMerkleProof {
                             if let Some(hash) = hash_layers.last().unwrap().get(&left_idx) {
                                 *hash
                             } else {
-                                let v = *hash_iter.next().unwrap();
+                                let v = *hash_iter.next().ok_or(invalid)?;
                                 hash_layers.last_mut().unwrap().insert(left_idx, v);
                                 v
                             };
@@ -167,7 +175,7 @@ impl SinglePairMerkleProof {
                             if let Some(hash) = hash_layers.last().unwrap().get(&right_idx) {
                                 *hash
                             } else {
-                                let v = *hash_iter.next().unwrap();
+                                let v = *hash_iter.next().ok_or(invalid)?;
                                 hash_layers.last_mut().unwrap().insert(right_idx, v);
                                 v
                             };
@@ -191,7 +199,7 @@ impl SinglePairMerkleProof {
                     let left_hash = if let Some(hash) = hash_layers.last().unwrap().get(&left_idx) {
                         *hash
                     } else {
-                        let v = *hash_iter.next().unwrap();
+                        let v = *hash_iter.next().ok_or(invalid)?;
                         hash_layers.last_mut().unwrap().insert(left_idx, v);
                         v
                     };
@@ -199,7 +207,7 @@ impl SinglePairMerkleProof {
                     {
                         *hash
                     } else {
-                        let v = *hash_iter.next().unwrap();
+                        let v = *hash_iter.next().ok_or(invalid)?;
                         hash_layers.last_mut().unwrap().insert(right_idx, v);
                         v
                     };
@@ -214,11 +222,13 @@ impl SinglePairMerkleProof {
             queries.iter_mut().for_each(|v| *v >>= 1);
         }
 
-        assert!(values_iter.next().is_none());
-        assert!(hash_iter.next().is_none());
-
-        assert_eq!(hash_layers.last().unwrap().len(), 1);
-        assert_eq!(*hash_layers.last().unwrap().get(&0).unwrap(), root);
+        if values_iter.next().is_some() || hash_iter.next().is_some() {
+            return Err(invalid);
+        }
+        let root_layer = hash_layers.last().unwrap();
+        if root_layer.len() != 1 || root_layer.get(&0) != Some(&root) {
+            return Err(invalid);
+        }
 
         let mut proofs = vec![];
         for leaf_query in leaf_queries.iter() {
@@ -290,10 +300,12 @@ impl SinglePairMerkleProof {
                 depth: max_log_size as usize,
                 log_blowup_factor,
             };
-            proof.verify();
+            if proof.compute_root() != root {
+                return Err(invalid);
+            }
             proofs.push(proof);
         }
-        proofs
+        Ok(proofs)
     }
 }
 
@@ -308,7 +320,11 @@ impl FirstLayerHints {
         fiat_shamir_hints: &CairoFiatShamirHints,
         answer_hints: &AnswerHints,
         proof: &CairoProof<Poseidon31MerkleHasher>,
-    ) -> FirstLayerHints {
+    ) -> Result<FirstLayerHints, VerificationError> {
+        let invalid = VerificationError::Decommitment {
+            tree: "FRI first layer",
+        };
+
         // Columns are provided in descending order by size.
         let max_column_log_size = fiat_shamir_hints
             .fri_verifier
@@ -345,7 +361,8 @@ impl FirstLayerHints {
                     column_domain.log_size(),
                     column_query_evals,
                     &mut fri_witness,
-                );
+                )
+                .ok_or(invalid)?;
 
             // Columns of the same size have the same decommitment positions.
             decommitment_positions_by_log_size
@@ -368,7 +385,9 @@ impl FirstLayerHints {
             );
         }
 
-        assert!(fri_witness.next().is_none());
+        if fri_witness.next().is_some() {
+            return Err(invalid);
+        }
 
         let merkle_verifier = MerkleVerifier::new(
             proof.stark_proof.fri_proof.first_layer.commitment,
@@ -387,7 +406,7 @@ impl FirstLayerHints {
                 decommitmented_values.clone(),
                 proof.stark_proof.fri_proof.first_layer.decommitment.clone(),
             )
-            .unwrap();
+            .map_err(|_| invalid)?;
 
         // log_sizes with data
         let mut log_sizes_with_data = BTreeSet::new();
@@ -407,12 +426,12 @@ impl FirstLayerHints {
             &decommitmented_values,
             &proof.stark_proof.fri_proof.first_layer.decommitment,
             fiat_shamir_hints.pcs_config.fri_config.log_blowup_factor,
-        );
+        )?;
 
-        FirstLayerHints {
+        Ok(FirstLayerHints {
             merkle_proofs,
             folded_evals_by_column,
-        }
+        })
     }
 
     pub fn compute_decommitment_positions_and_rebuild_evals(
@@ -420,7 +439,7 @@ impl FirstLayerHints {
         domain_log_size: u32,
         query_evals: &[SecureField],
         mut witness_evals: impl Iterator<Item = SecureField>,
-    ) -> (Vec<usize>, SparseEvaluation) {
+    ) -> Option<(Vec<usize>, SparseEvaluation)> {
         let mut queries = queries.to_vec();
         queries.dedup();
         queries.sort_unstable();
@@ -441,17 +460,17 @@ impl FirstLayerHints {
 
             let subset_eval = subset_decommitment_positions
                 .map(|position| match subset_queries_iter.next_if_eq(&position) {
-                    Some(_) => query_evals.next().unwrap(),
-                    None => witness_evals.next().unwrap(),
+                    Some(_) => query_evals.next(),
+                    None => witness_evals.next(),
                 })
-                .collect_vec();
+                .collect::<Option<Vec<_>>>()?;
 
             subset_evals.push(subset_eval.clone());
             subset_domain_index_initials.push(bit_reverse_index(subset_start, domain_log_size));
         }
 
         let sparse_evaluation = SparseEvaluation::new(subset_evals, subset_domain_index_initials);
-        (decommitment_positions, sparse_evaluation)
+        Some((decommitment_positions, sparse_evaluation))
     }
 }
 
@@ -465,7 +484,11 @@ impl InnerLayersHints {
         folded_evals_by_column: &BTreeMap<u32, Vec<SecureField>>,
         fiat_shamir_hints: &CairoFiatShamirHints,
         proof: &CairoProof<Poseidon31MerkleHasher>,
-    ) -> InnerLayersHints {
+    ) -> Result<InnerLayersHints, VerificationError> {
+        let invalid = VerificationError::Decommitment {
+            tree: "FRI inner layer",
+        };
+
         let mut log_size = fiat_shamir_hints.max_first_layer_column_log_size;
 
         let mut folded = BTreeMap::new();
@@ -502,7 +525,7 @@ impl InnerLayersHints {
                 let sibling_v = if let Some(&sibling_v) = folded.get(&(k ^ 1)) {
                     sibling_v
                 } else {
-                    *fri_witness.next().unwrap()
+                    *fri_witness.next().ok_or(invalid)?
                 };
 
                 let (left_v, right_v) = if k & 1 == 0 {
@@ -544,7 +567,7 @@ impl InnerLayersHints {
                     decommitmented_values.clone(),
                     inner_layer.decommitment.clone(),
                 )
-                .unwrap();
+                .map_err(|_| invalid)?;
 
             let merkle_proofs = SinglePairMerkleProof::from_stwo_proof(
                 &BTreeSet::from([log_size]),
@@ -559,26 +582,34 @@ impl InnerLayersHints {
                 &decommitmented_values,
                 &inner_layer.decommitment,
                 fiat_shamir_hints.pcs_config.fri_config.log_blowup_factor,
-            );
-            for merkle_proof in merkle_proofs.iter() {
-                merkle_proof.verify();
-            }
+            )?;
             all_merkle_proofs.insert(log_size, merkle_proofs);
 
-            assert!(fri_witness.next().is_none());
+            if fri_witness.next().is_some() {
+                return Err(invalid);
+            }
             all_folded_intermediate_results.insert(log_size, folded.clone());
             folded = new_folded;
         }
 
-        for (_, v) in folded.iter() {
-            assert_eq!(proof.stark_proof.fri_proof.last_layer_poly.coeffs.len(), 1);
-            assert_eq!(*v, proof.stark_proof.fri_proof.last_layer_poly.coeffs[0]);
+        let last_layer_domain = Coset::half_odds(log_size);
+        for (k, v) in folded.iter() {
+            let x = last_layer_domain.at(bit_reverse_index(*k, log_size)).x;
+            if *v
+                != proof
+                    .stark_proof
+                    .fri_proof
+                    .last_layer_poly
+                    .eval_at_point(x.into())
+            {
+                return Err(VerificationError::LastLayer);
+            }
         }
 
-        Self {
+        Ok(Self {
             merkle_proofs: all_merkle_proofs,
             folded_intermediate_results: all_folded_intermediate_results,
-        }
+        })
     }
 }
 
@@ -593,27 +624,34 @@ impl CairoFoldingHints {
         answer_hints: &AnswerHints,
         proof: &CairoProof<Poseidon31MerkleHasher>,
     ) -> Self {
-        let first_layer_hints = FirstLayerHints::compute(fiat_shamir_hints, answer_hints, proof);
-        for proof in first_layer_hints.merkle_proofs.iter() {
-            proof.verify();
-        }
+        Self::try_new(fiat_shamir_hints, answer_hints, proof).unwrap_or_else(|err| panic!("{err}"))
+    }
 
+    /// Fold the answers through the FRI layers, and return an error when a decommitment of a
+    /// layer or the last layer does not match.
+    pub fn try_new(
+        fiat_shamir_hints: &CairoFiatShamirHints,
+        answer_hints: &AnswerHints,
+        proof: &CairoProof<Poseidon31MerkleHasher>,
+    ) -> Result<Self, VerificationError> {
+        let first_layer_hints = FirstLayerHints::compute(fiat_shamir_hints, answer_hints, proof)?;
         let inner_layers_hints = InnerLayersHints::compute(
             &first_layer_hints.folded_evals_by_column,
             fiat_shamir_hints,
             proof,
-        );
+        )?;
 
-        Self {
+        Ok(Self {
             first_layer_hints,
             inner_layers_hints,
-        }
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::CairoVerifierParams;
     use cairo_air::utils::{deserialize_proof_from_file, ProofFormat};
     use std::path::PathBuf;
 
@@ -627,7 +665,8 @@ mod tests {
             .join("recursive_proof.bin.bz");
 
         let proof = deserialize_proof_from_file(&data_path, ProofFormat::Binary).unwrap();
-        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof);
+        let params = CairoVerifierParams::from_proof(&proof);
+        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof, &params);
         let answer_hints = AnswerHints::new(&fiat_shamir_hints, &proof);
         let _ = CairoFoldingHints::new(&fiat_shamir_hints, &answer_hints, &proof);
     }
@@ -1,6 +1,18 @@
+pub mod params;
+pub use params::*;
+
+pub mod error;
+pub use error::*;
+
+pub mod validate;
+pub use validate::*;
+
 pub mod fiat_shamir;
 pub use fiat_shamir::*;
 
+pub mod padding;
+pub use padding::*;
+
 pub mod composition;
 pub use composition::*;
 
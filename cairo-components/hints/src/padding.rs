@@ -0,0 +1,362 @@
+use crate::MAX_COMPONENT_INSTANCES;
+use cairo_air::{
+    air::{CairoClaim, CairoComponents, CairoInteractionClaim, CairoInteractionElements},
+    components, poseidon, PreProcessedTraceVariant,
+};
+use itertools::Itertools;
+use num_traits::Zero;
+use stwo::{
+    core::{
+        air::Components,
+        channel::Poseidon31Channel,
+        circle::CirclePoint,
+        fields::qm31::SecureField,
+        pcs::{TreeSubspan, TreeVec},
+        ColumnVec,
+    },
+    prover::backend::simd::m31::LOG_N_LANES,
+};
+use stwo_constraint_framework::PREPROCESSED_TRACE_IDX;
+
+/// The columns of one instance slot in the padded trace.
+#[derive(Debug, Clone)]
+pub struct InstanceSlot {
+    pub is_active: bool,
+    pub locations: Vec<TreeSubspan>,
+}
+
+/// The layout of the trace of a proof whose opcodes and big memory tables are padded to
+/// `MAX_COMPONENT_INSTANCES` instance slots each, and whose absent optional builtins and Poseidon
+/// context are padded to one slot each, which is the layout of the recursive verifier.
+///
+/// The padding instances have the log size `LOG_N_LANES` here, so that their native components
+/// have a valid layout, while the circuit gives them the log size 0.
+pub struct CairoPadding {
+    pub claim: CairoClaim,
+    pub interaction_claim: CairoInteractionClaim,
+    pub components: CairoComponents,
+    /// The slots of the opcodes in the order of the opcode claim, followed by the slots of the
+    /// big memory tables, of the optional builtins and of the Poseidon context.
+    pub slots: Vec<InstanceSlot>,
+    /// The number of samples of each column of the padded trace.
+    pub n_samples: TreeVec<ColumnVec<usize>>,
+}
+
+impl CairoPadding {
+    pub fn new(claim: &CairoClaim, interaction_claim: &CairoInteractionClaim) -> Self {
+        let mut padded_claim = claim.clone();
+        let mut padded_interaction_claim = interaction_claim.clone();
+
+        macro_rules! pad_opcode {
+            ($($name:ident: $component:ident),*) => {$(
+                padded_claim.opcodes.$name.resize(
+                    MAX_COMPONENT_INSTANCES,
+                    components::$component::Claim {
+                        log_size: LOG_N_LANES,
+                    },
+                );
+                padded_interaction_claim.opcodes.$name.resize(
+                    MAX_COMPONENT_INSTANCES,
+                    components::$component::InteractionClaim {
+                        claimed_sum: SecureField::zero(),
+                    },
+                );
+            )*};
+        }
+        pad_opcode!(
+            add: add_opcode,
+            add_small: add_opcode_small,
+            add_ap: add_ap_opcode,
+            assert_eq: assert_eq_opcode,
+            assert_eq_imm: assert_eq_opcode_imm,
+            assert_eq_double_deref: assert_eq_opcode_double_deref,
+            blake: blake_compress_opcode,
+            call: call_opcode_abs,
+            call_rel_imm: call_opcode_rel_imm,
+            generic: generic_opcode,
+            jnz: jnz_opcode_non_taken,
+            jnz_taken: jnz_opcode_taken,
+            jump: jump_opcode_abs,
+            jump_double_deref: jump_opcode_double_deref,
+            jump_rel: jump_opcode_rel,
+            jump_rel_imm: jump_opcode_rel_imm,
+            mul: mul_opcode,
+            mul_small: mul_opcode_small,
+            qm31: qm_31_add_mul_opcode,
+            ret: ret_opcode
+        );
+        padded_claim
+            .memory_id_to_value
+            .big_log_sizes
+            .resize(MAX_COMPONENT_INSTANCES, LOG_N_LANES);
+        padded_interaction_claim
+            .memory_id_to_value
+            .big_claimed_sums
+            .resize(MAX_COMPONENT_INSTANCES, SecureField::zero());
+
+        macro_rules! pad_builtin {
+            ($($name:ident: $component:ident, $segment_start:ident),*) => {$(
+                padded_claim.builtins.$name.get_or_insert(components::$component::Claim {
+                    log_size: LOG_N_LANES,
+                    $segment_start: 0,
+                });
+                padded_interaction_claim.builtins.$name.get_or_insert(
+                    components::$component::InteractionClaim {
+                        claimed_sum: SecureField::zero(),
+                    },
+                );
+            )*};
+        }
+        pad_builtin!(
+            add_mod_builtin: add_mod_builtin, add_mod_builtin_segment_start,
+            bitwise_builtin: bitwise_builtin, bitwise_builtin_segment_start,
+            mul_mod_builtin: mul_mod_builtin, mul_mod_builtin_segment_start,
+            poseidon_builtin: poseidon_builtin, poseidon_builtin_segment_start,
+            range_check_96_builtin: range_check_builtin_bits_96, range_check96_builtin_segment_start
+        );
+        padded_claim
+            .poseidon_context
+            .claim
+            .get_or_insert(poseidon::air::Claim {
+                poseidon_aggregator: components::poseidon_aggregator::Claim {
+                    log_size: LOG_N_LANES,
+                },
+                poseidon_3_partial_rounds_chain:
+                    components::poseidon_3_partial_rounds_chain::Claim {
+                        log_size: LOG_N_LANES,
+                    },
+                poseidon_full_round_chain: components::poseidon_full_round_chain::Claim {
+                    log_size: LOG_N_LANES,
+                },
+                cube_252: components::cube_252::Claim {
+                    log_size: LOG_N_LANES,
+                },
+                poseidon_round_keys: components::poseidon_round_keys::Claim {},
+                range_check_252_width_27: components::range_check_252_width_27::Claim {
+                    log_size: LOG_N_LANES,
+                },
+            });
+        padded_interaction_claim
+            .poseidon_context
+            .claim
+            .get_or_insert(poseidon::air::InteractionClaim {
+                poseidon_aggregator: components::poseidon_aggregator::InteractionClaim {
+                    claimed_sum: SecureField::zero(),
+                },
+                poseidon_3_partial_rounds_chain:
+                    components::poseidon_3_partial_rounds_chain::InteractionClaim {
+                        claimed_sum: SecureField::zero(),
+                    },
+                poseidon_full_round_chain:
+                    components::poseidon_full_round_chain::InteractionClaim {
+                        claimed_sum: SecureField::zero(),
+                    },
+                cube_252: components::cube_252::InteractionClaim {
+                    claimed_sum: SecureField::zero(),
+                },
+                poseidon_round_keys: components::poseidon_round_keys::InteractionClaim {
+                    claimed_sum: SecureField::zero(),
+                },
+                range_check_252_width_27: components::range_check_252_width_27::InteractionClaim {
+                    claimed_sum: SecureField::zero(),
+                },
+            });
+
+        // the layout does not depend on the interaction elements
+        let interaction_elements =
+            CairoInteractionElements::draw(&mut Poseidon31Channel::default());
+        let preprocessed_trace =
+            PreProcessedTraceVariant::CanonicalWithoutPedersen.to_preprocessed_trace();
+        let components = CairoComponents::new(
+            &padded_claim,
+            &interaction_elements,
+            &padded_interaction_claim,
+            &preprocessed_trace.ids(),
+        );
+
+        let mut slots = vec![];
+        macro_rules! add_slots {
+            ($($name:ident),*) => {$(
+                for (i, component) in components.opcodes.$name.iter().enumerate() {
+                    slots.push(InstanceSlot {
+                        is_active: i < claim.opcodes.$name.len(),
+                        locations: component.trace_locations().to_vec(),
+                    });
+                }
+            )*};
+        }
+        add_slots!(
+            add,
+            add_small,
+            add_ap,
+            assert_eq,
+            assert_eq_imm,
+            assert_eq_double_deref,
+            blake,
+            call,
+            call_rel_imm,
+            generic,
+            jnz,
+            jnz_taken,
+            jump,
+            jump_double_deref,
+            jump_rel,
+            jump_rel_imm,
+            mul,
+            mul_small,
+            qm31,
+            ret
+        );
+        for (i, component) in components.memory_id_to_value.0.iter().enumerate() {
+            slots.push(InstanceSlot {
+                is_active: i < claim.memory_id_to_value.big_log_sizes.len(),
+                locations: component.trace_locations().to_vec(),
+            });
+        }
+        macro_rules! add_builtin_slots {
+            ($($name:ident),*) => {$(
+                slots.push(InstanceSlot {
+                    is_active: claim.builtins.$name.is_some(),
+                    locations: components
+                        .builtins
+                        .$name
+                        .as_ref()
+                        .unwrap()
+                        .trace_locations()
+                        .to_vec(),
+                });
+            )*};
+        }
+        add_builtin_slots!(
+            add_mod_builtin,
+            bitwise_builtin,
+            mul_mod_builtin,
+            poseidon_builtin,
+            range_check_96_builtin
+        );
+        let poseidon_context = components.poseidon_context.components.as_ref().unwrap();
+        slots.push(InstanceSlot {
+            is_active: claim.poseidon_context.claim.is_some(),
+            locations: [
+                poseidon_context.poseidon_aggregator.trace_locations(),
+                poseidon_context
+                    .poseidon_3_partial_rounds_chain
+                    .trace_locations(),
+                poseidon_context.poseidon_full_round_chain.trace_locations(),
+                poseidon_context.cube_252.trace_locations(),
+                poseidon_context.poseidon_round_keys.trace_locations(),
+                poseidon_context.range_check_252_width_27.trace_locations(),
+            ]
+            .concat(),
+        });
+
+        let n_samples = Components {
+            components: components.components().to_vec(),
+            n_preprocessed_columns: preprocessed_trace.log_sizes().len(),
+        }
+        .mask_points(CirclePoint::zero())
+        .map_cols(|points| points.len());
+
+        Self {
+            claim: padded_claim,
+            interaction_claim: padded_interaction_claim,
+            components,
+            slots,
+            n_samples,
+        }
+    }
+
+    /// Insert zero columns for the inactive slots into the sampled values of the trace and the
+    /// interaction trace.
+    pub fn pad_sampled_values(
+        &self,
+        sampled_values: &TreeVec<ColumnVec<Vec<SecureField>>>,
+    ) -> TreeVec<ColumnVec<Vec<SecureField>>> {
+        let mut is_padding = self
+            .n_samples
+            .iter()
+            .map(|tree| vec![false; tree.len()])
+            .collect_vec();
+        for slot in self.slots.iter().filter(|slot| !slot.is_active) {
+            for location in slot.locations.iter() {
+                if location.tree_index != PREPROCESSED_TRACE_IDX {
+                    is_padding[location.tree_index][location.col_start..location.col_end]
+                        .fill(true);
+                }
+            }
+        }
+
+        let mut padded = TreeVec::new(vec![]);
+        for (tree_index, tree) in sampled_values.iter().enumerate() {
+            if tree_index == PREPROCESSED_TRACE_IDX || tree_index >= is_padding.len() {
+                padded.push(tree.clone());
+                continue;
+            }
+
+            let mut columns = tree.iter();
+            let padded_tree = is_padding[tree_index]
+                .iter()
+                .zip_eq(self.n_samples[tree_index].iter())
+                .map(|(is_padding, n_samples)| {
+                    if *is_padding {
+                        vec![SecureField::zero(); *n_samples]
+                    } else {
+                        columns.next().unwrap().clone()
+                    }
+                })
+                .collect_vec();
+            assert!(columns.next().is_none());
+            padded.push(padded_tree);
+        }
+        padded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cairo_air::utils::{deserialize_proof_from_file, ProofFormat};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_padding_keeps_the_sampled_values_of_active_slots() {
+        let manifest_dir = env!("CARGO_MANIFEST_DIR");
+        let data_path = PathBuf::from(manifest_dir)
+            .parent()
+            .unwrap()
+            .join("test_data")
+            .join("recursive_proof.bin.bz");
+        let proof = deserialize_proof_from_file(&data_path, ProofFormat::Binary).unwrap();
+
+        let padding = CairoPadding::new(&proof.claim, &proof.interaction_claim);
+        assert_eq!(
+            padding.slots.len(),
+            21 * MAX_COMPONENT_INSTANCES + 6,
+            "twenty opcodes, the big memory table, five optional builtins and the Poseidon context"
+        );
+
+        let sampled_values = &proof.stark_proof.sampled_values;
+        let padded = padding.pad_sampled_values(sampled_values);
+
+        // dropping the columns of the inactive slots gives back the sampled values
+        for tree_index in 1..=2 {
+            let mut is_padding = vec![false; padded[tree_index].len()];
+            for slot in padding.slots.iter().filter(|slot| !slot.is_active) {
+                for location in slot.locations.iter() {
+                    if location.tree_index == tree_index {
+                        is_padding[location.col_start..location.col_end].fill(true);
+                    }
+                }
+            }
+            let unpadded = padded[tree_index]
+                .iter()
+                .zip_eq(is_padding.iter())
+                .filter(|(_, is_padding)| !**is_padding)
+                .map(|(column, _)| column.clone())
+                .collect_vec();
+            assert_eq!(unpadded, sampled_values[tree_index]);
+        }
+        assert_eq!(padded[0], sampled_values[0]);
+        assert_eq!(padded[3], sampled_values[3]);
+    }
+}
@@ -0,0 +1,255 @@
+use crate::{ParamsMismatch, VerificationError};
+use cairo_air::air::{CairoClaim, CairoProof, MemorySection, PublicSegmentRanges};
+use itertools::Itertools;
+use serde::Deserialize;
+use std::{collections::HashMap, path::Path};
+use stwo::core::pcs::PcsConfig;
+use stwo::core::vcs::poseidon31_merkle::Poseidon31MerkleHasher;
+use stwo_cairo_common::memory::LARGE_MEMORY_VALUE_ID_BASE;
+
+/// The builtins of the layout that the verifier supports, in the order of the public segments,
+/// named as in a compiled Cairo program. The circuit allocates every public segment, so a program
+/// must declare all of them, even those that it leaves empty.
+pub const CAIRO_VERIFIER_BUILTINS: [&str; 11] = [
+    "output",
+    "pedersen",
+    "range_check",
+    "ecdsa",
+    "bitwise",
+    "ec_op",
+    "keccak",
+    "poseidon",
+    "range_check96",
+    "add_mod",
+    "mul_mod",
+];
+
+/// How the circuit treats the program memory section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgramMode {
+    /// The program is a constant of the circuit, pre-absorbed into the initial channel.
+    Hardcoded,
+    /// The program is a witness, and its hash is a public input. The circuit then accepts any
+    /// program of the same length, and the final verifier checks the program identity.
+    Hashed,
+}
+
+/// The parameters that the Cairo verifier circuit is generated for, namely the program of the
+/// Cairo proof, the layout of its builtin segments, and the PCS config of the prover.
+///
+/// When the Cairo program runs as a task of a bootloader, as it does for the Cairo-to-Cairo
+/// recursive verifier, the program here is the bootloader, while the task is only visible through
+/// the output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CairoVerifierParams {
+    /// The program memory section, as the ids and the values of the memory cells from address 1.
+    /// In the hashed mode, only its length is part of the circuit.
+    pub program: MemorySection,
+    /// The builtins of the program, which are the present public segments.
+    pub builtins: Vec<String>,
+    pub program_mode: ProgramMode,
+    /// The proof of work and FRI parameters, which fix the number of queries and the shape of the
+    /// FRI layers in the circuit. A proof with another config is rejected.
+    pub pcs_config: PcsConfig,
+}
+
+#[derive(Deserialize)]
+struct CompiledProgram {
+    data: Vec<String>,
+    builtins: Vec<String>,
+}
+
+impl CairoVerifierParams {
+    /// Take the parameters from the claim and the config of a Cairo proof.
+    pub fn from_proof(proof: &CairoProof<Poseidon31MerkleHasher>) -> Self {
+        let claim = &proof.claim;
+        Self {
+            program: claim.public_data.public_memory.program.clone(),
+            builtins: segment_layout(&claim.public_data.public_memory.public_segments),
+            program_mode: ProgramMode::Hardcoded,
+            pcs_config: proof.stark_proof.config,
+        }
+    }
+
+    /// Switch to the hashed mode, where the program is a witness whose hash is a public input.
+    pub fn with_program_hash(mut self) -> Self {
+        self.program_mode = ProgramMode::Hashed;
+        self
+    }
+
+    /// Load the parameters from a compiled Cairo program, for proofs made with `pcs_config`.
+    ///
+    /// The memory ids of the program are assigned the way the prover does when the program is
+    /// loaded from address 1: each distinct value receives the next id in the order of first
+    /// appearance, with values below 2^72 counted from 0 and the other values counted from
+    /// `LARGE_MEMORY_VALUE_ID_BASE`.
+    pub fn load_compiled_program(path: &Path, pcs_config: PcsConfig) -> std::io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        let compiled: CompiledProgram = serde_json::from_reader(file)?;
+
+        let values = compiled
+            .data
+            .iter()
+            .map(|word| parse_felt252(word))
+            .collect::<std::io::Result<Vec<_>>>()?;
+
+        let mut small_ids = HashMap::new();
+        let mut big_ids = HashMap::new();
+        let program = values
+            .into_iter()
+            .map(|value| {
+                let id = if value[3..].iter().all(|limb| *limb == 0) && value[2] < 1 << 8 {
+                    let next = small_ids.len() as u32;
+                    *small_ids.entry(value).or_insert(next)
+                } else {
+                    let next = LARGE_MEMORY_VALUE_ID_BASE + big_ids.len() as u32;
+                    *big_ids.entry(value).or_insert(next)
+                };
+                (id, value)
+            })
+            .collect();
+
+        Ok(Self {
+            program,
+            builtins: std::iter::once("output".to_string())
+                .chain(
+                    compiled
+                        .builtins
+                        .into_iter()
+                        .filter(|name| name != "output"),
+                )
+                .collect(),
+            program_mode: ProgramMode::Hardcoded,
+            pcs_config,
+        })
+    }
+
+    /// Write the program and the builtins as a compiled Cairo program, which
+    /// `load_compiled_program` reads back. Only the values of the program are written, so the
+    /// memory ids are assigned again when it is loaded.
+    pub fn write_compiled_program(&self, path: &Path) -> std::io::Result<()> {
+        let data = self
+            .program
+            .iter()
+            .map(|(_, value)| {
+                let hex = value
+                    .iter()
+                    .rev()
+                    .map(|limb| format!("{limb:08x}"))
+                    .collect::<String>();
+                format!("0x{hex}")
+            })
+            .collect::<Vec<_>>();
+        let compiled = serde_json::json!({
+            "data": data,
+            "builtins": self.builtins.iter().filter(|name| *name != "output").collect_vec(),
+        });
+        std::fs::write(path, compiled.to_string())
+    }
+
+    /// Check that the claim of a Cairo proof matches the parameters, and that the layout is the
+    /// one supported by the verifier.
+    pub fn verify_claim(&self, claim: &CairoClaim) {
+        if let Err(err) = self.check_claim(claim) {
+            panic!("{err}");
+        }
+    }
+
+    /// The same as `verify_claim`, but returning the mismatch instead of panicking.
+    pub fn check_claim(&self, claim: &CairoClaim) -> Result<(), VerificationError> {
+        if self.builtins != CAIRO_VERIFIER_BUILTINS {
+            return Err(VerificationError::Params(ParamsMismatch::Builtins));
+        }
+        if segment_layout(&claim.public_data.public_memory.public_segments) != self.builtins {
+            return Err(VerificationError::Params(ParamsMismatch::SegmentLayout));
+        }
+        let program = &claim.public_data.public_memory.program;
+        match self.program_mode {
+            ProgramMode::Hardcoded if *program != self.program => {
+                Err(VerificationError::Params(ParamsMismatch::Program))
+            }
+            ProgramMode::Hashed if program.len() != self.program.len() => {
+                Err(VerificationError::Params(ParamsMismatch::ProgramLength))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The names of the present public segments, in order.
+fn segment_layout(segments: &PublicSegmentRanges) -> Vec<String> {
+    let optional_segments = [
+        &segments.pedersen,
+        &segments.range_check_128,
+        &segments.ecdsa,
+        &segments.bitwise,
+        &segments.ec_op,
+        &segments.keccak,
+        &segments.poseidon,
+        &segments.range_check_96,
+        &segments.add_mod,
+        &segments.mul_mod,
+    ];
+    std::iter::once(CAIRO_VERIFIER_BUILTINS[0])
+        .chain(
+            CAIRO_VERIFIER_BUILTINS[1..]
+                .iter()
+                .zip_eq(optional_segments)
+                .filter(|(_, segment)| segment.is_some())
+                .map(|(name, _)| *name),
+        )
+        .map(str::to_string)
+        .collect()
+}
+
+/// Parse a field element given in hexadecimal into its eight little-endian 32-bit limbs.
+fn parse_felt252(word: &str) -> std::io::Result<[u32; 8]> {
+    let invalid = || {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("invalid field element in the program: {word}"),
+        )
+    };
+
+    let hex = word.strip_prefix("0x").ok_or_else(invalid)?;
+    if hex.is_empty() || hex.len() > 64 {
+        return Err(invalid());
+    }
+    let hex = format!("{hex:0>64}");
+
+    let mut limbs = [0u32; 8];
+    for (i, limb) in limbs.iter_mut().enumerate() {
+        let start = 64 - 8 * (i + 1);
+        *limb = u32::from_str_radix(&hex[start..start + 8], 16).map_err(|_| invalid())?;
+    }
+    Ok(limbs)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cairo_air::utils::{deserialize_proof_from_file, ProofFormat};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_params_from_proof() {
+        let manifest_dir = env!("CARGO_MANIFEST_DIR");
+        let data_path = PathBuf::from(manifest_dir)
+            .parent()
+            .unwrap()
+            .join("test_data")
+            .join("recursive_proof.bin.bz");
+
+        let proof = deserialize_proof_from_file(&data_path, ProofFormat::Binary).unwrap();
+        let params = CairoVerifierParams::from_proof(&proof);
+        params.verify_claim(&proof.claim);
+
+        // the ids of a compiled program follow the assignment of the prover
+        let path = std::env::temp_dir().join("cairo_verifier_params_test_program.json");
+        params.write_compiled_program(&path).unwrap();
+
+        let loaded =
+            CairoVerifierParams::load_compiled_program(&path, proof.stark_proof.config).unwrap();
+        assert_eq!(loaded, params);
+    }
+}
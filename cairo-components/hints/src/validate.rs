@@ -0,0 +1,686 @@
+use crate::{MAX_COMPONENT_INSTANCES, MAX_OUTPUT_LEN};
+use cairo_air::{
+    air::{CairoClaim, SegmentRange},
+    CairoProof, PreProcessedTraceVariant,
+};
+use num_traits::One;
+use std::{collections::HashMap, fmt};
+use stwo::{
+    core::{
+        fields::m31::BaseField, pcs::PcsConfig, vcs::poseidon31_merkle::Poseidon31MerkleHasher,
+    },
+    prover::backend::simd::m31::LOG_N_LANES,
+};
+use stwo_cairo_common::{
+    builtins::{
+        ADD_MOD_MEMORY_CELLS, BITWISE_MEMORY_CELLS, MUL_MOD_MEMORY_CELLS, POSEIDON_MEMORY_CELLS,
+        RANGE_CHECK_MEMORY_CELLS,
+    },
+    memory::LARGE_MEMORY_VALUE_ID_BASE,
+    preprocessed_columns::preprocessed_trace::MAX_SEQUENCE_LOG_SIZE,
+    prover_types::cpu::PRIME,
+};
+
+/// The assumption of the Cairo-to-Plonk verifier that an incompatibility violates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assumption {
+    PcsConfig,
+    Trees,
+    PreprocessedTrace,
+    PublicSegments,
+    Builtins,
+    Contexts,
+    ComponentInstances,
+    LogSizes,
+    Output,
+    Program,
+    CasmStates,
+    InteractionClaim,
+    RelationUses,
+}
+
+/// A reason why the Cairo-to-Plonk verifier cannot verify a Cairo proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Incompatibility {
+    pub assumption: Assumption,
+    pub explanation: String,
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {}", self.assumption, self.explanation)
+    }
+}
+
+/// The shape of a Cairo proof that the Cairo-to-Plonk verifier accepts, which is what the
+/// generated circuit depends on.
+#[derive(Debug, Clone)]
+pub struct ProofShapeReport {
+    pub pcs_config: PcsConfig,
+    /// The number of instances of each opcode component, including the absent ones.
+    pub opcode_instances: Vec<(&'static str, usize)>,
+    /// The optional builtins that are present, in addition to `range_check_128`.
+    pub optional_builtins: Vec<&'static str>,
+    pub n_big_memory_instances: usize,
+    pub max_trace_log_size: u32,
+    pub program_len: usize,
+    pub output_len: usize,
+}
+
+/// Check up front that a Cairo proof satisfies the assumptions of the Cairo-to-Plonk verifier,
+/// which otherwise fail as panics during the synthesis of the circuit. This does not verify the
+/// proof itself.
+pub fn validate_cairo_proof_for_recursion(
+    proof: &CairoProof<Poseidon31MerkleHasher>,
+) -> Result<ProofShapeReport, Vec<Incompatibility>> {
+    let mut errors = Errors::default();
+    let claim = &proof.claim;
+
+    check_pcs_config(proof, &mut errors);
+    check_trees(proof, &mut errors);
+    check_preprocessed_trace(proof, &mut errors);
+    check_claim(claim, &mut errors);
+    let opcode_instances = check_component_instances(proof, &mut errors);
+
+    if !errors.0.is_empty() {
+        return Err(errors.0);
+    }
+
+    Ok(ProofShapeReport {
+        pcs_config: proof.stark_proof.config,
+        opcode_instances,
+        optional_builtins: optional_builtins(claim),
+        n_big_memory_instances: claim.memory_id_to_value.big_log_sizes.len(),
+        max_trace_log_size: claim.log_sizes()[1]
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or_default(),
+        program_len: claim.public_data.public_memory.program.len(),
+        output_len: claim.public_data.public_memory.output.len(),
+    })
+}
+
+/// Check that the claim of a Cairo proof satisfies the formality checks of the Cairo-to-Plonk
+/// verifier, which are the part of `validate_cairo_proof_for_recursion` that only depends on the
+/// claim.
+pub fn validate_claim_for_recursion(claim: &CairoClaim) -> Result<(), Vec<Incompatibility>> {
+    let mut errors = Errors::default();
+    check_claim(claim, &mut errors);
+    if !errors.0.is_empty() {
+        return Err(errors.0);
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct Errors(Vec<Incompatibility>);
+
+impl Errors {
+    fn check(
+        &mut self,
+        condition: bool,
+        assumption: Assumption,
+        explanation: impl FnOnce() -> String,
+    ) {
+        if !condition {
+            self.0.push(Incompatibility {
+                assumption,
+                explanation: explanation(),
+            });
+        }
+    }
+}
+
+fn check_pcs_config(proof: &CairoProof<Poseidon31MerkleHasher>, errors: &mut Errors) {
+    let config = &proof.stark_proof.config;
+    errors.check(proof.channel_salt.is_none(), Assumption::PcsConfig, || {
+        "the channel is salted, but the circuit starts from an unsalted channel".to_string()
+    });
+    errors.check(config.pow_bits < 31, Assumption::PcsConfig, || {
+        format!(
+            "pow_bits is {}, but the circuit checks the proof of work within a single M31 element",
+            config.pow_bits
+        )
+    });
+    let n_coeffs = proof.stark_proof.fri_proof.last_layer_poly.coeffs.len();
+    let log_last_layer_degree_bound = config.fri_config.log_last_layer_degree_bound;
+    errors.check(
+        1usize.checked_shl(log_last_layer_degree_bound) == Some(n_coeffs),
+        Assumption::PcsConfig,
+        || {
+            format!(
+                "the last layer has {n_coeffs} coefficients, but log_last_layer_degree_bound is \
+                 {log_last_layer_degree_bound}"
+            )
+        },
+    );
+}
+
+fn check_trees(proof: &CairoProof<Poseidon31MerkleHasher>, errors: &mut Errors) {
+    // the preprocessed trace, the trace, the interaction trace, and the composition polynomial
+    let stark_proof = &proof.stark_proof;
+    for (name, n_trees) in [
+        ("commitments", stark_proof.commitments.len()),
+        ("sampled values", stark_proof.sampled_values.len()),
+        ("queried values", stark_proof.queried_values.len()),
+        ("decommitments", stark_proof.decommitments.len()),
+    ] {
+        errors.check(n_trees == 4, Assumption::Trees, || {
+            format!("the proof has {name} for {n_trees} trees, but the circuit expects 4")
+        });
+    }
+}
+
+fn check_preprocessed_trace(proof: &CairoProof<Poseidon31MerkleHasher>, errors: &mut Errors) {
+    let n_columns = PreProcessedTraceVariant::CanonicalWithoutPedersen
+        .to_preprocessed_trace()
+        .ids()
+        .len();
+    let Some(sampled_values) = proof.stark_proof.sampled_values.first() else {
+        return;
+    };
+    errors.check(
+        sampled_values.len() == n_columns,
+        Assumption::PreprocessedTrace,
+        || {
+            format!(
+                "the proof has {} preprocessed columns, but `canonical_without_pedersen` has \
+                 {n_columns}",
+                sampled_values.len()
+            )
+        },
+    );
+    for (i, column) in sampled_values.iter().enumerate() {
+        errors.check(column.len() <= 1, Assumption::PreprocessedTrace, || {
+            format!(
+                "preprocessed column {i} is sampled at {} points, but at most one is supported",
+                column.len()
+            )
+        });
+    }
+}
+
+fn check_builtin_segment(
+    name: &'static str,
+    segment: Option<&SegmentRange>,
+    builtin_claim: Option<(u32, u32)>,
+    n_cells: usize,
+    errors: &mut Errors,
+) {
+    // a missing segment is reported on its own
+    let Some(segment) = segment else {
+        return;
+    };
+    let Some((segment_start, log_size)) = builtin_claim else {
+        errors.check(segment.is_empty(), Assumption::Builtins, || {
+            format!("the {name} segment is not empty, but there is no {name} component")
+        });
+        return;
+    };
+
+    errors.check(!segment.is_empty(), Assumption::Builtins, || {
+        format!("the {name} segment is empty, but there is a {name} component")
+    });
+    let start_ptr = segment.start_ptr.value;
+    let stop_ptr = segment.stop_ptr.value;
+    let segment_end = segment_start as u64 + ((n_cells as u64) << log_size);
+    let well_formed = start_ptr == segment_start
+        && start_ptr <= stop_ptr
+        && (stop_ptr - start_ptr) % n_cells as u32 == 0
+        && stop_ptr as u64 <= segment_end
+        && segment_end < 1 << 31;
+    errors.check(well_formed, Assumption::Builtins, || {
+        format!(
+            "the {name} segment [{start_ptr}, {stop_ptr}) does not consist of whole instances \
+             within the component that starts at {segment_start} with log size {log_size}"
+        )
+    });
+}
+
+fn check_claim(claim: &CairoClaim, errors: &mut Errors) {
+    check_builtins(claim, errors);
+    check_contexts(claim, errors);
+    check_log_sizes(claim, errors);
+    check_public_memory(claim, errors);
+    check_relation_uses(claim, errors);
+}
+
+fn check_builtins(claim: &CairoClaim, errors: &mut Errors) {
+    let segments = &claim.public_data.public_memory.public_segments;
+    let builtins = &claim.builtins;
+
+    // the circuit allocates every public segment
+    let optional_segments = [
+        ("pedersen", &segments.pedersen),
+        ("range_check_128", &segments.range_check_128),
+        ("ecdsa", &segments.ecdsa),
+        ("bitwise", &segments.bitwise),
+        ("ec_op", &segments.ec_op),
+        ("keccak", &segments.keccak),
+        ("poseidon", &segments.poseidon),
+        ("range_check_96", &segments.range_check_96),
+        ("add_mod", &segments.add_mod),
+        ("mul_mod", &segments.mul_mod),
+    ];
+    for (name, segment) in optional_segments.iter() {
+        errors.check(segment.is_some(), Assumption::PublicSegments, || {
+            format!("the proof has no {name} segment, but the circuit requires all the segments")
+        });
+    }
+    // the preprocessed trace has no Pedersen points table, and the other builtins have no components
+    for (name, unsupported) in [
+        ("pedersen", &segments.pedersen),
+        ("ecdsa", &segments.ecdsa),
+        ("ec_op", &segments.ec_op),
+        ("keccak", &segments.keccak),
+    ] {
+        errors.check(
+            unsupported.iter().all(|segment| segment.is_empty()),
+            Assumption::Builtins,
+            || format!("the {name} builtin is used, but the circuit does not support it"),
+        );
+    }
+    errors.check(
+        builtins.pedersen_builtin.is_none(),
+        Assumption::Builtins,
+        || "the proof has a pedersen component, but the circuit does not support it".to_string(),
+    );
+
+    errors.check(
+        segments.output.start_ptr.value <= segments.output.stop_ptr.value
+            && segments.output.stop_ptr.value < 1 << 31,
+        Assumption::PublicSegments,
+        || "the output segment is malformed".to_string(),
+    );
+
+    match &builtins.range_check_128_builtin {
+        Some(range_check_128) => check_builtin_segment(
+            "range_check_128",
+            segments.range_check_128.as_ref(),
+            Some((
+                range_check_128.range_check_builtin_segment_start,
+                range_check_128.log_size,
+            )),
+            RANGE_CHECK_MEMORY_CELLS,
+            errors,
+        ),
+        None => errors.check(false, Assumption::Builtins, || {
+            "the proof has no range_check_128 component, which the circuit requires".to_string()
+        }),
+    }
+    check_builtin_segment(
+        "add_mod",
+        segments.add_mod.as_ref(),
+        builtins
+            .add_mod_builtin
+            .map(|claim| (claim.add_mod_builtin_segment_start, claim.log_size)),
+        ADD_MOD_MEMORY_CELLS,
+        errors,
+    );
+    check_builtin_segment(
+        "bitwise",
+        segments.bitwise.as_ref(),
+        builtins
+            .bitwise_builtin
+            .map(|claim| (claim.bitwise_builtin_segment_start, claim.log_size)),
+        BITWISE_MEMORY_CELLS,
+        errors,
+    );
+    check_builtin_segment(
+        "mul_mod",
+        segments.mul_mod.as_ref(),
+        builtins
+            .mul_mod_builtin
+            .map(|claim| (claim.mul_mod_builtin_segment_start, claim.log_size)),
+        MUL_MOD_MEMORY_CELLS,
+        errors,
+    );
+    check_builtin_segment(
+        "poseidon",
+        segments.poseidon.as_ref(),
+        builtins
+            .poseidon_builtin
+            .map(|claim| (claim.poseidon_builtin_segment_start, claim.log_size)),
+        POSEIDON_MEMORY_CELLS,
+        errors,
+    );
+    check_builtin_segment(
+        "range_check_96",
+        segments.range_check_96.as_ref(),
+        builtins
+            .range_check_96_builtin
+            .map(|claim| (claim.range_check96_builtin_segment_start, claim.log_size)),
+        RANGE_CHECK_MEMORY_CELLS,
+        errors,
+    );
+}
+
+/// The optional builtins that are present, in addition to `range_check_128`.
+fn optional_builtins(claim: &CairoClaim) -> Vec<&'static str> {
+    let builtins = &claim.builtins;
+    [
+        ("add_mod", builtins.add_mod_builtin.is_some()),
+        ("bitwise", builtins.bitwise_builtin.is_some()),
+        ("mul_mod", builtins.mul_mod_builtin.is_some()),
+        ("poseidon", builtins.poseidon_builtin.is_some()),
+        ("range_check_96", builtins.range_check_96_builtin.is_some()),
+    ]
+    .into_iter()
+    .filter(|(_, is_present)| *is_present)
+    .map(|(name, _)| name)
+    .collect()
+}
+
+fn check_contexts(claim: &CairoClaim, errors: &mut Errors) {
+    errors.check(
+        claim.blake_context.claim.is_some(),
+        Assumption::Contexts,
+        || "the proof has no blake context, which the circuit requires".to_string(),
+    );
+    errors.check(
+        claim.pedersen_context.claim.is_none(),
+        Assumption::Contexts,
+        || "the proof has a pedersen context, but the circuit does not support it".to_string(),
+    );
+    errors.check(
+        claim.poseidon_context.claim.is_some() == claim.builtins.poseidon_builtin.is_some(),
+        Assumption::Contexts,
+        || "the poseidon context must be present exactly when the poseidon builtin is".to_string(),
+    );
+}
+
+fn check_component_instances(
+    proof: &CairoProof<Poseidon31MerkleHasher>,
+    errors: &mut Errors,
+) -> Vec<(&'static str, usize)> {
+    let claim = &proof.claim;
+    let interaction_claim = &proof.interaction_claim;
+
+    let mut opcode_instances = vec![];
+    macro_rules! check_opcode {
+        ($($name:ident),*) => {$(
+            let n_instances = claim.opcodes.$name.len();
+            errors.check(
+                n_instances <= MAX_COMPONENT_INSTANCES,
+                Assumption::ComponentInstances,
+                || {
+                    format!(
+                        "the {} opcode has {n_instances} instances, but at most \
+                         {MAX_COMPONENT_INSTANCES} are supported",
+                        stringify!($name)
+                    )
+                },
+            );
+            errors.check(
+                interaction_claim.opcodes.$name.len() == n_instances,
+                Assumption::InteractionClaim,
+                || format!("the {} opcode has a different number of claimed sums", stringify!($name)),
+            );
+            opcode_instances.push((stringify!($name), n_instances));
+        )*};
+    }
+    check_opcode!(
+        add,
+        add_small,
+        add_ap,
+        assert_eq,
+        assert_eq_imm,
+        assert_eq_double_deref,
+        blake,
+        call,
+        call_rel_imm,
+        generic,
+        jnz,
+        jnz_taken,
+        jump,
+        jump_double_deref,
+        jump_rel,
+        jump_rel_imm,
+        mul,
+        mul_small,
+        qm31,
+        ret
+    );
+
+    let n_big = claim.memory_id_to_value.big_log_sizes.len();
+    errors.check(
+        n_big <= MAX_COMPONENT_INSTANCES,
+        Assumption::ComponentInstances,
+        || {
+            format!(
+                "the big memory table has {n_big} instances, but at most \
+                 {MAX_COMPONENT_INSTANCES} are supported"
+            )
+        },
+    );
+    errors.check(
+        interaction_claim.memory_id_to_value.big_claimed_sums.len() == n_big,
+        Assumption::InteractionClaim,
+        || "the big memory table has a different number of claimed sums".to_string(),
+    );
+
+    let builtins = &claim.builtins;
+    let interaction_builtins = &interaction_claim.builtins;
+    for (name, claimed, has_sum) in [
+        (
+            "add_mod",
+            builtins.add_mod_builtin.is_some(),
+            interaction_builtins.add_mod_builtin.is_some(),
+        ),
+        (
+            "bitwise",
+            builtins.bitwise_builtin.is_some(),
+            interaction_builtins.bitwise_builtin.is_some(),
+        ),
+        (
+            "mul_mod",
+            builtins.mul_mod_builtin.is_some(),
+            interaction_builtins.mul_mod_builtin.is_some(),
+        ),
+        (
+            "pedersen",
+            builtins.pedersen_builtin.is_some(),
+            interaction_builtins.pedersen_builtin.is_some(),
+        ),
+        (
+            "poseidon",
+            builtins.poseidon_builtin.is_some(),
+            interaction_builtins.poseidon_builtin.is_some(),
+        ),
+        (
+            "range_check_96",
+            builtins.range_check_96_builtin.is_some(),
+            interaction_builtins.range_check_96_builtin.is_some(),
+        ),
+        (
+            "range_check_128",
+            builtins.range_check_128_builtin.is_some(),
+            interaction_builtins.range_check_128_builtin.is_some(),
+        ),
+        (
+            "blake context",
+            claim.blake_context.claim.is_some(),
+            interaction_claim.blake_context.claim.is_some(),
+        ),
+        (
+            "pedersen context",
+            claim.pedersen_context.claim.is_some(),
+            interaction_claim.pedersen_context.claim.is_some(),
+        ),
+        (
+            "poseidon context",
+            claim.poseidon_context.claim.is_some(),
+            interaction_claim.poseidon_context.claim.is_some(),
+        ),
+    ] {
+        errors.check(claimed == has_sum, Assumption::InteractionClaim, || {
+            format!("the {name} component and its claimed sum are not both present")
+        });
+    }
+
+    opcode_instances
+}
+
+fn check_log_sizes(claim: &CairoClaim, errors: &mut Errors) {
+    let log_sizes = claim.log_sizes();
+    for log_size in log_sizes[1].iter() {
+        errors.check(
+            (LOG_N_LANES..=MAX_SEQUENCE_LOG_SIZE).contains(log_size),
+            Assumption::LogSizes,
+            || {
+                format!(
+                    "a component has log size {log_size}, but the circuit supports log sizes \
+                     from {LOG_N_LANES} to {MAX_SEQUENCE_LOG_SIZE}"
+                )
+            },
+        );
+    }
+}
+
+fn check_public_memory(claim: &CairoClaim, errors: &mut Errors) {
+    let public_memory = &claim.public_data.public_memory;
+
+    let output_len = public_memory.output.len();
+    errors.check(output_len <= MAX_OUTPUT_LEN, Assumption::Output, || {
+        format!("the output has {output_len} entries, but at most {MAX_OUTPUT_LEN} are supported")
+    });
+    let output_segment = &public_memory.public_segments.output;
+    let segment_len = output_segment
+        .stop_ptr
+        .value
+        .checked_sub(output_segment.start_ptr.value);
+    errors.check(
+        segment_len == Some(output_len as u32),
+        Assumption::Output,
+        || format!("the output has {output_len} entries, but does not fill the output segment"),
+    );
+
+    // the instructions that push the builtin pointers and call the main function, and the final
+    // `jmp rel 0`
+    let program = &public_memory.program;
+    let n_builtins = public_memory.public_segments.present_segments().len() as u32;
+    let entries: [(usize, [u32; 8]); 5] = [
+        (0, [0x7fff7fff, 0x4078001, 0, 0, 0, 0, 0, 0]),
+        (1, [n_builtins, 0, 0, 0, 0, 0, 0, 0]),
+        (2, [0x80018000, 0x11048001, 0, 0, 0, 0, 0, 0]),
+        (4, [0x7fff7fff, 0x1078001, 0, 0, 0, 0, 0, 0]),
+        (5, [0, 0, 0, 0, 0, 0, 0, 0]),
+    ];
+    for (index, expected) in entries {
+        errors.check(
+            program.get(index).map(|(_, value)| *value) == Some(expected),
+            Assumption::Program,
+            || format!("the program entry at index {index} is not {expected:x?}"),
+        );
+    }
+
+    let initial_state = &claim.public_data.initial_state;
+    let final_state = &claim.public_data.final_state;
+    for (holds, explanation) in [
+        (initial_state.pc == BaseField::one(), "initial_pc must be 1"),
+        (
+            initial_state.pc + BaseField::from(2) < initial_state.ap,
+            "initial_pc + 2 must be less than initial_ap",
+        ),
+        (
+            initial_state.fp == final_state.fp,
+            "initial_fp must equal final_fp",
+        ),
+        (
+            initial_state.fp == initial_state.ap,
+            "initial_fp must equal initial_ap",
+        ),
+        (final_state.pc == BaseField::from(5), "final_pc must be 5"),
+        (
+            initial_state.ap <= final_state.ap,
+            "initial_ap must not exceed final_ap",
+        ),
+    ] {
+        errors.check(holds, Assumption::CasmStates, || explanation.to_string());
+    }
+}
+
+fn check_relation_uses(claim: &CairoClaim, errors: &mut Errors) {
+    let mut relation_uses = HashMap::<&'static str, u64>::new();
+    claim.accumulate_relation_uses(&mut relation_uses);
+    for (relation, count) in relation_uses.iter() {
+        errors.check(*count < PRIME as u64, Assumption::RelationUses, || {
+            format!("the {relation} relation is used {count} times, which overflows M31")
+        });
+    }
+
+    let n_big_ids = claim
+        .memory_id_to_value
+        .big_log_sizes
+        .iter()
+        .map(|log_size| 1u64.checked_shl(*log_size).unwrap_or(u64::MAX))
+        .fold(0u64, u64::saturating_add);
+    errors.check(
+        LARGE_MEMORY_VALUE_ID_BASE as u64 + n_big_ids <= PRIME as u64,
+        Assumption::RelationUses,
+        || "the ids of the big memory values overflow M31".to_string(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cairo_air::utils::{deserialize_proof_from_file, ProofFormat};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_validate_cairo_proof_for_recursion() {
+        let manifest_dir = env!("CARGO_MANIFEST_DIR");
+        let data_path = PathBuf::from(manifest_dir)
+            .parent()
+            .unwrap()
+            .join("test_data")
+            .join("recursive_proof.bin.bz");
+
+        let mut proof = deserialize_proof_from_file(&data_path, ProofFormat::Binary).unwrap();
+        let report = validate_cairo_proof_for_recursion(&proof).unwrap();
+        assert_eq!(report.output_len, 5);
+
+        proof.stark_proof.config.pow_bits = 31;
+        proof.claim.public_data.final_state.pc = BaseField::from(6);
+        let incompatibilities = validate_cairo_proof_for_recursion(&proof).unwrap_err();
+        assert_eq!(
+            incompatibilities
+                .iter()
+                .map(|incompatibility| incompatibility.assumption)
+                .collect::<Vec<_>>(),
+            vec![Assumption::PcsConfig, Assumption::CasmStates]
+        );
+    }
+
+    #[test]
+    fn test_validate_claim_for_recursion() {
+        let manifest_dir = env!("CARGO_MANIFEST_DIR");
+        let data_path = PathBuf::from(manifest_dir)
+            .parent()
+            .unwrap()
+            .join("test_data")
+            .join("recursive_proof.bin.bz");
+
+        let mut proof = deserialize_proof_from_file(&data_path, ProofFormat::Binary).unwrap();
+        validate_claim_for_recursion(&proof.claim).unwrap();
+
+        // a degree bound that overflows the shift is reported rather than panicking
+        proof
+            .stark_proof
+            .config
+            .fri_config
+            .log_last_layer_degree_bound = 70;
+        let incompatibilities = validate_cairo_proof_for_recursion(&proof).unwrap_err();
+        assert_eq!(incompatibilities[0].assumption, Assumption::PcsConfig);
+        // which is not a formality of the claim
+        validate_claim_for_recursion(&proof.claim).unwrap();
+
+        proof.claim.public_data.initial_state.fp += BaseField::one();
+        let incompatibilities = validate_claim_for_recursion(&proof.claim).unwrap_err();
+        assert!(incompatibilities
+            .iter()
+            .all(|incompatibility| incompatibility.assumption == Assumption::CasmStates));
+    }
+}
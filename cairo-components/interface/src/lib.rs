@@ -1,15 +1,22 @@
-use cairo_air::CairoProof;
+pub mod output;
+pub use output::*;
+pub mod native;
+pub use native::*;
+
+use cairo_air::{air::MemorySection, CairoProof};
 use cairo_plonk_dsl_answer::AnswerResults;
 use cairo_plonk_dsl_composition::CairoCompositionCheck;
 use cairo_plonk_dsl_data_structures::CairoProofVar;
 use cairo_plonk_dsl_decommitment::CairoDecommitmentResultsVar;
 use cairo_plonk_dsl_fiat_shamir::CairoFiatShamirResults;
 use cairo_plonk_dsl_folding::FoldingResults;
+pub use cairo_plonk_dsl_hints::validate::*;
 use cairo_plonk_dsl_hints::{
     folding::CairoFoldingHints, AnswerHints, CairoDecommitmentHints, CairoFiatShamirHints,
 };
+pub use cairo_plonk_dsl_hints::{CairoVerifierParams, ProgramMode, MAX_OUTPUT_LEN};
 use circle_plonk_dsl_constraint_system::var::{AllocVar, Var};
-use circle_plonk_dsl_primitives::{ChannelVar, Poseidon2HalfVar};
+use circle_plonk_dsl_primitives::{ChannelVar, M31Var, Poseidon2HalfVar, QM31Var};
 use num_traits::One;
 use stwo::core::{
     channel::{Channel, Poseidon31Channel},
@@ -17,62 +24,89 @@ use stwo::core::{
     vcs::{poseidon31_hash::Poseidon31Hash, poseidon31_merkle::Poseidon31MerkleHasher},
 };
 
+/// The hash of the output section, which starts with the length of the section so that outputs of
+/// different lengths have different hashes.
 pub fn compute_output_hash(proof: &CairoProof<Poseidon31MerkleHasher>) -> Poseidon31Hash {
     let output = &proof.claim.public_data.public_memory.output;
-    assert_eq!(output.len(), 5);
+    assert!(output.len() <= MAX_OUTPUT_LEN);
 
     let mut channel = Poseidon31Channel::default();
+    channel.mix_u32s(&[output.len() as u32]);
     for (_, value) in output.iter() {
         channel.mix_u32s(value);
     }
     Poseidon31Hash(channel.digest())
 }
 
-pub fn verifier_input(output_hash: &Poseidon31Hash) -> Vec<(usize, QM31)> {
+/// The hash of the program values, which identifies the program in the hashed program mode.
+pub fn compute_program_hash(program: &MemorySection) -> Poseidon31Hash {
+    let mut channel = Poseidon31Channel::default();
+    for (_, value) in program.iter() {
+        channel.mix_u32s(value);
+    }
+    Poseidon31Hash(channel.digest())
+}
+
+/// The public input of the circuit. The program hash, which follows the output hash, is only
+/// present in the hashed program mode.
+pub fn verifier_input(
+    output_hash: &Poseidon31Hash,
+    program_hash: Option<&Poseidon31Hash>,
+) -> Vec<(usize, QM31)> {
     let h = output_hash.0;
-    vec![
+    let mut input = vec![
         (1, QM31::one()),
         (2, QM31::from_u32_unchecked(0, 1, 0, 0)),
         (3, QM31::from_u32_unchecked(0, 0, 1, 0)),
         (4, QM31::from_m31(h[0], h[1], h[2], h[3])),
         (5, QM31::from_m31(h[4], h[5], h[6], h[7])),
-    ]
+    ];
+    if let Some(program_hash) = program_hash {
+        let h = program_hash.0;
+        input.push((6, QM31::from_m31(h[0], h[1], h[2], h[3])));
+        input.push((7, QM31::from_m31(h[4], h[5], h[6], h[7])));
+    }
+    input
 }
 
+/// Check the output hash in the circuit, where the output section is padded to `MAX_OUTPUT_LEN`
+/// and only the active entries are mixed.
 pub fn verify_output_hash(expected_hash: Poseidon2HalfVar, proof_var: &CairoProofVar) {
-    assert_eq!(
-        proof_var
-            .claim
-            .public_data
-            .public_memory
-            .output
-            .values
-            .len(),
-        5
-    );
+    let cs = proof_var.cs();
+    let output = &proof_var.claim.public_data.public_memory.output;
 
-    let mut channel = ChannelVar::default(&proof_var.cs());
-    for value in proof_var
-        .claim
-        .public_data
-        .public_memory
-        .output
-        .values
-        .iter()
-    {
+    // the length is below 2^22, so it is the first limb of the felt of a u32
+    let mut channel = ChannelVar::default(&cs);
+    let zero = M31Var::zero(&cs);
+    channel.mix_one_felt(&QM31Var::from_m31(&output.length(), &zero, &zero, &zero));
+    for (value, is_active) in output.values.iter().zip(output.is_active.iter()) {
         for v in value.iter() {
-            v.mix_into(&mut channel);
+            v.conditional_mix_into(&mut channel, is_active);
         }
     }
     channel.digest.equalverify(&expected_hash);
 }
 
-pub fn verify_proof(proof: &CairoProof<Poseidon31MerkleHasher>, proof_var: &CairoProofVar) {
+/// Verify the Cairo proof in the circuit. The program hash must be given if and only if the
+/// params are in the hashed program mode.
+pub fn verify_proof(
+    params: &CairoVerifierParams,
+    proof: &CairoProof<Poseidon31MerkleHasher>,
+    proof_var: &CairoProofVar,
+    program_hash: Option<&Poseidon2HalfVar>,
+) {
     let cs = proof_var.cs();
 
-    let fiat_shamir_hints = CairoFiatShamirHints::new(proof);
+    let fiat_shamir_hints = CairoFiatShamirHints::new(proof, params);
     let proof_var = CairoProofVar::new_witness(&cs, proof);
-    let fiat_shamir_results = CairoFiatShamirResults::compute(&fiat_shamir_hints, &proof_var);
+    let fiat_shamir_results = match program_hash {
+        Some(program_hash) => CairoFiatShamirResults::compute_with_program_hash(
+            &fiat_shamir_hints,
+            &proof_var,
+            program_hash,
+        ),
+        None => CairoFiatShamirResults::compute(&fiat_shamir_hints, &proof_var),
+    };
 
     CairoCompositionCheck::compute(&fiat_shamir_results, &fiat_shamir_hints, &proof_var);
 
@@ -105,6 +139,7 @@ pub fn verify_proof(proof: &CairoProof<Poseidon31MerkleHasher>, proof_var: &Cair
 mod tests {
     use super::*;
     use cairo_air::utils::{deserialize_proof_from_file, ProofFormat};
+    use cairo_plonk_dsl_data_structures::public_data::MemorySectionVar;
     use circle_plonk_dsl_constraint_system::{var::AllocVar, ConstraintSystemRef};
     use std::path::PathBuf;
 
@@ -125,7 +160,35 @@ mod tests {
         let output_hash_var = Poseidon2HalfVar::new_public_input(&cs, &output_hash);
         let proof_var = CairoProofVar::new_witness(&cs, &proof);
         verify_output_hash(output_hash_var, &proof_var);
-        verify_proof(&proof, &proof_var);
+        let params = CairoVerifierParams::from_proof(&proof);
+        verify_proof(&params, &proof, &proof_var, None);
+
+        cs.pad();
+        cs.check_arithmetics();
+        cs.populate_logup_arguments();
+        cs.check_poseidon_invocations();
+    }
+
+    #[test]
+    fn test_program_hash() {
+        let manifest_dir = env!("CARGO_MANIFEST_DIR");
+        let data_path = PathBuf::from(manifest_dir)
+            .parent()
+            .unwrap()
+            .join("test_data")
+            .join("recursive_proof.bin.bz");
+
+        let proof = deserialize_proof_from_file(&data_path, ProofFormat::Binary).unwrap();
+        let program = &proof.claim.public_data.public_memory.program;
+
+        // the circuit hashes the program of the hashed mode as `compute_program_hash` does
+        let cs = ConstraintSystemRef::new();
+        let program_var = MemorySectionVar::new_witness(&cs, program);
+        let program_hash_var = CairoFiatShamirResults::program_hash(&program_var);
+        assert_eq!(
+            Poseidon31Hash(program_hash_var.value()),
+            compute_program_hash(program)
+        );
 
         cs.pad();
         cs.check_arithmetics();
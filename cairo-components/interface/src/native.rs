@@ -0,0 +1,243 @@
+use crate::{
+    compute_output_hash, compute_program_hash, validate_cairo_proof_for_recursion, Incompatibility,
+};
+use cairo_air::CairoProof;
+use cairo_plonk_dsl_hints::{
+    folding::CairoFoldingHints, AnswerHints, CairoCompositionHints, CairoDecommitmentHints,
+    CairoFiatShamirHints, CairoVerifierParams, ParamsMismatch, ProgramMode, VerificationError,
+};
+use std::fmt;
+use stwo::core::vcs::{poseidon31_hash::Poseidon31Hash, poseidon31_merkle::Poseidon31MerkleHasher};
+
+/// The reason why `verify_cairo_proof_like_circuit` rejects a proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NativeVerificationError {
+    /// The proof breaks assumptions of the circuit, so the circuit cannot even be synthesized.
+    Incompatible(Vec<Incompatibility>),
+    /// The output section does not hash to the output hash of the public input.
+    OutputHash,
+    /// The program section does not hash to the program hash of the public input.
+    ProgramHash,
+    /// The proof fails a check that the circuit enforces.
+    Proof(VerificationError),
+}
+
+impl fmt::Display for NativeVerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Incompatible(incompatibilities) => {
+                write!(f, "the proof is not compatible with the circuit:")?;
+                for incompatibility in incompatibilities.iter() {
+                    write!(f, "\n- {incompatibility}")?;
+                }
+                Ok(())
+            }
+            Self::OutputHash => write!(f, "the output does not match the output hash"),
+            Self::ProgramHash => write!(f, "the program does not match the program hash"),
+            Self::Proof(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for NativeVerificationError {}
+
+impl From<VerificationError> for NativeVerificationError {
+    fn from(err: VerificationError) -> Self {
+        Self::Proof(err)
+    }
+}
+
+/// Verify the Cairo proof natively, with the checks that the circuit of `verify_proof` and
+/// `verify_output_hash` enforces for this public input, and without panicking.
+///
+/// The checks run on the same hints as the circuit, so the decommitments are checked through the
+/// padded query results and the FRI layers through the folding of the circuit, rather than by the
+/// verifier of Stwo. When a proof passes here but not in the circuit, the circuit is at fault.
+pub fn verify_cairo_proof_like_circuit(
+    params: &CairoVerifierParams,
+    proof: &CairoProof<Poseidon31MerkleHasher>,
+    output_hash: &Poseidon31Hash,
+    program_hash: Option<&Poseidon31Hash>,
+) -> Result<(), NativeVerificationError> {
+    validate_cairo_proof_for_recursion(proof).map_err(NativeVerificationError::Incompatible)?;
+    params.check_claim(&proof.claim)?;
+
+    if compute_output_hash(proof) != *output_hash {
+        return Err(NativeVerificationError::OutputHash);
+    }
+    match (params.program_mode, program_hash) {
+        (ProgramMode::Hardcoded, None) => {}
+        (ProgramMode::Hashed, Some(program_hash)) => {
+            if compute_program_hash(&proof.claim.public_data.public_memory.program) != *program_hash
+            {
+                return Err(NativeVerificationError::ProgramHash);
+            }
+        }
+        _ => return Err(VerificationError::Params(ParamsMismatch::ProgramHashMode).into()),
+    }
+
+    let fiat_shamir_hints = CairoFiatShamirHints::try_new(proof, params)?;
+    verify_after_fiat_shamir(&fiat_shamir_hints, proof)?;
+
+    Ok(())
+}
+
+/// The checks of `verify_cairo_proof_like_circuit` that follow the Fiat-Shamir transform, which
+/// the proof has to pass with the randomness that `fiat_shamir_hints` has drawn.
+fn verify_after_fiat_shamir(
+    fiat_shamir_hints: &CairoFiatShamirHints,
+    proof: &CairoProof<Poseidon31MerkleHasher>,
+) -> Result<(), VerificationError> {
+    CairoCompositionHints::try_new(fiat_shamir_hints, proof)?;
+
+    let decommitment_hints = CairoDecommitmentHints::try_new(fiat_shamir_hints, proof)?;
+    let composition_log_size = fiat_shamir_hints.composition_log_size;
+    for (i, preprocessed_trace) in decommitment_hints.preprocessed_trace.iter().enumerate() {
+        let trees = [
+            (
+                "preprocessed trace",
+                preprocessed_trace.compute_column_hashes(),
+                &decommitment_hints.preprocessed_trace_decommitment_proofs[i],
+            ),
+            (
+                "trace",
+                decommitment_hints.trace[i].compute_hashes(&proof.claim),
+                &decommitment_hints.trace_decommitment_proofs[i],
+            ),
+            (
+                "interaction",
+                decommitment_hints.interaction[i].compute_hashes(&proof.claim),
+                &decommitment_hints.interaction_decommitment_proofs[i],
+            ),
+            (
+                "composition",
+                decommitment_hints.composition[i].compute_hashes(composition_log_size),
+                &decommitment_hints.composition_decommitment_proofs[i],
+            ),
+        ];
+        for (tree, column_hashes, decommitment_proof) in trees {
+            if !decommitment_proof.verify_column_hashes(&column_hashes) {
+                return Err(VerificationError::Decommitment { tree });
+            }
+        }
+    }
+
+    let answer_hints = AnswerHints::try_new(fiat_shamir_hints, proof)?;
+    CairoFoldingHints::try_new(fiat_shamir_hints, &answer_hints, proof)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cairo_air::utils::{deserialize_proof_from_file, ProofFormat};
+    use num_traits::One;
+    use std::path::PathBuf;
+    use stwo::core::fields::{m31::BaseField, qm31::SecureField};
+
+    fn load_proof() -> CairoProof<Poseidon31MerkleHasher> {
+        let manifest_dir = env!("CARGO_MANIFEST_DIR");
+        let data_path = PathBuf::from(manifest_dir)
+            .parent()
+            .unwrap()
+            .join("test_data")
+            .join("recursive_proof.bin.bz");
+
+        deserialize_proof_from_file(&data_path, ProofFormat::Binary).unwrap()
+    }
+
+    #[test]
+    fn test_verify_cairo_proof_like_circuit() {
+        let mut proof = load_proof();
+        let params = CairoVerifierParams::from_proof(&proof);
+        let output_hash = compute_output_hash(&proof);
+        verify_cairo_proof_like_circuit(&params, &proof, &output_hash, None).unwrap();
+
+        assert_eq!(
+            verify_cairo_proof_like_circuit(&params, &proof, &Poseidon31Hash::default(), None),
+            Err(NativeVerificationError::OutputHash)
+        );
+
+        proof.stark_proof.proof_of_work ^= 1;
+        assert_eq!(
+            verify_cairo_proof_like_circuit(&params, &proof, &output_hash, None),
+            Err(NativeVerificationError::Proof(
+                VerificationError::ProofOfWork
+            ))
+        );
+    }
+
+    #[test]
+    fn test_verify_cairo_proof_like_circuit_rejects_params() {
+        let proof = load_proof();
+        let params = CairoVerifierParams::from_proof(&proof);
+        let output_hash = compute_output_hash(&proof);
+
+        assert_eq!(
+            verify_cairo_proof_like_circuit(
+                &params,
+                &proof,
+                &output_hash,
+                Some(&Poseidon31Hash::default())
+            ),
+            Err(NativeVerificationError::Proof(VerificationError::Params(
+                ParamsMismatch::ProgramHashMode
+            )))
+        );
+
+        let mut other_params = params.clone();
+        other_params.program[0].1[0] ^= 1;
+        assert_eq!(
+            verify_cairo_proof_like_circuit(&other_params, &proof, &output_hash, None),
+            Err(NativeVerificationError::Proof(VerificationError::Params(
+                ParamsMismatch::Program
+            )))
+        );
+    }
+
+    // Every value below is mixed into the channel before the proof of work, so changing it in a
+    // whole proof fails the proof of work first. The checks after the Fiat-Shamir transform are
+    // therefore run with the randomness drawn for the original proof.
+
+    #[test]
+    fn test_verify_after_fiat_shamir_rejects_composition() {
+        let mut proof = load_proof();
+        let params = CairoVerifierParams::from_proof(&proof);
+        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof, &params);
+        verify_after_fiat_shamir(&fiat_shamir_hints, &proof).unwrap();
+
+        // the first coordinate of the composition polynomial at the OODS point
+        proof.stark_proof.sampled_values[3][0][0] += SecureField::one();
+        assert_eq!(
+            verify_after_fiat_shamir(&fiat_shamir_hints, &proof),
+            Err(VerificationError::CompositionEval)
+        );
+    }
+
+    #[test]
+    fn test_verify_after_fiat_shamir_rejects_decommitment() {
+        let mut proof = load_proof();
+        let params = CairoVerifierParams::from_proof(&proof);
+        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof, &params);
+
+        proof.stark_proof.queried_values[1][0] += BaseField::one();
+        assert_eq!(
+            verify_after_fiat_shamir(&fiat_shamir_hints, &proof),
+            Err(VerificationError::Decommitment { tree: "trace" })
+        );
+    }
+
+    #[test]
+    fn test_verify_after_fiat_shamir_rejects_last_layer() {
+        let mut proof = load_proof();
+        let params = CairoVerifierParams::from_proof(&proof);
+        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof, &params);
+
+        proof.stark_proof.fri_proof.last_layer_poly.coeffs[0] += SecureField::one();
+        assert_eq!(
+            verify_after_fiat_shamir(&fiat_shamir_hints, &proof),
+            Err(VerificationError::LastLayer)
+        );
+    }
+}
@@ -0,0 +1,402 @@
+use cairo_plonk_dsl_data_structures::CairoProofVar;
+use cairo_plonk_dsl_hints::MAX_OUTPUT_LEN;
+use circle_plonk_dsl_constraint_system::{
+    var::{AllocVar, Var},
+    ConstraintSystemRef,
+};
+use circle_plonk_dsl_primitives::{BitVar, QM31Var};
+use std::fmt;
+use stwo::core::fields::{m31::M31, qm31::QM31};
+
+/// An entry of the output section, together with its position in the section, so that the
+/// circuit can expose it as a public input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputField {
+    pub index: usize,
+    pub value: [u32; 8],
+}
+
+impl OutputField {
+    /// Take the fields of an output section, in order.
+    pub fn from_section(section: &[(u32, [u32; 8])]) -> Vec<Self> {
+        section
+            .iter()
+            .enumerate()
+            .map(|(index, (_, value))| Self {
+                index,
+                value: *value,
+            })
+            .collect()
+    }
+
+    /// The value as an integer, if it fits in 64 bits.
+    pub fn as_u64(&self) -> Option<u64> {
+        if self.value[2..].iter().all(|limb| *limb == 0) {
+            Some(self.value[0] as u64 | (self.value[1] as u64) << 32)
+        } else {
+            None
+        }
+    }
+
+    /// The sixteen 16-bit limbs of the value, in little-endian order, packed into four QM31
+    /// elements. This is how the field appears in the public input.
+    pub fn to_qm31(&self) -> [QM31; 4] {
+        let limbs = self
+            .value
+            .iter()
+            .flat_map(|limb| [M31::from(limb & 0xffff), M31::from(limb >> 16)])
+            .collect::<Vec<_>>();
+        std::array::from_fn(|i| {
+            QM31::from_m31(
+                limbs[4 * i],
+                limbs[4 * i + 1],
+                limbs[4 * i + 2],
+                limbs[4 * i + 3],
+            )
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputDecodeError {
+    /// The output ends before the entry at this index.
+    MissingEntry { index: usize },
+    /// The entry at this index should be a small integer, such as a count or a size.
+    NotAnInteger { index: usize },
+    /// The entries do not match the expected layout.
+    UnexpectedLength { expected: usize, actual: usize },
+}
+
+impl fmt::Display for OutputDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingEntry { index } => write!(f, "the output has no entry at index {index}"),
+            Self::NotAnInteger { index } => {
+                write!(
+                    f,
+                    "the output entry at index {index} is not a small integer"
+                )
+            }
+            Self::UnexpectedLength { expected, actual } => {
+                write!(f, "expected {expected} output entries, found {actual}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for OutputDecodeError {}
+
+fn entry(fields: &[OutputField], i: usize) -> Result<OutputField, OutputDecodeError> {
+    fields
+        .get(i)
+        .copied()
+        .ok_or(OutputDecodeError::MissingEntry {
+            index: fields.first().map_or(i, |first| first.index + i),
+        })
+}
+
+fn integer(field: &OutputField) -> Result<usize, OutputDecodeError> {
+    field
+        .as_u64()
+        .and_then(|value| usize::try_from(value).ok())
+        .ok_or(OutputDecodeError::NotAnInteger { index: field.index })
+}
+
+/// A task executed by the simple bootloader. Its output starts with the size of the task output,
+/// which counts these two header entries, and the hash of the task program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BootloaderTask {
+    pub output_size: OutputField,
+    pub program_hash: OutputField,
+    pub output: Vec<OutputField>,
+}
+
+/// The output of the simple bootloader, which is the number of tasks followed by the output of
+/// each task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BootloaderOutput {
+    pub n_tasks: OutputField,
+    pub tasks: Vec<BootloaderTask>,
+}
+
+impl BootloaderOutput {
+    pub fn decode(output: &[(u32, [u32; 8])]) -> Result<Self, OutputDecodeError> {
+        let fields = OutputField::from_section(output);
+
+        let n_tasks = entry(&fields, 0)?;
+        let mut cur = 1;
+        let mut tasks = vec![];
+        for _ in 0..integer(&n_tasks)? {
+            let output_size = entry(&fields, cur)?;
+            let program_hash = entry(&fields, cur + 1)?;
+            let size = integer(&output_size)?;
+            if size < 2 {
+                return Err(OutputDecodeError::UnexpectedLength {
+                    expected: 2,
+                    actual: size,
+                });
+            }
+            let end = cur
+                .checked_add(size)
+                .ok_or(OutputDecodeError::MissingEntry {
+                    index: fields.len(),
+                })?;
+            if end > fields.len() {
+                return Err(OutputDecodeError::MissingEntry { index: end - 1 });
+            }
+            tasks.push(BootloaderTask {
+                output_size,
+                program_hash,
+                output: fields[cur + 2..end].to_vec(),
+            });
+            cur = end;
+        }
+        if cur != fields.len() {
+            return Err(OutputDecodeError::UnexpectedLength {
+                expected: cur,
+                actual: fields.len(),
+            });
+        }
+
+        Ok(Self { n_tasks, tasks })
+    }
+}
+
+/// The output of the Cairo-to-Cairo recursive verifier, run as a bootloader task, which is the
+/// program hash of the verified proof, such as the one of SNOS, and the Blake2s hash of its
+/// outputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecursiveVerifierOutput {
+    pub program_hash: OutputField,
+    pub output_hash: OutputField,
+}
+
+impl RecursiveVerifierOutput {
+    pub fn decode(task: &BootloaderTask) -> Result<Self, OutputDecodeError> {
+        match task.output.as_slice() {
+            [program_hash, output_hash] => Ok(Self {
+                program_hash: *program_hash,
+                output_hash: *output_hash,
+            }),
+            output => Err(OutputDecodeError::UnexpectedLength {
+                expected: 2,
+                actual: output.len(),
+            }),
+        }
+    }
+}
+
+/// The header of the output of the Starknet OS (SNOS).
+///
+/// When the SNOS proof is verified by the Cairo-to-Cairo recursive verifier with
+/// `blake_outputs_packing`, the SNOS output is only visible through its hash, so the header is
+/// decoded from the SNOS output obtained separately, and the fields cannot be exposed by the
+/// circuit. When SNOS runs directly as a bootloader task, the header is the start of the task
+/// output and the fields can be exposed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OsOutputHeader {
+    pub initial_root: OutputField,
+    pub final_root: OutputField,
+    pub prev_block_number: OutputField,
+    pub new_block_number: OutputField,
+    pub prev_block_hash: OutputField,
+    pub new_block_hash: OutputField,
+    pub os_program_hash: OutputField,
+    pub starknet_os_config_hash: OutputField,
+    pub use_kzg_da: OutputField,
+    pub full_output: OutputField,
+}
+
+impl OsOutputHeader {
+    pub const N_FIELDS: usize = 10;
+
+    pub fn decode(output: &[OutputField]) -> Result<Self, OutputDecodeError> {
+        let header = (0..Self::N_FIELDS)
+            .map(|i| entry(output, i))
+            .collect::<Result<Vec<_>, _>>()?;
+        for i in [2, 3, 8, 9] {
+            integer(&header[i])?;
+        }
+
+        Ok(Self {
+            initial_root: header[0],
+            final_root: header[1],
+            prev_block_number: header[2],
+            new_block_number: header[3],
+            prev_block_hash: header[4],
+            new_block_hash: header[5],
+            os_program_hash: header[6],
+            starknet_os_config_hash: header[7],
+            use_kzg_da: header[8],
+            full_output: header[9],
+        })
+    }
+}
+
+/// Append the public input of output fields exposed by `OutputFieldVar`, in the same order, to
+/// the public input of the circuit.
+pub fn append_output_fields(input: &mut Vec<(usize, QM31)>, fields: &[OutputField]) {
+    for field in fields.iter() {
+        for limbs in field.to_qm31() {
+            input.push((input.len() + 1, limbs));
+        }
+    }
+}
+
+/// An output field exposed as a public input of the circuit, as in `OutputField::to_qm31`.
+pub struct OutputFieldVar {
+    pub index: usize,
+    pub limbs: [QM31Var; 4],
+}
+
+impl OutputFieldVar {
+    pub fn new_public_input(cs: &ConstraintSystemRef, field: &OutputField) -> Self {
+        Self {
+            index: field.index,
+            limbs: field
+                .to_qm31()
+                .map(|limbs| QM31Var::new_public_input(cs, &limbs)),
+        }
+    }
+
+    /// Check that the output section of the proof has an active entry with this value at the
+    /// index of the field.
+    pub fn verify(&self, proof_var: &CairoProofVar) {
+        assert!(self.index < MAX_OUTPUT_LEN);
+        let output = &proof_var.claim.public_data.public_memory.output;
+        output.is_active[self.index].equalverify(&BitVar::new_true(&proof_var.cs()));
+
+        let limbs = output.values[self.index]
+            .iter()
+            .flat_map(|limb| {
+                [
+                    limb.bits.compose_range(0..16),
+                    limb.bits.compose_range(16..32),
+                ]
+            })
+            .collect::<Vec<_>>();
+        for (i, expected) in self.limbs.iter().enumerate() {
+            QM31Var::from_m31(
+                &limbs[4 * i],
+                &limbs[4 * i + 1],
+                &limbs[4 * i + 2],
+                &limbs[4 * i + 3],
+            )
+            .equalverify(expected);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cairo_air::utils::{deserialize_proof_from_file, ProofFormat};
+    use cairo_air::CairoProof;
+    use std::path::PathBuf;
+    use stwo::core::vcs::poseidon31_merkle::Poseidon31MerkleHasher;
+
+    fn load_proof() -> CairoProof<Poseidon31MerkleHasher> {
+        let manifest_dir = env!("CARGO_MANIFEST_DIR");
+        let data_path = PathBuf::from(manifest_dir)
+            .parent()
+            .unwrap()
+            .join("test_data")
+            .join("recursive_proof.bin.bz");
+
+        deserialize_proof_from_file(&data_path, ProofFormat::Binary).unwrap()
+    }
+
+    fn integer_entry(value: u32) -> (u32, [u32; 8]) {
+        (0, [value, 0, 0, 0, 0, 0, 0, 0])
+    }
+
+    #[test]
+    fn test_decode_bootloader_output() {
+        let proof = load_proof();
+        let output = &proof.claim.public_data.public_memory.output;
+        let bootloader_output = BootloaderOutput::decode(output).unwrap();
+
+        assert_eq!(bootloader_output.n_tasks.as_u64(), Some(1));
+        assert_eq!(bootloader_output.tasks.len(), 1);
+        assert_eq!(bootloader_output.tasks[0].output_size.as_u64(), Some(4));
+
+        let verifier_output = RecursiveVerifierOutput::decode(&bootloader_output.tasks[0]).unwrap();
+        assert_eq!(verifier_output.program_hash.index, 3);
+        assert_eq!(verifier_output.output_hash.index, 4);
+
+        // a truncated output is rejected
+        assert_eq!(
+            BootloaderOutput::decode(&output[..4]),
+            Err(OutputDecodeError::MissingEntry { index: 4 })
+        );
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_decode_bootloader_output_with_overflowing_size() {
+        let output = [
+            integer_entry(1),
+            (0, [u32::MAX, u32::MAX, 0, 0, 0, 0, 0, 0]),
+            integer_entry(0),
+        ];
+        assert_eq!(
+            BootloaderOutput::decode(&output),
+            Err(OutputDecodeError::MissingEntry { index: 3 })
+        );
+    }
+
+    #[test]
+    fn test_decode_os_output_header() {
+        let mut output = (0..OsOutputHeader::N_FIELDS as u32)
+            .map(|i| (0, [i, i + 1, 0, 0, 0, 0, 0, 0]))
+            .collect::<Vec<_>>();
+        for i in [2, 3, 8, 9] {
+            output[i] = integer_entry(i as u32);
+        }
+        output.push(integer_entry(100));
+        let fields = OutputField::from_section(&output);
+
+        let header = OsOutputHeader::decode(&fields).unwrap();
+        assert_eq!(header.initial_root, fields[0]);
+        assert_eq!(header.new_block_hash, fields[5]);
+        assert_eq!(header.new_block_number.as_u64(), Some(3));
+        assert_eq!(header.full_output.as_u64(), Some(9));
+
+        // the header of a task output keeps the indices of the section
+        let header = OsOutputHeader::decode(&fields[1..]).unwrap();
+        assert_eq!(header.initial_root.index, 1);
+
+        // the block numbers and the flags are small integers
+        let mut not_an_integer = fields.clone();
+        not_an_integer[8].value[2] = 1;
+        assert_eq!(
+            OsOutputHeader::decode(&not_an_integer),
+            Err(OutputDecodeError::NotAnInteger { index: 8 })
+        );
+
+        assert_eq!(
+            OsOutputHeader::decode(&fields[..OsOutputHeader::N_FIELDS - 1]),
+            Err(OutputDecodeError::MissingEntry { index: 9 })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "is incorrect")]
+    fn test_output_field_var_rejects_wrong_value() {
+        let proof = load_proof();
+        let fields = OutputField::from_section(&proof.claim.public_data.public_memory.output);
+
+        let cs = ConstraintSystemRef::new();
+        let proof_var = CairoProofVar::new_witness(&cs, &proof);
+        let field_var = OutputFieldVar::new_public_input(&cs, &fields[3]);
+        field_var.verify(&proof_var);
+
+        cs.pad();
+        cs.check_arithmetics();
+
+        // a public input that differs from the output section cannot satisfy the constraints
+        let wrong_limbs = field_var.limbs[0].value + QM31::from(M31::from(1));
+        cs.set_value(field_var.limbs[0].variable, wrong_limbs);
+        cs.check_arithmetics();
+    }
+}
@@ -1,5 +1,16 @@
-use cairo_plonk_dsl_data_structures::{BlakeContextClaimVar, CairoClaimVar, OpcodeClaimVar};
+use cairo_plonk_dsl_data_structures::{
+    BlakeContextClaimVar, CairoClaimVar, OpcodeClaimVar, PoseidonContextClaimVar,
+};
 use cairo_plonk_dsl_decommitment::CairoDecommitmentResultsVar;
+use cairo_plonk_dsl_hints::decommitment::{
+    ADD_MOD_BUILTIN_N_INTERACTION_COLUMNS, BITWISE_BUILTIN_N_INTERACTION_COLUMNS,
+    CUBE_252_N_INTERACTION_COLUMNS, GENERIC_OPCODE_N_INTERACTION_COLUMNS,
+    JUMP_OPCODE_ABS_N_INTERACTION_COLUMNS, JUMP_OPCODE_DOUBLE_DEREF_N_INTERACTION_COLUMNS,
+    MUL_MOD_BUILTIN_N_INTERACTION_COLUMNS, POSEIDON_3_PARTIAL_ROUNDS_CHAIN_N_INTERACTION_COLUMNS,
+    POSEIDON_AGGREGATOR_N_INTERACTION_COLUMNS, POSEIDON_BUILTIN_N_INTERACTION_COLUMNS,
+    POSEIDON_FULL_ROUND_CHAIN_N_INTERACTION_COLUMNS,
+    RANGE_CHECK_252_WIDTH_27_N_INTERACTION_COLUMNS, RANGE_CHECK_96_BUILTIN_N_INTERACTION_COLUMNS,
+};
 use circle_plonk_dsl_constraint_system::{var::Var, ConstraintSystemRef};
 use circle_plonk_dsl_primitives::{
     oblivious_map::ObliviousMapVar, CM31Var, CirclePointM31Var, CirclePointQM31Var, LogSizeVar,
@@ -25,9 +36,15 @@ pub struct InteractionSampleResultVar {
     pub opcodes: OpcodesInteractionSampleResultVar,
     pub verify_instruction: InteractionEntryVar<3>,
     pub blake: BlakeInteractionSampleResultVar,
+    pub add_mod_builtin: InteractionEntryVar<ADD_MOD_BUILTIN_N_INTERACTION_COLUMNS>,
+    pub bitwise_builtin: InteractionEntryVar<BITWISE_BUILTIN_N_INTERACTION_COLUMNS>,
+    pub mul_mod_builtin: InteractionEntryVar<MUL_MOD_BUILTIN_N_INTERACTION_COLUMNS>,
+    pub poseidon_builtin: InteractionEntryVar<POSEIDON_BUILTIN_N_INTERACTION_COLUMNS>,
+    pub range_check_96_builtin: InteractionEntryVar<RANGE_CHECK_96_BUILTIN_N_INTERACTION_COLUMNS>,
     pub range_check_128_builtin: InteractionEntryVar<1>,
+    pub poseidon: PoseidonInteractionSampleResultVar,
     pub memory_address_to_id: InteractionEntryVar<8>,
-    pub memory_id_to_big_big: InteractionEntryVar<8>,
+    pub memory_id_to_big_big: Vec<InteractionEntryVar<8>>,
     pub memory_id_to_big_small: InteractionEntryVar<3>,
     pub range_checks: RangeChecksInteractionSampleResultVar,
     pub verify_bitwise: VerifyBitwiseInteractionSampleResultVar,
@@ -35,23 +52,26 @@ pub struct InteractionSampleResultVar {
 
 pub struct OpcodesInteractionSampleResultVar {
     pub cs: ConstraintSystemRef,
-    pub add: InteractionEntryVar<5>,
-    pub add_small: InteractionEntryVar<5>,
-    pub add_ap: InteractionEntryVar<4>,
-    pub assert_eq: InteractionEntryVar<3>,
-    pub assert_eq_imm: InteractionEntryVar<3>,
-    pub assert_eq_double_deref: InteractionEntryVar<4>,
-    pub blake: InteractionEntryVar<37>,
-    pub call: InteractionEntryVar<5>,
-    pub call_rel_imm: InteractionEntryVar<5>,
-    pub jnz: InteractionEntryVar<3>,
-    pub jnz_taken: InteractionEntryVar<4>,
-    pub jump_rel: InteractionEntryVar<3>,
-    pub jump_rel_imm: InteractionEntryVar<3>,
-    pub mul: InteractionEntryVar<19>,
-    pub mul_small: InteractionEntryVar<6>,
-    pub qm31: InteractionEntryVar<6>,
-    pub ret: InteractionEntryVar<4>,
+    pub add: Vec<InteractionEntryVar<5>>,
+    pub add_small: Vec<InteractionEntryVar<5>>,
+    pub add_ap: Vec<InteractionEntryVar<4>>,
+    pub assert_eq: Vec<InteractionEntryVar<3>>,
+    pub assert_eq_imm: Vec<InteractionEntryVar<3>>,
+    pub assert_eq_double_deref: Vec<InteractionEntryVar<4>>,
+    pub blake: Vec<InteractionEntryVar<37>>,
+    pub call: Vec<InteractionEntryVar<5>>,
+    pub call_rel_imm: Vec<InteractionEntryVar<5>>,
+    pub generic: Vec<InteractionEntryVar<GENERIC_OPCODE_N_INTERACTION_COLUMNS>>,
+    pub jnz: Vec<InteractionEntryVar<3>>,
+    pub jnz_taken: Vec<InteractionEntryVar<4>>,
+    pub jump: Vec<InteractionEntryVar<JUMP_OPCODE_ABS_N_INTERACTION_COLUMNS>>,
+    pub jump_double_deref: Vec<InteractionEntryVar<JUMP_OPCODE_DOUBLE_DEREF_N_INTERACTION_COLUMNS>>,
+    pub jump_rel: Vec<InteractionEntryVar<3>>,
+    pub jump_rel_imm: Vec<InteractionEntryVar<3>>,
+    pub mul: Vec<InteractionEntryVar<19>>,
+    pub mul_small: Vec<InteractionEntryVar<6>>,
+    pub qm31: Vec<InteractionEntryVar<6>>,
+    pub ret: Vec<InteractionEntryVar<4>>,
 }
 
 pub struct BlakeInteractionSampleResultVar {
@@ -63,6 +83,18 @@ pub struct BlakeInteractionSampleResultVar {
     pub verify_bitwise_xor_12: InteractionEntryVar<8>,
 }
 
+pub struct PoseidonInteractionSampleResultVar {
+    pub cs: ConstraintSystemRef,
+    pub aggregator: InteractionEntryVar<POSEIDON_AGGREGATOR_N_INTERACTION_COLUMNS>,
+    pub partial_rounds_chain:
+        InteractionEntryVar<POSEIDON_3_PARTIAL_ROUNDS_CHAIN_N_INTERACTION_COLUMNS>,
+    pub full_round_chain: InteractionEntryVar<POSEIDON_FULL_ROUND_CHAIN_N_INTERACTION_COLUMNS>,
+    pub cube_252: InteractionEntryVar<CUBE_252_N_INTERACTION_COLUMNS>,
+    pub round_keys: InteractionEntryVar<1>,
+    pub range_check_252_width_27:
+        InteractionEntryVar<RANGE_CHECK_252_WIDTH_27_N_INTERACTION_COLUMNS>,
+}
+
 pub struct RangeChecksInteractionSampleResultVar {
     pub cs: ConstraintSystemRef,
     pub range_check_6: InteractionEntryVar<1>,
@@ -140,17 +172,57 @@ fn allocate_interaction_entry<const N: usize>(
     InteractionEntryVar { data, presum }
 }
 
+/// Helper function to allocate one InteractionEntryVar<N> per instance of a component
+fn allocate_interaction_entries<const N: usize>(
+    sampled_values: &Vec<Vec<QM31Var>>,
+    offset: &mut usize,
+    n_instances: usize,
+) -> Vec<InteractionEntryVar<N>> {
+    (0..n_instances)
+        .map(|_| allocate_interaction_entry::<N>(sampled_values, offset))
+        .collect()
+}
+
 impl InteractionSampleResultVar {
-    pub fn new(cs: &ConstraintSystemRef, sampled_values: &Vec<Vec<QM31Var>>) -> Self {
+    pub fn new(
+        cs: &ConstraintSystemRef,
+        sampled_values: &Vec<Vec<QM31Var>>,
+        claim: &CairoClaimVar,
+    ) -> Self {
         let mut offset = 0;
 
         // Allocate in the exact order as defined in InteractionSampleResultVar
-        let opcodes = allocate_opcodes_interaction(cs, sampled_values, &mut offset);
+        let opcodes =
+            allocate_opcodes_interaction(cs, sampled_values, &mut offset, &claim.opcode_claim);
         let verify_instruction = allocate_interaction_entry::<3>(sampled_values, &mut offset);
         let blake = allocate_blake_interaction(cs, sampled_values, &mut offset);
+        let add_mod_builtin = allocate_interaction_entry::<ADD_MOD_BUILTIN_N_INTERACTION_COLUMNS>(
+            sampled_values,
+            &mut offset,
+        );
+        let bitwise_builtin = allocate_interaction_entry::<BITWISE_BUILTIN_N_INTERACTION_COLUMNS>(
+            sampled_values,
+            &mut offset,
+        );
+        let mul_mod_builtin = allocate_interaction_entry::<MUL_MOD_BUILTIN_N_INTERACTION_COLUMNS>(
+            sampled_values,
+            &mut offset,
+        );
+        let poseidon_builtin = allocate_interaction_entry::<POSEIDON_BUILTIN_N_INTERACTION_COLUMNS>(
+            sampled_values,
+            &mut offset,
+        );
+        let range_check_96_builtin = allocate_interaction_entry::<
+            RANGE_CHECK_96_BUILTIN_N_INTERACTION_COLUMNS,
+        >(sampled_values, &mut offset);
         let range_check_128_builtin = allocate_interaction_entry::<1>(sampled_values, &mut offset);
+        let poseidon = allocate_poseidon_interaction(cs, sampled_values, &mut offset);
         let memory_address_to_id = allocate_interaction_entry::<8>(sampled_values, &mut offset);
-        let memory_id_to_big_big = allocate_interaction_entry::<8>(sampled_values, &mut offset);
+        let memory_id_to_big_big = allocate_interaction_entries::<8>(
+            sampled_values,
+            &mut offset,
+            claim.memory_id_to_value.big_log_sizes.log_sizes.len(),
+        );
         let memory_id_to_big_small = allocate_interaction_entry::<3>(sampled_values, &mut offset);
         let range_checks = allocate_range_checks_interaction(cs, sampled_values, &mut offset);
         let verify_bitwise = allocate_verify_bitwise_interaction(cs, sampled_values, &mut offset);
@@ -166,7 +238,13 @@ impl InteractionSampleResultVar {
             opcodes,
             verify_instruction,
             blake,
+            add_mod_builtin,
+            bitwise_builtin,
+            mul_mod_builtin,
+            poseidon_builtin,
+            range_check_96_builtin,
             range_check_128_builtin,
+            poseidon,
             memory_address_to_id,
             memory_id_to_big_big,
             memory_id_to_big_small,
@@ -176,31 +254,94 @@ impl InteractionSampleResultVar {
     }
 }
 
-/// Allocate OpcodesInteractionSampleResultVar from sampled_values[2]
+/// Allocate OpcodesInteractionSampleResultVar from sampled_values[2], with one entry per instance
+/// of each opcode in the claim
 fn allocate_opcodes_interaction(
     cs: &ConstraintSystemRef,
     sampled_values: &Vec<Vec<QM31Var>>,
     offset: &mut usize,
+    claim: &OpcodeClaimVar,
 ) -> OpcodesInteractionSampleResultVar {
     OpcodesInteractionSampleResultVar {
         cs: cs.clone(),
-        add: allocate_interaction_entry::<5>(sampled_values, offset),
-        add_small: allocate_interaction_entry::<5>(sampled_values, offset),
-        add_ap: allocate_interaction_entry::<4>(sampled_values, offset),
-        assert_eq: allocate_interaction_entry::<3>(sampled_values, offset),
-        assert_eq_imm: allocate_interaction_entry::<3>(sampled_values, offset),
-        assert_eq_double_deref: allocate_interaction_entry::<4>(sampled_values, offset),
-        blake: allocate_interaction_entry::<37>(sampled_values, offset),
-        call: allocate_interaction_entry::<5>(sampled_values, offset),
-        call_rel_imm: allocate_interaction_entry::<5>(sampled_values, offset),
-        jnz: allocate_interaction_entry::<3>(sampled_values, offset),
-        jnz_taken: allocate_interaction_entry::<4>(sampled_values, offset),
-        jump_rel: allocate_interaction_entry::<3>(sampled_values, offset),
-        jump_rel_imm: allocate_interaction_entry::<3>(sampled_values, offset),
-        mul: allocate_interaction_entry::<19>(sampled_values, offset),
-        mul_small: allocate_interaction_entry::<6>(sampled_values, offset),
-        qm31: allocate_interaction_entry::<6>(sampled_values, offset),
-        ret: allocate_interaction_entry::<4>(sampled_values, offset),
+        add: allocate_interaction_entries::<5>(sampled_values, offset, claim.add.log_sizes.len()),
+        add_small: allocate_interaction_entries::<5>(
+            sampled_values,
+            offset,
+            claim.add_small.log_sizes.len(),
+        ),
+        add_ap: allocate_interaction_entries::<4>(
+            sampled_values,
+            offset,
+            claim.add_ap.log_sizes.len(),
+        ),
+        assert_eq: allocate_interaction_entries::<3>(
+            sampled_values,
+            offset,
+            claim.assert_eq.log_sizes.len(),
+        ),
+        assert_eq_imm: allocate_interaction_entries::<3>(
+            sampled_values,
+            offset,
+            claim.assert_eq_imm.log_sizes.len(),
+        ),
+        assert_eq_double_deref: allocate_interaction_entries::<4>(
+            sampled_values,
+            offset,
+            claim.assert_eq_double_deref.log_sizes.len(),
+        ),
+        blake: allocate_interaction_entries::<37>(
+            sampled_values,
+            offset,
+            claim.blake.log_sizes.len(),
+        ),
+        call: allocate_interaction_entries::<5>(sampled_values, offset, claim.call.log_sizes.len()),
+        call_rel_imm: allocate_interaction_entries::<5>(
+            sampled_values,
+            offset,
+            claim.call_rel_imm.log_sizes.len(),
+        ),
+        generic: allocate_interaction_entries::<GENERIC_OPCODE_N_INTERACTION_COLUMNS>(
+            sampled_values,
+            offset,
+            claim.generic.log_sizes.len(),
+        ),
+        jnz: allocate_interaction_entries::<3>(sampled_values, offset, claim.jnz.log_sizes.len()),
+        jnz_taken: allocate_interaction_entries::<4>(
+            sampled_values,
+            offset,
+            claim.jnz_taken.log_sizes.len(),
+        ),
+        jump: allocate_interaction_entries::<JUMP_OPCODE_ABS_N_INTERACTION_COLUMNS>(
+            sampled_values,
+            offset,
+            claim.jump.log_sizes.len(),
+        ),
+        jump_double_deref: allocate_interaction_entries::<
+            JUMP_OPCODE_DOUBLE_DEREF_N_INTERACTION_COLUMNS,
+        >(
+            sampled_values,
+            offset,
+            claim.jump_double_deref.log_sizes.len(),
+        ),
+        jump_rel: allocate_interaction_entries::<3>(
+            sampled_values,
+            offset,
+            claim.jump_rel.log_sizes.len(),
+        ),
+        jump_rel_imm: allocate_interaction_entries::<3>(
+            sampled_values,
+            offset,
+            claim.jump_rel_imm.log_sizes.len(),
+        ),
+        mul: allocate_interaction_entries::<19>(sampled_values, offset, claim.mul.log_sizes.len()),
+        mul_small: allocate_interaction_entries::<6>(
+            sampled_values,
+            offset,
+            claim.mul_small.log_sizes.len(),
+        ),
+        qm31: allocate_interaction_entries::<6>(sampled_values, offset, claim.qm31.log_sizes.len()),
+        ret: allocate_interaction_entries::<4>(sampled_values, offset, claim.ret.log_sizes.len()),
     }
 }
 
@@ -220,6 +361,35 @@ fn allocate_blake_interaction(
     }
 }
 
+/// Allocate PoseidonInteractionSampleResultVar from sampled_values[2]
+fn allocate_poseidon_interaction(
+    cs: &ConstraintSystemRef,
+    sampled_values: &Vec<Vec<QM31Var>>,
+    offset: &mut usize,
+) -> PoseidonInteractionSampleResultVar {
+    PoseidonInteractionSampleResultVar {
+        cs: cs.clone(),
+        aggregator: allocate_interaction_entry::<POSEIDON_AGGREGATOR_N_INTERACTION_COLUMNS>(
+            sampled_values,
+            offset,
+        ),
+        partial_rounds_chain: allocate_interaction_entry::<
+            POSEIDON_3_PARTIAL_ROUNDS_CHAIN_N_INTERACTION_COLUMNS,
+        >(sampled_values, offset),
+        full_round_chain: allocate_interaction_entry::<
+            POSEIDON_FULL_ROUND_CHAIN_N_INTERACTION_COLUMNS,
+        >(sampled_values, offset),
+        cube_252: allocate_interaction_entry::<CUBE_252_N_INTERACTION_COLUMNS>(
+            sampled_values,
+            offset,
+        ),
+        round_keys: allocate_interaction_entry::<1>(sampled_values, offset),
+        range_check_252_width_27: allocate_interaction_entry::<
+            RANGE_CHECK_252_WIDTH_27_N_INTERACTION_COLUMNS,
+        >(sampled_values, offset),
+    }
+}
+
 /// Allocate RangeChecksInteractionSampleResultVar from sampled_values[2]
 fn allocate_range_checks_interaction(
     cs: &ConstraintSystemRef,
@@ -287,9 +457,20 @@ pub struct InteractionQuotientConstantsVar {
     pub opcodes: OpcodesInteractionQuotientConstantsVar,
     pub verify_instruction: InteractionQuotientConstantsEntryVar<3>,
     pub blake: BlakeInteractionQuotientConstantsVar,
+    pub add_mod_builtin:
+        InteractionQuotientConstantsEntryVar<ADD_MOD_BUILTIN_N_INTERACTION_COLUMNS>,
+    pub bitwise_builtin:
+        InteractionQuotientConstantsEntryVar<BITWISE_BUILTIN_N_INTERACTION_COLUMNS>,
+    pub mul_mod_builtin:
+        InteractionQuotientConstantsEntryVar<MUL_MOD_BUILTIN_N_INTERACTION_COLUMNS>,
+    pub poseidon_builtin:
+        InteractionQuotientConstantsEntryVar<POSEIDON_BUILTIN_N_INTERACTION_COLUMNS>,
+    pub range_check_96_builtin:
+        InteractionQuotientConstantsEntryVar<RANGE_CHECK_96_BUILTIN_N_INTERACTION_COLUMNS>,
     pub range_check_128_builtin: InteractionQuotientConstantsEntryVar<1>,
+    pub poseidon: PoseidonInteractionQuotientConstantsVar,
     pub memory_address_to_id: InteractionQuotientConstantsEntryVar<8>,
-    pub memory_id_to_big_big: InteractionQuotientConstantsEntryVar<8>,
+    pub memory_id_to_big_big: Vec<InteractionQuotientConstantsEntryVar<8>>,
     pub memory_id_to_big_small: InteractionQuotientConstantsEntryVar<3>,
     pub range_checks: RangeChecksInteractionQuotientConstantsVar,
     pub verify_bitwise: VerifyBitwiseInteractionQuotientConstantsVar,
@@ -323,24 +504,68 @@ impl InteractionQuotientConstantsVar {
                 shifted_points,
                 &sample_result.blake,
             ),
+            add_mod_builtin: InteractionQuotientConstantsEntryVar::new(
+                &claim.builtins.add_mod_builtin.log_size,
+                oods_point,
+                shifted_points,
+                &sample_result.add_mod_builtin,
+            ),
+            bitwise_builtin: InteractionQuotientConstantsEntryVar::new(
+                &claim.builtins.bitwise_builtin.log_size,
+                oods_point,
+                shifted_points,
+                &sample_result.bitwise_builtin,
+            ),
+            mul_mod_builtin: InteractionQuotientConstantsEntryVar::new(
+                &claim.builtins.mul_mod_builtin.log_size,
+                oods_point,
+                shifted_points,
+                &sample_result.mul_mod_builtin,
+            ),
+            poseidon_builtin: InteractionQuotientConstantsEntryVar::new(
+                &claim.builtins.poseidon_builtin.log_size,
+                oods_point,
+                shifted_points,
+                &sample_result.poseidon_builtin,
+            ),
+            range_check_96_builtin: InteractionQuotientConstantsEntryVar::new(
+                &claim.builtins.range_check_96_builtin.log_size,
+                oods_point,
+                shifted_points,
+                &sample_result.range_check_96_builtin,
+            ),
             range_check_128_builtin: InteractionQuotientConstantsEntryVar::new(
                 &claim.builtins.range_check_128_builtin_log_size,
                 oods_point,
                 shifted_points,
                 &sample_result.range_check_128_builtin,
             ),
-            memory_address_to_id: InteractionQuotientConstantsEntryVar::new(
-                &claim.memory_address_to_id,
+            poseidon: PoseidonInteractionQuotientConstantsVar::new(
+                &claim.poseidon_context,
                 oods_point,
                 shifted_points,
-                &sample_result.memory_address_to_id,
+                &sample_result.poseidon,
             ),
-            memory_id_to_big_big: InteractionQuotientConstantsEntryVar::new(
-                &claim.memory_id_to_value.big_log_size,
+            memory_address_to_id: InteractionQuotientConstantsEntryVar::new(
+                &claim.memory_address_to_id,
                 oods_point,
                 shifted_points,
-                &sample_result.memory_id_to_big_big,
+                &sample_result.memory_address_to_id,
             ),
+            memory_id_to_big_big: claim
+                .memory_id_to_value
+                .big_log_sizes
+                .iter()
+                .zip_eq(sample_result.memory_id_to_big_big.iter())
+                .map(|(log_size, entry)| {
+                    InteractionQuotientConstantsEntryVar::new(
+                        log_size,
+                        oods_point,
+                        shifted_points,
+                        entry,
+                    )
+                })
+                .collect(),
             memory_id_to_big_small: InteractionQuotientConstantsEntryVar::new(
                 &claim.memory_id_to_value.small_log_size,
                 oods_point,
@@ -363,23 +588,27 @@ impl InteractionQuotientConstantsVar {
 
 pub struct OpcodesInteractionQuotientConstantsVar {
     pub cs: ConstraintSystemRef,
-    pub add: InteractionQuotientConstantsEntryVar<5>,
-    pub add_small: InteractionQuotientConstantsEntryVar<5>,
-    pub add_ap: InteractionQuotientConstantsEntryVar<4>,
-    pub assert_eq: InteractionQuotientConstantsEntryVar<3>,
-    pub assert_eq_imm: InteractionQuotientConstantsEntryVar<3>,
-    pub assert_eq_double_deref: InteractionQuotientConstantsEntryVar<4>,
-    pub blake: InteractionQuotientConstantsEntryVar<37>,
-    pub call: InteractionQuotientConstantsEntryVar<5>,
-    pub call_rel_imm: InteractionQuotientConstantsEntryVar<5>,
-    pub jnz: InteractionQuotientConstantsEntryVar<3>,
-    pub jnz_taken: InteractionQuotientConstantsEntryVar<4>,
-    pub jump_rel: InteractionQuotientConstantsEntryVar<3>,
-    pub jump_rel_imm: InteractionQuotientConstantsEntryVar<3>,
-    pub mul: InteractionQuotientConstantsEntryVar<19>,
-    pub mul_small: InteractionQuotientConstantsEntryVar<6>,
-    pub qm31: InteractionQuotientConstantsEntryVar<6>,
-    pub ret: InteractionQuotientConstantsEntryVar<4>,
+    pub add: Vec<InteractionQuotientConstantsEntryVar<5>>,
+    pub add_small: Vec<InteractionQuotientConstantsEntryVar<5>>,
+    pub add_ap: Vec<InteractionQuotientConstantsEntryVar<4>>,
+    pub assert_eq: Vec<InteractionQuotientConstantsEntryVar<3>>,
+    pub assert_eq_imm: Vec<InteractionQuotientConstantsEntryVar<3>>,
+    pub assert_eq_double_deref: Vec<InteractionQuotientConstantsEntryVar<4>>,
+    pub blake: Vec<InteractionQuotientConstantsEntryVar<37>>,
+    pub call: Vec<InteractionQuotientConstantsEntryVar<5>>,
+    pub call_rel_imm: Vec<InteractionQuotientConstantsEntryVar<5>>,
+    pub generic: Vec<InteractionQuotientConstantsEntryVar<GENERIC_OPCODE_N_INTERACTION_COLUMNS>>,
+    pub jnz: Vec<InteractionQuotientConstantsEntryVar<3>>,
+    pub jnz_taken: Vec<InteractionQuotientConstantsEntryVar<4>>,
+    pub jump: Vec<InteractionQuotientConstantsEntryVar<JUMP_OPCODE_ABS_N_INTERACTION_COLUMNS>>,
+    pub jump_double_deref:
+        Vec<InteractionQuotientConstantsEntryVar<JUMP_OPCODE_DOUBLE_DEREF_N_INTERACTION_COLUMNS>>,
+    pub jump_rel: Vec<InteractionQuotientConstantsEntryVar<3>>,
+    pub jump_rel_imm: Vec<InteractionQuotientConstantsEntryVar<3>>,
+    pub mul: Vec<InteractionQuotientConstantsEntryVar<19>>,
+    pub mul_small: Vec<InteractionQuotientConstantsEntryVar<6>>,
+    pub qm31: Vec<InteractionQuotientConstantsEntryVar<6>>,
+    pub ret: Vec<InteractionQuotientConstantsEntryVar<4>>,
 }
 
 impl OpcodesInteractionQuotientConstantsVar {
@@ -389,110 +618,46 @@ impl OpcodesInteractionQuotientConstantsVar {
         shifted_points: &ObliviousMapVar<CirclePointQM31Var>,
         sample_result: &OpcodesInteractionSampleResultVar,
     ) -> Self {
+        macro_rules! new_instances {
+            ($name:ident) => {
+                claim
+                    .$name
+                    .iter()
+                    .zip_eq(sample_result.$name.iter())
+                    .map(|(log_size, entry)| {
+                        InteractionQuotientConstantsEntryVar::new(
+                            log_size,
+                            oods_point,
+                            shifted_points,
+                            entry,
+                        )
+                    })
+                    .collect()
+            };
+        }
+
         Self {
             cs: sample_result.cs.clone(),
-            add: InteractionQuotientConstantsEntryVar::new(
-                &claim.add,
-                oods_point,
-                shifted_points,
-                &sample_result.add,
-            ),
-            add_small: InteractionQuotientConstantsEntryVar::new(
-                &claim.add_small,
-                oods_point,
-                shifted_points,
-                &sample_result.add_small,
-            ),
-            add_ap: InteractionQuotientConstantsEntryVar::new(
-                &claim.add_ap,
-                oods_point,
-                shifted_points,
-                &sample_result.add_ap,
-            ),
-            assert_eq: InteractionQuotientConstantsEntryVar::new(
-                &claim.assert_eq,
-                oods_point,
-                shifted_points,
-                &sample_result.assert_eq,
-            ),
-            assert_eq_imm: InteractionQuotientConstantsEntryVar::new(
-                &claim.assert_eq_imm,
-                oods_point,
-                shifted_points,
-                &sample_result.assert_eq_imm,
-            ),
-            assert_eq_double_deref: InteractionQuotientConstantsEntryVar::new(
-                &claim.assert_eq_double_deref,
-                oods_point,
-                shifted_points,
-                &sample_result.assert_eq_double_deref,
-            ),
-            blake: InteractionQuotientConstantsEntryVar::new(
-                &claim.blake,
-                oods_point,
-                shifted_points,
-                &sample_result.blake,
-            ),
-            call: InteractionQuotientConstantsEntryVar::new(
-                &claim.call,
-                oods_point,
-                shifted_points,
-                &sample_result.call,
-            ),
-            call_rel_imm: InteractionQuotientConstantsEntryVar::new(
-                &claim.call_rel_imm,
-                oods_point,
-                shifted_points,
-                &sample_result.call_rel_imm,
-            ),
-            jnz: InteractionQuotientConstantsEntryVar::new(
-                &claim.jnz,
-                oods_point,
-                shifted_points,
-                &sample_result.jnz,
-            ),
-            jnz_taken: InteractionQuotientConstantsEntryVar::new(
-                &claim.jnz_taken,
-                oods_point,
-                shifted_points,
-                &sample_result.jnz_taken,
-            ),
-            jump_rel: InteractionQuotientConstantsEntryVar::new(
-                &claim.jump_rel,
-                oods_point,
-                shifted_points,
-                &sample_result.jump_rel,
-            ),
-            jump_rel_imm: InteractionQuotientConstantsEntryVar::new(
-                &claim.jump_rel_imm,
-                oods_point,
-                shifted_points,
-                &sample_result.jump_rel_imm,
-            ),
-            mul: InteractionQuotientConstantsEntryVar::new(
-                &claim.mul,
-                oods_point,
-                shifted_points,
-                &sample_result.mul,
-            ),
-            mul_small: InteractionQuotientConstantsEntryVar::new(
-                &claim.mul_small,
-                oods_point,
-                shifted_points,
-                &sample_result.mul_small,
-            ),
-            qm31: InteractionQuotientConstantsEntryVar::new(
-                &claim.qm31,
-                oods_point,
-                shifted_points,
-                &sample_result.qm31,
-            ),
-            ret: InteractionQuotientConstantsEntryVar::new(
-                &claim.ret,
-                oods_point,
-                shifted_points,
-                &sample_result.ret,
-            ),
+            add: new_instances!(add),
+            add_small: new_instances!(add_small),
+            add_ap: new_instances!(add_ap),
+            assert_eq: new_instances!(assert_eq),
+            assert_eq_imm: new_instances!(assert_eq_imm),
+            assert_eq_double_deref: new_instances!(assert_eq_double_deref),
+            blake: new_instances!(blake),
+            call: new_instances!(call),
+            call_rel_imm: new_instances!(call_rel_imm),
+            generic: new_instances!(generic),
+            jnz: new_instances!(jnz),
+            jnz_taken: new_instances!(jnz_taken),
+            jump: new_instances!(jump),
+            jump_double_deref: new_instances!(jump_double_deref),
+            jump_rel: new_instances!(jump_rel),
+            jump_rel_imm: new_instances!(jump_rel_imm),
+            mul: new_instances!(mul),
+            mul_small: new_instances!(mul_small),
+            qm31: new_instances!(qm31),
+            ret: new_instances!(ret),
         }
     }
 }
@@ -548,6 +713,68 @@ impl BlakeInteractionQuotientConstantsVar {
         }
     }
 }
+pub struct PoseidonInteractionQuotientConstantsVar {
+    pub cs: ConstraintSystemRef,
+    pub aggregator: InteractionQuotientConstantsEntryVar<POSEIDON_AGGREGATOR_N_INTERACTION_COLUMNS>,
+    pub partial_rounds_chain:
+        InteractionQuotientConstantsEntryVar<POSEIDON_3_PARTIAL_ROUNDS_CHAIN_N_INTERACTION_COLUMNS>,
+    pub full_round_chain:
+        InteractionQuotientConstantsEntryVar<POSEIDON_FULL_ROUND_CHAIN_N_INTERACTION_COLUMNS>,
+    pub cube_252: InteractionQuotientConstantsEntryVar<CUBE_252_N_INTERACTION_COLUMNS>,
+    pub round_keys: InteractionQuotientConstantsEntryVar<1>,
+    pub range_check_252_width_27:
+        InteractionQuotientConstantsEntryVar<RANGE_CHECK_252_WIDTH_27_N_INTERACTION_COLUMNS>,
+}
+
+impl PoseidonInteractionQuotientConstantsVar {
+    pub fn new(
+        claim: &PoseidonContextClaimVar,
+        oods_point: &CirclePointQM31Var,
+        shifted_points: &ObliviousMapVar<CirclePointQM31Var>,
+        sample_result: &PoseidonInteractionSampleResultVar,
+    ) -> Self {
+        Self {
+            cs: sample_result.cs.clone(),
+            aggregator: InteractionQuotientConstantsEntryVar::new(
+                &claim.poseidon_aggregator,
+                oods_point,
+                shifted_points,
+                &sample_result.aggregator,
+            ),
+            partial_rounds_chain: InteractionQuotientConstantsEntryVar::new(
+                &claim.poseidon_3_partial_rounds_chain,
+                oods_point,
+                shifted_points,
+                &sample_result.partial_rounds_chain,
+            ),
+            full_round_chain: InteractionQuotientConstantsEntryVar::new(
+                &claim.poseidon_full_round_chain,
+                oods_point,
+                shifted_points,
+                &sample_result.full_round_chain,
+            ),
+            cube_252: InteractionQuotientConstantsEntryVar::new(
+                &claim.cube_252,
+                oods_point,
+                shifted_points,
+                &sample_result.cube_252,
+            ),
+            round_keys: InteractionQuotientConstantsEntryVar::new(
+                &claim.poseidon_round_keys,
+                oods_point,
+                shifted_points,
+                &sample_result.round_keys,
+            ),
+            range_check_252_width_27: InteractionQuotientConstantsEntryVar::new(
+                &claim.range_check_252_width_27,
+                oods_point,
+                shifted_points,
+                &sample_result.range_check_252_width_27,
+            ),
+        }
+    }
+}
+
 pub struct RangeChecksInteractionQuotientConstantsVar {
     pub cs: ConstraintSystemRef,
     pub range_check_6: InteractionQuotientConstantsEntryVar<1>,
@@ -958,84 +1185,71 @@ pub fn compute_interaction_answers_without_shift(
         let answer_accumulator = &mut answer_accumulator[idx];
         let query_result = &query_result[idx].interaction_query_result;
 
+        macro_rules! update_instances {
+            ($name:ident) => {
+                for ((log_size, query), constants) in claim
+                    .opcode_claim
+                    .$name
+                    .iter()
+                    .zip_eq(query_result.opcodes.$name.iter())
+                    .zip_eq(quotient_constants.opcodes.$name.iter())
+                {
+                    update(
+                        answer_accumulator,
+                        &domain_points,
+                        &denominator_inverses_with_oods_point,
+                        log_size,
+                        query,
+                        constants,
+                        idx,
+                        &oods_point_y,
+                    );
+                }
+            };
+        }
+
         // opcodes
+        update_instances!(add);
+        update_instances!(add_small);
+        update_instances!(add_ap);
+        update_instances!(assert_eq);
+        update_instances!(assert_eq_imm);
+        update_instances!(assert_eq_double_deref);
+        update_instances!(blake);
+        update_instances!(call);
+        update_instances!(call_rel_imm);
+        update_instances!(generic);
+        update_instances!(jnz);
+        update_instances!(jnz_taken);
+        update_instances!(jump);
+        update_instances!(jump_double_deref);
+        update_instances!(jump_rel);
+        update_instances!(jump_rel_imm);
+        update_instances!(mul);
+        update_instances!(mul_small);
+        update_instances!(qm31);
+        update_instances!(ret);
+
+        // verify_instruction
         update(
             answer_accumulator,
             &domain_points,
             &denominator_inverses_with_oods_point,
-            &claim.opcode_claim.add,
-            &query_result.opcodes.add,
-            &quotient_constants.opcodes.add,
-            idx,
-            &oods_point_y,
-        );
-        update(
-            answer_accumulator,
-            &domain_points,
-            &denominator_inverses_with_oods_point,
-            &claim.opcode_claim.add_small,
-            &query_result.opcodes.add_small,
-            &quotient_constants.opcodes.add_small,
-            idx,
-            &oods_point_y,
-        );
-        update(
-            answer_accumulator,
-            &domain_points,
-            &denominator_inverses_with_oods_point,
-            &claim.opcode_claim.add_ap,
-            &query_result.opcodes.add_ap,
-            &quotient_constants.opcodes.add_ap,
-            idx,
-            &oods_point_y,
-        );
-        update(
-            answer_accumulator,
-            &domain_points,
-            &denominator_inverses_with_oods_point,
-            &claim.opcode_claim.assert_eq,
-            &query_result.opcodes.assert_eq,
-            &quotient_constants.opcodes.assert_eq,
-            idx,
-            &oods_point_y,
-        );
-        update(
-            answer_accumulator,
-            &domain_points,
-            &denominator_inverses_with_oods_point,
-            &claim.opcode_claim.assert_eq_imm,
-            &query_result.opcodes.assert_eq_imm,
-            &quotient_constants.opcodes.assert_eq_imm,
-            idx,
-            &oods_point_y,
-        );
-        update(
-            answer_accumulator,
-            &domain_points,
-            &denominator_inverses_with_oods_point,
-            &claim.opcode_claim.assert_eq_double_deref,
-            &query_result.opcodes.assert_eq_double_deref,
-            &quotient_constants.opcodes.assert_eq_double_deref,
-            idx,
-            &oods_point_y,
-        );
-        update(
-            answer_accumulator,
-            &domain_points,
-            &denominator_inverses_with_oods_point,
-            &claim.opcode_claim.blake,
-            &query_result.opcodes.blake,
-            &quotient_constants.opcodes.blake,
+            &claim.verify_instruction,
+            &query_result.verify_instruction,
+            &quotient_constants.verify_instruction,
             idx,
             &oods_point_y,
         );
+
+        // blake
         update(
             answer_accumulator,
             &domain_points,
             &denominator_inverses_with_oods_point,
-            &claim.opcode_claim.call,
-            &query_result.opcodes.call,
-            &quotient_constants.opcodes.call,
+            &claim.blake_context.blake_round,
+            &query_result.blake.round,
+            &quotient_constants.blake.round,
             idx,
             &oods_point_y,
         );
@@ -1043,19 +1257,19 @@ pub fn compute_interaction_answers_without_shift(
             answer_accumulator,
             &domain_points,
             &denominator_inverses_with_oods_point,
-            &claim.opcode_claim.call_rel_imm,
-            &query_result.opcodes.call_rel_imm,
-            &quotient_constants.opcodes.call_rel_imm,
+            &claim.blake_context.blake_g,
+            &query_result.blake.g,
+            &quotient_constants.blake.g,
             idx,
             &oods_point_y,
         );
-        update(
+        update_fixed_log_size(
             answer_accumulator,
             &domain_points,
             &denominator_inverses_with_oods_point,
-            &claim.opcode_claim.jnz,
-            &query_result.opcodes.jnz,
-            &quotient_constants.opcodes.jnz,
+            cairo_air::components::blake_round_sigma::LOG_SIZE,
+            &query_result.blake.sigma,
+            &quotient_constants.blake.sigma,
             idx,
             &oods_point_y,
         );
@@ -1063,93 +1277,104 @@ pub fn compute_interaction_answers_without_shift(
             answer_accumulator,
             &domain_points,
             &denominator_inverses_with_oods_point,
-            &claim.opcode_claim.jnz_taken,
-            &query_result.opcodes.jnz_taken,
-            &quotient_constants.opcodes.jnz_taken,
+            &claim.blake_context.triple_xor_32,
+            &query_result.blake.triple_xor_32,
+            &quotient_constants.blake.triple_xor_32,
             idx,
             &oods_point_y,
         );
-        update(
+        update_fixed_log_size(
             answer_accumulator,
             &domain_points,
             &denominator_inverses_with_oods_point,
-            &claim.opcode_claim.jump_rel,
-            &query_result.opcodes.jump_rel,
-            &quotient_constants.opcodes.jump_rel,
+            cairo_air::components::verify_bitwise_xor_12::LOG_SIZE,
+            &query_result.blake.verify_bitwise_xor_12,
+            &quotient_constants.blake.verify_bitwise_xor_12,
             idx,
             &oods_point_y,
         );
+
+        // add_mod_builtin
         update(
             answer_accumulator,
             &domain_points,
             &denominator_inverses_with_oods_point,
-            &claim.opcode_claim.jump_rel_imm,
-            &query_result.opcodes.jump_rel_imm,
-            &quotient_constants.opcodes.jump_rel_imm,
+            &claim.builtins.add_mod_builtin.log_size,
+            &query_result.add_mod_builtin,
+            &quotient_constants.add_mod_builtin,
             idx,
             &oods_point_y,
         );
+
+        // bitwise_builtin
         update(
             answer_accumulator,
             &domain_points,
             &denominator_inverses_with_oods_point,
-            &claim.opcode_claim.mul,
-            &query_result.opcodes.mul,
-            &quotient_constants.opcodes.mul,
+            &claim.builtins.bitwise_builtin.log_size,
+            &query_result.bitwise_builtin,
+            &quotient_constants.bitwise_builtin,
             idx,
             &oods_point_y,
         );
+
+        // mul_mod_builtin
         update(
             answer_accumulator,
             &domain_points,
             &denominator_inverses_with_oods_point,
-            &claim.opcode_claim.mul_small,
-            &query_result.opcodes.mul_small,
-            &quotient_constants.opcodes.mul_small,
+            &claim.builtins.mul_mod_builtin.log_size,
+            &query_result.mul_mod_builtin,
+            &quotient_constants.mul_mod_builtin,
             idx,
             &oods_point_y,
         );
+
+        // poseidon_builtin
         update(
             answer_accumulator,
             &domain_points,
             &denominator_inverses_with_oods_point,
-            &claim.opcode_claim.qm31,
-            &query_result.opcodes.qm31,
-            &quotient_constants.opcodes.qm31,
+            &claim.builtins.poseidon_builtin.log_size,
+            &query_result.poseidon_builtin,
+            &quotient_constants.poseidon_builtin,
             idx,
             &oods_point_y,
         );
+
+        // range_check_96_builtin
         update(
             answer_accumulator,
             &domain_points,
             &denominator_inverses_with_oods_point,
-            &claim.opcode_claim.ret,
-            &query_result.opcodes.ret,
-            &quotient_constants.opcodes.ret,
+            &claim.builtins.range_check_96_builtin.log_size,
+            &query_result.range_check_96_builtin,
+            &quotient_constants.range_check_96_builtin,
             idx,
             &oods_point_y,
         );
 
-        // verify_instruction
+        // range_check_128_builtin
         update(
             answer_accumulator,
             &domain_points,
             &denominator_inverses_with_oods_point,
-            &claim.verify_instruction,
-            &query_result.verify_instruction,
-            &quotient_constants.verify_instruction,
+            &claim.builtins.range_check_128_builtin_log_size,
+            &query_result.range_check_128_builtin,
+            &quotient_constants.range_check_128_builtin,
             idx,
             &oods_point_y,
         );
 
-        // blake
+        // poseidon
+        let poseidon_context = &claim.poseidon_context;
         update(
             answer_accumulator,
             &domain_points,
             &denominator_inverses_with_oods_point,
-            &claim.blake_context.blake_round,
-            &query_result.blake.round,
-            &quotient_constants.blake.round,
+            &poseidon_context.poseidon_aggregator,
+            &query_result.poseidon.aggregator,
+            &quotient_constants.poseidon.aggregator,
             idx,
             &oods_point_y,
         );
@@ -1157,19 +1382,19 @@ pub fn compute_interaction_answers_without_shift(
             answer_accumulator,
             &domain_points,
             &denominator_inverses_with_oods_point,
-            &claim.blake_context.blake_g,
-            &query_result.blake.g,
-            &quotient_constants.blake.g,
+            &poseidon_context.poseidon_3_partial_rounds_chain,
+            &query_result.poseidon.partial_rounds_chain,
+            &quotient_constants.poseidon.partial_rounds_chain,
             idx,
             &oods_point_y,
         );
-        update_fixed_log_size(
+        update(
             answer_accumulator,
             &domain_points,
             &denominator_inverses_with_oods_point,
-            cairo_air::components::blake_round_sigma::LOG_SIZE,
-            &query_result.blake.sigma,
-            &quotient_constants.blake.sigma,
+            &poseidon_context.poseidon_full_round_chain,
+            &query_result.poseidon.full_round_chain,
+            &quotient_constants.poseidon.full_round_chain,
             idx,
             &oods_point_y,
         );
@@ -1177,31 +1402,29 @@ pub fn compute_interaction_answers_without_shift(
             answer_accumulator,
             &domain_points,
             &denominator_inverses_with_oods_point,
-            &claim.blake_context.triple_xor_32,
-            &query_result.blake.triple_xor_32,
-            &quotient_constants.blake.triple_xor_32,
+            &poseidon_context.cube_252,
+            &query_result.poseidon.cube_252,
+            &quotient_constants.poseidon.cube_252,
             idx,
             &oods_point_y,
         );
-        update_fixed_log_size(
+        update(
             answer_accumulator,
             &domain_points,
             &denominator_inverses_with_oods_point,
-            cairo_air::components::verify_bitwise_xor_12::LOG_SIZE,
-            &query_result.blake.verify_bitwise_xor_12,
-            &quotient_constants.blake.verify_bitwise_xor_12,
+            &poseidon_context.poseidon_round_keys,
+            &query_result.poseidon.round_keys,
+            &quotient_constants.poseidon.round_keys,
             idx,
             &oods_point_y,
         );
-
-        // range_check_128_builtin
         update(
             answer_accumulator,
             &domain_points,
             &denominator_inverses_with_oods_point,
-            &claim.builtins.range_check_128_builtin_log_size,
-            &query_result.range_check_128_builtin,
-            &quotient_constants.range_check_128_builtin,
+            &poseidon_context.range_check_252_width_27,
+            &query_result.poseidon.range_check_252_width_27,
+            &quotient_constants.poseidon.range_check_252_width_27,
             idx,
             &oods_point_y,
         );
@@ -1219,16 +1442,24 @@ pub fn compute_interaction_answers_without_shift(
         );
 
         // memory_id_to_big_big
-        update(
-            answer_accumulator,
-            &domain_points,
-            &denominator_inverses_with_oods_point,
-            &claim.memory_id_to_value.big_log_size,
-            &query_result.memory_id_to_big_big,
-            &quotient_constants.memory_id_to_big_big,
-            idx,
-            &oods_point_y,
-        );
+        for ((log_size, query), constants) in claim
+            .memory_id_to_value
+            .big_log_sizes
+            .iter()
+            .zip_eq(query_result.memory_id_to_big_big.iter())
+            .zip_eq(quotient_constants.memory_id_to_big_big.iter())
+        {
+            update(
+                answer_accumulator,
+                &domain_points,
+                &denominator_inverses_with_oods_point,
+                log_size,
+                query,
+                constants,
+                idx,
+                &oods_point_y,
+            );
+        }
 
         // memory_id_to_big_small
         update(
@@ -1663,203 +1894,209 @@ pub fn compute_interaction_answers_shift_only(
         let answer_accumulator = &mut answer_accumulator[idx];
         let query_result = &query_result[idx].interaction_query_result;
 
+        macro_rules! update_instances {
+            ($name:ident) => {
+                for ((log_size, query), constants) in claim
+                    .opcode_claim
+                    .$name
+                    .iter()
+                    .zip_eq(query_result.opcodes.$name.iter())
+                    .zip_eq(quotient_constants.opcodes.$name.iter())
+                {
+                    update(
+                        answer_accumulator,
+                        &domain_points,
+                        log_size,
+                        query,
+                        constants,
+                        idx,
+                    );
+                }
+            };
+        }
+
         // opcodes
+        update_instances!(add);
+        update_instances!(add_small);
+        update_instances!(add_ap);
+        update_instances!(assert_eq);
+        update_instances!(assert_eq_imm);
+        update_instances!(assert_eq_double_deref);
+        update_instances!(blake);
+        update_instances!(call);
+        update_instances!(call_rel_imm);
+        update_instances!(generic);
+        update_instances!(jnz);
+        update_instances!(jnz_taken);
+        update_instances!(jump);
+        update_instances!(jump_double_deref);
+        update_instances!(jump_rel);
+        update_instances!(jump_rel_imm);
+        update_instances!(mul);
+        update_instances!(mul_small);
+        update_instances!(qm31);
+        update_instances!(ret);
+
+        // verify_instruction
         update(
             answer_accumulator,
             &domain_points,
-            &claim.opcode_claim.add,
-            &query_result.opcodes.add,
-            &quotient_constants.opcodes.add,
-            idx,
-        );
-        update(
-            answer_accumulator,
-            &domain_points,
-            &claim.opcode_claim.add_small,
-            &query_result.opcodes.add_small,
-            &quotient_constants.opcodes.add_small,
-            idx,
-        );
-        update(
-            answer_accumulator,
-            &domain_points,
-            &claim.opcode_claim.add_ap,
-            &query_result.opcodes.add_ap,
-            &quotient_constants.opcodes.add_ap,
-            idx,
-        );
-        update(
-            answer_accumulator,
-            &domain_points,
-            &claim.opcode_claim.assert_eq,
-            &query_result.opcodes.assert_eq,
-            &quotient_constants.opcodes.assert_eq,
-            idx,
-        );
-        update(
-            answer_accumulator,
-            &domain_points,
-            &claim.opcode_claim.assert_eq_imm,
-            &query_result.opcodes.assert_eq_imm,
-            &quotient_constants.opcodes.assert_eq_imm,
-            idx,
-        );
-        update(
-            answer_accumulator,
-            &domain_points,
-            &claim.opcode_claim.assert_eq_double_deref,
-            &query_result.opcodes.assert_eq_double_deref,
-            &quotient_constants.opcodes.assert_eq_double_deref,
-            idx,
-        );
-        update(
-            answer_accumulator,
-            &domain_points,
-            &claim.opcode_claim.blake,
-            &query_result.opcodes.blake,
-            &quotient_constants.opcodes.blake,
+            &claim.verify_instruction,
+            &query_result.verify_instruction,
+            &quotient_constants.verify_instruction,
             idx,
         );
+
+        // blake
         update(
             answer_accumulator,
             &domain_points,
-            &claim.opcode_claim.call,
-            &query_result.opcodes.call,
-            &quotient_constants.opcodes.call,
+            &claim.blake_context.blake_round,
+            &query_result.blake.round,
+            &quotient_constants.blake.round,
             idx,
         );
         update(
             answer_accumulator,
             &domain_points,
-            &claim.opcode_claim.call_rel_imm,
-            &query_result.opcodes.call_rel_imm,
-            &quotient_constants.opcodes.call_rel_imm,
+            &claim.blake_context.blake_g,
+            &query_result.blake.g,
+            &quotient_constants.blake.g,
             idx,
         );
-        update(
+        update_fixed_log_size(
             answer_accumulator,
             &domain_points,
-            &claim.opcode_claim.jnz,
-            &query_result.opcodes.jnz,
-            &quotient_constants.opcodes.jnz,
+            cairo_air::components::blake_round_sigma::LOG_SIZE,
+            &query_result.blake.sigma,
+            &quotient_constants.blake.sigma,
             idx,
         );
         update(
             answer_accumulator,
             &domain_points,
-            &claim.opcode_claim.jnz_taken,
-            &query_result.opcodes.jnz_taken,
-            &quotient_constants.opcodes.jnz_taken,
+            &claim.blake_context.triple_xor_32,
+            &query_result.blake.triple_xor_32,
+            &quotient_constants.blake.triple_xor_32,
             idx,
         );
-        update(
+        update_fixed_log_size(
             answer_accumulator,
             &domain_points,
-            &claim.opcode_claim.jump_rel,
-            &query_result.opcodes.jump_rel,
-            &quotient_constants.opcodes.jump_rel,
+            cairo_air::components::verify_bitwise_xor_12::LOG_SIZE,
+            &query_result.blake.verify_bitwise_xor_12,
+            &quotient_constants.blake.verify_bitwise_xor_12,
             idx,
         );
+
+        // add_mod_builtin
         update(
             answer_accumulator,
             &domain_points,
-            &claim.opcode_claim.jump_rel_imm,
-            &query_result.opcodes.jump_rel_imm,
-            &quotient_constants.opcodes.jump_rel_imm,
+            &claim.builtins.add_mod_builtin.log_size,
+            &query_result.add_mod_builtin,
+            &quotient_constants.add_mod_builtin,
             idx,
         );
+
+        // bitwise_builtin
         update(
             answer_accumulator,
             &domain_points,
-            &claim.opcode_claim.mul,
-            &query_result.opcodes.mul,
-            &quotient_constants.opcodes.mul,
+            &claim.builtins.bitwise_builtin.log_size,
+            &query_result.bitwise_builtin,
+            &quotient_constants.bitwise_builtin,
             idx,
         );
+
+        // mul_mod_builtin
         update(
             answer_accumulator,
             &domain_points,
-            &claim.opcode_claim.mul_small,
-            &query_result.opcodes.mul_small,
-            &quotient_constants.opcodes.mul_small,
+            &claim.builtins.mul_mod_builtin.log_size,
+            &query_result.mul_mod_builtin,
+            &quotient_constants.mul_mod_builtin,
             idx,
         );
+
+        // poseidon_builtin
         update(
             answer_accumulator,
             &domain_points,
-            &claim.opcode_claim.qm31,
-            &query_result.opcodes.qm31,
-            &quotient_constants.opcodes.qm31,
+            &claim.builtins.poseidon_builtin.log_size,
+            &query_result.poseidon_builtin,
+            &quotient_constants.poseidon_builtin,
             idx,
         );
+
+        // range_check_96_builtin
         update(
             answer_accumulator,
             &domain_points,
-            &claim.opcode_claim.ret,
-            &query_result.opcodes.ret,
-            &quotient_constants.opcodes.ret,
+            &claim.builtins.range_check_96_builtin.log_size,
+            &query_result.range_check_96_builtin,
+            &quotient_constants.range_check_96_builtin,
             idx,
         );
 
-        // verify_instruction
+        // range_check_128_builtin
         update(
             answer_accumulator,
             &domain_points,
-            &claim.verify_instruction,
-            &query_result.verify_instruction,
-            &quotient_constants.verify_instruction,
+            &claim.builtins.range_check_128_builtin_log_size,
+            &query_result.range_check_128_builtin,
+            &quotient_constants.range_check_128_builtin,
             idx,
         );
 
-        // blake
+        // poseidon
+        let poseidon_context = &claim.poseidon_context;
         update(
             answer_accumulator,
             &domain_points,
-            &claim.blake_context.blake_round,
-            &query_result.blake.round,
-            &quotient_constants.blake.round,
+            &poseidon_context.poseidon_aggregator,
+            &query_result.poseidon.aggregator,
+            &quotient_constants.poseidon.aggregator,
             idx,
         );
         update(
             answer_accumulator,
             &domain_points,
-            &claim.blake_context.blake_g,
-            &query_result.blake.g,
-            &quotient_constants.blake.g,
+            &poseidon_context.poseidon_3_partial_rounds_chain,
+            &query_result.poseidon.partial_rounds_chain,
+            &quotient_constants.poseidon.partial_rounds_chain,
             idx,
         );
-        update_fixed_log_size(
+        update(
             answer_accumulator,
             &domain_points,
-            cairo_air::components::blake_round_sigma::LOG_SIZE,
-            &query_result.blake.sigma,
-            &quotient_constants.blake.sigma,
+            &poseidon_context.poseidon_full_round_chain,
+            &query_result.poseidon.full_round_chain,
+            &quotient_constants.poseidon.full_round_chain,
             idx,
         );
         update(
             answer_accumulator,
             &domain_points,
-            &claim.blake_context.triple_xor_32,
-            &query_result.blake.triple_xor_32,
-            &quotient_constants.blake.triple_xor_32,
+            &poseidon_context.cube_252,
+            &query_result.poseidon.cube_252,
+            &quotient_constants.poseidon.cube_252,
             idx,
         );
-        update_fixed_log_size(
+        update(
             answer_accumulator,
             &domain_points,
-            cairo_air::components::verify_bitwise_xor_12::LOG_SIZE,
-            &query_result.blake.verify_bitwise_xor_12,
-            &quotient_constants.blake.verify_bitwise_xor_12,
+            &poseidon_context.poseidon_round_keys,
+            &query_result.poseidon.round_keys,
+            &quotient_constants.poseidon.round_keys,
             idx,
         );
-
-        // range_check_128_builtin
         update(
             answer_accumulator,
             &domain_points,
-            &claim.builtins.range_check_128_builtin_log_size,
-            &query_result.range_check_128_builtin,
-            &quotient_constants.range_check_128_builtin,
+            &poseidon_context.range_check_252_width_27,
+            &query_result.poseidon.range_check_252_width_27,
+            &quotient_constants.poseidon.range_check_252_width_27,
             idx,
         );
 
@@ -1874,14 +2111,22 @@ pub fn compute_interaction_answers_shift_only(
         );
 
         // memory_id_to_big_big
-        update(
-            answer_accumulator,
-            &domain_points,
-            &claim.memory_id_to_value.big_log_size,
-            &query_result.memory_id_to_big_big,
-            &quotient_constants.memory_id_to_big_big,
-            idx,
-        );
+        for ((log_size, query), constants) in claim
+            .memory_id_to_value
+            .big_log_sizes
+            .iter()
+            .zip_eq(query_result.memory_id_to_big_big.iter())
+            .zip_eq(quotient_constants.memory_id_to_big_big.iter())
+        {
+            update(
+                answer_accumulator,
+                &domain_points,
+                log_size,
+                query,
+                constants,
+                idx,
+            );
+        }
 
         // memory_id_to_big_small
         update(
@@ -1,5 +1,5 @@
 use cairo_air::components;
-use cairo_plonk_dsl_data_structures::CairoClaimVar;
+use cairo_plonk_dsl_data_structures::{CairoClaimVar, OpcodeClaimVar};
 use cairo_plonk_dsl_decommitment::CairoDecommitmentResultsVar;
 use circle_plonk_dsl_constraint_system::{var::Var, ConstraintSystemRef};
 use circle_plonk_dsl_primitives::{
@@ -19,10 +19,16 @@ pub struct TraceSampleResultVar {
     pub opcodes: OpcodesTraceSampleResultVar,
     pub verify_instruction: [QM31Var; components::verify_instruction::N_TRACE_COLUMNS],
     pub blake: BlakeTraceSampleResultVar,
+    pub add_mod_builtin: [QM31Var; components::add_mod_builtin::N_TRACE_COLUMNS],
+    pub bitwise_builtin: [QM31Var; components::bitwise_builtin::N_TRACE_COLUMNS],
+    pub mul_mod_builtin: [QM31Var; components::mul_mod_builtin::N_TRACE_COLUMNS],
+    pub poseidon_builtin: [QM31Var; components::poseidon_builtin::N_TRACE_COLUMNS],
+    pub range_check_96_builtin: [QM31Var; components::range_check_builtin_bits_96::N_TRACE_COLUMNS],
     pub range_check_128_builtin:
         [QM31Var; components::range_check_builtin_bits_128::N_TRACE_COLUMNS],
+    pub poseidon: PoseidonTraceSampleResultVar,
     pub memory_address_to_id: [QM31Var; components::memory_address_to_id::N_TRACE_COLUMNS],
-    pub memory_id_to_big_big: [QM31Var; components::memory_id_to_big::BIG_N_COLUMNS],
+    pub memory_id_to_big_big: Vec<[QM31Var; components::memory_id_to_big::BIG_N_COLUMNS]>,
     pub memory_id_to_big_small: [QM31Var; components::memory_id_to_big::SMALL_N_COLUMNS],
     pub range_checks: RangeChecksTraceSampleResultVar,
     pub verify_bitwise: VerifyBitwiseTraceSampleResultVar,
@@ -36,26 +42,51 @@ fn extract_array<const N: usize>(slice: &[&QM31Var], offset: &mut usize) -> [QM3
 }
 
 impl TraceSampleResultVar {
-    pub fn new(cs: &ConstraintSystemRef, sampled_values: &Vec<Vec<QM31Var>>) -> Self {
+    pub fn new(
+        cs: &ConstraintSystemRef,
+        sampled_values: &Vec<Vec<QM31Var>>,
+        claim: &CairoClaimVar,
+    ) -> Self {
         let sampled_values: Vec<&QM31Var> = sampled_values.iter().map(|v| &v[0]).collect();
         let mut offset = 0;
 
         // Allocate in the exact order as defined in TraceSampleResultVar
-        let opcodes = allocate_opcodes(cs, &sampled_values, &mut offset);
+        let opcodes = allocate_opcodes(cs, &sampled_values, &mut offset, &claim.opcode_claim);
         let verify_instruction = extract_array::<{ components::verify_instruction::N_TRACE_COLUMNS }>(
             &sampled_values,
             &mut offset,
         );
         let blake = allocate_blake(cs, &sampled_values, &mut offset);
+        let add_mod_builtin = extract_array::<{ components::add_mod_builtin::N_TRACE_COLUMNS }>(
+            &sampled_values,
+            &mut offset,
+        );
+        let bitwise_builtin = extract_array::<{ components::bitwise_builtin::N_TRACE_COLUMNS }>(
+            &sampled_values,
+            &mut offset,
+        );
+        let mul_mod_builtin = extract_array::<{ components::mul_mod_builtin::N_TRACE_COLUMNS }>(
+            &sampled_values,
+            &mut offset,
+        );
+        let poseidon_builtin = extract_array::<{ components::poseidon_builtin::N_TRACE_COLUMNS }>(
+            &sampled_values,
+            &mut offset,
+        );
+        let range_check_96_builtin = extract_array::<
+            { components::range_check_builtin_bits_96::N_TRACE_COLUMNS },
+        >(&sampled_values, &mut offset);
         let range_check_128_builtin = extract_array::<
             { components::range_check_builtin_bits_128::N_TRACE_COLUMNS },
         >(&sampled_values, &mut offset);
+        let poseidon = allocate_poseidon(cs, &sampled_values, &mut offset);
         let memory_address_to_id = extract_array::<
             { components::memory_address_to_id::N_TRACE_COLUMNS },
         >(&sampled_values, &mut offset);
-        let memory_id_to_big_big = extract_array::<{ components::memory_id_to_big::BIG_N_COLUMNS }>(
+        let memory_id_to_big_big = extract_arrays::<{ components::memory_id_to_big::BIG_N_COLUMNS }>(
             &sampled_values,
             &mut offset,
+            claim.memory_id_to_value.big_log_sizes.log_sizes.len(),
         );
         let memory_id_to_big_small = extract_array::<
             { components::memory_id_to_big::SMALL_N_COLUMNS },
@@ -74,7 +105,13 @@ impl TraceSampleResultVar {
             opcodes,
             verify_instruction,
             blake,
+            add_mod_builtin,
+            bitwise_builtin,
+            mul_mod_builtin,
+            poseidon_builtin,
+            range_check_96_builtin,
             range_check_128_builtin,
+            poseidon,
             memory_address_to_id,
             memory_id_to_big_big,
             memory_id_to_big_small,
@@ -84,49 +121,125 @@ impl TraceSampleResultVar {
     }
 }
 
-/// Allocate OpcodesTraceSampleResultVar from slice
+/// Helper function to extract one fixed-size array per instance of a component from a slice
+fn extract_arrays<const N: usize>(
+    slice: &[&QM31Var],
+    offset: &mut usize,
+    n_instances: usize,
+) -> Vec<[QM31Var; N]> {
+    (0..n_instances)
+        .map(|_| extract_array::<N>(slice, offset))
+        .collect()
+}
+
+/// Allocate OpcodesTraceSampleResultVar from slice, with one entry per instance of each opcode in
+/// the claim
 fn allocate_opcodes(
     cs: &ConstraintSystemRef,
     slice: &[&QM31Var],
     offset: &mut usize,
+    claim: &OpcodeClaimVar,
 ) -> OpcodesTraceSampleResultVar {
     OpcodesTraceSampleResultVar {
         cs: cs.clone(),
-        add: extract_array::<{ components::add_opcode::N_TRACE_COLUMNS }>(slice, offset),
-        add_small: extract_array::<{ components::add_opcode_small::N_TRACE_COLUMNS }>(
-            slice, offset,
+        add: extract_arrays::<{ components::add_opcode::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.add.log_sizes.len(),
         ),
-        add_ap: extract_array::<{ components::add_ap_opcode::N_TRACE_COLUMNS }>(slice, offset),
-        assert_eq: extract_array::<{ components::assert_eq_opcode::N_TRACE_COLUMNS }>(
-            slice, offset,
+        add_small: extract_arrays::<{ components::add_opcode_small::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.add_small.log_sizes.len(),
         ),
-        assert_eq_imm: extract_array::<{ components::assert_eq_opcode_imm::N_TRACE_COLUMNS }>(
-            slice, offset,
+        add_ap: extract_arrays::<{ components::add_ap_opcode::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.add_ap.log_sizes.len(),
+        ),
+        assert_eq: extract_arrays::<{ components::assert_eq_opcode::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.assert_eq.log_sizes.len(),
+        ),
+        assert_eq_imm: extract_arrays::<{ components::assert_eq_opcode_imm::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.assert_eq_imm.log_sizes.len(),
         ),
-        assert_eq_double_deref: extract_array::<
+        assert_eq_double_deref: extract_arrays::<
             { components::assert_eq_opcode_double_deref::N_TRACE_COLUMNS },
-        >(slice, offset),
-        blake: extract_array::<{ components::blake_compress_opcode::N_TRACE_COLUMNS }>(
-            slice, offset,
+        >(
+            slice, offset, claim.assert_eq_double_deref.log_sizes.len()
         ),
-        call: extract_array::<{ components::call_opcode_abs::N_TRACE_COLUMNS }>(slice, offset),
-        call_rel_imm: extract_array::<{ components::call_opcode_rel_imm::N_TRACE_COLUMNS }>(
-            slice, offset,
+        blake: extract_arrays::<{ components::blake_compress_opcode::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.blake.log_sizes.len(),
         ),
-        jnz: extract_array::<{ components::jnz_opcode_non_taken::N_TRACE_COLUMNS }>(slice, offset),
-        jnz_taken: extract_array::<{ components::jnz_opcode_taken::N_TRACE_COLUMNS }>(
-            slice, offset,
+        call: extract_arrays::<{ components::call_opcode_abs::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.call.log_sizes.len(),
         ),
-        jump_rel: extract_array::<{ components::jump_opcode_rel::N_TRACE_COLUMNS }>(slice, offset),
-        jump_rel_imm: extract_array::<{ components::jump_opcode_rel_imm::N_TRACE_COLUMNS }>(
-            slice, offset,
+        call_rel_imm: extract_arrays::<{ components::call_opcode_rel_imm::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.call_rel_imm.log_sizes.len(),
         ),
-        mul: extract_array::<{ components::mul_opcode::N_TRACE_COLUMNS }>(slice, offset),
-        mul_small: extract_array::<{ components::mul_opcode_small::N_TRACE_COLUMNS }>(
-            slice, offset,
+        generic: extract_arrays::<{ components::generic_opcode::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.generic.log_sizes.len(),
+        ),
+        jnz: extract_arrays::<{ components::jnz_opcode_non_taken::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.jnz.log_sizes.len(),
+        ),
+        jnz_taken: extract_arrays::<{ components::jnz_opcode_taken::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.jnz_taken.log_sizes.len(),
+        ),
+        jump: extract_arrays::<{ components::jump_opcode_abs::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.jump.log_sizes.len(),
+        ),
+        jump_double_deref: extract_arrays::<
+            { components::jump_opcode_double_deref::N_TRACE_COLUMNS },
+        >(slice, offset, claim.jump_double_deref.log_sizes.len()),
+        jump_rel: extract_arrays::<{ components::jump_opcode_rel::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.jump_rel.log_sizes.len(),
+        ),
+        jump_rel_imm: extract_arrays::<{ components::jump_opcode_rel_imm::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.jump_rel_imm.log_sizes.len(),
+        ),
+        mul: extract_arrays::<{ components::mul_opcode::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.mul.log_sizes.len(),
+        ),
+        mul_small: extract_arrays::<{ components::mul_opcode_small::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.mul_small.log_sizes.len(),
+        ),
+        qm31: extract_arrays::<{ components::qm_31_add_mul_opcode::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.qm31.log_sizes.len(),
+        ),
+        ret: extract_arrays::<{ components::ret_opcode::N_TRACE_COLUMNS }>(
+            slice,
+            offset,
+            claim.ret.log_sizes.len(),
         ),
-        qm31: extract_array::<{ components::qm_31_add_mul_opcode::N_TRACE_COLUMNS }>(slice, offset),
-        ret: extract_array::<{ components::ret_opcode::N_TRACE_COLUMNS }>(slice, offset),
     }
 }
 
@@ -150,6 +263,33 @@ fn allocate_blake(
     }
 }
 
+/// Allocate PoseidonTraceSampleResultVar from slice
+fn allocate_poseidon(
+    cs: &ConstraintSystemRef,
+    slice: &[&QM31Var],
+    offset: &mut usize,
+) -> PoseidonTraceSampleResultVar {
+    PoseidonTraceSampleResultVar {
+        cs: cs.clone(),
+        aggregator: extract_array::<{ components::poseidon_aggregator::N_TRACE_COLUMNS }>(
+            slice, offset,
+        ),
+        partial_rounds_chain: extract_array::<
+            { components::poseidon_3_partial_rounds_chain::N_TRACE_COLUMNS },
+        >(slice, offset),
+        full_round_chain: extract_array::<{ components::poseidon_full_round_chain::N_TRACE_COLUMNS }>(
+            slice, offset,
+        ),
+        cube_252: extract_array::<{ components::cube_252::N_TRACE_COLUMNS }>(slice, offset),
+        round_keys: extract_array::<{ components::poseidon_round_keys::N_TRACE_COLUMNS }>(
+            slice, offset,
+        ),
+        range_check_252_width_27: extract_array::<
+            { components::range_check_252_width_27::N_TRACE_COLUMNS },
+        >(slice, offset),
+    }
+}
+
 /// Allocate RangeChecksTraceSampleResultVar from slice
 fn allocate_range_checks(
     cs: &ConstraintSystemRef,
@@ -276,24 +416,27 @@ fn allocate_verify_bitwise(
 
 pub struct OpcodesTraceSampleResultVar {
     pub cs: ConstraintSystemRef,
-    pub add: [QM31Var; components::add_opcode::N_TRACE_COLUMNS],
-    pub add_small: [QM31Var; components::add_opcode_small::N_TRACE_COLUMNS],
-    pub add_ap: [QM31Var; components::add_ap_opcode::N_TRACE_COLUMNS],
-    pub assert_eq: [QM31Var; components::assert_eq_opcode::N_TRACE_COLUMNS],
-    pub assert_eq_imm: [QM31Var; components::assert_eq_opcode_imm::N_TRACE_COLUMNS],
+    pub add: Vec<[QM31Var; components::add_opcode::N_TRACE_COLUMNS]>,
+    pub add_small: Vec<[QM31Var; components::add_opcode_small::N_TRACE_COLUMNS]>,
+    pub add_ap: Vec<[QM31Var; components::add_ap_opcode::N_TRACE_COLUMNS]>,
+    pub assert_eq: Vec<[QM31Var; components::assert_eq_opcode::N_TRACE_COLUMNS]>,
+    pub assert_eq_imm: Vec<[QM31Var; components::assert_eq_opcode_imm::N_TRACE_COLUMNS]>,
     pub assert_eq_double_deref:
-        [QM31Var; components::assert_eq_opcode_double_deref::N_TRACE_COLUMNS],
-    pub blake: [QM31Var; components::blake_compress_opcode::N_TRACE_COLUMNS],
-    pub call: [QM31Var; components::call_opcode_abs::N_TRACE_COLUMNS],
-    pub call_rel_imm: [QM31Var; components::call_opcode_rel_imm::N_TRACE_COLUMNS],
-    pub jnz: [QM31Var; components::jnz_opcode_non_taken::N_TRACE_COLUMNS],
-    pub jnz_taken: [QM31Var; components::jnz_opcode_taken::N_TRACE_COLUMNS],
-    pub jump_rel: [QM31Var; components::jump_opcode_rel::N_TRACE_COLUMNS],
-    pub jump_rel_imm: [QM31Var; components::jump_opcode_rel_imm::N_TRACE_COLUMNS],
-    pub mul: [QM31Var; components::mul_opcode::N_TRACE_COLUMNS],
-    pub mul_small: [QM31Var; components::mul_opcode_small::N_TRACE_COLUMNS],
-    pub qm31: [QM31Var; components::qm_31_add_mul_opcode::N_TRACE_COLUMNS],
-    pub ret: [QM31Var; components::ret_opcode::N_TRACE_COLUMNS],
+        Vec<[QM31Var; components::assert_eq_opcode_double_deref::N_TRACE_COLUMNS]>,
+    pub blake: Vec<[QM31Var; components::blake_compress_opcode::N_TRACE_COLUMNS]>,
+    pub call: Vec<[QM31Var; components::call_opcode_abs::N_TRACE_COLUMNS]>,
+    pub call_rel_imm: Vec<[QM31Var; components::call_opcode_rel_imm::N_TRACE_COLUMNS]>,
+    pub generic: Vec<[QM31Var; components::generic_opcode::N_TRACE_COLUMNS]>,
+    pub jnz: Vec<[QM31Var; components::jnz_opcode_non_taken::N_TRACE_COLUMNS]>,
+    pub jnz_taken: Vec<[QM31Var; components::jnz_opcode_taken::N_TRACE_COLUMNS]>,
+    pub jump: Vec<[QM31Var; components::jump_opcode_abs::N_TRACE_COLUMNS]>,
+    pub jump_double_deref: Vec<[QM31Var; components::jump_opcode_double_deref::N_TRACE_COLUMNS]>,
+    pub jump_rel: Vec<[QM31Var; components::jump_opcode_rel::N_TRACE_COLUMNS]>,
+    pub jump_rel_imm: Vec<[QM31Var; components::jump_opcode_rel_imm::N_TRACE_COLUMNS]>,
+    pub mul: Vec<[QM31Var; components::mul_opcode::N_TRACE_COLUMNS]>,
+    pub mul_small: Vec<[QM31Var; components::mul_opcode_small::N_TRACE_COLUMNS]>,
+    pub qm31: Vec<[QM31Var; components::qm_31_add_mul_opcode::N_TRACE_COLUMNS]>,
+    pub ret: Vec<[QM31Var; components::ret_opcode::N_TRACE_COLUMNS]>,
 }
 
 pub struct BlakeTraceSampleResultVar {
@@ -305,6 +448,17 @@ pub struct BlakeTraceSampleResultVar {
     pub verify_bitwise_xor_12: [QM31Var; components::verify_bitwise_xor_12::N_TRACE_COLUMNS],
 }
 
+pub struct PoseidonTraceSampleResultVar {
+    pub cs: ConstraintSystemRef,
+    pub aggregator: [QM31Var; components::poseidon_aggregator::N_TRACE_COLUMNS],
+    pub partial_rounds_chain:
+        [QM31Var; components::poseidon_3_partial_rounds_chain::N_TRACE_COLUMNS],
+    pub full_round_chain: [QM31Var; components::poseidon_full_round_chain::N_TRACE_COLUMNS],
+    pub cube_252: [QM31Var; components::cube_252::N_TRACE_COLUMNS],
+    pub round_keys: [QM31Var; components::poseidon_round_keys::N_TRACE_COLUMNS],
+    pub range_check_252_width_27: [QM31Var; components::range_check_252_width_27::N_TRACE_COLUMNS],
+}
+
 pub struct RangeChecksTraceSampleResultVar {
     pub cs: ConstraintSystemRef,
     pub range_check_6: [QM31Var; components::range_check_6::N_TRACE_COLUMNS],
@@ -354,10 +508,17 @@ pub struct TraceQuotientConstantsVar {
     pub opcodes: OpcodesTraceQuotientConstantsVar,
     pub verify_instruction: [[CM31Var; 2]; components::verify_instruction::N_TRACE_COLUMNS],
     pub blake: BlakeTraceQuotientConstantsVar,
+    pub add_mod_builtin: [[CM31Var; 2]; components::add_mod_builtin::N_TRACE_COLUMNS],
+    pub bitwise_builtin: [[CM31Var; 2]; components::bitwise_builtin::N_TRACE_COLUMNS],
+    pub mul_mod_builtin: [[CM31Var; 2]; components::mul_mod_builtin::N_TRACE_COLUMNS],
+    pub poseidon_builtin: [[CM31Var; 2]; components::poseidon_builtin::N_TRACE_COLUMNS],
+    pub range_check_96_builtin:
+        [[CM31Var; 2]; components::range_check_builtin_bits_96::N_TRACE_COLUMNS],
     pub range_check_128_builtin:
         [[CM31Var; 2]; components::range_check_builtin_bits_128::N_TRACE_COLUMNS],
+    pub poseidon: PoseidonTraceQuotientConstantsVar,
     pub memory_address_to_id: [[CM31Var; 2]; components::memory_address_to_id::N_TRACE_COLUMNS],
-    pub memory_id_to_big_big: [[CM31Var; 2]; components::memory_id_to_big::BIG_N_COLUMNS],
+    pub memory_id_to_big_big: Vec<[[CM31Var; 2]; components::memory_id_to_big::BIG_N_COLUMNS]>,
     pub memory_id_to_big_small: [[CM31Var; 2]; components::memory_id_to_big::SMALL_N_COLUMNS],
     pub range_checks: RangeChecksTraceQuotientConstantsVar,
     pub verify_bitwise: VerifyBitwiseTraceQuotientConstantsVar,
@@ -373,24 +534,46 @@ impl TraceQuotientConstantsVar {
                 complex_conjugate_line_coeffs_var(oods_point, &sample_result.verify_instruction[i])
             }),
             blake: BlakeTraceQuotientConstantsVar::new(oods_point, &sample_result.blake),
-            range_check_128_builtin: std::array::from_fn(|i| {
+            add_mod_builtin: std::array::from_fn(|i| {
+                complex_conjugate_line_coeffs_var(oods_point, &sample_result.add_mod_builtin[i])
+            }),
+            bitwise_builtin: std::array::from_fn(|i| {
+                complex_conjugate_line_coeffs_var(oods_point, &sample_result.bitwise_builtin[i])
+            }),
+            mul_mod_builtin: std::array::from_fn(|i| {
+                complex_conjugate_line_coeffs_var(oods_point, &sample_result.mul_mod_builtin[i])
+            }),
+            poseidon_builtin: std::array::from_fn(|i| {
+                complex_conjugate_line_coeffs_var(oods_point, &sample_result.poseidon_builtin[i])
+            }),
+            range_check_96_builtin: std::array::from_fn(|i| {
                 complex_conjugate_line_coeffs_var(
                     oods_point,
-                    &sample_result.range_check_128_builtin[i],
+                    &sample_result.range_check_96_builtin[i],
                 )
             }),
-            memory_address_to_id: std::array::from_fn(|i| {
+            range_check_128_builtin: std::array::from_fn(|i| {
                 complex_conjugate_line_coeffs_var(
                     oods_point,
-                    &sample_result.memory_address_to_id[i],
+                    &sample_result.range_check_128_builtin[i],
                 )
             }),
-            memory_id_to_big_big: std::array::from_fn(|i| {
+            poseidon: PoseidonTraceQuotientConstantsVar::new(oods_point, &sample_result.poseidon),
+            memory_address_to_id: std::array::from_fn(|i| {
                 complex_conjugate_line_coeffs_var(
                     oods_point,
-                    &sample_result.memory_id_to_big_big[i],
+                    &sample_result.memory_address_to_id[i],
                 )
             }),
+            memory_id_to_big_big: sample_result
+                .memory_id_to_big_big
+                .iter()
+                .map(|sampled_values| {
+                    std::array::from_fn(|i| {
+                        complex_conjugate_line_coeffs_var(oods_point, &sampled_values[i])
+                    })
+                })
+                .collect(),
             memory_id_to_big_small: std::array::from_fn(|i| {
                 complex_conjugate_line_coeffs_var(
                     oods_point,
@@ -411,24 +594,28 @@ impl TraceQuotientConstantsVar {
 
 pub struct OpcodesTraceQuotientConstantsVar {
     pub cs: ConstraintSystemRef,
-    pub add: [[CM31Var; 2]; components::add_opcode::N_TRACE_COLUMNS],
-    pub add_small: [[CM31Var; 2]; components::add_opcode_small::N_TRACE_COLUMNS],
-    pub add_ap: [[CM31Var; 2]; components::add_ap_opcode::N_TRACE_COLUMNS],
-    pub assert_eq: [[CM31Var; 2]; components::assert_eq_opcode::N_TRACE_COLUMNS],
-    pub assert_eq_imm: [[CM31Var; 2]; components::assert_eq_opcode_imm::N_TRACE_COLUMNS],
+    pub add: Vec<[[CM31Var; 2]; components::add_opcode::N_TRACE_COLUMNS]>,
+    pub add_small: Vec<[[CM31Var; 2]; components::add_opcode_small::N_TRACE_COLUMNS]>,
+    pub add_ap: Vec<[[CM31Var; 2]; components::add_ap_opcode::N_TRACE_COLUMNS]>,
+    pub assert_eq: Vec<[[CM31Var; 2]; components::assert_eq_opcode::N_TRACE_COLUMNS]>,
+    pub assert_eq_imm: Vec<[[CM31Var; 2]; components::assert_eq_opcode_imm::N_TRACE_COLUMNS]>,
     pub assert_eq_double_deref:
-        [[CM31Var; 2]; components::assert_eq_opcode_double_deref::N_TRACE_COLUMNS],
-    pub blake: [[CM31Var; 2]; components::blake_compress_opcode::N_TRACE_COLUMNS],
-    pub call: [[CM31Var; 2]; components::call_opcode_abs::N_TRACE_COLUMNS],
-    pub call_rel_imm: [[CM31Var; 2]; components::call_opcode_rel_imm::N_TRACE_COLUMNS],
-    pub jnz: [[CM31Var; 2]; components::jnz_opcode_non_taken::N_TRACE_COLUMNS],
-    pub jnz_taken: [[CM31Var; 2]; components::jnz_opcode_taken::N_TRACE_COLUMNS],
-    pub jump_rel: [[CM31Var; 2]; components::jump_opcode_rel::N_TRACE_COLUMNS],
-    pub jump_rel_imm: [[CM31Var; 2]; components::jump_opcode_rel_imm::N_TRACE_COLUMNS],
-    pub mul: [[CM31Var; 2]; components::mul_opcode::N_TRACE_COLUMNS],
-    pub mul_small: [[CM31Var; 2]; components::mul_opcode_small::N_TRACE_COLUMNS],
-    pub qm31: [[CM31Var; 2]; components::qm_31_add_mul_opcode::N_TRACE_COLUMNS],
-    pub ret: [[CM31Var; 2]; components::ret_opcode::N_TRACE_COLUMNS],
+        Vec<[[CM31Var; 2]; components::assert_eq_opcode_double_deref::N_TRACE_COLUMNS]>,
+    pub blake: Vec<[[CM31Var; 2]; components::blake_compress_opcode::N_TRACE_COLUMNS]>,
+    pub call: Vec<[[CM31Var; 2]; components::call_opcode_abs::N_TRACE_COLUMNS]>,
+    pub call_rel_imm: Vec<[[CM31Var; 2]; components::call_opcode_rel_imm::N_TRACE_COLUMNS]>,
+    pub generic: Vec<[[CM31Var; 2]; components::generic_opcode::N_TRACE_COLUMNS]>,
+    pub jnz: Vec<[[CM31Var; 2]; components::jnz_opcode_non_taken::N_TRACE_COLUMNS]>,
+    pub jnz_taken: Vec<[[CM31Var; 2]; components::jnz_opcode_taken::N_TRACE_COLUMNS]>,
+    pub jump: Vec<[[CM31Var; 2]; components::jump_opcode_abs::N_TRACE_COLUMNS]>,
+    pub jump_double_deref:
+        Vec<[[CM31Var; 2]; components::jump_opcode_double_deref::N_TRACE_COLUMNS]>,
+    pub jump_rel: Vec<[[CM31Var; 2]; components::jump_opcode_rel::N_TRACE_COLUMNS]>,
+    pub jump_rel_imm: Vec<[[CM31Var; 2]; components::jump_opcode_rel_imm::N_TRACE_COLUMNS]>,
+    pub mul: Vec<[[CM31Var; 2]; components::mul_opcode::N_TRACE_COLUMNS]>,
+    pub mul_small: Vec<[[CM31Var; 2]; components::mul_opcode_small::N_TRACE_COLUMNS]>,
+    pub qm31: Vec<[[CM31Var; 2]; components::qm_31_add_mul_opcode::N_TRACE_COLUMNS]>,
+    pub ret: Vec<[[CM31Var; 2]; components::ret_opcode::N_TRACE_COLUMNS]>,
 }
 
 impl OpcodesTraceQuotientConstantsVar {
@@ -437,62 +624,43 @@ impl OpcodesTraceQuotientConstantsVar {
         sample_result: &OpcodesTraceSampleResultVar,
     ) -> Self {
         use super::complex_conjugate_line_coeffs_var;
+
+        macro_rules! new_instances {
+            ($name:ident) => {
+                sample_result
+                    .$name
+                    .iter()
+                    .map(|sampled_values| {
+                        std::array::from_fn(|i| {
+                            complex_conjugate_line_coeffs_var(oods_point, &sampled_values[i])
+                        })
+                    })
+                    .collect()
+            };
+        }
+
         Self {
             cs: sample_result.cs.clone(),
-            add: std::array::from_fn(|i| {
-                complex_conjugate_line_coeffs_var(oods_point, &sample_result.add[i])
-            }),
-            add_small: std::array::from_fn(|i| {
-                complex_conjugate_line_coeffs_var(oods_point, &sample_result.add_small[i])
-            }),
-            add_ap: std::array::from_fn(|i| {
-                complex_conjugate_line_coeffs_var(oods_point, &sample_result.add_ap[i])
-            }),
-            assert_eq: std::array::from_fn(|i| {
-                complex_conjugate_line_coeffs_var(oods_point, &sample_result.assert_eq[i])
-            }),
-            assert_eq_imm: std::array::from_fn(|i| {
-                complex_conjugate_line_coeffs_var(oods_point, &sample_result.assert_eq_imm[i])
-            }),
-            assert_eq_double_deref: std::array::from_fn(|i| {
-                complex_conjugate_line_coeffs_var(
-                    oods_point,
-                    &sample_result.assert_eq_double_deref[i],
-                )
-            }),
-            blake: std::array::from_fn(|i| {
-                complex_conjugate_line_coeffs_var(oods_point, &sample_result.blake[i])
-            }),
-            call: std::array::from_fn(|i| {
-                complex_conjugate_line_coeffs_var(oods_point, &sample_result.call[i])
-            }),
-            call_rel_imm: std::array::from_fn(|i| {
-                complex_conjugate_line_coeffs_var(oods_point, &sample_result.call_rel_imm[i])
-            }),
-            jnz: std::array::from_fn(|i| {
-                complex_conjugate_line_coeffs_var(oods_point, &sample_result.jnz[i])
-            }),
-            jnz_taken: std::array::from_fn(|i| {
-                complex_conjugate_line_coeffs_var(oods_point, &sample_result.jnz_taken[i])
-            }),
-            jump_rel: std::array::from_fn(|i| {
-                complex_conjugate_line_coeffs_var(oods_point, &sample_result.jump_rel[i])
-            }),
-            jump_rel_imm: std::array::from_fn(|i| {
-                complex_conjugate_line_coeffs_var(oods_point, &sample_result.jump_rel_imm[i])
-            }),
-            mul: std::array::from_fn(|i| {
-                complex_conjugate_line_coeffs_var(oods_point, &sample_result.mul[i])
-            }),
-            mul_small: std::array::from_fn(|i| {
-                complex_conjugate_line_coeffs_var(oods_point, &sample_result.mul_small[i])
-            }),
-            qm31: std::array::from_fn(|i| {
-                complex_conjugate_line_coeffs_var(oods_point, &sample_result.qm31[i])
-            }),
-            ret: std::array::from_fn(|i| {
-                complex_conjugate_line_coeffs_var(oods_point, &sample_result.ret[i])
-            }),
+            add: new_instances!(add),
+            add_small: new_instances!(add_small),
+            add_ap: new_instances!(add_ap),
+            assert_eq: new_instances!(assert_eq),
+            assert_eq_imm: new_instances!(assert_eq_imm),
+            assert_eq_double_deref: new_instances!(assert_eq_double_deref),
+            blake: new_instances!(blake),
+            call: new_instances!(call),
+            call_rel_imm: new_instances!(call_rel_imm),
+            generic: new_instances!(generic),
+            jnz: new_instances!(jnz),
+            jnz_taken: new_instances!(jnz_taken),
+            jump: new_instances!(jump),
+            jump_double_deref: new_instances!(jump_double_deref),
+            jump_rel: new_instances!(jump_rel),
+            jump_rel_imm: new_instances!(jump_rel_imm),
+            mul: new_instances!(mul),
+            mul_small: new_instances!(mul_small),
+            qm31: new_instances!(qm31),
+            ret: new_instances!(ret),
         }
     }
 }
@@ -533,6 +701,54 @@ impl BlakeTraceQuotientConstantsVar {
     }
 }
 
+pub struct PoseidonTraceQuotientConstantsVar {
+    pub cs: ConstraintSystemRef,
+    pub aggregator: [[CM31Var; 2]; components::poseidon_aggregator::N_TRACE_COLUMNS],
+    pub partial_rounds_chain:
+        [[CM31Var; 2]; components::poseidon_3_partial_rounds_chain::N_TRACE_COLUMNS],
+    pub full_round_chain: [[CM31Var; 2]; components::poseidon_full_round_chain::N_TRACE_COLUMNS],
+    pub cube_252: [[CM31Var; 2]; components::cube_252::N_TRACE_COLUMNS],
+    pub round_keys: [[CM31Var; 2]; components::poseidon_round_keys::N_TRACE_COLUMNS],
+    pub range_check_252_width_27:
+        [[CM31Var; 2]; components::range_check_252_width_27::N_TRACE_COLUMNS],
+}
+
+impl PoseidonTraceQuotientConstantsVar {
+    pub fn new(
+        oods_point: &CirclePointQM31Var,
+        sample_result: &PoseidonTraceSampleResultVar,
+    ) -> Self {
+        use super::complex_conjugate_line_coeffs_var;
+        Self {
+            cs: sample_result.cs.clone(),
+            aggregator: std::array::from_fn(|i| {
+                complex_conjugate_line_coeffs_var(oods_point, &sample_result.aggregator[i])
+            }),
+            partial_rounds_chain: std::array::from_fn(|i| {
+                complex_conjugate_line_coeffs_var(
+                    oods_point,
+                    &sample_result.partial_rounds_chain[i],
+                )
+            }),
+            full_round_chain: std::array::from_fn(|i| {
+                complex_conjugate_line_coeffs_var(oods_point, &sample_result.full_round_chain[i])
+            }),
+            cube_252: std::array::from_fn(|i| {
+                complex_conjugate_line_coeffs_var(oods_point, &sample_result.cube_252[i])
+            }),
+            round_keys: std::array::from_fn(|i| {
+                complex_conjugate_line_coeffs_var(oods_point, &sample_result.round_keys[i])
+            }),
+            range_check_252_width_27: std::array::from_fn(|i| {
+                complex_conjugate_line_coeffs_var(
+                    oods_point,
+                    &sample_result.range_check_252_width_27[i],
+                )
+            }),
+        }
+    }
+}
+
 pub struct RangeChecksTraceQuotientConstantsVar {
     pub cs: ConstraintSystemRef,
     pub range_check_6: [[CM31Var; 2]; components::range_check_6::N_TRACE_COLUMNS],
@@ -784,179 +1000,174 @@ pub fn compute_trace_answers(
         let answer_accumulator = &mut answer_accumulator[idx];
         let query_result = &query_result[idx].trace_query_result;
 
+        macro_rules! update_instances {
+            ($name:ident) => {
+                for ((log_size, query), constants) in claim
+                    .opcode_claim
+                    .$name
+                    .iter()
+                    .zip_eq(query_result.opcodes.$name.iter())
+                    .zip_eq(quotient_constants.opcodes.$name.iter())
+                {
+                    update(answer_accumulator, log_size, query, constants, idx);
+                }
+            };
+        }
+
         // opcodes
+        update_instances!(add);
+        update_instances!(add_small);
+        update_instances!(add_ap);
+        update_instances!(assert_eq);
+        update_instances!(assert_eq_imm);
+        update_instances!(assert_eq_double_deref);
+        update_instances!(blake);
+        update_instances!(call);
+        update_instances!(call_rel_imm);
+        update_instances!(generic);
+        update_instances!(jnz);
+        update_instances!(jnz_taken);
+        update_instances!(jump);
+        update_instances!(jump_double_deref);
+        update_instances!(jump_rel);
+        update_instances!(jump_rel_imm);
+        update_instances!(mul);
+        update_instances!(mul_small);
+        update_instances!(qm31);
+        update_instances!(ret);
+
+        // verify_instruction
         update(
             answer_accumulator,
-            &claim.opcode_claim.add,
-            &query_result.opcodes.add,
-            &quotient_constants.opcodes.add,
-            idx,
-        );
-        update(
-            answer_accumulator,
-            &claim.opcode_claim.add_small,
-            &query_result.opcodes.add_small,
-            &quotient_constants.opcodes.add_small,
-            idx,
-        );
-        update(
-            answer_accumulator,
-            &claim.opcode_claim.add_ap,
-            &query_result.opcodes.add_ap,
-            &quotient_constants.opcodes.add_ap,
-            idx,
-        );
-        update(
-            answer_accumulator,
-            &claim.opcode_claim.assert_eq,
-            &query_result.opcodes.assert_eq,
-            &quotient_constants.opcodes.assert_eq,
-            idx,
-        );
-        update(
-            answer_accumulator,
-            &claim.opcode_claim.assert_eq_imm,
-            &query_result.opcodes.assert_eq_imm,
-            &quotient_constants.opcodes.assert_eq_imm,
-            idx,
-        );
-        update(
-            answer_accumulator,
-            &claim.opcode_claim.assert_eq_double_deref,
-            &query_result.opcodes.assert_eq_double_deref,
-            &quotient_constants.opcodes.assert_eq_double_deref,
+            &claim.verify_instruction,
+            &query_result.verify_instruction,
+            &quotient_constants.verify_instruction,
             idx,
         );
+
+        // blake
         update(
             answer_accumulator,
-            &claim.opcode_claim.blake,
-            &query_result.opcodes.blake,
-            &quotient_constants.opcodes.blake,
+            &claim.blake_context.blake_round,
+            &query_result.blake.round,
+            &quotient_constants.blake.round,
             idx,
         );
         update(
             answer_accumulator,
-            &claim.opcode_claim.call,
-            &query_result.opcodes.call,
-            &quotient_constants.opcodes.call,
+            &claim.blake_context.blake_g,
+            &query_result.blake.g,
+            &quotient_constants.blake.g,
             idx,
         );
-        update(
+        update_fixed_log_size(
             answer_accumulator,
-            &claim.opcode_claim.call_rel_imm,
-            &query_result.opcodes.call_rel_imm,
-            &quotient_constants.opcodes.call_rel_imm,
+            cairo_air::components::blake_round_sigma::LOG_SIZE,
+            &query_result.blake.sigma,
+            &quotient_constants.blake.sigma,
             idx,
         );
         update(
             answer_accumulator,
-            &claim.opcode_claim.jnz,
-            &query_result.opcodes.jnz,
-            &quotient_constants.opcodes.jnz,
+            &claim.blake_context.triple_xor_32,
+            &query_result.blake.triple_xor_32,
+            &quotient_constants.blake.triple_xor_32,
             idx,
         );
-        update(
+        update_fixed_log_size(
             answer_accumulator,
-            &claim.opcode_claim.jnz_taken,
-            &query_result.opcodes.jnz_taken,
-            &quotient_constants.opcodes.jnz_taken,
+            cairo_air::components::verify_bitwise_xor_12::LOG_SIZE,
+            &query_result.blake.verify_bitwise_xor_12,
+            &quotient_constants.blake.verify_bitwise_xor_12,
             idx,
         );
+
+        // builtins
         update(
             answer_accumulator,
-            &claim.opcode_claim.jump_rel,
-            &query_result.opcodes.jump_rel,
-            &quotient_constants.opcodes.jump_rel,
+            &claim.builtins.add_mod_builtin.log_size,
+            &query_result.add_mod_builtin,
+            &quotient_constants.add_mod_builtin,
             idx,
         );
         update(
             answer_accumulator,
-            &claim.opcode_claim.jump_rel_imm,
-            &query_result.opcodes.jump_rel_imm,
-            &quotient_constants.opcodes.jump_rel_imm,
+            &claim.builtins.bitwise_builtin.log_size,
+            &query_result.bitwise_builtin,
+            &quotient_constants.bitwise_builtin,
             idx,
         );
         update(
             answer_accumulator,
-            &claim.opcode_claim.mul,
-            &query_result.opcodes.mul,
-            &quotient_constants.opcodes.mul,
+            &claim.builtins.mul_mod_builtin.log_size,
+            &query_result.mul_mod_builtin,
+            &quotient_constants.mul_mod_builtin,
             idx,
         );
         update(
             answer_accumulator,
-            &claim.opcode_claim.mul_small,
-            &query_result.opcodes.mul_small,
-            &quotient_constants.opcodes.mul_small,
+            &claim.builtins.poseidon_builtin.log_size,
+            &query_result.poseidon_builtin,
+            &quotient_constants.poseidon_builtin,
             idx,
         );
         update(
             answer_accumulator,
-            &claim.opcode_claim.qm31,
-            &query_result.opcodes.qm31,
-            &quotient_constants.opcodes.qm31,
+            &claim.builtins.range_check_96_builtin.log_size,
+            &query_result.range_check_96_builtin,
+            &quotient_constants.range_check_96_builtin,
             idx,
         );
         update(
             answer_accumulator,
-            &claim.opcode_claim.ret,
-            &query_result.opcodes.ret,
-            &quotient_constants.opcodes.ret,
+            &claim.builtins.range_check_128_builtin_log_size,
+            &query_result.range_check_128_builtin,
+            &quotient_constants.range_check_128_builtin,
             idx,
         );
 
-        // verify_instruction
+        // poseidon
+        let poseidon_context = &claim.poseidon_context;
         update(
             answer_accumulator,
-            &claim.verify_instruction,
-            &query_result.verify_instruction,
-            &quotient_constants.verify_instruction,
+            &poseidon_context.poseidon_aggregator,
+            &query_result.poseidon.aggregator,
+            &quotient_constants.poseidon.aggregator,
             idx,
         );
-
-        // blake
         update(
             answer_accumulator,
-            &claim.blake_context.blake_round,
-            &query_result.blake.round,
-            &quotient_constants.blake.round,
+            &poseidon_context.poseidon_3_partial_rounds_chain,
+            &query_result.poseidon.partial_rounds_chain,
+            &quotient_constants.poseidon.partial_rounds_chain,
             idx,
         );
         update(
             answer_accumulator,
-            &claim.blake_context.blake_g,
-            &query_result.blake.g,
-            &quotient_constants.blake.g,
-            idx,
-        );
-        update_fixed_log_size(
-            answer_accumulator,
-            cairo_air::components::blake_round_sigma::LOG_SIZE,
-            &query_result.blake.sigma,
-            &quotient_constants.blake.sigma,
+            &poseidon_context.poseidon_full_round_chain,
+            &query_result.poseidon.full_round_chain,
+            &quotient_constants.poseidon.full_round_chain,
             idx,
         );
         update(
             answer_accumulator,
-            &claim.blake_context.triple_xor_32,
-            &query_result.blake.triple_xor_32,
-            &quotient_constants.blake.triple_xor_32,
+            &poseidon_context.cube_252,
+            &query_result.poseidon.cube_252,
+            &quotient_constants.poseidon.cube_252,
             idx,
         );
-        update_fixed_log_size(
+        update(
             answer_accumulator,
-            cairo_air::components::verify_bitwise_xor_12::LOG_SIZE,
-            &query_result.blake.verify_bitwise_xor_12,
-            &quotient_constants.blake.verify_bitwise_xor_12,
+            &poseidon_context.poseidon_round_keys,
+            &query_result.poseidon.round_keys,
+            &quotient_constants.poseidon.round_keys,
             idx,
         );
-
-        // range_check_128_builtin
         update(
             answer_accumulator,
-            &claim.builtins.range_check_128_builtin_log_size,
-            &query_result.range_check_128_builtin,
-            &quotient_constants.range_check_128_builtin,
+            &poseidon_context.range_check_252_width_27,
+            &query_result.poseidon.range_check_252_width_27,
+            &quotient_constants.poseidon.range_check_252_width_27,
             idx,
         );
 
@@ -970,13 +1181,15 @@ pub fn compute_trace_answers(
         );
 
         // memory_id_to_big_big
-        update(
-            answer_accumulator,
-            &claim.memory_id_to_value.big_log_size,
-            &query_result.memory_id_to_big_big,
-            &quotient_constants.memory_id_to_big_big,
-            idx,
-        );
+        for ((log_size, query), constants) in claim
+            .memory_id_to_value
+            .big_log_sizes
+            .iter()
+            .zip_eq(query_result.memory_id_to_big_big.iter())
+            .zip_eq(quotient_constants.memory_id_to_big_big.iter())
+        {
+            update(answer_accumulator, log_size, query, constants, idx);
+        }
 
         // memory_id_to_big_small
         update(
@@ -44,10 +44,16 @@ impl AnswerResults {
             &proof_var.stark_proof.sampled_values[0],
             &proof_var.stark_proof.is_preprocessed_trace_present,
         );
-        let trace_sample_result =
-            TraceSampleResultVar::new(&cs, &proof_var.stark_proof.sampled_values[1]);
-        let interaction_sample_result =
-            InteractionSampleResultVar::new(&cs, &proof_var.stark_proof.sampled_values[2]);
+        let trace_sample_result = TraceSampleResultVar::new(
+            &cs,
+            &proof_var.stark_proof.sampled_values[1],
+            &proof_var.claim,
+        );
+        let interaction_sample_result = InteractionSampleResultVar::new(
+            &cs,
+            &proof_var.stark_proof.sampled_values[2],
+            &proof_var.claim,
+        );
         let composition_sample_result =
             CompositionSampleResultVar::new(&proof_var.stark_proof.sampled_values[3]);
 
@@ -181,7 +187,9 @@ mod test {
     use super::*;
     use cairo_air::utils::{deserialize_proof_from_file, ProofFormat};
     use cairo_plonk_dsl_data_structures::CairoProofVar;
-    use cairo_plonk_dsl_hints::{CairoDecommitmentHints, CairoFiatShamirHints};
+    use cairo_plonk_dsl_hints::{
+        CairoDecommitmentHints, CairoFiatShamirHints, CairoVerifierParams,
+    };
     use circle_plonk_dsl_constraint_system::{var::AllocVar, ConstraintSystemRef};
     use std::path::PathBuf;
 
@@ -199,7 +207,8 @@ mod test {
             .join("recursive_proof.bin.bz");
 
         let proof = deserialize_proof_from_file(&data_path, ProofFormat::Binary).unwrap();
-        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof);
+        let params = CairoVerifierParams::from_proof(&proof);
+        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof, &params);
         let proof_var = CairoProofVar::new_witness(&cs, &proof);
         let fiat_shamir_results = CairoFiatShamirResults::compute(&fiat_shamir_hints, &proof_var);
         let decommitment_hints = CairoDecommitmentHints::new(&fiat_shamir_hints, &proof);
@@ -0,0 +1,11 @@
+//! The add_mod builtin checks `a + b = c (mod p)` for the batches of modular additions in its
+//! segment, each with its own modulus of four 96-bit limbs. It is evaluated through its native
+//! `Eval`, see [`NativeComponentVar`].
+//!
+//! Besides the reads within its segment, which the composition shifts to the segment start of the
+//! claim in the circuit, the builtin reads memory at the pointers of its segment, which are left
+//! as they are, see [`NativeComponentVar::with_segment_start`].
+
+use crate::components::native::NativeComponentVar;
+
+pub type Component<'a> = NativeComponentVar<'a, cairo_air::components::add_mod_builtin::Eval>;
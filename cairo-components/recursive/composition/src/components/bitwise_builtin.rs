@@ -0,0 +1,10 @@
+//! The bitwise builtin reads the operands of its segment and writes their `and`, `xor` and `or`.
+//! It is evaluated through its native `Eval`, see [`NativeComponentVar`].
+//!
+//! The native `Eval` embeds the segment start of the claim as a constant. Since the builtin only
+//! reads memory within its segment, the composition shifts its addresses to the segment start of
+//! the claim in the circuit, see [`NativeComponentVar::with_segment_start`].
+
+use crate::components::native::NativeComponentVar;
+
+pub type Component<'a> = NativeComponentVar<'a, cairo_air::components::bitwise_builtin::Eval>;
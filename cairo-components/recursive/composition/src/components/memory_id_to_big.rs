@@ -16,8 +16,9 @@ pub const SMALL_N_COLUMNS: usize = N_M31_IN_SMALL_FELT252 + N_MULTIPLICITY_COLUM
 #[derive(Clone)]
 pub struct BigComponent {
     pub log_size: u32,
-    // Internal offset of the ids when there are multiple components.
-    pub offset: u32,
+    // Internal offset of the ids when there are multiple components, which is a variable since the
+    // number of rows of the components before it is.
+    pub offset: WrappedQM31Var,
     pub lookup_elements: MemoryIdToBigVar,
     pub range_check_9_9_lookup_elements: RangeCheck99Var,
     pub range_check_9_9_b_lookup_elements: RangeCheck99BVar,
@@ -33,7 +34,7 @@ pub struct BigComponent {
 impl BigComponent {
     pub fn new(
         log_size: u32,
-        offset: u32,
+        offset: WrappedQM31Var,
         lookup_elements: MemoryIdToBigVar,
         range_check_9_9_lookup_elements: RangeCheck99Var,
         range_check_9_9_b_lookup_elements: RangeCheck99BVar,
@@ -117,9 +118,7 @@ impl ComponentVar for BigComponent {
         }
 
         // Yield the value.
-        let id = seq
-            + E::F::from(M31::from(LARGE_MEMORY_VALUE_ID_BASE))
-            + E::F::from(M31::from(self.offset));
+        let id = seq + E::F::from(M31::from(LARGE_MEMORY_VALUE_ID_BASE)) + self.offset.clone();
         eval.add_to_relation(RelationEntry::new(
             &self.lookup_elements,
             E::EF::from(-multiplicity),
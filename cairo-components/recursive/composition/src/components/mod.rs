@@ -3,11 +3,18 @@ use stwo_constraint_framework::EvalAtRow;
 
 pub mod subroutines;
 
+pub mod add_mod_builtin;
+pub mod bitwise_builtin;
 pub mod blake;
 pub mod memory_address_to_id;
 pub mod memory_id_to_big;
+pub mod mul_mod_builtin;
+pub mod native;
 pub mod opcodes;
+pub mod poseidon;
+pub mod poseidon_builtin;
 pub mod range_check_builtin_bits_128;
+pub mod range_check_builtin_bits_96;
 pub mod range_checks;
 pub mod verify_bitwise;
 pub mod verify_instruction;
@@ -0,0 +1,213 @@
+use std::ops::Mul;
+
+use cairo_plonk_dsl_data_structures::lookup::CairoInteractionElementsVar;
+use circle_plonk_dsl_constraint_system::var::{AllocVar, Var};
+use circle_plonk_dsl_primitives::fields::WrappedQM31Var;
+use circle_plonk_dsl_primitives::{
+    BitIntVar, M31Var, QM31Var, RelationElementsVar, RelationRedirect,
+};
+use itertools::Itertools;
+use num_traits::Zero;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use stwo::core::fields::m31::{M31, P};
+use stwo::core::fields::qm31::{SecureField, SECURE_EXTENSION_DEGREE};
+use stwo::core::Fraction;
+use stwo_constraint_framework::preprocessed_columns::PreProcessedColumnId;
+use stwo_constraint_framework::{EvalAtRow, FrameworkEval, Relation, RelationEntry};
+
+use crate::components::ComponentVar;
+
+/// A component evaluated through the native `Eval` of `cairo_air` rather than a ported evaluator.
+///
+/// The native `Eval` is generic over `EvalAtRow`, so its constraints can be evaluated in the
+/// circuit directly, through a [`RelationRedirect`] to the interaction elements. The components
+/// that are evaluated this way, and why, are listed in `doc/components.md`.
+pub struct NativeComponentVar<'a, C: FrameworkEval> {
+    pub eval: &'a C,
+    pub interaction_elements: &'a CairoInteractionElementsVar,
+    /// The difference between the segment start of the claim in the circuit and the one embedded
+    /// in the native `Eval`, which is added to the addresses read within the segment.
+    pub address_shift: Option<AddressShift>,
+}
+
+/// The shift of the memory reads of a builtin from the segment start embedded in its native
+/// `Eval` to the segment start of the claim in the circuit.
+pub struct AddressShift {
+    pub shift: QM31Var,
+    /// Whether each memory address lookup of a row, in the order of the native `Eval`, reads
+    /// within the segment, so that its address is shifted. The other lookups read at pointers
+    /// taken from memory, which already point into the segment of the claim.
+    pub is_segment_relative: Vec<bool>,
+}
+
+impl<'a, C: FrameworkEval> NativeComponentVar<'a, C> {
+    pub fn new(eval: &'a C, interaction_elements: &'a CairoInteractionElementsVar) -> Self {
+        Self {
+            eval,
+            interaction_elements,
+            address_shift: None,
+        }
+    }
+
+    /// Take the segment start from the claim in the circuit instead of the constant
+    /// `native_segment_start` embedded in the native `Eval`.
+    ///
+    /// The memory reads within the segment, i.e., at the segment start plus a function of the
+    /// row, are shifted by the difference of the two, while the reads at pointers taken from
+    /// memory, as in add_mod and mul_mod, are left as they are. Which reads are within the segment
+    /// is determined once by [`SegmentAddressProbe`], independently of the proof.
+    pub fn with_segment_start(
+        mut self,
+        native_segment_start: u32,
+        segment_start: &BitIntVar<31>,
+    ) -> Self {
+        let cs = segment_start.cs();
+        let shift =
+            &segment_start.to_m31() - &M31Var::new_constant(&cs, &M31::from(native_segment_start));
+        let is_segment_relative = SegmentAddressProbe::run(
+            self.eval,
+            self.interaction_elements,
+            M31::from(native_segment_start),
+        );
+        self.address_shift = Some(AddressShift {
+            shift: QM31Var::from(&shift),
+            is_segment_relative,
+        });
+        self
+    }
+}
+
+impl<C: FrameworkEval> ComponentVar for NativeComponentVar<'_, C> {
+    fn evaluate<E: EvalAtRow<F = WrappedQM31Var, EF = WrappedQM31Var>>(&self, eval: E) -> E {
+        let relations = NativeRelationsVar {
+            interaction_elements: self.interaction_elements,
+            address_shift: self.address_shift.as_ref(),
+            n_address_lookups: 0,
+        };
+        self.eval
+            .evaluate(RelationRedirect::new(eval, relations))
+            .inner
+    }
+}
+
+/// The interaction elements of the circuit as the lookup elements of a native `Eval`, with the
+/// memory reads within the segment shifted to the segment start of the claim.
+struct NativeRelationsVar<'a> {
+    interaction_elements: &'a CairoInteractionElementsVar,
+    address_shift: Option<&'a AddressShift>,
+    n_address_lookups: usize,
+}
+
+impl RelationElementsVar for NativeRelationsVar<'_> {
+    fn combine(&mut self, relation: &str, values: &[WrappedQM31Var]) -> QM31Var {
+        let lookup_elements = self.interaction_elements.get_by_native_name(relation);
+        let cs = lookup_elements.cs();
+        let mut values = values.iter().map(|v| v.unwrap(&cs)).collect_vec();
+        if let Some(address_shift) = self.address_shift {
+            if std::ptr::eq(
+                lookup_elements,
+                &self.interaction_elements.memory_address_to_id.0,
+            ) {
+                if address_shift.is_segment_relative[self.n_address_lookups] {
+                    values[0] = &values[0] + &address_shift.shift;
+                }
+                self.n_address_lookups += 1;
+            }
+        }
+        lookup_elements.combine_ef(&values)
+    }
+}
+
+/// An evaluator that finds which memory reads of a builtin are within its segment.
+///
+/// It evaluates the native `Eval` at a row where the preprocessed columns, and thus the sequence
+/// number of the row, are zero and the trace columns are pseudo-random. A read within the segment
+/// is then at the segment start plus a small constant, while a read at a pointer taken from memory
+/// is at a pseudo-random address. The outcome depends only on the `Eval`, not on the proof.
+pub struct SegmentAddressProbe<'a> {
+    rng: ChaCha20Rng,
+    interaction_elements: &'a CairoInteractionElementsVar,
+    segment_start: M31,
+    is_segment_relative: Vec<bool>,
+}
+
+impl<'a> SegmentAddressProbe<'a> {
+    /// The largest distance from the segment start of a read within the segment, at the first row.
+    const MAX_OFFSET: u32 = 1 << 10;
+
+    pub fn run<C: FrameworkEval>(
+        eval: &C,
+        interaction_elements: &'a CairoInteractionElementsVar,
+        segment_start: M31,
+    ) -> Vec<bool> {
+        let probe = Self {
+            rng: ChaCha20Rng::seed_from_u64(0),
+            interaction_elements,
+            segment_start,
+            is_segment_relative: vec![],
+        };
+        eval.evaluate(probe).is_segment_relative
+    }
+}
+
+impl EvalAtRow for SegmentAddressProbe<'_> {
+    type F = SecureField;
+    type EF = SecureField;
+
+    fn get_preprocessed_column(&mut self, _column: PreProcessedColumnId) -> Self::F {
+        SecureField::zero()
+    }
+
+    fn next_interaction_mask<const N: usize>(
+        &mut self,
+        _interaction: usize,
+        _offsets: [isize; N],
+    ) -> [Self::F; N] {
+        std::array::from_fn(|_| self.rng.gen())
+    }
+
+    fn add_constraint<G>(&mut self, _constraint: G)
+    where
+        Self::EF: Mul<G, Output = Self::EF> + From<G>,
+    {
+    }
+
+    fn combine_ef(values: [Self::F; SECURE_EXTENSION_DEGREE]) -> Self::EF {
+        SecureField::from_partial_evals(values)
+    }
+
+    fn add_to_relation<R: Relation<Self::F, Self::EF>>(
+        &mut self,
+        entry: RelationEntry<Self::F, Self::EF, R>,
+    ) {
+        let lookup_elements = self
+            .interaction_elements
+            .get_by_native_name(entry.relation.get_name());
+        if std::ptr::eq(
+            lookup_elements,
+            &self.interaction_elements.memory_address_to_id.0,
+        ) {
+            let offset = entry.values[0] - SecureField::from(self.segment_start);
+            let [offset, rest @ ..] = offset.to_m31_array();
+            let is_small = offset.0 <= Self::MAX_OFFSET || offset.0 >= P - Self::MAX_OFFSET;
+            self.is_segment_relative
+                .push(is_small && rest.iter().all(|v| v.is_zero()));
+        }
+    }
+
+    fn add_to_relation_ef<R: Relation<Self::F, Self::EF>>(
+        &mut self,
+        entry: RelationEntry<Self::F, Self::EF, R>,
+    ) {
+        self.add_to_relation(entry);
+    }
+
+    fn write_logup_frac(&mut self, _fraction: Fraction<Self::EF, Self::EF>) {}
+
+    fn finalize_logup(&mut self) {}
+
+    fn finalize_logup_in_pairs(&mut self) {}
+
+    fn finalize_logup_batched(&mut self, _batching: &Vec<usize>) {}
+}
@@ -0,0 +1,6 @@
+//! The generic opcode covers the instructions that none of the specialized opcode components
+//! handle. It is evaluated through its native `Eval`, see [`NativeComponentVar`].
+
+use crate::components::native::NativeComponentVar;
+
+pub type Component<'a> = NativeComponentVar<'a, cairo_air::components::generic_opcode::Eval>;
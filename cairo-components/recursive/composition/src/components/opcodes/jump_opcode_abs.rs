@@ -0,0 +1,6 @@
+//! The absolute jump, `jmp abs`. It is evaluated through its native `Eval`, see
+//! [`NativeComponentVar`].
+
+use crate::components::native::NativeComponentVar;
+
+pub type Component<'a> = NativeComponentVar<'a, cairo_air::components::jump_opcode_abs::Eval>;
@@ -0,0 +1,7 @@
+//! The absolute jump to an address read through a double dereference, `jmp abs [[ap + off]]`. It
+//! is evaluated through its native `Eval`, see [`NativeComponentVar`].
+
+use crate::components::native::NativeComponentVar;
+
+pub type Component<'a> =
+    NativeComponentVar<'a, cairo_air::components::jump_opcode_double_deref::Eval>;
@@ -10,8 +10,12 @@ pub mod blake_compress_opcode;
 pub mod call_opcode_abs;
 pub mod call_opcode_rel_imm;
 
+pub mod generic_opcode;
+
 pub mod jnz_opcode_non_taken;
 pub mod jnz_opcode_taken;
+pub mod jump_opcode_abs;
+pub mod jump_opcode_double_deref;
 pub mod jump_opcode_rel;
 pub mod jump_opcode_rel_imm;
 
@@ -0,0 +1,6 @@
+//! The cube_252 component computes the cube of a felt252 for the poseidon rounds. It is evaluated
+//! through its native `Eval`, see [`NativeComponentVar`].
+
+use crate::components::native::NativeComponentVar;
+
+pub type Component<'a> = NativeComponentVar<'a, cairo_air::components::cube_252::Eval>;
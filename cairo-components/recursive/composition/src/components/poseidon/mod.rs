@@ -0,0 +1,6 @@
+pub mod cube_252;
+pub mod poseidon_3_partial_rounds_chain;
+pub mod poseidon_aggregator;
+pub mod poseidon_full_round_chain;
+pub mod poseidon_round_keys;
+pub mod range_check_252_width_27;
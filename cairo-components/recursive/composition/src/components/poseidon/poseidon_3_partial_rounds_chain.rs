@@ -0,0 +1,7 @@
+//! The poseidon_3_partial_rounds_chain component applies three partial rounds of the Hades
+//! permutation. It is evaluated through its native `Eval`, see [`NativeComponentVar`].
+
+use crate::components::native::NativeComponentVar;
+
+pub type Component<'a> =
+    NativeComponentVar<'a, cairo_air::components::poseidon_3_partial_rounds_chain::Eval>;
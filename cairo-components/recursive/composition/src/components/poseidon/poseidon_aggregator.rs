@@ -0,0 +1,6 @@
+//! The poseidon_aggregator component links the poseidon builtin to the full and partial rounds. It
+//! is evaluated through its native `Eval`, see [`NativeComponentVar`].
+
+use crate::components::native::NativeComponentVar;
+
+pub type Component<'a> = NativeComponentVar<'a, cairo_air::components::poseidon_aggregator::Eval>;
@@ -0,0 +1,7 @@
+//! The poseidon_full_round_chain component applies one full round of the Hades permutation. It is
+//! evaluated through its native `Eval`, see [`NativeComponentVar`].
+
+use crate::components::native::NativeComponentVar;
+
+pub type Component<'a> =
+    NativeComponentVar<'a, cairo_air::components::poseidon_full_round_chain::Eval>;
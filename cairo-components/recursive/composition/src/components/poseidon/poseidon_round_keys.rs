@@ -0,0 +1,6 @@
+//! The poseidon_round_keys component looks up the round keys of the Hades permutation, and has a
+//! fixed log size. It is evaluated through its native `Eval`, see [`NativeComponentVar`].
+
+use crate::components::native::NativeComponentVar;
+
+pub type Component<'a> = NativeComponentVar<'a, cairo_air::components::poseidon_round_keys::Eval>;
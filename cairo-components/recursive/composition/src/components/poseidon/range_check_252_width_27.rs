@@ -0,0 +1,7 @@
+//! The range_check_252_width_27 component checks that a felt252 splits into limbs of 27 bits. It is
+//! evaluated through its native `Eval`, see [`NativeComponentVar`].
+
+use crate::components::native::NativeComponentVar;
+
+pub type Component<'a> =
+    NativeComponentVar<'a, cairo_air::components::range_check_252_width_27::Eval>;
@@ -0,0 +1,10 @@
+//! The poseidon builtin applies the Hades permutation to the states of its segment, with the help
+//! of the poseidon context. It is evaluated through its native `Eval`, see [`NativeComponentVar`].
+//!
+//! The native `Eval` embeds the segment start of the claim as a constant. Since the builtin only
+//! reads memory within its segment, the composition shifts its addresses to the segment start of
+//! the claim in the circuit, see [`NativeComponentVar::with_segment_start`].
+
+use crate::components::native::NativeComponentVar;
+
+pub type Component<'a> = NativeComponentVar<'a, cairo_air::components::poseidon_builtin::Eval>;
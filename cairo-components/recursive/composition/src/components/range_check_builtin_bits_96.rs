@@ -0,0 +1,11 @@
+//! The range_check_96 builtin checks that every value of its segment fits in 96 bits. It is
+//! evaluated through its native `Eval`, see [`NativeComponentVar`].
+//!
+//! The native `Eval` embeds the segment start of the claim as a constant. Since the builtin only
+//! reads memory within its segment, the composition shifts its addresses to the segment start of
+//! the claim in the circuit, see [`NativeComponentVar::with_segment_start`].
+
+use crate::components::native::NativeComponentVar;
+
+pub type Component<'a> =
+    NativeComponentVar<'a, cairo_air::components::range_check_builtin_bits_96::Eval>;
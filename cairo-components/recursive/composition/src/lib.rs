@@ -16,7 +16,7 @@ use circle_plonk_dsl_primitives::{
     channel::PreProcessedTracePresent,
     fields::WrappedQM31Var,
     oblivious_map::{ObliviousMapVar, SelectVar},
-    CirclePointQM31Var, LogSizeVar, M31Var, QM31Var,
+    BitVar, CirclePointQM31Var, LogSizeVar, M31Var, QM31Var,
 };
 use indexmap::IndexMap;
 use itertools::Itertools;
@@ -85,7 +85,7 @@ impl CairoCompositionCheck {
 
         Self::opcodes_evaluation(
             &mut point_evaluation_accumulator,
-            &fiat_shamir_hints.component_generator,
+            &fiat_shamir_hints.padding.components,
             &fiat_shamir_results.interaction_elements,
             &oods_map,
             &proof,
@@ -94,7 +94,7 @@ impl CairoCompositionCheck {
 
         Self::verify_instruction_evaluation(
             &mut point_evaluation_accumulator,
-            &fiat_shamir_hints.component_generator,
+            &fiat_shamir_hints.padding.components,
             &fiat_shamir_results.interaction_elements,
             &oods_map,
             &proof,
@@ -103,16 +103,25 @@ impl CairoCompositionCheck {
 
         Self::blake_context_evaluation(
             &mut point_evaluation_accumulator,
-            &fiat_shamir_hints.component_generator,
+            &fiat_shamir_hints.padding.components,
             &fiat_shamir_results.interaction_elements,
             &oods_map,
             &proof,
             &samples,
         );
 
-        Self::range_check_builtin_bits_128_evaluation(
+        Self::builtins_evaluation(
             &mut point_evaluation_accumulator,
-            &fiat_shamir_hints.component_generator,
+            &fiat_shamir_hints.padding.components,
+            &fiat_shamir_results.interaction_elements,
+            &oods_map,
+            &proof,
+            &samples,
+        );
+
+        Self::poseidon_context_evaluation(
+            &mut point_evaluation_accumulator,
+            &fiat_shamir_hints.padding.components,
             &fiat_shamir_results.interaction_elements,
             &oods_map,
             &proof,
@@ -121,7 +130,7 @@ impl CairoCompositionCheck {
 
         Self::memory_evaluation(
             &mut point_evaluation_accumulator,
-            &fiat_shamir_hints.component_generator,
+            &fiat_shamir_hints.padding.components,
             &fiat_shamir_results.interaction_elements,
             &oods_map,
             &proof,
@@ -130,7 +139,7 @@ impl CairoCompositionCheck {
 
         Self::range_checks_evaluation(
             &mut point_evaluation_accumulator,
-            &fiat_shamir_hints.component_generator,
+            &fiat_shamir_hints.padding.components,
             &fiat_shamir_results.interaction_elements,
             &oods_map,
             &proof,
@@ -139,7 +148,7 @@ impl CairoCompositionCheck {
 
         Self::verify_bitwise_evaluation(
             &mut point_evaluation_accumulator,
-            &fiat_shamir_hints.component_generator,
+            &fiat_shamir_hints.padding.components,
             &fiat_shamir_results.interaction_elements,
             &oods_map,
             &proof,
@@ -207,17 +216,27 @@ impl CairoCompositionCheck {
             memory_address_to_id_lookup_elements: interaction_elements.memory_address_to_id.clone(),
             memory_id_to_big_lookup_elements: interaction_elements.memory_id_to_value.clone(),
         };
-        update_evaluation_accumulator_var(
-            evaluation_accumulator,
-            &component_generator.opcodes.add[0],
-            &add_var,
-            &oods_map,
-            &samples,
-            &proof.claim.opcode_claim.add,
-            &proof.interaction_claim.opcodes.add,
-            false,
-            &proof.stark_proof.is_preprocessed_trace_present,
-        );
+        for (((component, log_size), is_active), claimed_sum) in component_generator
+            .opcodes
+            .add
+            .iter()
+            .zip_eq(proof.claim.opcode_claim.add.iter())
+            .zip_eq(proof.claim.opcode_claim.add.is_active.iter())
+            .zip_eq(&proof.interaction_claim.opcodes.add)
+        {
+            update_evaluation_accumulator_var_conditional(
+                evaluation_accumulator,
+                component,
+                &add_var,
+                &oods_map,
+                &samples,
+                log_size,
+                claimed_sum,
+                false,
+                &proof.stark_proof.is_preprocessed_trace_present,
+                is_active,
+            );
+        }
 
         let add_small_var = crate::components::opcodes::add_opcode_small::Component {
             verify_instruction_lookup_elements: interaction_elements.verify_instruction.clone(),
@@ -225,17 +244,27 @@ impl CairoCompositionCheck {
             memory_id_to_big_lookup_elements: interaction_elements.memory_id_to_value.clone(),
             opcodes_lookup_elements: interaction_elements.opcodes.clone(),
         };
-        update_evaluation_accumulator_var(
-            evaluation_accumulator,
-            &component_generator.opcodes.add_small[0],
-            &add_small_var,
-            &oods_map,
-            &samples,
-            &proof.claim.opcode_claim.add_small,
-            &proof.interaction_claim.opcodes.add_small,
-            false,
-            &proof.stark_proof.is_preprocessed_trace_present,
-        );
+        for (((component, log_size), is_active), claimed_sum) in component_generator
+            .opcodes
+            .add_small
+            .iter()
+            .zip_eq(proof.claim.opcode_claim.add_small.iter())
+            .zip_eq(proof.claim.opcode_claim.add_small.is_active.iter())
+            .zip_eq(&proof.interaction_claim.opcodes.add_small)
+        {
+            update_evaluation_accumulator_var_conditional(
+                evaluation_accumulator,
+                component,
+                &add_small_var,
+                &oods_map,
+                &samples,
+                log_size,
+                claimed_sum,
+                false,
+                &proof.stark_proof.is_preprocessed_trace_present,
+                is_active,
+            );
+        }
 
         let add_ap_opcode_var = crate::components::opcodes::add_ap_opcode::Component {
             verify_instruction_lookup_elements: interaction_elements.verify_instruction.clone(),
@@ -245,34 +274,54 @@ impl CairoCompositionCheck {
             range_check_11_lookup_elements: interaction_elements.range_checks.rc_11.clone(),
             opcodes_lookup_elements: interaction_elements.opcodes.clone(),
         };
-        update_evaluation_accumulator_var(
-            evaluation_accumulator,
-            &component_generator.opcodes.add_ap[0],
-            &add_ap_opcode_var,
-            &oods_map,
-            &samples,
-            &proof.claim.opcode_claim.add_ap,
-            &proof.interaction_claim.opcodes.add_ap,
-            false,
-            &proof.stark_proof.is_preprocessed_trace_present,
-        );
+        for (((component, log_size), is_active), claimed_sum) in component_generator
+            .opcodes
+            .add_ap
+            .iter()
+            .zip_eq(proof.claim.opcode_claim.add_ap.iter())
+            .zip_eq(proof.claim.opcode_claim.add_ap.is_active.iter())
+            .zip_eq(&proof.interaction_claim.opcodes.add_ap)
+        {
+            update_evaluation_accumulator_var_conditional(
+                evaluation_accumulator,
+                component,
+                &add_ap_opcode_var,
+                &oods_map,
+                &samples,
+                log_size,
+                claimed_sum,
+                false,
+                &proof.stark_proof.is_preprocessed_trace_present,
+                is_active,
+            );
+        }
 
         let assert_eq_opcode_var = crate::components::opcodes::assert_eq_opcode::Component {
             verify_instruction_lookup_elements: interaction_elements.verify_instruction.clone(),
             memory_address_to_id_lookup_elements: interaction_elements.memory_address_to_id.clone(),
             opcodes_lookup_elements: interaction_elements.opcodes.clone(),
         };
-        update_evaluation_accumulator_var(
-            evaluation_accumulator,
-            &component_generator.opcodes.assert_eq[0],
-            &assert_eq_opcode_var,
-            &oods_map,
-            &samples,
-            &proof.claim.opcode_claim.assert_eq,
-            &proof.interaction_claim.opcodes.assert_eq,
-            false,
-            &proof.stark_proof.is_preprocessed_trace_present,
-        );
+        for (((component, log_size), is_active), claimed_sum) in component_generator
+            .opcodes
+            .assert_eq
+            .iter()
+            .zip_eq(proof.claim.opcode_claim.assert_eq.iter())
+            .zip_eq(proof.claim.opcode_claim.assert_eq.is_active.iter())
+            .zip_eq(&proof.interaction_claim.opcodes.assert_eq)
+        {
+            update_evaluation_accumulator_var_conditional(
+                evaluation_accumulator,
+                component,
+                &assert_eq_opcode_var,
+                &oods_map,
+                &samples,
+                log_size,
+                claimed_sum,
+                false,
+                &proof.stark_proof.is_preprocessed_trace_present,
+                is_active,
+            );
+        }
 
         let assert_eq_opcode_imm_var =
             crate::components::opcodes::assert_eq_opcode_imm::Component {
@@ -282,17 +331,27 @@ impl CairoCompositionCheck {
                     .clone(),
                 opcodes_lookup_elements: interaction_elements.opcodes.clone(),
             };
-        update_evaluation_accumulator_var(
-            evaluation_accumulator,
-            &component_generator.opcodes.assert_eq_imm[0],
-            &assert_eq_opcode_imm_var,
-            &oods_map,
-            &samples,
-            &proof.claim.opcode_claim.assert_eq_imm,
-            &proof.interaction_claim.opcodes.assert_eq_imm,
-            false,
-            &proof.stark_proof.is_preprocessed_trace_present,
-        );
+        for (((component, log_size), is_active), claimed_sum) in component_generator
+            .opcodes
+            .assert_eq_imm
+            .iter()
+            .zip_eq(proof.claim.opcode_claim.assert_eq_imm.iter())
+            .zip_eq(proof.claim.opcode_claim.assert_eq_imm.is_active.iter())
+            .zip_eq(&proof.interaction_claim.opcodes.assert_eq_imm)
+        {
+            update_evaluation_accumulator_var_conditional(
+                evaluation_accumulator,
+                component,
+                &assert_eq_opcode_imm_var,
+                &oods_map,
+                &samples,
+                log_size,
+                claimed_sum,
+                false,
+                &proof.stark_proof.is_preprocessed_trace_present,
+                is_active,
+            );
+        }
 
         let assert_eq_opcode_double_deref_var =
             crate::components::opcodes::assert_eq_opcode_double_deref::Component {
@@ -303,48 +362,79 @@ impl CairoCompositionCheck {
                 memory_id_to_big_lookup_elements: interaction_elements.memory_id_to_value.clone(),
                 opcodes_lookup_elements: interaction_elements.opcodes.clone(),
             };
-        update_evaluation_accumulator_var(
-            evaluation_accumulator,
-            &component_generator.opcodes.assert_eq_double_deref[0],
-            &assert_eq_opcode_double_deref_var,
-            &oods_map,
-            &samples,
-            &proof.claim.opcode_claim.assert_eq_double_deref,
-            &proof.interaction_claim.opcodes.assert_eq_double_deref,
-            false,
-            &proof.stark_proof.is_preprocessed_trace_present,
-        );
+        for (((component, log_size), is_active), claimed_sum) in component_generator
+            .opcodes
+            .assert_eq_double_deref
+            .iter()
+            .zip_eq(proof.claim.opcode_claim.assert_eq_double_deref.iter())
+            .zip_eq(
+                proof
+                    .claim
+                    .opcode_claim
+                    .assert_eq_double_deref
+                    .is_active
+                    .iter(),
+            )
+            .zip_eq(&proof.interaction_claim.opcodes.assert_eq_double_deref)
+        {
+            update_evaluation_accumulator_var_conditional(
+                evaluation_accumulator,
+                component,
+                &assert_eq_opcode_double_deref_var,
+                &oods_map,
+                &samples,
+                log_size,
+                claimed_sum,
+                false,
+                &proof.stark_proof.is_preprocessed_trace_present,
+                is_active,
+            );
+        }
 
-        let blake_compress_opcode_var =
-            crate::components::opcodes::blake_compress_opcode::Component {
-                log_size: component_generator.opcodes.blake[0].log_size(),
-                verify_instruction_lookup_elements: interaction_elements.verify_instruction.clone(),
-                memory_address_to_id_lookup_elements: interaction_elements
-                    .memory_address_to_id
-                    .clone(),
-                memory_id_to_big_lookup_elements: interaction_elements.memory_id_to_value.clone(),
-                range_check_7_2_5_lookup_elements: interaction_elements
-                    .range_checks
-                    .rc_7_2_5
-                    .clone(),
-                verify_bitwise_xor_8_lookup_elements: interaction_elements
-                    .verify_bitwise_xor_8
-                    .clone(),
-                blake_round_lookup_elements: interaction_elements.blake_round.clone(),
-                triple_xor_32_lookup_elements: interaction_elements.triple_xor_32.clone(),
-                opcodes_lookup_elements: interaction_elements.opcodes.clone(),
-            };
-        update_evaluation_accumulator_var(
-            evaluation_accumulator,
-            &component_generator.opcodes.blake[0],
-            &blake_compress_opcode_var,
-            &oods_map,
-            &samples,
-            &proof.claim.opcode_claim.blake,
-            &proof.interaction_claim.opcodes.blake,
-            true,
-            &proof.stark_proof.is_preprocessed_trace_present,
-        );
+        for (((component, log_size), is_active), claimed_sum) in component_generator
+            .opcodes
+            .blake
+            .iter()
+            .zip_eq(proof.claim.opcode_claim.blake.iter())
+            .zip_eq(proof.claim.opcode_claim.blake.is_active.iter())
+            .zip_eq(&proof.interaction_claim.opcodes.blake)
+        {
+            let blake_compress_opcode_var =
+                crate::components::opcodes::blake_compress_opcode::Component {
+                    log_size: component.log_size(),
+                    verify_instruction_lookup_elements: interaction_elements
+                        .verify_instruction
+                        .clone(),
+                    memory_address_to_id_lookup_elements: interaction_elements
+                        .memory_address_to_id
+                        .clone(),
+                    memory_id_to_big_lookup_elements: interaction_elements
+                        .memory_id_to_value
+                        .clone(),
+                    range_check_7_2_5_lookup_elements: interaction_elements
+                        .range_checks
+                        .rc_7_2_5
+                        .clone(),
+                    verify_bitwise_xor_8_lookup_elements: interaction_elements
+                        .verify_bitwise_xor_8
+                        .clone(),
+                    blake_round_lookup_elements: interaction_elements.blake_round.clone(),
+                    triple_xor_32_lookup_elements: interaction_elements.triple_xor_32.clone(),
+                    opcodes_lookup_elements: interaction_elements.opcodes.clone(),
+                };
+            update_evaluation_accumulator_var_conditional(
+                evaluation_accumulator,
+                component,
+                &blake_compress_opcode_var,
+                &oods_map,
+                &samples,
+                log_size,
+                claimed_sum,
+                true,
+                &proof.stark_proof.is_preprocessed_trace_present,
+                is_active,
+            );
+        }
 
         let call_opcode_abs_var = crate::components::opcodes::call_opcode_abs::Component {
             verify_instruction_lookup_elements: interaction_elements.verify_instruction.clone(),
@@ -352,17 +442,27 @@ impl CairoCompositionCheck {
             memory_id_to_big_lookup_elements: interaction_elements.memory_id_to_value.clone(),
             opcodes_lookup_elements: interaction_elements.opcodes.clone(),
         };
-        update_evaluation_accumulator_var(
-            evaluation_accumulator,
-            &component_generator.opcodes.call[0],
-            &call_opcode_abs_var,
-            &oods_map,
-            &samples,
-            &proof.claim.opcode_claim.call,
-            &proof.interaction_claim.opcodes.call,
-            false,
-            &proof.stark_proof.is_preprocessed_trace_present,
-        );
+        for (((component, log_size), is_active), claimed_sum) in component_generator
+            .opcodes
+            .call
+            .iter()
+            .zip_eq(proof.claim.opcode_claim.call.iter())
+            .zip_eq(proof.claim.opcode_claim.call.is_active.iter())
+            .zip_eq(&proof.interaction_claim.opcodes.call)
+        {
+            update_evaluation_accumulator_var_conditional(
+                evaluation_accumulator,
+                component,
+                &call_opcode_abs_var,
+                &oods_map,
+                &samples,
+                log_size,
+                claimed_sum,
+                false,
+                &proof.stark_proof.is_preprocessed_trace_present,
+                is_active,
+            );
+        }
 
         let call_opcode_rel_imm_var = crate::components::opcodes::call_opcode_rel_imm::Component {
             verify_instruction_lookup_elements: interaction_elements.verify_instruction.clone(),
@@ -370,17 +470,53 @@ impl CairoCompositionCheck {
             memory_id_to_big_lookup_elements: interaction_elements.memory_id_to_value.clone(),
             opcodes_lookup_elements: interaction_elements.opcodes.clone(),
         };
-        update_evaluation_accumulator_var(
-            evaluation_accumulator,
-            &component_generator.opcodes.call_rel_imm[0],
-            &call_opcode_rel_imm_var,
-            &oods_map,
-            &samples,
-            &proof.claim.opcode_claim.call_rel_imm,
-            &proof.interaction_claim.opcodes.call_rel_imm,
-            false,
-            &proof.stark_proof.is_preprocessed_trace_present,
-        );
+        for (((component, log_size), is_active), claimed_sum) in component_generator
+            .opcodes
+            .call_rel_imm
+            .iter()
+            .zip_eq(proof.claim.opcode_claim.call_rel_imm.iter())
+            .zip_eq(proof.claim.opcode_claim.call_rel_imm.is_active.iter())
+            .zip_eq(&proof.interaction_claim.opcodes.call_rel_imm)
+        {
+            update_evaluation_accumulator_var_conditional(
+                evaluation_accumulator,
+                component,
+                &call_opcode_rel_imm_var,
+                &oods_map,
+                &samples,
+                log_size,
+                claimed_sum,
+                false,
+                &proof.stark_proof.is_preprocessed_trace_present,
+                is_active,
+            );
+        }
+
+        for (((component, log_size), is_active), claimed_sum) in component_generator
+            .opcodes
+            .generic
+            .iter()
+            .zip_eq(proof.claim.opcode_claim.generic.iter())
+            .zip_eq(proof.claim.opcode_claim.generic.is_active.iter())
+            .zip_eq(&proof.interaction_claim.opcodes.generic)
+        {
+            let generic_opcode_var = crate::components::opcodes::generic_opcode::Component::new(
+                component,
+                interaction_elements,
+            );
+            update_evaluation_accumulator_var_conditional(
+                evaluation_accumulator,
+                component,
+                &generic_opcode_var,
+                &oods_map,
+                &samples,
+                log_size,
+                claimed_sum,
+                false,
+                &proof.stark_proof.is_preprocessed_trace_present,
+                is_active,
+            );
+        }
 
         let jnz_opcode_non_taken_var =
             crate::components::opcodes::jnz_opcode_non_taken::Component {
@@ -391,17 +527,27 @@ impl CairoCompositionCheck {
                 memory_id_to_big_lookup_elements: interaction_elements.memory_id_to_value.clone(),
                 opcodes_lookup_elements: interaction_elements.opcodes.clone(),
             };
-        update_evaluation_accumulator_var(
-            evaluation_accumulator,
-            &component_generator.opcodes.jnz[0],
-            &jnz_opcode_non_taken_var,
-            &oods_map,
-            &samples,
-            &proof.claim.opcode_claim.jnz,
-            &proof.interaction_claim.opcodes.jnz,
-            false,
-            &proof.stark_proof.is_preprocessed_trace_present,
-        );
+        for (((component, log_size), is_active), claimed_sum) in component_generator
+            .opcodes
+            .jnz
+            .iter()
+            .zip_eq(proof.claim.opcode_claim.jnz.iter())
+            .zip_eq(proof.claim.opcode_claim.jnz.is_active.iter())
+            .zip_eq(&proof.interaction_claim.opcodes.jnz)
+        {
+            update_evaluation_accumulator_var_conditional(
+                evaluation_accumulator,
+                component,
+                &jnz_opcode_non_taken_var,
+                &oods_map,
+                &samples,
+                log_size,
+                claimed_sum,
+                false,
+                &proof.stark_proof.is_preprocessed_trace_present,
+                is_active,
+            );
+        }
 
         let jnz_opcode_taken_var = crate::components::opcodes::jnz_opcode_taken::Component {
             verify_instruction_lookup_elements: interaction_elements.verify_instruction.clone(),
@@ -409,17 +555,80 @@ impl CairoCompositionCheck {
             memory_id_to_big_lookup_elements: interaction_elements.memory_id_to_value.clone(),
             opcodes_lookup_elements: interaction_elements.opcodes.clone(),
         };
-        update_evaluation_accumulator_var(
-            evaluation_accumulator,
-            &component_generator.opcodes.jnz_taken[0],
-            &jnz_opcode_taken_var,
-            &oods_map,
-            &samples,
-            &proof.claim.opcode_claim.jnz_taken,
-            &proof.interaction_claim.opcodes.jnz_taken,
-            false,
-            &proof.stark_proof.is_preprocessed_trace_present,
-        );
+        for (((component, log_size), is_active), claimed_sum) in component_generator
+            .opcodes
+            .jnz_taken
+            .iter()
+            .zip_eq(proof.claim.opcode_claim.jnz_taken.iter())
+            .zip_eq(proof.claim.opcode_claim.jnz_taken.is_active.iter())
+            .zip_eq(&proof.interaction_claim.opcodes.jnz_taken)
+        {
+            update_evaluation_accumulator_var_conditional(
+                evaluation_accumulator,
+                component,
+                &jnz_opcode_taken_var,
+                &oods_map,
+                &samples,
+                log_size,
+                claimed_sum,
+                false,
+                &proof.stark_proof.is_preprocessed_trace_present,
+                is_active,
+            );
+        }
+
+        for (((component, log_size), is_active), claimed_sum) in component_generator
+            .opcodes
+            .jump
+            .iter()
+            .zip_eq(proof.claim.opcode_claim.jump.iter())
+            .zip_eq(proof.claim.opcode_claim.jump.is_active.iter())
+            .zip_eq(&proof.interaction_claim.opcodes.jump)
+        {
+            let jump_opcode_abs_var = crate::components::opcodes::jump_opcode_abs::Component::new(
+                component,
+                interaction_elements,
+            );
+            update_evaluation_accumulator_var_conditional(
+                evaluation_accumulator,
+                component,
+                &jump_opcode_abs_var,
+                &oods_map,
+                &samples,
+                log_size,
+                claimed_sum,
+                false,
+                &proof.stark_proof.is_preprocessed_trace_present,
+                is_active,
+            );
+        }
+
+        for (((component, log_size), is_active), claimed_sum) in component_generator
+            .opcodes
+            .jump_double_deref
+            .iter()
+            .zip_eq(proof.claim.opcode_claim.jump_double_deref.iter())
+            .zip_eq(proof.claim.opcode_claim.jump_double_deref.is_active.iter())
+            .zip_eq(&proof.interaction_claim.opcodes.jump_double_deref)
+        {
+            let jump_opcode_double_deref_var =
+                crate::components::opcodes::jump_opcode_double_deref::Component::new(
+                    component,
+                    interaction_elements,
+                );
+            update_evaluation_accumulator_var_conditional(
+                evaluation_accumulator,
+                component,
+                &jump_opcode_double_deref_var,
+                &oods_map,
+                &samples,
+                log_size,
+                claimed_sum,
+                false,
+                &proof.stark_proof.is_preprocessed_trace_present,
+                is_active,
+            );
+        }
 
         let jump_opcode_rel_var = crate::components::opcodes::jump_opcode_rel::Component {
             verify_instruction_lookup_elements: interaction_elements.verify_instruction.clone(),
@@ -427,17 +636,27 @@ impl CairoCompositionCheck {
             memory_id_to_big_lookup_elements: interaction_elements.memory_id_to_value.clone(),
             opcodes_lookup_elements: interaction_elements.opcodes.clone(),
         };
-        update_evaluation_accumulator_var(
-            evaluation_accumulator,
-            &component_generator.opcodes.jump_rel[0],
-            &jump_opcode_rel_var,
-            &oods_map,
-            &samples,
-            &proof.claim.opcode_claim.jump_rel,
-            &proof.interaction_claim.opcodes.jump_rel,
-            false,
-            &proof.stark_proof.is_preprocessed_trace_present,
-        );
+        for (((component, log_size), is_active), claimed_sum) in component_generator
+            .opcodes
+            .jump_rel
+            .iter()
+            .zip_eq(proof.claim.opcode_claim.jump_rel.iter())
+            .zip_eq(proof.claim.opcode_claim.jump_rel.is_active.iter())
+            .zip_eq(&proof.interaction_claim.opcodes.jump_rel)
+        {
+            update_evaluation_accumulator_var_conditional(
+                evaluation_accumulator,
+                component,
+                &jump_opcode_rel_var,
+                &oods_map,
+                &samples,
+                log_size,
+                claimed_sum,
+                false,
+                &proof.stark_proof.is_preprocessed_trace_present,
+                is_active,
+            );
+        }
 
         let jump_opcode_rel_imm_var = crate::components::opcodes::jump_opcode_rel_imm::Component {
             verify_instruction_lookup_elements: interaction_elements.verify_instruction.clone(),
@@ -445,17 +664,27 @@ impl CairoCompositionCheck {
             memory_id_to_big_lookup_elements: interaction_elements.memory_id_to_value.clone(),
             opcodes_lookup_elements: interaction_elements.opcodes.clone(),
         };
-        update_evaluation_accumulator_var(
-            evaluation_accumulator,
-            &component_generator.opcodes.jump_rel_imm[0],
-            &jump_opcode_rel_imm_var,
-            &oods_map,
-            &samples,
-            &proof.claim.opcode_claim.jump_rel_imm,
-            &proof.interaction_claim.opcodes.jump_rel_imm,
-            false,
-            &proof.stark_proof.is_preprocessed_trace_present,
-        );
+        for (((component, log_size), is_active), claimed_sum) in component_generator
+            .opcodes
+            .jump_rel_imm
+            .iter()
+            .zip_eq(proof.claim.opcode_claim.jump_rel_imm.iter())
+            .zip_eq(proof.claim.opcode_claim.jump_rel_imm.is_active.iter())
+            .zip_eq(&proof.interaction_claim.opcodes.jump_rel_imm)
+        {
+            update_evaluation_accumulator_var_conditional(
+                evaluation_accumulator,
+                component,
+                &jump_opcode_rel_imm_var,
+                &oods_map,
+                &samples,
+                log_size,
+                claimed_sum,
+                false,
+                &proof.stark_proof.is_preprocessed_trace_present,
+                is_active,
+            );
+        }
 
         let mul_opcode_var = crate::components::opcodes::mul_opcode::Component {
             verify_instruction_lookup_elements: interaction_elements.verify_instruction.clone(),
@@ -471,17 +700,27 @@ impl CairoCompositionCheck {
             range_check_20_g_lookup_elements: interaction_elements.range_checks.rc_20_g.clone(),
             range_check_20_h_lookup_elements: interaction_elements.range_checks.rc_20_h.clone(),
         };
-        update_evaluation_accumulator_var(
-            evaluation_accumulator,
-            &component_generator.opcodes.mul[0],
-            &mul_opcode_var,
-            &oods_map,
-            &samples,
-            &proof.claim.opcode_claim.mul,
-            &proof.interaction_claim.opcodes.mul,
-            false,
-            &proof.stark_proof.is_preprocessed_trace_present,
-        );
+        for (((component, log_size), is_active), claimed_sum) in component_generator
+            .opcodes
+            .mul
+            .iter()
+            .zip_eq(proof.claim.opcode_claim.mul.iter())
+            .zip_eq(proof.claim.opcode_claim.mul.is_active.iter())
+            .zip_eq(&proof.interaction_claim.opcodes.mul)
+        {
+            update_evaluation_accumulator_var_conditional(
+                evaluation_accumulator,
+                component,
+                &mul_opcode_var,
+                &oods_map,
+                &samples,
+                log_size,
+                claimed_sum,
+                false,
+                &proof.stark_proof.is_preprocessed_trace_present,
+                is_active,
+            );
+        }
 
         let mul_opcode_small_var = crate::components::opcodes::mul_opcode_small::Component {
             verify_instruction_lookup_elements: interaction_elements.verify_instruction.clone(),
@@ -490,17 +729,27 @@ impl CairoCompositionCheck {
             opcodes_lookup_elements: interaction_elements.opcodes.clone(),
             range_check_11_lookup_elements: interaction_elements.range_checks.rc_11.clone(),
         };
-        update_evaluation_accumulator_var(
-            evaluation_accumulator,
-            &component_generator.opcodes.mul_small[0],
-            &mul_opcode_small_var,
-            &oods_map,
-            &samples,
-            &proof.claim.opcode_claim.mul_small,
-            &proof.interaction_claim.opcodes.mul_small,
-            false,
-            &proof.stark_proof.is_preprocessed_trace_present,
-        );
+        for (((component, log_size), is_active), claimed_sum) in component_generator
+            .opcodes
+            .mul_small
+            .iter()
+            .zip_eq(proof.claim.opcode_claim.mul_small.iter())
+            .zip_eq(proof.claim.opcode_claim.mul_small.is_active.iter())
+            .zip_eq(&proof.interaction_claim.opcodes.mul_small)
+        {
+            update_evaluation_accumulator_var_conditional(
+                evaluation_accumulator,
+                component,
+                &mul_opcode_small_var,
+                &oods_map,
+                &samples,
+                log_size,
+                claimed_sum,
+                false,
+                &proof.stark_proof.is_preprocessed_trace_present,
+                is_active,
+            );
+        }
 
         let qm_31_add_mul_opcode_var =
             crate::components::opcodes::qm_31_add_mul_opcode::Component {
@@ -515,17 +764,27 @@ impl CairoCompositionCheck {
                     .rc_4_4_4_4
                     .clone(),
             };
-        update_evaluation_accumulator_var(
-            evaluation_accumulator,
-            &component_generator.opcodes.qm31[0],
-            &qm_31_add_mul_opcode_var,
-            &oods_map,
-            &samples,
-            &proof.claim.opcode_claim.qm31,
-            &proof.interaction_claim.opcodes.qm31,
-            false,
-            &proof.stark_proof.is_preprocessed_trace_present,
-        );
+        for (((component, log_size), is_active), claimed_sum) in component_generator
+            .opcodes
+            .qm31
+            .iter()
+            .zip_eq(proof.claim.opcode_claim.qm31.iter())
+            .zip_eq(proof.claim.opcode_claim.qm31.is_active.iter())
+            .zip_eq(&proof.interaction_claim.opcodes.qm31)
+        {
+            update_evaluation_accumulator_var_conditional(
+                evaluation_accumulator,
+                component,
+                &qm_31_add_mul_opcode_var,
+                &oods_map,
+                &samples,
+                log_size,
+                claimed_sum,
+                false,
+                &proof.stark_proof.is_preprocessed_trace_present,
+                is_active,
+            );
+        }
 
         let ret_opcode_var = crate::components::opcodes::ret_opcode::Component {
             verify_instruction_lookup_elements: interaction_elements.verify_instruction.clone(),
@@ -533,17 +792,27 @@ impl CairoCompositionCheck {
             memory_id_to_big_lookup_elements: interaction_elements.memory_id_to_value.clone(),
             opcodes_lookup_elements: interaction_elements.opcodes.clone(),
         };
-        update_evaluation_accumulator_var(
-            evaluation_accumulator,
-            &component_generator.opcodes.ret[0],
-            &ret_opcode_var,
-            &oods_map,
-            &samples,
-            &proof.claim.opcode_claim.ret,
-            &proof.interaction_claim.opcodes.ret,
-            false,
-            &proof.stark_proof.is_preprocessed_trace_present,
-        );
+        for (((component, log_size), is_active), claimed_sum) in component_generator
+            .opcodes
+            .ret
+            .iter()
+            .zip_eq(proof.claim.opcode_claim.ret.iter())
+            .zip_eq(proof.claim.opcode_claim.ret.is_active.iter())
+            .zip_eq(&proof.interaction_claim.opcodes.ret)
+        {
+            update_evaluation_accumulator_var_conditional(
+                evaluation_accumulator,
+                component,
+                &ret_opcode_var,
+                &oods_map,
+                &samples,
+                log_size,
+                claimed_sum,
+                false,
+                &proof.stark_proof.is_preprocessed_trace_present,
+                is_active,
+            );
+        }
     }
 
     pub fn verify_instruction_evaluation(
@@ -687,7 +956,7 @@ impl CairoCompositionCheck {
         );
     }
 
-    pub fn range_check_builtin_bits_128_evaluation(
+    pub fn builtins_evaluation(
         evaluation_accumulator: &mut PointEvaluationAccumulatorVar,
         component_generator: &CairoComponents,
         interaction_elements: &CairoInteractionElementsVar,
@@ -695,6 +964,138 @@ impl CairoCompositionCheck {
         proof: &CairoProofVar,
         samples: &WrappedSamplesValues,
     ) {
+        // The native `Eval` of the builtins embeds the segment start of the claim as a constant,
+        // so their reads within the segment are shifted to the segment start of the claim in the
+        // circuit. The reads of add_mod and mul_mod at pointers taken from memory are not. An
+        // absent builtin is evaluated on the padding component, and its evaluation is dropped.
+        let add_mod_builtin = &proof.claim.builtins.add_mod_builtin;
+        let component = component_generator
+            .builtins
+            .add_mod_builtin
+            .as_ref()
+            .unwrap();
+        let add_mod_builtin_var =
+            crate::components::add_mod_builtin::Component::new(component, interaction_elements)
+                .with_segment_start(
+                    component.claim.add_mod_builtin_segment_start,
+                    &add_mod_builtin.segment_start,
+                );
+        update_evaluation_accumulator_var_conditional(
+            evaluation_accumulator,
+            component,
+            &add_mod_builtin_var,
+            &oods_map,
+            &samples,
+            &add_mod_builtin.log_size,
+            &proof.interaction_claim.builtins.add_mod_builtin,
+            true,
+            &proof.stark_proof.is_preprocessed_trace_present,
+            &add_mod_builtin.is_present,
+        );
+
+        let bitwise_builtin = &proof.claim.builtins.bitwise_builtin;
+        let component = component_generator
+            .builtins
+            .bitwise_builtin
+            .as_ref()
+            .unwrap();
+        let bitwise_builtin_var =
+            crate::components::bitwise_builtin::Component::new(component, interaction_elements)
+                .with_segment_start(
+                    component.claim.bitwise_builtin_segment_start,
+                    &bitwise_builtin.segment_start,
+                );
+        update_evaluation_accumulator_var_conditional(
+            evaluation_accumulator,
+            component,
+            &bitwise_builtin_var,
+            &oods_map,
+            &samples,
+            &bitwise_builtin.log_size,
+            &proof.interaction_claim.builtins.bitwise_builtin,
+            true,
+            &proof.stark_proof.is_preprocessed_trace_present,
+            &bitwise_builtin.is_present,
+        );
+
+        let mul_mod_builtin = &proof.claim.builtins.mul_mod_builtin;
+        let component = component_generator
+            .builtins
+            .mul_mod_builtin
+            .as_ref()
+            .unwrap();
+        let mul_mod_builtin_var =
+            crate::components::mul_mod_builtin::Component::new(component, interaction_elements)
+                .with_segment_start(
+                    component.claim.mul_mod_builtin_segment_start,
+                    &mul_mod_builtin.segment_start,
+                );
+        update_evaluation_accumulator_var_conditional(
+            evaluation_accumulator,
+            component,
+            &mul_mod_builtin_var,
+            &oods_map,
+            &samples,
+            &mul_mod_builtin.log_size,
+            &proof.interaction_claim.builtins.mul_mod_builtin,
+            true,
+            &proof.stark_proof.is_preprocessed_trace_present,
+            &mul_mod_builtin.is_present,
+        );
+
+        let poseidon_builtin = &proof.claim.builtins.poseidon_builtin;
+        let component = component_generator
+            .builtins
+            .poseidon_builtin
+            .as_ref()
+            .unwrap();
+        let poseidon_builtin_var =
+            crate::components::poseidon_builtin::Component::new(component, interaction_elements)
+                .with_segment_start(
+                    component.claim.poseidon_builtin_segment_start,
+                    &poseidon_builtin.segment_start,
+                );
+        update_evaluation_accumulator_var_conditional(
+            evaluation_accumulator,
+            component,
+            &poseidon_builtin_var,
+            &oods_map,
+            &samples,
+            &poseidon_builtin.log_size,
+            &proof.interaction_claim.builtins.poseidon_builtin,
+            true,
+            &proof.stark_proof.is_preprocessed_trace_present,
+            &poseidon_builtin.is_present,
+        );
+
+        let range_check_96_builtin = &proof.claim.builtins.range_check_96_builtin;
+        let component = component_generator
+            .builtins
+            .range_check_96_builtin
+            .as_ref()
+            .unwrap();
+        let range_check_builtin_bits_96_var =
+            crate::components::range_check_builtin_bits_96::Component::new(
+                component,
+                interaction_elements,
+            )
+            .with_segment_start(
+                component.claim.range_check96_builtin_segment_start,
+                &range_check_96_builtin.segment_start,
+            );
+        update_evaluation_accumulator_var_conditional(
+            evaluation_accumulator,
+            component,
+            &range_check_builtin_bits_96_var,
+            &oods_map,
+            &samples,
+            &range_check_96_builtin.log_size,
+            &proof.interaction_claim.builtins.range_check_96_builtin,
+            true,
+            &proof.stark_proof.is_preprocessed_trace_present,
+            &range_check_96_builtin.is_present,
+        );
+
         let range_check_128_builtin = component_generator
             .builtins
             .range_check_128_builtin
@@ -722,12 +1123,137 @@ impl CairoCompositionCheck {
             &oods_map,
             &samples,
             &proof.claim.builtins.range_check_128_builtin_log_size,
-            &proof.interaction_claim.builtins,
+            &proof.interaction_claim.builtins.range_check_128_builtin,
             true,
             &proof.stark_proof.is_preprocessed_trace_present,
         );
     }
 
+    pub fn poseidon_context_evaluation(
+        evaluation_accumulator: &mut PointEvaluationAccumulatorVar,
+        component_generator: &CairoComponents,
+        interaction_elements: &CairoInteractionElementsVar,
+        oods_map: &CosetVanishingMapVar,
+        proof: &CairoProofVar,
+        samples: &WrappedSamplesValues,
+    ) {
+        // An absent context is evaluated on the padding components, and its evaluation is dropped.
+        let poseidon_context_claim = &proof.claim.poseidon_context;
+        let poseidon_context_interaction_claim = &proof.interaction_claim.poseidon_context;
+        let poseidon_context_components = &component_generator
+            .poseidon_context
+            .components
+            .as_ref()
+            .unwrap();
+
+        let poseidon_aggregator_var =
+            crate::components::poseidon::poseidon_aggregator::Component::new(
+                &poseidon_context_components.poseidon_aggregator,
+                interaction_elements,
+            );
+        update_evaluation_accumulator_var_conditional(
+            evaluation_accumulator,
+            &poseidon_context_components.poseidon_aggregator,
+            &poseidon_aggregator_var,
+            &oods_map,
+            &samples,
+            &poseidon_context_claim.poseidon_aggregator,
+            &poseidon_context_interaction_claim.poseidon_aggregator,
+            false,
+            &proof.stark_proof.is_preprocessed_trace_present,
+            &poseidon_context_claim.is_present,
+        );
+
+        let poseidon_3_partial_rounds_chain_var =
+            crate::components::poseidon::poseidon_3_partial_rounds_chain::Component::new(
+                &poseidon_context_components.poseidon_3_partial_rounds_chain,
+                interaction_elements,
+            );
+        update_evaluation_accumulator_var_conditional(
+            evaluation_accumulator,
+            &poseidon_context_components.poseidon_3_partial_rounds_chain,
+            &poseidon_3_partial_rounds_chain_var,
+            &oods_map,
+            &samples,
+            &poseidon_context_claim.poseidon_3_partial_rounds_chain,
+            &poseidon_context_interaction_claim.poseidon_3_partial_rounds_chain,
+            false,
+            &proof.stark_proof.is_preprocessed_trace_present,
+            &poseidon_context_claim.is_present,
+        );
+
+        let poseidon_full_round_chain_var =
+            crate::components::poseidon::poseidon_full_round_chain::Component::new(
+                &poseidon_context_components.poseidon_full_round_chain,
+                interaction_elements,
+            );
+        update_evaluation_accumulator_var_conditional(
+            evaluation_accumulator,
+            &poseidon_context_components.poseidon_full_round_chain,
+            &poseidon_full_round_chain_var,
+            &oods_map,
+            &samples,
+            &poseidon_context_claim.poseidon_full_round_chain,
+            &poseidon_context_interaction_claim.poseidon_full_round_chain,
+            false,
+            &proof.stark_proof.is_preprocessed_trace_present,
+            &poseidon_context_claim.is_present,
+        );
+
+        let cube_252_var = crate::components::poseidon::cube_252::Component::new(
+            &poseidon_context_components.cube_252,
+            interaction_elements,
+        );
+        update_evaluation_accumulator_var_conditional(
+            evaluation_accumulator,
+            &poseidon_context_components.cube_252,
+            &cube_252_var,
+            &oods_map,
+            &samples,
+            &poseidon_context_claim.cube_252,
+            &poseidon_context_interaction_claim.cube_252,
+            false,
+            &proof.stark_proof.is_preprocessed_trace_present,
+            &poseidon_context_claim.is_present,
+        );
+
+        let poseidon_round_keys_var =
+            crate::components::poseidon::poseidon_round_keys::Component::new(
+                &poseidon_context_components.poseidon_round_keys,
+                interaction_elements,
+            );
+        update_evaluation_accumulator_var_conditional(
+            evaluation_accumulator,
+            &poseidon_context_components.poseidon_round_keys,
+            &poseidon_round_keys_var,
+            &oods_map,
+            &samples,
+            &poseidon_context_claim.poseidon_round_keys,
+            &poseidon_context_interaction_claim.poseidon_round_keys,
+            false,
+            &proof.stark_proof.is_preprocessed_trace_present,
+            &poseidon_context_claim.is_present,
+        );
+
+        let range_check_252_width_27_var =
+            crate::components::poseidon::range_check_252_width_27::Component::new(
+                &poseidon_context_components.range_check_252_width_27,
+                interaction_elements,
+            );
+        update_evaluation_accumulator_var_conditional(
+            evaluation_accumulator,
+            &poseidon_context_components.range_check_252_width_27,
+            &range_check_252_width_27_var,
+            &oods_map,
+            &samples,
+            &poseidon_context_claim.range_check_252_width_27,
+            &poseidon_context_interaction_claim.range_check_252_width_27,
+            false,
+            &proof.stark_proof.is_preprocessed_trace_present,
+            &poseidon_context_claim.is_present,
+        );
+    }
+
     pub fn memory_evaluation(
         evaluation_accumulator: &mut PointEvaluationAccumulatorVar,
         component_generator: &CairoComponents,
@@ -753,32 +1279,64 @@ impl CairoCompositionCheck {
             &proof.stark_proof.is_preprocessed_trace_present,
         );
 
-        let memory_id_to_big_components = &component_generator.memory_id_to_value.0[0];
-        assert_eq!(memory_id_to_big_components.offset, 0);
-        let memory_id_to_big_var = crate::components::memory_id_to_big::BigComponent {
-            log_size: memory_id_to_big_components.log_size(),
-            offset: memory_id_to_big_components.offset,
-            lookup_elements: interaction_elements.memory_id_to_value.clone(),
-            range_check_9_9_lookup_elements: interaction_elements.range_checks.rc_9_9.clone(),
-            range_check_9_9_b_lookup_elements: interaction_elements.range_checks.rc_9_9_b.clone(),
-            range_check_9_9_c_lookup_elements: interaction_elements.range_checks.rc_9_9_c.clone(),
-            range_check_9_9_d_lookup_elements: interaction_elements.range_checks.rc_9_9_d.clone(),
-            range_check_9_9_e_lookup_elements: interaction_elements.range_checks.rc_9_9_e.clone(),
-            range_check_9_9_f_lookup_elements: interaction_elements.range_checks.rc_9_9_f.clone(),
-            range_check_9_9_g_lookup_elements: interaction_elements.range_checks.rc_9_9_g.clone(),
-            range_check_9_9_h_lookup_elements: interaction_elements.range_checks.rc_9_9_h.clone(),
-        };
-        update_evaluation_accumulator_var(
-            evaluation_accumulator,
-            &memory_id_to_big_components,
-            &memory_id_to_big_var,
-            &oods_map,
-            &samples,
-            &proof.claim.memory_id_to_value.big_log_size,
-            &proof.interaction_claim.memory_id_to_value.big_claimed_sum,
-            true,
-            &proof.stark_proof.is_preprocessed_trace_present,
-        );
+        let big_log_sizes = &proof.claim.memory_id_to_value.big_log_sizes;
+        for ((((memory_id_to_big_components, log_size), is_active), offset), claimed_sum) in
+            component_generator
+                .memory_id_to_value
+                .0
+                .iter()
+                .zip_eq(big_log_sizes.iter())
+                .zip_eq(big_log_sizes.is_active.iter())
+                .zip_eq(proof.claim.memory_id_to_value.big_offsets())
+                .zip_eq(&proof.interaction_claim.memory_id_to_value.big_claimed_sums)
+        {
+            let memory_id_to_big_var = crate::components::memory_id_to_big::BigComponent {
+                log_size: memory_id_to_big_components.log_size(),
+                offset: WrappedQM31Var::wrap(QM31Var::from(&offset)),
+                lookup_elements: interaction_elements.memory_id_to_value.clone(),
+                range_check_9_9_lookup_elements: interaction_elements.range_checks.rc_9_9.clone(),
+                range_check_9_9_b_lookup_elements: interaction_elements
+                    .range_checks
+                    .rc_9_9_b
+                    .clone(),
+                range_check_9_9_c_lookup_elements: interaction_elements
+                    .range_checks
+                    .rc_9_9_c
+                    .clone(),
+                range_check_9_9_d_lookup_elements: interaction_elements
+                    .range_checks
+                    .rc_9_9_d
+                    .clone(),
+                range_check_9_9_e_lookup_elements: interaction_elements
+                    .range_checks
+                    .rc_9_9_e
+                    .clone(),
+                range_check_9_9_f_lookup_elements: interaction_elements
+                    .range_checks
+                    .rc_9_9_f
+                    .clone(),
+                range_check_9_9_g_lookup_elements: interaction_elements
+                    .range_checks
+                    .rc_9_9_g
+                    .clone(),
+                range_check_9_9_h_lookup_elements: interaction_elements
+                    .range_checks
+                    .rc_9_9_h
+                    .clone(),
+            };
+            update_evaluation_accumulator_var_conditional(
+                evaluation_accumulator,
+                &memory_id_to_big_components,
+                &memory_id_to_big_var,
+                &oods_map,
+                &samples,
+                log_size,
+                claimed_sum,
+                true,
+                &proof.stark_proof.is_preprocessed_trace_present,
+                is_active,
+            );
+        }
 
         let memory_id_to_small_components = &component_generator.memory_id_to_value.1;
         let memory_id_to_small_var = crate::components::memory_id_to_big::SmallComponent {
@@ -1367,13 +1925,46 @@ pub fn update_evaluation_accumulator_var<C: FrameworkEval, R: ComponentVar>(
     ));
 }
 
+/// Like `update_evaluation_accumulator_var`, but leaves the accumulator unchanged if the instance
+/// slot is inactive.
+pub fn update_evaluation_accumulator_var_conditional<C: FrameworkEval, R: ComponentVar>(
+    evaluation_accumulator: &mut PointEvaluationAccumulatorVar,
+    component: &FrameworkComponent<C>,
+    component_var: &R,
+    point: &CosetVanishingMapVar,
+    mask: &WrappedSamplesValues,
+    log_size: &LogSizeVar,
+    claimed_sum: &QM31Var,
+    seq_franking: bool,
+    is_preprocessed_trace_present: &[PreProcessedTracePresent],
+    is_active: &BitVar,
+) {
+    let old_accumulation = evaluation_accumulator.accumulation.clone();
+    update_evaluation_accumulator_var(
+        evaluation_accumulator,
+        component,
+        component_var,
+        point,
+        mask,
+        log_size,
+        claimed_sum,
+        seq_franking,
+        is_preprocessed_trace_present,
+    );
+    evaluation_accumulator.accumulation = QM31Var::select(
+        &old_accumulation,
+        &evaluation_accumulator.accumulation,
+        is_active,
+    );
+}
+
 #[cfg(test)]
 mod test {
     use std::path::PathBuf;
 
     use cairo_air::utils::{deserialize_proof_from_file, ProofFormat};
     use cairo_plonk_dsl_data_structures::CairoProofVar;
-    use cairo_plonk_dsl_hints::CairoCompositionHints;
+    use cairo_plonk_dsl_hints::{CairoCompositionHints, CairoVerifierParams};
     use circle_plonk_dsl_constraint_system::{var::AllocVar, ConstraintSystemRef};
     use rand::{Rng, SeedableRng};
     use stwo::core::{
@@ -1418,7 +2009,8 @@ mod test {
 
         let proof = deserialize_proof_from_file(&data_path, ProofFormat::Binary).unwrap();
 
-        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof);
+        let params = CairoVerifierParams::from_proof(&proof);
+        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof, &params);
         let proof_var = CairoProofVar::new_witness(&cs, &proof);
         let fiat_shamir_results = CairoFiatShamirResults::compute(&fiat_shamir_hints, &proof_var);
         let _ = CairoCompositionHints::new(&fiat_shamir_hints, &proof);
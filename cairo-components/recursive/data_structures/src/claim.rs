@@ -1,186 +1,304 @@
 use cairo_air::{
     air::CairoClaim, blake::air::BlakeContextClaim, builtins_air::BuiltinsClaim,
-    components::memory_id_to_big, opcodes_air::OpcodeClaim,
+    components::memory_id_to_big, opcodes_air::OpcodeClaim, poseidon::air::PoseidonContextClaim,
 };
+use cairo_plonk_dsl_hints::MAX_COMPONENT_INSTANCES;
 use circle_plonk_dsl_constraint_system::{
     var::{AllocVar, AllocationMode, Var},
     ConstraintSystemRef,
 };
-use circle_plonk_dsl_primitives::{ChannelVar, LogSizeVar, M31Var, QM31Var};
+use circle_plonk_dsl_primitives::{BitVar, ChannelVar, LogSizeVar, M31Var, QM31Var};
 use indexmap::IndexMap;
+use itertools::Itertools;
 use stwo::core::fields::m31::M31;
 
 use crate::{public_data::PublicDataVar, BitIntVar};
 
-macro_rules! accumulate_component {
-    ($component_name:ident, $expr:expr, $relation_uses:ident) => {{
-        let pow2_var = &($expr.pow2);
-        let zero = M31Var::zero(&pow2_var.cs());
+/// Enforce that a present component has a log size within the bitmap, and an absent one the log
+/// size 0.
+fn enforce_presence(log_size: &LogSizeVar, is_present: &BitVar) {
+    let cs = log_size.cs();
+    let mut in_bitmap = M31Var::zero(&cs);
+    for bit in log_size.bitmap.values() {
+        in_bitmap = &in_bitmap + &bit.0;
+    }
+    in_bitmap.equalverify(&is_present.0);
+    (&log_size.m31 * &is_present.neg().0).equalverify(&M31Var::zero(&cs));
+}
+
+/// The number of rows of a component, which is 0 if it is absent.
+fn n_rows(log_size: &LogSizeVar, is_present: &BitVar) -> M31Var {
+    &log_size.pow2 * &is_present.0
+}
+
+macro_rules! accumulate_rows {
+    ($component_name:ident, $n_rows:expr, $relation_uses:ident) => {{
+        let n_rows: &M31Var = $n_rows;
+        let zero = M31Var::zero(&n_rows.cs());
         for entry in cairo_air::components::$component_name::RELATION_USES_PER_ROW {
             let cur = $relation_uses.get(entry.relation_id).unwrap_or(&zero);
-            let new = pow2_var.mul_constant(M31::from(entry.uses as u32));
+            let new = n_rows.mul_constant(M31::from(entry.uses as u32));
             $relation_uses.insert(entry.relation_id, cur.add_assert_no_overflow(&new));
         }
     }};
 }
 
+macro_rules! accumulate_component {
+    ($component_name:ident, $expr:expr, $relation_uses:ident) => {{
+        accumulate_rows!($component_name, &($expr.pow2), $relation_uses);
+    }};
+}
+
+macro_rules! accumulate_instances {
+    ($component_name:ident, $expr:expr, $relation_uses:ident) => {{
+        for n_rows in $expr.n_rows() {
+            accumulate_rows!($component_name, &n_rows, $relation_uses);
+        }
+    }};
+}
+
+/// The log sizes of the instances of a component, padded to `MAX_COMPONENT_INSTANCES` slots. The
+/// active slots form a prefix, and an inactive slot has the log size 0, which is outside of the
+/// bitmap of its `LogSizeVar`, so that the oblivious selections by log size skip it.
+#[derive(Debug, Clone)]
+pub struct InstancesVar {
+    pub log_sizes: Vec<LogSizeVar>,
+    pub is_active: Vec<BitVar>,
+}
+
+impl InstancesVar {
+    pub fn new_variables(
+        cs: &ConstraintSystemRef,
+        log_sizes: &[u32],
+        mode: AllocationMode,
+    ) -> Self {
+        assert!(log_sizes.len() <= MAX_COMPONENT_INSTANCES);
+
+        let mut padded = log_sizes.to_vec();
+        padded.resize(MAX_COMPONENT_INSTANCES, 0);
+        let is_active = (0..MAX_COMPONENT_INSTANCES)
+            .map(|i| BitVar::new_variables(cs, &(i < log_sizes.len()), mode))
+            .collect_vec();
+        let log_sizes = padded
+            .iter()
+            .map(|log_size| LogSizeVar::new_variables(cs, log_size, mode))
+            .collect_vec();
+
+        // a slot can only be active if the previous one is
+        for pair in is_active.windows(2) {
+            (&pair[1] & &pair[0].neg()).0.equalverify(&M31Var::zero(cs));
+        }
+        for (log_size, is_active) in log_sizes.iter().zip_eq(is_active.iter()) {
+            enforce_presence(log_size, is_active);
+        }
+
+        Self {
+            log_sizes,
+            is_active,
+        }
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, LogSizeVar> {
+        self.log_sizes.iter()
+    }
+
+    /// The number of active slots.
+    pub fn n_active(&self) -> M31Var {
+        let mut n_active = M31Var::zero(&self.is_active[0].cs());
+        for is_active in self.is_active.iter() {
+            n_active = &n_active + &is_active.0;
+        }
+        n_active
+    }
+
+    /// The number of rows of each slot, which is 0 for an inactive slot.
+    pub fn n_rows(&self) -> Vec<M31Var> {
+        self.log_sizes
+            .iter()
+            .zip_eq(self.is_active.iter())
+            .map(|(log_size, is_active)| n_rows(log_size, is_active))
+            .collect()
+    }
+
+    /// Mix the log sizes of the active slots.
+    pub fn mix_log_sizes(&self, channel: &mut ChannelVar) {
+        for (log_size, is_active) in self.log_sizes.iter().zip_eq(self.is_active.iter()) {
+            log_size.bits.conditional_mix_into(channel, is_active);
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct OpcodeClaimVar {
-    pub add: LogSizeVar,
-    pub add_small: LogSizeVar,
-    pub add_ap: LogSizeVar,
-    pub assert_eq: LogSizeVar,
-    pub assert_eq_imm: LogSizeVar,
-    pub assert_eq_double_deref: LogSizeVar,
-    pub blake: LogSizeVar,
-    pub call: LogSizeVar,
-    pub call_rel_imm: LogSizeVar,
-    pub jnz: LogSizeVar,
-    pub jnz_taken: LogSizeVar,
-    pub jump_rel: LogSizeVar,
-    pub jump_rel_imm: LogSizeVar,
-    pub mul: LogSizeVar,
-    pub mul_small: LogSizeVar,
-    pub qm31: LogSizeVar,
-    pub ret: LogSizeVar,
+    pub cs: ConstraintSystemRef,
+    pub add: InstancesVar,
+    pub add_small: InstancesVar,
+    pub add_ap: InstancesVar,
+    pub assert_eq: InstancesVar,
+    pub assert_eq_imm: InstancesVar,
+    pub assert_eq_double_deref: InstancesVar,
+    pub blake: InstancesVar,
+    pub call: InstancesVar,
+    pub call_rel_imm: InstancesVar,
+    pub generic: InstancesVar,
+    pub jnz: InstancesVar,
+    pub jnz_taken: InstancesVar,
+    pub jump: InstancesVar,
+    pub jump_double_deref: InstancesVar,
+    pub jump_rel: InstancesVar,
+    pub jump_rel_imm: InstancesVar,
+    pub mul: InstancesVar,
+    pub mul_small: InstancesVar,
+    pub qm31: InstancesVar,
+    pub ret: InstancesVar,
 }
 
 impl Var for OpcodeClaimVar {
     type Value = OpcodeClaim;
 
     fn cs(&self) -> ConstraintSystemRef {
-        self.add.cs()
+        self.cs.clone()
     }
 }
 
 impl AllocVar for OpcodeClaimVar {
     fn new_variables(cs: &ConstraintSystemRef, value: &Self::Value, mode: AllocationMode) -> Self {
-        let add = LogSizeVar::new_variables(cs, &value.add[0].log_size, mode);
-        let add_small = LogSizeVar::new_variables(cs, &value.add_small[0].log_size, mode);
-        let add_ap = LogSizeVar::new_variables(cs, &value.add_ap[0].log_size, mode);
-        let assert_eq = LogSizeVar::new_variables(cs, &value.assert_eq[0].log_size, mode);
-        let assert_eq_imm = LogSizeVar::new_variables(cs, &value.assert_eq_imm[0].log_size, mode);
-        let assert_eq_double_deref =
-            LogSizeVar::new_variables(cs, &value.assert_eq_double_deref[0].log_size, mode);
-        let blake = LogSizeVar::new_variables(cs, &value.blake[0].log_size, mode);
-        let call = LogSizeVar::new_variables(cs, &value.call[0].log_size, mode);
-        let call_rel_imm = LogSizeVar::new_variables(cs, &value.call_rel_imm[0].log_size, mode);
-        let jnz = LogSizeVar::new_variables(cs, &value.jnz[0].log_size, mode);
-        let jnz_taken = LogSizeVar::new_variables(cs, &value.jnz_taken[0].log_size, mode);
-        let jump_rel = LogSizeVar::new_variables(cs, &value.jump_rel[0].log_size, mode);
-        let jump_rel_imm = LogSizeVar::new_variables(cs, &value.jump_rel_imm[0].log_size, mode);
-        let mul = LogSizeVar::new_variables(cs, &value.mul[0].log_size, mode);
-        let mul_small = LogSizeVar::new_variables(cs, &value.mul_small[0].log_size, mode);
-        let qm31 = LogSizeVar::new_variables(cs, &value.qm31[0].log_size, mode);
-        let ret = LogSizeVar::new_variables(cs, &value.ret[0].log_size, mode);
+        // Each opcode has `MAX_COMPONENT_INSTANCES` slots, so that the number of its instances is
+        // a variable like their log sizes.
+        macro_rules! new_instances {
+            ($name:ident) => {
+                InstancesVar::new_variables(
+                    cs,
+                    &value.$name.iter().map(|claim| claim.log_size).collect_vec(),
+                    mode,
+                )
+            };
+        }
 
         Self {
-            add,
-            add_small,
-            add_ap,
-            assert_eq,
-            assert_eq_imm,
-            assert_eq_double_deref,
-            blake,
-            call,
-            call_rel_imm,
-            jnz,
-            jnz_taken,
-            jump_rel,
-            jump_rel_imm,
-            mul,
-            mul_small,
-            qm31,
-            ret,
+            cs: cs.clone(),
+            add: new_instances!(add),
+            add_small: new_instances!(add_small),
+            add_ap: new_instances!(add_ap),
+            assert_eq: new_instances!(assert_eq),
+            assert_eq_imm: new_instances!(assert_eq_imm),
+            assert_eq_double_deref: new_instances!(assert_eq_double_deref),
+            blake: new_instances!(blake),
+            call: new_instances!(call),
+            call_rel_imm: new_instances!(call_rel_imm),
+            generic: new_instances!(generic),
+            jnz: new_instances!(jnz),
+            jnz_taken: new_instances!(jnz_taken),
+            jump: new_instances!(jump),
+            jump_double_deref: new_instances!(jump_double_deref),
+            jump_rel: new_instances!(jump_rel),
+            jump_rel_imm: new_instances!(jump_rel_imm),
+            mul: new_instances!(mul),
+            mul_small: new_instances!(mul_small),
+            qm31: new_instances!(qm31),
+            ret: new_instances!(ret),
         }
     }
 }
 
 impl OpcodeClaimVar {
+    /// Mix the number of instances of an opcode, followed by the log size of each instance.
+    fn mix_instances(channel: &mut ChannelVar, instances: &InstancesVar) {
+        channel.mix_one_felt(&QM31Var::from(&instances.n_active()));
+        instances.mix_log_sizes(channel);
+    }
+
     pub fn mix_into(&self, channel: &mut ChannelVar) {
-        channel.mix_one_felt(&QM31Var::one(&self.cs()));
-        self.add.mix_into(channel);
-        channel.mix_one_felt(&QM31Var::one(&self.cs()));
-        self.add_small.mix_into(channel);
-        channel.mix_one_felt(&QM31Var::one(&self.cs()));
-        self.add_ap.mix_into(channel);
-        channel.mix_one_felt(&QM31Var::one(&self.cs()));
-        self.assert_eq.mix_into(channel);
-        channel.mix_one_felt(&QM31Var::one(&self.cs()));
-        self.assert_eq_imm.mix_into(channel);
-        channel.mix_one_felt(&QM31Var::one(&self.cs()));
-        self.assert_eq_double_deref.mix_into(channel);
-        channel.mix_one_felt(&QM31Var::one(&self.cs()));
-        self.blake.mix_into(channel);
-        channel.mix_one_felt(&QM31Var::one(&self.cs()));
-        self.call.mix_into(channel);
-        channel.mix_one_felt(&QM31Var::one(&self.cs()));
-        self.call_rel_imm.mix_into(channel);
-        channel.mix_one_felt(&QM31Var::zero(&self.cs()));
-        channel.mix_one_felt(&QM31Var::one(&self.cs()));
-        self.jnz.mix_into(channel);
-        channel.mix_one_felt(&QM31Var::one(&self.cs()));
-        self.jnz_taken.mix_into(channel);
-        channel.mix_one_felt(&QM31Var::zero(&self.cs()));
-        channel.mix_one_felt(&QM31Var::zero(&self.cs()));
-        channel.mix_one_felt(&QM31Var::one(&self.cs()));
-        self.jump_rel.mix_into(channel);
-        channel.mix_one_felt(&QM31Var::one(&self.cs()));
-        self.jump_rel_imm.mix_into(channel);
-        channel.mix_one_felt(&QM31Var::one(&self.cs()));
-        self.mul.mix_into(channel);
-        channel.mix_one_felt(&QM31Var::one(&self.cs()));
-        self.mul_small.mix_into(channel);
-        channel.mix_one_felt(&QM31Var::one(&self.cs()));
-        self.qm31.mix_into(channel);
-        channel.mix_one_felt(&QM31Var::one(&self.cs()));
-        self.ret.mix_into(channel);
+        Self::mix_instances(channel, &self.add);
+        Self::mix_instances(channel, &self.add_small);
+        Self::mix_instances(channel, &self.add_ap);
+        Self::mix_instances(channel, &self.assert_eq);
+        Self::mix_instances(channel, &self.assert_eq_imm);
+        Self::mix_instances(channel, &self.assert_eq_double_deref);
+        Self::mix_instances(channel, &self.blake);
+        Self::mix_instances(channel, &self.call);
+        Self::mix_instances(channel, &self.call_rel_imm);
+        Self::mix_instances(channel, &self.generic);
+        Self::mix_instances(channel, &self.jnz);
+        Self::mix_instances(channel, &self.jnz_taken);
+        Self::mix_instances(channel, &self.jump);
+        Self::mix_instances(channel, &self.jump_double_deref);
+        Self::mix_instances(channel, &self.jump_rel);
+        Self::mix_instances(channel, &self.jump_rel_imm);
+        Self::mix_instances(channel, &self.mul);
+        Self::mix_instances(channel, &self.mul_small);
+        Self::mix_instances(channel, &self.qm31);
+        Self::mix_instances(channel, &self.ret);
     }
 
     pub fn accumulate_relation_uses(&self, relation_uses: &mut IndexMap<&str, M31Var>) {
-        accumulate_component!(add_opcode, self.add, relation_uses);
-        accumulate_component!(add_opcode_small, self.add_small, relation_uses);
-        accumulate_component!(add_ap_opcode, self.add_ap, relation_uses);
-        accumulate_component!(assert_eq_opcode, self.assert_eq, relation_uses);
-        accumulate_component!(assert_eq_opcode_imm, self.assert_eq_imm, relation_uses);
-        accumulate_component!(
+        accumulate_instances!(add_opcode, self.add, relation_uses);
+        accumulate_instances!(add_opcode_small, self.add_small, relation_uses);
+        accumulate_instances!(add_ap_opcode, self.add_ap, relation_uses);
+        accumulate_instances!(assert_eq_opcode, self.assert_eq, relation_uses);
+        accumulate_instances!(assert_eq_opcode_imm, self.assert_eq_imm, relation_uses);
+        accumulate_instances!(
             assert_eq_opcode_double_deref,
             self.assert_eq_double_deref,
             relation_uses
         );
-        accumulate_component!(blake_compress_opcode, self.blake, relation_uses);
-        accumulate_component!(call_opcode_abs, self.call, relation_uses);
-        accumulate_component!(call_opcode_rel_imm, self.call_rel_imm, relation_uses);
-        accumulate_component!(jnz_opcode_non_taken, self.jnz, relation_uses);
-        accumulate_component!(jnz_opcode_taken, self.jnz_taken, relation_uses);
-        accumulate_component!(jump_opcode_rel, self.jump_rel, relation_uses);
-        accumulate_component!(jump_opcode_rel_imm, self.jump_rel_imm, relation_uses);
-        accumulate_component!(mul_opcode, self.mul, relation_uses);
-        accumulate_component!(mul_opcode_small, self.mul_small, relation_uses);
-        accumulate_component!(qm_31_add_mul_opcode, self.qm31, relation_uses);
-        accumulate_component!(ret_opcode, self.ret, relation_uses);
+        accumulate_instances!(blake_compress_opcode, self.blake, relation_uses);
+        accumulate_instances!(call_opcode_abs, self.call, relation_uses);
+        accumulate_instances!(call_opcode_rel_imm, self.call_rel_imm, relation_uses);
+        accumulate_instances!(generic_opcode, self.generic, relation_uses);
+        accumulate_instances!(jnz_opcode_non_taken, self.jnz, relation_uses);
+        accumulate_instances!(jnz_opcode_taken, self.jnz_taken, relation_uses);
+        accumulate_instances!(jump_opcode_abs, self.jump, relation_uses);
+        accumulate_instances!(
+            jump_opcode_double_deref,
+            self.jump_double_deref,
+            relation_uses
+        );
+        accumulate_instances!(jump_opcode_rel, self.jump_rel, relation_uses);
+        accumulate_instances!(jump_opcode_rel_imm, self.jump_rel_imm, relation_uses);
+        accumulate_instances!(mul_opcode, self.mul, relation_uses);
+        accumulate_instances!(mul_opcode_small, self.mul_small, relation_uses);
+        accumulate_instances!(qm_31_add_mul_opcode, self.qm31, relation_uses);
+        accumulate_instances!(ret_opcode, self.ret, relation_uses);
     }
 
-    pub fn max_log_size(&self) -> M31Var {
-        let mut max = self.add.m31.clone();
-        max = max.max(&self.add_small.m31, 5);
-        max = max.max(&self.add_ap.m31, 5);
-        max = max.max(&self.assert_eq.m31, 5);
-        max = max.max(&self.assert_eq_imm.m31, 5);
-        max = max.max(&self.assert_eq_double_deref.m31, 5);
-        max = max.max(&self.blake.m31, 5);
-        max = max.max(&self.call.m31, 5);
-        max = max.max(&self.call_rel_imm.m31, 5);
-        max = max.max(&self.jnz.m31, 5);
-        max = max.max(&self.jnz_taken.m31, 5);
-        max = max.max(&self.jump_rel.m31, 5);
-        max = max.max(&self.jump_rel_imm.m31, 5);
-        max = max.max(&self.mul.m31, 5);
-        max = max.max(&self.mul_small.m31, 5);
-        max = max.max(&self.qm31.m31, 5);
-        max = max.max(&self.ret.m31, 5);
+    /// Return the instance slots of all the opcodes, in the order of the opcodes.
+    pub fn instances(&self) -> [&InstancesVar; 20] {
+        [
+            &self.add,
+            &self.add_small,
+            &self.add_ap,
+            &self.assert_eq,
+            &self.assert_eq_imm,
+            &self.assert_eq_double_deref,
+            &self.blake,
+            &self.call,
+            &self.call_rel_imm,
+            &self.generic,
+            &self.jnz,
+            &self.jnz_taken,
+            &self.jump,
+            &self.jump_double_deref,
+            &self.jump_rel,
+            &self.jump_rel_imm,
+            &self.mul,
+            &self.mul_small,
+            &self.qm31,
+            &self.ret,
+        ]
+    }
 
-        max
+    /// Return the log sizes of all the opcode instance slots, in the order of the opcodes.
+    pub fn log_sizes(&self) -> impl Iterator<Item = &LogSizeVar> {
+        self.instances()
+            .into_iter()
+            .flat_map(|instances| instances.iter())
+    }
+
+    pub fn max_log_size(&self) -> Option<M31Var> {
+        self.log_sizes()
+            .map(|log_size| log_size.m31.clone())
+            .reduce(|max, log_size| max.max(&log_size, 5))
     }
 }
 
@@ -236,8 +354,171 @@ impl BlakeContextClaimVar {
     }
 }
 
+/// The claim of the Poseidon context, which is present if and only if the poseidon builtin is. An
+/// absent context has the log size 0 for each of its components.
+#[derive(Debug, Clone)]
+pub struct PoseidonContextClaimVar {
+    pub is_present: BitVar,
+    pub poseidon_aggregator: LogSizeVar,
+    pub poseidon_3_partial_rounds_chain: LogSizeVar,
+    pub poseidon_full_round_chain: LogSizeVar,
+    pub cube_252: LogSizeVar,
+    pub range_check_252_width_27: LogSizeVar,
+    /// The fixed log size of the round keys table if the context is present, and 0 otherwise.
+    pub poseidon_round_keys: LogSizeVar,
+}
+
+impl Var for PoseidonContextClaimVar {
+    type Value = PoseidonContextClaim;
+
+    fn cs(&self) -> ConstraintSystemRef {
+        self.poseidon_aggregator.cs()
+    }
+}
+
+impl AllocVar for PoseidonContextClaimVar {
+    fn new_variables(cs: &ConstraintSystemRef, value: &Self::Value, mode: AllocationMode) -> Self {
+        let is_present = BitVar::new_variables(cs, &value.claim.is_some(), mode);
+        let new_log_size = |log_size: Option<u32>| {
+            let log_size = LogSizeVar::new_variables(cs, &log_size.unwrap_or(0), mode);
+            enforce_presence(&log_size, &is_present);
+            log_size
+        };
+
+        let value = value.claim.as_ref();
+        let poseidon_aggregator = new_log_size(value.map(|v| v.poseidon_aggregator.log_size));
+        let poseidon_3_partial_rounds_chain =
+            new_log_size(value.map(|v| v.poseidon_3_partial_rounds_chain.log_size));
+        let poseidon_full_round_chain =
+            new_log_size(value.map(|v| v.poseidon_full_round_chain.log_size));
+        let cube_252 = new_log_size(value.map(|v| v.cube_252.log_size));
+        let range_check_252_width_27 =
+            new_log_size(value.map(|v| v.range_check_252_width_27.log_size));
+        let poseidon_round_keys =
+            new_log_size(value.map(|_| cairo_air::components::poseidon_round_keys::LOG_SIZE));
+        poseidon_round_keys
+            .m31
+            .equalverify(&is_present.0.mul_constant(M31::from(
+                cairo_air::components::poseidon_round_keys::LOG_SIZE,
+            )));
+        Self {
+            is_present,
+            poseidon_aggregator,
+            poseidon_3_partial_rounds_chain,
+            poseidon_full_round_chain,
+            cube_252,
+            range_check_252_width_27,
+            poseidon_round_keys,
+        }
+    }
+}
+
+impl PoseidonContextClaimVar {
+    /// Mix the log sizes if the context is present.
+    pub fn mix_into(&self, channel: &mut ChannelVar) {
+        for log_size in self.log_sizes() {
+            log_size
+                .bits
+                .conditional_mix_into(channel, &self.is_present);
+        }
+    }
+
+    /// The log sizes of the claim, without the fixed one of the round keys table.
+    fn log_sizes(&self) -> [&LogSizeVar; 5] {
+        [
+            &self.poseidon_aggregator,
+            &self.poseidon_3_partial_rounds_chain,
+            &self.poseidon_full_round_chain,
+            &self.cube_252,
+            &self.range_check_252_width_27,
+        ]
+    }
+
+    pub fn accumulate_relation_uses(&self, relation_uses: &mut IndexMap<&str, M31Var>) {
+        let is_present = &self.is_present;
+        accumulate_rows!(
+            poseidon_aggregator,
+            &n_rows(&self.poseidon_aggregator, is_present),
+            relation_uses
+        );
+        accumulate_rows!(
+            poseidon_3_partial_rounds_chain,
+            &n_rows(&self.poseidon_3_partial_rounds_chain, is_present),
+            relation_uses
+        );
+        accumulate_rows!(
+            poseidon_full_round_chain,
+            &n_rows(&self.poseidon_full_round_chain, is_present),
+            relation_uses
+        );
+        accumulate_rows!(cube_252, &n_rows(&self.cube_252, is_present), relation_uses);
+        accumulate_rows!(
+            range_check_252_width_27,
+            &n_rows(&self.range_check_252_width_27, is_present),
+            relation_uses
+        );
+    }
+
+    pub fn max_log_size(&self) -> M31Var {
+        let mut max = self.poseidon_aggregator.m31.clone();
+        max = max.max(&self.poseidon_3_partial_rounds_chain.m31, 5);
+        max = max.max(&self.poseidon_full_round_chain.m31, 5);
+        max = max.max(&self.cube_252.m31, 5);
+        max = max.max(&self.range_check_252_width_27.m31, 5);
+
+        max
+    }
+}
+
+/// The claim of an optional builtin, which is present if and only if its segment is not empty. An
+/// absent builtin has the log size 0 and the segment start 0.
+#[derive(Debug, Clone)]
+pub struct BuiltinClaimVar {
+    pub is_present: BitVar,
+    pub log_size: LogSizeVar,
+    pub segment_start: BitIntVar<31>,
+}
+
+impl BuiltinClaimVar {
+    /// Allocate the claim from the log size and the segment start of the builtin, if present.
+    pub fn new_variables(
+        cs: &ConstraintSystemRef,
+        claim: Option<(u32, u32)>,
+        mode: AllocationMode,
+    ) -> Self {
+        let (log_size, segment_start) = claim.unwrap_or((0, 0));
+        let is_present = BitVar::new_variables(cs, &claim.is_some(), mode);
+        let log_size = LogSizeVar::new_variables(cs, &log_size, mode);
+        enforce_presence(&log_size, &is_present);
+        Self {
+            is_present,
+            log_size,
+            segment_start: BitIntVar::<31>::new_variables(cs, &(segment_start as u64), mode),
+        }
+    }
+
+    /// Mix the log size and the segment start if the builtin is present.
+    pub fn mix_into(&self, channel: &mut ChannelVar) {
+        self.log_size
+            .bits
+            .conditional_mix_into(channel, &self.is_present);
+        self.segment_start
+            .conditional_mix_into(channel, &self.is_present);
+    }
+
+    /// The number of rows of the builtin, which is 0 if it is absent.
+    pub fn n_rows(&self) -> M31Var {
+        n_rows(&self.log_size, &self.is_present)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct BuiltinsClaimVar {
+    pub add_mod_builtin: BuiltinClaimVar,
+    pub bitwise_builtin: BuiltinClaimVar,
+    pub mul_mod_builtin: BuiltinClaimVar,
+    pub poseidon_builtin: BuiltinClaimVar,
+    pub range_check_96_builtin: BuiltinClaimVar,
     pub range_check_128_builtin_log_size: LogSizeVar,
     pub range_check_builtin_segment_start: BitIntVar<31>,
 }
@@ -252,6 +533,46 @@ impl Var for BuiltinsClaimVar {
 
 impl AllocVar for BuiltinsClaimVar {
     fn new_variables(cs: &ConstraintSystemRef, value: &Self::Value, mode: AllocationMode) -> Self {
+        let add_mod_builtin = BuiltinClaimVar::new_variables(
+            cs,
+            value
+                .add_mod_builtin
+                .as_ref()
+                .map(|claim| (claim.log_size, claim.add_mod_builtin_segment_start)),
+            mode,
+        );
+        let bitwise_builtin = BuiltinClaimVar::new_variables(
+            cs,
+            value
+                .bitwise_builtin
+                .as_ref()
+                .map(|claim| (claim.log_size, claim.bitwise_builtin_segment_start)),
+            mode,
+        );
+        let mul_mod_builtin = BuiltinClaimVar::new_variables(
+            cs,
+            value
+                .mul_mod_builtin
+                .as_ref()
+                .map(|claim| (claim.log_size, claim.mul_mod_builtin_segment_start)),
+            mode,
+        );
+        let poseidon_builtin = BuiltinClaimVar::new_variables(
+            cs,
+            value
+                .poseidon_builtin
+                .as_ref()
+                .map(|claim| (claim.log_size, claim.poseidon_builtin_segment_start)),
+            mode,
+        );
+        let range_check_96_builtin = BuiltinClaimVar::new_variables(
+            cs,
+            value
+                .range_check_96_builtin
+                .as_ref()
+                .map(|claim| (claim.log_size, claim.range_check96_builtin_segment_start)),
+            mode,
+        );
         let range_check_128_builtin_log_size = LogSizeVar::new_variables(
             cs,
             &value.range_check_128_builtin.as_ref().unwrap().log_size,
@@ -267,6 +588,11 @@ impl AllocVar for BuiltinsClaimVar {
             mode,
         );
         Self {
+            add_mod_builtin,
+            bitwise_builtin,
+            mul_mod_builtin,
+            poseidon_builtin,
+            range_check_96_builtin,
             range_check_128_builtin_log_size,
             range_check_builtin_segment_start,
         }
@@ -275,18 +601,70 @@ impl AllocVar for BuiltinsClaimVar {
 
 impl BuiltinsClaimVar {
     pub fn mix_into(&self, channel: &mut ChannelVar) {
+        for builtin in self.optional_builtins() {
+            builtin.mix_into(channel);
+        }
         self.range_check_128_builtin_log_size.mix_into(channel);
         self.range_check_builtin_segment_start.mix_into(channel);
     }
 
+    /// The optional builtins, in the order of the builtins.
+    pub fn optional_builtins(&self) -> [&BuiltinClaimVar; 5] {
+        [
+            &self.add_mod_builtin,
+            &self.bitwise_builtin,
+            &self.mul_mod_builtin,
+            &self.poseidon_builtin,
+            &self.range_check_96_builtin,
+        ]
+    }
+
+    pub fn accumulate_relation_uses(&self, relation_uses: &mut IndexMap<&str, M31Var>) {
+        accumulate_rows!(
+            add_mod_builtin,
+            &self.add_mod_builtin.n_rows(),
+            relation_uses
+        );
+        accumulate_rows!(
+            bitwise_builtin,
+            &self.bitwise_builtin.n_rows(),
+            relation_uses
+        );
+        accumulate_rows!(
+            mul_mod_builtin,
+            &self.mul_mod_builtin.n_rows(),
+            relation_uses
+        );
+        accumulate_rows!(
+            poseidon_builtin,
+            &self.poseidon_builtin.n_rows(),
+            relation_uses
+        );
+        accumulate_rows!(
+            range_check_builtin_bits_96,
+            &self.range_check_96_builtin.n_rows(),
+            relation_uses
+        );
+        accumulate_component!(
+            range_check_builtin_bits_128,
+            self.range_check_128_builtin_log_size,
+            relation_uses
+        );
+    }
+
     pub fn max_log_size(&self) -> M31Var {
-        self.range_check_128_builtin_log_size.m31.clone()
+        let mut max = self.range_check_128_builtin_log_size.m31.clone();
+        for builtin in self.optional_builtins() {
+            max = max.max(&builtin.log_size.m31, 5);
+        }
+
+        max
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct MemoryIdToBigClaimVar {
-    pub big_log_size: LogSizeVar,
+    pub big_log_sizes: InstancesVar,
     pub small_log_size: LogSizeVar,
 }
 
@@ -294,16 +672,16 @@ impl Var for MemoryIdToBigClaimVar {
     type Value = memory_id_to_big::Claim;
 
     fn cs(&self) -> ConstraintSystemRef {
-        self.big_log_size.cs()
+        self.small_log_size.cs()
     }
 }
 
 impl AllocVar for MemoryIdToBigClaimVar {
     fn new_variables(cs: &ConstraintSystemRef, value: &Self::Value, mode: AllocationMode) -> Self {
-        let big_log_size = LogSizeVar::new_variables(cs, &value.big_log_sizes[0], mode);
+        let big_log_sizes = InstancesVar::new_variables(cs, &value.big_log_sizes, mode);
         let small_log_size = LogSizeVar::new_variables(cs, &value.small_log_size, mode);
         Self {
-            big_log_size,
+            big_log_sizes,
             small_log_size,
         }
     }
@@ -311,13 +689,27 @@ impl AllocVar for MemoryIdToBigClaimVar {
 
 impl MemoryIdToBigClaimVar {
     pub fn mix_into(&self, channel: &mut ChannelVar) {
-        self.big_log_size.mix_into(channel);
+        self.big_log_sizes.mix_log_sizes(channel);
         self.small_log_size.mix_into(channel);
     }
 
+    /// The offset of the ids of each big memory table, which follow the ids of the previous
+    /// tables.
+    pub fn big_offsets(&self) -> Vec<M31Var> {
+        let mut offset = M31Var::zero(&self.cs());
+        let mut offsets = vec![];
+        for n_rows in self.big_log_sizes.n_rows() {
+            offsets.push(offset.clone());
+            offset = &offset + &n_rows;
+        }
+        offsets
+    }
+
     pub fn max_log_size(&self) -> M31Var {
-        let mut max = self.big_log_size.m31.clone();
-        max = max.max(&self.small_log_size.m31, 5);
+        let mut max = self.small_log_size.m31.clone();
+        for big_log_size in self.big_log_sizes.iter() {
+            max = max.max(&big_log_size.m31, 5);
+        }
 
         max
     }
@@ -331,6 +723,7 @@ pub struct CairoClaimVar {
     pub verify_instruction: LogSizeVar,
     pub blake_context: BlakeContextClaimVar,
     pub builtins: BuiltinsClaimVar,
+    pub poseidon_context: PoseidonContextClaimVar,
     pub memory_address_to_id: LogSizeVar,
     pub memory_id_to_value: MemoryIdToBigClaimVar,
 }
@@ -351,6 +744,8 @@ impl AllocVar for CairoClaimVar {
             LogSizeVar::new_variables(cs, &value.verify_instruction.log_size, mode);
         let blake_context = BlakeContextClaimVar::new_variables(cs, &value.blake_context, mode);
         let builtins = BuiltinsClaimVar::new_variables(cs, &value.builtins, mode);
+        let poseidon_context =
+            PoseidonContextClaimVar::new_variables(cs, &value.poseidon_context, mode);
         let memory_address_to_id =
             LogSizeVar::new_variables(cs, &value.memory_address_to_id.log_size, mode);
         let memory_id_to_value =
@@ -362,6 +757,7 @@ impl AllocVar for CairoClaimVar {
             verify_instruction,
             blake_context,
             builtins,
+            poseidon_context,
             memory_address_to_id,
             memory_id_to_value,
         }
@@ -375,26 +771,26 @@ impl CairoClaimVar {
         self.verify_instruction.mix_into(channel);
         self.blake_context.mix_into(channel);
         self.builtins.mix_into(channel);
+        self.poseidon_context.mix_into(channel);
         self.memory_address_to_id.mix_into(channel);
         self.memory_id_to_value.mix_into(channel);
     }
 
     pub fn accumulate_relation_uses(&self, relation_uses: &mut IndexMap<&str, M31Var>) {
         self.opcode_claim.accumulate_relation_uses(relation_uses);
-        accumulate_component!(
-            range_check_builtin_bits_128,
-            self.builtins.range_check_128_builtin_log_size,
-            relation_uses
-        );
+        self.builtins.accumulate_relation_uses(relation_uses);
         accumulate_component!(verify_instruction, self.verify_instruction, relation_uses);
         self.blake_context.accumulate_relation_uses(relation_uses);
-
-        let m31_var = self.memory_id_to_value.big_log_size.to_m31();
-        let zero = M31Var::zero(&m31_var.cs());
-        for entry in cairo_air::components::memory_id_to_big::RELATION_USES_PER_ROW_BIG {
-            let cur = relation_uses.get(entry.relation_id).unwrap_or(&zero);
-            let new = m31_var.exp2().mul_constant(M31::from(entry.uses as u32));
-            relation_uses.insert(entry.relation_id, cur.add_assert_no_overflow(&new));
+        self.poseidon_context
+            .accumulate_relation_uses(relation_uses);
+
+        let zero = M31Var::zero(&self.cs);
+        for n_rows in self.memory_id_to_value.big_log_sizes.n_rows() {
+            for entry in cairo_air::components::memory_id_to_big::RELATION_USES_PER_ROW_BIG {
+                let cur = relation_uses.get(entry.relation_id).unwrap_or(&zero);
+                let new = n_rows.mul_constant(M31::from(entry.uses as u32));
+                relation_uses.insert(entry.relation_id, cur.add_assert_no_overflow(&new));
+            }
         }
         let m31_var = self.memory_id_to_value.small_log_size.to_m31();
         for entry in cairo_air::components::memory_id_to_big::RELATION_USES_PER_ROW_SMALL {
@@ -404,11 +800,32 @@ impl CairoClaimVar {
         }
     }
 
+    /// The activity bits of the instance slots of the opcodes, in the order of the opcodes,
+    /// followed by those of the big memory tables, of the optional builtins and of the Poseidon
+    /// context, which is the order of the slots of `CairoPadding`.
+    pub fn instances_is_active(&self) -> Vec<BitVar> {
+        self.opcode_claim
+            .instances()
+            .into_iter()
+            .chain([&self.memory_id_to_value.big_log_sizes])
+            .flat_map(|instances| instances.is_active.iter().cloned())
+            .chain(
+                self.builtins
+                    .optional_builtins()
+                    .map(|builtin| builtin.is_present.clone()),
+            )
+            .chain([self.poseidon_context.is_present.clone()])
+            .collect()
+    }
+
     pub fn max_trace_and_interaction_log_size(&self) -> M31Var {
-        let mut max = self.opcode_claim.max_log_size();
-        max = max.max(&self.verify_instruction.m31, 5);
+        let mut max = self.verify_instruction.m31.clone();
+        if let Some(opcodes_max) = self.opcode_claim.max_log_size() {
+            max = max.max(&opcodes_max, 5);
+        }
         max = max.max(&self.blake_context.max_log_size(), 5);
         max = max.max(&self.builtins.max_log_size(), 5);
+        max = max.max(&self.poseidon_context.max_log_size(), 5);
         max = max.max(&self.memory_address_to_id.m31, 5);
         max = max.max(&self.memory_id_to_value.max_log_size(), 5);
         max
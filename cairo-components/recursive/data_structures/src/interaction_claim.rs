@@ -1,17 +1,28 @@
-use cairo_air::{air::CairoInteractionClaim, opcodes_air::OpcodeInteractionClaim};
+use cairo_air::{
+    air::CairoInteractionClaim, builtins_air::BuiltinsInteractionClaim,
+    opcodes_air::OpcodeInteractionClaim, poseidon::air::PoseidonContextInteractionClaim,
+};
+use cairo_plonk_dsl_hints::MAX_COMPONENT_INSTANCES;
 use circle_plonk_dsl_constraint_system::{
     var::{AllocVar, AllocationMode, Var},
     ConstraintSystemRef,
 };
+use circle_plonk_dsl_primitives::BitVar;
 use circle_plonk_dsl_primitives::ChannelVar;
 use circle_plonk_dsl_primitives::QM31Var;
+use itertools::Itertools;
+use num_traits::Zero;
+use stwo::core::fields::qm31::QM31;
+
+use crate::claim::{BuiltinsClaimVar, CairoClaimVar, OpcodeClaimVar};
 
 #[derive(Debug, Clone)]
 pub struct CairoInteractionClaimVar {
     pub opcodes: OpcodeInteractionClaimVar,
     pub verify_instruction: QM31Var,
     pub blake_context: BlakeContextInteractionClaimVar,
-    pub builtins: QM31Var,
+    pub builtins: BuiltinsInteractionClaimVar,
+    pub poseidon_context: PoseidonContextInteractionClaimVar,
     pub memory_address_to_id: QM31Var,
     pub memory_id_to_value: MemoryIdToValueClaimVar,
     pub range_checks: RangeChecksInteractionClaimVar,
@@ -44,14 +55,10 @@ impl AllocVar for CairoInteractionClaimVar {
                 value.blake_context.claim.as_ref().unwrap(),
                 mode,
             ),
-            builtins: QM31Var::new_variables(
+            builtins: BuiltinsInteractionClaimVar::new_variables(cs, &value.builtins, mode),
+            poseidon_context: PoseidonContextInteractionClaimVar::new_variables(
                 cs,
-                &value
-                    .builtins
-                    .range_check_128_builtin
-                    .as_ref()
-                    .unwrap()
-                    .claimed_sum,
+                &value.poseidon_context,
                 mode,
             ),
             memory_address_to_id: QM31Var::new_variables(
@@ -99,13 +106,16 @@ impl AllocVar for CairoInteractionClaimVar {
 }
 
 impl CairoInteractionClaimVar {
-    pub fn mix_into(&self, channel: &mut ChannelVar) {
-        self.opcodes.mix_into(channel);
+    /// Mix the claimed sums, skipping the inactive instance slots of `claim`.
+    pub fn mix_into(&self, channel: &mut ChannelVar, claim: &CairoClaimVar) {
+        self.opcodes.mix_into(channel, &claim.opcode_claim);
         channel.mix_one_felt(&self.verify_instruction);
         self.blake_context.mix_into(channel);
-        channel.mix_one_felt(&self.builtins);
+        self.builtins.mix_into(channel, &claim.builtins);
+        self.poseidon_context
+            .mix_into(channel, &claim.poseidon_context.is_present);
         channel.mix_one_felt(&self.memory_address_to_id);
-        self.memory_id_to_value.mix_into(channel);
+        self.memory_id_to_value.mix_into(channel, claim);
         self.range_checks.mix_into(channel);
         channel.mix_one_felt(&self.verify_bitwise_xor_4);
         channel.mix_one_felt(&self.verify_bitwise_xor_7);
@@ -115,103 +125,148 @@ impl CairoInteractionClaimVar {
     }
 }
 
+impl CairoInteractionClaimVar {
+    /// Check that the inactive instance slots, the absent builtins and the absent Poseidon
+    /// context of `claim` have a zero claimed sum, so that the padding does not change the logup
+    /// sum.
+    pub fn check_instances(&self, claim: &CairoClaimVar) {
+        let mut claimed_sums = self
+            .opcodes
+            .claimed_sums()
+            .chain(self.memory_id_to_value.big_claimed_sums.iter())
+            .chain(self.builtins.optional_claimed_sums())
+            .map(|claimed_sum| vec![claimed_sum])
+            .collect_vec();
+        claimed_sums.push(self.poseidon_context.claimed_sums().to_vec());
+
+        for (claimed_sums, is_active) in
+            claimed_sums.into_iter().zip_eq(claim.instances_is_active())
+        {
+            for claimed_sum in claimed_sums {
+                (claimed_sum * &is_active.neg().0).equalverify(&QM31Var::zero(&claimed_sum.cs()));
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct OpcodeInteractionClaimVar {
-    pub add: QM31Var,
-    pub add_small: QM31Var,
-    pub add_ap: QM31Var,
-    pub assert_eq: QM31Var,
-    pub assert_eq_imm: QM31Var,
-    pub assert_eq_double_deref: QM31Var,
-    pub blake: QM31Var,
-    pub call: QM31Var,
-    pub call_rel_imm: QM31Var,
-    pub jnz: QM31Var,
-    pub jnz_taken: QM31Var,
-    pub jump_rel: QM31Var,
-    pub jump_rel_imm: QM31Var,
-    pub mul: QM31Var,
-    pub mul_small: QM31Var,
-    pub qm31: QM31Var,
-    pub ret: QM31Var,
+    pub cs: ConstraintSystemRef,
+    pub add: Vec<QM31Var>,
+    pub add_small: Vec<QM31Var>,
+    pub add_ap: Vec<QM31Var>,
+    pub assert_eq: Vec<QM31Var>,
+    pub assert_eq_imm: Vec<QM31Var>,
+    pub assert_eq_double_deref: Vec<QM31Var>,
+    pub blake: Vec<QM31Var>,
+    pub call: Vec<QM31Var>,
+    pub call_rel_imm: Vec<QM31Var>,
+    pub generic: Vec<QM31Var>,
+    pub jnz: Vec<QM31Var>,
+    pub jnz_taken: Vec<QM31Var>,
+    pub jump: Vec<QM31Var>,
+    pub jump_double_deref: Vec<QM31Var>,
+    pub jump_rel: Vec<QM31Var>,
+    pub jump_rel_imm: Vec<QM31Var>,
+    pub mul: Vec<QM31Var>,
+    pub mul_small: Vec<QM31Var>,
+    pub qm31: Vec<QM31Var>,
+    pub ret: Vec<QM31Var>,
 }
 
 impl Var for OpcodeInteractionClaimVar {
     type Value = OpcodeInteractionClaim;
 
     fn cs(&self) -> ConstraintSystemRef {
-        self.add.cs()
+        self.cs.clone()
     }
 }
 
 impl AllocVar for OpcodeInteractionClaimVar {
     fn new_variables(cs: &ConstraintSystemRef, value: &Self::Value, mode: AllocationMode) -> Self {
+        // padded to the instance slots of `OpcodeClaimVar`
+        macro_rules! new_instances {
+            ($name:ident) => {{
+                assert!(value.$name.len() <= MAX_COMPONENT_INSTANCES);
+                (0..MAX_COMPONENT_INSTANCES)
+                    .map(|i| {
+                        let claimed_sum = value
+                            .$name
+                            .get(i)
+                            .map_or(QM31::zero(), |claim| claim.claimed_sum);
+                        QM31Var::new_variables(cs, &claimed_sum, mode)
+                    })
+                    .collect()
+            }};
+        }
+
         Self {
-            add: QM31Var::new_variables(cs, &value.add[0].claimed_sum, mode),
-            add_small: QM31Var::new_variables(cs, &value.add_small[0].claimed_sum, mode),
-            add_ap: QM31Var::new_variables(cs, &value.add_ap[0].claimed_sum, mode),
-            assert_eq: QM31Var::new_variables(cs, &value.assert_eq[0].claimed_sum, mode),
-            assert_eq_imm: QM31Var::new_variables(cs, &value.assert_eq_imm[0].claimed_sum, mode),
-            assert_eq_double_deref: QM31Var::new_variables(
-                cs,
-                &value.assert_eq_double_deref[0].claimed_sum,
-                mode,
-            ),
-            blake: QM31Var::new_variables(cs, &value.blake[0].claimed_sum, mode),
-            call: QM31Var::new_variables(cs, &value.call[0].claimed_sum, mode),
-            call_rel_imm: QM31Var::new_variables(cs, &value.call_rel_imm[0].claimed_sum, mode),
-            jnz: QM31Var::new_variables(cs, &value.jnz[0].claimed_sum, mode),
-            jnz_taken: QM31Var::new_variables(cs, &value.jnz_taken[0].claimed_sum, mode),
-            jump_rel: QM31Var::new_variables(cs, &value.jump_rel[0].claimed_sum, mode),
-            jump_rel_imm: QM31Var::new_variables(cs, &value.jump_rel_imm[0].claimed_sum, mode),
-            mul: QM31Var::new_variables(cs, &value.mul[0].claimed_sum, mode),
-            mul_small: QM31Var::new_variables(cs, &value.mul_small[0].claimed_sum, mode),
-            qm31: QM31Var::new_variables(cs, &value.qm31[0].claimed_sum, mode),
-            ret: QM31Var::new_variables(cs, &value.ret[0].claimed_sum, mode),
+            cs: cs.clone(),
+            add: new_instances!(add),
+            add_small: new_instances!(add_small),
+            add_ap: new_instances!(add_ap),
+            assert_eq: new_instances!(assert_eq),
+            assert_eq_imm: new_instances!(assert_eq_imm),
+            assert_eq_double_deref: new_instances!(assert_eq_double_deref),
+            blake: new_instances!(blake),
+            call: new_instances!(call),
+            call_rel_imm: new_instances!(call_rel_imm),
+            generic: new_instances!(generic),
+            jnz: new_instances!(jnz),
+            jnz_taken: new_instances!(jnz_taken),
+            jump: new_instances!(jump),
+            jump_double_deref: new_instances!(jump_double_deref),
+            jump_rel: new_instances!(jump_rel),
+            jump_rel_imm: new_instances!(jump_rel_imm),
+            mul: new_instances!(mul),
+            mul_small: new_instances!(mul_small),
+            qm31: new_instances!(qm31),
+            ret: new_instances!(ret),
         }
     }
 }
 
 impl OpcodeInteractionClaimVar {
-    pub fn mix_into(&self, channel: &mut ChannelVar) {
-        channel.mix_one_felt(&self.add);
-        channel.mix_one_felt(&self.add_small);
-        channel.mix_one_felt(&self.add_ap);
-        channel.mix_one_felt(&self.assert_eq);
-        channel.mix_one_felt(&self.assert_eq_imm);
-        channel.mix_one_felt(&self.assert_eq_double_deref);
-        channel.mix_one_felt(&self.blake);
-        channel.mix_one_felt(&self.call);
-        channel.mix_one_felt(&self.call_rel_imm);
-        channel.mix_one_felt(&self.jnz);
-        channel.mix_one_felt(&self.jnz_taken);
-        channel.mix_one_felt(&self.jump_rel);
-        channel.mix_one_felt(&self.jump_rel_imm);
-        channel.mix_one_felt(&self.mul);
-        channel.mix_one_felt(&self.mul_small);
-        channel.mix_one_felt(&self.qm31);
-        channel.mix_one_felt(&self.ret);
+    /// Return the claimed sums of all the opcode instances, in the order of the opcodes.
+    pub fn claimed_sums(&self) -> impl Iterator<Item = &QM31Var> {
+        self.add
+            .iter()
+            .chain(self.add_small.iter())
+            .chain(self.add_ap.iter())
+            .chain(self.assert_eq.iter())
+            .chain(self.assert_eq_imm.iter())
+            .chain(self.assert_eq_double_deref.iter())
+            .chain(self.blake.iter())
+            .chain(self.call.iter())
+            .chain(self.call_rel_imm.iter())
+            .chain(self.generic.iter())
+            .chain(self.jnz.iter())
+            .chain(self.jnz_taken.iter())
+            .chain(self.jump.iter())
+            .chain(self.jump_double_deref.iter())
+            .chain(self.jump_rel.iter())
+            .chain(self.jump_rel_imm.iter())
+            .chain(self.mul.iter())
+            .chain(self.mul_small.iter())
+            .chain(self.qm31.iter())
+            .chain(self.ret.iter())
     }
 
-    pub fn sum(&self) -> QM31Var {
-        let mut sum = self.add.clone();
-        sum = &sum + &self.add_small;
-        sum = &sum + &self.add_ap;
-        sum = &sum + &self.assert_eq;
-        sum = &sum + &self.assert_eq_imm;
-        sum = &sum + &self.assert_eq_double_deref;
-        sum = &sum + &self.blake;
-        sum = &sum + &self.call;
-        sum = &sum + &self.call_rel_imm;
-        sum = &sum + &self.jnz;
-        sum = &sum + &self.jnz_taken;
-        sum = &sum + &self.jump_rel;
-        sum = &sum + &self.jump_rel_imm;
-        sum = &sum + &self.mul;
-        sum = &sum + &self.mul_small;
-        sum = &sum + &self.qm31;
-        sum = &sum + &self.ret;
+    pub fn mix_into(&self, channel: &mut ChannelVar, claim: &OpcodeClaimVar) {
+        let is_active = claim
+            .instances()
+            .into_iter()
+            .flat_map(|instances| instances.is_active.iter());
+        for (claimed_sum, is_active) in self.claimed_sums().zip_eq(is_active) {
+            channel.conditional_mix_one_felt(claimed_sum, is_active);
+        }
+    }
 
+    pub fn sum(&self) -> QM31Var {
+        let mut sum = QM31Var::zero(&self.cs);
+        for claimed_sum in self.claimed_sums() {
+            sum = &sum + claimed_sum;
+        }
         sum
     }
 }
@@ -268,9 +323,187 @@ impl BlakeContextInteractionClaimVar {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct BuiltinsInteractionClaimVar {
+    /// The claimed sums of the optional builtins are zero for the absent ones.
+    pub add_mod_builtin: QM31Var,
+    pub bitwise_builtin: QM31Var,
+    pub mul_mod_builtin: QM31Var,
+    pub poseidon_builtin: QM31Var,
+    pub range_check_96_builtin: QM31Var,
+    pub range_check_128_builtin: QM31Var,
+}
+
+impl Var for BuiltinsInteractionClaimVar {
+    type Value = BuiltinsInteractionClaim;
+
+    fn cs(&self) -> ConstraintSystemRef {
+        self.range_check_128_builtin.cs()
+    }
+}
+
+impl AllocVar for BuiltinsInteractionClaimVar {
+    fn new_variables(cs: &ConstraintSystemRef, value: &Self::Value, mode: AllocationMode) -> Self {
+        let new_optional_claimed_sum = |claimed_sum: Option<QM31>| {
+            QM31Var::new_variables(cs, &claimed_sum.unwrap_or_else(QM31::zero), mode)
+        };
+        Self {
+            add_mod_builtin: new_optional_claimed_sum(
+                value
+                    .add_mod_builtin
+                    .as_ref()
+                    .map(|claim| claim.claimed_sum),
+            ),
+            bitwise_builtin: new_optional_claimed_sum(
+                value
+                    .bitwise_builtin
+                    .as_ref()
+                    .map(|claim| claim.claimed_sum),
+            ),
+            mul_mod_builtin: new_optional_claimed_sum(
+                value
+                    .mul_mod_builtin
+                    .as_ref()
+                    .map(|claim| claim.claimed_sum),
+            ),
+            poseidon_builtin: new_optional_claimed_sum(
+                value
+                    .poseidon_builtin
+                    .as_ref()
+                    .map(|claim| claim.claimed_sum),
+            ),
+            range_check_96_builtin: new_optional_claimed_sum(
+                value
+                    .range_check_96_builtin
+                    .as_ref()
+                    .map(|claim| claim.claimed_sum),
+            ),
+            range_check_128_builtin: QM31Var::new_variables(
+                cs,
+                &value.range_check_128_builtin.as_ref().unwrap().claimed_sum,
+                mode,
+            ),
+        }
+    }
+}
+
+impl BuiltinsInteractionClaimVar {
+    /// Return the claimed sums of the optional builtins, in the order of the builtins.
+    pub fn optional_claimed_sums(&self) -> [&QM31Var; 5] {
+        [
+            &self.add_mod_builtin,
+            &self.bitwise_builtin,
+            &self.mul_mod_builtin,
+            &self.poseidon_builtin,
+            &self.range_check_96_builtin,
+        ]
+    }
+
+    /// Return the claimed sums of the builtins, in the order of the builtins.
+    pub fn claimed_sums(&self) -> impl Iterator<Item = &QM31Var> {
+        self.optional_claimed_sums()
+            .into_iter()
+            .chain(std::iter::once(&self.range_check_128_builtin))
+    }
+
+    /// Mix the claimed sums, skipping the absent builtins of `claim`.
+    pub fn mix_into(&self, channel: &mut ChannelVar, claim: &BuiltinsClaimVar) {
+        for (claimed_sum, builtin) in self
+            .optional_claimed_sums()
+            .into_iter()
+            .zip_eq(claim.optional_builtins())
+        {
+            channel.conditional_mix_one_felt(claimed_sum, &builtin.is_present);
+        }
+        channel.mix_one_felt(&self.range_check_128_builtin);
+    }
+
+    pub fn sum(&self) -> QM31Var {
+        let mut sum = QM31Var::zero(&self.cs());
+        for claimed_sum in self.claimed_sums() {
+            sum = &sum + claimed_sum;
+        }
+        sum
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PoseidonContextInteractionClaimVar {
+    pub poseidon_aggregator: QM31Var,
+    pub poseidon_3_partial_rounds_chain: QM31Var,
+    pub poseidon_full_round_chain: QM31Var,
+    pub cube_252: QM31Var,
+    pub poseidon_round_keys: QM31Var,
+    pub range_check_252_width_27: QM31Var,
+}
+
+impl Var for PoseidonContextInteractionClaimVar {
+    type Value = PoseidonContextInteractionClaim;
+
+    fn cs(&self) -> ConstraintSystemRef {
+        self.poseidon_aggregator.cs()
+    }
+}
+
+impl AllocVar for PoseidonContextInteractionClaimVar {
+    /// An absent Poseidon context has zero claimed sums.
+    fn new_variables(cs: &ConstraintSystemRef, value: &Self::Value, mode: AllocationMode) -> Self {
+        let value = value.claim.as_ref();
+        let new_claimed_sum = |claimed_sum: Option<QM31>| {
+            QM31Var::new_variables(cs, &claimed_sum.unwrap_or_else(QM31::zero), mode)
+        };
+        Self {
+            poseidon_aggregator: new_claimed_sum(
+                value.map(|claim| claim.poseidon_aggregator.claimed_sum),
+            ),
+            poseidon_3_partial_rounds_chain: new_claimed_sum(
+                value.map(|claim| claim.poseidon_3_partial_rounds_chain.claimed_sum),
+            ),
+            poseidon_full_round_chain: new_claimed_sum(
+                value.map(|claim| claim.poseidon_full_round_chain.claimed_sum),
+            ),
+            cube_252: new_claimed_sum(value.map(|claim| claim.cube_252.claimed_sum)),
+            poseidon_round_keys: new_claimed_sum(
+                value.map(|claim| claim.poseidon_round_keys.claimed_sum),
+            ),
+            range_check_252_width_27: new_claimed_sum(
+                value.map(|claim| claim.range_check_252_width_27.claimed_sum),
+            ),
+        }
+    }
+}
+
+impl PoseidonContextInteractionClaimVar {
+    pub fn claimed_sums(&self) -> [&QM31Var; 6] {
+        [
+            &self.poseidon_aggregator,
+            &self.poseidon_3_partial_rounds_chain,
+            &self.poseidon_full_round_chain,
+            &self.cube_252,
+            &self.poseidon_round_keys,
+            &self.range_check_252_width_27,
+        ]
+    }
+
+    /// Mix the claimed sums if the Poseidon context is present.
+    pub fn mix_into(&self, channel: &mut ChannelVar, is_present: &BitVar) {
+        for claimed_sum in self.claimed_sums() {
+            channel.conditional_mix_one_felt(claimed_sum, is_present);
+        }
+    }
+
+    pub fn sum(&self) -> QM31Var {
+        let mut sum = self.poseidon_aggregator.clone();
+        for claimed_sum in self.claimed_sums().into_iter().skip(1) {
+            sum = &sum + claimed_sum;
+        }
+        sum
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct MemoryIdToValueClaimVar {
-    pub big_claimed_sum: QM31Var,
+    pub big_claimed_sums: Vec<QM31Var>,
     pub small_claimed_sum: QM31Var,
 }
 
@@ -278,28 +511,46 @@ impl Var for MemoryIdToValueClaimVar {
     type Value = cairo_air::components::memory_id_to_big::InteractionClaim;
 
     fn cs(&self) -> ConstraintSystemRef {
-        self.big_claimed_sum.cs()
+        self.small_claimed_sum.cs()
     }
 }
 
 impl AllocVar for MemoryIdToValueClaimVar {
     fn new_variables(cs: &ConstraintSystemRef, value: &Self::Value, mode: AllocationMode) -> Self {
         Self {
-            big_claimed_sum: QM31Var::new_variables(cs, &value.big_claimed_sums[0], mode),
+            // padded to the instance slots of `MemoryIdToBigClaimVar`
+            big_claimed_sums: (0..MAX_COMPONENT_INSTANCES)
+                .map(|i| {
+                    let claimed_sum = value
+                        .big_claimed_sums
+                        .get(i)
+                        .copied()
+                        .unwrap_or_else(QM31::zero);
+                    QM31Var::new_variables(cs, &claimed_sum, mode)
+                })
+                .collect(),
             small_claimed_sum: QM31Var::new_variables(cs, &value.small_claimed_sum, mode),
         }
     }
 }
 
 impl MemoryIdToValueClaimVar {
-    pub fn mix_into(&self, channel: &mut ChannelVar) {
-        channel.mix_one_felt(&self.big_claimed_sum);
+    pub fn mix_into(&self, channel: &mut ChannelVar, claim: &CairoClaimVar) {
+        for (big_claimed_sum, is_active) in self
+            .big_claimed_sums
+            .iter()
+            .zip_eq(claim.memory_id_to_value.big_log_sizes.is_active.iter())
+        {
+            channel.conditional_mix_one_felt(big_claimed_sum, is_active);
+        }
         channel.mix_one_felt(&self.small_claimed_sum);
     }
 
     pub fn sum(&self) -> QM31Var {
-        let mut sum = self.big_claimed_sum.clone();
-        sum = &sum + &self.small_claimed_sum;
+        let mut sum = self.small_claimed_sum.clone();
+        for big_claimed_sum in self.big_claimed_sums.iter() {
+            sum = &sum + big_claimed_sum;
+        }
         sum
     }
 }
@@ -1,10 +1,13 @@
 use cairo_air::CairoProof;
+use cairo_plonk_dsl_hints::{CairoPadding, InstanceSlot};
 use circle_plonk_dsl_constraint_system::{
     var::{AllocVar, AllocationMode, Var},
     ConstraintSystemRef,
 };
-use circle_plonk_dsl_primitives::BitIntVar;
+use circle_plonk_dsl_primitives::{channel::PreProcessedTracePresent, BitIntVar};
+use itertools::Itertools;
 use stwo::core::vcs::poseidon31_merkle::Poseidon31MerkleHasher;
+use stwo_constraint_framework::PREPROCESSED_TRACE_IDX;
 
 use crate::{interaction_claim::CairoInteractionClaimVar, stark_proof::StarkProofVar};
 
@@ -28,6 +31,9 @@ pub struct CairoProofVar {
     pub stark_proof: StarkProofVar,
     pub interaction_pow: BitIntVar<64>,
     pub interaction_claim: CairoInteractionClaimVar,
+    /// The columns of the instance slots of the opcodes and the big memory tables, in the order
+    /// of `CairoClaimVar::instances_is_active`.
+    pub slots: Vec<InstanceSlot>,
 }
 
 impl Var for CairoProofVar {
@@ -40,8 +46,13 @@ impl Var for CairoProofVar {
 
 impl AllocVar for CairoProofVar {
     fn new_variables(cs: &ConstraintSystemRef, value: &Self::Value, mode: AllocationMode) -> Self {
-        let stark_proof = StarkProofVar::new_variables(cs, &value.stark_proof, mode);
-        let claim = CairoClaimVar::new_constant(cs, &value.claim);
+        // the sampled values of the trace and the interaction trace follow the padded layout
+        let padding = CairoPadding::new(&value.claim, &value.interaction_claim);
+        let mut stark_proof = value.stark_proof.clone();
+        stark_proof.sampled_values = padding.pad_sampled_values(&value.stark_proof.sampled_values);
+
+        let stark_proof = StarkProofVar::new_variables(cs, &stark_proof, mode);
+        let claim = CairoClaimVar::new_variables(cs, &value.claim, mode);
         let interaction_pow = BitIntVar::<64>::new_variables(cs, &value.interaction_pow, mode);
         let interaction_claim =
             CairoInteractionClaimVar::new_variables(cs, &value.interaction_claim, mode);
@@ -52,6 +63,41 @@ impl AllocVar for CairoProofVar {
             stark_proof,
             interaction_pow,
             interaction_claim,
+            slots: padding.slots,
         }
     }
 }
+
+impl CairoProofVar {
+    /// Whether each of the flattened sampled values is present: the preprocessed columns follow
+    /// `is_preprocessed_trace_present`, and the columns of an instance slot are present if the
+    /// slot is active.
+    pub fn sampled_values_presence(&self) -> Vec<PreProcessedTracePresent> {
+        let sampled_values = &self.stark_proof.sampled_values;
+
+        let mut presence = sampled_values
+            .iter()
+            .map(|tree| vec![PreProcessedTracePresent::Fixed(true); tree.len()])
+            .collect_vec();
+        presence[PREPROCESSED_TRACE_IDX] = self.stark_proof.is_preprocessed_trace_present.clone();
+        for (slot, is_active) in self.slots.iter().zip_eq(self.claim.instances_is_active()) {
+            for location in slot.locations.iter() {
+                if location.tree_index != PREPROCESSED_TRACE_IDX {
+                    presence[location.tree_index][location.col_start..location.col_end]
+                        .fill(PreProcessedTracePresent::Dynamic(is_active.clone()));
+                }
+            }
+        }
+
+        sampled_values
+            .iter()
+            .zip_eq(presence)
+            .flat_map(|(tree, presence)| {
+                tree.iter()
+                    .zip_eq(presence)
+                    .flat_map(|(column, presence)| vec![presence; column.len()])
+                    .collect_vec()
+            })
+            .collect()
+    }
+}
@@ -236,6 +236,79 @@ impl CairoInteractionElementsVar {
             verify_bitwise_xor_12: VerifyBitwiseXor12Var::draw(channel),
         }
     }
+
+    /// Find the lookup elements of a relation by the name of its native counterpart in
+    /// `cairo_air::relations`, e.g. `RangeCheck_9_9_B` for [`RangeCheck99BVar`].
+    pub fn get_by_native_name(&self, name: &str) -> &LookupElementsVar {
+        let name = format!("{}Var", name.replace('_', ""));
+
+        macro_rules! find {
+            ($($relation:expr),*) => {
+                $(
+                    if $relation.get_name() == name {
+                        return &$relation.0;
+                    }
+                )*
+            };
+        }
+
+        find!(
+            self.opcodes,
+            self.verify_instruction,
+            self.blake_round,
+            self.blake_g,
+            self.blake_sigma,
+            self.triple_xor_32,
+            self.poseidon_aggregator,
+            self.poseidon_3_partial_rounds_chain,
+            self.poseidon_full_round_chain,
+            self.cube_252,
+            self.poseidon_round_keys,
+            self.range_check_252_width_27,
+            self.pedersen_aggregator,
+            self.partial_ec_mul,
+            self.pedersen_points_table,
+            self.memory_address_to_id,
+            self.memory_id_to_value,
+            self.range_checks.rc_6,
+            self.range_checks.rc_8,
+            self.range_checks.rc_11,
+            self.range_checks.rc_12,
+            self.range_checks.rc_18,
+            self.range_checks.rc_18_b,
+            self.range_checks.rc_20,
+            self.range_checks.rc_20_b,
+            self.range_checks.rc_20_c,
+            self.range_checks.rc_20_d,
+            self.range_checks.rc_20_e,
+            self.range_checks.rc_20_f,
+            self.range_checks.rc_20_g,
+            self.range_checks.rc_20_h,
+            self.range_checks.rc_4_3,
+            self.range_checks.rc_4_4,
+            self.range_checks.rc_5_4,
+            self.range_checks.rc_9_9,
+            self.range_checks.rc_9_9_b,
+            self.range_checks.rc_9_9_c,
+            self.range_checks.rc_9_9_d,
+            self.range_checks.rc_9_9_e,
+            self.range_checks.rc_9_9_f,
+            self.range_checks.rc_9_9_g,
+            self.range_checks.rc_9_9_h,
+            self.range_checks.rc_7_2_5,
+            self.range_checks.rc_3_6_6_3,
+            self.range_checks.rc_4_4_4_4,
+            self.range_checks.rc_3_3_3_3_3,
+            self.verify_bitwise_xor_4,
+            self.verify_bitwise_xor_7,
+            self.verify_bitwise_xor_8,
+            self.verify_bitwise_xor_8_b,
+            self.verify_bitwise_xor_9,
+            self.verify_bitwise_xor_12
+        );
+
+        panic!("Unknown relation {}", name);
+    }
 }
 
 pub struct RangeChecksInteractionElementsVar {
@@ -1,3 +1,12 @@
+use cairo_plonk_dsl_hints::{
+    ADD_MOD_BUILTIN_N_INTERACTION_COLUMNS, BITWISE_BUILTIN_N_INTERACTION_COLUMNS,
+    CUBE_252_N_INTERACTION_COLUMNS, GENERIC_OPCODE_N_INTERACTION_COLUMNS,
+    JUMP_OPCODE_ABS_N_INTERACTION_COLUMNS, JUMP_OPCODE_DOUBLE_DEREF_N_INTERACTION_COLUMNS,
+    MUL_MOD_BUILTIN_N_INTERACTION_COLUMNS, POSEIDON_3_PARTIAL_ROUNDS_CHAIN_N_INTERACTION_COLUMNS,
+    POSEIDON_AGGREGATOR_N_INTERACTION_COLUMNS, POSEIDON_BUILTIN_N_INTERACTION_COLUMNS,
+    POSEIDON_FULL_ROUND_CHAIN_N_INTERACTION_COLUMNS,
+    RANGE_CHECK_252_WIDTH_27_N_INTERACTION_COLUMNS, RANGE_CHECK_96_BUILTIN_N_INTERACTION_COLUMNS,
+};
 use circle_plonk_dsl_primitives::{BitIntVar, LogSizeVar};
 use stwo::core::fields::qm31::SECURE_EXTENSION_DEGREE;
 
@@ -22,6 +31,13 @@ impl MaskTableVar {
             ));
         };
 
+        let helper_instances =
+            |res: &mut Vec<(MaskVar, usize)>, log_sizes: &[LogSizeVar], l: usize| {
+                for log_size in log_sizes.iter() {
+                    helper(res, log_size, l);
+                }
+            };
+
         let helper2 = |res: &mut Vec<(MaskVar, usize)>, log_size: u32, l: usize| {
             res.push((MaskVar::NoMask, (l - 1) * SECURE_EXTENSION_DEGREE));
             res.push((MaskVar::ConstantLogSize(log_size), SECURE_EXTENSION_DEGREE));
@@ -30,23 +46,42 @@ impl MaskTableVar {
         let mut res = Vec::new();
 
         // opcodes
-        helper(&mut res, &claim.opcode_claim.add, 5);
-        helper(&mut res, &claim.opcode_claim.add_small, 5);
-        helper(&mut res, &claim.opcode_claim.add_ap, 4);
-        helper(&mut res, &claim.opcode_claim.assert_eq, 3);
-        helper(&mut res, &claim.opcode_claim.assert_eq_imm, 3);
-        helper(&mut res, &claim.opcode_claim.assert_eq_double_deref, 4);
-        helper(&mut res, &claim.opcode_claim.blake, 37);
-        helper(&mut res, &claim.opcode_claim.call, 5);
-        helper(&mut res, &claim.opcode_claim.call_rel_imm, 5);
-        helper(&mut res, &claim.opcode_claim.jnz, 3);
-        helper(&mut res, &claim.opcode_claim.jnz_taken, 4);
-        helper(&mut res, &claim.opcode_claim.jump_rel, 3);
-        helper(&mut res, &claim.opcode_claim.jump_rel_imm, 3);
-        helper(&mut res, &claim.opcode_claim.mul, 19);
-        helper(&mut res, &claim.opcode_claim.mul_small, 6);
-        helper(&mut res, &claim.opcode_claim.qm31, 6);
-        helper(&mut res, &claim.opcode_claim.ret, 4);
+        helper_instances(&mut res, &claim.opcode_claim.add.log_sizes, 5);
+        helper_instances(&mut res, &claim.opcode_claim.add_small.log_sizes, 5);
+        helper_instances(&mut res, &claim.opcode_claim.add_ap.log_sizes, 4);
+        helper_instances(&mut res, &claim.opcode_claim.assert_eq.log_sizes, 3);
+        helper_instances(&mut res, &claim.opcode_claim.assert_eq_imm.log_sizes, 3);
+        helper_instances(
+            &mut res,
+            &claim.opcode_claim.assert_eq_double_deref.log_sizes,
+            4,
+        );
+        helper_instances(&mut res, &claim.opcode_claim.blake.log_sizes, 37);
+        helper_instances(&mut res, &claim.opcode_claim.call.log_sizes, 5);
+        helper_instances(&mut res, &claim.opcode_claim.call_rel_imm.log_sizes, 5);
+        helper_instances(
+            &mut res,
+            &claim.opcode_claim.generic.log_sizes,
+            GENERIC_OPCODE_N_INTERACTION_COLUMNS,
+        );
+        helper_instances(&mut res, &claim.opcode_claim.jnz.log_sizes, 3);
+        helper_instances(&mut res, &claim.opcode_claim.jnz_taken.log_sizes, 4);
+        helper_instances(
+            &mut res,
+            &claim.opcode_claim.jump.log_sizes,
+            JUMP_OPCODE_ABS_N_INTERACTION_COLUMNS,
+        );
+        helper_instances(
+            &mut res,
+            &claim.opcode_claim.jump_double_deref.log_sizes,
+            JUMP_OPCODE_DOUBLE_DEREF_N_INTERACTION_COLUMNS,
+        );
+        helper_instances(&mut res, &claim.opcode_claim.jump_rel.log_sizes, 3);
+        helper_instances(&mut res, &claim.opcode_claim.jump_rel_imm.log_sizes, 3);
+        helper_instances(&mut res, &claim.opcode_claim.mul.log_sizes, 19);
+        helper_instances(&mut res, &claim.opcode_claim.mul_small.log_sizes, 6);
+        helper_instances(&mut res, &claim.opcode_claim.qm31.log_sizes, 6);
+        helper_instances(&mut res, &claim.opcode_claim.ret.log_sizes, 4);
 
         // verify_instruction
         helper(&mut res, &claim.verify_instruction, 3);
@@ -59,17 +94,75 @@ impl MaskTableVar {
         helper2(&mut res, 20, 8);
 
         // builtins
+        helper(
+            &mut res,
+            &claim.builtins.add_mod_builtin.log_size,
+            ADD_MOD_BUILTIN_N_INTERACTION_COLUMNS,
+        );
+        helper(
+            &mut res,
+            &claim.builtins.bitwise_builtin.log_size,
+            BITWISE_BUILTIN_N_INTERACTION_COLUMNS,
+        );
+        helper(
+            &mut res,
+            &claim.builtins.mul_mod_builtin.log_size,
+            MUL_MOD_BUILTIN_N_INTERACTION_COLUMNS,
+        );
+        helper(
+            &mut res,
+            &claim.builtins.poseidon_builtin.log_size,
+            POSEIDON_BUILTIN_N_INTERACTION_COLUMNS,
+        );
+        helper(
+            &mut res,
+            &claim.builtins.range_check_96_builtin.log_size,
+            RANGE_CHECK_96_BUILTIN_N_INTERACTION_COLUMNS,
+        );
         helper(
             &mut res,
             &claim.builtins.range_check_128_builtin_log_size,
             1,
         );
 
+        // poseidon context
+        let poseidon_context = &claim.poseidon_context;
+        helper(
+            &mut res,
+            &poseidon_context.poseidon_aggregator,
+            POSEIDON_AGGREGATOR_N_INTERACTION_COLUMNS,
+        );
+        helper(
+            &mut res,
+            &poseidon_context.poseidon_3_partial_rounds_chain,
+            POSEIDON_3_PARTIAL_ROUNDS_CHAIN_N_INTERACTION_COLUMNS,
+        );
+        helper(
+            &mut res,
+            &poseidon_context.poseidon_full_round_chain,
+            POSEIDON_FULL_ROUND_CHAIN_N_INTERACTION_COLUMNS,
+        );
+        helper(
+            &mut res,
+            &poseidon_context.cube_252,
+            CUBE_252_N_INTERACTION_COLUMNS,
+        );
+        helper(&mut res, &poseidon_context.poseidon_round_keys, 1);
+        helper(
+            &mut res,
+            &poseidon_context.range_check_252_width_27,
+            RANGE_CHECK_252_WIDTH_27_N_INTERACTION_COLUMNS,
+        );
+
         // memory_address_to_id
         helper(&mut res, &claim.memory_address_to_id, 8);
 
         // memory_id_to_value
-        helper(&mut res, &claim.memory_id_to_value.big_log_size, 8);
+        helper_instances(
+            &mut res,
+            &claim.memory_id_to_value.big_log_sizes.log_sizes,
+            8,
+        );
         helper(&mut res, &claim.memory_id_to_value.small_log_size, 3);
 
         // range_checks
@@ -119,10 +212,11 @@ mod tests {
 
     use cairo_air::utils::deserialize_proof_from_file;
     use cairo_air::utils::ProofFormat;
-    use cairo_plonk_dsl_hints::CairoFiatShamirHints;
+    use cairo_plonk_dsl_hints::{CairoFiatShamirHints, CairoVerifierParams};
     use circle_plonk_dsl_constraint_system::var::AllocVar;
     use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
     use itertools::Itertools;
+    use stwo::core::air::Components;
     use stwo::core::poly::circle::CanonicCoset;
 
     use crate::CairoProofVar;
@@ -144,7 +238,8 @@ mod tests {
 
         let proof = deserialize_proof_from_file(&data_path, ProofFormat::Binary).unwrap();
 
-        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof);
+        let params = CairoVerifierParams::from_proof(&proof);
+        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof, &params);
         let proof_var = CairoProofVar::new_witness(&cs, &proof);
 
         let mask_table_var = MaskTableVar::from_claim(&proof_var.claim);
@@ -155,7 +250,14 @@ mod tests {
             .flat_map(|(mask_var, size)| (0..*size).map(move |_| mask_var.clone()))
             .collect::<Vec<MaskVar>>();
 
-        for (l, (i, j)) in fiat_shamir_hints.sample_points[2]
+        // the mask table follows the layout padded to the instance slots
+        let sample_points = Components {
+            components: fiat_shamir_hints.padding.components.components(),
+            n_preprocessed_columns: fiat_shamir_hints.sample_points[0].len(),
+        }
+        .mask_points(fiat_shamir_hints.oods_point);
+
+        for (l, (i, j)) in sample_points[2]
             .iter()
             .zip_eq(expanded_mask_table_var.iter())
             .enumerate()
@@ -169,6 +271,10 @@ mod tests {
                     MaskVar::VariableLogSize(log_size) => log_size.bits.compose().value.0,
                     _ => panic!("Invalid mask var"),
                 };
+                if log_size == 0 {
+                    // an inactive slot, whose samples are skipped
+                    continue;
+                }
                 let trace_step = CanonicCoset::new(log_size).step();
                 let expected = fiat_shamir_hints.oods_point + trace_step.mul_signed(-1).into_ef();
                 assert_eq!(i[0], expected, "l: {:?}", l);
@@ -1,12 +1,14 @@
 use cairo_air::air::{
     MemorySection, MemorySmallValue, PublicData, PublicMemory, PublicSegmentRanges, SegmentRange,
 };
+use cairo_plonk_dsl_hints::MAX_OUTPUT_LEN;
 use circle_plonk_dsl_constraint_system::{
     var::{AllocVar, AllocationMode, Var},
     ConstraintSystemRef,
 };
-use circle_plonk_dsl_primitives::{BitIntVar, ChannelVar, M31Var, QM31Var};
+use circle_plonk_dsl_primitives::{BitIntVar, BitVar, ChannelVar, M31Var, QM31Var};
 use itertools::Itertools;
+use std::ops::Neg;
 use stwo::core::fields::m31::M31;
 use stwo_cairo_common::prover_types::{cpu::CasmState, felt::split_f252};
 
@@ -232,21 +234,35 @@ impl PublicDataVar {
         self.final_state.mix_into(channel);
     }
 
-    pub fn logup_sum(&self, elements: &CairoInteractionElementsVar) -> QM31Var {
-        let mut sum =
-            self.public_memory
-                .logup_sum(elements, &self.initial_state.ap, &self.final_state.ap);
+    pub fn logup_sum(
+        &self,
+        elements: &CairoInteractionElementsVar,
+        program: ProgramSectionVar<'_>,
+    ) -> QM31Var {
+        let mut sum = self.public_memory.logup_sum(
+            elements,
+            program,
+            &self.initial_state.ap,
+            &self.final_state.ap,
+        );
         sum = &sum - &self.initial_state.logup_sum(elements);
         sum = &sum + &self.final_state.logup_sum(elements);
         sum
     }
 }
 
+/// The program memory section in the lookup sum. It is a constant of the circuit taken from the
+/// verifier params, unless it is a witness, as in the hashed program mode.
+#[derive(Debug, Clone, Copy)]
+pub enum ProgramSectionVar<'a> {
+    Constant(&'a MemorySection),
+    Witness(&'a MemorySectionVar),
+}
+
 #[derive(Debug, Clone)]
 pub struct PublicMemoryVar {
-    pub program_constant: MemorySection,
     pub public_segments: PublicSegmentRangesVar,
-    pub output: MemorySectionVar,
+    pub output: OutputSectionVar,
     pub safe_call_ids: [BitIntVar<31>; 2],
 }
 
@@ -262,12 +278,16 @@ impl AllocVar for PublicMemoryVar {
     fn new_variables(cs: &ConstraintSystemRef, value: &Self::Value, mode: AllocationMode) -> Self {
         let public_segments =
             PublicSegmentRangesVar::new_variables(cs, &value.public_segments, mode);
-        let output = MemorySectionVar::new_variables(cs, &value.output, mode);
+        let output = OutputSectionVar::new_variables(cs, &value.output, mode);
+        // the active entries are exactly the output segment, so the prover cannot truncate it
+        let output_segment = &public_segments.output;
+        output.length().equalverify(
+            &(&output_segment.stop_ptr.value.to_m31() - &output_segment.start_ptr.value.to_m31()),
+        );
         let safe_call_ids = value
             .safe_call_ids
             .map(|id| BitIntVar::<31>::new_variables(cs, &(id as u64), mode));
         Self {
-            program_constant: value.program.clone(),
             public_segments,
             output,
             safe_call_ids,
@@ -287,35 +307,65 @@ impl PublicMemoryVar {
     pub fn logup_sum(
         &self,
         lookup_elements: &CairoInteractionElementsVar,
+        program: ProgramSectionVar<'_>,
         initial_ap: &BitIntVar<31>,
         final_ap: &BitIntVar<31>,
     ) -> QM31Var {
         let mut sum = QM31Var::zero(&self.cs());
-        for (addr_offset, (id, value)) in self.program_constant.iter().enumerate() {
-            let addr = M31::from((1 + addr_offset) as u32);
-            let id = M31::from_u32_unchecked(*id);
-            let value = split_f252(*value);
-
-            sum = &sum
-                + &lookup_elements
-                    .memory_address_to_id
-                    .0
-                    .combine_constant(&[addr, id])
-                    .inv();
-            sum = &sum
-                + &lookup_elements
-                    .memory_id_to_value
-                    .0
-                    .combine_constant(&[[id].as_slice(), value.as_slice()].concat())
-                    .inv();
+        match program {
+            ProgramSectionVar::Witness(program) => {
+                for (addr_offset, (id, value)) in
+                    program.ids.iter().zip_eq(program.values.iter()).enumerate()
+                {
+                    let addr =
+                        M31Var::new_constant(&self.cs(), &M31::from((1 + addr_offset) as u32));
+                    let id = id.to_m31();
+                    let value = split_f252_memory_var(value);
+
+                    sum = &sum
+                        + &lookup_elements
+                            .memory_address_to_id
+                            .0
+                            .combine(&[addr, id.clone()])
+                            .inv();
+                    sum = &sum
+                        + &lookup_elements
+                            .memory_id_to_value
+                            .0
+                            .combine(&[[id].as_slice(), value.as_slice()].concat())
+                            .inv();
+                }
+            }
+            ProgramSectionVar::Constant(program) => {
+                for (addr_offset, (id, value)) in program.iter().enumerate() {
+                    let addr = M31::from((1 + addr_offset) as u32);
+                    let id = M31::from_u32_unchecked(*id);
+                    let value = split_f252(*value);
+
+                    sum = &sum
+                        + &lookup_elements
+                            .memory_address_to_id
+                            .0
+                            .combine_constant(&[addr, id])
+                            .inv();
+                    sum = &sum
+                        + &lookup_elements
+                            .memory_id_to_value
+                            .0
+                            .combine_constant(&[[id].as_slice(), value.as_slice()].concat())
+                            .inv();
+                }
+            }
         }
 
+        // only the active entries of the output section are in the sum
         let final_ap_m31 = final_ap.to_m31();
-        for (addr_offset, (id, value)) in self
+        for (addr_offset, ((id, value), is_active)) in self
             .output
             .ids
             .iter()
             .zip_eq(self.output.values.iter())
+            .zip_eq(self.output.is_active.iter())
             .enumerate()
         {
             let addr =
@@ -324,17 +374,19 @@ impl PublicMemoryVar {
             let value = split_f252_memory_var(value);
 
             sum = &sum
-                + &lookup_elements
+                + &(&lookup_elements
                     .memory_address_to_id
                     .0
                     .combine(&[addr, id.clone()])
-                    .inv();
+                    .inv()
+                    * &is_active.0);
             sum = &sum
-                + &lookup_elements
+                + &(&lookup_elements
                     .memory_id_to_value
                     .0
                     .combine(&[[id].as_slice(), value.as_slice()].concat())
-                    .inv();
+                    .inv()
+                    * &is_active.0);
         }
 
         let initial_ap_m31: M31Var = initial_ap.to_m31();
@@ -527,3 +579,128 @@ impl MemorySectionVar {
             .for_each(|value| value.iter().for_each(|v| v.mix_into(channel)));
     }
 }
+
+/// The output memory section, padded to `MAX_OUTPUT_LEN` entries so that its length is not part of
+/// the shape of the circuit. The active entries, which form a prefix, are the actual section.
+#[derive(Debug, Clone)]
+pub struct OutputSectionVar {
+    pub ids: Vec<BitIntVar<31>>,
+    pub values: Vec<[BitIntVar<32>; 8]>,
+    pub is_active: Vec<BitVar>,
+}
+
+impl Var for OutputSectionVar {
+    type Value = MemorySection;
+
+    fn cs(&self) -> ConstraintSystemRef {
+        self.ids[0].cs()
+    }
+}
+
+impl AllocVar for OutputSectionVar {
+    fn new_variables(cs: &ConstraintSystemRef, value: &Self::Value, mode: AllocationMode) -> Self {
+        assert!(value.len() <= MAX_OUTPUT_LEN);
+
+        let mut padded = value.clone();
+        padded.resize(MAX_OUTPUT_LEN, (0, [0; 8]));
+        let MemorySectionVar { ids, values } = MemorySectionVar::new_variables(cs, &padded, mode);
+
+        let is_active = (0..MAX_OUTPUT_LEN)
+            .map(|i| BitVar::new_variables(cs, &(i < value.len()), mode))
+            .collect_vec();
+        // an entry can only be active if the previous one is
+        for pair in is_active.windows(2) {
+            (&pair[1] & &pair[0].neg()).0.equalverify(&M31Var::zero(cs));
+        }
+
+        Self {
+            ids,
+            values,
+            is_active,
+        }
+    }
+}
+
+impl OutputSectionVar {
+    /// The number of active entries.
+    pub fn length(&self) -> M31Var {
+        let mut length = M31Var::zero(&self.cs());
+        for is_active in self.is_active.iter() {
+            length = &length + &is_active.0;
+        }
+        length
+    }
+
+    /// Mix the active entries in the same way as `MemorySectionVar::mix_into` mixes a section of
+    /// their length.
+    pub fn mix_into(&self, channel: &mut ChannelVar) {
+        for (id, is_active) in self.ids.iter().zip_eq(self.is_active.iter()) {
+            id.conditional_mix_into(channel, is_active);
+        }
+        for (value, is_active) in self.values.iter().zip_eq(self.is_active.iter()) {
+            value
+                .iter()
+                .for_each(|v| v.conditional_mix_into(channel, is_active));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cairo_air::utils::{deserialize_proof_from_file, ProofFormat};
+    use std::path::PathBuf;
+
+    fn load_public_memory() -> PublicMemory {
+        let manifest_dir = env!("CARGO_MANIFEST_DIR");
+        let data_path = PathBuf::from(manifest_dir)
+            .parent()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .join("test_data")
+            .join("recursive_proof.bin.bz");
+
+        let proof = deserialize_proof_from_file(&data_path, ProofFormat::Binary).unwrap();
+        proof.claim.public_data.public_memory
+    }
+
+    #[test]
+    fn test_output_section_shorter_than_max() {
+        let mut public_memory = load_public_memory();
+        assert!(public_memory.output.len() < MAX_OUTPUT_LEN);
+
+        // an even shorter output, with a segment that ends accordingly
+        public_memory.output.truncate(3);
+        let output_segment = &mut public_memory.public_segments.output;
+        output_segment.stop_ptr.value = output_segment.start_ptr.value + 3;
+
+        let cs = ConstraintSystemRef::new();
+        let public_memory_var = PublicMemoryVar::new_witness(&cs, &public_memory);
+        assert_eq!(public_memory_var.output.length().value, M31::from(3));
+        assert_eq!(
+            public_memory_var
+                .output
+                .is_active
+                .iter()
+                .map(|is_active| is_active.value())
+                .collect_vec(),
+            (0..MAX_OUTPUT_LEN).map(|i| i < 3).collect_vec()
+        );
+
+        cs.pad();
+        cs.check_arithmetics();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_output_section_rejects_truncation() {
+        let mut public_memory = load_public_memory();
+
+        // the prover drops the last output entry, but the output segment still covers it
+        public_memory.output.pop();
+
+        let cs = ConstraintSystemRef::new();
+        let _ = PublicMemoryVar::new_witness(&cs, &public_memory);
+    }
+}
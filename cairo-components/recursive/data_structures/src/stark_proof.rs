@@ -4,7 +4,7 @@ use circle_plonk_dsl_constraint_system::{
     ConstraintSystemRef,
 };
 use circle_plonk_dsl_primitives::{channel::PreProcessedTracePresent, BitVar, HashVar};
-use circle_plonk_dsl_primitives::{M31Var, QM31Var};
+use circle_plonk_dsl_primitives::{LinePolyVar, M31Var, QM31Var};
 use indexmap::IndexMap;
 use itertools::Itertools;
 use num_traits::Zero;
@@ -156,13 +156,13 @@ impl StarkProofVar {
 pub struct FriProofVar {
     pub first_layer: FriLayerProofVar,
     pub inner_layers: IndexMap<u32, FriLayerProofVar>,
-    pub last_layer_constant: QM31Var,
+    pub last_layer_poly: LinePolyVar,
 }
 
 impl Var for FriProofVar {
     type Value = FriProof<Poseidon31MerkleHasher>;
     fn cs(&self) -> ConstraintSystemRef {
-        self.last_layer_constant.cs()
+        self.last_layer_poly.cs()
     }
 }
 
@@ -174,7 +174,10 @@ impl AllocVar for FriProofVar {
 
         let mut inner_layers = IndexMap::new();
 
-        let mut layer_log_size = 1;
+        // the inner layers are keyed by their log degree bounds, which end right above the
+        // log degree bound of the last layer
+        let log_last_layer_degree_bound = value.last_layer_poly.coeffs.len().ilog2();
+        let mut layer_log_size = log_last_layer_degree_bound + 1;
         for layer in value.inner_layers.iter().rev() {
             inner_layers.insert(
                 layer_log_size,
@@ -195,13 +198,12 @@ impl AllocVar for FriProofVar {
             layer_log_size += 1;
         }
 
-        let last_layer_constant =
-            QM31Var::new_variables(cs, &value.last_layer_poly.coeffs[0], mode);
+        let last_layer_poly = LinePolyVar::new_variables(cs, &value.last_layer_poly, mode);
 
         Self {
             first_layer,
             inner_layers,
-            last_layer_constant,
+            last_layer_poly,
         }
     }
 }
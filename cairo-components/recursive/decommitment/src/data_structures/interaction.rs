@@ -1,15 +1,29 @@
 use cairo_air::components;
-use cairo_plonk_dsl_data_structures::{BlakeContextClaimVar, CairoClaimVar, OpcodeClaimVar};
+use cairo_plonk_dsl_data_structures::{
+    BlakeContextClaimVar, CairoClaimVar, OpcodeClaimVar, PoseidonContextClaimVar,
+};
 use cairo_plonk_dsl_hints::decommitment::{
     BlakeInteractionQueryResult, InteractionQueryResult, OpcodesInteractionQueryResult,
-    RangeChecksInteractionQueryResult, VerifyBitwiseInteractionQueryResult,
+    PoseidonInteractionQueryResult, RangeChecksInteractionQueryResult,
+    VerifyBitwiseInteractionQueryResult, ADD_MOD_BUILTIN_N_INTERACTION_COLUMNS,
+    BITWISE_BUILTIN_N_INTERACTION_COLUMNS, CUBE_252_N_INTERACTION_COLUMNS,
+    GENERIC_OPCODE_N_INTERACTION_COLUMNS, JUMP_OPCODE_ABS_N_INTERACTION_COLUMNS,
+    JUMP_OPCODE_DOUBLE_DEREF_N_INTERACTION_COLUMNS, MUL_MOD_BUILTIN_N_INTERACTION_COLUMNS,
+    POSEIDON_3_PARTIAL_ROUNDS_CHAIN_N_INTERACTION_COLUMNS,
+    POSEIDON_AGGREGATOR_N_INTERACTION_COLUMNS, POSEIDON_BUILTIN_N_INTERACTION_COLUMNS,
+    POSEIDON_FULL_ROUND_CHAIN_N_INTERACTION_COLUMNS,
+    RANGE_CHECK_252_WIDTH_27_N_INTERACTION_COLUMNS, RANGE_CHECK_96_BUILTIN_N_INTERACTION_COLUMNS,
 };
+use cairo_plonk_dsl_hints::MAX_COMPONENT_INSTANCES;
 use circle_plonk_dsl_constraint_system::{
     var::{AllocVar, AllocationMode, Var},
     ConstraintSystemRef,
 };
 use circle_plonk_dsl_primitives::{option::OptionVar, Poseidon2HalfVar, QM31Var};
 use indexmap::IndexMap;
+use itertools::Itertools;
+use num_traits::Zero;
+use stwo::core::fields::qm31::QM31;
 
 use crate::utils::ColumnsHasherQM31Var;
 
@@ -18,9 +32,15 @@ pub struct InteractionQueryResultVar {
     pub opcodes: OpcodesInteractionQueryResultVar,
     pub verify_instruction: [QM31Var; 3],
     pub blake: BlakeInteractionQueryResultVar,
+    pub add_mod_builtin: [QM31Var; ADD_MOD_BUILTIN_N_INTERACTION_COLUMNS],
+    pub bitwise_builtin: [QM31Var; BITWISE_BUILTIN_N_INTERACTION_COLUMNS],
+    pub mul_mod_builtin: [QM31Var; MUL_MOD_BUILTIN_N_INTERACTION_COLUMNS],
+    pub poseidon_builtin: [QM31Var; POSEIDON_BUILTIN_N_INTERACTION_COLUMNS],
+    pub range_check_96_builtin: [QM31Var; RANGE_CHECK_96_BUILTIN_N_INTERACTION_COLUMNS],
     pub range_check_128_builtin: [QM31Var; 1],
+    pub poseidon: PoseidonInteractionQueryResultVar,
     pub memory_address_to_id: [QM31Var; 8],
-    pub memory_id_to_big_big: [QM31Var; 8],
+    pub memory_id_to_big_big: Vec<[QM31Var; 8]>,
     pub memory_id_to_big_small: [QM31Var; 3],
     pub range_checks: RangeChecksInteractionQueryResultVar,
     pub verify_bitwise: VerifyBitwiseInteractionQueryResultVar,
@@ -43,15 +63,27 @@ impl AllocVar for InteractionQueryResultVar {
                 QM31Var::new_variables(cs, &value.verify_instruction[i], mode)
             }),
             blake: AllocVar::new_variables(cs, &value.blake, mode),
+            add_mod_builtin: new_optional_columns(cs, value.add_mod_builtin.as_ref(), mode),
+            bitwise_builtin: new_optional_columns(cs, value.bitwise_builtin.as_ref(), mode),
+            mul_mod_builtin: new_optional_columns(cs, value.mul_mod_builtin.as_ref(), mode),
+            poseidon_builtin: new_optional_columns(cs, value.poseidon_builtin.as_ref(), mode),
+            range_check_96_builtin: new_optional_columns(
+                cs,
+                value.range_check_96_builtin.as_ref(),
+                mode,
+            ),
             range_check_128_builtin: std::array::from_fn(|i| {
                 QM31Var::new_variables(cs, &value.range_check_128_builtin[i], mode)
             }),
+            poseidon: PoseidonInteractionQueryResultVar::new_optional(
+                cs,
+                value.poseidon.as_ref(),
+                mode,
+            ),
             memory_address_to_id: std::array::from_fn(|i| {
                 QM31Var::new_variables(cs, &value.memory_address_to_id[i], mode)
             }),
-            memory_id_to_big_big: std::array::from_fn(|i| {
-                QM31Var::new_variables(cs, &value.memory_id_to_big_big[i], mode)
-            }),
+            memory_id_to_big_big: new_padded_instances(cs, &value.memory_id_to_big_big, mode),
             memory_id_to_big_small: std::array::from_fn(|i| {
                 QM31Var::new_variables(cs, &value.memory_id_to_big_small[i], mode)
             }),
@@ -72,15 +104,41 @@ impl InteractionQueryResultVar {
         columns_hasher.update(&claim.verify_instruction, &self.verify_instruction);
         self.blake
             .update_hashes(&mut columns_hasher, &claim.blake_context);
+        columns_hasher.update(
+            &claim.builtins.add_mod_builtin.log_size,
+            &self.add_mod_builtin,
+        );
+        columns_hasher.update(
+            &claim.builtins.bitwise_builtin.log_size,
+            &self.bitwise_builtin,
+        );
+        columns_hasher.update(
+            &claim.builtins.mul_mod_builtin.log_size,
+            &self.mul_mod_builtin,
+        );
+        columns_hasher.update(
+            &claim.builtins.poseidon_builtin.log_size,
+            &self.poseidon_builtin,
+        );
+        columns_hasher.update(
+            &claim.builtins.range_check_96_builtin.log_size,
+            &self.range_check_96_builtin,
+        );
         columns_hasher.update(
             &claim.builtins.range_check_128_builtin_log_size,
             &self.range_check_128_builtin,
         );
+        self.poseidon
+            .update_hashes(&mut columns_hasher, &claim.poseidon_context);
         columns_hasher.update(&claim.memory_address_to_id, &self.memory_address_to_id);
-        columns_hasher.update(
-            &claim.memory_id_to_value.big_log_size,
-            &self.memory_id_to_big_big,
-        );
+        for (log_size, columns) in claim
+            .memory_id_to_value
+            .big_log_sizes
+            .iter()
+            .zip_eq(self.memory_id_to_big_big.iter())
+        {
+            columns_hasher.update(log_size, columns);
+        }
         columns_hasher.update(
             &claim.memory_id_to_value.small_log_size,
             &self.memory_id_to_big_small,
@@ -93,23 +151,26 @@ impl InteractionQueryResultVar {
 
 pub struct OpcodesInteractionQueryResultVar {
     pub cs: ConstraintSystemRef,
-    pub add: [QM31Var; 5],
-    pub add_small: [QM31Var; 5],
-    pub add_ap: [QM31Var; 4],
-    pub assert_eq: [QM31Var; 3],
-    pub assert_eq_imm: [QM31Var; 3],
-    pub assert_eq_double_deref: [QM31Var; 4],
-    pub blake: [QM31Var; 37],
-    pub call: [QM31Var; 5],
-    pub call_rel_imm: [QM31Var; 5],
-    pub jnz: [QM31Var; 3],
-    pub jnz_taken: [QM31Var; 4],
-    pub jump_rel: [QM31Var; 3],
-    pub jump_rel_imm: [QM31Var; 3],
-    pub mul: [QM31Var; 19],
-    pub mul_small: [QM31Var; 6],
-    pub qm31: [QM31Var; 6],
-    pub ret: [QM31Var; 4],
+    pub add: Vec<[QM31Var; 5]>,
+    pub add_small: Vec<[QM31Var; 5]>,
+    pub add_ap: Vec<[QM31Var; 4]>,
+    pub assert_eq: Vec<[QM31Var; 3]>,
+    pub assert_eq_imm: Vec<[QM31Var; 3]>,
+    pub assert_eq_double_deref: Vec<[QM31Var; 4]>,
+    pub blake: Vec<[QM31Var; 37]>,
+    pub call: Vec<[QM31Var; 5]>,
+    pub call_rel_imm: Vec<[QM31Var; 5]>,
+    pub generic: Vec<[QM31Var; GENERIC_OPCODE_N_INTERACTION_COLUMNS]>,
+    pub jnz: Vec<[QM31Var; 3]>,
+    pub jnz_taken: Vec<[QM31Var; 4]>,
+    pub jump: Vec<[QM31Var; JUMP_OPCODE_ABS_N_INTERACTION_COLUMNS]>,
+    pub jump_double_deref: Vec<[QM31Var; JUMP_OPCODE_DOUBLE_DEREF_N_INTERACTION_COLUMNS]>,
+    pub jump_rel: Vec<[QM31Var; 3]>,
+    pub jump_rel_imm: Vec<[QM31Var; 3]>,
+    pub mul: Vec<[QM31Var; 19]>,
+    pub mul_small: Vec<[QM31Var; 6]>,
+    pub qm31: Vec<[QM31Var; 6]>,
+    pub ret: Vec<[QM31Var; 4]>,
 }
 
 impl Var for OpcodesInteractionQueryResultVar {
@@ -122,64 +183,68 @@ impl Var for OpcodesInteractionQueryResultVar {
 
 impl AllocVar for OpcodesInteractionQueryResultVar {
     fn new_variables(cs: &ConstraintSystemRef, value: &Self::Value, mode: AllocationMode) -> Self {
+        macro_rules! new_instances {
+            ($name:ident) => {
+                new_padded_instances(cs, &value.$name, mode)
+            };
+        }
+
         Self {
             cs: cs.clone(),
-            add: std::array::from_fn(|i| QM31Var::new_variables(cs, &value.add[i], mode)),
-            add_small: std::array::from_fn(|i| {
-                QM31Var::new_variables(cs, &value.add_small[i], mode)
-            }),
-            add_ap: std::array::from_fn(|i| QM31Var::new_variables(cs, &value.add_ap[i], mode)),
-            assert_eq: std::array::from_fn(|i| {
-                QM31Var::new_variables(cs, &value.assert_eq[i], mode)
-            }),
-            assert_eq_imm: std::array::from_fn(|i| {
-                QM31Var::new_variables(cs, &value.assert_eq_imm[i], mode)
-            }),
-            assert_eq_double_deref: std::array::from_fn(|i| {
-                QM31Var::new_variables(cs, &value.assert_eq_double_deref[i], mode)
-            }),
-            blake: std::array::from_fn(|i| QM31Var::new_variables(cs, &value.blake[i], mode)),
-            call: std::array::from_fn(|i| QM31Var::new_variables(cs, &value.call[i], mode)),
-            call_rel_imm: std::array::from_fn(|i| {
-                QM31Var::new_variables(cs, &value.call_rel_imm[i], mode)
-            }),
-            jnz: std::array::from_fn(|i| QM31Var::new_variables(cs, &value.jnz[i], mode)),
-            jnz_taken: std::array::from_fn(|i| {
-                QM31Var::new_variables(cs, &value.jnz_taken[i], mode)
-            }),
-            jump_rel: std::array::from_fn(|i| QM31Var::new_variables(cs, &value.jump_rel[i], mode)),
-            jump_rel_imm: std::array::from_fn(|i| {
-                QM31Var::new_variables(cs, &value.jump_rel_imm[i], mode)
-            }),
-            mul: std::array::from_fn(|i| QM31Var::new_variables(cs, &value.mul[i], mode)),
-            mul_small: std::array::from_fn(|i| {
-                QM31Var::new_variables(cs, &value.mul_small[i], mode)
-            }),
-            qm31: std::array::from_fn(|i| QM31Var::new_variables(cs, &value.qm31[i], mode)),
-            ret: std::array::from_fn(|i| QM31Var::new_variables(cs, &value.ret[i], mode)),
+            add: new_instances!(add),
+            add_small: new_instances!(add_small),
+            add_ap: new_instances!(add_ap),
+            assert_eq: new_instances!(assert_eq),
+            assert_eq_imm: new_instances!(assert_eq_imm),
+            assert_eq_double_deref: new_instances!(assert_eq_double_deref),
+            blake: new_instances!(blake),
+            call: new_instances!(call),
+            call_rel_imm: new_instances!(call_rel_imm),
+            generic: new_instances!(generic),
+            jnz: new_instances!(jnz),
+            jnz_taken: new_instances!(jnz_taken),
+            jump: new_instances!(jump),
+            jump_double_deref: new_instances!(jump_double_deref),
+            jump_rel: new_instances!(jump_rel),
+            jump_rel_imm: new_instances!(jump_rel_imm),
+            mul: new_instances!(mul),
+            mul_small: new_instances!(mul_small),
+            qm31: new_instances!(qm31),
+            ret: new_instances!(ret),
         }
     }
 }
 
 impl OpcodesInteractionQueryResultVar {
     pub fn update_hashes(&self, columns_hasher: &mut ColumnsHasherQM31Var, claim: &OpcodeClaimVar) {
-        columns_hasher.update(&claim.add, &self.add);
-        columns_hasher.update(&claim.add_small, &self.add_small);
-        columns_hasher.update(&claim.add_ap, &self.add_ap);
-        columns_hasher.update(&claim.assert_eq, &self.assert_eq);
-        columns_hasher.update(&claim.assert_eq_imm, &self.assert_eq_imm);
-        columns_hasher.update(&claim.assert_eq_double_deref, &self.assert_eq_double_deref);
-        columns_hasher.update(&claim.blake, &self.blake);
-        columns_hasher.update(&claim.call, &self.call);
-        columns_hasher.update(&claim.call_rel_imm, &self.call_rel_imm);
-        columns_hasher.update(&claim.jnz, &self.jnz);
-        columns_hasher.update(&claim.jnz_taken, &self.jnz_taken);
-        columns_hasher.update(&claim.jump_rel, &self.jump_rel);
-        columns_hasher.update(&claim.jump_rel_imm, &self.jump_rel_imm);
-        columns_hasher.update(&claim.mul, &self.mul);
-        columns_hasher.update(&claim.mul_small, &self.mul_small);
-        columns_hasher.update(&claim.qm31, &self.qm31);
-        columns_hasher.update(&claim.ret, &self.ret);
+        macro_rules! update_instances {
+            ($name:ident) => {
+                for (log_size, columns) in claim.$name.iter().zip_eq(self.$name.iter()) {
+                    columns_hasher.update(log_size, columns);
+                }
+            };
+        }
+
+        update_instances!(add);
+        update_instances!(add_small);
+        update_instances!(add_ap);
+        update_instances!(assert_eq);
+        update_instances!(assert_eq_imm);
+        update_instances!(assert_eq_double_deref);
+        update_instances!(blake);
+        update_instances!(call);
+        update_instances!(call_rel_imm);
+        update_instances!(generic);
+        update_instances!(jnz);
+        update_instances!(jnz_taken);
+        update_instances!(jump);
+        update_instances!(jump_double_deref);
+        update_instances!(jump_rel);
+        update_instances!(jump_rel_imm);
+        update_instances!(mul);
+        update_instances!(mul_small);
+        update_instances!(qm31);
+        update_instances!(ret);
     }
 }
 
@@ -237,6 +302,77 @@ impl BlakeInteractionQueryResultVar {
     }
 }
 
+pub struct PoseidonInteractionQueryResultVar {
+    pub cs: ConstraintSystemRef,
+    pub aggregator: [QM31Var; POSEIDON_AGGREGATOR_N_INTERACTION_COLUMNS],
+    pub partial_rounds_chain: [QM31Var; POSEIDON_3_PARTIAL_ROUNDS_CHAIN_N_INTERACTION_COLUMNS],
+    pub full_round_chain: [QM31Var; POSEIDON_FULL_ROUND_CHAIN_N_INTERACTION_COLUMNS],
+    pub cube_252: [QM31Var; CUBE_252_N_INTERACTION_COLUMNS],
+    pub round_keys: [QM31Var; 1],
+    pub range_check_252_width_27: [QM31Var; RANGE_CHECK_252_WIDTH_27_N_INTERACTION_COLUMNS],
+}
+
+impl Var for PoseidonInteractionQueryResultVar {
+    type Value = PoseidonInteractionQueryResult;
+
+    fn cs(&self) -> ConstraintSystemRef {
+        self.cs.clone()
+    }
+}
+
+impl AllocVar for PoseidonInteractionQueryResultVar {
+    fn new_variables(cs: &ConstraintSystemRef, value: &Self::Value, mode: AllocationMode) -> Self {
+        Self::new_optional(cs, Some(value), mode)
+    }
+}
+
+impl PoseidonInteractionQueryResultVar {
+    /// Allocates the query result of an absent Poseidon context as zero columns, which the
+    /// zero log sizes of its claim leave out of the column hashes.
+    pub fn new_optional(
+        cs: &ConstraintSystemRef,
+        value: Option<&PoseidonInteractionQueryResult>,
+        mode: AllocationMode,
+    ) -> Self {
+        Self {
+            cs: cs.clone(),
+            aggregator: new_optional_columns(cs, value.map(|v| &v.aggregator), mode),
+            partial_rounds_chain: new_optional_columns(
+                cs,
+                value.map(|v| &v.partial_rounds_chain),
+                mode,
+            ),
+            full_round_chain: new_optional_columns(cs, value.map(|v| &v.full_round_chain), mode),
+            cube_252: new_optional_columns(cs, value.map(|v| &v.cube_252), mode),
+            round_keys: new_optional_columns(cs, value.map(|v| &v.round_keys), mode),
+            range_check_252_width_27: new_optional_columns(
+                cs,
+                value.map(|v| &v.range_check_252_width_27),
+                mode,
+            ),
+        }
+    }
+
+    pub fn update_hashes(
+        &self,
+        columns_hasher: &mut ColumnsHasherQM31Var,
+        claim: &PoseidonContextClaimVar,
+    ) {
+        columns_hasher.update(&claim.poseidon_aggregator, &self.aggregator);
+        columns_hasher.update(
+            &claim.poseidon_3_partial_rounds_chain,
+            &self.partial_rounds_chain,
+        );
+        columns_hasher.update(&claim.poseidon_full_round_chain, &self.full_round_chain);
+        columns_hasher.update(&claim.cube_252, &self.cube_252);
+        columns_hasher.update(&claim.poseidon_round_keys, &self.round_keys);
+        columns_hasher.update(
+            &claim.range_check_252_width_27,
+            &self.range_check_252_width_27,
+        );
+    }
+}
+
 pub struct RangeChecksInteractionQueryResultVar {
     pub cs: ConstraintSystemRef,
     pub range_check_6: [QM31Var; 1],
@@ -538,3 +674,33 @@ impl VerifyBitwiseInteractionQueryResultVar {
         );
     }
 }
+
+/// Allocate the columns of the instances, padded with zero columns to the instance slots of the
+/// claim.
+fn new_padded_instances<const N: usize>(
+    cs: &ConstraintSystemRef,
+    instances: &[[QM31; N]],
+    mode: AllocationMode,
+) -> Vec<[QM31Var; N]> {
+    assert!(instances.len() <= MAX_COMPONENT_INSTANCES);
+    (0..MAX_COMPONENT_INSTANCES)
+        .map(|j| {
+            std::array::from_fn(|i| {
+                let value = instances.get(j).map_or(QM31::zero(), |columns| columns[i]);
+                QM31Var::new_variables(cs, &value, mode)
+            })
+        })
+        .collect()
+}
+
+/// Allocates the columns of an optional component, as zeros if the component is absent.
+fn new_optional_columns<const N: usize>(
+    cs: &ConstraintSystemRef,
+    columns: Option<&[QM31; N]>,
+    mode: AllocationMode,
+) -> [QM31Var; N] {
+    std::array::from_fn(|i| {
+        let value = columns.map_or(QM31::zero(), |columns| columns[i]);
+        QM31Var::new_variables(cs, &value, mode)
+    })
+}
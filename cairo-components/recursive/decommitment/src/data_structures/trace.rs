@@ -1,15 +1,21 @@
 use cairo_air::components;
-use cairo_plonk_dsl_data_structures::{BlakeContextClaimVar, CairoClaimVar, OpcodeClaimVar};
+use cairo_plonk_dsl_data_structures::{
+    BlakeContextClaimVar, CairoClaimVar, OpcodeClaimVar, PoseidonContextClaimVar,
+};
 use cairo_plonk_dsl_hints::decommitment::{
-    BlakeTraceQueryResult, OpcodesTraceQueryResult, RangeChecksTraceQueryResult, TraceQueryResult,
-    VerifyBitwiseTraceQueryResult,
+    BlakeTraceQueryResult, OpcodesTraceQueryResult, PoseidonTraceQueryResult,
+    RangeChecksTraceQueryResult, TraceQueryResult, VerifyBitwiseTraceQueryResult,
 };
+use cairo_plonk_dsl_hints::MAX_COMPONENT_INSTANCES;
 use circle_plonk_dsl_constraint_system::{
     var::{AllocVar, AllocationMode, Var},
     ConstraintSystemRef,
 };
 use circle_plonk_dsl_primitives::{option::OptionVar, M31Var, Poseidon2HalfVar};
 use indexmap::IndexMap;
+use itertools::Itertools;
+use num_traits::Zero;
+use stwo::core::fields::m31::M31;
 
 use crate::utils::ColumnsHasherVar;
 
@@ -18,10 +24,16 @@ pub struct TraceQueryResultVar {
     pub opcodes: OpcodesTraceQueryResultVar,
     pub verify_instruction: [M31Var; components::verify_instruction::N_TRACE_COLUMNS],
     pub blake: BlakeTraceQueryResultVar,
+    pub add_mod_builtin: [M31Var; components::add_mod_builtin::N_TRACE_COLUMNS],
+    pub bitwise_builtin: [M31Var; components::bitwise_builtin::N_TRACE_COLUMNS],
+    pub mul_mod_builtin: [M31Var; components::mul_mod_builtin::N_TRACE_COLUMNS],
+    pub poseidon_builtin: [M31Var; components::poseidon_builtin::N_TRACE_COLUMNS],
+    pub range_check_96_builtin: [M31Var; components::range_check_builtin_bits_96::N_TRACE_COLUMNS],
     pub range_check_128_builtin:
         [M31Var; components::range_check_builtin_bits_128::N_TRACE_COLUMNS],
+    pub poseidon: PoseidonTraceQueryResultVar,
     pub memory_address_to_id: [M31Var; components::memory_address_to_id::N_TRACE_COLUMNS],
-    pub memory_id_to_big_big: [M31Var; components::memory_id_to_big::BIG_N_COLUMNS],
+    pub memory_id_to_big_big: Vec<[M31Var; components::memory_id_to_big::BIG_N_COLUMNS]>,
     pub memory_id_to_big_small: [M31Var; components::memory_id_to_big::SMALL_N_COLUMNS],
     pub range_checks: RangeChecksTraceQueryResultVar,
     pub verify_bitwise: VerifyBitwiseTraceQueryResultVar,
@@ -44,15 +56,23 @@ impl AllocVar for TraceQueryResultVar {
                 M31Var::new_variables(cs, &value.verify_instruction[i], mode)
             }),
             blake: AllocVar::new_variables(cs, &value.blake, mode),
+            add_mod_builtin: new_optional_columns(cs, value.add_mod_builtin.as_ref(), mode),
+            bitwise_builtin: new_optional_columns(cs, value.bitwise_builtin.as_ref(), mode),
+            mul_mod_builtin: new_optional_columns(cs, value.mul_mod_builtin.as_ref(), mode),
+            poseidon_builtin: new_optional_columns(cs, value.poseidon_builtin.as_ref(), mode),
+            range_check_96_builtin: new_optional_columns(
+                cs,
+                value.range_check_96_builtin.as_ref(),
+                mode,
+            ),
             range_check_128_builtin: std::array::from_fn(|i| {
                 M31Var::new_variables(cs, &value.range_check_128_builtin[i], mode)
             }),
+            poseidon: PoseidonTraceQueryResultVar::new_optional(cs, value.poseidon.as_ref(), mode),
             memory_address_to_id: std::array::from_fn(|i| {
                 M31Var::new_variables(cs, &value.memory_address_to_id[i], mode)
             }),
-            memory_id_to_big_big: std::array::from_fn(|i| {
-                M31Var::new_variables(cs, &value.memory_id_to_big_big[i], mode)
-            }),
+            memory_id_to_big_big: new_padded_instances(cs, &value.memory_id_to_big_big, mode),
             memory_id_to_big_small: std::array::from_fn(|i| {
                 M31Var::new_variables(cs, &value.memory_id_to_big_small[i], mode)
             }),
@@ -73,15 +93,41 @@ impl TraceQueryResultVar {
         columns_hasher.update(&claim.verify_instruction, &self.verify_instruction);
         self.blake
             .update_hashes(&mut columns_hasher, &claim.blake_context);
+        columns_hasher.update(
+            &claim.builtins.add_mod_builtin.log_size,
+            &self.add_mod_builtin,
+        );
+        columns_hasher.update(
+            &claim.builtins.bitwise_builtin.log_size,
+            &self.bitwise_builtin,
+        );
+        columns_hasher.update(
+            &claim.builtins.mul_mod_builtin.log_size,
+            &self.mul_mod_builtin,
+        );
+        columns_hasher.update(
+            &claim.builtins.poseidon_builtin.log_size,
+            &self.poseidon_builtin,
+        );
+        columns_hasher.update(
+            &claim.builtins.range_check_96_builtin.log_size,
+            &self.range_check_96_builtin,
+        );
         columns_hasher.update(
             &claim.builtins.range_check_128_builtin_log_size,
             &self.range_check_128_builtin,
         );
+        self.poseidon
+            .update_hashes(&mut columns_hasher, &claim.poseidon_context);
         columns_hasher.update(&claim.memory_address_to_id, &self.memory_address_to_id);
-        columns_hasher.update(
-            &claim.memory_id_to_value.big_log_size,
-            &self.memory_id_to_big_big,
-        );
+        for (log_size, columns) in claim
+            .memory_id_to_value
+            .big_log_sizes
+            .iter()
+            .zip_eq(self.memory_id_to_big_big.iter())
+        {
+            columns_hasher.update(log_size, columns);
+        }
         columns_hasher.update(
             &claim.memory_id_to_value.small_log_size,
             &self.memory_id_to_big_small,
@@ -94,24 +140,27 @@ impl TraceQueryResultVar {
 
 pub struct OpcodesTraceQueryResultVar {
     pub cs: ConstraintSystemRef,
-    pub add: [M31Var; components::add_opcode::N_TRACE_COLUMNS],
-    pub add_small: [M31Var; components::add_opcode_small::N_TRACE_COLUMNS],
-    pub add_ap: [M31Var; components::add_ap_opcode::N_TRACE_COLUMNS],
-    pub assert_eq: [M31Var; components::assert_eq_opcode::N_TRACE_COLUMNS],
-    pub assert_eq_imm: [M31Var; components::assert_eq_opcode_imm::N_TRACE_COLUMNS],
+    pub add: Vec<[M31Var; components::add_opcode::N_TRACE_COLUMNS]>,
+    pub add_small: Vec<[M31Var; components::add_opcode_small::N_TRACE_COLUMNS]>,
+    pub add_ap: Vec<[M31Var; components::add_ap_opcode::N_TRACE_COLUMNS]>,
+    pub assert_eq: Vec<[M31Var; components::assert_eq_opcode::N_TRACE_COLUMNS]>,
+    pub assert_eq_imm: Vec<[M31Var; components::assert_eq_opcode_imm::N_TRACE_COLUMNS]>,
     pub assert_eq_double_deref:
-        [M31Var; components::assert_eq_opcode_double_deref::N_TRACE_COLUMNS],
-    pub blake: [M31Var; components::blake_compress_opcode::N_TRACE_COLUMNS],
-    pub call: [M31Var; components::call_opcode_abs::N_TRACE_COLUMNS],
-    pub call_rel_imm: [M31Var; components::call_opcode_rel_imm::N_TRACE_COLUMNS],
-    pub jnz: [M31Var; components::jnz_opcode_non_taken::N_TRACE_COLUMNS],
-    pub jnz_taken: [M31Var; components::jnz_opcode_taken::N_TRACE_COLUMNS],
-    pub jump_rel: [M31Var; components::jump_opcode_rel::N_TRACE_COLUMNS],
-    pub jump_rel_imm: [M31Var; components::jump_opcode_rel_imm::N_TRACE_COLUMNS],
-    pub mul: [M31Var; components::mul_opcode::N_TRACE_COLUMNS],
-    pub mul_small: [M31Var; components::mul_opcode_small::N_TRACE_COLUMNS],
-    pub qm31: [M31Var; components::qm_31_add_mul_opcode::N_TRACE_COLUMNS],
-    pub ret: [M31Var; components::ret_opcode::N_TRACE_COLUMNS],
+        Vec<[M31Var; components::assert_eq_opcode_double_deref::N_TRACE_COLUMNS]>,
+    pub blake: Vec<[M31Var; components::blake_compress_opcode::N_TRACE_COLUMNS]>,
+    pub call: Vec<[M31Var; components::call_opcode_abs::N_TRACE_COLUMNS]>,
+    pub call_rel_imm: Vec<[M31Var; components::call_opcode_rel_imm::N_TRACE_COLUMNS]>,
+    pub generic: Vec<[M31Var; components::generic_opcode::N_TRACE_COLUMNS]>,
+    pub jnz: Vec<[M31Var; components::jnz_opcode_non_taken::N_TRACE_COLUMNS]>,
+    pub jnz_taken: Vec<[M31Var; components::jnz_opcode_taken::N_TRACE_COLUMNS]>,
+    pub jump: Vec<[M31Var; components::jump_opcode_abs::N_TRACE_COLUMNS]>,
+    pub jump_double_deref: Vec<[M31Var; components::jump_opcode_double_deref::N_TRACE_COLUMNS]>,
+    pub jump_rel: Vec<[M31Var; components::jump_opcode_rel::N_TRACE_COLUMNS]>,
+    pub jump_rel_imm: Vec<[M31Var; components::jump_opcode_rel_imm::N_TRACE_COLUMNS]>,
+    pub mul: Vec<[M31Var; components::mul_opcode::N_TRACE_COLUMNS]>,
+    pub mul_small: Vec<[M31Var; components::mul_opcode_small::N_TRACE_COLUMNS]>,
+    pub qm31: Vec<[M31Var; components::qm_31_add_mul_opcode::N_TRACE_COLUMNS]>,
+    pub ret: Vec<[M31Var; components::ret_opcode::N_TRACE_COLUMNS]>,
 }
 
 impl Var for OpcodesTraceQueryResultVar {
@@ -124,64 +173,68 @@ impl Var for OpcodesTraceQueryResultVar {
 
 impl AllocVar for OpcodesTraceQueryResultVar {
     fn new_variables(cs: &ConstraintSystemRef, value: &Self::Value, mode: AllocationMode) -> Self {
+        macro_rules! new_instances {
+            ($name:ident) => {
+                new_padded_instances(cs, &value.$name, mode)
+            };
+        }
+
         Self {
             cs: cs.clone(),
-            add: std::array::from_fn(|i| M31Var::new_variables(cs, &value.add[i], mode)),
-            add_small: std::array::from_fn(|i| {
-                M31Var::new_variables(cs, &value.add_small[i], mode)
-            }),
-            add_ap: std::array::from_fn(|i| M31Var::new_variables(cs, &value.add_ap[i], mode)),
-            assert_eq: std::array::from_fn(|i| {
-                M31Var::new_variables(cs, &value.assert_eq[i], mode)
-            }),
-            assert_eq_imm: std::array::from_fn(|i| {
-                M31Var::new_variables(cs, &value.assert_eq_imm[i], mode)
-            }),
-            assert_eq_double_deref: std::array::from_fn(|i| {
-                M31Var::new_variables(cs, &value.assert_eq_double_deref[i], mode)
-            }),
-            blake: std::array::from_fn(|i| M31Var::new_variables(cs, &value.blake[i], mode)),
-            call: std::array::from_fn(|i| M31Var::new_variables(cs, &value.call[i], mode)),
-            call_rel_imm: std::array::from_fn(|i| {
-                M31Var::new_variables(cs, &value.call_rel_imm[i], mode)
-            }),
-            jnz: std::array::from_fn(|i| M31Var::new_variables(cs, &value.jnz[i], mode)),
-            jnz_taken: std::array::from_fn(|i| {
-                M31Var::new_variables(cs, &value.jnz_taken[i], mode)
-            }),
-            jump_rel: std::array::from_fn(|i| M31Var::new_variables(cs, &value.jump_rel[i], mode)),
-            jump_rel_imm: std::array::from_fn(|i| {
-                M31Var::new_variables(cs, &value.jump_rel_imm[i], mode)
-            }),
-            mul: std::array::from_fn(|i| M31Var::new_variables(cs, &value.mul[i], mode)),
-            mul_small: std::array::from_fn(|i| {
-                M31Var::new_variables(cs, &value.mul_small[i], mode)
-            }),
-            qm31: std::array::from_fn(|i| M31Var::new_variables(cs, &value.qm31[i], mode)),
-            ret: std::array::from_fn(|i| M31Var::new_variables(cs, &value.ret[i], mode)),
+            add: new_instances!(add),
+            add_small: new_instances!(add_small),
+            add_ap: new_instances!(add_ap),
+            assert_eq: new_instances!(assert_eq),
+            assert_eq_imm: new_instances!(assert_eq_imm),
+            assert_eq_double_deref: new_instances!(assert_eq_double_deref),
+            blake: new_instances!(blake),
+            call: new_instances!(call),
+            call_rel_imm: new_instances!(call_rel_imm),
+            generic: new_instances!(generic),
+            jnz: new_instances!(jnz),
+            jnz_taken: new_instances!(jnz_taken),
+            jump: new_instances!(jump),
+            jump_double_deref: new_instances!(jump_double_deref),
+            jump_rel: new_instances!(jump_rel),
+            jump_rel_imm: new_instances!(jump_rel_imm),
+            mul: new_instances!(mul),
+            mul_small: new_instances!(mul_small),
+            qm31: new_instances!(qm31),
+            ret: new_instances!(ret),
         }
     }
 }
 
 impl OpcodesTraceQueryResultVar {
     pub fn update_hashes(&self, columns_hasher: &mut ColumnsHasherVar, claim: &OpcodeClaimVar) {
-        columns_hasher.update(&claim.add, &self.add);
-        columns_hasher.update(&claim.add_small, &self.add_small);
-        columns_hasher.update(&claim.add_ap, &self.add_ap);
-        columns_hasher.update(&claim.assert_eq, &self.assert_eq);
-        columns_hasher.update(&claim.assert_eq_imm, &self.assert_eq_imm);
-        columns_hasher.update(&claim.assert_eq_double_deref, &self.assert_eq_double_deref);
-        columns_hasher.update(&claim.blake, &self.blake);
-        columns_hasher.update(&claim.call, &self.call);
-        columns_hasher.update(&claim.call_rel_imm, &self.call_rel_imm);
-        columns_hasher.update(&claim.jnz, &self.jnz);
-        columns_hasher.update(&claim.jnz_taken, &self.jnz_taken);
-        columns_hasher.update(&claim.jump_rel, &self.jump_rel);
-        columns_hasher.update(&claim.jump_rel_imm, &self.jump_rel_imm);
-        columns_hasher.update(&claim.mul, &self.mul);
-        columns_hasher.update(&claim.mul_small, &self.mul_small);
-        columns_hasher.update(&claim.qm31, &self.qm31);
-        columns_hasher.update(&claim.ret, &self.ret);
+        macro_rules! update_instances {
+            ($name:ident) => {
+                for (log_size, columns) in claim.$name.iter().zip_eq(self.$name.iter()) {
+                    columns_hasher.update(log_size, columns);
+                }
+            };
+        }
+
+        update_instances!(add);
+        update_instances!(add_small);
+        update_instances!(add_ap);
+        update_instances!(assert_eq);
+        update_instances!(assert_eq_imm);
+        update_instances!(assert_eq_double_deref);
+        update_instances!(blake);
+        update_instances!(call);
+        update_instances!(call_rel_imm);
+        update_instances!(generic);
+        update_instances!(jnz);
+        update_instances!(jnz_taken);
+        update_instances!(jump);
+        update_instances!(jump_double_deref);
+        update_instances!(jump_rel);
+        update_instances!(jump_rel_imm);
+        update_instances!(mul);
+        update_instances!(mul_small);
+        update_instances!(qm31);
+        update_instances!(ret);
     }
 }
 
@@ -239,6 +292,78 @@ impl BlakeTraceQueryResultVar {
     }
 }
 
+pub struct PoseidonTraceQueryResultVar {
+    pub cs: ConstraintSystemRef,
+    pub aggregator: [M31Var; components::poseidon_aggregator::N_TRACE_COLUMNS],
+    pub partial_rounds_chain:
+        [M31Var; components::poseidon_3_partial_rounds_chain::N_TRACE_COLUMNS],
+    pub full_round_chain: [M31Var; components::poseidon_full_round_chain::N_TRACE_COLUMNS],
+    pub cube_252: [M31Var; components::cube_252::N_TRACE_COLUMNS],
+    pub round_keys: [M31Var; components::poseidon_round_keys::N_TRACE_COLUMNS],
+    pub range_check_252_width_27: [M31Var; components::range_check_252_width_27::N_TRACE_COLUMNS],
+}
+
+impl Var for PoseidonTraceQueryResultVar {
+    type Value = PoseidonTraceQueryResult;
+
+    fn cs(&self) -> ConstraintSystemRef {
+        self.cs.clone()
+    }
+}
+
+impl AllocVar for PoseidonTraceQueryResultVar {
+    fn new_variables(cs: &ConstraintSystemRef, value: &Self::Value, mode: AllocationMode) -> Self {
+        Self::new_optional(cs, Some(value), mode)
+    }
+}
+
+impl PoseidonTraceQueryResultVar {
+    /// Allocates the query result of an absent Poseidon context as zero columns, which the
+    /// zero log sizes of its claim leave out of the column hashes.
+    pub fn new_optional(
+        cs: &ConstraintSystemRef,
+        value: Option<&PoseidonTraceQueryResult>,
+        mode: AllocationMode,
+    ) -> Self {
+        Self {
+            cs: cs.clone(),
+            aggregator: new_optional_columns(cs, value.map(|v| &v.aggregator), mode),
+            partial_rounds_chain: new_optional_columns(
+                cs,
+                value.map(|v| &v.partial_rounds_chain),
+                mode,
+            ),
+            full_round_chain: new_optional_columns(cs, value.map(|v| &v.full_round_chain), mode),
+            cube_252: new_optional_columns(cs, value.map(|v| &v.cube_252), mode),
+            round_keys: new_optional_columns(cs, value.map(|v| &v.round_keys), mode),
+            range_check_252_width_27: new_optional_columns(
+                cs,
+                value.map(|v| &v.range_check_252_width_27),
+                mode,
+            ),
+        }
+    }
+
+    pub fn update_hashes(
+        &self,
+        columns_hasher: &mut ColumnsHasherVar,
+        claim: &PoseidonContextClaimVar,
+    ) {
+        columns_hasher.update(&claim.poseidon_aggregator, &self.aggregator);
+        columns_hasher.update(
+            &claim.poseidon_3_partial_rounds_chain,
+            &self.partial_rounds_chain,
+        );
+        columns_hasher.update(&claim.poseidon_full_round_chain, &self.full_round_chain);
+        columns_hasher.update(&claim.cube_252, &self.cube_252);
+        columns_hasher.update(&claim.poseidon_round_keys, &self.round_keys);
+        columns_hasher.update(
+            &claim.range_check_252_width_27,
+            &self.range_check_252_width_27,
+        );
+    }
+}
+
 pub struct RangeChecksTraceQueryResultVar {
     pub cs: ConstraintSystemRef,
     pub range_check_6: [M31Var; components::range_check_6::N_TRACE_COLUMNS],
@@ -540,3 +665,33 @@ impl VerifyBitwiseTraceQueryResultVar {
         );
     }
 }
+
+/// Allocate the columns of the instances, padded with zero columns to the instance slots of the
+/// claim.
+fn new_padded_instances<const N: usize>(
+    cs: &ConstraintSystemRef,
+    instances: &[[M31; N]],
+    mode: AllocationMode,
+) -> Vec<[M31Var; N]> {
+    assert!(instances.len() <= MAX_COMPONENT_INSTANCES);
+    (0..MAX_COMPONENT_INSTANCES)
+        .map(|j| {
+            std::array::from_fn(|i| {
+                let value = instances.get(j).map_or(M31::zero(), |columns| columns[i]);
+                M31Var::new_variables(cs, &value, mode)
+            })
+        })
+        .collect()
+}
+
+/// Allocates the columns of an optional component, as zeros if the component is absent.
+fn new_optional_columns<const N: usize>(
+    cs: &ConstraintSystemRef,
+    columns: Option<&[M31; N]>,
+    mode: AllocationMode,
+) -> [M31Var; N] {
+    std::array::from_fn(|i| {
+        let value = columns.map_or(M31::zero(), |columns| columns[i]);
+        M31Var::new_variables(cs, &value, mode)
+    })
+}
@@ -124,7 +124,9 @@ mod tests {
     use cairo_air::utils::{deserialize_proof_from_file, ProofFormat};
     use cairo_plonk_dsl_data_structures::CairoProofVar;
     use cairo_plonk_dsl_fiat_shamir::CairoFiatShamirResults;
-    use cairo_plonk_dsl_hints::{CairoDecommitmentHints, CairoFiatShamirHints};
+    use cairo_plonk_dsl_hints::{
+        CairoDecommitmentHints, CairoFiatShamirHints, CairoVerifierParams,
+    };
     use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
     use std::path::PathBuf;
 
@@ -140,7 +142,8 @@ mod tests {
             .join("recursive_proof.bin.bz");
 
         let proof = deserialize_proof_from_file(&data_path, ProofFormat::Binary).unwrap();
-        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof);
+        let params = CairoVerifierParams::from_proof(&proof);
+        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof, &params);
         let decommitment_hints = CairoDecommitmentHints::new(&fiat_shamir_hints, &proof);
 
         let cs = ConstraintSystemRef::new();
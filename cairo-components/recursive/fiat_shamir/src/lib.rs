@@ -3,17 +3,23 @@ use std::ops::Neg;
 
 use cairo_air::verifier::INTERACTION_POW_BITS;
 use cairo_plonk_dsl_data_structures::{
-    interaction_claim::CairoInteractionClaimVar, lookup::CairoInteractionElementsVar,
+    interaction_claim::CairoInteractionClaimVar,
+    lookup::CairoInteractionElementsVar,
+    public_data::{MemorySectionVar, ProgramSectionVar, SegmentRangeVar},
     CairoClaimVar, CairoProofVar,
 };
-use cairo_plonk_dsl_hints::CairoFiatShamirHints;
+use cairo_plonk_dsl_hints::{CairoFiatShamirHints, ProgramMode};
 use circle_plonk_dsl_constraint_system::var::{AllocVar, Var};
 use circle_plonk_dsl_primitives::{
     channel::ConditionalChannelMixer, option::OptionVar, BitIntVar, BitVar, BitsVar, ChannelVar,
-    CirclePointQM31Var, M31Var, Poseidon2HalfVar, QM31Var,
+    CirclePointQM31Var, LogSizeVar, M31Var, Poseidon2HalfVar, QM31Var, TranscriptLog,
 };
 use stwo::core::{fields::m31::M31, vcs::poseidon31_hash::Poseidon31Hash};
 use stwo_cairo_common::{
+    builtins::{
+        ADD_MOD_MEMORY_CELLS, BITWISE_MEMORY_CELLS, MUL_MOD_MEMORY_CELLS, POSEIDON_MEMORY_CELLS,
+        RANGE_CHECK_MEMORY_CELLS,
+    },
     memory::LARGE_MEMORY_VALUE_ID_BASE,
     preprocessed_columns::preprocessed_trace::MAX_SEQUENCE_LOG_SIZE,
 };
@@ -30,16 +36,74 @@ pub struct CairoFiatShamirResults {
 
     pub first_layer_alpha: QM31Var,
     pub inner_layers_alphas: IndexMap<u32, OptionVar<QM31Var>>,
+
+    /// The log of the channel operations from the interaction commitment on, to be compared
+    /// with [`CairoFiatShamirHints::transcript`], which is only kept by
+    /// [`CairoFiatShamirResults::compute_with_transcript_log`].
+    pub transcript: Option<TranscriptLog>,
 }
 
 impl CairoFiatShamirResults {
     pub fn compute(fiat_shamir_hints: &CairoFiatShamirHints, proof: &CairoProofVar) -> Self {
+        assert_eq!(
+            fiat_shamir_hints.params.program_mode,
+            ProgramMode::Hardcoded,
+            "The hashed program mode requires the program hash"
+        );
+        Self::compute_inner(fiat_shamir_hints, proof, None, false)
+    }
+
+    /// Same as [`CairoFiatShamirResults::compute`], but also log the channel operations, so that
+    /// the transcript can be compared with the one of the native verifier when a proof fails to
+    /// verify.
+    pub fn compute_with_transcript_log(
+        fiat_shamir_hints: &CairoFiatShamirHints,
+        proof: &CairoProofVar,
+    ) -> Self {
+        assert_eq!(
+            fiat_shamir_hints.params.program_mode,
+            ProgramMode::Hardcoded,
+            "The hashed program mode requires the program hash"
+        );
+        Self::compute_inner(fiat_shamir_hints, proof, None, true)
+    }
+
+    /// Compute the results in the hashed program mode, where the program memory section is a
+    /// witness that must hash to `program_hash`.
+    pub fn compute_with_program_hash(
+        fiat_shamir_hints: &CairoFiatShamirHints,
+        proof: &CairoProofVar,
+        program_hash: &Poseidon2HalfVar,
+    ) -> Self {
+        assert_eq!(
+            fiat_shamir_hints.params.program_mode,
+            ProgramMode::Hashed,
+            "The hardcoded program mode does not take a program hash"
+        );
+        Self::compute_inner(fiat_shamir_hints, proof, Some(program_hash), false)
+    }
+
+    fn compute_inner(
+        fiat_shamir_hints: &CairoFiatShamirHints,
+        proof: &CairoProofVar,
+        program_hash: Option<&Poseidon2HalfVar>,
+        log_transcript: bool,
+    ) -> Self {
         let cs = proof.cs();
 
         let mut channel = ChannelVar::default(&cs);
         channel.digest =
             Poseidon2HalfVar::new_constant(&cs, &Poseidon31Hash(fiat_shamir_hints.initial_channel));
 
+        // in the hashed mode, the initial channel stops right before the program
+        let program_witness = program_hash.map(|program_hash| {
+            let program = MemorySectionVar::new_witness(&cs, &fiat_shamir_hints.program);
+            Self::check_program(&program, fiat_shamir_hints.params.builtins.len() as u32);
+            Self::program_hash(&program).equalverify(program_hash);
+            program.mix_into(&mut channel);
+            program
+        });
+
         Self::check_claim(&proof.claim);
         proof.claim.mix_into(&mut channel);
 
@@ -52,8 +116,12 @@ impl CairoFiatShamirResults {
         lower_bits.equalverify(&M31Var::zero(&cs));
 
         let interaction_elements = CairoInteractionElementsVar::draw(&mut channel);
-        proof.interaction_claim.mix_into(&mut channel);
+        proof.interaction_claim.check_instances(&proof.claim);
+        proof.interaction_claim.mix_into(&mut channel, &proof.claim);
 
+        if log_transcript {
+            channel.enable_transcript_log();
+        }
         channel.mix_root(&proof.stark_proof.interaction_commitment);
         let random_coeff = channel.draw_felts()[0].clone();
         channel.mix_root(&proof.stark_proof.composition_commitment);
@@ -64,7 +132,7 @@ impl CairoFiatShamirResults {
         let channel_mixer = ConditionalChannelMixer::new(channel);
         channel = channel_mixer.mix(
             &proof.stark_proof.sampled_values.clone().flatten_cols(),
-            &proof.stark_proof.is_preprocessed_trace_present,
+            &proof.sampled_values_presence(),
         );
         let after_sampled_values_random_coeff = channel.draw_felts()[0].clone();
 
@@ -74,8 +142,13 @@ impl CairoFiatShamirResults {
             cs.num_poseidon_invocations()
         );
 
+        let program = match &program_witness {
+            Some(program) => ProgramSectionVar::Witness(program),
+            None => ProgramSectionVar::Constant(&fiat_shamir_hints.params.program),
+        };
         let lookup_sum = Self::lookup_sum(
             &proof.claim,
+            program,
             &interaction_elements,
             &proof.interaction_claim,
         );
@@ -88,6 +161,10 @@ impl CairoFiatShamirResults {
             max_preprocessed_trace_log_size.max(&max_trace_and_interaction_log_size, 5);
         let composition_log_size = &max_log_size + &M31Var::one(&cs);
 
+        let pcs_config = &fiat_shamir_hints.pcs_config;
+        let log_blowup_factor = pcs_config.fri_config.log_blowup_factor;
+        let log_last_layer_degree_bound = pcs_config.fri_config.log_last_layer_degree_bound;
+
         channel.mix_root(&proof.stark_proof.fri_proof.first_layer.commitment);
         let first_layer_alpha = channel.draw_felts()[0].clone();
 
@@ -95,10 +172,17 @@ impl CairoFiatShamirResults {
             &M31Var::new_constant(&cs, &M31::from(MAX_SEQUENCE_LOG_SIZE)) - &max_log_size;
 
         let mut inner_layers_alphas = IndexMap::new();
-        for layer_log_size in (1..MAX_SEQUENCE_LOG_SIZE).rev() {
+        for layer_log_size in (log_last_layer_degree_bound + 1..MAX_SEQUENCE_LOG_SIZE).rev() {
             let skip = num_layers_to_skip.is_zero().neg();
             num_layers_to_skip = &num_layers_to_skip - &skip.0;
 
+            // a skipped layer is not part of the native transcript
+            let paused_log = if skip.value() {
+                channel.log.take()
+            } else {
+                None
+            };
+
             let existing_channel = channel.digest.to_qm31();
             channel.mix_root(
                 &proof
@@ -120,18 +204,29 @@ impl CairoFiatShamirResults {
             ];
 
             channel.digest = Poseidon2HalfVar::from_qm31(&new_digest[0], &new_digest[1]);
+            if paused_log.is_some() {
+                channel.log = paused_log;
+            }
         }
 
-        channel.mix_one_felt(&proof.stark_proof.fri_proof.last_layer_constant);
+        for chunk in proof.stark_proof.fri_proof.last_layer_poly.coeffs.chunks(2) {
+            if chunk.len() == 1 {
+                channel.mix_one_felt(&chunk[0]);
+            } else {
+                channel.mix_two_felts(&chunk[0], &chunk[1]);
+            }
+        }
         proof.stark_proof.proof_of_work.mix_into(&mut channel);
 
         let lower_bits = BitsVar::from_m31(&channel.digest.to_qm31()[0].decompose_m31()[0], 31)
-            .compose_range(0..26); // hardcoded pow_bits of 26
+            .compose_range(0..pcs_config.pow_bits as usize);
         lower_bits.equalverify(&M31Var::zero(&cs));
 
-        let query_log_size = composition_log_size.clone(); // when the log_blowup_factor is 1
+        // the composition polynomial is split into parts of degree bound `max_log_size`, which
+        // are committed on a domain that is `log_blowup_factor` larger
+        let query_log_size =
+            &max_log_size + &M31Var::new_constant(&cs, &M31::from(log_blowup_factor));
 
-        let pcs_config = &fiat_shamir_hints.pcs_config;
         let mut raw_queries = Vec::with_capacity(pcs_config.fri_config.n_queries);
         let mut draw_queries_felts =
             Vec::with_capacity(pcs_config.fri_config.n_queries.div_ceil(4));
@@ -145,9 +240,7 @@ impl CairoFiatShamirResults {
         }
         raw_queries.truncate(pcs_config.fri_config.n_queries);
 
-        let max_len = (MAX_SEQUENCE_LOG_SIZE
-            + fiat_shamir_hints.pcs_config.fri_config.log_blowup_factor)
-            as usize;
+        let max_len = (MAX_SEQUENCE_LOG_SIZE + log_blowup_factor) as usize;
 
         let mut mask: Vec<BitVar> = vec![];
         let mut cur = query_log_size.clone();
@@ -179,6 +272,37 @@ impl CairoFiatShamirResults {
 
             first_layer_alpha,
             inner_layers_alphas,
+            transcript: channel.transcript_log(),
+        }
+    }
+
+    /// The hash of the program values, mixed one by one into a fresh channel.
+    pub fn program_hash(program: &MemorySectionVar) -> Poseidon2HalfVar {
+        let mut channel = ChannelVar::default(&program.cs());
+        for value in program.values.iter() {
+            for v in value.iter() {
+                v.mix_into(&mut channel);
+            }
+        }
+        channel.digest
+    }
+
+    /// Check the entries of a witness program that the hints check on a hardcoded program: the
+    /// instructions that push the builtin pointers and call the main function, and the final
+    /// `jmp rel 0`.
+    fn check_program(program: &MemorySectionVar, n_builtins: u32) {
+        let cs = program.cs();
+        let entries: [(usize, [u32; 8]); 5] = [
+            (0, [0x7fff7fff, 0x4078001, 0, 0, 0, 0, 0, 0]),
+            (1, [n_builtins, 0, 0, 0, 0, 0, 0, 0]),
+            (2, [0x80018000, 0x11048001, 0, 0, 0, 0, 0, 0]),
+            (4, [0x7fff7fff, 0x1078001, 0, 0, 0, 0, 0, 0]),
+            (5, [0, 0, 0, 0, 0, 0, 0, 0]),
+        ];
+        for (index, limbs) in entries {
+            for (v, limb) in program.values[index].iter().zip(limbs) {
+                v.enforce_equal(&BitIntVar::<32>::new_constant(&cs, &(limb as u64)));
+            }
         }
     }
 
@@ -186,16 +310,10 @@ impl CairoFiatShamirResults {
         let public_data = &claim.public_data;
         let segment_ranges = &public_data.public_memory.public_segments;
 
-        segment_ranges.range_check_128.enforce_is_not_empty();
         segment_ranges.pedersen.enforce_is_empty();
         segment_ranges.ecdsa.enforce_is_empty();
-        segment_ranges.bitwise.enforce_is_empty();
         segment_ranges.ec_op.enforce_is_empty();
         segment_ranges.keccak.enforce_is_empty();
-        segment_ranges.poseidon.enforce_is_empty();
-        segment_ranges.range_check_96.enforce_is_empty();
-        segment_ranges.add_mod.enforce_is_empty();
-        segment_ranges.mul_mod.enforce_is_empty();
 
         // check output builtin
         {
@@ -207,28 +325,61 @@ impl CairoFiatShamirResults {
         }
 
         // find the claim for range_check_128
-        {
-            let segment_start = &claim.builtins.range_check_builtin_segment_start;
-            let start_ptr = &segment_ranges.range_check_128.start_ptr.value;
-            let stop_ptr = &segment_ranges.range_check_128.stop_ptr.value;
-            start_ptr.enforce_equal(segment_start);
-
-            let start_ptr_bits = &start_ptr.bits;
-            let stop_ptr_bits = &stop_ptr.bits;
-            start_ptr_bits
-                .is_greater_than(stop_ptr_bits)
-                .equalverify(&BitVar::new_false(&start_ptr_bits.cs()));
-
-            let segment_end =
-                &segment_start.to_m31() + &claim.builtins.range_check_128_builtin_log_size.pow2;
-            let segment_end_bits = BitsVar::from_m31(&segment_end, 31);
+        Self::check_builtin_segment(
+            &segment_ranges.range_check_128,
+            &claim.builtins.range_check_builtin_segment_start,
+            &claim.builtins.range_check_128_builtin_log_size,
+            &BitVar::new_true(&claim.cs()),
+            RANGE_CHECK_MEMORY_CELLS,
+        );
 
-            stop_ptr_bits
-                .is_greater_than(&segment_end_bits)
-                .equalverify(&BitVar::new_false(&stop_ptr_bits.cs()));
+        // the add_mod, bitwise, mul_mod, poseidon, and range_check_96 builtins are present if and
+        // only if their segments are not empty
+        let builtins = &claim.builtins;
+        for (segment_range, builtin, n_cells) in [
+            (
+                &segment_ranges.add_mod,
+                &builtins.add_mod_builtin,
+                ADD_MOD_MEMORY_CELLS,
+            ),
+            (
+                &segment_ranges.bitwise,
+                &builtins.bitwise_builtin,
+                BITWISE_MEMORY_CELLS,
+            ),
+            (
+                &segment_ranges.mul_mod,
+                &builtins.mul_mod_builtin,
+                MUL_MOD_MEMORY_CELLS,
+            ),
+            (
+                &segment_ranges.poseidon,
+                &builtins.poseidon_builtin,
+                POSEIDON_MEMORY_CELLS,
+            ),
+            (
+                &segment_ranges.range_check_96,
+                &builtins.range_check_96_builtin,
+                RANGE_CHECK_MEMORY_CELLS,
+            ),
+        ] {
+            Self::check_builtin_segment(
+                segment_range,
+                &builtin.segment_start,
+                &builtin.log_size,
+                &builtin.is_present,
+                n_cells,
+            );
         }
 
-        // program is a constant, so we do not check it
+        // the Poseidon context comes with the poseidon builtin
+        claim
+            .poseidon_context
+            .is_present
+            .equalverify(&builtins.poseidon_builtin.is_present);
+
+        // the program entries are checked by `check_program` in the hashed mode, and are the
+        // constant of the verifier params otherwise
         let initial_pc = &claim.public_data.initial_state.pc;
         let initial_ap = &claim.public_data.initial_state.ap;
         let initial_fp = &claim.public_data.initial_state.fp;
@@ -261,24 +412,89 @@ impl CairoFiatShamirResults {
         claim.accumulate_relation_uses(&mut relation_uses);
 
         // check that the largest id does not overflow PRIME
-        let _ = (&claim.memory_id_to_value.big_log_size.to_m31().exp2()
-            - &M31Var::one(&claim.cs()))
-            .add_assert_no_overflow(&M31Var::new_constant(
-                &claim.cs(),
-                &M31::from(LARGE_MEMORY_VALUE_ID_BASE),
-            ));
+        let n_large_ids = claim
+            .memory_id_to_value
+            .big_log_sizes
+            .n_rows()
+            .into_iter()
+            .reduce(|sum, n_ids| sum.add_assert_no_overflow(&n_ids))
+            .unwrap();
+        let _ = n_large_ids.add_assert_no_overflow(&M31Var::new_constant(
+            &claim.cs(),
+            &M31::from(LARGE_MEMORY_VALUE_ID_BASE - 1),
+        ));
+    }
+
+    /// Check that the segment of a builtin is empty if and only if the builtin is absent, and
+    /// otherwise that it starts at the segment start of the claim and consists of whole instances
+    /// within the rows of the builtin component. An empty segment passes the other checks.
+    fn check_builtin_segment(
+        segment_range: &SegmentRangeVar,
+        segment_start: &BitIntVar<31>,
+        log_size: &LogSizeVar,
+        is_present: &BitVar,
+        n_cells: usize,
+    ) {
+        let cs = segment_range.cs();
+        let start_ptr = &segment_range.start_ptr.value;
+        let stop_ptr = &segment_range.stop_ptr.value;
+
+        start_ptr
+            .to_m31()
+            .is_eq(&stop_ptr.to_m31())
+            .equalverify(&is_present.neg());
+        (&(&start_ptr.to_m31() - &segment_start.to_m31()) * &is_present.0)
+            .equalverify(&M31Var::zero(&cs));
+
+        let start_ptr_bits = &start_ptr.bits;
+        let stop_ptr_bits = &stop_ptr.bits;
+        start_ptr_bits
+            .is_greater_than(stop_ptr_bits)
+            .equalverify(&BitVar::new_false(&cs));
+
+        let n_cells_m31 = M31::from(n_cells as u32);
+        if n_cells > 1 {
+            // the number of instances is at most the number of rows, which avoids any overflow
+            let n_instances = M31Var::new_witness(
+                &cs,
+                &M31::from(
+                    stop_ptr
+                        .to_m31()
+                        .value
+                        .0
+                        .saturating_sub(start_ptr.to_m31().value.0)
+                        / n_cells as u32,
+                ),
+            );
+            n_instances.range_check(MAX_SEQUENCE_LOG_SIZE as usize + 1);
+            start_ptr
+                .to_m31()
+                .add_assert_no_overflow(&n_instances.mul_constant(n_cells_m31))
+                .equalverify(&stop_ptr.to_m31());
+        }
+
+        let segment_end = start_ptr
+            .to_m31()
+            .add_assert_no_overflow(&log_size.pow2.mul_constant(n_cells_m31));
+        let segment_end_bits = BitsVar::from_m31(&segment_end, 31);
+
+        stop_ptr_bits
+            .is_greater_than(&segment_end_bits)
+            .equalverify(&BitVar::new_false(&cs));
     }
 
     pub fn lookup_sum(
         claim: &CairoClaimVar,
+        program: ProgramSectionVar<'_>,
         elements: &CairoInteractionElementsVar,
         interaction_claim: &CairoInteractionClaimVar,
     ) -> QM31Var {
-        let mut sum = claim.public_data.logup_sum(elements);
+        let mut sum = claim.public_data.logup_sum(elements, program);
         sum = &sum + &interaction_claim.opcodes.sum();
         sum = &sum + &interaction_claim.verify_instruction;
         sum = &sum + &interaction_claim.blake_context.sum();
-        sum = &sum + &interaction_claim.builtins;
+        sum = &sum + &interaction_claim.builtins.sum();
+        sum = &sum + &interaction_claim.poseidon_context.sum();
         sum = &sum + &interaction_claim.memory_address_to_id;
         sum = &sum + &interaction_claim.memory_id_to_value.sum();
         sum = &sum + &interaction_claim.range_checks.sum();
@@ -295,13 +511,17 @@ impl CairoFiatShamirResults {
 mod tests {
     use super::*;
     use cairo_air::utils::{deserialize_proof_from_file, ProofFormat};
+    use cairo_air::CairoProof;
+    use cairo_plonk_dsl_hints::CairoVerifierParams;
     use circle_plonk_dsl_constraint_system::{var::AllocVar, ConstraintSystemRef};
+    use circle_plonk_dsl_primitives::compare_transcripts;
     use std::path::PathBuf;
+    use stwo::core::{
+        channel::{Channel, Poseidon31Channel},
+        vcs::poseidon31_merkle::Poseidon31MerkleHasher,
+    };
 
-    #[test]
-    fn test_fiat_shamir() {
-        let cs = ConstraintSystemRef::new();
-
+    fn load_proof() -> CairoProof<Poseidon31MerkleHasher> {
         let manifest_dir = env!("CARGO_MANIFEST_DIR");
         let data_path = PathBuf::from(manifest_dir)
             .parent()
@@ -310,16 +530,100 @@ mod tests {
             .unwrap()
             .join("test_data")
             .join("recursive_proof.bin.bz");
+        deserialize_proof_from_file(&data_path, ProofFormat::Binary).unwrap()
+    }
+
+    /// Load the verifier params from a compiled program, written from the program values alone,
+    /// so that the ids of the program are not taken from the claim.
+    fn load_params(proof: &CairoProof<Poseidon31MerkleHasher>) -> CairoVerifierParams {
+        let path = std::env::temp_dir().join("cairo_fiat_shamir_test_program.json");
+        CairoVerifierParams::from_proof(proof)
+            .write_compiled_program(&path)
+            .unwrap();
+        CairoVerifierParams::load_compiled_program(&path, proof.stark_proof.config).unwrap()
+    }
+
+    #[test]
+    fn test_fiat_shamir() {
+        let cs = ConstraintSystemRef::new();
+        let proof = load_proof();
+
+        let params = CairoVerifierParams::from_proof(&proof);
+        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof, &params);
+        let proof_var = CairoProofVar::new_witness(&cs, &proof);
+        let fiat_shamir_results =
+            CairoFiatShamirResults::compute_with_transcript_log(&fiat_shamir_hints, &proof_var);
+        if let Err(divergence) = compare_transcripts(
+            fiat_shamir_results.transcript.as_ref().unwrap(),
+            &fiat_shamir_hints.transcript(&proof),
+        ) {
+            panic!("{}", divergence);
+        }
+
+        cs.pad();
+        cs.check_arithmetics();
+        cs.populate_logup_arguments();
+        cs.check_poseidon_invocations();
+    }
+
+    #[test]
+    fn test_fiat_shamir_with_program_hash() {
+        let cs = ConstraintSystemRef::new();
+        let proof = load_proof();
 
-        let proof = deserialize_proof_from_file(&data_path, ProofFormat::Binary).unwrap();
+        let mut channel = Poseidon31Channel::default();
+        for (_, value) in proof.claim.public_data.public_memory.program.iter() {
+            channel.mix_u32s(value);
+        }
+        let program_hash = Poseidon31Hash(channel.digest());
 
-        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof);
+        let program_hash_var = Poseidon2HalfVar::new_public_input(&cs, &program_hash);
+        let params = CairoVerifierParams::from_proof(&proof).with_program_hash();
+        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof, &params);
         let proof_var = CairoProofVar::new_witness(&cs, &proof);
-        let _fiat_shamir_results = CairoFiatShamirResults::compute(&fiat_shamir_hints, &proof_var);
+        let fiat_shamir_results = CairoFiatShamirResults::compute_with_program_hash(
+            &fiat_shamir_hints,
+            &proof_var,
+            &program_hash_var,
+        );
+        // the channel operations are only logged on request
+        assert!(fiat_shamir_results.transcript.is_none());
 
         cs.pad();
         cs.check_arithmetics();
         cs.populate_logup_arguments();
         cs.check_poseidon_invocations();
     }
+
+    #[test]
+    fn test_fiat_shamir_with_loaded_program() {
+        let cs = ConstraintSystemRef::new();
+        let proof = load_proof();
+
+        let params = load_params(&proof);
+        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof, &params);
+        let proof_var = CairoProofVar::new_witness(&cs, &proof);
+        let _ = CairoFiatShamirResults::compute(&fiat_shamir_hints, &proof_var);
+
+        cs.pad();
+        cs.check_arithmetics();
+        cs.populate_logup_arguments();
+        cs.check_poseidon_invocations();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fiat_shamir_rejects_program_other_than_params() {
+        let cs = ConstraintSystemRef::new();
+        let proof = load_proof();
+
+        // the hints accept the proof, but the circuit hardcodes a program that differs in one
+        // value, so that the memory lookups of the program no longer balance
+        let params = load_params(&proof);
+        let mut fiat_shamir_hints = CairoFiatShamirHints::new(&proof, &params);
+        fiat_shamir_hints.params.program[0].1[0] ^= 1;
+
+        let proof_var = CairoProofVar::new_witness(&cs, &proof);
+        let _ = CairoFiatShamirResults::compute(&fiat_shamir_hints, &proof_var);
+    }
 }
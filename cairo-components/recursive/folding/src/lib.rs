@@ -10,8 +10,8 @@ use circle_plonk_dsl_constraint_system::{
     ConstraintSystemRef,
 };
 use circle_plonk_dsl_primitives::{
-    option::OptionVar, BitVar, BitsVar, HashVar, M31Var, Poseidon2HalfVar,
-    Poseidon31MerkleHasherVar, QM31Var,
+    option::OptionVar, BitVar, BitsVar, HashVar, LinePolyVar, M31Var, PointCarryingQueryVar,
+    Poseidon2HalfVar, Poseidon31MerkleHasherVar, QM31Var,
 };
 use indexmap::IndexMap;
 use num_traits::Zero;
@@ -383,6 +383,11 @@ impl FoldingResults {
     ) {
         let cs = fiat_shamir_results.max_log_size.cs();
         let log_blowup_factor = fiat_shamir_hints.pcs_config.fri_config.log_blowup_factor;
+        let log_last_layer_degree_bound = fiat_shamir_hints
+            .pcs_config
+            .fri_config
+            .log_last_layer_degree_bound;
+        let last_layer_poly = &proof_var.stark_proof.fri_proof.last_layer_poly;
 
         for (i, proof) in folding_hints
             .first_layer_hints
@@ -437,7 +442,10 @@ impl FoldingResults {
                 let self_var = &proof_column.value.0;
                 let sibling_var = &proof_column.value.1;
 
-                let bit = padded_query_bits.lsb.get(&h).unwrap();
+                let bit = padded_query_bits
+                    .lsb
+                    .get(&(h + log_blowup_factor - 1))
+                    .unwrap();
 
                 let point = &answer_results
                     .query_positions_var
@@ -480,7 +488,10 @@ impl FoldingResults {
                         &cs,
                         &M31::from(MAX_SEQUENCE_LOG_SIZE),
                     ));
-            for h in (log_blowup_factor + 1..MAX_SEQUENCE_LOG_SIZE + log_blowup_factor).rev() {
+            for h in (log_last_layer_degree_bound + log_blowup_factor + 1
+                ..MAX_SEQUENCE_LOG_SIZE + log_blowup_factor)
+                .rev()
+            {
                 let proof = if folding_hints
                     .inner_layers_hints
                     .merkle_proofs
@@ -513,9 +524,11 @@ impl FoldingResults {
 
                 (&verify_result | &is_layer_present.neg()).equalverify(&BitVar::new_true(&cs));
 
-                if f_primes.contains_key(&h) {
-                    let folded_into = f_primes.get(&h).unwrap();
-                    let alpha = alphas.get(&h).unwrap();
+                // the columns folded into this layer have a degree bound one above the layer
+                let column_log_size = h - log_blowup_factor + 1;
+                if f_primes.contains_key(&column_log_size) {
+                    let folded_into = f_primes.get(&column_log_size).unwrap();
+                    let alpha = alphas.get(&column_log_size).unwrap();
 
                     let new_folded = &(&folded * &(alpha * alpha)) + &folded_into.value;
                     folded = QM31Var::select(&folded, &new_folded, &folded_into.is_some);
@@ -524,16 +537,9 @@ impl FoldingResults {
                 let expected = QM31Var::select(&proof.column.0, &folded, &is_layer_present);
                 expected.equalverify(&proof.column.0);
 
-                let bit = padded_query_bits
-                    .lsb
-                    .get(&(h - 2 + log_blowup_factor))
-                    .unwrap();
+                let bit = padded_query_bits.lsb.get(&(h - 1)).unwrap();
 
-                let point = &answer_results
-                    .query_positions_var
-                    .points
-                    .get(&(h - 1 + log_blowup_factor))
-                    .unwrap()[i]
+                let point = &answer_results.query_positions_var.points.get(&h).unwrap()[i]
                     .get_absolute_point();
 
                 let x_inv = point.x.inv();
@@ -542,12 +548,13 @@ impl FoldingResults {
                 let new_left_var = &left_var + &right_var;
                 let new_right_var = &(&left_var - &right_var) * &x_inv;
 
-                let alpha = if alphas.contains_key(&(h - 1)) {
-                    alphas.get(&(h - 1)).unwrap()
+                let layer_log_size = h - log_blowup_factor;
+                let alpha = if alphas.contains_key(&layer_log_size) {
+                    alphas.get(&layer_log_size).unwrap()
                 } else {
                     &fiat_shamir_results
                         .inner_layers_alphas
-                        .get(&(h - 1))
+                        .get(&layer_log_size)
                         .unwrap()
                         .value
                 };
@@ -558,23 +565,53 @@ impl FoldingResults {
                 is_layer_present = &is_layer_present
                     | &fiat_shamir_results
                         .max_log_size
-                        .is_eq(&M31Var::new_constant(&cs, &M31::from(h - 1)));
+                        .is_eq(&M31Var::new_constant(&cs, &M31::from(layer_log_size)));
             }
 
-            folded.equalverify(&proof_var.stark_proof.fri_proof.last_layer_constant);
+            verify_last_layer(
+                &folded,
+                last_layer_poly,
+                &answer_results
+                    .query_positions_var
+                    .points
+                    .get(&(log_last_layer_degree_bound + log_blowup_factor + 1))
+                    .unwrap()[i],
+            );
         }
     }
 }
 
+/// Check the folded value of a query against the last layer polynomial, where `query` is the
+/// query on the last inner layer. The folded value sits on the last layer domain, which is one
+/// level below, so it is evaluated at the next point of the query.
+pub fn verify_last_layer(
+    folded: &QM31Var,
+    last_layer_poly: &LinePolyVar,
+    query: &PointCarryingQueryVar,
+) {
+    if last_layer_poly.coeffs.len() == 1 {
+        folded.equalverify(&last_layer_poly.coeffs[0]);
+    } else {
+        folded.equalverify(&last_layer_poly.eval_at_point(&query.get_next_point_x()));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use cairo_air::utils::{deserialize_proof_from_file, ProofFormat};
     use cairo_plonk_dsl_answer::AnswerResults;
     use cairo_plonk_dsl_decommitment::CairoDecommitmentResultsVar;
-    use cairo_plonk_dsl_hints::{AnswerHints, CairoDecommitmentHints, CairoFiatShamirHints};
+    use cairo_plonk_dsl_hints::{
+        AnswerHints, CairoDecommitmentHints, CairoFiatShamirHints, CairoVerifierParams,
+    };
     use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
     use std::path::PathBuf;
+    use stwo::core::circle::Coset;
+    use stwo::core::poly::line::LinePoly;
+    use stwo::core::utils::bit_reverse_index;
 
     #[test]
     fn test_folding_results() {
@@ -591,7 +628,8 @@ mod tests {
 
         let proof = deserialize_proof_from_file(&data_path, ProofFormat::Binary).unwrap();
 
-        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof);
+        let params = CairoVerifierParams::from_proof(&proof);
+        let fiat_shamir_hints = CairoFiatShamirHints::new(&proof, &params);
         let proof_var = CairoProofVar::new_witness(&cs, &proof);
         let answer_hints = AnswerHints::new(&fiat_shamir_hints, &proof);
         let decommitment_hints = CairoDecommitmentHints::new(&fiat_shamir_hints, &proof);
@@ -623,4 +661,42 @@ mod tests {
         cs.populate_logup_arguments();
         cs.check_poseidon_invocations();
     }
+
+    #[test]
+    fn test_last_layer_of_higher_degree() {
+        // unlike the test data, a last layer of degree 2^3 with a blowup factor of 2^2
+        let log_last_layer_degree_bound = 3;
+        let log_blowup_factor = 2;
+        let log_size = log_last_layer_degree_bound + log_blowup_factor;
+
+        let mut prng = StdRng::seed_from_u64(0);
+        let last_layer_poly = LinePoly::new(
+            (0..1 << log_last_layer_degree_bound)
+                .map(|_| prng.gen())
+                .collect(),
+        );
+        let domain = Coset::half_odds(log_size);
+
+        for _ in 0..8 {
+            // a query on the last inner layer, folded into the last layer domain as the native
+            // FRI verifier does
+            let query = prng.gen_range(0..1 << (log_size + 1));
+            let x = domain.at(bit_reverse_index(query >> 1, log_size)).x;
+            let folded = last_layer_poly.eval_at_point(x.into());
+
+            let cs = ConstraintSystemRef::new();
+            let query_var = PointCarryingQueryVar::new(BitsVar::from_m31(
+                &M31Var::new_witness(&cs, &M31::from(query as u32)),
+                (log_size + 1) as usize,
+            ));
+            verify_last_layer(
+                &QM31Var::new_witness(&cs, &folded),
+                &LinePolyVar::new_witness(&cs, &last_layer_poly),
+                &query_var,
+            );
+
+            cs.pad();
+            cs.check_arithmetics();
+        }
+    }
 }
@@ -1,35 +1,39 @@
 use crate::FiatShamirHints;
+use circle_plonk_dsl_primitives::merkle::{
+    merkle_multi_proof_positions, Poseidon31MerkleMultiProof,
+};
 use std::collections::{BTreeMap, BTreeSet, HashMap};
+use stwo::core::channel::MerkleChannel;
 use stwo::core::fields::m31::{BaseField, M31};
 use stwo::core::vcs::poseidon31_hash::Poseidon31Hash;
-use stwo::core::vcs::poseidon31_merkle::{Poseidon31MerkleChannel, Poseidon31MerkleHasher};
+use stwo::core::vcs::poseidon31_merkle::Poseidon31MerkleHasher;
 use stwo::core::vcs::verifier::MerkleDecommitment;
 use stwo::core::vcs::MerkleHasher;
 use stwo_examples::plonk_with_poseidon::air::PlonkWithPoseidonProof;
 
 #[derive(Clone, Debug)]
-pub struct SinglePathMerkleProof {
+pub struct SinglePathMerkleProof<H: MerkleHasher = Poseidon31MerkleHasher> {
     pub query: usize,
 
-    pub sibling_hashes: Vec<Poseidon31Hash>,
+    pub sibling_hashes: Vec<H::Hash>,
     pub columns: BTreeMap<usize, Vec<M31>>,
 
-    pub root: Poseidon31Hash,
+    pub root: H::Hash,
     pub depth: usize,
 }
 
-impl SinglePathMerkleProof {
+impl<H: MerkleHasher> SinglePathMerkleProof<H> {
     pub fn verify(&self) {
         let leaf = self
             .columns
             .get(&self.depth)
             .map_or(&[][..], |v| v.as_slice());
-        let mut cur_hash = Poseidon31MerkleHasher::hash_node(None, leaf);
+        let mut cur_hash = H::hash_node(None, leaf);
 
         for i in 0..self.depth {
             let h = self.depth - i - 1;
 
-            cur_hash = Poseidon31MerkleHasher::hash_node(
+            cur_hash = H::hash_node(
                 if (self.query >> i) & 1 == 0 {
                     Some((cur_hash, self.sibling_hashes[i]))
                 } else {
@@ -46,10 +50,10 @@ impl SinglePathMerkleProof {
         max_log_size: u32,
         raw_queries: &[usize],
         values: &[BaseField],
-        root: Poseidon31Hash,
+        root: H::Hash,
         n_columns_per_log_size: &BTreeMap<u32, usize>,
-        merkle_decommitment: &MerkleDecommitment<Poseidon31MerkleHasher>,
-    ) -> Vec<SinglePathMerkleProof> {
+        merkle_decommitment: &MerkleDecommitment<H>,
+    ) -> Vec<SinglePathMerkleProof<H>> {
         // find out all the queried positions and sort them
         let mut queries = raw_queries.to_vec();
         queries.sort_unstable();
@@ -75,12 +79,12 @@ impl SinglePathMerkleProof {
         let mut hash_iterator = merkle_decommitment.hash_witness.iter();
 
         // create the merkle partial tree
-        let mut hash_layers: Vec<HashMap<usize, Poseidon31Hash>> = vec![];
+        let mut hash_layers: Vec<HashMap<usize, H::Hash>> = vec![];
 
         // create the leaf layer
         let mut hash_layer = HashMap::new();
         for (&query, value) in queries_values_map.iter() {
-            hash_layer.insert(query, Poseidon31MerkleHasher::hash_node(None, value));
+            hash_layer.insert(query, H::hash_node(None, value));
         }
         hash_layers.push(hash_layer);
 
@@ -117,7 +121,7 @@ impl SinglePathMerkleProof {
                         } else {
                             (sibling, hash_layers[i].get(&position).unwrap())
                         };
-                        Poseidon31MerkleHasher::hash_node(Some((*left, *right)), &columns)
+                        H::hash_node(Some((*left, *right)), &columns)
                     } else {
                         let sibling = hash_iterator.next().unwrap();
                         hash_layers[i].insert(sibling_idx, *sibling);
@@ -126,7 +130,7 @@ impl SinglePathMerkleProof {
                         } else {
                             (sibling, hash_layers[i].get(&position).unwrap())
                         };
-                        Poseidon31MerkleHasher::hash_node(Some((*left, *right)), &columns)
+                        H::hash_node(Some((*left, *right)), &columns)
                     };
 
                     e.insert(hash);
@@ -184,18 +188,234 @@ impl SinglePathMerkleProof {
     }
 }
 
+/// A batched Merkle decommitment for several queries into the same Poseidon31 tree, where the
+/// queries can be at any layer of the tree.
+///
+/// It is built by walking the tree layer by layer from the leaves, in the same way as the Merkle
+/// verifier of stwo, so that it consumes exactly the witness of a stwo `MerkleDecommitment`. The
+/// walk records every node that it visits, from which the path of each query can be read.
+#[derive(Clone, Debug)]
+pub struct MerkleMultiProof {
+    /// The queried positions at each log size, sorted and deduplicated.
+    pub queries_per_log_size: BTreeMap<u32, Vec<usize>>,
+
+    /// The visited nodes, indexed first by the height of the layer and then by the node position.
+    /// The nodes that are not computed by the walk are the hashes of the decommitment witness.
+    pub nodes: BTreeMap<usize, BTreeMap<usize, Poseidon31Hash>>,
+    /// The column values, indexed first by the height of the layer and then by the node position.
+    pub columns: BTreeMap<usize, BTreeMap<usize, Vec<M31>>>,
+
+    pub root: Poseidon31Hash,
+    pub depth: usize,
+}
+
+/// The Merkle path of a single query of a [`MerkleMultiProof`], from the queried node to the root.
+#[derive(Clone, Debug)]
+pub struct MerkleMultiProofPath {
+    pub log_size: u32,
+    pub position: usize,
+    /// The children of the queried node, which are absent when the node is a leaf.
+    pub children: Option<(Poseidon31Hash, Poseidon31Hash)>,
+    /// The sibling hashes, ordered from the queried layer to the root.
+    pub sibling_hashes: Vec<Poseidon31Hash>,
+    /// The column values of the nodes on the path, indexed by the height of the layer.
+    pub columns: BTreeMap<usize, Vec<M31>>,
+}
+
+impl MerkleMultiProof {
+    pub fn verify(&self) {
+        let positions = merkle_multi_proof_positions(self.depth, &self.queries_per_log_size);
+        for (h, positions) in positions.into_iter().enumerate() {
+            for position in positions {
+                let children = (h < self.depth).then(|| {
+                    (
+                        self.nodes[&(h + 1)][&(2 * position)],
+                        self.nodes[&(h + 1)][&(2 * position + 1)],
+                    )
+                });
+                let columns = self
+                    .columns
+                    .get(&h)
+                    .and_then(|layer| layer.get(&position))
+                    .map_or(&[][..], |v| v.as_slice());
+                assert_eq!(
+                    Poseidon31MerkleHasher::hash_node(children, columns),
+                    self.nodes[&h][&position]
+                );
+            }
+        }
+        assert_eq!(self.nodes[&0][&0], self.root);
+    }
+
+    /// Return the Merkle path of the query at `position` of the layer at `log_size`.
+    pub fn path(&self, log_size: u32, position: usize) -> MerkleMultiProofPath {
+        let h = log_size as usize;
+        assert!(self.queries_per_log_size[&log_size].contains(&position));
+
+        let children = (h < self.depth).then(|| {
+            (
+                self.nodes[&(h + 1)][&(2 * position)],
+                self.nodes[&(h + 1)][&(2 * position + 1)],
+            )
+        });
+
+        let mut sibling_hashes = vec![];
+        let mut columns = BTreeMap::new();
+        let mut cur = position;
+        for k in (0..=h).rev() {
+            if let Some(values) = self.columns.get(&k).and_then(|layer| layer.get(&cur)) {
+                columns.insert(k, values.clone());
+            }
+            if k > 0 {
+                sibling_hashes.push(self.nodes[&k][&(cur ^ 1)]);
+                cur >>= 1;
+            }
+        }
+
+        MerkleMultiProofPath {
+            log_size,
+            position,
+            children,
+            sibling_hashes,
+            columns,
+        }
+    }
+
+    /// Return the witness of the in-circuit multi-proof, which only keeps the hashes of the
+    /// children that the walk does not compute.
+    pub fn to_witness(&self) -> Poseidon31MerkleMultiProof {
+        let positions = merkle_multi_proof_positions(self.depth, &self.queries_per_log_size);
+
+        let mut witness = vec![];
+        for h in (0..self.depth).rev() {
+            let below = &positions[h + 1];
+            for &position in positions[h].iter() {
+                for child in [2 * position, 2 * position + 1] {
+                    if below.binary_search(&child).is_err() {
+                        witness.push(self.nodes[&(h + 1)][&child]);
+                    }
+                }
+            }
+        }
+
+        let res = Poseidon31MerkleMultiProof {
+            depth: self.depth,
+            queries_per_log_size: self.queries_per_log_size.clone(),
+            witness,
+            columns: self.columns.clone(),
+        };
+        assert_eq!(res.compute_root(), self.root);
+        res
+    }
+
+    pub fn from_stwo_proof(
+        queries_per_log_size: &BTreeMap<u32, Vec<usize>>,
+        values: &[BaseField],
+        root: Poseidon31Hash,
+        n_columns_per_log_size: &BTreeMap<u32, usize>,
+        merkle_decommitment: &MerkleDecommitment<Poseidon31MerkleHasher>,
+    ) -> MerkleMultiProof {
+        let depth = *n_columns_per_log_size.keys().max().unwrap() as usize;
+
+        let queries_per_log_size = queries_per_log_size
+            .iter()
+            .filter(|(log_size, _)| **log_size as usize <= depth)
+            .map(|(&log_size, queries)| {
+                let mut queries = queries.clone();
+                queries.sort_unstable();
+                queries.dedup();
+                (log_size, queries)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let mut value_iterator = values.iter();
+        let mut hash_iterator = merkle_decommitment.hash_witness.iter();
+        let mut column_witness_iterator = merkle_decommitment.column_witness.iter();
+
+        let mut nodes: BTreeMap<usize, BTreeMap<usize, Poseidon31Hash>> = BTreeMap::new();
+        let mut columns = BTreeMap::new();
+
+        let mut positions: Vec<usize> = vec![];
+        for h in (0..=depth).rev() {
+            let queries = queries_per_log_size
+                .get(&(h as u32))
+                .map_or(&[][..], |v| v.as_slice());
+            positions = positions
+                .iter()
+                .map(|p| p >> 1)
+                .chain(queries.iter().copied())
+                .collect();
+            positions.sort_unstable();
+            positions.dedup();
+
+            let num_columns = n_columns_per_log_size
+                .get(&(h as u32))
+                .copied()
+                .unwrap_or_default();
+
+            let mut layer = BTreeMap::new();
+            let mut column_layer = BTreeMap::new();
+            for &position in positions.iter() {
+                // the children that are not computed at the layer below are read from the witness,
+                // the left one first
+                let children = nodes.get_mut(&(h + 1)).map(|child_layer| {
+                    let mut child = |idx: usize| {
+                        *child_layer
+                            .entry(idx)
+                            .or_insert_with(|| *hash_iterator.next().unwrap())
+                    };
+                    (child(2 * position), child(2 * position + 1))
+                });
+
+                // the column values of the queried nodes are in the queried values, and the others
+                // are in the column witness
+                let value_source = if queries.binary_search(&position).is_ok() {
+                    &mut value_iterator
+                } else {
+                    &mut column_witness_iterator
+                };
+                let v = value_source.take(num_columns).copied().collect::<Vec<_>>();
+                assert_eq!(v.len(), num_columns);
+
+                layer.insert(position, Poseidon31MerkleHasher::hash_node(children, &v));
+                if num_columns > 0 {
+                    column_layer.insert(position, v);
+                }
+            }
+            nodes.insert(h, layer);
+            if num_columns > 0 {
+                columns.insert(h, column_layer);
+            }
+        }
+
+        assert_eq!(hash_iterator.next(), None);
+        assert_eq!(value_iterator.next(), None);
+        assert_eq!(column_witness_iterator.next(), None);
+
+        let res = MerkleMultiProof {
+            queries_per_log_size,
+            nodes,
+            columns,
+            root,
+            depth,
+        };
+        res.verify();
+        res
+    }
+}
+
 #[derive(Debug, Clone)]
-pub struct DecommitHints {
-    pub precomputed_proofs: Vec<SinglePathMerkleProof>,
-    pub trace_proofs: Vec<SinglePathMerkleProof>,
-    pub interaction_proofs: Vec<SinglePathMerkleProof>,
-    pub composition_proofs: Vec<SinglePathMerkleProof>,
+pub struct DecommitHints<H: MerkleHasher = Poseidon31MerkleHasher> {
+    pub precomputed_proofs: Vec<SinglePathMerkleProof<H>>,
+    pub trace_proofs: Vec<SinglePathMerkleProof<H>>,
+    pub interaction_proofs: Vec<SinglePathMerkleProof<H>>,
+    pub composition_proofs: Vec<SinglePathMerkleProof<H>>,
 }
 
-impl DecommitHints {
-    pub fn compute(
-        fiat_shamir_hints: &FiatShamirHints<Poseidon31MerkleChannel>,
-        proof: &PlonkWithPoseidonProof<Poseidon31MerkleHasher>,
+impl<H: MerkleHasher> DecommitHints<H> {
+    pub fn compute<MC: MerkleChannel<H = H>>(
+        fiat_shamir_hints: &FiatShamirHints<MC>,
+        proof: &PlonkWithPoseidonProof<H>,
     ) -> Self {
         let mut precomputed_proofs = vec![];
         let mut trace_proofs = vec![];
@@ -244,9 +464,16 @@ impl DecommitHints {
 
 #[cfg(test)]
 mod test {
-    use crate::{DecommitHints, FiatShamirHints};
+    use crate::{AnswerHints, DecommitHints, FiatShamirHints, FirstLayerHints, MerkleMultiProof};
+    use circle_plonk_dsl_constraint_system::var::AllocVar;
+    use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
+    use circle_plonk_dsl_primitives::{
+        BitsVar, M31Var, Poseidon2HalfVar, Poseidon31MerkleHasherVar, Poseidon31MerkleMultiProofVar,
+    };
     use num_traits::One;
-    use stwo::core::fields::qm31::QM31;
+    use std::collections::BTreeMap;
+    use stwo::core::fields::m31::M31;
+    use stwo::core::fields::qm31::{QM31, SECURE_EXTENSION_DEGREE};
     use stwo::core::fri::FriConfig;
     use stwo::core::pcs::PcsConfig;
     use stwo::core::vcs::poseidon31_merkle::{Poseidon31MerkleChannel, Poseidon31MerkleHasher};
@@ -273,4 +500,203 @@ mod test {
             FiatShamirHints::<Poseidon31MerkleChannel>::new(&proof, config, &[(1, QM31::one())]);
         let _ = DecommitHints::compute(&fiat_shamir_hints, &proof);
     }
+
+    #[test]
+    fn test_merkle_multi_proof() {
+        let proof: PlonkWithPoseidonProof<Poseidon31MerkleHasher> =
+            bincode::deserialize(include_bytes!("../../test_data/small_proof.bin")).unwrap();
+        let config = PcsConfig {
+            pow_bits: 20,
+            fri_config: FriConfig::new(2, 5, 16),
+        };
+
+        let fiat_shamir_hints =
+            FiatShamirHints::<Poseidon31MerkleChannel>::new(&proof, config, &[(1, QM31::one())]);
+
+        // the queries are at every log size of the columns, as in the stwo verifier
+        for i in 0..4 {
+            let multi_proof = MerkleMultiProof::from_stwo_proof(
+                &fiat_shamir_hints.sorted_query_positions_per_log_size,
+                &proof.stark_proof.queried_values[i],
+                proof.stark_proof.commitments[i],
+                &fiat_shamir_hints.n_columns_per_log_size[i],
+                &proof.stark_proof.decommitments[i],
+            );
+            multi_proof.verify();
+
+            for (&log_size, queries) in multi_proof.queries_per_log_size.iter() {
+                for &query in queries.iter() {
+                    let path = multi_proof.path(log_size, query);
+                    assert_eq!(path.sibling_hashes.len(), log_size as usize);
+                    assert_eq!(
+                        path.children.is_some(),
+                        log_size as usize != multi_proof.depth
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_merkle_multi_proof_var() {
+        let proof: PlonkWithPoseidonProof<Poseidon31MerkleHasher> =
+            bincode::deserialize(include_bytes!("../../test_data/small_proof.bin")).unwrap();
+        let config = PcsConfig {
+            pow_bits: 20,
+            fri_config: FriConfig::new(2, 5, 16),
+        };
+
+        let fiat_shamir_hints =
+            FiatShamirHints::<Poseidon31MerkleChannel>::new(&proof, config, &[(1, QM31::one())]);
+        let answer_hints = AnswerHints::compute(&fiat_shamir_hints, &proof);
+        let first_layer_hints = FirstLayerHints::compute(&fiat_shamir_hints, &answer_hints, &proof);
+
+        // the trace tree is queried at every log size of its columns, and the first FRI layer
+        // at the log size of each of its columns, with the decommitted subsets of the queries
+        let mut first_layer_n_columns_per_log_size = BTreeMap::new();
+        for column_domain in fiat_shamir_hints
+            .fri_verifier
+            .first_layer
+            .column_commitment_domains
+            .iter()
+        {
+            *first_layer_n_columns_per_log_size
+                .entry(column_domain.log_size())
+                .or_insert(0) += SECURE_EXTENSION_DEGREE;
+        }
+        let multi_proofs = [
+            MerkleMultiProof::from_stwo_proof(
+                &fiat_shamir_hints.sorted_query_positions_per_log_size,
+                &proof.stark_proof.queried_values[1],
+                proof.stark_proof.commitments[1],
+                &fiat_shamir_hints.n_columns_per_log_size[1],
+                &proof.stark_proof.decommitments[1],
+            ),
+            MerkleMultiProof::from_stwo_proof(
+                &first_layer_hints.decommitment_positions_by_log_size,
+                &first_layer_hints.decommitmented_values,
+                proof.stark_proof.fri_proof.first_layer.commitment,
+                &first_layer_n_columns_per_log_size,
+                &proof.stark_proof.fri_proof.first_layer.decommitment,
+            ),
+        ];
+
+        for multi_proof in multi_proofs.iter() {
+            let alloc_queries = |cs: &ConstraintSystemRef| {
+                let root = Poseidon2HalfVar::new_witness(cs, &multi_proof.root);
+                let queries = multi_proof
+                    .queries_per_log_size
+                    .iter()
+                    .map(|(&log_size, positions)| {
+                        let bits = positions
+                            .iter()
+                            .map(|&position| {
+                                let position = M31Var::new_witness(cs, &M31::from(position));
+                                BitsVar::from_m31(&position, log_size as usize)
+                            })
+                            .collect::<Vec<_>>();
+                        (log_size, bits)
+                    })
+                    .collect::<BTreeMap<_, _>>();
+                (root, queries)
+            };
+
+            // the multi-proof hashes every node of the walk once
+            let cs = ConstraintSystemRef::new();
+            let (root, queries) = alloc_queries(&cs);
+            let (rows, invocations) = (cs.num_plonk_rows(), cs.num_poseidon_invocations());
+            let proof_var =
+                Poseidon31MerkleMultiProofVar::new_witness(&cs, &multi_proof.to_witness());
+            proof_var.verify(&root, &queries);
+            let multi_cost = (
+                cs.num_plonk_rows() - rows,
+                cs.num_poseidon_invocations() - invocations,
+            );
+
+            cs.pad();
+            cs.check_arithmetics();
+            cs.populate_logup_arguments();
+            cs.check_poseidon_invocations();
+
+            // the same queries verified as independent paths
+            let cs = ConstraintSystemRef::new();
+            let (root, queries) = alloc_queries(&cs);
+            let (rows, invocations) = (cs.num_plonk_rows(), cs.num_poseidon_invocations());
+            for (&log_size, bits) in queries.iter() {
+                let positions = &multi_proof.queries_per_log_size[&log_size];
+                for (&position, query) in positions.iter().zip(bits.iter()) {
+                    let path = multi_proof.path(log_size, position);
+                    let h = log_size as usize;
+                    let columns = path
+                        .columns
+                        .iter()
+                        .map(|(&k, values)| {
+                            let values = values
+                                .iter()
+                                .map(|v| M31Var::new_witness(&cs, v))
+                                .collect::<Vec<_>>();
+                            (k, values)
+                        })
+                        .collect::<BTreeMap<_, _>>();
+                    let column_values =
+                        |k: usize| columns.get(&k).map_or(&[][..], |v| v.as_slice());
+
+                    let mut cur = match path.children {
+                        Some((left, right)) => {
+                            let left = Poseidon2HalfVar::new_single_use_witness_only(&cs, &left.0);
+                            let right =
+                                Poseidon2HalfVar::new_single_use_witness_only(&cs, &right.0);
+                            if column_values(h).is_empty() {
+                                Poseidon31MerkleHasherVar::hash_tree(&left, &right)
+                            } else {
+                                let column_hash =
+                                    Poseidon31MerkleHasherVar::hash_m31_columns_get_capacity(
+                                        column_values(h),
+                                    );
+                                Poseidon31MerkleHasherVar::hash_tree_with_column(
+                                    &left,
+                                    &right,
+                                    &column_hash,
+                                )
+                            }
+                        }
+                        None => {
+                            Poseidon31MerkleHasherVar::hash_m31_columns_get_rate(column_values(h))
+                        }
+                    };
+                    for (i, sibling) in path.sibling_hashes.iter().enumerate() {
+                        let sibling =
+                            Poseidon2HalfVar::new_single_use_witness_only(&cs, &sibling.0);
+                        let k = h - i - 1;
+                        cur = if column_values(k).is_empty() {
+                            Poseidon31MerkleHasherVar::hash_tree_with_swap(
+                                &cur,
+                                &sibling,
+                                &query.0[i],
+                            )
+                        } else {
+                            let column_hash =
+                                Poseidon31MerkleHasherVar::hash_m31_columns_get_capacity(
+                                    column_values(k),
+                                );
+                            Poseidon31MerkleHasherVar::hash_tree_with_column_hash_with_swap(
+                                &cur,
+                                &sibling,
+                                &query.0[i],
+                                &column_hash,
+                            )
+                        };
+                    }
+                    cur.equalverify(&root);
+                }
+            }
+            let single_cost = (
+                cs.num_plonk_rows() - rows,
+                cs.num_poseidon_invocations() - invocations,
+            );
+
+            assert!(multi_cost.0 < single_cost.0);
+            assert!(multi_cost.1 < single_cost.1);
+        }
+    }
 }
@@ -1,3 +1,4 @@
+use circle_plonk_dsl_primitives::{LoggedPoseidon31Channel, TranscriptLog};
 use itertools::Itertools;
 use num_traits::{One, Zero};
 use std::collections::{BTreeMap, BTreeSet};
@@ -11,6 +12,7 @@ use stwo::core::fields::qm31::{SecureField, QM31};
 use stwo::core::fields::{Field, FieldExpOps};
 use stwo::core::fri::{CirclePolyDegreeBound, FriVerifier};
 use stwo::core::pcs::{CommitmentSchemeVerifier, PcsConfig, TreeSubspan, TreeVec};
+use stwo::core::vcs::poseidon31_merkle::{Poseidon31MerkleChannel, Poseidon31MerkleHasher};
 use stwo::core::vcs::MerkleHasher;
 use stwo::core::ColumnVec;
 use stwo_constraint_framework::{Relation, PREPROCESSED_TRACE_IDX};
@@ -31,6 +33,7 @@ pub struct FiatShamirHints<MC: MerkleChannel> {
     pub poseidon_total_sum: SecureField,
     pub alpha: SecureField,
     pub z: SecureField,
+    pub lookup_elements: PlonkWithAcceleratorLookupElements,
     pub random_coeff: SecureField,
     pub after_sampled_values_random_coeff: SecureField,
     pub oods_t: SecureField,
@@ -48,6 +51,8 @@ pub struct FiatShamirHints<MC: MerkleChannel> {
     pub n_columns_per_log_size: TreeVec<BTreeMap<u32, usize>>,
     pub trees_log_sizes: TreeVec<Vec<u32>>,
 
+    /// The PCS configuration that the hints have been computed with.
+    pub config: PcsConfig,
     pub log_blowup_factor: u32,
 
     pub plonk_tree_subspan: Vec<TreeSubspan>,
@@ -276,6 +281,7 @@ impl<MC: MerkleChannel> FiatShamirHints<MC> {
             poseidon_total_sum: proof.stmt1.poseidon_total_sum,
             alpha: lookup_elements.0.alpha,
             z: lookup_elements.0.z,
+            lookup_elements: lookup_elements.clone(),
             random_coeff,
             after_sampled_values_random_coeff,
             oods_t,
@@ -292,6 +298,7 @@ impl<MC: MerkleChannel> FiatShamirHints<MC> {
             column_log_sizes,
             n_columns_per_log_size,
             trees_log_sizes,
+            config,
             log_blowup_factor: config.fri_config.log_blowup_factor,
 
             plonk_tree_subspan,
@@ -306,3 +313,60 @@ impl<MC: MerkleChannel> FiatShamirHints<MC> {
         }
     }
 }
+
+impl FiatShamirHints<Poseidon31MerkleChannel> {
+    /// Replay the channel operations of the verifier circuit on a logged native channel, so that
+    /// the transcript can be compared with the one of `ChannelVar` when a proof fails to verify.
+    pub fn transcript(
+        &self,
+        proof: &PlonkWithPoseidonProof<Poseidon31MerkleHasher>,
+    ) -> TranscriptLog {
+        let mut channel = LoggedPoseidon31Channel::default();
+
+        // Preprocessed trace.
+        channel.mix_root(self.preprocessed_commitment);
+
+        // Trace.
+        channel.mix_felts(&[QM31::from(BaseField::from(self.log_size_plonk))]);
+        channel.mix_felts(&[QM31::from(BaseField::from(self.log_size_poseidon))]);
+        channel.mix_root(self.trace_commitment);
+
+        // Draw interaction elements.
+        assert_eq!(channel.draw_secure_felt(), self.z);
+
+        // Interaction trace.
+        channel.mix_felts(&[self.plonk_total_sum, self.poseidon_total_sum]);
+        channel.mix_root(self.interaction_commitment);
+
+        assert_eq!(channel.draw_secure_felt(), self.random_coeff);
+
+        // Read composition polynomial commitment.
+        channel.mix_root(self.composition_commitment);
+
+        // Draw OODS point.
+        assert_eq!(channel.draw_secure_felt(), self.oods_t);
+
+        channel.mix_felts(&proof.stark_proof.sampled_values.clone().flatten_cols());
+        assert_eq!(
+            channel.draw_secure_felt(),
+            self.after_sampled_values_random_coeff
+        );
+
+        // FRI layers commitments and alphas
+        let layer_commitments =
+            std::iter::once(&self.first_layer_commitment).chain(&self.inner_layer_commitments);
+        for (commitment, fri_alpha) in layer_commitments.zip_eq(self.fri_alphas.iter()) {
+            channel.mix_root(*commitment);
+            assert_eq!(channel.draw_secure_felt(), *fri_alpha);
+        }
+        channel.mix_felts(&self.last_layer_coeffs);
+
+        channel.mix_u64(proof.stark_proof.proof_of_work);
+
+        for _ in 0..self.config.fri_config.n_queries.div_ceil(4) {
+            let _ = channel.draw_u32s();
+        }
+
+        channel.log
+    }
+}
@@ -2,6 +2,7 @@ use crate::{AnswerHints, FiatShamirHints};
 use itertools::{zip_eq, Itertools};
 use num_traits::Zero;
 use std::collections::{BTreeMap, BTreeSet, HashMap};
+use stwo::core::channel::MerkleChannel;
 use stwo::core::circle::{CirclePoint, Coset};
 use stwo::core::fields::m31::M31;
 use stwo::core::fields::qm31::SECURE_EXTENSION_DEGREE;
@@ -9,94 +10,68 @@ use stwo::core::fields::qm31::{SecureField, QM31};
 use stwo::core::fields::{ExtensionOf, Field, FieldExpOps};
 use stwo::core::fri::SparseEvaluation;
 use stwo::core::utils::bit_reverse_index;
-use stwo::core::vcs::poseidon31_hash::Poseidon31Hash;
-use stwo::core::vcs::poseidon31_merkle::{Poseidon31MerkleChannel, Poseidon31MerkleHasher};
-use stwo::core::vcs::poseidon31_ref::Poseidon31CRH;
+use stwo::core::vcs::poseidon31_merkle::Poseidon31MerkleHasher;
 use stwo::core::vcs::verifier::MerkleDecommitment;
 use stwo::core::vcs::verifier::MerkleVerifier;
 use stwo::core::vcs::MerkleHasher;
 use stwo_examples::plonk_with_poseidon::air::PlonkWithPoseidonProof;
 
+/// The Merkle paths of a query and of its sibling in a FRI layer tree, which meet at their parent.
 #[derive(Clone)]
-pub struct SinglePairMerkleProof {
+pub struct SinglePairMerkleProof<H: MerkleHasher = Poseidon31MerkleHasher> {
     pub query: usize,
 
-    pub sibling_hashes: Vec<Poseidon31Hash>,
+    /// The hashes of the siblings on the path, from the leaves, at the layers without columns.
+    pub sibling_hashes: Vec<H::Hash>,
+    /// The children of the sibling at the layers with columns, other than the leaves, from which
+    /// the sibling is recomputed along with its column.
+    pub sibling_children: BTreeMap<usize, (H::Hash, H::Hash)>,
     pub self_columns: BTreeMap<usize, QM31>,
     pub siblings_columns: BTreeMap<usize, QM31>,
 
-    pub root: Poseidon31Hash,
+    pub root: H::Hash,
     pub depth: usize,
 }
 
-impl SinglePairMerkleProof {
+impl<H: MerkleHasher> SinglePairMerkleProof<H> {
     pub fn verify(&self) {
-        let mut self_hash = Poseidon31MerkleHasher::hash_node(
-            None,
-            &self
-                .self_columns
-                .get(&self.depth)
-                .map_or(vec![], |v| v.to_m31_array().to_vec()),
-        );
-        let mut sibling_hash = Poseidon31MerkleHasher::hash_node(
-            None,
-            &self
-                .siblings_columns
-                .get(&self.depth)
-                .map_or(vec![], |v| v.to_m31_array().to_vec()),
-        );
+        let columns = |column: Option<&QM31>| column.map_or(vec![], |v| v.to_m31_array().to_vec());
 
+        let mut self_hash = H::hash_node(None, &columns(self.self_columns.get(&self.depth)));
+        let mut sibling_hash = H::hash_node(None, &columns(self.siblings_columns.get(&self.depth)));
+
+        let mut sibling_hashes = self.sibling_hashes.iter();
         for i in 0..self.depth {
             let h = self.depth - i - 1;
 
-            if !self.self_columns.contains_key(&h) {
-                self_hash = Poseidon31MerkleHasher::hash_node(
-                    if (self.query >> i) & 1 == 0 {
-                        Some((self_hash, sibling_hash))
-                    } else {
-                        Some((sibling_hash, self_hash))
-                    },
-                    &[],
-                );
-                if i != self.depth - 1 {
-                    sibling_hash = self.sibling_hashes[i];
-                }
-            } else {
-                self_hash = Poseidon31MerkleHasher::hash_node(
-                    if (self.query >> i) & 1 == 0 {
-                        Some((self_hash, sibling_hash))
-                    } else {
-                        Some((sibling_hash, self_hash))
-                    },
-                    &self
-                        .self_columns
-                        .get(&h)
-                        .map_or(vec![], |v| v.to_m31_array().to_vec()),
-                );
-                sibling_hash = {
-                    let column_hash = Poseidon31MerkleHasher::hash_column_get_capacity(
-                        &self
-                            .siblings_columns
-                            .get(&h)
-                            .map_or(vec![], |v| v.to_m31_array().to_vec()),
-                    );
-                    let mut state = [M31::zero(); 16];
-                    state[..8].copy_from_slice(&self.sibling_hashes[i].0);
-                    state[8..].copy_from_slice(&column_hash.0);
-                    Poseidon31Hash(Poseidon31CRH::permute_get_rate(&state))
+            self_hash = H::hash_node(
+                if (self.query >> i) & 1 == 0 {
+                    Some((self_hash, sibling_hash))
+                } else {
+                    Some((sibling_hash, self_hash))
+                },
+                &columns(self.self_columns.get(&h)),
+            );
+            if i != self.depth - 1 {
+                sibling_hash = match self.sibling_children.get(&h) {
+                    Some(&children) => {
+                        H::hash_node(Some(children), &columns(self.siblings_columns.get(&h)))
+                    }
+                    None => *sibling_hashes.next().unwrap(),
                 };
             }
         }
+        assert!(sibling_hashes.next().is_none());
         assert_eq!(self_hash, self.root);
     }
 
     pub fn from_stwo_proof(
         log_sizes_with_data: &BTreeSet<u32>,
-        root: Poseidon31Hash,
+        root: H::Hash,
         leaf_queries: &[usize],
         values: &[M31],
-        decommitment: &MerkleDecommitment<Poseidon31MerkleHasher>,
-    ) -> Vec<SinglePairMerkleProof> {
+        decommitment: &MerkleDecommitment<H>,
+    ) -> Vec<SinglePairMerkleProof<H>> {
         // require the column witness to be empty
         // (all the values are provided)
         assert_eq!(decommitment.column_witness.len(), 0);
@@ -111,7 +86,7 @@ impl SinglePairMerkleProof {
         let mut hash_iter = decommitment.hash_witness.iter();
 
         let mut queries_values_map = BTreeMap::new();
-        let mut hash_layers: Vec<HashMap<usize, Poseidon31Hash>> = vec![];
+        let mut hash_layers: Vec<HashMap<usize, H::Hash>> = vec![];
 
         for current_log_size in (0..=max_log_size).rev() {
             queries.sort_unstable();
@@ -141,7 +116,7 @@ impl SinglePairMerkleProof {
                 let mut hash_layer = HashMap::new();
                 for (&query, value) in queries_values.iter() {
                     if current_log_size == max_log_size {
-                        hash_layer.insert(query, Poseidon31MerkleHasher::hash_node(None, value));
+                        hash_layer.insert(query, H::hash_node(None, value));
                     } else {
                         let left_idx = query << 1;
                         let right_idx = left_idx + 1;
@@ -162,10 +137,8 @@ impl SinglePairMerkleProof {
                                 hash_layers.last_mut().unwrap().insert(right_idx, v);
                                 v
                             };
-                        hash_layer.insert(
-                            query,
-                            Poseidon31MerkleHasher::hash_node(Some((left_hash, right_hash)), value),
-                        );
+                        hash_layer
+                            .insert(query, H::hash_node(Some((left_hash, right_hash)), value));
                     }
                 }
 
@@ -195,7 +168,7 @@ impl SinglePairMerkleProof {
                         v
                     };
 
-                    let h = Poseidon31MerkleHasher::hash_node(Some((left_hash, right_hash)), &[]);
+                    let h = H::hash_node(Some((left_hash, right_hash)), &[]);
                     hash_layer.insert(query, h);
                 }
 
@@ -214,6 +187,7 @@ impl SinglePairMerkleProof {
         let mut proofs = vec![];
         for leaf_query in leaf_queries.iter() {
             let mut sibling_hashes = vec![];
+            let mut sibling_children = BTreeMap::new();
             let mut self_columns = BTreeMap::new();
             let mut siblings_columns = BTreeMap::new();
 
@@ -255,10 +229,7 @@ impl SinglePairMerkleProof {
                             .get(&sibling_right)
                             .unwrap();
 
-                        sibling_hashes.push(Poseidon31MerkleHasher::hash_node(
-                            Some((left_hash, right_hash)),
-                            &[],
-                        ));
+                        sibling_children.insert(current_log_size as usize, (left_hash, right_hash));
                     }
                 } else {
                     let self_idx = query;
@@ -275,6 +246,7 @@ impl SinglePairMerkleProof {
             let proof = SinglePairMerkleProof {
                 query: *leaf_query,
                 sibling_hashes,
+                sibling_children,
                 self_columns,
                 siblings_columns,
                 root,
@@ -288,17 +260,20 @@ impl SinglePairMerkleProof {
 }
 
 #[derive(Clone)]
-pub struct FirstLayerHints {
-    pub merkle_proofs: Vec<SinglePairMerkleProof>,
+pub struct FirstLayerHints<H: MerkleHasher = Poseidon31MerkleHasher> {
+    pub merkle_proofs: Vec<SinglePairMerkleProof<H>>,
     pub folded_evals_by_column: BTreeMap<u32, Vec<SecureField>>,
+    /// The decommitted positions in the first layer tree, at the log size of each column.
+    pub decommitment_positions_by_log_size: BTreeMap<u32, Vec<usize>>,
+    pub decommitmented_values: Vec<M31>,
 }
 
-impl FirstLayerHints {
-    pub fn compute(
-        fiat_shamir_hints: &FiatShamirHints<Poseidon31MerkleChannel>,
-        answer_hints: &AnswerHints<Poseidon31MerkleChannel>,
-        proof: &PlonkWithPoseidonProof<Poseidon31MerkleHasher>,
-    ) -> FirstLayerHints {
+impl<H: MerkleHasher> FirstLayerHints<H> {
+    pub fn compute<MC: MerkleChannel<H = H>>(
+        fiat_shamir_hints: &FiatShamirHints<MC>,
+        answer_hints: &AnswerHints<MC>,
+        proof: &PlonkWithPoseidonProof<H>,
+    ) -> FirstLayerHints<H> {
         // Columns are provided in descending order by size.
         let max_column_log_size = fiat_shamir_hints
             .fri_verifier
@@ -408,6 +383,8 @@ impl FirstLayerHints {
         FirstLayerHints {
             merkle_proofs,
             folded_evals_by_column,
+            decommitment_positions_by_log_size,
+            decommitmented_values,
         }
     }
 
@@ -451,17 +428,17 @@ impl FirstLayerHints {
     }
 }
 
-pub struct InnerLayersHints {
-    pub merkle_proofs: BTreeMap<u32, Vec<SinglePairMerkleProof>>,
+pub struct InnerLayersHints<H: MerkleHasher = Poseidon31MerkleHasher> {
+    pub merkle_proofs: BTreeMap<u32, Vec<SinglePairMerkleProof<H>>>,
     pub folded_intermediate_results: BTreeMap<u32, BTreeMap<usize, SecureField>>,
 }
 
-impl InnerLayersHints {
-    pub fn compute(
+impl<H: MerkleHasher> InnerLayersHints<H> {
+    pub fn compute<MC: MerkleChannel<H = H>>(
         folded_evals_by_column: &BTreeMap<u32, Vec<SecureField>>,
-        fiat_shamir_hints: &FiatShamirHints<Poseidon31MerkleChannel>,
-        proof: &PlonkWithPoseidonProof<Poseidon31MerkleHasher>,
-    ) -> InnerLayersHints {
+        fiat_shamir_hints: &FiatShamirHints<MC>,
+        proof: &PlonkWithPoseidonProof<H>,
+    ) -> InnerLayersHints<H> {
         let mut log_size = fiat_shamir_hints.max_first_layer_column_log_size;
 
         let mut folded = BTreeMap::new();
@@ -530,7 +507,7 @@ impl InnerLayersHints {
                 .flat_map(|v| v.to_m31_array())
                 .collect_vec();
 
-            let merkle_verifier: MerkleVerifier<Poseidon31MerkleHasher> = MerkleVerifier::new(
+            let merkle_verifier: MerkleVerifier<H> = MerkleVerifier::new(
                 inner_layer.commitment,
                 vec![log_size; SECURE_EXTENSION_DEGREE],
             );
@@ -0,0 +1,200 @@
+use circle_plonk_dsl_answer::AnswerResults;
+use circle_plonk_dsl_composition::CompositionCheck;
+use circle_plonk_dsl_constraint_system::var::AllocVar;
+use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
+use circle_plonk_dsl_data_structures::{
+    PlonkVerifyingKey, PlonkVerifyingKeyMembershipVar, PlonkVerifyingKeyVar,
+    PlonkWithPoseidonProofVar,
+};
+use circle_plonk_dsl_fiat_shamir::FiatShamirResults;
+use circle_plonk_dsl_folding::FoldingResults;
+use circle_plonk_dsl_hints::{
+    AnswerHints, DecommitHints, FiatShamirHints, FirstLayerHints, InnerLayersHints,
+};
+use circle_plonk_dsl_primitives::{
+    CirclePointQM31Var, Poseidon2HalfVar, Poseidon31MerkleHasherVar, QM31Var,
+};
+use num_traits::One;
+use stwo::core::fields::qm31::QM31;
+use stwo::core::vcs::poseidon31_hash::Poseidon31Hash;
+use stwo::core::vcs::poseidon31_merkle::{Poseidon31MerkleChannel, Poseidon31MerkleHasher};
+use stwo::core::vcs::MerkleHasher;
+use stwo_examples::plonk_with_poseidon::air::PlonkWithPoseidonProof;
+
+/// The public inputs of a proof of the recursion chain, which are (1, i, j, A, B), where A and B
+/// together form the output hash.
+pub fn public_inputs(output_hash: Poseidon31Hash) -> Vec<(usize, QM31)> {
+    let h = output_hash.0;
+    vec![
+        (1, QM31::one()),
+        (2, QM31::from_u32_unchecked(0, 1, 0, 0)),
+        (3, QM31::from_u32_unchecked(0, 0, 1, 0)),
+        (4, QM31::from_m31(h[0], h[1], h[2], h[3])),
+        (5, QM31::from_m31(h[4], h[5], h[6], h[7])),
+    ]
+}
+
+/// Verify a proof of the recursion chain, whose output hash is `output_hash_var`, inside the
+/// circuit. The verifying key is a constant of the circuit, unless a proof of its membership in a
+/// set of allowed keys is given, in which case it is a witness.
+pub fn verify_with_output_hash(
+    cs: &ConstraintSystemRef,
+    proof: &PlonkWithPoseidonProof<Poseidon31MerkleHasher>,
+    verifying_key: &PlonkVerifyingKey,
+    membership: Option<&PlonkVerifyingKeyMembershipVar>,
+    output_hash_var: &Poseidon2HalfVar,
+) {
+    let config = verifying_key.pcs_config;
+    let inputs = public_inputs(Poseidon31Hash(output_hash_var.value()));
+
+    let fiat_shamir_hints = FiatShamirHints::<Poseidon31MerkleChannel>::new(proof, config, &inputs);
+    let answer_hints = AnswerHints::compute(&fiat_shamir_hints, proof);
+    let decommitment_hints = DecommitHints::compute(&fiat_shamir_hints, proof);
+    let first_layer_hints = FirstLayerHints::compute(&fiat_shamir_hints, &answer_hints, proof);
+    let inner_layer_hints = InnerLayersHints::compute(
+        &first_layer_hints.folded_evals_by_column,
+        &fiat_shamir_hints,
+        proof,
+    );
+
+    let mut proof_var = PlonkWithPoseidonProofVar::new_witness(cs, proof);
+
+    let output_hash_var_qm31 = output_hash_var.to_qm31();
+    let input_vars = [
+        (1, QM31Var::one(cs)),
+        (2, QM31Var::i(cs)),
+        (3, QM31Var::j(cs)),
+        (4, output_hash_var_qm31[0].clone()),
+        (5, output_hash_var_qm31[1].clone()),
+    ];
+    let fiat_shamir_results = match membership {
+        Some(membership) => FiatShamirResults::compute_with_allowed_keys(
+            &fiat_shamir_hints,
+            &mut proof_var,
+            &PlonkVerifyingKeyVar::new_witness(cs, verifying_key),
+            membership,
+            &input_vars,
+        ),
+        None => FiatShamirResults::compute(
+            &fiat_shamir_hints,
+            &mut proof_var,
+            &PlonkVerifyingKeyVar::new_constant(cs, verifying_key),
+            &input_vars,
+        ),
+    };
+
+    CompositionCheck::compute(
+        &fiat_shamir_hints,
+        &fiat_shamir_results.lookup_elements,
+        fiat_shamir_results.random_coeff.clone(),
+        fiat_shamir_results.oods_point.clone(),
+        &proof_var,
+    );
+
+    let answer_results = AnswerResults::compute(
+        &CirclePointQM31Var::new_witness(cs, &fiat_shamir_hints.oods_point),
+        &fiat_shamir_hints,
+        &fiat_shamir_results,
+        &answer_hints,
+        &decommitment_hints,
+        &proof_var,
+        config,
+    );
+
+    FoldingResults::compute(
+        &proof_var,
+        &fiat_shamir_hints,
+        &fiat_shamir_results,
+        &answer_results,
+        &first_layer_hints,
+        &inner_layer_hints,
+    );
+}
+
+/// The output hash of an aggregated proof, which combines the output hashes of the inner proofs
+/// from left to right.
+pub fn combine_output_hashes(output_hashes: &[Poseidon31Hash]) -> Poseidon31Hash {
+    let mut combined = output_hashes[0];
+    for output_hash in output_hashes.iter().skip(1) {
+        combined = Poseidon31MerkleHasher::hash_node(Some((combined, *output_hash)), &[]);
+    }
+    combined
+}
+
+/// A proof to aggregate, with the verifying key that it is checked against and its output hash.
+#[derive(Clone, Copy)]
+pub struct AggregatedProof<'a> {
+    pub proof: &'a PlonkWithPoseidonProof<Poseidon31MerkleHasher>,
+    pub verifying_key: &'a PlonkVerifyingKey,
+    pub output_hash: Poseidon31Hash,
+}
+
+/// Verify two or more proofs in one circuit, and expose the combined output hash of the inner
+/// proofs as the output hash of the circuit, so that the aggregated proofs can be aggregated
+/// again as a binary tree. Return the combined output hash, which is a public input.
+pub fn aggregate(cs: &ConstraintSystemRef, proofs: &[AggregatedProof]) -> Poseidon31Hash {
+    assert!(proofs.len() >= 2);
+
+    let output_hashes = proofs
+        .iter()
+        .map(|proof| proof.output_hash)
+        .collect::<Vec<_>>();
+    let combined_output_hash = combine_output_hashes(&output_hashes);
+    let combined_output_hash_var = Poseidon2HalfVar::new_public_input(cs, &combined_output_hash);
+
+    let mut combined = None;
+    for proof in proofs.iter() {
+        let output_hash_var = Poseidon2HalfVar::new_witness(cs, &proof.output_hash);
+        verify_with_output_hash(cs, proof.proof, proof.verifying_key, None, &output_hash_var);
+
+        combined = Some(match combined {
+            None => output_hash_var,
+            Some(combined) => Poseidon31MerkleHasherVar::hash_tree(&combined, &output_hash_var),
+        });
+    }
+    combined.unwrap().equalverify(&combined_output_hash_var);
+
+    combined_output_hash
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{aggregate, combine_output_hashes, AggregatedProof};
+    use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
+    use circle_plonk_dsl_data_structures::PlonkVerifyingKey;
+    use std::path::PathBuf;
+    use stwo::core::vcs::poseidon31_hash::Poseidon31Hash;
+    use stwo::core::vcs::poseidon31_merkle::Poseidon31MerkleHasher;
+    use stwo_examples::plonk_with_poseidon::air::PlonkWithPoseidonProof;
+
+    #[test]
+    fn test_aggregate() {
+        let data_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../../examples/data");
+        let proof: PlonkWithPoseidonProof<Poseidon31MerkleHasher> =
+            bincode::deserialize(&std::fs::read(data_dir.join("level5_28_7_9.bin")).unwrap())
+                .unwrap();
+        let verifying_key =
+            PlonkVerifyingKey::load(&data_dir.join("level5_28_7_9.vk.json")).unwrap();
+        let output_hash: Poseidon31Hash = serde_json::from_str(
+            &std::fs::read_to_string(data_dir.join("output_hash.txt")).unwrap(),
+        )
+        .unwrap();
+
+        let cs = ConstraintSystemRef::new();
+        let aggregated_proof = AggregatedProof {
+            proof: &proof,
+            verifying_key: &verifying_key,
+            output_hash,
+        };
+        let combined_output_hash = aggregate(&cs, &[aggregated_proof, aggregated_proof]);
+        assert_eq!(
+            combined_output_hash,
+            combine_output_hashes(&[output_hash, output_hash])
+        );
+
+        cs.pad();
+        cs.check_arithmetics();
+        cs.populate_logup_arguments();
+        cs.check_poseidon_invocations();
+    }
+}
@@ -7,7 +7,7 @@ use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
 use circle_plonk_dsl_data_structures::{DecommitmentVar, PlonkWithPoseidonProofVar};
 use circle_plonk_dsl_fiat_shamir::FiatShamirResults;
 use circle_plonk_dsl_hints::{AnswerHints, DecommitHints, FiatShamirHints};
-use circle_plonk_dsl_primitives::{CirclePointM31Var, CirclePointQM31Var};
+use circle_plonk_dsl_primitives::{CirclePointM31Var, CirclePointQM31Var, MerkleChannelVar};
 use circle_plonk_dsl_primitives::{M31Var, QM31Var};
 use circle_plonk_dsl_primitives::{PointCarryingQueryVar, QueryPositionsPerLogSizeVar};
 use indexmap::IndexMap;
@@ -18,7 +18,6 @@ use std::iter::zip;
 use std::ops::Add;
 use stwo::core::pcs::{PcsConfig, TreeVec};
 use stwo::core::poly::circle::CanonicCoset;
-use stwo::core::vcs::poseidon31_merkle::Poseidon31MerkleChannel;
 use stwo::core::ColumnVec;
 use stwo_constraint_framework::PREPROCESSED_TRACE_IDX;
 
@@ -32,13 +31,13 @@ pub struct AnswerResults {
 }
 
 impl AnswerResults {
-    pub fn compute(
+    pub fn compute<MC: MerkleChannelVar>(
         oods_point: &CirclePointQM31Var,
-        fiat_shamir_hints: &FiatShamirHints<Poseidon31MerkleChannel>,
-        fiat_shamir_results: &FiatShamirResults,
-        fri_answer_hints: &AnswerHints<Poseidon31MerkleChannel>,
-        decommit_hints: &DecommitHints,
-        proof: &PlonkWithPoseidonProofVar,
+        fiat_shamir_hints: &FiatShamirHints<MC>,
+        fiat_shamir_results: &FiatShamirResults<MC>,
+        fri_answer_hints: &AnswerHints<MC>,
+        decommit_hints: &DecommitHints<MC::H>,
+        proof: &PlonkWithPoseidonProofVar<MC>,
         pcs_config: PcsConfig,
     ) -> AnswerResults {
         let cs = oods_point.cs();
@@ -84,8 +83,7 @@ impl AnswerResults {
                     })
                     .collect_vec()
             });
-        mask_points_plonk[PREPROCESSED_TRACE_IDX] =
-            vec![vec![(ShiftIndex::Zero, oods_point.clone())]; 10];
+        mask_points_plonk[PREPROCESSED_TRACE_IDX] = vec![];
         let mut mask_points_poseidon: TreeVec<ColumnVec<Vec<(ShiftIndex, CirclePointQM31Var)>>> =
             fiat_shamir_hints.mask_poseidon.as_ref().map_cols(|column| {
                 column
@@ -98,18 +96,34 @@ impl AnswerResults {
                     })
                     .collect_vec()
             });
-        mask_points_poseidon[PREPROCESSED_TRACE_IDX] =
-            vec![vec![(ShiftIndex::Zero, oods_point.clone())]; 40];
+        mask_points_poseidon[PREPROCESSED_TRACE_IDX] = vec![];
+
+        let preprocessed_sample_points = &fiat_shamir_hints.sample_points[PREPROCESSED_TRACE_IDX];
+        let mask_points_preprocessed = Self::preprocessed_mask_points(
+            oods_point,
+            &fiat_shamir_hints.plonk_prepared_column_indices,
+            &fiat_shamir_hints.poseidon_prepared_column_indices,
+            preprocessed_sample_points.len(),
+        );
+        for (column, expected) in zip(&mask_points_preprocessed, preprocessed_sample_points) {
+            assert_eq!(column.len(), expected.len());
+            for ((_, point), expected) in zip(column, expected) {
+                assert_eq!(point.x.value(), expected.x);
+                assert_eq!(point.y.value(), expected.y);
+            }
+        }
 
         assert_eq!(
             mask_points_plonk.len(),
             fiat_shamir_hints.sample_points.len() - 1
         );
+        // the preprocessed round has been checked above
         for (round_idx, (round_plonk, round_poseidon)) in mask_points_plonk
             .iter()
             .zip(mask_points_poseidon.iter())
             .enumerate()
             .take(3)
+            .skip(1)
         {
             assert_eq!(
                 round_plonk.len() + round_poseidon.len(),
@@ -159,6 +173,7 @@ impl AnswerResults {
 
         let mut sampled_points =
             TreeVec::concat_cols([mask_points_plonk, mask_points_poseidon].into_iter());
+        sampled_points[PREPROCESSED_TRACE_IDX] = mask_points_preprocessed;
         sampled_points.push(vec![vec![(ShiftIndex::Zero, oods_point.clone())]; 8]);
 
         let samples = sampled_points
@@ -212,7 +227,7 @@ impl AnswerResults {
             );
         }
 
-        let mut decommitment_var = DecommitmentVar::new(&cs, decommit_hints);
+        let mut decommitment_var = DecommitmentVar::<MC>::new(&cs, decommit_hints);
         for (i, query) in query_positions_per_log_size[*fiat_shamir_hints.trees_log_sizes[0]
             .iter()
             .max()
@@ -354,6 +369,31 @@ impl AnswerResults {
         }
     }
 
+    /// The mask points of the preprocessed round. The preprocessed columns can be shared between
+    /// the two components (e.g., `is_first`), so each column is sampled once at the OODS point if
+    /// any component uses it.
+    pub fn preprocessed_mask_points(
+        oods_point: &CirclePointQM31Var,
+        plonk_column_indices: &[usize],
+        poseidon_column_indices: &[usize],
+        n_columns: usize,
+    ) -> ColumnVec<Vec<(ShiftIndex, CirclePointQM31Var)>> {
+        let used_columns: BTreeSet<usize> = plonk_column_indices
+            .iter()
+            .chain(poseidon_column_indices.iter())
+            .copied()
+            .collect();
+        (0..n_columns)
+            .map(|column_idx| {
+                if used_columns.contains(&column_idx) {
+                    vec![(ShiftIndex::Zero, oods_point.clone())]
+                } else {
+                    vec![]
+                }
+            })
+            .collect_vec()
+    }
+
     pub fn fri_answers_for_log_size(
         samples: &[&Vec<PointSampleVar>],
         random_coeff: &QM31Var,
@@ -385,19 +425,27 @@ impl AnswerResults {
 
 #[cfg(test)]
 mod test {
+    use crate::data_structures::ShiftIndex;
     use crate::AnswerResults;
+    use circle_plonk_dsl_composition::CompositionCheck;
     use circle_plonk_dsl_constraint_system::var::AllocVar;
     use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
-    use circle_plonk_dsl_data_structures::PlonkWithPoseidonProofVar;
+    use circle_plonk_dsl_data_structures::{
+        PlonkVerifyingKey, PlonkVerifyingKeyVar, PlonkWithPoseidonProofVar,
+    };
     use circle_plonk_dsl_fiat_shamir::FiatShamirResults;
     use circle_plonk_dsl_hints::{AnswerHints, DecommitHints, FiatShamirHints};
     use circle_plonk_dsl_primitives::CirclePointQM31Var;
     use circle_plonk_dsl_primitives::QM31Var;
+    use itertools::Itertools;
     use num_traits::One;
+    use std::path::{Path, PathBuf};
+    use stwo::core::circle::SECURE_FIELD_CIRCLE_GEN;
     use stwo::core::fields::m31::M31;
     use stwo::core::fields::qm31::QM31;
     use stwo::core::fri::FriConfig;
     use stwo::core::pcs::PcsConfig;
+    use stwo::core::vcs::poseidon31_hash::Poseidon31Hash;
     use stwo::core::vcs::poseidon31_merkle::{Poseidon31MerkleChannel, Poseidon31MerkleHasher};
     use stwo_examples::plonk_with_poseidon::air::{
         prove_plonk_with_poseidon, verify_plonk_with_poseidon, PlonkWithPoseidonProof,
@@ -412,15 +460,24 @@ mod test {
             fri_config: FriConfig::new(2, 5, 16),
         };
 
-        let fiat_shamir_hints = FiatShamirHints::new(&proof, config, &[(1, QM31::one())]);
+        let fiat_shamir_hints =
+            FiatShamirHints::<Poseidon31MerkleChannel>::new(&proof, config, &[(1, QM31::one())]);
 
         let cs = ConstraintSystemRef::new();
         let mut proof_var = PlonkWithPoseidonProofVar::new_witness(&cs, &proof);
+        let verifying_key = PlonkVerifyingKeyVar::new_constant(
+            &cs,
+            &PlonkVerifyingKey::load(Path::new(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../../test_data/small_proof.vk.json"
+            )))
+            .unwrap(),
+        );
 
         let fiat_shamir_results = FiatShamirResults::compute(
             &fiat_shamir_hints,
             &mut proof_var,
-            config,
+            &verifying_key,
             &[(1, QM31Var::one(&cs))],
         );
         let fri_answer_hints = AnswerHints::compute(&fiat_shamir_hints, &proof);
@@ -468,4 +525,105 @@ mod test {
         )
         .unwrap();
     }
+
+    #[test]
+    fn test_preprocessed_mask_points_with_shared_columns() {
+        let cs = ConstraintSystemRef::new();
+        let oods_point = SECURE_FIELD_CIRCLE_GEN.mul(12345);
+        let oods_point_var = CirclePointQM31Var::new_witness(&cs, &oods_point);
+
+        // the two components share the columns 2 and 3, and the column 5 is unused
+        let mask_points =
+            AnswerResults::preprocessed_mask_points(&oods_point_var, &[0, 1, 2, 3], &[2, 3, 4], 6);
+
+        assert_eq!(
+            mask_points
+                .iter()
+                .map(|column| column.len())
+                .collect::<Vec<_>>(),
+            vec![1, 1, 1, 1, 1, 0]
+        );
+        for (shift, point) in mask_points.iter().flatten() {
+            assert_eq!(*shift, ShiftIndex::Zero);
+            assert_eq!(point.x.value(), oods_point.x);
+            assert_eq!(point.y.value(), oods_point.y);
+        }
+    }
+
+    #[test]
+    fn test_answer_with_shared_preprocessed_columns() {
+        // the Plonk and the Poseidon components of the first recursive proof of the example have
+        // the same log size, so that they share preprocessed columns such as `is_first`
+        let data_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../../../examples/data")
+            .canonicalize()
+            .unwrap();
+        let proof: PlonkWithPoseidonProof<Poseidon31MerkleHasher> =
+            bincode::deserialize(&std::fs::read(data_dir.join("level1_20_8_1.bin")).unwrap())
+                .unwrap();
+        let verifying_key =
+            PlonkVerifyingKey::load(&data_dir.join("level1_20_8_1.vk.json")).unwrap();
+        let output_hash: Poseidon31Hash = serde_json::from_str(
+            &std::fs::read_to_string(data_dir.join("output_hash.txt")).unwrap(),
+        )
+        .unwrap();
+        let config = verifying_key.pcs_config;
+
+        let h = output_hash.0;
+        let inputs = vec![
+            (1, QM31::one()),
+            (2, QM31::from_u32_unchecked(0, 1, 0, 0)),
+            (3, QM31::from_u32_unchecked(0, 0, 1, 0)),
+            (4, QM31::from_m31(h[0], h[1], h[2], h[3])),
+            (5, QM31::from_m31(h[4], h[5], h[6], h[7])),
+        ];
+
+        let fiat_shamir_hints =
+            FiatShamirHints::<Poseidon31MerkleChannel>::new(&proof, config, &inputs);
+        assert!(fiat_shamir_hints
+            .plonk_prepared_column_indices
+            .iter()
+            .any(|idx| fiat_shamir_hints
+                .poseidon_prepared_column_indices
+                .contains(idx)));
+
+        let cs = ConstraintSystemRef::new();
+        let mut proof_var = PlonkWithPoseidonProofVar::new_witness(&cs, &proof);
+        let verifying_key_var = PlonkVerifyingKeyVar::new_constant(&cs, &verifying_key);
+        let input_vars = inputs
+            .iter()
+            .map(|(idx, value)| (*idx, QM31Var::new_constant(&cs, value)))
+            .collect_vec();
+
+        let fiat_shamir_results = FiatShamirResults::compute(
+            &fiat_shamir_hints,
+            &mut proof_var,
+            &verifying_key_var,
+            &input_vars,
+        );
+        CompositionCheck::compute(
+            &fiat_shamir_hints,
+            &fiat_shamir_results.lookup_elements,
+            fiat_shamir_results.random_coeff.clone(),
+            fiat_shamir_results.oods_point.clone(),
+            &proof_var,
+        );
+
+        let fri_answer_hints = AnswerHints::compute(&fiat_shamir_hints, &proof);
+        let decommitment_hints = DecommitHints::compute(&fiat_shamir_hints, &proof);
+        AnswerResults::compute(
+            &CirclePointQM31Var::new_witness(&cs, &fiat_shamir_hints.oods_point),
+            &fiat_shamir_hints,
+            &fiat_shamir_results,
+            &fri_answer_hints,
+            &decommitment_hints,
+            &proof_var,
+            config,
+        );
+
+        cs.pad();
+        cs.check_arithmetics();
+        cs.populate_logup_arguments();
+        cs.check_poseidon_invocations();
+    }
 }
@@ -1,6 +1,7 @@
 use circle_plonk_dsl_constraint_system::var::Var;
 use circle_plonk_dsl_data_structures::LookupElementsVar;
 use circle_plonk_dsl_primitives::QM31Var;
+use std::collections::BTreeMap;
 use stwo::core::fields::m31::BaseField;
 use stwo::core::fields::qm31::SECURE_EXTENSION_DEGREE;
 use stwo::core::pcs::TreeVec;
@@ -157,10 +158,14 @@ impl<'a> EvalAtRowVar<'a> {
         }
         denom = &denom - &entry.relation.z;
 
+        self.write_logup_frac(entry.multiplicity, denom);
+    }
+
+    pub fn write_logup_frac(&mut self, numerator: QM31Var, denominator: QM31Var) {
         if self.logup.fracs.is_empty() {
             self.logup.is_finalized = false;
         }
-        self.logup.fracs.push((entry.multiplicity, denom));
+        self.logup.fracs.push((numerator, denominator));
     }
 
     pub fn add_constraint(&mut self, value: QM31Var) {
@@ -169,27 +174,39 @@ impl<'a> EvalAtRowVar<'a> {
     }
 
     pub fn finalize_logup(&mut self, batch_size: usize) {
-        let num_batches = self.logup.fracs.len().div_ceil(batch_size);
-
-        let mut batched_fracs = vec![];
-        for chunk in self.logup.fracs.chunks(batch_size) {
-            let (num, denom) = if chunk.len() == 1 {
-                chunk[0].clone()
-            } else {
-                let mut p = chunk[0].0.clone();
-                let mut q = chunk[0].1.clone();
-
-                for elem in chunk.iter().skip(1) {
-                    p = &(&p * &elem.1) + &(&elem.0 * &q);
-                    q = &q * &elem.1;
-                }
-                (p, q)
+        let batching = (0..self.logup.fracs.len())
+            .map(|i| i / batch_size)
+            .collect::<Vec<_>>();
+        self.finalize_logup_batched(&batching)
+    }
+
+    /// Finalize the logup columns where the fractions are summed in the given batches, which must
+    /// be consecutive, as in `EvalAtRow::finalize_logup_batched`.
+    pub fn finalize_logup_batched(&mut self, batching: &[usize]) {
+        assert!(!self.logup.is_finalized, "LogupAtRow was already finalized");
+        assert_eq!(
+            batching.len(),
+            self.logup.fracs.len(),
+            "Batching must be of the same length as the number of entries"
+        );
+
+        let mut batched_fracs = BTreeMap::<usize, (QM31Var, QM31Var)>::new();
+        for (batch, (num, denom)) in batching.iter().zip(self.logup.fracs.iter()) {
+            let sum = match batched_fracs.remove(batch) {
+                None => (num.clone(), denom.clone()),
+                Some((p, q)) => (&(&p * denom) + &(num * &q), &q * denom),
             };
-            batched_fracs.push((num, denom));
+            batched_fracs.insert(*batch, sum);
         }
 
+        let last_batch = *batching.iter().max().unwrap();
+        assert!(
+            (0..=last_batch).all(|batch| batched_fracs.contains_key(&batch)),
+            "Batching must contain all consecutive batches"
+        );
+
         let mut prev_col_cumsum = QM31Var::zero(&self.logup.cumsum_shift.cs());
-        for (num, denom) in batched_fracs.iter().take(num_batches - 1) {
+        for (num, denom) in batched_fracs.values().take(last_batch) {
             let [cur_cumsum] = self.next_extension_interaction_mask(self.logup.interaction, [0]);
             let diff = &cur_cumsum - &prev_col_cumsum;
             prev_col_cumsum = cur_cumsum;
@@ -197,16 +214,15 @@ impl<'a> EvalAtRowVar<'a> {
             self.add_constraint(&(&diff * denom) - num);
         }
 
-        for (num, denom) in batched_fracs.iter().skip(num_batches - 1) {
-            let [prev_row_cumsum, cur_cumsum] =
-                self.next_extension_interaction_mask(self.logup.interaction, [-1, 0]);
+        let (num, denom) = &batched_fracs[&last_batch];
+        let [prev_row_cumsum, cur_cumsum] =
+            self.next_extension_interaction_mask(self.logup.interaction, [-1, 0]);
 
-            let diff = &(&cur_cumsum - &prev_row_cumsum) - &prev_col_cumsum;
-            let fixed_diff = &diff + &self.logup.cumsum_shift;
+        let diff = &(&cur_cumsum - &prev_row_cumsum) - &prev_col_cumsum;
+        let fixed_diff = &diff + &self.logup.cumsum_shift;
 
-            self.add_constraint(&(&fixed_diff * denom) - num);
-            self.logup.is_finalized = true;
-        }
+        self.add_constraint(&(&fixed_diff * denom) - num);
+        self.logup.is_finalized = true;
     }
 
     pub fn finalize_logup_in_pairs(&mut self) {
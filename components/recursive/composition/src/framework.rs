@@ -0,0 +1,550 @@
+use crate::coset_vanishing;
+use crate::data_structures::{EvalAtRowVar, PointEvaluationAccumulatorVar};
+use circle_plonk_dsl_constraint_system::var::Var;
+use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
+use circle_plonk_dsl_primitives::fields::WrappedQM31Var;
+use circle_plonk_dsl_primitives::{
+    ChannelVar, CirclePointQM31Var, QM31Var, RelationElementsVar, RelationRedirect,
+};
+use indexmap::IndexMap;
+use itertools::Itertools;
+use std::ops::{Deref, Mul};
+use stwo::core::air::Component;
+use stwo::core::fields::qm31::{QM31, SECURE_EXTENSION_DEGREE};
+use stwo::core::pcs::{TreeSubspan, TreeVec};
+use stwo::core::{ColumnVec, Fraction};
+use stwo_constraint_framework::preprocessed_columns::PreProcessedColumnId;
+use stwo_constraint_framework::{
+    EvalAtRow, FrameworkComponent, FrameworkEval, Relation, RelationEntry, PREPROCESSED_TRACE_IDX,
+};
+
+/// The lookup elements of a relation of a generic AIR, i.e., `z` and the powers of `alpha`.
+#[derive(Debug, Clone)]
+pub struct FrameworkLookupElementsVar {
+    pub z: QM31Var,
+    pub alpha: QM31Var,
+    pub alpha_powers: Vec<QM31Var>,
+}
+
+impl FrameworkLookupElementsVar {
+    /// Draw the lookup elements in the same way as `LookupElements::<N>::draw`.
+    pub fn draw(channel: &mut ChannelVar, n: usize) -> Self {
+        let [z, alpha] = channel.draw_felts();
+        Self::from_z_and_alpha(z, alpha, n)
+    }
+
+    pub fn from_z_and_alpha(z: QM31Var, alpha: QM31Var, n: usize) -> Self {
+        let cs = z.cs().and(&alpha.cs());
+
+        let mut alpha_powers = Vec::with_capacity(n);
+        alpha_powers.push(QM31Var::one(&cs));
+        if n > 1 {
+            alpha_powers.push(alpha.clone());
+        }
+
+        let mut cur = alpha.clone();
+        for _ in 2..n {
+            cur = &cur * &alpha;
+            alpha_powers.push(cur.clone());
+        }
+
+        Self {
+            z,
+            alpha,
+            alpha_powers,
+        }
+    }
+
+    pub fn combine(&self, values: &[QM31Var]) -> QM31Var {
+        assert!(!values.is_empty());
+        assert!(values.len() <= self.alpha_powers.len());
+
+        let mut denom = &self.alpha_powers[0] * &values[0];
+        for (alpha_power, value) in self.alpha_powers.iter().zip(values.iter()).skip(1) {
+            denom = &denom + &(alpha_power * value);
+        }
+        &denom - &self.z
+    }
+}
+
+/// The lookup elements of all the relations of a generic AIR, indexed by the names of the
+/// relations, which is how the in-circuit evaluator finds the elements for a native relation.
+#[derive(Debug, Clone, Default)]
+pub struct FrameworkRelationsVar {
+    pub relations: IndexMap<String, FrameworkLookupElementsVar>,
+}
+
+impl FrameworkRelationsVar {
+    /// Draw the lookup elements of a relation, which must be done in the same order as the native
+    /// prover draws them.
+    pub fn draw(&mut self, channel: &mut ChannelVar, name: &str, n: usize) {
+        self.insert(name, FrameworkLookupElementsVar::draw(channel, n));
+    }
+
+    pub fn insert(&mut self, name: &str, elements: FrameworkLookupElementsVar) {
+        let old = self.relations.insert(name.to_string(), elements);
+        assert!(old.is_none(), "relation {} is registered twice", name);
+    }
+
+    pub fn get(&self, name: &str) -> &FrameworkLookupElementsVar {
+        self.relations
+            .get(name)
+            .unwrap_or_else(|| panic!("relation {} is not registered", name))
+    }
+}
+
+impl RelationElementsVar for &FrameworkRelationsVar {
+    fn combine(&mut self, relation: &str, values: &[WrappedQM31Var]) -> QM31Var {
+        let lookup_elements = self.get(relation);
+        let cs = lookup_elements.z.cs();
+        lookup_elements.combine(&values.iter().map(|v| v.unwrap(&cs)).collect_vec())
+    }
+}
+
+/// An [`EvalAtRow`] that evaluates the constraints of a stwo [`FrameworkEval`] at the OODS point in
+/// the circuit, by forwarding the mask, the constraints and the logup fractions to an
+/// [`EvalAtRowVar`].
+///
+/// The relation entries are combined with the native lookup elements that the component carries,
+/// so the component is evaluated through a [`RelationRedirect`] to the [`FrameworkRelationsVar`].
+pub struct FrameworkEvalAtRowVar<'a> {
+    pub cs: ConstraintSystemRef,
+    pub eval: EvalAtRowVar<'a>,
+}
+
+impl<'a> FrameworkEvalAtRowVar<'a> {
+    pub fn new(
+        mask: TreeVec<ColumnVec<&'a Vec<QM31Var>>>,
+        claimed_sum: QM31Var,
+        denom_inverse: QM31Var,
+        log_size: u32,
+        evaluation_accumulator: &'a mut PointEvaluationAccumulatorVar,
+    ) -> Self {
+        Self {
+            cs: denom_inverse.cs(),
+            eval: EvalAtRowVar::new(
+                mask,
+                claimed_sum,
+                denom_inverse,
+                log_size,
+                evaluation_accumulator,
+            ),
+        }
+    }
+}
+
+impl EvalAtRow for FrameworkEvalAtRowVar<'_> {
+    type F = WrappedQM31Var;
+    type EF = WrappedQM31Var;
+
+    fn get_preprocessed_column(&mut self, column: PreProcessedColumnId) -> Self::F {
+        WrappedQM31Var::wrap(self.eval.get_preprocessed_column(column))
+    }
+
+    fn next_interaction_mask<const N: usize>(
+        &mut self,
+        interaction: usize,
+        offsets: [isize; N],
+    ) -> [WrappedQM31Var; N] {
+        self.eval
+            .next_interaction_mask(interaction, offsets)
+            .map(WrappedQM31Var::wrap)
+    }
+
+    fn add_constraint<G>(&mut self, constraint: G)
+    where
+        Self::EF: Mul<G, Output = Self::EF> + From<G>,
+    {
+        let value: Self::EF = constraint.into();
+        self.eval.add_constraint(value.unwrap(&self.cs));
+    }
+
+    fn combine_ef(values: [WrappedQM31Var; SECURE_EXTENSION_DEGREE]) -> WrappedQM31Var {
+        let cs = values.iter().find_map(|value| match value {
+            WrappedQM31Var::Allocated(variable) => Some(variable.cs()),
+            WrappedQM31Var::Constant(_) => None,
+        });
+        if let Some(cs) = cs {
+            WrappedQM31Var::wrap(EvalAtRowVar::combine_ef(values.map(|v| v.unwrap(&cs))))
+        } else {
+            let values = values.map(|v| v.unwrap_constant());
+            WrappedQM31Var::Constant(
+                values[0]
+                    + values[1] * QM31::from_u32_unchecked(0, 1, 0, 0)
+                    + values[2] * QM31::from_u32_unchecked(0, 0, 1, 0)
+                    + values[3] * QM31::from_u32_unchecked(0, 0, 0, 1),
+            )
+        }
+    }
+
+    fn add_to_relation<R: Relation<WrappedQM31Var, WrappedQM31Var>>(
+        &mut self,
+        entry: RelationEntry<WrappedQM31Var, WrappedQM31Var, R>,
+    ) {
+        let denom = entry.relation.combine(entry.values);
+        self.write_logup_frac(Fraction::new(entry.multiplicity, denom));
+    }
+
+    fn write_logup_frac(&mut self, fraction: Fraction<Self::EF, Self::EF>) {
+        self.eval.write_logup_frac(
+            fraction.numerator.unwrap(&self.cs),
+            fraction.denominator.unwrap(&self.cs),
+        );
+    }
+
+    fn add_to_relation_ef<R: Relation<WrappedQM31Var, WrappedQM31Var>>(
+        &mut self,
+        entry: RelationEntry<WrappedQM31Var, WrappedQM31Var, R>,
+    ) {
+        self.add_to_relation(entry);
+    }
+
+    fn finalize_logup(&mut self) {
+        self.eval.finalize_logup(1);
+    }
+
+    fn finalize_logup_in_pairs(&mut self) {
+        self.eval.finalize_logup_in_pairs();
+    }
+
+    fn finalize_logup_batched(&mut self, batching: &Vec<usize>) {
+        self.eval.finalize_logup_batched(batching);
+    }
+}
+
+/// A component of a generic AIR that can be evaluated in the circuit, which is implemented for
+/// all the stwo [`FrameworkComponent`]s.
+pub trait FrameworkComponentVar {
+    fn log_size(&self) -> u32;
+    fn max_constraint_log_degree_bound(&self) -> u32;
+    fn trace_locations(&self) -> &[TreeSubspan];
+    fn preprocessed_column_indices(&self) -> &[usize];
+    fn evaluate_at_oods<'a>(
+        &self,
+        eval: FrameworkEvalAtRowVar<'a>,
+        relations: &FrameworkRelationsVar,
+    ) -> FrameworkEvalAtRowVar<'a>;
+}
+
+impl<E: FrameworkEval> FrameworkComponentVar for FrameworkComponent<E> {
+    fn log_size(&self) -> u32 {
+        FrameworkEval::log_size(self.deref())
+    }
+
+    fn max_constraint_log_degree_bound(&self) -> u32 {
+        Component::max_constraint_log_degree_bound(self)
+    }
+
+    fn trace_locations(&self) -> &[TreeSubspan] {
+        FrameworkComponent::trace_locations(self)
+    }
+
+    fn preprocessed_column_indices(&self) -> &[usize] {
+        FrameworkComponent::preprocessed_column_indices(self)
+    }
+
+    fn evaluate_at_oods<'a>(
+        &self,
+        eval: FrameworkEvalAtRowVar<'a>,
+        relations: &FrameworkRelationsVar,
+    ) -> FrameworkEvalAtRowVar<'a> {
+        self.deref()
+            .evaluate(RelationRedirect::new(eval, relations))
+            .inner
+    }
+}
+
+/// The composition check for a proof over a list of generic components, which plays the role of
+/// [`crate::CompositionCheck`] for AIRs other than Plonk-with-Poseidon.
+///
+/// This is only the composition stage of the verifier. The Fiat-Shamir, answer, decommitment and
+/// FRI stages of this repository are written for Plonk-with-Poseidon proofs, so the caller has to
+/// draw the OODS point, the random coefficient and the relations, and check the sampled values
+/// against the commitments, for the AIR at hand.
+pub struct FrameworkCompositionCheck;
+
+impl FrameworkCompositionCheck {
+    /// Check the composition polynomial at the OODS point, where the components and their claimed
+    /// sums are in the same order as in the native verifier, and components without lookups have
+    /// a zero claimed sum.
+    pub fn compute(
+        components: &[&dyn FrameworkComponentVar],
+        claimed_sums: &[QM31Var],
+        relations: &FrameworkRelationsVar,
+        random_coeff: QM31Var,
+        oods_point: CirclePointQM31Var,
+        sampled_values: &TreeVec<ColumnVec<Vec<QM31Var>>>,
+    ) {
+        assert_eq!(components.len(), claimed_sums.len());
+
+        let mut evaluation_accumulator = PointEvaluationAccumulatorVar::new(random_coeff);
+        for (component, claimed_sum) in components.iter().zip(claimed_sums.iter()) {
+            let preprocessed_mask: Vec<&Vec<QM31Var>> = component
+                .preprocessed_column_indices()
+                .iter()
+                .map(|idx| &sampled_values[PREPROCESSED_TRACE_IDX][*idx])
+                .collect_vec();
+
+            let mut mask_points = sampled_values.sub_tree(component.trace_locations());
+            mask_points[PREPROCESSED_TRACE_IDX] = preprocessed_mask;
+
+            let eval = FrameworkEvalAtRowVar::new(
+                mask_points,
+                claimed_sum.clone(),
+                coset_vanishing(&oods_point, component.log_size()).inv(),
+                component.log_size(),
+                &mut evaluation_accumulator,
+            );
+            component.evaluate_at_oods(eval, relations);
+        }
+
+        let composition_log_degree_bound = components
+            .iter()
+            .map(|component| component.max_constraint_log_degree_bound())
+            .max()
+            .unwrap();
+
+        let composition_mask = sampled_values.last().unwrap();
+        assert_eq!(composition_mask.len(), 2 * SECURE_EXTENSION_DEGREE);
+        let left_value = &(&(&composition_mask[0][0] + &composition_mask[1][0].shift_by_i())
+            + &composition_mask[2][0].shift_by_j())
+            + &composition_mask[3][0].shift_by_ij();
+        let right_value = &(&(&composition_mask[4][0] + &composition_mask[5][0].shift_by_i())
+            + &composition_mask[6][0].shift_by_j())
+            + &composition_mask[7][0].shift_by_ij();
+        let doubled_oods_x = oods_point.repeated_double_x_only(composition_log_degree_bound - 2);
+        let expected_composition = &left_value + &(&right_value * &doubled_oods_x);
+
+        evaluation_accumulator
+            .finalize()
+            .equalverify(&expected_composition);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::framework::{
+        FrameworkComponentVar, FrameworkCompositionCheck, FrameworkLookupElementsVar,
+        FrameworkRelationsVar,
+    };
+    use circle_plonk_dsl_constraint_system::var::AllocVar;
+    use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
+    use circle_plonk_dsl_data_structures::PlonkWithPoseidonProofVar;
+    use circle_plonk_dsl_hints::FiatShamirHints;
+    use circle_plonk_dsl_primitives::{ChannelVar, CirclePointQM31Var, Poseidon2HalfVar, QM31Var};
+    use itertools::Itertools;
+    use num_traits::{One, Zero};
+    use stwo::core::air::accumulation::PointEvaluationAccumulator;
+    use stwo::core::air::Component;
+    use stwo::core::channel::{Channel, MerkleChannel, Poseidon31Channel};
+    use stwo::core::circle::CirclePoint;
+    use stwo::core::fields::m31::M31;
+    use stwo::core::fields::qm31::{QM31, SECURE_EXTENSION_DEGREE};
+    use stwo::core::fri::FriConfig;
+    use stwo::core::pcs::{PcsConfig, TreeVec};
+    use stwo::core::vcs::poseidon31_merkle::{Poseidon31MerkleChannel, Poseidon31MerkleHasher};
+    use stwo::core::vcs::MerkleHasher;
+    use stwo_constraint_framework::{
+        relation, EvalAtRow, FrameworkComponent, FrameworkEval, Relation, RelationEntry,
+        TraceLocationAllocator, ORIGINAL_TRACE_IDX,
+    };
+    use stwo_examples::plonk_with_poseidon::air::{
+        PlonkWithPoseidonComponents, PlonkWithPoseidonProof,
+    };
+
+    #[test]
+    fn test_framework_composition() {
+        let proof: PlonkWithPoseidonProof<Poseidon31MerkleHasher> =
+            bincode::deserialize(include_bytes!("../../../test_data/small_proof.bin")).unwrap();
+        let config = PcsConfig {
+            pow_bits: 20,
+            fri_config: FriConfig::new(2, 5, 16),
+        };
+
+        let fiat_shamir_hints =
+            FiatShamirHints::<Poseidon31MerkleChannel>::new(&proof, config, &[(1, QM31::one())]);
+        let lookup_elements = &fiat_shamir_hints.lookup_elements;
+        let components =
+            PlonkWithPoseidonComponents::new(&proof.stmt0, lookup_elements, &proof.stmt1);
+
+        let cs = ConstraintSystemRef::new();
+        let proof_var =
+            PlonkWithPoseidonProofVar::<Poseidon31MerkleChannel>::new_witness(&cs, &proof);
+
+        // the Plonk-with-Poseidon components, evaluated through their `FrameworkEval`s
+        let mut relations = FrameworkRelationsVar::default();
+        relations.insert(
+            Relation::<M31, QM31>::get_name(lookup_elements),
+            FrameworkLookupElementsVar::from_z_and_alpha(
+                QM31Var::new_witness(&cs, &fiat_shamir_hints.z),
+                QM31Var::new_witness(&cs, &fiat_shamir_hints.alpha),
+                Relation::<M31, QM31>::get_size(lookup_elements),
+            ),
+        );
+
+        FrameworkCompositionCheck::compute(
+            &[
+                &components.plonk as &dyn FrameworkComponentVar,
+                &components.poseidon,
+            ],
+            &[
+                proof_var.stmt1.plonk_total_sum.clone(),
+                proof_var.stmt1.poseidon_total_sum.clone(),
+            ],
+            &relations,
+            QM31Var::new_witness(&cs, &fiat_shamir_hints.random_coeff),
+            CirclePointQM31Var::new_witness(&cs, &fiat_shamir_hints.oods_point),
+            &proof_var.stark_proof.sampled_values,
+        );
+
+        cs.pad();
+        cs.check_arithmetics();
+        cs.populate_logup_arguments();
+        cs.check_poseidon_invocations();
+    }
+
+    relation!(TestRelation, 2);
+
+    /// A small AIR that is not Plonk-with-Poseidon, with a transition constraint and a lookup.
+    #[derive(Clone)]
+    struct TestEval {
+        log_size: u32,
+        lookup_elements: TestRelation,
+    }
+
+    impl FrameworkEval for TestEval {
+        fn log_size(&self) -> u32 {
+            self.log_size
+        }
+
+        fn max_constraint_log_degree_bound(&self) -> u32 {
+            self.log_size + 1
+        }
+
+        fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+            let a = eval.next_trace_mask();
+            let b = eval.next_trace_mask();
+            let [c, c_next] = eval.next_interaction_mask(ORIGINAL_TRACE_IDX, [0, 1]);
+            eval.add_constraint(c_next - a.clone() * b.clone() - c.clone());
+            eval.add_to_relation(RelationEntry::new(
+                &self.lookup_elements,
+                E::EF::from(a),
+                &[b, c],
+            ));
+            eval.finalize_logup();
+            eval
+        }
+    }
+
+    #[test]
+    fn test_framework_composition_of_other_air() {
+        let log_size = 4;
+        let trace_commitment = Poseidon31MerkleHasher::hash_node(None, &[M31::from(7)]);
+        let claimed_sum = QM31::from_u32_unchecked(1, 2, 3, 4);
+
+        // the native Fiat-Shamir, in the same order as the circuit below
+        let mut channel = Poseidon31Channel::default();
+        Poseidon31MerkleChannel::mix_root(&mut channel, trace_commitment);
+        let lookup_elements = TestRelation::draw(&mut channel);
+        channel.mix_felts(&[claimed_sum]);
+        let random_coeff = channel.draw_secure_felt();
+        let oods_point = CirclePoint::get_random_point(&mut channel);
+
+        let component = FrameworkComponent::new(
+            &mut TraceLocationAllocator::default(),
+            TestEval {
+                log_size,
+                lookup_elements,
+            },
+            claimed_sum,
+        );
+
+        // arbitrary sampled values, with the composition value that the native verifier expects
+        let mut counter = 0;
+        let mut sampled_values = TreeVec::new(
+            component
+                .info
+                .mask_offsets
+                .iter()
+                .map(|tree| {
+                    tree.iter()
+                        .map(|offsets| {
+                            offsets
+                                .iter()
+                                .map(|_| {
+                                    counter += 1;
+                                    QM31::from_u32_unchecked(counter, 2 * counter, 3, 5 * counter)
+                                })
+                                .collect_vec()
+                        })
+                        .collect_vec()
+                })
+                .collect_vec(),
+        );
+        let mut accumulator = PointEvaluationAccumulator::new(random_coeff);
+        component.evaluate_constraint_quotients_at_point(
+            oods_point,
+            &sampled_values,
+            &mut accumulator,
+        );
+        let composition_value = accumulator.finalize();
+        sampled_values.push(
+            (0..2 * SECURE_EXTENSION_DEGREE)
+                .map(|i| {
+                    vec![if i == 0 {
+                        composition_value
+                    } else {
+                        QM31::zero()
+                    }]
+                })
+                .collect_vec(),
+        );
+
+        let cs = ConstraintSystemRef::new();
+        let mut channel_var = ChannelVar::default(&cs);
+        channel_var.mix_root(&Poseidon2HalfVar::new_constant(&cs, &trace_commitment));
+        let mut relations = FrameworkRelationsVar::default();
+        relations.draw(
+            &mut channel_var,
+            Relation::<M31, QM31>::get_name(&component.lookup_elements),
+            2,
+        );
+        let claimed_sum_var = QM31Var::new_witness(&cs, &claimed_sum);
+        channel_var.mix_one_felt(&claimed_sum_var);
+        let random_coeff_var = channel_var.draw_felts()[0].clone();
+        let oods_point_var = CirclePointQM31Var::from_channel(&mut channel_var);
+        assert_eq!(random_coeff_var.value(), random_coeff);
+        assert_eq!(oods_point_var.x.value(), oods_point.x);
+        assert_eq!(oods_point_var.y.value(), oods_point.y);
+
+        let sampled_values_var = TreeVec::new(
+            sampled_values
+                .iter()
+                .map(|tree| {
+                    tree.iter()
+                        .map(|column| {
+                            column
+                                .iter()
+                                .map(|value| QM31Var::new_witness(&cs, value))
+                                .collect_vec()
+                        })
+                        .collect_vec()
+                })
+                .collect_vec(),
+        );
+
+        FrameworkCompositionCheck::compute(
+            &[&component as &dyn FrameworkComponentVar],
+            &[claimed_sum_var],
+            &relations,
+            random_coeff_var,
+            oods_point_var,
+            &sampled_values_var,
+        );
+
+        cs.pad();
+        cs.check_arithmetics();
+        cs.populate_logup_arguments();
+        cs.check_poseidon_invocations();
+    }
+}
@@ -4,14 +4,14 @@ use crate::poseidon::evaluate_poseidon;
 use circle_plonk_dsl_constraint_system::var::Var;
 use circle_plonk_dsl_data_structures::{LookupElementsVar, PlonkWithPoseidonProofVar};
 use circle_plonk_dsl_hints::FiatShamirHints;
-use circle_plonk_dsl_primitives::CirclePointQM31Var;
+use circle_plonk_dsl_primitives::{CirclePointQM31Var, MerkleChannelVar};
 use circle_plonk_dsl_primitives::{M31Var, QM31Var};
 use itertools::Itertools;
 use stwo::core::poly::circle::CanonicCoset;
-use stwo::core::vcs::poseidon31_merkle::Poseidon31MerkleChannel;
 use stwo_constraint_framework::PREPROCESSED_TRACE_IDX;
 
 pub mod data_structures;
+pub mod framework;
 pub mod plonk;
 pub mod poseidon;
 
@@ -31,32 +31,18 @@ pub fn coset_vanishing(p: &CirclePointQM31Var, coset_log_size: u32) -> QM31Var {
 pub struct CompositionCheck;
 
 impl CompositionCheck {
-    pub fn compute(
-        fiat_shamir_hints: &FiatShamirHints<Poseidon31MerkleChannel>,
+    pub fn compute<MC: MerkleChannelVar>(
+        fiat_shamir_hints: &FiatShamirHints<MC>,
         lookup_elements: &LookupElementsVar,
         random_coeff: QM31Var,
         oods_point: CirclePointQM31Var,
-        proof: &PlonkWithPoseidonProofVar,
+        proof: &PlonkWithPoseidonProofVar<MC>,
     ) {
         let plonk_tree_subspan = &fiat_shamir_hints.plonk_tree_subspan;
         let plonk_prepared_column_indices = &fiat_shamir_hints.plonk_prepared_column_indices;
         let poseidon_tree_subspan = &fiat_shamir_hints.poseidon_tree_subspan;
         let poseidon_prepared_column_indices = &fiat_shamir_hints.poseidon_prepared_column_indices;
 
-        // enforce that the columns are separate, which would be the case if the Poseidon circuit is
-        // much smaller than the Plonk circuit (expected), so there is_first column does not overlap.
-        assert_eq!(
-            *plonk_prepared_column_indices,
-            vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9]
-        );
-        assert_eq!(
-            *poseidon_prepared_column_indices,
-            vec![
-                10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30,
-                31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49,
-            ]
-        );
-
         let mut evaluation_accumulator = PointEvaluationAccumulatorVar::new(random_coeff);
 
         let eval_row_plonk = {
@@ -150,7 +136,8 @@ mod test {
             fri_config: FriConfig::new(2, 5, 16),
         };
 
-        let fiat_shamir_hints = FiatShamirHints::new(&proof, config, &[(1, QM31::one())]);
+        let fiat_shamir_hints =
+            FiatShamirHints::<Poseidon31MerkleChannel>::new(&proof, config, &[(1, QM31::one())]);
 
         let cs = ConstraintSystemRef::new();
         let proof_var = PlonkWithPoseidonProofVar::new_witness(&cs, &proof);
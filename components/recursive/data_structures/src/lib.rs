@@ -1,21 +1,31 @@
 use circle_plonk_dsl_constraint_system::var::{AllocVar, AllocationMode, Var};
 use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
 use circle_plonk_dsl_hints::{DecommitHints, SinglePairMerkleProof, SinglePathMerkleProof};
+use circle_plonk_dsl_primitives::merkle::{compute_merkle_path_root, MerkleHasherVar};
 use circle_plonk_dsl_primitives::{
-    BitsVar, ChannelVar, HashVar, LinePolyVar, M31Var, Poseidon31MerkleHasherVar, QM31Var,
+    BitsVar, FiatShamirChannelVar, LinePolyVar, M31Var, MerkleChannelVar, QM31Var,
 };
 use std::collections::BTreeMap;
 use stwo::core::fields::m31::M31;
+use stwo::core::fields::qm31::QM31;
 use stwo::core::fri::FriProof;
 use stwo::core::pcs::TreeVec;
 use stwo::core::proof::StarkProof;
-use stwo::core::vcs::poseidon31_merkle::Poseidon31MerkleHasher;
+use stwo::core::vcs::poseidon31_merkle::Poseidon31MerkleChannel;
 use stwo::core::ColumnVec;
 use stwo_examples::plonk_with_poseidon::air::{
     PlonkWithPoseidonProof, PlonkWithPoseidonStatement0, PlonkWithPoseidonStatement1,
 };
 use stwo_examples::plonk_with_poseidon::plonk::PlonkWithAcceleratorLookupElements;
 
+mod public_inputs;
+pub use public_inputs::PlonkPublicInputsVar;
+
+mod verifying_key;
+pub use verifying_key::{
+    PlonkVerifyingKey, PlonkVerifyingKeyMembershipVar, PlonkVerifyingKeySet, PlonkVerifyingKeyVar,
+};
+
 #[derive(Debug, Clone)]
 pub struct PlonkWithPoseidonStatement0Var {
     pub log_size_plonk: M31Var,
@@ -47,9 +57,9 @@ impl AllocVar for PlonkWithPoseidonStatement0Var {
 }
 
 impl PlonkWithPoseidonStatement0Var {
-    pub fn mix_into(&self, channel: &mut ChannelVar) {
-        channel.mix_one_felt(&QM31Var::from(&self.log_size_plonk));
-        channel.mix_one_felt(&QM31Var::from(&self.log_size_poseidon))
+    pub fn mix_into(&self, channel: &mut impl FiatShamirChannelVar) {
+        channel.mix_felts(&[QM31Var::from(&self.log_size_plonk)]);
+        channel.mix_felts(&[QM31Var::from(&self.log_size_poseidon)]);
     }
 }
 
@@ -80,20 +90,24 @@ impl AllocVar for PlonkWithPoseidonStatement1Var {
 }
 
 impl PlonkWithPoseidonStatement1Var {
-    pub fn mix_into(&self, channel: &mut ChannelVar) {
-        channel.mix_two_felts(&self.plonk_total_sum, &self.poseidon_total_sum);
+    pub fn mix_into(&self, channel: &mut impl FiatShamirChannelVar) {
+        channel.mix_felts(&[
+            self.plonk_total_sum.clone(),
+            self.poseidon_total_sum.clone(),
+        ]);
     }
 }
 
+/// A proof of a Plonk-with-Poseidon circuit, committed with the Merkle channel `MC`.
 #[derive(Debug, Clone)]
-pub struct PlonkWithPoseidonProofVar {
+pub struct PlonkWithPoseidonProofVar<MC: MerkleChannelVar = Poseidon31MerkleChannel> {
     pub stmt0: PlonkWithPoseidonStatement0Var,
     pub stmt1: PlonkWithPoseidonStatement1Var,
-    pub stark_proof: StarkProofVar,
+    pub stark_proof: StarkProofVar<MC>,
 }
 
-impl Var for PlonkWithPoseidonProofVar {
-    type Value = PlonkWithPoseidonProof<Poseidon31MerkleHasher>;
+impl<MC: MerkleChannelVar> Var for PlonkWithPoseidonProofVar<MC> {
+    type Value = PlonkWithPoseidonProof<MC::H>;
 
     fn cs(&self) -> ConstraintSystemRef {
         self.stmt0
@@ -103,7 +117,7 @@ impl Var for PlonkWithPoseidonProofVar {
     }
 }
 
-impl AllocVar for PlonkWithPoseidonProofVar {
+impl<MC: MerkleChannelVar> AllocVar for PlonkWithPoseidonProofVar<MC> {
     fn new_variables(cs: &ConstraintSystemRef, value: &Self::Value, mode: AllocationMode) -> Self {
         let stmt0 = PlonkWithPoseidonStatement0Var::new_variables(cs, &value.stmt0, mode);
         let stmt1 = PlonkWithPoseidonStatement1Var::new_variables(cs, &value.stmt1, mode);
@@ -118,28 +132,28 @@ impl AllocVar for PlonkWithPoseidonProofVar {
 }
 
 #[derive(Debug, Clone)]
-pub struct FriProofVar {
+pub struct FriProofVar<MC: MerkleChannelVar = Poseidon31MerkleChannel> {
     pub cs: ConstraintSystemRef,
-    pub first_layer_commitment: HashVar,
-    pub inner_layer_commitments: Vec<HashVar>,
+    pub first_layer_commitment: MC::HashVar,
+    pub inner_layer_commitments: Vec<MC::HashVar>,
     pub last_poly: LinePolyVar,
 }
 
-impl Var for FriProofVar {
-    type Value = FriProof<Poseidon31MerkleHasher>;
+impl<MC: MerkleChannelVar> Var for FriProofVar<MC> {
+    type Value = FriProof<MC::H>;
 
     fn cs(&self) -> ConstraintSystemRef {
         self.cs.clone()
     }
 }
 
-impl AllocVar for FriProofVar {
+impl<MC: MerkleChannelVar> AllocVar for FriProofVar<MC> {
     fn new_variables(cs: &ConstraintSystemRef, value: &Self::Value, mode: AllocationMode) -> Self {
         let first_layer_commitment =
-            HashVar::new_variables(cs, &value.first_layer.commitment, mode);
+            MC::HashVar::new_variables(cs, &value.first_layer.commitment, mode);
         let mut inner_layer_commitments = vec![];
         for layer in value.inner_layers.iter() {
-            inner_layer_commitments.push(HashVar::new_variables(cs, &layer.commitment, mode));
+            inner_layer_commitments.push(MC::HashVar::new_variables(cs, &layer.commitment, mode));
         }
         let last_poly = LinePolyVar::new_variables(cs, &value.last_layer_poly, mode);
 
@@ -153,28 +167,28 @@ impl AllocVar for FriProofVar {
 }
 
 #[derive(Debug, Clone)]
-pub struct StarkProofVar {
+pub struct StarkProofVar<MC: MerkleChannelVar = Poseidon31MerkleChannel> {
     pub cs: ConstraintSystemRef,
 
-    pub commitments: Vec<HashVar>,
+    pub commitments: Vec<MC::HashVar>,
     pub sampled_values: TreeVec<ColumnVec<Vec<QM31Var>>>,
-    pub fri_proof: FriProofVar,
+    pub fri_proof: FriProofVar<MC>,
     pub proof_of_work: [M31Var; 3],
 }
 
-impl Var for StarkProofVar {
-    type Value = StarkProof<Poseidon31MerkleHasher>;
+impl<MC: MerkleChannelVar> Var for StarkProofVar<MC> {
+    type Value = StarkProof<MC::H>;
 
     fn cs(&self) -> ConstraintSystemRef {
         self.cs.clone()
     }
 }
 
-impl AllocVar for StarkProofVar {
+impl<MC: MerkleChannelVar> AllocVar for StarkProofVar<MC> {
     fn new_variables(cs: &ConstraintSystemRef, value: &Self::Value, mode: AllocationMode) -> Self {
         let mut commitments = Vec::with_capacity(value.commitments.len());
         for commitment in value.commitments.iter() {
-            commitments.push(HashVar::new_variables(cs, commitment, mode));
+            commitments.push(MC::HashVar::new_variables(cs, commitment, mode));
         }
 
         let mut sampled_values = TreeVec::new(vec![]);
@@ -237,7 +251,7 @@ impl Var for LookupElementsVar {
 }
 
 impl LookupElementsVar {
-    pub fn draw(channel: &mut ChannelVar) -> Self {
+    pub fn draw(channel: &mut impl FiatShamirChannelVar) -> Self {
         let [z, alpha] = channel.draw_felts();
         Self::from_z_and_alpha(z, alpha)
     }
@@ -264,29 +278,42 @@ impl LookupElementsVar {
             alpha_powers,
         }
     }
+
+    /// The logup contribution of the public inputs, i.e., the sum of `1 / (value + idx * alpha - z)`
+    /// over a list of any length.
+    pub fn public_input_sum(&self, inputs: &[(usize, QM31Var)]) -> QM31Var {
+        let cs = self.cs();
+        let mut input_sum = QM31Var::zero(&cs);
+        for (idx, v) in inputs.iter() {
+            let sum = &(v + &(&QM31Var::new_constant(&cs, &QM31::from(*idx as u32)) * &self.alpha))
+                - &self.z;
+            input_sum = &input_sum + &sum.inv();
+        }
+        input_sum
+    }
 }
 
 #[derive(Debug, Clone)]
-pub struct SinglePathMerkleProofVar {
+pub struct SinglePathMerkleProofVar<MC: MerkleChannelVar = Poseidon31MerkleChannel> {
     pub cs: ConstraintSystemRef,
-    pub value: SinglePathMerkleProof,
-    pub sibling_hashes: Vec<HashVar>,
+    pub value: SinglePathMerkleProof<MC::H>,
+    pub sibling_hashes: Vec<MC::HashVar>,
     pub columns: BTreeMap<usize, Vec<M31Var>>,
 }
 
-impl Var for SinglePathMerkleProofVar {
-    type Value = SinglePathMerkleProof;
+impl<MC: MerkleChannelVar> Var for SinglePathMerkleProofVar<MC> {
+    type Value = SinglePathMerkleProof<MC::H>;
 
     fn cs(&self) -> ConstraintSystemRef {
         self.cs.clone()
     }
 }
 
-impl SinglePathMerkleProofVar {
-    pub fn new(cs: &ConstraintSystemRef, value: &SinglePathMerkleProof) -> Self {
+impl<MC: MerkleChannelVar> SinglePathMerkleProofVar<MC> {
+    pub fn new(cs: &ConstraintSystemRef, value: &SinglePathMerkleProof<MC::H>) -> Self {
         let mut sibling_hashes = vec![];
         for sibling_hash in value.sibling_hashes.iter() {
-            sibling_hashes.push(HashVar::new_single_use_witness_only(cs, &sibling_hash.0));
+            sibling_hashes.push(MC::new_sibling_hash(cs, sibling_hash));
         }
 
         let mut columns = BTreeMap::new();
@@ -310,69 +337,55 @@ impl SinglePathMerkleProofVar {
         &self.columns
     }
 
-    pub fn verify(&mut self, root: &HashVar, query: &BitsVar) {
+    pub fn verify(&mut self, root: &MC::HashVar, query: &BitsVar) {
         // verify that the Merkle proof is valid
         self.value.verify();
-        assert_eq!(root.value(), self.value.root.0);
+        assert_eq!(MC::hash_value(root), self.value.root);
         assert_eq!(query.get_value().0, self.value.query as u32);
 
-        let leaf = self
-            .columns
-            .get(&self.value.depth)
-            .map_or(&[][..], |v| v.as_slice());
-        let mut cur_hash = Poseidon31MerkleHasherVar::hash_m31_columns_get_rate(leaf);
-
-        for i in 0..self.value.depth {
-            let h = self.value.depth - i - 1;
-
-            if self.columns.contains_key(&h) {
-                let columns = self.columns.get(&h).map_or(&[][..], |v| v.as_slice());
-                let column_hash = Poseidon31MerkleHasherVar::hash_m31_columns_get_capacity(columns);
-                cur_hash = Poseidon31MerkleHasherVar::hash_tree_with_column_hash_with_swap(
-                    &cur_hash,
-                    &self.sibling_hashes[i],
-                    &query.0[i],
-                    &column_hash,
-                );
-            } else {
-                cur_hash = Poseidon31MerkleHasherVar::hash_tree_with_swap(
-                    &cur_hash,
-                    &self.sibling_hashes[i],
-                    &query.0[i],
-                );
-            }
-        }
+        let cur_hash = compute_merkle_path_root::<MC::HasherVar>(
+            self.value.depth,
+            query,
+            &self.sibling_hashes,
+            &self.columns,
+        );
 
-        assert_eq!(cur_hash.value(), root.value());
+        assert_eq!(MC::hash_value(&cur_hash), MC::hash_value(root));
 
         // check that the left_variable and right_variable are the same
         // as though in self.root
-        cur_hash.equalverify(root);
+        MC::HasherVar::equalverify(&cur_hash, root);
     }
 }
 
 #[derive(Clone)]
-pub struct SinglePairMerkleProofVar {
+pub struct SinglePairMerkleProofVar<MC: MerkleChannelVar = Poseidon31MerkleChannel> {
     pub cs: ConstraintSystemRef,
-    pub value: SinglePairMerkleProof,
-    pub sibling_hashes: Vec<HashVar>,
+    pub value: SinglePairMerkleProof<MC::H>,
+    pub sibling_hashes: Vec<MC::HashVar>,
+    pub sibling_children: BTreeMap<usize, MC::NodeChildrenVar>,
     pub self_columns: BTreeMap<usize, QM31Var>,
     pub siblings_columns: BTreeMap<usize, QM31Var>,
 }
 
-impl Var for SinglePairMerkleProofVar {
-    type Value = SinglePairMerkleProof;
+impl<MC: MerkleChannelVar> Var for SinglePairMerkleProofVar<MC> {
+    type Value = SinglePairMerkleProof<MC::H>;
 
     fn cs(&self) -> ConstraintSystemRef {
         self.cs.clone()
     }
 }
 
-impl SinglePairMerkleProofVar {
-    pub fn new(cs: &ConstraintSystemRef, value: &SinglePairMerkleProof) -> Self {
+impl<MC: MerkleChannelVar> SinglePairMerkleProofVar<MC> {
+    pub fn new(cs: &ConstraintSystemRef, value: &SinglePairMerkleProof<MC::H>) -> Self {
         let mut sibling_hashes = vec![];
         for sibling_hash in value.sibling_hashes.iter() {
-            sibling_hashes.push(HashVar::new_single_use_witness_only(cs, &sibling_hash.0));
+            sibling_hashes.push(MC::new_sibling_hash(cs, sibling_hash));
+        }
+
+        let mut sibling_children = BTreeMap::new();
+        for (k, (left, right)) in value.sibling_children.iter() {
+            sibling_children.insert(*k, MC::new_node_children(cs, left, right));
         }
 
         let mut self_columns = BTreeMap::new();
@@ -389,95 +402,80 @@ impl SinglePairMerkleProofVar {
             cs: cs.clone(),
             value: value.clone(),
             sibling_hashes,
+            sibling_children,
             self_columns,
             siblings_columns,
         }
     }
 
-    pub fn verify(&mut self, root: &HashVar, query: &BitsVar) {
+    pub fn verify(&mut self, root: &MC::HashVar, query: &BitsVar) {
         // verify that the Merkle proof is valid
         self.value.verify();
-        assert_eq!(root.value(), self.value.root.0);
+        assert_eq!(MC::hash_value(root), self.value.root);
         assert_eq!(query.get_value().0, self.value.query as u32);
 
-        let cs = self.cs().and(&root.cs()).and(&query.cs());
-
-        let mut self_hash = Poseidon31MerkleHasherVar::hash_qm31_columns_get_rate(&[
-            self.self_columns.get(&self.value.depth).unwrap().clone(),
-            QM31Var::zero(&cs),
-        ]);
-        let mut sibling_hash = Poseidon31MerkleHasherVar::hash_qm31_columns_get_rate(&[
-            self.siblings_columns
-                .get(&self.value.depth)
-                .unwrap()
-                .clone(),
-            QM31Var::zero(&cs),
-        ]);
+        let mut self_hash =
+            MC::HasherVar::hash_qm31_leaf(self.self_columns.get(&self.value.depth).unwrap());
+        let mut sibling_hash =
+            MC::HasherVar::hash_qm31_leaf(self.siblings_columns.get(&self.value.depth).unwrap());
 
+        let mut sibling_hashes = self.sibling_hashes.iter();
         for i in 0..self.value.depth {
             let h = self.value.depth - i - 1;
 
-            if !self.self_columns.contains_key(&h) {
-                self_hash = Poseidon31MerkleHasherVar::hash_tree_with_swap(
-                    &self_hash,
-                    &sibling_hash,
-                    &query.0[i],
-                );
-                if i != self.value.depth - 1 {
-                    sibling_hash = self.sibling_hashes[i].clone();
+            match self.self_columns.get(&h) {
+                None => {
+                    self_hash = MC::HasherVar::hash_node_with_swap(
+                        &self_hash,
+                        &sibling_hash,
+                        &query.0[i],
+                        &[],
+                    );
+                    if i != self.value.depth - 1 {
+                        sibling_hash = sibling_hashes.next().unwrap().clone();
+                    }
+                }
+                Some(self_column) => {
+                    (self_hash, sibling_hash) = MC::hash_qm31_node_pair_with_swap(
+                        &self_hash,
+                        &sibling_hash,
+                        &query.0[i],
+                        self_column,
+                        self.sibling_children.get(&h).unwrap(),
+                        self.siblings_columns.get(&h).unwrap(),
+                    );
                 }
-            } else {
-                let self_column_hash =
-                    Poseidon31MerkleHasherVar::hash_qm31_columns_get_capacity(&[
-                        self.self_columns.get(&h).unwrap().clone(),
-                        QM31Var::zero(&cs),
-                    ]);
-                let sibling_column_hash =
-                    Poseidon31MerkleHasherVar::hash_qm31_columns_get_capacity(&[
-                        self.siblings_columns.get(&h).unwrap().clone(),
-                        QM31Var::zero(&cs),
-                    ]);
-
-                self_hash = Poseidon31MerkleHasherVar::hash_tree_with_column_hash_with_swap(
-                    &self_hash,
-                    &sibling_hash,
-                    &query.0[i],
-                    &self_column_hash,
-                );
-                sibling_hash = Poseidon31MerkleHasherVar::combine_hash_tree_with_column(
-                    &self.sibling_hashes[i],
-                    &sibling_column_hash,
-                );
             }
         }
+        assert!(sibling_hashes.next().is_none());
 
-        assert_eq!(self_hash.value(), root.value());
+        assert_eq!(MC::hash_value(&self_hash), MC::hash_value(root));
 
         // check that the left_variable and right_variable are the same
         // as though in self.root
-        self_hash.equalverify(root);
+        MC::HasherVar::equalverify(&self_hash, root);
     }
 }
 
 #[derive(Debug, Clone)]
-pub struct DecommitmentVar {
+pub struct DecommitmentVar<MC: MerkleChannelVar = Poseidon31MerkleChannel> {
     pub cs: ConstraintSystemRef,
-    pub precomputed_proofs: Vec<SinglePathMerkleProofVar>,
-    pub trace_proofs: Vec<SinglePathMerkleProofVar>,
-    pub interaction_proofs: Vec<SinglePathMerkleProofVar>,
-    pub composition_proofs: Vec<SinglePathMerkleProofVar>,
+    pub precomputed_proofs: Vec<SinglePathMerkleProofVar<MC>>,
+    pub trace_proofs: Vec<SinglePathMerkleProofVar<MC>>,
+    pub interaction_proofs: Vec<SinglePathMerkleProofVar<MC>>,
+    pub composition_proofs: Vec<SinglePathMerkleProofVar<MC>>,
 }
 
-impl Var for DecommitmentVar {
-    type Value = DecommitHints;
+impl<MC: MerkleChannelVar> Var for DecommitmentVar<MC> {
+    type Value = DecommitHints<MC::H>;
 
     fn cs(&self) -> ConstraintSystemRef {
         self.cs.clone()
     }
 }
 
-impl DecommitmentVar {
-    pub fn new(cs: &ConstraintSystemRef, value: &DecommitHints) -> Self {
+impl<MC: MerkleChannelVar> DecommitmentVar<MC> {
+    pub fn new(cs: &ConstraintSystemRef, value: &DecommitHints<MC::H>) -> Self {
         let mut precomputed_proofs = vec![];
         for proof in value.precomputed_proofs.iter() {
             precomputed_proofs.push(SinglePathMerkleProofVar::new(cs, proof));
@@ -510,7 +508,7 @@ impl DecommitmentVar {
 
 #[cfg(test)]
 mod test {
-    use crate::{SinglePairMerkleProofVar, SinglePathMerkleProofVar};
+    use crate::{PlonkVerifyingKey, SinglePairMerkleProofVar, SinglePathMerkleProofVar};
     use circle_plonk_dsl_constraint_system::var::AllocVar;
     use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
     use circle_plonk_dsl_hints::{
@@ -520,6 +518,7 @@ mod test {
     use circle_plonk_dsl_primitives::HashVar;
     use circle_plonk_dsl_primitives::M31Var;
     use num_traits::One;
+    use std::path::{Path, PathBuf};
     use stwo::core::fields::m31::M31;
     use stwo::core::fields::qm31::QM31;
     use stwo::core::fri::FriConfig;
@@ -561,7 +560,8 @@ mod test {
         let cs = ConstraintSystemRef::new();
         let root = HashVar::new_witness(&cs, &proof.stark_proof.commitments[0]);
         for proof in proofs.iter() {
-            let mut proof_var = SinglePathMerkleProofVar::new(&cs, proof);
+            let mut proof_var =
+                SinglePathMerkleProofVar::<Poseidon31MerkleChannel>::new(&cs, proof);
             let query = M31Var::new_witness(&cs, &M31::from(proof.query));
             let query_bits = BitsVar::from_m31(&query, proof.depth);
             proof_var.verify(&root, &query_bits);
@@ -577,7 +577,8 @@ mod test {
             fri_config: FriConfig::new(2, 5, 16),
         };
 
-        let fiat_shamir_hints = FiatShamirHints::new(&proof, config, &[(1, QM31::one())]);
+        let fiat_shamir_hints =
+            FiatShamirHints::<Poseidon31MerkleChannel>::new(&proof, config, &[(1, QM31::one())]);
         let answer_hints = AnswerHints::compute(&fiat_shamir_hints, &proof);
         let first_layer_hints = FirstLayerHints::compute(&fiat_shamir_hints, &answer_hints, &proof);
         for proof in first_layer_hints.merkle_proofs.iter() {
@@ -587,10 +588,61 @@ mod test {
         let cs = ConstraintSystemRef::new();
         let root = HashVar::new_witness(&cs, &proof.stark_proof.fri_proof.first_layer.commitment);
         for proof in first_layer_hints.merkle_proofs.iter() {
-            let mut proof_var = SinglePairMerkleProofVar::new(&cs, proof);
+            let mut proof_var =
+                SinglePairMerkleProofVar::<Poseidon31MerkleChannel>::new(&cs, proof);
             let query = M31Var::new_witness(&cs, &M31::from(proof.query));
             let query_bits = BitsVar::from_m31(&query, proof.depth);
             proof_var.verify(&root, &query_bits);
         }
     }
+
+    #[test]
+    fn test_verifying_key() {
+        let proof: PlonkWithPoseidonProof<Poseidon31MerkleHasher> =
+            bincode::deserialize(include_bytes!("../../../test_data/small_proof.bin")).unwrap();
+        let verifying_key = PlonkVerifyingKey::load(Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../test_data/small_proof.vk.json"
+        )))
+        .unwrap();
+        assert!(verifying_key.matches_proof(&proof));
+
+        let path = std::env::temp_dir().join("circle_plonk_dsl_test_verifying_key.json");
+        verifying_key.save(&path).unwrap();
+        let loaded = PlonkVerifyingKey::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, verifying_key);
+    }
+
+    #[test]
+    fn test_committed_verifying_keys() {
+        let data_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../../examples/data");
+        let names = [
+            "initial_proof",
+            "level1_20_8_1",
+            "level2_20_8_3",
+            "level3_23_8_7",
+            "level4_20_8_8",
+            "level5_28_7_9",
+        ];
+
+        let mut keys = vec![];
+        for name in names {
+            let proof: PlonkWithPoseidonProof<Poseidon31MerkleHasher> =
+                bincode::deserialize(&std::fs::read(data_dir.join(format!("{name}.bin"))).unwrap())
+                    .unwrap();
+            let verifying_key =
+                PlonkVerifyingKey::load(&data_dir.join(format!("{name}.vk.json"))).unwrap();
+            assert!(verifying_key.matches_proof(&proof), "{name}");
+            keys.push((verifying_key, proof));
+        }
+
+        // every proof of the chain is for another circuit, so no key accepts another proof
+        for (i, (verifying_key, _)) in keys.iter().enumerate() {
+            for (j, (_, proof)) in keys.iter().enumerate() {
+                assert_eq!(verifying_key.matches_proof(proof), i == j);
+            }
+        }
+    }
 }
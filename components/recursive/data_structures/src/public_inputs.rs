@@ -0,0 +1,196 @@
+use crate::LookupElementsVar;
+use circle_plonk_dsl_constraint_system::var::{AllocVar, Var};
+use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
+use circle_plonk_dsl_primitives::{
+    BitVar, HashVar, M31Var, Poseidon2HalfVar, Poseidon31MerkleHasherVar, QM31Var,
+};
+use itertools::Itertools;
+use num_traits::Zero;
+use std::ops::Neg;
+use stwo::core::fields::m31::M31;
+use stwo::core::fields::qm31::QM31;
+use stwo::core::vcs::poseidon31_hash::Poseidon31Hash;
+use stwo::core::vcs::poseidon31_merkle::Poseidon31MerkleHasher;
+use stwo::core::vcs::MerkleHasher;
+
+/// The public inputs of an inner proof in the commitment mode: the values are witnesses of the
+/// outer circuit, and only their digest is exposed as a public input of the outer circuit, so
+/// that the number of public inputs of the outer circuit does not grow with the inner ones.
+///
+/// The inputs are padded to a fixed number of slots, of which a prefix of variable length is
+/// present, so that inner proofs with different numbers of public inputs share the same circuit.
+#[derive(Debug, Clone)]
+pub struct PlonkPublicInputsVar {
+    pub cs: ConstraintSystemRef,
+    /// The slots, with the indices of the verifying key and the values, which are zero in the
+    /// slots that are not present.
+    pub inputs: Vec<(usize, QM31Var)>,
+    pub is_present: Vec<BitVar>,
+    pub length: M31Var,
+    pub digest: HashVar,
+}
+
+impl Var for PlonkPublicInputsVar {
+    type Value = Vec<(usize, QM31)>;
+
+    fn cs(&self) -> ConstraintSystemRef {
+        self.cs.clone()
+    }
+}
+
+impl PlonkPublicInputsVar {
+    /// The digest of the values of the public inputs, padded with zeros to `n_slots` and prefixed
+    /// with their number, which hashes them as a single Merkle leaf. The indices are not hashed
+    /// since they are fixed by the verifying key.
+    pub fn digest(values: &[QM31], n_slots: usize) -> Poseidon31Hash {
+        assert!(values.len() <= n_slots);
+        let mut m31 = vec![
+            M31::from(values.len() as u32),
+            M31::zero(),
+            M31::zero(),
+            M31::zero(),
+        ];
+        m31.extend(values.iter().flat_map(|v| v.to_m31_array()));
+        m31.resize(4 * (n_slots + 1), M31::zero());
+        Poseidon31MerkleHasher::hash_node(None, &m31)
+    }
+
+    /// Allocate the public inputs as witnesses in the slots given by `indices`, and their digest
+    /// as a public input. The values fill a prefix of the slots.
+    pub fn new_committed(cs: &ConstraintSystemRef, indices: &[usize], values: &[QM31]) -> Self {
+        assert!(!values.is_empty());
+        assert!(values.len() <= indices.len());
+
+        let mut padded_values = values.to_vec();
+        padded_values.resize(indices.len(), QM31::zero());
+        let inputs = indices
+            .iter()
+            .zip_eq(padded_values.iter())
+            .map(|(idx, value)| (*idx, QM31Var::new_witness(cs, value)))
+            .collect_vec();
+
+        let is_present = (0..indices.len())
+            .map(|i| BitVar::new_witness(cs, &(i < values.len())))
+            .collect_vec();
+        // a slot can only be present if the previous one is
+        for pair in is_present.windows(2) {
+            (&pair[1] & &pair[0].neg()).0.equalverify(&M31Var::zero(cs));
+        }
+        // the slots that are not present are zero, so that the digest is unique
+        for ((_, value), is_present) in inputs.iter().zip_eq(is_present.iter()) {
+            (value * &is_present.neg().0).equalverify(&QM31Var::zero(cs));
+        }
+
+        let mut length = M31Var::zero(cs);
+        for is_present in is_present.iter() {
+            length = &length + &is_present.0;
+        }
+
+        let digest = Poseidon2HalfVar::new_public_input(cs, &Self::digest(values, indices.len()));
+        let zero = M31Var::zero(cs);
+        let mut columns = vec![QM31Var::from_m31(&length, &zero, &zero, &zero)];
+        columns.extend(inputs.iter().map(|(_, v)| v.clone()));
+        let computed_digest = Poseidon31MerkleHasherVar::hash_qm31_columns_get_rate(&columns);
+        computed_digest.equalverify(&digest);
+
+        Self {
+            cs: cs.clone(),
+            inputs,
+            is_present,
+            length,
+            digest,
+        }
+    }
+
+    pub fn indices(&self) -> Vec<usize> {
+        self.inputs.iter().map(|(idx, _)| *idx).collect()
+    }
+
+    /// The logup contribution of the present public inputs, see
+    /// [`LookupElementsVar::public_input_sum`].
+    pub fn logup_sum(&self, lookup_elements: &LookupElementsVar) -> QM31Var {
+        let mut sum = QM31Var::zero(&self.cs);
+        for (input, is_present) in self.inputs.iter().zip_eq(self.is_present.iter()) {
+            let input_sum = lookup_elements.public_input_sum(std::slice::from_ref(input));
+            sum = &sum + &(&input_sum * &is_present.0);
+        }
+        sum
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{LookupElementsVar, PlonkPublicInputsVar};
+    use circle_plonk_dsl_constraint_system::var::{AllocVar, Var};
+    use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
+    use circle_plonk_dsl_primitives::QM31Var;
+    use itertools::Itertools;
+    use num_traits::{One, Zero};
+    use stwo::core::fields::m31::M31;
+    use stwo::core::fields::qm31::QM31;
+    use stwo::core::fields::FieldExpOps;
+    use stwo::core::vcs::poseidon31_merkle::Poseidon31MerkleHasher;
+    use stwo::core::vcs::MerkleHasher;
+
+    #[test]
+    fn test_committed_inputs_with_padding() {
+        let cs = ConstraintSystemRef::new();
+
+        // more inputs than a single Poseidon2 rate, in slots 1..=16 of which 11 are present
+        let indices = (1..=16).collect_vec();
+        let values = (0..11u32)
+            .map(|i| QM31::from_u32_unchecked(i, i + 100, i + 200, i + 300))
+            .collect_vec();
+        let public_inputs = PlonkPublicInputsVar::new_committed(&cs, &indices, &values);
+
+        let mut m31 = vec![M31::from(11), M31::zero(), M31::zero(), M31::zero()];
+        for value in values.iter() {
+            m31.extend(value.to_m31_array());
+        }
+        m31.extend(vec![M31::zero(); 4 * 5]);
+        assert_eq!(
+            public_inputs.digest.value(),
+            Poseidon31MerkleHasher::hash_node(None, &m31).0
+        );
+        assert_eq!(public_inputs.length.value, M31::from(11));
+
+        let z = QM31::from_u32_unchecked(1, 2, 3, 4);
+        let alpha = QM31::from_u32_unchecked(5, 6, 7, 8);
+        let lookup_elements = LookupElementsVar {
+            cs: cs.clone(),
+            z: QM31Var::new_witness(&cs, &z),
+            alpha: QM31Var::new_witness(&cs, &alpha),
+            alpha_powers: std::array::from_fn(|i| QM31Var::new_witness(&cs, &alpha.pow(i as u128))),
+        };
+        let expected_sum = values
+            .iter()
+            .zip(indices.iter())
+            .map(|(value, idx)| (*value + QM31::from(*idx as u32) * alpha - z).inverse())
+            .fold(QM31::zero(), |acc, x| acc + x);
+        assert_eq!(
+            public_inputs.logup_sum(&lookup_elements).value(),
+            expected_sum
+        );
+
+        cs.pad();
+        cs.check_arithmetics();
+        cs.populate_logup_arguments();
+        cs.check_poseidon_invocations();
+    }
+
+    #[test]
+    #[should_panic(expected = "is incorrect")]
+    fn test_committed_inputs_reject_nonzero_padding() {
+        let cs = ConstraintSystemRef::new();
+
+        let indices = (1..=16).collect_vec();
+        let values = vec![QM31::one(); 11];
+        let public_inputs = PlonkPublicInputsVar::new_committed(&cs, &indices, &values);
+
+        // a value in a slot that is not present would change the digest of the same inputs
+        cs.set_value(public_inputs.inputs[13].1.variable, QM31::one());
+
+        cs.pad();
+        cs.check_arithmetics();
+    }
+}
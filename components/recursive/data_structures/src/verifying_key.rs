@@ -0,0 +1,280 @@
+use crate::PlonkWithPoseidonStatement0Var;
+use circle_plonk_dsl_constraint_system::var::{AllocVar, AllocationMode, Var};
+use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
+use circle_plonk_dsl_primitives::{
+    HashVar, M31Var, MerkleChannelVar, Poseidon2HalfVar, Poseidon31MerkleHasherVar,
+    SparseMerkleProof, SparseMerkleProofVar, SparseMerkleTree,
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use stwo::core::fields::m31::M31;
+use stwo::core::pcs::PcsConfig;
+use stwo::core::vcs::poseidon31_hash::Poseidon31Hash;
+use stwo::core::vcs::poseidon31_merkle::{Poseidon31MerkleChannel, Poseidon31MerkleHasher};
+use stwo::core::vcs::MerkleHasher;
+use stwo_examples::plonk_with_poseidon::air::PlonkWithPoseidonProof;
+
+/// What the verifier trusts about a Plonk-with-Poseidon circuit, as opposed to what the proof
+/// claims: the preprocessed root, the log sizes of the components, the PCS configuration, and the
+/// indices of the public inputs in the lookup relation.
+///
+/// The preprocessed root is a hash of `H`, the Merkle hasher that the circuit is proven with.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "H::Hash: Serialize",
+    deserialize = "H::Hash: Deserialize<'de>"
+))]
+pub struct PlonkVerifyingKey<H: MerkleHasher = Poseidon31MerkleHasher> {
+    pub preprocessed_root: H::Hash,
+    pub log_size_plonk: u32,
+    pub log_size_poseidon: u32,
+    pub pcs_config: PcsConfig,
+    pub public_input_indices: Vec<usize>,
+}
+
+// implemented by hand, since the Merkle hashers of stwo are not `PartialEq` themselves
+impl<H: MerkleHasher> PartialEq for PlonkVerifyingKey<H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.preprocessed_root == other.preprocessed_root
+            && self.log_size_plonk == other.log_size_plonk
+            && self.log_size_poseidon == other.log_size_poseidon
+            && self.pcs_config == other.pcs_config
+            && self.public_input_indices == other.public_input_indices
+    }
+}
+
+impl<H: MerkleHasher> Eq for PlonkVerifyingKey<H> {}
+
+impl<H: MerkleHasher> PlonkVerifyingKey<H> {
+    /// Extract the key of a circuit from a proof that the caller has just generated for it, which
+    /// is how the prover publishes the key along with the circuit. A verifier must load the
+    /// published key instead, because a key taken from the proof under verification trusts the
+    /// proof for the circuit that it claims to be for.
+    pub fn from_proof(
+        proof: &PlonkWithPoseidonProof<H>,
+        pcs_config: PcsConfig,
+        public_input_indices: &[usize],
+    ) -> Self {
+        Self {
+            preprocessed_root: proof.stark_proof.commitments[0],
+            log_size_plonk: proof.stmt0.log_size_plonk,
+            log_size_poseidon: proof.stmt0.log_size_poseidon,
+            pcs_config,
+            public_input_indices: public_input_indices.to_vec(),
+        }
+    }
+
+    /// Whether the proof claims the preprocessed root and the log sizes of the key, which is a
+    /// native precheck of what the verifier circuit enforces.
+    pub fn matches_proof(&self, proof: &PlonkWithPoseidonProof<H>) -> bool {
+        self.preprocessed_root == proof.stark_proof.commitments[0]
+            && self.log_size_plonk == proof.stmt0.log_size_plonk
+            && self.log_size_poseidon == proof.stmt0.log_size_poseidon
+    }
+
+    /// Whether the two keys only differ in their preprocessed roots, so that the verifier circuit,
+    /// which is generated for the log sizes, the PCS configuration and the public-input layout of
+    /// a key, is the same for both.
+    pub fn has_same_shape(&self, other: &Self) -> bool {
+        self.log_size_plonk == other.log_size_plonk
+            && self.log_size_poseidon == other.log_size_poseidon
+            && self.pcs_config == other.pcs_config
+            && self.public_input_indices == other.public_input_indices
+    }
+
+    /// The parameters of the key other than the preprocessed root, in the order they are hashed.
+    fn parameters(&self) -> [u32; 7] {
+        [
+            self.log_size_plonk,
+            self.log_size_poseidon,
+            self.pcs_config.pow_bits,
+            self.pcs_config.fri_config.log_blowup_factor,
+            self.pcs_config.fri_config.log_last_layer_degree_bound,
+            self.pcs_config.fri_config.n_queries as u32,
+            self.public_input_indices.len() as u32,
+        ]
+    }
+
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        Ok(serde_json::to_writer_pretty(file, self)?)
+    }
+}
+
+impl PlonkVerifyingKey<Poseidon31MerkleHasher> {
+    /// The digest of the key, which is the leaf of the key in a [`PlonkVerifyingKeySet`].
+    pub fn digest(&self) -> Poseidon31Hash {
+        let parameters = self.parameters();
+        let mut digest = Poseidon31MerkleHasher::hash_node(
+            Some((
+                self.preprocessed_root,
+                Poseidon31Hash(std::array::from_fn(|i| {
+                    M31::from(parameters.get(i).copied().unwrap_or_default())
+                })),
+            )),
+            &[],
+        );
+        for chunk in self.public_input_indices.chunks(8) {
+            let chunk = Poseidon31Hash(std::array::from_fn(|i| {
+                M31::from(chunk.get(i).copied().unwrap_or_default() as u32)
+            }));
+            digest = Poseidon31MerkleHasher::hash_node(Some((digest, chunk)), &[]);
+        }
+        digest
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PlonkVerifyingKeyVar<MC: MerkleChannelVar = Poseidon31MerkleChannel> {
+    pub cs: ConstraintSystemRef,
+    pub value: PlonkVerifyingKey<MC::H>,
+    pub preprocessed_root: MC::HashVar,
+    /// The log sizes as circuit variables, which are only allocated when the key is not a
+    /// constant, because a constant preprocessed root already pins the sizes of the circuit.
+    pub log_sizes: Option<(M31Var, M31Var)>,
+}
+
+impl<MC: MerkleChannelVar> Var for PlonkVerifyingKeyVar<MC> {
+    type Value = PlonkVerifyingKey<MC::H>;
+
+    fn cs(&self) -> ConstraintSystemRef {
+        self.cs.clone()
+    }
+}
+
+impl<MC: MerkleChannelVar> AllocVar for PlonkVerifyingKeyVar<MC> {
+    fn new_variables(cs: &ConstraintSystemRef, value: &Self::Value, mode: AllocationMode) -> Self {
+        let preprocessed_root = MC::HashVar::new_variables(cs, &value.preprocessed_root, mode);
+        let log_sizes = if mode == AllocationMode::Constant {
+            None
+        } else {
+            Some((
+                M31Var::new_variables(cs, &M31::from(value.log_size_plonk), mode),
+                M31Var::new_variables(cs, &M31::from(value.log_size_poseidon), mode),
+            ))
+        };
+
+        Self {
+            cs: cs.clone(),
+            value: value.clone(),
+            preprocessed_root,
+            log_sizes,
+        }
+    }
+}
+
+impl<MC: MerkleChannelVar> PlonkVerifyingKeyVar<MC> {
+    /// Enforce that the statement of the proof matches the log sizes of the key.
+    pub fn enforce_log_sizes(&self, stmt0: &PlonkWithPoseidonStatement0Var) {
+        if let Some((log_size_plonk, log_size_poseidon)) = &self.log_sizes {
+            log_size_plonk.equalverify(&stmt0.log_size_plonk);
+            log_size_poseidon.equalverify(&stmt0.log_size_poseidon);
+        } else {
+            assert_eq!(stmt0.log_size_plonk.value.0, self.value.log_size_plonk);
+            assert_eq!(
+                stmt0.log_size_poseidon.value.0,
+                self.value.log_size_poseidon
+            );
+        }
+    }
+}
+
+impl PlonkVerifyingKeyVar<Poseidon31MerkleChannel> {
+    /// Compute the digest of the key in the circuit, see [`PlonkVerifyingKey::digest`]. The PCS
+    /// configuration and the public-input layout shape the circuit and are therefore constants.
+    pub fn digest(&self) -> HashVar {
+        let cs = self.cs();
+        let (log_size_plonk, log_size_poseidon) = self
+            .log_sizes
+            .clone()
+            .expect("the digest of a constant verifying key is a constant");
+
+        let parameters = self.value.parameters();
+        let mut parameter_vars = vec![log_size_plonk, log_size_poseidon];
+        for parameter in parameters.iter().skip(2) {
+            parameter_vars.push(M31Var::new_constant(&cs, &M31::from(*parameter)));
+        }
+        parameter_vars.push(M31Var::zero(&cs));
+
+        let mut digest = Poseidon31MerkleHasherVar::hash_tree(
+            &self.preprocessed_root,
+            &Poseidon2HalfVar::from_m31(&parameter_vars),
+        );
+        for chunk in self.value.public_input_indices.chunks(8) {
+            let chunk = Poseidon2HalfVar::new_constant(
+                &cs,
+                &Poseidon31Hash(std::array::from_fn(|i| {
+                    M31::from(chunk.get(i).copied().unwrap_or_default() as u32)
+                })),
+            );
+            digest = Poseidon31MerkleHasherVar::hash_tree(&digest, &chunk);
+        }
+        digest
+    }
+}
+
+/// A committed set of allowed verifying keys, stored as a sparse Merkle tree from the index of a
+/// key to its digest.
+///
+/// Only the preprocessed root of the key is a witness of the verifier circuit, while the rest of
+/// the circuit is generated for the log sizes, the PCS configuration and the public-input layout
+/// of the key. The keys of a set must therefore have the same shape, see
+/// [`PlonkVerifyingKey::has_same_shape`], and only differ in their preprocessed roots.
+#[derive(Clone, Debug)]
+pub struct PlonkVerifyingKeySet {
+    pub keys: Vec<PlonkVerifyingKey>,
+    pub tree: SparseMerkleTree,
+}
+
+impl PlonkVerifyingKeySet {
+    pub fn new(keys: Vec<PlonkVerifyingKey>, depth: usize) -> Self {
+        assert!(keys.len() <= 1 << depth);
+        assert!(
+            keys.iter().all(|key| key.has_same_shape(&keys[0])),
+            "the allowed verifying keys must only differ in their preprocessed roots"
+        );
+        let mut tree = SparseMerkleTree::new(depth);
+        for (i, key) in keys.iter().enumerate() {
+            tree.insert(i as u32, key.digest());
+        }
+        Self { keys, tree }
+    }
+
+    pub fn root(&self) -> Poseidon31Hash {
+        self.tree.root()
+    }
+
+    pub fn prove(&self, key: &PlonkVerifyingKey) -> Option<SparseMerkleProof> {
+        let index = self.keys.iter().position(|k| k == key)?;
+        Some(self.tree.prove(index as u32))
+    }
+}
+
+/// The in-circuit proof that a verifying key belongs to a [`PlonkVerifyingKeySet`], whose root is
+/// a public input.
+#[derive(Debug, Clone)]
+pub struct PlonkVerifyingKeyMembershipVar {
+    pub root: HashVar,
+    pub index: M31Var,
+    pub path: SparseMerkleProofVar,
+}
+
+impl PlonkVerifyingKeyMembershipVar {
+    pub fn new(cs: &ConstraintSystemRef, root: Poseidon31Hash, proof: &SparseMerkleProof) -> Self {
+        Self {
+            root: Poseidon2HalfVar::new_public_input(cs, &root),
+            index: M31Var::new_witness(cs, &M31::from(proof.key)),
+            path: SparseMerkleProofVar::new_witness(cs, proof),
+        }
+    }
+
+    pub fn enforce(&self, verifying_key: &PlonkVerifyingKeyVar) {
+        self.path
+            .verify_membership(&self.root, &self.index, &verifying_key.digest());
+    }
+}
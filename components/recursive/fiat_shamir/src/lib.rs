@@ -1,21 +1,22 @@
 use circle_plonk_dsl_constraint_system::var::{AllocVar, Var};
-use circle_plonk_dsl_data_structures::{LookupElementsVar, PlonkWithPoseidonProofVar};
+use circle_plonk_dsl_data_structures::{
+    LookupElementsVar, PlonkPublicInputsVar, PlonkVerifyingKeyMembershipVar, PlonkVerifyingKeyVar,
+    PlonkWithPoseidonProofVar,
+};
 use circle_plonk_dsl_hints::FiatShamirHints;
+use circle_plonk_dsl_primitives::merkle::MerkleHasherVar;
 use circle_plonk_dsl_primitives::{
-    BitsVar, ChannelVar, CirclePointQM31Var, HashVar, Poseidon2HalfVar,
+    CirclePointQM31Var, FiatShamirChannelVar, MerkleChannelVar, TranscriptLog,
 };
 use circle_plonk_dsl_primitives::{M31Var, QM31Var};
-use stwo::core::fields::qm31::QM31;
 use stwo::core::fields::FieldExpOps;
-use stwo::core::pcs::PcsConfig;
-use stwo::core::vcs::poseidon31_hash::Poseidon31Hash;
 use stwo::core::vcs::poseidon31_merkle::Poseidon31MerkleChannel;
 
-pub struct FiatShamirResults {
-    pub preprocessed_commitment: HashVar,
-    pub trace_commitment: HashVar,
-    pub interaction_trace_commitment: HashVar,
-    pub composition_commitment: HashVar,
+pub struct FiatShamirResults<MC: MerkleChannelVar = Poseidon31MerkleChannel> {
+    pub preprocessed_commitment: MC::HashVar,
+    pub trace_commitment: MC::HashVar,
+    pub interaction_trace_commitment: MC::HashVar,
+    pub composition_commitment: MC::HashVar,
 
     pub plonk_total_sum: QM31Var,
     pub poseidon_total_sum: QM31Var,
@@ -26,28 +27,98 @@ pub struct FiatShamirResults {
     pub raw_queries: Vec<M31Var>,
 
     pub fri_alphas: Vec<QM31Var>,
+
+    /// The log of the channel operations, to be compared with [`FiatShamirHints::transcript`],
+    /// which is only kept by [`FiatShamirResults::compute_with_transcript_log`].
+    pub transcript: Option<TranscriptLog>,
 }
 
-impl FiatShamirResults {
+impl<MC: MerkleChannelVar> FiatShamirResults<MC> {
+    /// Compute the Fiat-Shamir results in the public-input commitment mode, where the public inputs
+    /// of the inner proof are witnesses and only their digest is a public input. The verifying key
+    /// lists the indices of all the slots, of which only the present ones are in the logup sum.
+    pub fn compute_with_committed_inputs(
+        fiat_shamir_hints: &FiatShamirHints<MC>,
+        proof: &mut PlonkWithPoseidonProofVar<MC>,
+        verifying_key: &PlonkVerifyingKeyVar<MC>,
+        public_inputs: &PlonkPublicInputsVar,
+    ) -> Self {
+        Self::compute_inner(
+            fiat_shamir_hints,
+            proof,
+            verifying_key,
+            &public_inputs.indices(),
+            |lookup_elements| public_inputs.logup_sum(lookup_elements),
+            false,
+        )
+    }
+
     pub fn compute(
-        fiat_shamir_hints: &FiatShamirHints<Poseidon31MerkleChannel>,
-        proof: &mut PlonkWithPoseidonProofVar,
-        pcs_config: PcsConfig,
+        fiat_shamir_hints: &FiatShamirHints<MC>,
+        proof: &mut PlonkWithPoseidonProofVar<MC>,
+        verifying_key: &PlonkVerifyingKeyVar<MC>,
         inputs: &[(usize, QM31Var)],
+    ) -> Self {
+        Self::compute_inner(
+            fiat_shamir_hints,
+            proof,
+            verifying_key,
+            &inputs.iter().map(|(idx, _)| *idx).collect::<Vec<_>>(),
+            |lookup_elements| lookup_elements.public_input_sum(inputs),
+            false,
+        )
+    }
+
+    /// Same as [`FiatShamirResults::compute`], but also log the channel operations, so that the
+    /// transcript can be compared with the one of the native verifier when a proof fails to verify.
+    pub fn compute_with_transcript_log(
+        fiat_shamir_hints: &FiatShamirHints<MC>,
+        proof: &mut PlonkWithPoseidonProofVar<MC>,
+        verifying_key: &PlonkVerifyingKeyVar<MC>,
+        inputs: &[(usize, QM31Var)],
+    ) -> Self {
+        Self::compute_inner(
+            fiat_shamir_hints,
+            proof,
+            verifying_key,
+            &inputs.iter().map(|(idx, _)| *idx).collect::<Vec<_>>(),
+            |lookup_elements| lookup_elements.public_input_sum(inputs),
+            true,
+        )
+    }
+
+    fn compute_inner(
+        fiat_shamir_hints: &FiatShamirHints<MC>,
+        proof: &mut PlonkWithPoseidonProofVar<MC>,
+        verifying_key: &PlonkVerifyingKeyVar<MC>,
+        input_indices: &[usize],
+        input_sum: impl FnOnce(&LookupElementsVar) -> QM31Var,
+        log_transcript: bool,
     ) -> Self {
         let cs = proof.cs();
+        let pcs_config = verifying_key.value.pcs_config;
+        assert_eq!(
+            fiat_shamir_hints.config, pcs_config,
+            "the hints have been computed with a PCS configuration other than the verifying key's"
+        );
+        assert_eq!(
+            input_indices, verifying_key.value.public_input_indices,
+            "the public inputs do not follow the layout of the verifying key"
+        );
 
         let preprocessed_commitment = proof.stark_proof.commitments[0].clone();
         let trace_commitment = proof.stark_proof.commitments[1].clone();
         let interaction_trace_commitment = proof.stark_proof.commitments[2].clone();
         let composition_commitment = proof.stark_proof.commitments[3].clone();
 
-        // fix the preprocessed commitment
-        let preprocessed_commitment_constant =
-            Poseidon2HalfVar::new_constant(&cs, &Poseidon31Hash(preprocessed_commitment.value));
-        preprocessed_commitment_constant.equalverify(&preprocessed_commitment);
+        // fix the preprocessed commitment and the log sizes to the verifying key
+        MC::HasherVar::equalverify(&verifying_key.preprocessed_root, &preprocessed_commitment);
+        verifying_key.enforce_log_sizes(&proof.stmt0);
 
-        let mut channel = ChannelVar::default(&cs);
+        let mut channel = MC::ChannelVar::default(&cs);
+        if log_transcript {
+            channel.enable_transcript_log();
+        }
 
         // Preprocessed trace.
         channel.mix_root(&preprocessed_commitment);
@@ -72,13 +143,7 @@ impl FiatShamirResults {
         let oods_point = CirclePointQM31Var::from_channel(&mut channel);
 
         let sampled_values_flattened = proof.stark_proof.sampled_values.clone().flatten_cols();
-        for chunk in sampled_values_flattened.chunks(2) {
-            if chunk.len() == 1 {
-                channel.mix_one_felt(&chunk[0]);
-            } else {
-                channel.mix_two_felts(&chunk[0], &chunk[1]);
-            }
-        }
+        channel.mix_felts(&sampled_values_flattened);
 
         let after_sampled_values_random_coeff = channel.draw_felts()[0].clone();
 
@@ -92,52 +157,15 @@ impl FiatShamirResults {
             fri_alphas.push(channel.draw_felts()[0].clone());
         }
 
-        for chunk in proof.stark_proof.fri_proof.last_poly.coeffs.chunks(2) {
-            if chunk.len() == 1 {
-                channel.mix_one_felt(&chunk[0]);
-            } else {
-                channel.mix_two_felts(&chunk[0], &chunk[1]);
-            }
-        }
+        channel.mix_felts(&proof.stark_proof.fri_proof.last_poly.coeffs);
 
-        let nonce_felt = QM31Var::from_m31(
-            &proof.stark_proof.proof_of_work[0],
-            &proof.stark_proof.proof_of_work[1],
-            &proof.stark_proof.proof_of_work[2],
-            &M31Var::zero(&cs),
-        );
-
-        let _ = BitsVar::from_m31(&proof.stark_proof.proof_of_work[0], 22);
-        let _ = BitsVar::from_m31(&proof.stark_proof.proof_of_work[1], 21);
-        let _ = BitsVar::from_m31(&proof.stark_proof.proof_of_work[2], 21);
-
-        channel.mix_one_felt(&nonce_felt);
-
-        let lower_bits = BitsVar::from_m31(&channel.digest.to_qm31()[0].decompose_m31()[0], 31)
-            .compose_range(0..pcs_config.pow_bits as usize);
-        lower_bits.equalverify(&M31Var::zero(&cs));
+        channel.mix_nonce(&proof.stark_proof.proof_of_work);
+        channel.check_pow(pcs_config.pow_bits);
 
-        let mut raw_queries = Vec::with_capacity(pcs_config.fri_config.n_queries);
-        let mut draw_queries_felts =
-            Vec::with_capacity(pcs_config.fri_config.n_queries.div_ceil(4));
-        for _ in 0..pcs_config.fri_config.n_queries.div_ceil(4) {
-            let [a, b] = channel.draw_felts();
-            draw_queries_felts.push(a);
-            draw_queries_felts.push(b);
-        }
-        for felt in draw_queries_felts.iter() {
-            raw_queries.extend_from_slice(&felt.decompose_m31());
-        }
-        raw_queries.truncate(pcs_config.fri_config.n_queries);
+        let raw_queries = channel.draw_raw_queries(pcs_config.fri_config.n_queries);
 
         // enforce the total sum
-        let mut input_sum = QM31Var::zero(&cs);
-        for (idx, v) in inputs.iter() {
-            let sum = &(v + &(&QM31Var::new_constant(&cs, &QM31::from(*idx as u32))
-                * &lookup_elements.alpha))
-                - &lookup_elements.z;
-            input_sum = &input_sum + &sum.inv();
-        }
+        let input_sum = input_sum(&lookup_elements);
         (&(&input_sum + &proof.stmt1.poseidon_total_sum) + &proof.stmt1.plonk_total_sum)
             .equalverify(&QM31Var::zero(&cs));
 
@@ -173,28 +201,67 @@ impl FiatShamirResults {
             oods_point,
             raw_queries,
             fri_alphas,
+            transcript: channel.transcript_log(),
         }
     }
 }
 
+impl FiatShamirResults<Poseidon31MerkleChannel> {
+    /// Compute the Fiat-Shamir results for a proof of any circuit whose verifying key belongs to
+    /// a committed set, where the verifying key is a witness and the root of the set is a public
+    /// input.
+    ///
+    /// The rest of the verifier is generated for the shape of the key in the hints, so the set
+    /// must only contain keys of that shape, see
+    /// [`circle_plonk_dsl_data_structures::PlonkVerifyingKeySet`].
+    pub fn compute_with_allowed_keys(
+        fiat_shamir_hints: &FiatShamirHints<Poseidon31MerkleChannel>,
+        proof: &mut PlonkWithPoseidonProofVar,
+        verifying_key: &PlonkVerifyingKeyVar,
+        membership: &PlonkVerifyingKeyMembershipVar,
+        inputs: &[(usize, QM31Var)],
+    ) -> Self {
+        assert!(
+            verifying_key.log_sizes.is_some(),
+            "the verifying key must be a witness to be checked against a set of allowed keys"
+        );
+        membership.enforce(verifying_key);
+        Self::compute(fiat_shamir_hints, proof, verifying_key, inputs)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::FiatShamirResults;
     use circle_plonk_dsl_constraint_system::var::AllocVar;
     use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
-    use circle_plonk_dsl_data_structures::PlonkWithPoseidonProofVar;
+    use circle_plonk_dsl_data_structures::{
+        PlonkPublicInputsVar, PlonkVerifyingKey, PlonkVerifyingKeyMembershipVar,
+        PlonkVerifyingKeySet, PlonkVerifyingKeyVar, PlonkWithPoseidonProofVar,
+    };
     use circle_plonk_dsl_hints::FiatShamirHints;
-    use circle_plonk_dsl_primitives::QM31Var;
+    use circle_plonk_dsl_primitives::{compare_transcripts, QM31Var};
     use num_traits::One;
+    use std::path::{Path, PathBuf};
     use stwo::core::fields::m31::M31;
     use stwo::core::fields::qm31::QM31;
     use stwo::core::fri::FriConfig;
     use stwo::core::pcs::PcsConfig;
+    use stwo::core::vcs::poseidon31_hash::Poseidon31Hash;
     use stwo::core::vcs::poseidon31_merkle::{Poseidon31MerkleChannel, Poseidon31MerkleHasher};
     use stwo_examples::plonk_with_poseidon::air::{
         prove_plonk_with_poseidon, verify_plonk_with_poseidon, PlonkWithPoseidonProof,
     };
 
+    /// The committed verifying key of `small_proof.bin`.
+    fn small_proof_verifying_key() -> PlonkVerifyingKey {
+        PlonkVerifyingKey::load(Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../test_data/small_proof.vk.json"
+        )))
+        .unwrap()
+    }
+
     #[test]
     fn test_fiat_shamir() {
         let proof: PlonkWithPoseidonProof<Poseidon31MerkleHasher> =
@@ -204,17 +271,25 @@ mod test {
             fri_config: FriConfig::new(2, 5, 16),
         };
 
-        let fiat_shamir_hints = FiatShamirHints::new(&proof, config, &[(1, QM31::one())]);
+        let fiat_shamir_hints =
+            FiatShamirHints::<Poseidon31MerkleChannel>::new(&proof, config, &[(1, QM31::one())]);
 
         let cs = ConstraintSystemRef::new();
         let mut proof_var = PlonkWithPoseidonProofVar::new_witness(&cs, &proof);
+        let verifying_key = PlonkVerifyingKeyVar::new_constant(&cs, &small_proof_verifying_key());
 
-        let _results = FiatShamirResults::compute(
+        let results = FiatShamirResults::compute_with_transcript_log(
             &fiat_shamir_hints,
             &mut proof_var,
-            config,
+            &verifying_key,
             &[(1, QM31Var::one(&cs))],
         );
+        if let Err(divergence) = compare_transcripts(
+            results.transcript.as_ref().unwrap(),
+            &fiat_shamir_hints.transcript(&proof),
+        ) {
+            panic!("{}", divergence);
+        }
 
         cs.pad();
         cs.check_arithmetics();
@@ -249,4 +324,185 @@ mod test {
         )
         .unwrap();
     }
+
+    #[test]
+    fn test_fiat_shamir_with_allowed_keys() {
+        let proof: PlonkWithPoseidonProof<Poseidon31MerkleHasher> =
+            bincode::deserialize(include_bytes!("../../../test_data/small_proof.bin")).unwrap();
+        let config = PcsConfig {
+            pow_bits: 20,
+            fri_config: FriConfig::new(2, 5, 16),
+        };
+
+        let fiat_shamir_hints =
+            FiatShamirHints::<Poseidon31MerkleChannel>::new(&proof, config, &[(1, QM31::one())]);
+
+        let verifying_key = small_proof_verifying_key();
+        // a circuit of the same shape with other preprocessed columns
+        let mut other_key = verifying_key.clone();
+        other_key.preprocessed_root.0[0] += M31::one();
+        let key_set = PlonkVerifyingKeySet::new(vec![other_key, verifying_key.clone()], 4);
+        let path = key_set.prove(&verifying_key).unwrap();
+
+        let cs = ConstraintSystemRef::new();
+        let mut proof_var = PlonkWithPoseidonProofVar::new_witness(&cs, &proof);
+        let verifying_key_var = PlonkVerifyingKeyVar::new_witness(&cs, &verifying_key);
+        let membership = PlonkVerifyingKeyMembershipVar::new(&cs, key_set.root(), &path);
+
+        let results = FiatShamirResults::compute_with_allowed_keys(
+            &fiat_shamir_hints,
+            &mut proof_var,
+            &verifying_key_var,
+            &membership,
+            &[(1, QM31Var::one(&cs))],
+        );
+        // the channel operations are only logged on request
+        assert!(results.transcript.is_none());
+
+        cs.pad();
+        cs.check_arithmetics();
+        cs.populate_logup_arguments();
+        cs.check_poseidon_invocations();
+    }
+
+    #[test]
+    #[should_panic(expected = "only differ in their preprocessed roots")]
+    fn test_allowed_keys_reject_other_shape() {
+        // the verifier circuit of the set would be generated for the log sizes of one key only
+        let verifying_key = small_proof_verifying_key();
+        let mut other_key = verifying_key.clone();
+        other_key.log_size_plonk += 1;
+        let _ = PlonkVerifyingKeySet::new(vec![other_key, verifying_key], 4);
+    }
+
+    #[test]
+    fn test_fiat_shamir_with_committed_inputs() {
+        let proof: PlonkWithPoseidonProof<Poseidon31MerkleHasher> =
+            bincode::deserialize(include_bytes!("../../../test_data/small_proof.bin")).unwrap();
+        let config = PcsConfig {
+            pow_bits: 20,
+            fri_config: FriConfig::new(2, 5, 16),
+        };
+
+        let inputs = [(1, QM31::one())];
+        let fiat_shamir_hints =
+            FiatShamirHints::<Poseidon31MerkleChannel>::new(&proof, config, &inputs);
+
+        let cs = ConstraintSystemRef::new();
+        let mut proof_var = PlonkWithPoseidonProofVar::new_witness(&cs, &proof);
+        let verifying_key = PlonkVerifyingKeyVar::new_constant(
+            &cs,
+            &PlonkVerifyingKey {
+                public_input_indices: vec![1, 2, 3, 4],
+                ..small_proof_verifying_key()
+            },
+        );
+        // the single public input of the proof is in the first of four slots
+        let public_inputs = PlonkPublicInputsVar::new_committed(&cs, &[1, 2, 3, 4], &[QM31::one()]);
+        assert_eq!(
+            public_inputs.digest.value(),
+            PlonkPublicInputsVar::digest(&[QM31::one()], 4).0
+        );
+
+        let _results = FiatShamirResults::compute_with_committed_inputs(
+            &fiat_shamir_hints,
+            &mut proof_var,
+            &verifying_key,
+            &public_inputs,
+        );
+
+        cs.pad();
+        cs.check_arithmetics();
+        cs.populate_logup_arguments();
+        cs.check_poseidon_invocations();
+    }
+
+    #[test]
+    #[should_panic(expected = "PCS configuration")]
+    fn test_fiat_shamir_rejects_key_with_other_config() {
+        let proof: PlonkWithPoseidonProof<Poseidon31MerkleHasher> =
+            bincode::deserialize(include_bytes!("../../../test_data/small_proof.bin")).unwrap();
+        let config = PcsConfig {
+            pow_bits: 20,
+            fri_config: FriConfig::new(2, 5, 16),
+        };
+        let other_config = PcsConfig {
+            pow_bits: 21,
+            fri_config: FriConfig::new(2, 5, 16),
+        };
+
+        let fiat_shamir_hints =
+            FiatShamirHints::<Poseidon31MerkleChannel>::new(&proof, config, &[(1, QM31::one())]);
+
+        let cs = ConstraintSystemRef::new();
+        let mut proof_var = PlonkWithPoseidonProofVar::new_witness(&cs, &proof);
+        let verifying_key = PlonkVerifyingKeyVar::new_constant(
+            &cs,
+            &PlonkVerifyingKey {
+                pcs_config: other_config,
+                ..small_proof_verifying_key()
+            },
+        );
+
+        let _ = FiatShamirResults::compute(
+            &fiat_shamir_hints,
+            &mut proof_var,
+            &verifying_key,
+            &[(1, QM31Var::one(&cs))],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "is incorrect")]
+    fn test_fiat_shamir_rejects_key_of_other_circuit() {
+        // the first two recursive proofs of the example have the same log sizes, so that only the
+        // preprocessed roots of their keys differ
+        let data_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../../examples/data");
+        let proof: PlonkWithPoseidonProof<Poseidon31MerkleHasher> =
+            bincode::deserialize(&std::fs::read(data_dir.join("level2_20_8_3.bin")).unwrap())
+                .unwrap();
+        let verifying_key = PlonkVerifyingKey {
+            preprocessed_root: PlonkVerifyingKey::load(&data_dir.join("level1_20_8_1.vk.json"))
+                .unwrap()
+                .preprocessed_root,
+            ..PlonkVerifyingKey::load(&data_dir.join("level2_20_8_3.vk.json")).unwrap()
+        };
+        assert!(!verifying_key.matches_proof(&proof));
+
+        let output_hash: Poseidon31Hash = serde_json::from_str(
+            &std::fs::read_to_string(data_dir.join("output_hash.txt")).unwrap(),
+        )
+        .unwrap();
+        let h = output_hash.0;
+        let inputs = [
+            (1, QM31::one()),
+            (2, QM31::from_u32_unchecked(0, 1, 0, 0)),
+            (3, QM31::from_u32_unchecked(0, 0, 1, 0)),
+            (4, QM31::from_m31(h[0], h[1], h[2], h[3])),
+            (5, QM31::from_m31(h[4], h[5], h[6], h[7])),
+        ];
+        let fiat_shamir_hints = FiatShamirHints::<Poseidon31MerkleChannel>::new(
+            &proof,
+            verifying_key.pcs_config,
+            &inputs,
+        );
+
+        let cs = ConstraintSystemRef::new();
+        let mut proof_var = PlonkWithPoseidonProofVar::new_witness(&cs, &proof);
+        let verifying_key_var = PlonkVerifyingKeyVar::new_constant(&cs, &verifying_key);
+        let input_vars = inputs
+            .iter()
+            .map(|(idx, value)| (*idx, QM31Var::new_constant(&cs, value)))
+            .collect::<Vec<_>>();
+
+        let _ = FiatShamirResults::compute(
+            &fiat_shamir_hints,
+            &mut proof_var,
+            &verifying_key_var,
+            &input_vars,
+        );
+
+        cs.pad();
+        cs.check_arithmetics();
+    }
 }
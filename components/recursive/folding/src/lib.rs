@@ -2,27 +2,26 @@ use circle_plonk_dsl_answer::AnswerResults;
 use circle_plonk_dsl_data_structures::{PlonkWithPoseidonProofVar, SinglePairMerkleProofVar};
 use circle_plonk_dsl_fiat_shamir::FiatShamirResults;
 use circle_plonk_dsl_hints::{FiatShamirHints, FirstLayerHints, InnerLayersHints};
-use circle_plonk_dsl_primitives::QM31Var;
+use circle_plonk_dsl_primitives::{MerkleChannelVar, QM31Var};
 use std::collections::{BTreeMap, HashMap};
-use stwo::core::vcs::poseidon31_merkle::Poseidon31MerkleChannel;
 
 pub struct FoldingResults;
 
 impl FoldingResults {
-    pub fn compute(
-        proof_var: &PlonkWithPoseidonProofVar,
-        fiat_shamir_hints: &FiatShamirHints<Poseidon31MerkleChannel>,
-        fiat_shamir_results: &FiatShamirResults,
+    pub fn compute<MC: MerkleChannelVar>(
+        proof_var: &PlonkWithPoseidonProofVar<MC>,
+        fiat_shamir_hints: &FiatShamirHints<MC>,
+        fiat_shamir_results: &FiatShamirResults<MC>,
         answer_results: &AnswerResults,
-        first_layer_hints: &FirstLayerHints,
-        inner_layers_hints: &InnerLayersHints,
+        first_layer_hints: &FirstLayerHints<MC::H>,
+        inner_layers_hints: &InnerLayersHints<MC::H>,
     ) {
         let cs = answer_results.cs.clone();
 
         // allocate all the first layer merkle proofs
         let mut proofs = vec![];
         for (i, proof) in first_layer_hints.merkle_proofs.iter().enumerate() {
-            let mut proof = SinglePairMerkleProofVar::new(&cs, proof);
+            let mut proof = SinglePairMerkleProofVar::<MC>::new(&cs, proof);
             proof.verify(
                 &proof_var.stark_proof.fri_proof.first_layer_commitment,
                 &answer_results.query_positions_per_log_size
@@ -148,7 +147,7 @@ impl FoldingResults {
             for ((folded_result, query), proof) in
                 folded.iter().zip(queries.iter()).zip(merkle_proofs.iter())
             {
-                let mut merkle_proof = SinglePairMerkleProofVar::new(&cs, proof);
+                let mut merkle_proof = SinglePairMerkleProofVar::<MC>::new(&cs, proof);
 
                 let self_val = merkle_proof.self_columns.get(&(log_size as usize)).unwrap();
                 let sibling_val = merkle_proof
@@ -200,7 +199,9 @@ mod test {
     use circle_plonk_dsl_answer::AnswerResults;
     use circle_plonk_dsl_constraint_system::var::AllocVar;
     use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
-    use circle_plonk_dsl_data_structures::PlonkWithPoseidonProofVar;
+    use circle_plonk_dsl_data_structures::{
+        PlonkVerifyingKey, PlonkVerifyingKeyVar, PlonkWithPoseidonProofVar,
+    };
     use circle_plonk_dsl_fiat_shamir::FiatShamirResults;
     use circle_plonk_dsl_hints::{
         AnswerHints, DecommitHints, FiatShamirHints, FirstLayerHints, InnerLayersHints,
@@ -208,23 +209,21 @@ mod test {
     use circle_plonk_dsl_primitives::CirclePointQM31Var;
     use circle_plonk_dsl_primitives::QM31Var;
     use num_traits::One;
+    use std::path::Path;
     use stwo::core::fields::m31::M31;
     use stwo::core::fields::qm31::QM31;
     use stwo::core::fri::FriConfig;
     use stwo::core::pcs::PcsConfig;
+    use stwo::core::vcs::blake2_merkle::Blake2sMerkleChannel;
     use stwo::core::vcs::poseidon31_merkle::{Poseidon31MerkleChannel, Poseidon31MerkleHasher};
     use stwo_examples::plonk_with_poseidon::air::{
         prove_plonk_with_poseidon, verify_plonk_with_poseidon, PlonkWithPoseidonProof,
     };
 
-    #[test]
-    pub fn test_folding() {
+    /// The circuit that verifies the small proof, whose public inputs are (1, i, j).
+    fn small_proof_verifier_circuit(config: PcsConfig) -> ConstraintSystemRef {
         let proof: PlonkWithPoseidonProof<Poseidon31MerkleHasher> =
             bincode::deserialize(include_bytes!("../../../test_data/small_proof.bin")).unwrap();
-        let config = PcsConfig {
-            pow_bits: 20,
-            fri_config: FriConfig::new(2, 5, 16),
-        };
 
         verify_plonk_with_poseidon::<Poseidon31MerkleChannel>(
             proof.clone(),
@@ -233,7 +232,8 @@ mod test {
         )
         .unwrap();
 
-        let fiat_shamir_hints = FiatShamirHints::new(&proof, config, &[(1, QM31::one())]);
+        let fiat_shamir_hints =
+            FiatShamirHints::<Poseidon31MerkleChannel>::new(&proof, config, &[(1, QM31::one())]);
         let answer_hints = AnswerHints::compute(&fiat_shamir_hints, &proof);
         let fri_answer_hints = AnswerHints::compute(&fiat_shamir_hints, &proof);
         let decommitment_hints = DecommitHints::compute(&fiat_shamir_hints, &proof);
@@ -246,11 +246,19 @@ mod test {
 
         let cs = ConstraintSystemRef::new();
         let mut proof_var = PlonkWithPoseidonProofVar::new_witness(&cs, &proof);
+        let verifying_key = PlonkVerifyingKeyVar::new_constant(
+            &cs,
+            &PlonkVerifyingKey::load(Path::new(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../../test_data/small_proof.vk.json"
+            )))
+            .unwrap(),
+        );
 
         let fiat_shamir_results = FiatShamirResults::compute(
             &fiat_shamir_hints,
             &mut proof_var,
-            config,
+            &verifying_key,
             &[(1, QM31Var::one(&cs))],
         );
 
@@ -277,6 +285,16 @@ mod test {
         cs.check_arithmetics();
         cs.populate_logup_arguments();
         cs.check_poseidon_invocations();
+        cs
+    }
+
+    #[test]
+    pub fn test_folding() {
+        let config = PcsConfig {
+            pow_bits: 20,
+            fri_config: FriConfig::new(2, 5, 16),
+        };
+        let cs = small_proof_verifier_circuit(config);
 
         let (plonk, mut poseidon) = cs.generate_plonk_with_poseidon_circuit();
         let proof =
@@ -305,4 +323,77 @@ mod test {
         )
         .unwrap();
     }
+
+    #[test]
+    pub fn test_folding_with_blake2s() {
+        let config = PcsConfig {
+            pow_bits: 20,
+            fri_config: FriConfig::new(2, 5, 16),
+        };
+        let cs = small_proof_verifier_circuit(config);
+
+        // prove the verifier circuit with Blake2s, and verify that proof in another circuit
+        let (plonk, mut poseidon) = cs.generate_plonk_with_poseidon_circuit();
+        let proof =
+            prove_plonk_with_poseidon::<Blake2sMerkleChannel>(config, &plonk, &mut poseidon);
+        let inputs = [
+            (1, QM31::one()),
+            (2, QM31::from_u32_unchecked(0, 1, 0, 0)),
+            (3, QM31::from_u32_unchecked(0, 0, 1, 0)),
+        ];
+        verify_plonk_with_poseidon::<Blake2sMerkleChannel>(proof.clone(), config, &inputs).unwrap();
+
+        let fiat_shamir_hints =
+            FiatShamirHints::<Blake2sMerkleChannel>::new(&proof, config, &inputs);
+        let answer_hints = AnswerHints::compute(&fiat_shamir_hints, &proof);
+        let decommitment_hints = DecommitHints::compute(&fiat_shamir_hints, &proof);
+        let first_layer_hints = FirstLayerHints::compute(&fiat_shamir_hints, &answer_hints, &proof);
+        let inner_layer_hints = InnerLayersHints::compute(
+            &first_layer_hints.folded_evals_by_column,
+            &fiat_shamir_hints,
+            &proof,
+        );
+
+        let cs = ConstraintSystemRef::new();
+        let mut proof_var = PlonkWithPoseidonProofVar::new_witness(&cs, &proof);
+        let verifying_key = PlonkVerifyingKeyVar::new_constant(
+            &cs,
+            &PlonkVerifyingKey::from_proof(&proof, config, &[1, 2, 3]),
+        );
+
+        let fiat_shamir_results = FiatShamirResults::compute(
+            &fiat_shamir_hints,
+            &mut proof_var,
+            &verifying_key,
+            &[
+                (1, QM31Var::one(&cs)),
+                (2, QM31Var::i(&cs)),
+                (3, QM31Var::j(&cs)),
+            ],
+        );
+
+        let answer_results = AnswerResults::compute(
+            &CirclePointQM31Var::new_witness(&cs, &fiat_shamir_hints.oods_point),
+            &fiat_shamir_hints,
+            &fiat_shamir_results,
+            &answer_hints,
+            &decommitment_hints,
+            &proof_var,
+            config,
+        );
+
+        FoldingResults::compute(
+            &proof_var,
+            &fiat_shamir_hints,
+            &fiat_shamir_results,
+            &answer_results,
+            &first_layer_hints,
+            &inner_layer_hints,
+        );
+
+        cs.pad();
+        cs.check_arithmetics();
+        cs.populate_logup_arguments();
+        cs.check_poseidon_invocations();
+    }
 }
@@ -40,6 +40,14 @@ impl ConstraintSystemRef {
         self.0.borrow().variables[idx]
     }
 
+    /// Overwrite the value of a variable, without recomputing the variables derived from it, so
+    /// that a test can check that the constraints reject a malicious witness. Only available to
+    /// tests, through the `test-utils` feature in the other crates.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn set_value(&self, idx: usize, value: QM31) {
+        self.0.borrow_mut().variables[idx] = value;
+    }
+
     pub fn get_cache(&self, str: impl ToString) -> Option<usize> {
         self.0.borrow().cache.get(&str.to_string()).cloned()
     }
@@ -1,16 +1,12 @@
-use circle_plonk_dsl_answer::AnswerResults;
-use circle_plonk_dsl_composition::CompositionCheck;
+use circle_plonk_dsl_aggregation::{
+    aggregate, public_inputs, verify_with_output_hash, AggregatedProof,
+};
 use circle_plonk_dsl_constraint_system::var::AllocVar;
 use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
-use circle_plonk_dsl_data_structures::PlonkWithPoseidonProofVar;
-use circle_plonk_dsl_fiat_shamir::FiatShamirResults;
-use circle_plonk_dsl_folding::FoldingResults;
-use circle_plonk_dsl_hints::{
-    AnswerHints, DecommitHints, FiatShamirHints, FirstLayerHints, InnerLayersHints,
+use circle_plonk_dsl_data_structures::{
+    PlonkVerifyingKey, PlonkVerifyingKeyMembershipVar, PlonkVerifyingKeySet,
 };
-use circle_plonk_dsl_primitives::QM31Var;
-use circle_plonk_dsl_primitives::{CirclePointQM31Var, Poseidon2HalfVar};
-use num_traits::One;
+use circle_plonk_dsl_primitives::Poseidon2HalfVar;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use stwo::core::fields::m31::M31;
@@ -23,189 +19,231 @@ use stwo_examples::plonk_with_poseidon::air::{
     prove_plonk_with_poseidon_unchecked, verify_plonk_with_poseidon, PlonkWithPoseidonProof,
 };
 
-pub fn demo_recurse(
-    src: &Path,
-    src_config: PcsConfig,
-    dest: &Path,
-    dest_config: PcsConfig,
-    output_hash: Poseidon31Hash,
-    expected_preprocessed_column_hash: Poseidon31Hash,
-) {
-    println!(
-        "Generating a proof at {} that verifies {}",
-        dest.display(),
-        src.display()
-    );
-
+fn data_path(file: &Path) -> PathBuf {
     let manifest_dir = env!("CARGO_MANIFEST_DIR");
-    let src_path = PathBuf::from(manifest_dir)
+    PathBuf::from(manifest_dir)
         .parent()
         .unwrap()
         .join("data")
-        .join(src);
+        .join(file)
+}
 
-    let mut fs = std::fs::File::open(src_path).unwrap();
+fn load_proof(src: &Path) -> PlonkWithPoseidonProof<Poseidon31MerkleHasher> {
+    let mut fs = std::fs::File::open(data_path(src)).unwrap();
+    bincode::deserialize_from(&mut fs).unwrap()
+}
 
-    let inputs = vec![
-        (1, QM31::one()),
-        (2, QM31::from_u32_unchecked(0, 1, 0, 0)),
-        (3, QM31::from_u32_unchecked(0, 0, 1, 0)),
-        (
-            4,
-            QM31::from_m31(
-                output_hash.0[0],
-                output_hash.0[1],
-                output_hash.0[2],
-                output_hash.0[3],
-            ),
-        ),
-        (
-            5,
-            QM31::from_m31(
-                output_hash.0[4],
-                output_hash.0[5],
-                output_hash.0[6],
-                output_hash.0[7],
-            ),
-        ),
-    ];
+/// The path of the verifying key of the circuit that the proof at `proof` is for.
+fn verifying_key_path(proof: &Path) -> PathBuf {
+    data_path(&proof.with_extension("vk.json"))
+}
 
-    let proof: PlonkWithPoseidonProof<Poseidon31MerkleHasher> =
-        bincode::deserialize_from(&mut fs).unwrap();
+/// Load the verifying key that the proof at `src` is checked against, which is stored next to
+/// the proof when the circuit is proven, so that it is not taken from the proof itself.
+fn load_verifying_key(src: &Path) -> PlonkVerifyingKey {
+    PlonkVerifyingKey::load(&verifying_key_path(src)).unwrap()
+}
 
-    let fiat_shamir_hints = FiatShamirHints::new(&proof, src_config, &inputs);
-    let answer_hints = AnswerHints::compute(&fiat_shamir_hints, &proof);
-    let decommitment_hints = DecommitHints::compute(&fiat_shamir_hints, &proof);
-    let first_layer_hints = FirstLayerHints::compute(&fiat_shamir_hints, &answer_hints, &proof);
-    let inner_layer_hints = InnerLayersHints::compute(
-        &first_layer_hints.folded_evals_by_column,
-        &fiat_shamir_hints,
-        &proof,
-    );
+/// Prove the circuit and store the proof at `dest` along with its verifying key, unless the proof
+/// has been cached, in which case the stored verifying key is checked against the cached proof,
+/// or written if it is missing.
+fn prove_and_store(
+    cs: ConstraintSystemRef,
+    dest: &Path,
+    dest_config: PcsConfig,
+    inputs: &[(usize, QM31)],
+    expected_preprocessed_column_hash: Option<Poseidon31Hash>,
+) {
+    cs.pad();
+    cs.check_arithmetics();
+    cs.populate_logup_arguments();
+    cs.check_poseidon_invocations();
 
-    let cs = ConstraintSystemRef::new();
+    let (plonk, mut poseidon) = cs.generate_plonk_with_poseidon_circuit();
 
-    let output_hash_var = Poseidon2HalfVar::new_public_input(&cs, &output_hash);
-    let output_hash_var_qm31 = output_hash_var.to_qm31();
+    let input_indices = inputs.iter().map(|(idx, _)| *idx).collect::<Vec<_>>();
 
-    let mut proof_var = PlonkWithPoseidonProofVar::new_witness(&cs, &proof);
+    let dest_path = data_path(dest);
+    let proof = if std::fs::exists(&dest_path).unwrap() {
+        println!("Skip proof generation because it has been cached.");
+        load_proof(dest)
+    } else {
+        let timer = std::time::Instant::now();
+        let proof = prove_plonk_with_poseidon_unchecked::<Poseidon31MerkleChannel>(
+            dest_config,
+            &plonk,
+            &mut poseidon,
+        );
+        println!("proof generation time: {}s", timer.elapsed().as_secs_f64());
 
+        let encoded = bincode::serialize(&proof).unwrap();
+        let mut fs = std::fs::File::create(dest_path).unwrap();
+        fs.write_all(&encoded).unwrap();
+        proof
+    };
     println!(
-        "-> after allocating the proof: {}, {}",
-        cs.num_plonk_rows(),
-        cs.num_poseidon_invocations()
+        "proof preprocessed column hash: {}",
+        proof.stark_proof.commitments[0]
     );
+    if let Some(expected_preprocessed_column_hash) = expected_preprocessed_column_hash {
+        assert_eq!(
+            proof.stark_proof.commitments[0].0,
+            expected_preprocessed_column_hash.0
+        );
+    }
 
-    let fiat_shamir_results = FiatShamirResults::compute(
-        &fiat_shamir_hints,
-        &mut proof_var,
-        src_config,
-        &[
-            (1, QM31Var::one(&cs)),
-            (2, QM31Var::i(&cs)),
-            (3, QM31Var::j(&cs)),
-            (4, output_hash_var_qm31[0].clone()),
-            (5, output_hash_var_qm31[1].clone()),
-        ],
-    );
+    // the proof has been generated for the circuit above, so its key is the circuit's
+    let verifying_key = PlonkVerifyingKey::from_proof(&proof, dest_config, &input_indices);
+    let verifying_key_path = verifying_key_path(dest);
+    if std::fs::exists(&verifying_key_path).unwrap() {
+        assert_eq!(
+            PlonkVerifyingKey::load(&verifying_key_path).unwrap(),
+            verifying_key,
+            "the stored verifying key is not the one of the proof at {}",
+            dest.display()
+        );
+    } else {
+        verifying_key.save(&verifying_key_path).unwrap();
+    }
 
+    verify_plonk_with_poseidon::<Poseidon31MerkleChannel>(proof, dest_config, inputs).unwrap();
+}
+
+pub fn demo_recurse(
+    src: &Path,
+    dest: &Path,
+    dest_config: PcsConfig,
+    output_hash: Poseidon31Hash,
+    expected_preprocessed_column_hash: Poseidon31Hash,
+) {
     println!(
-        "-> after fiat-shamir: {} {}",
-        cs.num_plonk_rows(),
-        cs.num_poseidon_invocations()
+        "Generating a proof at {} that verifies {}",
+        dest.display(),
+        src.display()
     );
 
-    CompositionCheck::compute(
-        &fiat_shamir_hints,
-        &fiat_shamir_results.lookup_elements,
-        fiat_shamir_results.random_coeff.clone(),
-        fiat_shamir_results.oods_point.clone(),
-        &proof_var,
-    );
+    let proof = load_proof(src);
+    let verifying_key = load_verifying_key(src);
 
-    println!(
-        "-> after composition: {} {}",
-        cs.num_plonk_rows(),
-        cs.num_poseidon_invocations()
-    );
+    let cs = ConstraintSystemRef::new();
+    let output_hash_var = Poseidon2HalfVar::new_public_input(&cs, &output_hash);
+    verify_with_output_hash(&cs, &proof, &verifying_key, None, &output_hash_var);
 
-    let answer_results = AnswerResults::compute(
-        &CirclePointQM31Var::new_witness(&cs, &fiat_shamir_hints.oods_point),
-        &fiat_shamir_hints,
-        &fiat_shamir_results,
-        &answer_hints,
-        &decommitment_hints,
-        &proof_var,
-        src_config,
+    prove_and_store(
+        cs,
+        dest,
+        dest_config,
+        &public_inputs(output_hash),
+        Some(expected_preprocessed_column_hash),
     );
+}
 
+/// Generate a proof at `dest` that verifies `src` against a set of allowed verifying keys, whose
+/// root is exposed as public inputs 6 and 7, instead of a single verifying key fixed in the
+/// circuit.
+///
+/// The circuit is generated for the shape of the key of `src`, so the allowed keys must all have
+/// that shape and can only differ in their preprocessed roots.
+pub fn demo_recurse_with_allowed_keys(
+    src: &Path,
+    allowed: &[&Path],
+    dest: &Path,
+    dest_config: PcsConfig,
+    output_hash: Poseidon31Hash,
+) {
     println!(
-        "-> after answer: {} {}",
-        cs.num_plonk_rows(),
-        cs.num_poseidon_invocations()
+        "Generating a proof at {} that verifies {} against {} allowed keys",
+        dest.display(),
+        src.display(),
+        allowed.len()
     );
 
-    FoldingResults::compute(
-        &proof_var,
-        &fiat_shamir_hints,
-        &fiat_shamir_results,
-        &answer_results,
-        &first_layer_hints,
-        &inner_layer_hints,
+    let proof = load_proof(src);
+    let verifying_key = load_verifying_key(src);
+    let allowed_keys = PlonkVerifyingKeySet::new(
+        allowed
+            .iter()
+            .map(|path| load_verifying_key(path))
+            .collect(),
+        4,
     );
+    let membership_proof = allowed_keys
+        .prove(&verifying_key)
+        .expect("the verifying key of the proof must be allowed");
 
-    println!(
-        "-> after folding: {} {}",
-        cs.num_plonk_rows(),
-        cs.num_poseidon_invocations()
+    let cs = ConstraintSystemRef::new();
+    let output_hash_var = Poseidon2HalfVar::new_public_input(&cs, &output_hash);
+    let membership =
+        PlonkVerifyingKeyMembershipVar::new(&cs, allowed_keys.root(), &membership_proof);
+    verify_with_output_hash(
+        &cs,
+        &proof,
+        &verifying_key,
+        Some(&membership),
+        &output_hash_var,
     );
 
-    cs.pad();
-    cs.check_arithmetics();
-    cs.populate_logup_arguments();
-    cs.check_poseidon_invocations();
+    let root = allowed_keys.root();
+    let mut inputs = public_inputs(output_hash);
+    inputs.push((
+        6,
+        QM31::from_m31(root.0[0], root.0[1], root.0[2], root.0[3]),
+    ));
+    inputs.push((
+        7,
+        QM31::from_m31(root.0[4], root.0[5], root.0[6], root.0[7]),
+    ));
+    prove_and_store(cs, dest, dest_config, &inputs, None);
+}
 
-    let (plonk, mut poseidon) = cs.generate_plonk_with_poseidon_circuit();
+/// Generate a proof at `dest` that verifies two or more proofs in one circuit, and exposes the
+/// combined output hash of the inner proofs as its own output hash, so that the aggregated proofs
+/// can be aggregated again as a binary tree.
+pub fn demo_aggregate(
+    srcs: &[(&Path, Poseidon31Hash)],
+    dest: &Path,
+    dest_config: PcsConfig,
+) -> Poseidon31Hash {
+    assert!(srcs.len() >= 2);
+    println!(
+        "Generating a proof at {} that aggregates {}",
+        dest.display(),
+        srcs.iter()
+            .map(|(src, _)| src.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
 
-    let dest_path = PathBuf::from(manifest_dir)
-        .parent()
-        .unwrap()
-        .join("data")
-        .join(dest);
+    let proofs = srcs
+        .iter()
+        .map(|(src, _)| (load_proof(src), load_verifying_key(src)))
+        .collect::<Vec<_>>();
+    let aggregated_proofs = proofs
+        .iter()
+        .zip(srcs.iter())
+        .map(
+            |((proof, verifying_key), (_, output_hash))| AggregatedProof {
+                proof,
+                verifying_key,
+                output_hash: *output_hash,
+            },
+        )
+        .collect::<Vec<_>>();
 
-    if std::fs::exists(&dest_path).unwrap() {
-        println!("Skip proof generation because it has been cached.");
-        return;
-    }
+    let cs = ConstraintSystemRef::new();
+    let combined_output_hash = aggregate(&cs, &aggregated_proofs);
 
-    let timer = std::time::Instant::now();
-    let proof = prove_plonk_with_poseidon_unchecked::<Poseidon31MerkleChannel>(
+    prove_and_store(
+        cs,
+        dest,
         dest_config,
-        &plonk,
-        &mut poseidon,
+        &public_inputs(combined_output_hash),
+        None,
     );
-    println!("proof generation time: {}s", timer.elapsed().as_secs_f64());
-    println!(
-        "proof preprocessed column hash: {}",
-        proof.stark_proof.commitments[0]
-    );
-    assert_eq!(
-        proof.stark_proof.commitments[0].0,
-        expected_preprocessed_column_hash.0
-    );
-
-    let encoded = bincode::serialize(&proof).unwrap();
-    let mut fs = std::fs::File::create(dest_path).unwrap();
-    fs.write_all(&encoded).unwrap();
-
-    verify_plonk_with_poseidon::<Poseidon31MerkleChannel>(proof, dest_config, &inputs).unwrap();
+    combined_output_hash
 }
 
 fn main() {
-    let initial_config = PcsConfig {
-        pow_bits: 26,
-        fri_config: FriConfig::new(0, 1, 70),
-    };
     let fast_prover_config = PcsConfig {
         pow_bits: 20,
         fri_config: FriConfig::new(8, 1, 80),
@@ -239,7 +277,6 @@ fn main() {
 
     demo_recurse(
         Path::new("initial_proof.bin"),
-        initial_config,
         Path::new("level1_20_8_1.bin"),
         fast_prover_config,
         output_hash,
@@ -256,7 +293,6 @@ fn main() {
     );
     demo_recurse(
         Path::new("level1_20_8_1.bin"),
-        fast_prover_config,
         Path::new("level2_20_8_3.bin"),
         fast_prover2_config,
         output_hash,
@@ -273,7 +309,6 @@ fn main() {
     );
     demo_recurse(
         Path::new("level2_20_8_3.bin"),
-        fast_prover2_config,
         Path::new("level3_23_8_7.bin"),
         fast_verifier_config,
         output_hash,
@@ -290,7 +325,6 @@ fn main() {
     );
     demo_recurse(
         Path::new("level3_23_8_7.bin"),
-        fast_verifier_config,
         Path::new("level4_20_8_8.bin"),
         fast_verifier2_config,
         output_hash,
@@ -307,7 +341,6 @@ fn main() {
     );
     demo_recurse(
         Path::new("level4_20_8_8.bin"),
-        fast_verifier2_config,
         Path::new("level5_28_7_9.bin"),
         fast_verifier3_config,
         output_hash,
@@ -322,4 +355,34 @@ fn main() {
             M31::from(1040490758),
         ]),
     );
+
+    // Aggregate two copies of the last proof, and then the result with the last proof again, to
+    // exercise the aggregation as a tree.
+    let aggregated_output_hash = demo_aggregate(
+        &[
+            (Path::new("level5_28_7_9.bin"), output_hash),
+            (Path::new("level5_28_7_9.bin"), output_hash),
+        ],
+        Path::new("aggregated1_20_8_1.bin"),
+        fast_prover_config,
+    );
+    demo_aggregate(
+        &[
+            (Path::new("aggregated1_20_8_1.bin"), aggregated_output_hash),
+            (Path::new("level5_28_7_9.bin"), output_hash),
+        ],
+        Path::new("aggregated2_20_8_1.bin"),
+        fast_prover_config,
+    );
+
+    // Verify the last proof against a set of allowed keys. Only keys of the same shape can share
+    // the circuit, and the other levels have other log sizes or PCS configurations, so the set
+    // only holds the key of the last level here.
+    demo_recurse_with_allowed_keys(
+        Path::new("level5_28_7_9.bin"),
+        &[Path::new("level5_28_7_9.bin")],
+        Path::new("level6_allowed_20_8_1.bin"),
+        fast_prover_config,
+        output_hash,
+    );
 }
@@ -1,9 +1,11 @@
 use cairo_air::utils::{deserialize_proof_from_file, ProofFormat};
 use cairo_plonk_dsl_data_structures::CairoProofVar;
 use cairo_plonk_dsl_interface::{
-    compute_output_hash, verifier_input, verify_output_hash, verify_proof,
+    compute_output_hash, validate_cairo_proof_for_recursion, verifier_input, verify_output_hash,
+    verify_proof, CairoVerifierParams,
 };
 use circle_plonk_dsl_constraint_system::{var::AllocVar, ConstraintSystemRef};
+use circle_plonk_dsl_data_structures::PlonkVerifyingKey;
 use circle_plonk_dsl_primitives::Poseidon2HalfVar;
 use std::io::Write;
 use std::path::PathBuf;
@@ -27,14 +29,34 @@ fn main() {
         .join("recursive_proof.bin.bz");
 
     let proof = deserialize_proof_from_file(&data_path, ProofFormat::Binary).unwrap();
+    if let Err(incompatibilities) = validate_cairo_proof_for_recursion(&proof) {
+        for incompatibility in incompatibilities.iter() {
+            eprintln!("{incompatibility}");
+        }
+        std::process::exit(1);
+    }
     let output_hash = compute_output_hash(&proof);
 
+    // The verifier is generated for the compiled program given as the first argument, if any, with
+    // the config of the zebra-fork prover, and otherwise for the program and config of the proof.
+    let params = match std::env::args().nth(1) {
+        Some(program_path) => CairoVerifierParams::load_compiled_program(
+            &PathBuf::from(program_path),
+            PcsConfig {
+                pow_bits: 26,
+                fri_config: FriConfig::new(0, 1, 70),
+            },
+        )
+        .unwrap(),
+        None => CairoVerifierParams::from_proof(&proof),
+    };
+
     let cs = ConstraintSystemRef::new();
 
     let output_hash_var = Poseidon2HalfVar::new_public_input(&cs, &output_hash);
     let proof_var = CairoProofVar::new_witness(&cs, &proof);
     verify_output_hash(output_hash_var, &proof_var);
-    verify_proof(&proof, &proof_var);
+    verify_proof(&params, &proof, &proof_var, None);
 
     cs.pad();
     cs.check_arithmetics();
@@ -89,13 +111,17 @@ fn main() {
     );
 
     let encoded = bincode::serialize(&proof).unwrap();
-    let mut fs = std::fs::File::create(initial_proof_path).unwrap();
+    let mut fs = std::fs::File::create(&initial_proof_path).unwrap();
     fs.write_all(&encoded).unwrap();
 
-    verify_plonk_with_poseidon::<Poseidon31MerkleChannel>(
-        proof,
-        config,
-        &verifier_input(&output_hash),
-    )
-    .unwrap();
+    let inputs = verifier_input(&output_hash, None);
+
+    // the proof has just been generated for the circuit above, so its key is the circuit's, and
+    // the Plonk-to-Plonk verifier loads it instead of trusting the proof
+    let input_indices = inputs.iter().map(|(idx, _)| *idx).collect::<Vec<_>>();
+    PlonkVerifyingKey::from_proof(&proof, config, &input_indices)
+        .save(&initial_proof_path.with_extension("vk.json"))
+        .unwrap();
+
+    verify_plonk_with_poseidon::<Poseidon31MerkleChannel>(proof, config, &inputs).unwrap();
 }
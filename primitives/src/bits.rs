@@ -99,6 +99,14 @@ impl AllocVar for BitsVar {
 
 impl BitsVar {
     pub fn from_m31(v: &M31Var, l: usize) -> BitsVar {
+        let res = Self::from_m31_constrained(v, l);
+        assert_eq!(res.get_value(), v.value);
+        res
+    }
+
+    /// Decompose the value into `l` bits like [`BitsVar::from_m31`], but without asserting natively
+    /// that the value fits, so that a witness out of range is only rejected by the constraints.
+    pub fn from_m31_constrained(v: &M31Var, l: usize) -> BitsVar {
         let cs = v.cs();
         let mut bools = Vec::with_capacity(l);
 
@@ -114,7 +122,7 @@ impl BitsVar {
         for i in 1..l {
             reconstructed = &reconstructed + &res.0[i].0.mul_constant(M31::from(1 << i));
         }
-        reconstructed.equalverify(v);
+        cs.insert_gate(reconstructed.variable, 0, v.variable, M31::one());
 
         if l == 31 {
             let mut product = cs.mul(res.0[0].0.variable, res.0[1].0.variable);
@@ -239,6 +247,16 @@ impl<const N: usize> AllocVar for BitIntVar<N> {
 
 impl<const N: usize> BitIntVar<N> {
     pub fn mix_into(&self, channel: &mut ChannelVar) {
+        channel.mix_one_felt(&self.to_channel_felt());
+    }
+
+    /// Mix the integer into the channel only if `bit` is set.
+    pub fn conditional_mix_into(&self, channel: &mut ChannelVar, bit: &BitVar) {
+        channel.conditional_mix_one_felt(&self.to_channel_felt(), bit);
+    }
+
+    /// The felt that represents the integer in the channel, with limbs of 22, 21, and 21 bits.
+    fn to_channel_felt(&self) -> QM31Var {
         let cs = self.cs();
         let zero = M31Var::zero(&cs);
 
@@ -267,8 +285,7 @@ impl<const N: usize> BitIntVar<N> {
             zero.clone()
         };
 
-        let felt = QM31Var::from_m31(&first, &second, &third, &zero);
-        channel.mix_one_felt(&felt);
+        QM31Var::from_m31(&first, &second, &third, &zero)
     }
 
     pub fn enforce_equal(&self, other: &BitIntVar<N>) {
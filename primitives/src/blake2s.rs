@@ -0,0 +1,601 @@
+use crate::channel::{FiatShamirChannelVar, MerkleChannelVar};
+use crate::{BitVar, BitsVar, M31Var, QM31Var};
+use circle_plonk_dsl_constraint_system::var::{AllocVar, AllocationMode, Var};
+use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
+use std::cmp::min;
+use stwo::core::fields::m31::M31;
+use stwo::core::vcs::blake2_hash::Blake2sHash;
+use stwo::core::vcs::blake2_merkle::Blake2sMerkleChannel;
+
+const IV: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 13, 0, 12],
+];
+
+/// A 32-bit word, represented by its bits from the least significant one.
+#[derive(Debug, Clone)]
+pub struct U32Var {
+    pub cs: ConstraintSystemRef,
+    pub bits: Vec<BitVar>,
+}
+
+impl Var for U32Var {
+    type Value = u32;
+
+    fn cs(&self) -> ConstraintSystemRef {
+        self.cs.clone()
+    }
+}
+
+impl AllocVar for U32Var {
+    fn new_variables(cs: &ConstraintSystemRef, value: &Self::Value, mode: AllocationMode) -> Self {
+        let bits = (0..32)
+            .map(|i| BitVar::new_variables(cs, &((value >> i) & 1 != 0), mode))
+            .collect();
+        Self {
+            cs: cs.clone(),
+            bits,
+        }
+    }
+}
+
+impl U32Var {
+    pub fn value(&self) -> u32 {
+        self.bits
+            .iter()
+            .enumerate()
+            .fold(0, |acc, (i, bit)| acc | ((bit.value() as u32) << i))
+    }
+
+    /// The word of an M31 element, i.e., its canonical representation.
+    pub fn from_m31(v: &M31Var) -> Self {
+        let cs = v.cs();
+        let mut bits = BitsVar::from_m31(v, 31).0;
+        bits.push(BitVar::new_false(&cs));
+        Self { cs, bits }
+    }
+
+    fn compose_half(&self, high: bool) -> M31Var {
+        let offset = if high { 16 } else { 0 };
+        BitsVar(self.bits[offset..offset + 16].to_vec()).compose()
+    }
+
+    /// The sum of two to four words modulo 2^32.
+    pub fn wrapping_add(values: &[&U32Var]) -> U32Var {
+        assert!((2..=4).contains(&values.len()));
+        let cs = values[0].cs();
+
+        let mut lo = values[0].compose_half(false);
+        for value in values.iter().skip(1) {
+            lo = &lo + &value.compose_half(false);
+        }
+        let lo_bits = BitsVar::from_m31(&lo, 18);
+
+        let mut hi = lo_bits.compose_range(16..18);
+        for value in values.iter() {
+            hi = &hi + &value.compose_half(true);
+        }
+        let hi_bits = BitsVar::from_m31(&hi, 18);
+
+        let mut bits = lo_bits.0[0..16].to_vec();
+        bits.extend_from_slice(&hi_bits.0[0..16]);
+        Self { cs, bits }
+    }
+
+    pub fn xor(&self, rhs: &U32Var) -> U32Var {
+        let bits = self
+            .bits
+            .iter()
+            .zip(rhs.bits.iter())
+            .map(|(a, b)| BitVar(&(&a.0 + &b.0) - &(&a.0 * &b.0).mul_constant(M31::from(2))))
+            .collect();
+        Self {
+            cs: self.cs.and(&rhs.cs),
+            bits,
+        }
+    }
+
+    pub fn rotate_right(&self, n: usize) -> U32Var {
+        let bits = (0..32).map(|i| self.bits[(i + n) % 32].clone()).collect();
+        Self {
+            cs: self.cs.clone(),
+            bits,
+        }
+    }
+
+    /// Reduce a word that is less than 2P to an M31 element, as the Blake2s channel does for the
+    /// words it draws. A larger word would make the native channel draw again, which happens
+    /// with a negligible probability, so it is rejected.
+    pub fn reduce_to_m31(&self) -> M31Var {
+        let cs = self.cs();
+
+        // the word is at least 2P = 2^32 - 2 if and only if the bits 1..32 are all ones
+        let mut product = cs.mul(self.bits[1].0.variable, self.bits[2].0.variable);
+        for bit in self.bits.iter().skip(3) {
+            product = cs.mul(product, bit.0.variable);
+        }
+        cs.enforce_zero(product);
+
+        // 2^31 = 1 in M31
+        &BitsVar(self.bits[0..31].to_vec()).compose() + &self.bits[31].0
+    }
+
+    pub fn equalverify(&self, rhs: &U32Var) {
+        for (a, b) in self.bits.iter().zip(rhs.bits.iter()) {
+            a.equalverify(b);
+        }
+    }
+}
+
+/// A Blake2s hash, as eight little-endian words.
+#[derive(Debug, Clone)]
+pub struct Blake2sHashVar(pub Vec<U32Var>);
+
+impl Var for Blake2sHashVar {
+    type Value = Blake2sHash;
+
+    fn cs(&self) -> ConstraintSystemRef {
+        self.0[0].cs()
+    }
+}
+
+impl AllocVar for Blake2sHashVar {
+    fn new_variables(cs: &ConstraintSystemRef, value: &Self::Value, mode: AllocationMode) -> Self {
+        Self(
+            value
+                .0
+                .chunks_exact(4)
+                .map(|chunk| {
+                    U32Var::new_variables(cs, &u32::from_le_bytes(chunk.try_into().unwrap()), mode)
+                })
+                .collect(),
+        )
+    }
+}
+
+impl Blake2sHashVar {
+    pub fn value(&self) -> Blake2sHash {
+        let mut res = [0u8; 32];
+        for (chunk, word) in res.chunks_exact_mut(4).zip(self.0.iter()) {
+            chunk.copy_from_slice(&word.value().to_le_bytes());
+        }
+        Blake2sHash(res)
+    }
+
+    pub fn equalverify(&self, rhs: &Blake2sHashVar) {
+        for (a, b) in self.0.iter().zip(rhs.0.iter()) {
+            a.equalverify(b);
+        }
+    }
+
+    /// Return `(a, b)` if the bit is false and `(b, a)` otherwise.
+    pub fn swap(a: &Blake2sHashVar, b: &Blake2sHashVar, bit: &BitVar) -> (Self, Self) {
+        let select = |x: &Blake2sHashVar, y: &Blake2sHashVar| {
+            Blake2sHashVar(
+                x.0.iter()
+                    .zip(y.0.iter())
+                    .map(|(x, y)| U32Var {
+                        cs: x.cs.and(&y.cs),
+                        bits: x
+                            .bits
+                            .iter()
+                            .zip(y.bits.iter())
+                            .map(|(x, y)| BitVar::select(x, y, bit))
+                            .collect(),
+                    })
+                    .collect(),
+            )
+        };
+        (select(a, b), select(b, a))
+    }
+}
+
+pub struct Blake2sHasherVar;
+
+impl Blake2sHasherVar {
+    fn g(v: &mut [U32Var], a: usize, b: usize, c: usize, d: usize, x: &U32Var, y: &U32Var) {
+        v[a] = U32Var::wrapping_add(&[&v[a], &v[b], x]);
+        v[d] = v[d].xor(&v[a]).rotate_right(16);
+        v[c] = U32Var::wrapping_add(&[&v[c], &v[d]]);
+        v[b] = v[b].xor(&v[c]).rotate_right(12);
+        v[a] = U32Var::wrapping_add(&[&v[a], &v[b], y]);
+        v[d] = v[d].xor(&v[a]).rotate_right(8);
+        v[c] = U32Var::wrapping_add(&[&v[c], &v[d]]);
+        v[b] = v[b].xor(&v[c]).rotate_right(7);
+    }
+
+    fn compress(h: &[U32Var], block: &[U32Var], t: u64, last: bool) -> Vec<U32Var> {
+        let cs = h[0].cs();
+
+        // the second half of the state only depends on constants
+        let mut v = h.to_vec();
+        for (i, iv) in IV.iter().enumerate() {
+            let word = match i {
+                4 => iv ^ (t as u32),
+                5 => iv ^ ((t >> 32) as u32),
+                6 if last => !iv,
+                _ => *iv,
+            };
+            v.push(U32Var::new_constant(&cs, &word));
+        }
+
+        for sigma in SIGMA.iter() {
+            let m = |i: usize| &block[sigma[i]];
+            Self::g(&mut v, 0, 4, 8, 12, m(0), m(1));
+            Self::g(&mut v, 1, 5, 9, 13, m(2), m(3));
+            Self::g(&mut v, 2, 6, 10, 14, m(4), m(5));
+            Self::g(&mut v, 3, 7, 11, 15, m(6), m(7));
+            Self::g(&mut v, 0, 5, 10, 15, m(8), m(9));
+            Self::g(&mut v, 1, 6, 11, 12, m(10), m(11));
+            Self::g(&mut v, 2, 7, 8, 13, m(12), m(13));
+            Self::g(&mut v, 3, 4, 9, 14, m(14), m(15));
+        }
+
+        (0..8).map(|i| h[i].xor(&v[i]).xor(&v[i + 8])).collect()
+    }
+
+    /// Hash a message of `n_bytes` bytes given as little-endian words, where the bytes of the last
+    /// word beyond the length of the message must be zero.
+    pub fn hash(cs: &ConstraintSystemRef, words: &[U32Var], n_bytes: usize) -> Blake2sHashVar {
+        assert_eq!(words.len(), n_bytes.div_ceil(4));
+        if n_bytes % 4 != 0 {
+            assert_eq!(
+                words.last().unwrap().value() >> (8 * (n_bytes % 4)),
+                0,
+                "the padding bytes of the last word must be zero"
+            );
+        }
+
+        // no key and a 32-byte output
+        let mut h = IV
+            .iter()
+            .enumerate()
+            .map(|(i, iv)| U32Var::new_constant(cs, &if i == 0 { iv ^ 0x01010020 } else { *iv }))
+            .collect::<Vec<_>>();
+
+        let n_blocks = n_bytes.div_ceil(64).max(1);
+        for block_idx in 0..n_blocks {
+            let mut block = words
+                [min(16 * block_idx, words.len())..min(16 * block_idx + 16, words.len())]
+                .to_vec();
+            block.resize(16, U32Var::new_constant(cs, &0));
+
+            let last = block_idx == n_blocks - 1;
+            let t = if last { n_bytes } else { 64 * (block_idx + 1) };
+            h = Self::compress(&h, &block, t as u64, last);
+        }
+
+        Blake2sHashVar(h)
+    }
+}
+
+/// The in-circuit counterpart of `Blake2sMerkleHasher`.
+pub struct Blake2sMerkleHasherVar;
+
+impl Blake2sMerkleHasherVar {
+    pub fn hash_node(
+        cs: &ConstraintSystemRef,
+        children: Option<(&Blake2sHashVar, &Blake2sHashVar)>,
+        column_values: &[M31Var],
+    ) -> Blake2sHashVar {
+        let mut words = vec![];
+        if let Some((left, right)) = children {
+            words.extend_from_slice(&left.0);
+            words.extend_from_slice(&right.0);
+        }
+        words.extend(column_values.iter().map(U32Var::from_m31));
+        let n_bytes = words.len() * 4;
+        Blake2sHasherVar::hash(cs, &words, n_bytes)
+    }
+
+    pub fn hash_tree_with_swap(
+        left: &Blake2sHashVar,
+        right: &Blake2sHashVar,
+        bit: &BitVar,
+        column_values: &[M31Var],
+    ) -> Blake2sHashVar {
+        let cs = left.cs().and(&right.cs());
+        let (left, right) = Blake2sHashVar::swap(left, right, bit);
+        Self::hash_node(&cs, Some((&left, &right)), column_values)
+    }
+}
+
+/// The in-circuit counterpart of `Blake2sChannel` together with `Blake2sMerkleChannel::mix_root`.
+#[derive(Debug, Clone)]
+pub struct Blake2sChannelVar {
+    pub n_sent: usize,
+    pub digest: Blake2sHashVar,
+}
+
+impl Var for Blake2sChannelVar {
+    type Value = Blake2sHash;
+
+    fn cs(&self) -> ConstraintSystemRef {
+        self.digest.cs()
+    }
+}
+
+impl Blake2sChannelVar {
+    pub fn default(cs: &ConstraintSystemRef) -> Self {
+        Self {
+            n_sent: 0,
+            digest: Blake2sHashVar::new_constant(cs, &Blake2sHash::default()),
+        }
+    }
+
+    fn mix_words(&mut self, words: &[U32Var]) {
+        let cs = self.cs();
+        let mut input = self.digest.0.clone();
+        input.extend_from_slice(words);
+        self.digest = Blake2sHasherVar::hash(&cs, &input, input.len() * 4);
+        self.n_sent = 0;
+    }
+
+    pub fn mix_root(&mut self, root: &Blake2sHashVar) {
+        self.mix_words(&root.0);
+    }
+
+    pub fn mix_felts(&mut self, felts: &[QM31Var]) {
+        let words = felts
+            .iter()
+            .flat_map(|felt| felt.decompose_m31())
+            .map(|v| U32Var::from_m31(&v))
+            .collect::<Vec<_>>();
+        self.mix_words(&words);
+    }
+
+    /// Mix a u64 given as its two little-endian words, as for the proof-of-work nonce.
+    pub fn mix_u64(&mut self, words: &[U32Var; 2]) {
+        self.mix_words(words);
+    }
+
+    pub fn draw_u32s(&mut self) -> Vec<U32Var> {
+        let cs = self.cs();
+
+        // the digest, the counter padded to 32 bytes, and a zero byte for domain separation
+        let mut input = self.digest.0.clone();
+        let n_sent = self.n_sent as u64;
+        input.push(U32Var::new_constant(&cs, &(n_sent as u32)));
+        input.push(U32Var::new_constant(&cs, &((n_sent >> 32) as u32)));
+        for _ in 2..9 {
+            input.push(U32Var::new_constant(&cs, &0));
+        }
+        self.n_sent += 1;
+
+        Blake2sHasherVar::hash(&cs, &input, 65).0
+    }
+
+    /// Draw two secure field elements from a single hash, in the same way as
+    /// `draw_secure_felts(2)` (the first of which is also what `draw_secure_felt` returns).
+    pub fn draw_felts(&mut self) -> [QM31Var; 2] {
+        let m31 = self
+            .draw_u32s()
+            .iter()
+            .map(|word| word.reduce_to_m31())
+            .collect::<Vec<_>>();
+        [
+            QM31Var::from_m31(&m31[0], &m31[1], &m31[2], &m31[3]),
+            QM31Var::from_m31(&m31[4], &m31[5], &m31[6], &m31[7]),
+        ]
+    }
+
+    /// Check that the digest has at least `pow_bits` trailing zeros.
+    pub fn check_trailing_zeros(&self, pow_bits: usize) {
+        let cs = self.cs();
+        for bit in self
+            .digest
+            .0
+            .iter()
+            .flat_map(|word| word.bits.iter())
+            .take(pow_bits)
+        {
+            bit.equalverify(&BitVar::new_false(&cs));
+        }
+    }
+}
+
+impl FiatShamirChannelVar for Blake2sChannelVar {
+    type HashVar = Blake2sHashVar;
+
+    fn default(cs: &ConstraintSystemRef) -> Self {
+        Blake2sChannelVar::default(cs)
+    }
+
+    fn mix_root(&mut self, root: &Blake2sHashVar) {
+        Blake2sChannelVar::mix_root(self, root);
+    }
+
+    fn mix_felts(&mut self, felts: &[QM31Var]) {
+        Blake2sChannelVar::mix_felts(self, felts);
+    }
+
+    fn draw_felts(&mut self) -> [QM31Var; 2] {
+        Blake2sChannelVar::draw_felts(self)
+    }
+
+    fn mix_nonce(&mut self, nonce: &[M31Var; 3]) {
+        let cs = self.cs();
+        let mut bits = BitsVar::from_m31(&nonce[0], 22).0;
+        bits.extend(BitsVar::from_m31(&nonce[1], 21).0);
+        bits.extend(BitsVar::from_m31(&nonce[2], 21).0);
+
+        let words = [
+            U32Var {
+                cs: cs.clone(),
+                bits: bits[0..32].to_vec(),
+            },
+            U32Var {
+                cs,
+                bits: bits[32..64].to_vec(),
+            },
+        ];
+        self.mix_u64(&words);
+    }
+
+    fn check_pow(&self, pow_bits: u32) {
+        self.check_trailing_zeros(pow_bits as usize);
+    }
+
+    /// Draw the queries from the words, of which the low 30 bits are kept, so that they are
+    /// canonical M31 elements and cover the log size of any domain.
+    fn draw_raw_queries(&mut self, n_queries: usize) -> Vec<M31Var> {
+        let mut raw_queries = Vec::with_capacity(n_queries);
+        while raw_queries.len() < n_queries {
+            for word in self.draw_u32s() {
+                raw_queries.push(BitsVar(word.bits[0..30].to_vec()).compose());
+            }
+        }
+        raw_queries.truncate(n_queries);
+        raw_queries
+    }
+}
+
+impl MerkleChannelVar for Blake2sMerkleChannel {
+    type HashVar = Blake2sHashVar;
+    type HasherVar = Blake2sMerkleHasherVar;
+    type ChannelVar = Blake2sChannelVar;
+
+    /// The two children, since the node hash depends on them and the column at once.
+    type NodeChildrenVar = (Blake2sHashVar, Blake2sHashVar);
+
+    fn new_sibling_hash(cs: &ConstraintSystemRef, hash: &Blake2sHash) -> Blake2sHashVar {
+        Blake2sHashVar::new_witness(cs, hash)
+    }
+
+    fn hash_value(hash: &Blake2sHashVar) -> Blake2sHash {
+        hash.value()
+    }
+
+    fn new_node_children(
+        cs: &ConstraintSystemRef,
+        left: &Blake2sHash,
+        right: &Blake2sHash,
+    ) -> (Blake2sHashVar, Blake2sHashVar) {
+        (
+            Blake2sHashVar::new_witness(cs, left),
+            Blake2sHashVar::new_witness(cs, right),
+        )
+    }
+
+    fn hash_qm31_node_pair_with_swap(
+        cur: &Blake2sHashVar,
+        sibling: &Blake2sHashVar,
+        bit: &BitVar,
+        column: &QM31Var,
+        sibling_children: &(Blake2sHashVar, Blake2sHashVar),
+        sibling_column: &QM31Var,
+    ) -> (Blake2sHashVar, Blake2sHashVar) {
+        let cur =
+            Blake2sMerkleHasherVar::hash_tree_with_swap(cur, sibling, bit, &column.decompose_m31());
+        let (left, right) = sibling_children;
+        let sibling = Blake2sMerkleHasherVar::hash_node(
+            &column.cs(),
+            Some((left, right)),
+            &sibling_column.decompose_m31(),
+        );
+        (cur, sibling)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::blake2s::{Blake2sChannelVar, Blake2sHashVar, Blake2sMerkleHasherVar};
+    use crate::{BitVar, M31Var, QM31Var};
+    use circle_plonk_dsl_constraint_system::var::AllocVar;
+    use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+    use stwo::core::channel::{Blake2sChannel, Channel, MerkleChannel};
+    use stwo::core::fields::m31::M31;
+    use stwo::core::fields::qm31::QM31;
+    use stwo::core::vcs::blake2_hash::Blake2sHash;
+    use stwo::core::vcs::blake2_merkle::{Blake2sMerkleChannel, Blake2sMerkleHasher};
+    use stwo::core::vcs::MerkleHasher;
+
+    #[test]
+    fn test_blake2s_merkle_hasher() {
+        let mut prng = SmallRng::seed_from_u64(0);
+        let left = Blake2sHash(prng.gen());
+        let right = Blake2sHash(prng.gen());
+        let values: [M31; 20] = prng.gen();
+
+        let cs = ConstraintSystemRef::new();
+        let left_var = Blake2sHashVar::new_witness(&cs, &left);
+        let right_var = Blake2sHashVar::new_witness(&cs, &right);
+        let values_var = values
+            .iter()
+            .map(|v| M31Var::new_witness(&cs, v))
+            .collect::<Vec<_>>();
+
+        let leaf = Blake2sMerkleHasherVar::hash_node(&cs, None, &values_var);
+        assert_eq!(leaf.value(), Blake2sMerkleHasher::hash_node(None, &values));
+
+        let node = Blake2sMerkleHasherVar::hash_tree_with_swap(
+            &left_var,
+            &right_var,
+            &BitVar::new_witness(&cs, &true),
+            &values_var[0..2],
+        );
+        assert_eq!(
+            node.value(),
+            Blake2sMerkleHasher::hash_node(Some((right, left)), &values[0..2])
+        );
+
+        cs.pad();
+        cs.check_arithmetics();
+        cs.populate_logup_arguments();
+        cs.check_poseidon_invocations();
+    }
+
+    #[test]
+    fn test_blake2s_channel() {
+        let mut prng = SmallRng::seed_from_u64(0);
+        let root = Blake2sHash(prng.gen());
+        let felts: [QM31; 3] = prng.gen();
+
+        let cs = ConstraintSystemRef::new();
+        let mut channel_var = Blake2sChannelVar::default(&cs);
+        let mut channel = Blake2sChannel::default();
+
+        channel_var.mix_root(&Blake2sHashVar::new_witness(&cs, &root));
+        Blake2sMerkleChannel::mix_root(&mut channel, root);
+        assert_eq!(channel_var.digest.value(), channel.digest());
+
+        let [a, b] = channel_var.draw_felts();
+        let expected = channel.draw_secure_felts(2);
+        assert_eq!(a.value(), expected[0]);
+        assert_eq!(b.value(), expected[1]);
+
+        let felt_vars = felts
+            .iter()
+            .map(|felt| QM31Var::new_witness(&cs, felt))
+            .collect::<Vec<_>>();
+        channel_var.mix_felts(&felt_vars);
+        channel.mix_felts(&felts);
+        assert_eq!(channel_var.digest.value(), channel.digest());
+
+        let words = channel_var.draw_u32s();
+        let expected = channel.draw_u32s();
+        assert_eq!(
+            words.iter().map(|word| word.value()).collect::<Vec<_>>(),
+            expected
+        );
+
+        cs.pad();
+        cs.check_arithmetics();
+        cs.populate_logup_arguments();
+        cs.check_poseidon_invocations();
+    }
+}
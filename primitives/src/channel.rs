@@ -1,16 +1,45 @@
+use crate::merkle::MerkleHasherVar;
 use crate::poseidon31::Poseidon2HalfVar;
-use crate::{BitVar, M31Var, QM31Var};
+use crate::transcript::{TranscriptLog, TranscriptOp};
+use crate::{BitVar, BitsVar, M31Var, Poseidon31MerkleHasherVar, QM31Var};
 use circle_plonk_dsl_constraint_system::var::{AllocVar, Var};
 use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
+use std::cell::RefCell;
+use std::fmt::Debug;
 use std::ops::Neg;
+use std::rc::Rc;
+use stwo::core::channel::MerkleChannel;
 use stwo::core::fields::m31::M31;
+use stwo::core::vcs::poseidon31_hash::Poseidon31Hash;
+use stwo::core::vcs::poseidon31_merkle::{Poseidon31MerkleChannel, Poseidon31MerkleHasher};
+use stwo::core::vcs::MerkleHasher;
 
 pub type HashVar = Poseidon2HalfVar;
 
-#[derive(Clone)]
 pub struct ChannelVar {
     pub n_sent: usize,
     pub digest: Poseidon2HalfVar,
+    /// An optional log of the channel operations, used to locate mismatches with the native
+    /// channel. It is a debug aid only: it is not part of the circuit, and it follows the witness
+    /// values, e.g., a conditional mix or a skipped FRI layer is only logged when it happens for
+    /// the witness at hand.
+    pub log: Option<Rc<RefCell<TranscriptLog>>>,
+}
+
+impl Clone for ChannelVar {
+    /// A clone continues from a detached copy of the log, so that the operations on the clone,
+    /// such as a lookahead draw, do not show up in the log of the original, and the other way
+    /// around.
+    fn clone(&self) -> Self {
+        Self {
+            n_sent: self.n_sent,
+            digest: self.digest.clone(),
+            log: self
+                .log
+                .as_ref()
+                .map(|log| Rc::new(RefCell::new(log.borrow().clone()))),
+        }
+    }
 }
 
 impl Var for ChannelVar {
@@ -25,17 +54,49 @@ impl ChannelVar {
     pub fn default(cs: &ConstraintSystemRef) -> Self {
         let n_sent = 0;
         let digest = Poseidon2HalfVar::zero(cs);
-        Self { n_sent, digest }
+        Self {
+            n_sent,
+            digest,
+            log: None,
+        }
+    }
+
+    pub fn enable_transcript_log(&mut self) {
+        self.log = Some(Rc::new(RefCell::new(TranscriptLog::default())));
+    }
+
+    pub fn transcript_log(&self) -> Option<TranscriptLog> {
+        self.log.as_ref().map(|log| log.borrow().clone())
+    }
+
+    pub fn enter_scope(&self, scope: &str) {
+        if let Some(log) = &self.log {
+            log.borrow_mut().enter_scope(scope);
+        }
+    }
+
+    pub fn exit_scope(&self) {
+        if let Some(log) = &self.log {
+            log.borrow_mut().exit_scope();
+        }
+    }
+
+    fn record(&self, op: TranscriptOp) {
+        if let Some(log) = &self.log {
+            log.borrow_mut().record(op, self.digest.value());
+        }
     }
 
     pub fn mix_root(&mut self, root: &HashVar) {
         self.digest = Poseidon2HalfVar::permute_get_capacity(root, &self.digest);
         self.n_sent = 0;
+        self.record(TranscriptOp::MixRoot(root.value()));
     }
 
     pub fn draw_felts(&mut self) -> [QM31Var; 2] {
         let cs = self.cs();
 
+        self.record(TranscriptOp::DrawFelts(self.n_sent));
         let n_sent = M31Var::new_constant(&cs, &M31::from(self.n_sent as u32));
         self.n_sent += 1;
 
@@ -50,12 +111,229 @@ impl ChannelVar {
         let left = Poseidon2HalfVar::from_qm31(felt, &QM31Var::zero(&cs));
         self.digest = Poseidon2HalfVar::permute_get_capacity(&left, &self.digest);
         self.n_sent = 0;
+        self.record(TranscriptOp::MixOneFelt(felt.value()));
+    }
+
+    /// Mix a felt only if `bit` is set, at the same cost either way. `n_sent` is reset in both
+    /// cases, so this should follow another mix rather than a draw.
+    pub fn conditional_mix_one_felt(&mut self, felt: &QM31Var, bit: &BitVar) {
+        let cs = self.cs();
+        let left = Poseidon2HalfVar::from_qm31(felt, &QM31Var::zero(&cs));
+        let candidate = Poseidon2HalfVar::permute_get_capacity(&left, &self.digest).to_qm31();
+        let existing = self.digest.to_qm31();
+        self.digest = Poseidon2HalfVar::from_qm31(
+            &QM31Var::select(&existing[0], &candidate[0], bit),
+            &QM31Var::select(&existing[1], &candidate[1], bit),
+        );
+        self.n_sent = 0;
+        if bit.value() {
+            self.record(TranscriptOp::MixOneFelt(felt.value()));
+        }
     }
 
     pub fn mix_two_felts(&mut self, felt1: &QM31Var, felt2: &QM31Var) {
         let left = Poseidon2HalfVar::from_qm31(felt1, felt2);
         self.digest = Poseidon2HalfVar::permute_get_capacity(&left, &self.digest);
         self.n_sent = 0;
+        self.record(TranscriptOp::MixTwoFelts(felt1.value(), felt2.value()));
+    }
+}
+
+/// An in-circuit Fiat-Shamir channel, which is the counterpart of a stwo `Channel` together with
+/// the `mix_root` of its `MerkleChannel`.
+pub trait FiatShamirChannelVar: Var + Clone {
+    type HashVar;
+
+    fn default(cs: &ConstraintSystemRef) -> Self;
+
+    fn mix_root(&mut self, root: &Self::HashVar);
+
+    /// Mix the felts, as `mix_felts`.
+    fn mix_felts(&mut self, felts: &[QM31Var]);
+
+    /// Draw two secure field elements, as `draw_secure_felts(2)`, the first of which is also what
+    /// `draw_secure_felt` returns.
+    fn draw_felts(&mut self) -> [QM31Var; 2];
+
+    /// Mix the proof-of-work nonce, given as limbs of 22, 21 and 21 bits from the least
+    /// significant one, which this also range-checks.
+    fn mix_nonce(&mut self, nonce: &[M31Var; 3]);
+
+    /// Check the proof of work on the channel that the nonce has just been mixed into.
+    fn check_pow(&self, pow_bits: u32);
+
+    /// Draw the raw FRI queries, as `draw_u32s` until there are `n_queries` of them, where only
+    /// the low bits of a query are meaningful.
+    fn draw_raw_queries(&mut self, n_queries: usize) -> Vec<M31Var>;
+
+    /// Start logging the channel operations, which is a no-op for a channel without a log.
+    fn enable_transcript_log(&mut self) {}
+
+    fn transcript_log(&self) -> Option<TranscriptLog> {
+        None
+    }
+}
+
+impl FiatShamirChannelVar for ChannelVar {
+    type HashVar = HashVar;
+
+    fn default(cs: &ConstraintSystemRef) -> Self {
+        ChannelVar::default(cs)
+    }
+
+    fn mix_root(&mut self, root: &HashVar) {
+        ChannelVar::mix_root(self, root);
+    }
+
+    /// Mix the felts two by two, in the same way as `Poseidon31Channel::mix_felts`.
+    fn mix_felts(&mut self, felts: &[QM31Var]) {
+        for chunk in felts.chunks(2) {
+            if chunk.len() == 1 {
+                self.mix_one_felt(&chunk[0]);
+            } else {
+                self.mix_two_felts(&chunk[0], &chunk[1]);
+            }
+        }
+    }
+
+    fn draw_felts(&mut self) -> [QM31Var; 2] {
+        ChannelVar::draw_felts(self)
+    }
+
+    fn mix_nonce(&mut self, nonce: &[M31Var; 3]) {
+        let cs = self.cs();
+        let nonce_felt = QM31Var::from_m31(&nonce[0], &nonce[1], &nonce[2], &M31Var::zero(&cs));
+
+        let _ = BitsVar::from_m31(&nonce[0], 22);
+        let _ = BitsVar::from_m31(&nonce[1], 21);
+        let _ = BitsVar::from_m31(&nonce[2], 21);
+
+        self.mix_one_felt(&nonce_felt);
+    }
+
+    fn check_pow(&self, pow_bits: u32) {
+        let cs = self.cs();
+        let lower_bits = BitsVar::from_m31(&self.digest.to_qm31()[0].decompose_m31()[0], 31)
+            .compose_range(0..pow_bits as usize);
+        lower_bits.equalverify(&M31Var::zero(&cs));
+    }
+
+    fn draw_raw_queries(&mut self, n_queries: usize) -> Vec<M31Var> {
+        let mut raw_queries = Vec::with_capacity(n_queries);
+        let mut draw_queries_felts = Vec::with_capacity(n_queries.div_ceil(4));
+        for _ in 0..n_queries.div_ceil(4) {
+            let [a, b] = ChannelVar::draw_felts(self);
+            draw_queries_felts.push(a);
+            draw_queries_felts.push(b);
+        }
+        for felt in draw_queries_felts.iter() {
+            raw_queries.extend_from_slice(&felt.decompose_m31());
+        }
+        raw_queries.truncate(n_queries);
+        raw_queries
+    }
+
+    fn enable_transcript_log(&mut self) {
+        ChannelVar::enable_transcript_log(self);
+    }
+
+    fn transcript_log(&self) -> Option<TranscriptLog> {
+        ChannelVar::transcript_log(self)
+    }
+}
+
+/// The in-circuit gadgets of a stwo `MerkleChannel`: the Fiat-Shamir channel, and the hasher of
+/// the Merkle trees that it commits to.
+pub trait MerkleChannelVar: MerkleChannel {
+    type HashVar: AllocVar + Var<Value = <Self::H as MerkleHasher>::Hash> + Clone + Debug;
+    type HasherVar: MerkleHasherVar<HashVar = Self::HashVar>;
+    type ChannelVar: FiatShamirChannelVar<HashVar = Self::HashVar>;
+
+    /// The witness from which a node of a FRI layer is recomputed from its column, i.e., what the
+    /// node hash depends on other than the column.
+    type NodeChildrenVar: Clone;
+
+    /// Allocate the hash of a sibling on a Merkle path, which is only used once.
+    fn new_sibling_hash(
+        cs: &ConstraintSystemRef,
+        hash: &<Self::H as MerkleHasher>::Hash,
+    ) -> Self::HashVar;
+
+    fn hash_value(hash: &Self::HashVar) -> <Self::H as MerkleHasher>::Hash;
+
+    fn new_node_children(
+        cs: &ConstraintSystemRef,
+        left: &<Self::H as MerkleHasher>::Hash,
+        right: &<Self::H as MerkleHasher>::Hash,
+    ) -> Self::NodeChildrenVar;
+
+    /// Hash the node on a FRI layer path and the node of its sibling path, at a layer with the
+    /// four columns of a secure field element, where `cur` and `sibling` are swapped if the bit is
+    /// set, and the next sibling is recomputed from its children and its column.
+    fn hash_qm31_node_pair_with_swap(
+        cur: &Self::HashVar,
+        sibling: &Self::HashVar,
+        bit: &BitVar,
+        column: &QM31Var,
+        sibling_children: &Self::NodeChildrenVar,
+        sibling_column: &QM31Var,
+    ) -> (Self::HashVar, Self::HashVar);
+}
+
+impl MerkleChannelVar for Poseidon31MerkleChannel {
+    type HashVar = HashVar;
+    type HasherVar = Poseidon31MerkleHasherVar;
+    type ChannelVar = ChannelVar;
+
+    /// The hash of the children without the column, which the column hash is combined with.
+    type NodeChildrenVar = HashVar;
+
+    fn new_sibling_hash(cs: &ConstraintSystemRef, hash: &Poseidon31Hash) -> HashVar {
+        HashVar::new_single_use_witness_only(cs, &hash.0)
+    }
+
+    fn hash_value(hash: &HashVar) -> Poseidon31Hash {
+        Poseidon31Hash(hash.value())
+    }
+
+    fn new_node_children(
+        cs: &ConstraintSystemRef,
+        left: &Poseidon31Hash,
+        right: &Poseidon31Hash,
+    ) -> HashVar {
+        let hash_tree = Poseidon31MerkleHasher::hash_node(Some((*left, *right)), &[]);
+        HashVar::new_single_use_witness_only(cs, &hash_tree.0)
+    }
+
+    fn hash_qm31_node_pair_with_swap(
+        cur: &HashVar,
+        sibling: &HashVar,
+        bit: &BitVar,
+        column: &QM31Var,
+        sibling_children: &HashVar,
+        sibling_column: &QM31Var,
+    ) -> (HashVar, HashVar) {
+        let cs = column.cs();
+        let column_hash = Poseidon31MerkleHasherVar::hash_qm31_columns_get_capacity(&[
+            column.clone(),
+            QM31Var::zero(&cs),
+        ]);
+        let sibling_column_hash = Poseidon31MerkleHasherVar::hash_qm31_columns_get_capacity(&[
+            sibling_column.clone(),
+            QM31Var::zero(&cs),
+        ]);
+
+        let cur = Poseidon31MerkleHasherVar::hash_tree_with_column_hash_with_swap(
+            cur,
+            sibling,
+            bit,
+            &column_hash,
+        );
+        let sibling = Poseidon31MerkleHasherVar::combine_hash_tree_with_column(
+            sibling_children,
+            &sibling_column_hash,
+        );
+        (cur, sibling)
     }
 }
 
@@ -127,6 +405,7 @@ impl ConditionalChannelMixer {
             if count % 2 == 0 {
                 let should_permute = is_input_2_occupied.clone();
 
+                let mixed = [input_1.value(), input_2.value()];
                 let left = Poseidon2HalfVar::from_qm31(&input_1, &input_2);
                 let existing_digest = self.channel.digest.to_qm31();
                 let candidate_digest =
@@ -145,6 +424,10 @@ impl ConditionalChannelMixer {
 
                 self.channel.digest =
                     Poseidon2HalfVar::from_qm31(&new_digest_left, &new_digest_right);
+                if should_permute.value() {
+                    self.channel
+                        .record(TranscriptOp::MixTwoFelts(mixed[0], mixed[1]));
+                }
             }
         }
 
@@ -166,6 +449,7 @@ impl ConditionalChannelMixer {
             if count % 2 == 0 {
                 let should_permute = is_input_2_occupied.clone();
 
+                let mixed = [input_1.value(), input_2.value()];
                 let left = Poseidon2HalfVar::from_qm31(&input_1, &input_2);
                 let existing_digest = self.channel.digest.to_qm31();
                 let candidate_digest =
@@ -184,6 +468,10 @@ impl ConditionalChannelMixer {
 
                 self.channel.digest =
                     Poseidon2HalfVar::from_qm31(&new_digest_left, &new_digest_right);
+                if should_permute.value() {
+                    self.channel
+                        .record(TranscriptOp::MixTwoFelts(mixed[0], mixed[1]));
+                }
             }
         }
 
@@ -201,6 +489,15 @@ impl ConditionalChannelMixer {
             QM31Var::select(&existing_digest[1], &candidate_digest[1], &should_permute);
 
         self.channel.digest = Poseidon2HalfVar::from_qm31(&new_digest_left, &new_digest_right);
+        if should_permute.value() {
+            if is_input_2_occupied.value() {
+                self.channel
+                    .record(TranscriptOp::MixTwoFelts(input_1.value(), input_2.value()));
+            } else {
+                self.channel
+                    .record(TranscriptOp::MixOneFelt(input_1.value()));
+            }
+        }
 
         self.channel.n_sent = 0;
         self.channel
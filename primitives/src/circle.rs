@@ -1,5 +1,5 @@
 use crate::bits::{BitVar, BitsVar};
-use crate::channel::ChannelVar;
+use crate::channel::FiatShamirChannelVar;
 use crate::{M31Var, QM31Var};
 use circle_plonk_dsl_constraint_system::var::{AllocVar, AllocationMode, Var};
 use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
@@ -126,6 +126,84 @@ impl CirclePointM31Var {
     }
 }
 
+impl CirclePointM31Var {
+    pub fn zero(cs: &ConstraintSystemRef) -> Self {
+        Self {
+            x: M31Var::one(cs),
+            y: M31Var::zero(cs),
+        }
+    }
+
+    pub fn conditional_select(a: &Self, b: &Self, bit: &BitVar) -> Self {
+        Self {
+            x: M31Var::select(&a.x, &b.x, bit),
+            y: M31Var::select(&a.y, &b.y, bit),
+        }
+    }
+
+    pub fn equalverify(&self, rhs: &Self) {
+        self.x.equalverify(&rhs.x);
+        self.y.equalverify(&rhs.y);
+    }
+
+    /// Compute `scalar * self` where the scalar is given in little-endian bits.
+    ///
+    /// The circle group law is complete, so the double-and-add does not need to
+    /// handle the identity or doubling as special cases.
+    pub fn scalar_mul(&self, scalar: &BitsVar) -> Self {
+        let cs = self.cs().and(&scalar.cs());
+
+        let mut res = Self::zero(&cs);
+        let mut cur = self.clone();
+        for (i, bit) in scalar.0.iter().enumerate() {
+            let added = &res + &cur;
+            res = Self::conditional_select(&res, &added, bit);
+            if i != scalar.0.len() - 1 {
+                cur = cur.double();
+            }
+        }
+        res
+    }
+
+    pub fn repeated_double_x_only(&self, log_size: u32) -> M31Var {
+        let cs = self.cs();
+        let mut x = self.x.clone();
+        for _ in 0..log_size {
+            let x_square = &x * &x;
+            x = &(&x_square + &x_square) - &M31Var::one(&cs);
+        }
+        x
+    }
+
+    pub fn is_on_curve(&self) -> BitVar {
+        let cs = self.cs();
+        let norm = &(&self.x * &self.x) + &(&self.y * &self.y);
+        norm.is_eq(&M31Var::one(&cs))
+    }
+
+    pub fn enforce_on_curve(&self) {
+        let cs = self.cs();
+        self.is_on_curve().equalverify(&BitVar::new_true(&cs));
+    }
+
+    /// Enforce that the point is on the circle and that its order divides `2^log_order`.
+    ///
+    /// Once the point is on the circle, doubling only depends on x, and a point with x = 1
+    /// must be the identity.
+    pub fn enforce_in_subgroup(&self, log_order: u32) {
+        let cs = self.cs();
+        self.enforce_on_curve();
+        self.repeated_double_x_only(log_order)
+            .equalverify(&M31Var::one(&cs));
+    }
+
+    /// Compute `initial + index * step`, the point at `index` of an arbitrary coset, where the
+    /// index is given in little-endian bits (natural order, not bit-reversed).
+    pub fn coset_at(initial: &Self, step: &Self, index: &BitsVar) -> Self {
+        initial + &step.scalar_mul(index)
+    }
+}
+
 impl CirclePointM31Var {
     pub fn bit_reverse_at(coset: &Coset, bits_var: &BitsVar, log_size: u32) -> Self {
         assert_eq!(bits_var.0.len(), log_size as usize);
@@ -204,7 +282,7 @@ impl CirclePointQM31Var {
         Self { x, y }
     }
 
-    pub fn from_channel(channel: &mut ChannelVar) -> Self {
+    pub fn from_channel(channel: &mut impl FiatShamirChannelVar) -> Self {
         let [t, _] = channel.draw_felts();
         Self::from_t(&t)
     }
@@ -219,6 +297,89 @@ impl CirclePointQM31Var {
     }
 }
 
+impl Add<&CirclePointQM31Var> for &CirclePointQM31Var {
+    type Output = CirclePointQM31Var;
+
+    fn add(self, rhs: &CirclePointQM31Var) -> Self::Output {
+        let x1x2 = &self.x * &rhs.x;
+        let y1y2 = &self.y * &rhs.y;
+        let x1y2 = &self.x * &rhs.y;
+        let y1x2 = &self.y * &rhs.x;
+
+        let new_x = &x1x2 - &y1y2;
+        let new_y = &x1y2 + &y1x2;
+
+        CirclePointQM31Var { x: new_x, y: new_y }
+    }
+}
+
+impl CirclePointQM31Var {
+    pub fn zero(cs: &ConstraintSystemRef) -> Self {
+        Self {
+            x: QM31Var::one(cs),
+            y: QM31Var::zero(cs),
+        }
+    }
+
+    pub fn double(&self) -> Self {
+        let xx = &self.x * &self.x;
+        let yy = &self.y * &self.y;
+        let xy = &self.x * &self.y;
+
+        let new_x = &xx - &yy;
+        let new_y = &xy + &xy;
+
+        CirclePointQM31Var { x: new_x, y: new_y }
+    }
+
+    pub fn conditional_select(a: &Self, b: &Self, bit: &BitVar) -> Self {
+        Self {
+            x: QM31Var::select(&a.x, &b.x, bit),
+            y: QM31Var::select(&a.y, &b.y, bit),
+        }
+    }
+
+    pub fn equalverify(&self, rhs: &Self) {
+        self.x.equalverify(&rhs.x);
+        self.y.equalverify(&rhs.y);
+    }
+
+    /// Compute `scalar * self` where the scalar is given in little-endian bits.
+    pub fn scalar_mul(&self, scalar: &BitsVar) -> Self {
+        let cs = self.cs().and(&scalar.cs());
+
+        let mut res = Self::zero(&cs);
+        let mut cur = self.clone();
+        for (i, bit) in scalar.0.iter().enumerate() {
+            let added = &res + &cur;
+            res = Self::conditional_select(&res, &added, bit);
+            if i != scalar.0.len() - 1 {
+                cur = cur.double();
+            }
+        }
+        res
+    }
+
+    pub fn is_on_curve(&self) -> BitVar {
+        let cs = self.cs();
+        let norm = &(&self.x * &self.x) + &(&self.y * &self.y);
+        norm.is_eq(&QM31Var::one(&cs))
+    }
+
+    pub fn enforce_on_curve(&self) {
+        let cs = self.cs();
+        self.is_on_curve().equalverify(&BitVar::new_true(&cs));
+    }
+
+    /// Enforce that the point is on the circle and that its order divides `2^log_order`.
+    pub fn enforce_in_subgroup(&self, log_order: u32) {
+        let cs = self.cs();
+        self.enforce_on_curve();
+        self.repeated_double_x_only(log_order)
+            .equalverify(&QM31Var::one(&cs));
+    }
+}
+
 impl Add<&CirclePoint<M31>> for &CirclePointQM31Var {
     type Output = CirclePointQM31Var;
 
@@ -254,10 +415,13 @@ impl Add<&CirclePointM31Var> for &CirclePointQM31Var {
 #[cfg(test)]
 mod test {
     use crate::BitsVar;
-    use crate::CirclePointM31Var;
     use crate::M31Var;
+    use crate::{CirclePointM31Var, CirclePointQM31Var};
     use circle_plonk_dsl_constraint_system::var::AllocVar;
     use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+    use stwo::core::circle::{CirclePoint, M31_CIRCLE_GEN, SECURE_FIELD_CIRCLE_GEN};
     use stwo::core::fields::m31::M31;
     use stwo::core::poly::circle::CanonicCoset;
     use stwo::core::utils::bit_reverse_index;
@@ -285,4 +449,93 @@ mod test {
         assert_eq!(b.x, b_point.x.value);
         assert_eq!(b.y, b_point.y.value);
     }
+
+    #[test]
+    fn test_scalar_mul() {
+        let mut prng = SmallRng::seed_from_u64(0);
+
+        let cs = ConstraintSystemRef::new();
+        for _ in 0..4 {
+            let scalar = prng.gen::<u32>() & ((1 << 31) - 1);
+            let point = M31_CIRCLE_GEN.mul(prng.gen::<u128>());
+            let expected = point.mul(scalar as u128);
+
+            let point_var = CirclePointM31Var::new_witness(&cs, &point);
+            point_var.enforce_on_curve();
+
+            let scalar_var = M31Var::new_witness(&cs, &M31::from(scalar));
+            let scalar_bits = BitsVar::from_m31(&scalar_var, 31);
+            let res = point_var.scalar_mul(&scalar_bits);
+            res.equalverify(&CirclePointM31Var::new_constant(&cs, &expected));
+        }
+
+        let oods_point = SECURE_FIELD_CIRCLE_GEN.mul(prng.gen::<u128>());
+        let scalar = prng.gen::<u32>() & ((1 << 20) - 1);
+        let expected = oods_point.mul(scalar as u128);
+
+        let point_var = CirclePointQM31Var::new_witness(&cs, &oods_point);
+        point_var.enforce_on_curve();
+
+        let scalar_var = M31Var::new_witness(&cs, &M31::from(scalar));
+        let scalar_bits = BitsVar::from_m31(&scalar_var, 20);
+        let res = point_var.scalar_mul(&scalar_bits);
+        res.equalverify(&CirclePointQM31Var::new_witness(&cs, &expected));
+
+        cs.pad();
+        cs.check_arithmetics();
+    }
+
+    #[test]
+    fn test_subgroup() {
+        let cs = ConstraintSystemRef::new();
+
+        let coset = CanonicCoset::new(10).coset;
+        let step = CirclePointM31Var::new_witness(&cs, &coset.step);
+        step.enforce_in_subgroup(10);
+        assert!(step.is_on_curve().value());
+
+        let initial = CirclePointM31Var::new_witness(&cs, &coset.initial);
+        let index_var = M31Var::new_witness(&cs, &M31::from(300));
+        let index_bits = BitsVar::from_m31(&index_var, 10);
+        let point = CirclePointM31Var::coset_at(&initial, &step, &index_bits);
+        assert_eq!(point.value(), coset.at(300));
+
+        let not_on_curve = CirclePointM31Var::new_witness(
+            &cs,
+            &CirclePoint {
+                x: M31::from(2),
+                y: M31::from(3),
+            },
+        );
+        assert!(!not_on_curve.is_on_curve().value());
+
+        cs.pad();
+        cs.check_arithmetics();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_subgroup_rejects() {
+        let cs = ConstraintSystemRef::new();
+
+        // the step of a coset of size 2^11 is on the circle but has order 2^11
+        let step = CirclePointM31Var::new_witness(&cs, &CanonicCoset::new(11).coset.step);
+        step.enforce_on_curve();
+        step.enforce_in_subgroup(10);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_on_curve_rejects() {
+        let cs = ConstraintSystemRef::new();
+
+        let not_on_curve = CirclePointM31Var::new_witness(
+            &cs,
+            &CirclePoint {
+                x: M31::from(2),
+                y: M31::from(3),
+            },
+        );
+        not_on_curve.enforce_on_curve();
+    }
 }
@@ -12,6 +12,7 @@ pub use qm31::*;
 
 // Other modules
 pub mod bits;
+pub mod blake2s;
 pub mod channel;
 pub mod circle;
 pub mod fields;
@@ -19,18 +20,28 @@ pub mod line;
 pub mod merkle;
 pub mod option;
 pub mod query;
+pub mod relation;
+pub mod smt;
+pub mod transcript;
 
 // Poseidon31 module
 pub mod poseidon31;
 
 // Re-export commonly used types for convenience
 pub use bits::{BitIntVar, BitVar, BitsVar};
-pub use channel::{ChannelVar, HashVar};
+pub use blake2s::{Blake2sChannelVar, Blake2sHashVar, Blake2sMerkleHasherVar, U32Var};
+pub use channel::{ChannelVar, FiatShamirChannelVar, HashVar, MerkleChannelVar};
 pub use circle::{CirclePointM31Var, CirclePointQM31Var};
 pub use line::LinePolyVar;
-pub use merkle::Poseidon31MerkleHasherVar;
+pub use merkle::{
+    merkle_multi_proof_positions, MerkleHasherVar, Poseidon31MerkleHasherVar,
+    Poseidon31MerkleMultiProof, Poseidon31MerkleMultiProofVar,
+};
 pub use poseidon31::Poseidon2HalfVar;
 pub use query::{PointCarryingQueryVar, QueryPositionsPerLogSizeVar};
+pub use relation::{RelationElementsVar, RelationRedirect};
+pub use smt::{SparseMerkleProof, SparseMerkleProofVar, SparseMerkleTree, SMT_MAX_DEPTH};
+pub use transcript::{compare_transcripts, LoggedPoseidon31Channel, TranscriptLog};
 
 // Oblivious data structures
 pub mod oblivious_map;
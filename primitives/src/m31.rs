@@ -240,11 +240,78 @@ impl M31Var {
 
         M31Var::select(rhs, self, is_lhs_no_less_than_rhs)
     }
+
+    /// Enforce that the value is less than `2^bits`, with a cost of `bits` bit variables.
+    ///
+    /// For `bits = 31`, the decomposition of the modulus itself is rejected, so the returned bits
+    /// are always the canonical representation of the value.
+    pub fn range_check(&self, bits: usize) -> crate::BitsVar {
+        assert!((1..=31).contains(&bits));
+        crate::BitsVar::from_m31(self, bits)
+    }
+
+    /// Enforce that the value, seen as an integer in `[0, P)`, is less than the constant `bound`.
+    ///
+    /// The bound is only enforced by the constraints, so a witness out of range is reported by
+    /// the constraint checks rather than during the synthesis.
+    pub fn assert_less_than(&self, bound: u32) {
+        assert!((1..=P).contains(&bound));
+
+        let bits = (32 - (bound - 1).leading_zeros()).max(1) as usize;
+        if bits <= 30 {
+            // Both `self` and `bound - 1 - self` fit in `bits` bits only when `self < bound`, as
+            // for `bound <= self < 2^bits` the wrapped difference is at least
+            // `P - 2^30 + 1 = 2^30`.
+            crate::BitsVar::from_m31_constrained(self, bits);
+            if bound != 1 << bits {
+                let upper = &M31Var::new_constant(&self.cs, &M31::from(bound - 1)) - self;
+                crate::BitsVar::from_m31_constrained(&upper, bits);
+            }
+        } else {
+            let cs = self.cs();
+            let self_bits = crate::BitsVar::from_m31_constrained(self, 31);
+            let bound_bits = crate::BitsVar::new_constant(
+                &cs,
+                &(0..31).map(|i| ((bound - 1) >> i) & 1 != 0).collect(),
+            );
+            cs.enforce_zero(self_bits.is_greater_than(&bound_bits).0.variable);
+        }
+    }
+
+    /// Enforce that `lo <= self < hi` for constants `lo` and `hi`.
+    pub fn assert_in_range(&self, lo: u32, hi: u32) {
+        assert!(lo < hi && hi <= P);
+        let shifted = self - &M31Var::new_constant(&self.cs, &M31::from(lo));
+        shifted.assert_less_than(hi - lo);
+    }
+
+    /// Return whether `self < rhs`, after enforcing that both are less than `2^bits`.
+    pub fn is_less_than(&self, rhs: &M31Var, bits: usize) -> BitVar {
+        assert!((1..=31).contains(&bits));
+        let cs = self.cs().and(&rhs.cs());
+
+        if bits <= 29 {
+            self.range_check(bits);
+            rhs.range_check(bits);
+
+            // `self + 2^bits - rhs` lies in `(0, 2^(bits + 1))` without wrapping around, and its
+            // top bit is set if and only if `self >= rhs`.
+            let shift = M31Var::new_constant(&cs, &M31::from(1 << bits));
+            let v = &(self + &shift) - rhs;
+            let v_bits = v.range_check(bits + 1);
+            v_bits.0[bits].neg()
+        } else {
+            let self_bits = self.range_check(bits);
+            let rhs_bits = rhs.range_check(bits);
+            rhs_bits.is_greater_than(&self_bits)
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use stwo::core::fields::qm31::QM31;
 
     #[test]
     fn test_exp2() {
@@ -256,4 +323,100 @@ mod tests {
         cs.pad();
         cs.check_arithmetics();
     }
+
+    #[test]
+    fn test_range_checks() {
+        let cs = ConstraintSystemRef::new();
+
+        let a = M31Var::new_witness(&cs, &M31::from(1000));
+        a.range_check(10);
+        a.assert_less_than(1001);
+        a.assert_less_than(1024);
+        a.assert_in_range(1000, 1001);
+
+        let big = M31Var::new_witness(&cs, &M31::from(P - 1));
+        big.range_check(31);
+        big.assert_less_than(P);
+
+        let b = M31Var::new_witness(&cs, &M31::from(999));
+        assert!(b.is_less_than(&a, 10).value());
+        assert!(!a.is_less_than(&b, 10).value());
+        assert!(!a.is_less_than(&a, 10).value());
+        assert!(b.is_less_than(&big, 31).value());
+        assert!(!big.is_less_than(&b, 31).value());
+
+        cs.pad();
+        cs.check_arithmetics();
+    }
+
+    #[test]
+    #[should_panic(expected = "is incorrect")]
+    fn test_assert_less_than_rejects() {
+        let cs = ConstraintSystemRef::new();
+        let a = M31Var::new_witness(&cs, &M31::from(999));
+        a.assert_less_than(1000);
+        cs.pad();
+        cs.check_arithmetics();
+
+        // a prover claiming `a = 1000` cannot satisfy the constraints
+        cs.set_value(a.variable, QM31::from(M31::from(1000)));
+        cs.check_arithmetics();
+    }
+
+    #[test]
+    #[should_panic(expected = "is incorrect")]
+    fn test_assert_less_than_rejects_large_bound() {
+        let cs = ConstraintSystemRef::new();
+        let a = M31Var::new_witness(&cs, &M31::from((1 << 30) + 4));
+        a.assert_less_than((1 << 30) + 5);
+        cs.pad();
+        cs.check_arithmetics();
+
+        cs.set_value(a.variable, QM31::from(M31::from((1 << 30) + 5)));
+        cs.check_arithmetics();
+    }
+
+    #[test]
+    #[should_panic(expected = "is incorrect")]
+    fn test_assert_less_than_rejects_equal_bound() {
+        // `a` has a valid decomposition into 10 bits, and only the bound is violated
+        let cs = ConstraintSystemRef::new();
+        let a = M31Var::new_witness(&cs, &M31::from(1000));
+        a.range_check(10);
+        a.assert_less_than(1000);
+        cs.pad();
+        cs.check_arithmetics();
+    }
+
+    #[test]
+    #[should_panic(expected = "is incorrect")]
+    fn test_assert_less_than_rejects_equal_large_bound() {
+        let cs = ConstraintSystemRef::new();
+        let a = M31Var::new_witness(&cs, &M31::from((1 << 30) + 5));
+        a.range_check(31);
+        a.assert_less_than((1 << 30) + 5);
+        cs.pad();
+        cs.check_arithmetics();
+    }
+
+    #[test]
+    #[should_panic(expected = "is incorrect")]
+    fn test_assert_in_range_rejects_above() {
+        // `a - lo = 501` has a valid decomposition into 9 bits, and only the bound is violated
+        let cs = ConstraintSystemRef::new();
+        let a = M31Var::new_witness(&cs, &M31::from(1501));
+        a.assert_in_range(1000, 1500);
+        cs.pad();
+        cs.check_arithmetics();
+    }
+
+    #[test]
+    #[should_panic(expected = "is incorrect")]
+    fn test_assert_in_range_rejects_below() {
+        let cs = ConstraintSystemRef::new();
+        let a = M31Var::new_witness(&cs, &M31::from(999));
+        a.assert_in_range(1000, 1500);
+        cs.pad();
+        cs.check_arithmetics();
+    }
 }
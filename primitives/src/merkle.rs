@@ -1,8 +1,16 @@
-use crate::bits::BitVar;
+use crate::bits::{BitVar, BitsVar};
+use crate::blake2s::{Blake2sHashVar, Blake2sMerkleHasherVar};
 use crate::poseidon31::Poseidon2HalfVar;
 use crate::{M31Var, QM31Var};
-use circle_plonk_dsl_constraint_system::var::Var;
+use circle_plonk_dsl_constraint_system::var::{AllocVar, AllocationMode, Var};
+use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
 use std::cmp::min;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use stwo::core::fields::m31::M31;
+use stwo::core::vcs::poseidon31_hash::Poseidon31Hash;
+use stwo::core::vcs::poseidon31_merkle::Poseidon31MerkleHasher;
+use stwo::core::vcs::MerkleHasher;
 
 pub struct Poseidon31MerkleHasherVar;
 
@@ -142,9 +150,424 @@ impl Poseidon31MerkleHasherVar {
     }
 }
 
+/// An in-circuit Merkle hasher, which lets the Merkle path verification work with the hash
+/// functions of different stwo Merkle channels.
+///
+/// It is the hasher of a [`crate::channel::MerkleChannelVar`], through which the Fiat-Shamir,
+/// decommitment and folding gadgets of the Plonk verifier work with either Merkle channel. The
+/// batched [`Poseidon31MerkleMultiProofVar`] is specific to Poseidon31.
+pub trait MerkleHasherVar {
+    type HashVar: Clone + Debug;
+
+    /// The hash of a leaf, i.e., `hash_node(None, column_values)`, where the leaf can have no
+    /// columns.
+    fn hash_leaf(cs: &ConstraintSystemRef, column_values: &[M31Var]) -> Self::HashVar;
+
+    /// The hash of an inner node, i.e., `hash_node(Some((cur, sibling)), column_values)`, where
+    /// `cur` and `sibling` are swapped if the bit is set.
+    fn hash_node_with_swap(
+        cur: &Self::HashVar,
+        sibling: &Self::HashVar,
+        bit: &BitVar,
+        column_values: &[M31Var],
+    ) -> Self::HashVar;
+
+    fn equalverify(a: &Self::HashVar, b: &Self::HashVar);
+
+    /// The hash of a leaf with the four columns of a secure field element, as in the FRI layers.
+    fn hash_qm31_leaf(column: &QM31Var) -> Self::HashVar {
+        Self::hash_leaf(&column.cs(), &column.decompose_m31())
+    }
+}
+
+impl MerkleHasherVar for Poseidon31MerkleHasherVar {
+    type HashVar = Poseidon2HalfVar;
+
+    fn hash_leaf(_cs: &ConstraintSystemRef, column_values: &[M31Var]) -> Poseidon2HalfVar {
+        assert!(
+            !column_values.is_empty(),
+            "the leaves of a Poseidon31 tree have columns"
+        );
+        Self::hash_m31_columns_get_rate(column_values)
+    }
+
+    fn hash_node_with_swap(
+        cur: &Poseidon2HalfVar,
+        sibling: &Poseidon2HalfVar,
+        bit: &BitVar,
+        column_values: &[M31Var],
+    ) -> Poseidon2HalfVar {
+        if column_values.is_empty() {
+            Self::hash_tree_with_swap(cur, sibling, bit)
+        } else {
+            let column_hash = Self::hash_m31_columns_get_capacity(column_values);
+            Self::hash_tree_with_column_hash_with_swap(cur, sibling, bit, &column_hash)
+        }
+    }
+
+    fn equalverify(a: &Poseidon2HalfVar, b: &Poseidon2HalfVar) {
+        a.equalverify(b);
+    }
+
+    fn hash_qm31_leaf(column: &QM31Var) -> Poseidon2HalfVar {
+        let cs = column.cs();
+        Self::hash_qm31_columns_get_rate(&[column.clone(), QM31Var::zero(&cs)])
+    }
+}
+
+impl MerkleHasherVar for Blake2sMerkleHasherVar {
+    type HashVar = Blake2sHashVar;
+
+    fn hash_leaf(cs: &ConstraintSystemRef, column_values: &[M31Var]) -> Blake2sHashVar {
+        Self::hash_node(cs, None, column_values)
+    }
+
+    fn hash_node_with_swap(
+        cur: &Blake2sHashVar,
+        sibling: &Blake2sHashVar,
+        bit: &BitVar,
+        column_values: &[M31Var],
+    ) -> Blake2sHashVar {
+        Self::hash_tree_with_swap(cur, sibling, bit, column_values)
+    }
+
+    fn equalverify(a: &Blake2sHashVar, b: &Blake2sHashVar) {
+        a.equalverify(b);
+    }
+}
+
+/// Compute the root of the Merkle path of a query, where `columns` maps a layer (with the leaves
+/// at layer `depth`) to the values of the columns of that layer, and the siblings are ordered
+/// from the leaves to the root.
+pub fn compute_merkle_path_root<H: MerkleHasherVar>(
+    depth: usize,
+    query: &BitsVar,
+    sibling_hashes: &[H::HashVar],
+    columns: &BTreeMap<usize, Vec<M31Var>>,
+) -> H::HashVar {
+    let leaf = columns.get(&depth).map_or(&[][..], |v| v.as_slice());
+    let mut cur_hash = H::hash_leaf(&query.cs(), leaf);
+
+    for (i, sibling_hash) in sibling_hashes.iter().enumerate().take(depth) {
+        let h = depth - i - 1;
+        let column_values = columns.get(&h).map_or(&[][..], |v| v.as_slice());
+        cur_hash = H::hash_node_with_swap(&cur_hash, sibling_hash, &query.0[i], column_values);
+    }
+    cur_hash
+}
+
+/// The positions of the nodes that the walk of a Merkle multi-proof computes at each height,
+/// indexed by the height, which are the parents of the nodes computed at the height below and the
+/// queries at this height, sorted and deduplicated.
+pub fn merkle_multi_proof_positions(
+    depth: usize,
+    queries_per_log_size: &BTreeMap<u32, Vec<usize>>,
+) -> Vec<Vec<usize>> {
+    let mut res = vec![vec![]; depth + 1];
+    let mut positions: Vec<usize> = vec![];
+    for h in (0..=depth).rev() {
+        positions = positions
+            .iter()
+            .map(|p| p >> 1)
+            .chain(
+                queries_per_log_size
+                    .get(&(h as u32))
+                    .into_iter()
+                    .flatten()
+                    .copied(),
+            )
+            .collect();
+        positions.sort_unstable();
+        positions.dedup();
+        res[h] = positions.clone();
+    }
+    res
+}
+
+/// A batched Merkle decommitment for several queries into the same Poseidon31 tree, where the
+/// queries can be at any layer of the tree, used as the witness of
+/// [`Poseidon31MerkleMultiProofVar`].
+#[derive(Clone, Debug)]
+pub struct Poseidon31MerkleMultiProof {
+    pub depth: usize,
+    /// The queried positions at each log size, sorted and deduplicated.
+    pub queries_per_log_size: BTreeMap<u32, Vec<usize>>,
+    /// The hashes of the children that the walk does not compute, in the order in which it
+    /// consumes them, the left child first, as in the `hash_witness` of a stwo
+    /// `MerkleDecommitment`.
+    pub witness: Vec<Poseidon31Hash>,
+    /// The column values of the computed nodes, indexed first by the height of the layer and then
+    /// by the node position.
+    pub columns: BTreeMap<usize, BTreeMap<usize, Vec<M31>>>,
+}
+
+impl Poseidon31MerkleMultiProof {
+    fn column_values(&self, h: usize, position: usize) -> &[M31] {
+        self.columns
+            .get(&h)
+            .and_then(|layer| layer.get(&position))
+            .map_or(&[][..], |v| v.as_slice())
+    }
+
+    /// Compute the root, hashing each node of the walk once, in the same order as the circuit.
+    pub fn compute_root(&self) -> Poseidon31Hash {
+        let positions = merkle_multi_proof_positions(self.depth, &self.queries_per_log_size);
+        let mut witness = self.witness.iter();
+
+        let mut below: Vec<(usize, Poseidon31Hash)> = vec![];
+        for h in (0..=self.depth).rev() {
+            let mut children = below.into_iter().peekable();
+            let mut layer = Vec::with_capacity(positions[h].len());
+            for &position in positions[h].iter() {
+                let child_hashes = (h < self.depth).then(|| {
+                    let mut child = |idx: usize| {
+                        children
+                            .next_if(|(p, _)| *p == idx)
+                            .map_or_else(|| *witness.next().unwrap(), |(_, hash)| hash)
+                    };
+                    (child(2 * position), child(2 * position + 1))
+                });
+                let hash =
+                    Poseidon31MerkleHasher::hash_node(children, self.column_values(h, position));
+                layer.push((position, hash));
+            }
+            assert!(children.next().is_none());
+            below = layer;
+        }
+        assert!(witness.next().is_none());
+
+        assert_eq!(below.len(), 1);
+        below[0].1
+    }
+}
+
+/// A node computed by the walk of a [`Poseidon31MerkleMultiProofVar`], with its position in
+/// little-endian bits, as many as the height of its layer.
+struct MultiProofNodeVar {
+    position: usize,
+    hash: Poseidon2HalfVar,
+    bits: Vec<BitVar>,
+}
+
+/// A batched Merkle decommitment gadget that verifies the paths of several queries into the same
+/// Poseidon31 tree, where the queries can be at any layer of the tree.
+///
+/// It walks the tree layer by layer from the leaves, like the Merkle verifier of stwo, and hashes
+/// every node of the walk once, so the nodes that several paths share are only hashed once, and
+/// the witness only has the siblings that the walk does not compute. Two nodes are combined into
+/// their parent only after checking that their positions, taken from the query bits, differ only
+/// in the last bit.
+///
+/// The layout, i.e., which nodes the walk combines, follows the native positions of the witness,
+/// so the circuit depends on how the queries share their paths, and not only on the number of
+/// queries. It is therefore not usable inside a verifier whose circuit must have a fixed shape,
+/// such as the recursive verifiers of this repository, which decommit every query along its own
+/// path. It only fits a circuit that is generated for the queries that it checks.
+#[derive(Debug, Clone)]
+pub struct Poseidon31MerkleMultiProofVar {
+    pub cs: ConstraintSystemRef,
+    pub depth: usize,
+    pub queries_per_log_size: BTreeMap<u32, Vec<usize>>,
+    pub witness: Vec<Poseidon2HalfVar>,
+    pub columns: BTreeMap<usize, BTreeMap<usize, Vec<M31Var>>>,
+}
+
+impl Var for Poseidon31MerkleMultiProofVar {
+    type Value = Poseidon31MerkleMultiProof;
+
+    fn cs(&self) -> ConstraintSystemRef {
+        self.cs.clone()
+    }
+}
+
+impl AllocVar for Poseidon31MerkleMultiProofVar {
+    fn new_variables(cs: &ConstraintSystemRef, value: &Self::Value, mode: AllocationMode) -> Self {
+        // the witness siblings are only used as the inputs of one hash
+        let witness = value
+            .witness
+            .iter()
+            .map(|hash| match mode {
+                AllocationMode::Witness => {
+                    Poseidon2HalfVar::new_single_use_witness_only(cs, &hash.0)
+                }
+                _ => Poseidon2HalfVar::new_variables(cs, hash, mode),
+            })
+            .collect();
+        let columns = value
+            .columns
+            .iter()
+            .map(|(&h, layer)| {
+                (
+                    h,
+                    layer
+                        .iter()
+                        .map(|(&position, values)| {
+                            (
+                                position,
+                                values
+                                    .iter()
+                                    .map(|v| M31Var::new_variables(cs, v, mode))
+                                    .collect::<Vec<_>>(),
+                            )
+                        })
+                        .collect(),
+                )
+            })
+            .collect();
+
+        Self {
+            cs: cs.clone(),
+            depth: value.depth,
+            queries_per_log_size: value.queries_per_log_size.clone(),
+            witness,
+            columns,
+        }
+    }
+}
+
+impl Poseidon31MerkleMultiProofVar {
+    /// The column values of the node at `position` of the layer at height `h`, which are the
+    /// queried values when the node is queried.
+    pub fn column_values(&self, h: usize, position: usize) -> &[M31Var] {
+        self.columns
+            .get(&h)
+            .and_then(|layer| layer.get(&position))
+            .map_or(&[][..], |v| v.as_slice())
+    }
+
+    fn hash_node(
+        left: &Poseidon2HalfVar,
+        right: &Poseidon2HalfVar,
+        swap: Option<&BitVar>,
+        column_values: &[M31Var],
+    ) -> Poseidon2HalfVar {
+        let column_hash = (!column_values.is_empty())
+            .then(|| Poseidon31MerkleHasherVar::hash_m31_columns_get_capacity(column_values));
+        match (swap, column_hash) {
+            (None, None) => Poseidon31MerkleHasherVar::hash_tree(left, right),
+            (None, Some(column_hash)) => {
+                Poseidon31MerkleHasherVar::hash_tree_with_column(left, right, &column_hash)
+            }
+            (Some(bit), None) => Poseidon31MerkleHasherVar::hash_tree_with_swap(left, right, bit),
+            (Some(bit), Some(column_hash)) => {
+                Poseidon31MerkleHasherVar::hash_tree_with_column_hash_with_swap(
+                    left,
+                    right,
+                    bit,
+                    &column_hash,
+                )
+            }
+        }
+    }
+
+    /// Verify the paths, where `queries[&log_size][j]` is the position of the j-th query of the
+    /// layer at `log_size`, in the order of the witness, in little-endian bits, with as many bits
+    /// as the log size.
+    pub fn verify(&self, root: &Poseidon2HalfVar, queries: &BTreeMap<u32, Vec<BitsVar>>) {
+        assert_eq!(
+            queries.keys().collect::<Vec<_>>(),
+            self.queries_per_log_size.keys().collect::<Vec<_>>()
+        );
+        for (log_size, positions) in self.queries_per_log_size.iter() {
+            assert_eq!(queries[log_size].len(), positions.len());
+            for query in queries[log_size].iter() {
+                assert_eq!(query.0.len(), *log_size as usize);
+            }
+        }
+
+        let cs = self.cs().and(&root.cs());
+        let positions = merkle_multi_proof_positions(self.depth, &self.queries_per_log_size);
+        let mut witness = self.witness.iter();
+
+        let mut below: Vec<MultiProofNodeVar> = vec![];
+        for h in (0..=self.depth).rev() {
+            let queried: BTreeMap<usize, &BitsVar> = self
+                .queries_per_log_size
+                .get(&(h as u32))
+                .into_iter()
+                .flatten()
+                .copied()
+                .zip(queries.get(&(h as u32)).into_iter().flatten())
+                .collect();
+
+            let mut children = below.into_iter().peekable();
+            let mut layer = Vec::with_capacity(positions[h].len());
+            for &position in positions[h].iter() {
+                let column_values = self.column_values(h, position);
+
+                let (hash, bits) = if h == self.depth {
+                    (
+                        Poseidon31MerkleHasherVar::hash_m31_columns_get_rate(column_values),
+                        None,
+                    )
+                } else {
+                    let left = children.next_if(|node| node.position == 2 * position);
+                    let right = children.next_if(|node| node.position == 2 * position + 1);
+                    match (left, right) {
+                        (Some(left), Some(right)) => {
+                            left.bits[0].equalverify(&BitVar::new_false(&cs));
+                            right.bits[0].equalverify(&BitVar::new_true(&cs));
+                            for (a, b) in left.bits[1..].iter().zip(right.bits[1..].iter()) {
+                                a.equalverify(b);
+                            }
+                            let hash =
+                                Self::hash_node(&left.hash, &right.hash, None, column_values);
+                            (hash, Some(left.bits[1..].to_vec()))
+                        }
+                        (Some(child), None) | (None, Some(child)) => {
+                            let sibling = witness.next().unwrap();
+                            let hash = Self::hash_node(
+                                &child.hash,
+                                sibling,
+                                Some(&child.bits[0]),
+                                column_values,
+                            );
+                            (hash, Some(child.bits[1..].to_vec()))
+                        }
+                        (None, None) => {
+                            let left = witness.next().unwrap();
+                            let right = witness.next().unwrap();
+                            (Self::hash_node(left, right, None, column_values), None)
+                        }
+                    }
+                };
+
+                // a queried node takes the position of the query, and a node computed from its
+                // children must agree with it
+                let bits = match (bits, queried.get(&position)) {
+                    (Some(bits), Some(query)) => {
+                        for (a, b) in bits.iter().zip(query.0.iter()) {
+                            a.equalverify(b);
+                        }
+                        bits
+                    }
+                    (Some(bits), None) => bits,
+                    (None, Some(query)) => query.0.clone(),
+                    (None, None) => unreachable!("the node is neither queried nor computed"),
+                };
+
+                layer.push(MultiProofNodeVar {
+                    position,
+                    hash,
+                    bits,
+                });
+            }
+            assert!(children.next().is_none());
+            below = layer;
+        }
+        assert!(witness.next().is_none());
+
+        assert_eq!(below.len(), 1);
+        below[0].hash.equalverify(root);
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use crate::blake2s::{Blake2sHashVar, Blake2sMerkleHasherVar};
+    use crate::merkle::compute_merkle_path_root;
     use crate::poseidon31::Poseidon2HalfVar;
+    use crate::BitsVar;
     use crate::M31Var;
     use crate::Poseidon31MerkleHasherVar;
     use circle_plonk_dsl_constraint_system::var::AllocVar;
@@ -152,10 +575,12 @@ mod test {
     use num_traits::One;
     use rand::rngs::SmallRng;
     use rand::{Rng, SeedableRng};
+    use std::collections::BTreeMap;
     use stwo::core::fields::m31::M31;
     use stwo::core::fields::qm31::QM31;
     use stwo::core::fri::FriConfig;
     use stwo::core::pcs::PcsConfig;
+    use stwo::core::vcs::blake2_merkle::Blake2sMerkleHasher;
     use stwo::core::vcs::poseidon31_hash::Poseidon31Hash;
     use stwo::core::vcs::poseidon31_merkle::{Poseidon31MerkleChannel, Poseidon31MerkleHasher};
     use stwo::core::vcs::MerkleHasher;
@@ -274,4 +699,52 @@ mod test {
         )
         .unwrap();
     }
+
+    #[test]
+    fn test_blake2s_merkle_path_root() {
+        let mut prng = SmallRng::seed_from_u64(0);
+        let leaf_values: [[M31; 2]; 4] = prng.gen();
+        let layer_values: [M31; 2] = prng.gen();
+
+        // a tree of depth 2 with two columns at the leaves and one column at the layer above
+        let leaves = leaf_values.map(|values| Blake2sMerkleHasher::hash_node(None, &values));
+        let nodes: [_; 2] = std::array::from_fn(|j| {
+            Blake2sMerkleHasher::hash_node(
+                Some((leaves[2 * j], leaves[2 * j + 1])),
+                &[layer_values[j]],
+            )
+        });
+        let root = Blake2sMerkleHasher::hash_node(Some((nodes[0], nodes[1])), &[]);
+
+        let cs = ConstraintSystemRef::new();
+        let position = 2;
+        let query = BitsVar::from_m31(&M31Var::new_witness(&cs, &M31::from(position)), 2);
+        let sibling_hashes = [
+            Blake2sHashVar::new_witness(&cs, &leaves[3]),
+            Blake2sHashVar::new_witness(&cs, &nodes[0]),
+        ];
+        let mut columns = BTreeMap::new();
+        columns.insert(
+            2,
+            leaf_values[position as usize]
+                .iter()
+                .map(|v| M31Var::new_witness(&cs, v))
+                .collect::<Vec<_>>(),
+        );
+        columns.insert(1, vec![M31Var::new_witness(&cs, &layer_values[1])]);
+
+        let computed_root = compute_merkle_path_root::<Blake2sMerkleHasherVar>(
+            2,
+            &query,
+            &sibling_hashes,
+            &columns,
+        );
+        assert_eq!(computed_root.value(), root);
+        computed_root.equalverify(&Blake2sHashVar::new_witness(&cs, &root));
+
+        cs.pad();
+        cs.check_arithmetics();
+        cs.populate_logup_arguments();
+        cs.check_poseidon_invocations();
+    }
 }
@@ -1,9 +1,14 @@
-use crate::{BitIntVar, BitVar, ChannelVar, CirclePointQM31Var, M31Var, Poseidon2HalfVar, QM31Var};
+use crate::{
+    BitIntVar, BitVar, ChannelVar, CirclePointQM31Var, M31Var, Poseidon2HalfVar, QM31Var,
+    TranscriptLog,
+};
 use circle_plonk_dsl_constraint_system::{
     var::{AllocVar, AllocationMode, Var},
     ConstraintSystemRef,
 };
 use indexmap::IndexMap;
+use std::cell::RefCell;
+use std::rc::Rc;
 use stwo::core::fields::{m31::M31, qm31::QM31};
 use stwo_cairo_common::preprocessed_columns::preprocessed_trace::MAX_SEQUENCE_LOG_SIZE;
 use stwo_cairo_common::prover_types::simd::LOG_N_LANES;
@@ -187,23 +192,28 @@ impl<T: SelectVar> SelectVar for (T, T) {
 }
 
 impl SelectVar for ChannelVar {
-    type SelectSession = [QM31Var; 2];
+    type SelectSession = ([QM31Var; 2], Option<Rc<RefCell<TranscriptLog>>>);
     type Output = ChannelVar;
 
     fn select_start(cs: &ConstraintSystemRef) -> Self::SelectSession {
-        [QM31Var::zero(cs), QM31Var::zero(cs)]
+        ([QM31Var::zero(cs), QM31Var::zero(cs)], None)
     }
 
     fn select_add(session: &mut Self::SelectSession, new: &Self, bit: &BitVar) {
         let new_qm31 = new.digest.to_qm31();
-        session[0] = QM31Var::select(&session[0], &new_qm31[0], bit);
-        session[1] = QM31Var::select(&session[1], &new_qm31[1], bit);
+        session.0[0] = QM31Var::select(&session.0[0], &new_qm31[0], bit);
+        session.0[1] = QM31Var::select(&session.0[1], &new_qm31[1], bit);
+        // the selected channel keeps logging into its own transcript
+        if bit.value() {
+            session.1 = new.log.clone();
+        }
     }
 
     fn select_end(session: Self::SelectSession) -> Self::Output {
         ChannelVar {
-            digest: Poseidon2HalfVar::from_qm31(&session[0], &session[1]),
+            digest: Poseidon2HalfVar::from_qm31(&session.0[0], &session.0[1]),
             n_sent: 0,
+            log: session.1,
         }
     }
 }
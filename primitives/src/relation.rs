@@ -0,0 +1,98 @@
+use crate::fields::WrappedQM31Var;
+use crate::QM31Var;
+use std::ops::Mul;
+use stwo::core::fields::qm31::SECURE_EXTENSION_DEGREE;
+use stwo::core::Fraction;
+use stwo_constraint_framework::preprocessed_columns::PreProcessedColumnId;
+use stwo_constraint_framework::{EvalAtRow, Relation, RelationEntry};
+
+/// The in-circuit lookup elements of the relations of a native `FrameworkEval`, which are found
+/// by the names of the native relations.
+pub trait RelationElementsVar {
+    /// The denominator of an entry of the relation with the native name `relation`.
+    fn combine(&mut self, relation: &str, values: &[WrappedQM31Var]) -> QM31Var;
+}
+
+/// An [`EvalAtRow`] through which a native `FrameworkEval` is evaluated in the circuit.
+///
+/// The native `Eval` carries native lookup elements, so every relation entry is redirected to the
+/// in-circuit lookup elements of the relation with the same name, and the circuit does not depend
+/// on the drawn elements. Everything else is forwarded to the inner evaluator.
+pub struct RelationRedirect<E, R> {
+    pub inner: E,
+    pub relations: R,
+}
+
+impl<E, R> RelationRedirect<E, R> {
+    pub fn new(inner: E, relations: R) -> Self {
+        Self { inner, relations }
+    }
+}
+
+impl<E, R> EvalAtRow for RelationRedirect<E, R>
+where
+    E: EvalAtRow<F = WrappedQM31Var, EF = WrappedQM31Var>,
+    R: RelationElementsVar,
+{
+    type F = WrappedQM31Var;
+    type EF = WrappedQM31Var;
+
+    fn get_preprocessed_column(&mut self, column: PreProcessedColumnId) -> Self::F {
+        self.inner.get_preprocessed_column(column)
+    }
+
+    fn next_interaction_mask<const N: usize>(
+        &mut self,
+        interaction: usize,
+        offsets: [isize; N],
+    ) -> [Self::F; N] {
+        self.inner.next_interaction_mask(interaction, offsets)
+    }
+
+    fn add_constraint<G>(&mut self, constraint: G)
+    where
+        Self::EF: Mul<G, Output = Self::EF> + From<G>,
+    {
+        self.inner.add_constraint(constraint)
+    }
+
+    fn combine_ef(values: [Self::F; SECURE_EXTENSION_DEGREE]) -> Self::EF {
+        E::combine_ef(values)
+    }
+
+    fn add_to_relation<Rel: Relation<Self::F, Self::EF>>(
+        &mut self,
+        entry: RelationEntry<Self::F, Self::EF, Rel>,
+    ) {
+        let denom = self
+            .relations
+            .combine(entry.relation.get_name(), entry.values);
+        self.inner.write_logup_frac(Fraction::new(
+            entry.multiplicity,
+            WrappedQM31Var::wrap(denom),
+        ));
+    }
+
+    fn add_to_relation_ef<Rel: Relation<Self::F, Self::EF>>(
+        &mut self,
+        entry: RelationEntry<Self::F, Self::EF, Rel>,
+    ) {
+        self.add_to_relation(entry);
+    }
+
+    fn write_logup_frac(&mut self, fraction: Fraction<Self::EF, Self::EF>) {
+        self.inner.write_logup_frac(fraction);
+    }
+
+    fn finalize_logup(&mut self) {
+        self.inner.finalize_logup();
+    }
+
+    fn finalize_logup_in_pairs(&mut self) {
+        self.inner.finalize_logup_in_pairs();
+    }
+
+    fn finalize_logup_batched(&mut self, batching: &Vec<usize>) {
+        self.inner.finalize_logup_batched(batching);
+    }
+}
@@ -0,0 +1,381 @@
+use crate::bits::BitsVar;
+use crate::poseidon31::Poseidon2HalfVar;
+use crate::{M31Var, Poseidon31MerkleHasherVar};
+use circle_plonk_dsl_constraint_system::var::{AllocVar, AllocationMode, Var};
+use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
+use std::collections::HashMap;
+use stwo::core::vcs::poseidon31_hash::Poseidon31Hash;
+use stwo::core::vcs::poseidon31_merkle::Poseidon31MerkleHasher;
+use stwo::core::vcs::MerkleHasher;
+
+/// The hash of an occupied leaf, which binds the value and differs from the all-zero empty leaf.
+pub fn smt_leaf_hash(value: &Poseidon31Hash) -> Poseidon31Hash {
+    Poseidon31MerkleHasher::hash_node(Some((*value, Poseidon31Hash::default())), &[])
+}
+
+/// The roots of empty subtrees, where `res[h]` is the root of an empty subtree of height `h`.
+pub fn smt_empty_hashes(depth: usize) -> Vec<Poseidon31Hash> {
+    let mut res = vec![Poseidon31Hash::default()];
+    for h in 0..depth {
+        res.push(Poseidon31MerkleHasher::hash_node(
+            Some((res[h], res[h])),
+            &[],
+        ));
+    }
+    res
+}
+
+/// The maximal depth of a sparse Merkle tree, so that every key of the tree, up to `2^depth - 1`,
+/// is a canonical M31 element. At depth 31, the keys `2^31 - 1` and above would not be.
+pub const SMT_MAX_DEPTH: usize = 30;
+
+/// A native sparse Merkle tree over Poseidon31 indexed by the `depth` lower bits of the key,
+/// used to generate the witnesses for [`SparseMerkleProofVar`].
+#[derive(Clone, Debug)]
+pub struct SparseMerkleTree {
+    pub depth: usize,
+    pub values: HashMap<u32, Poseidon31Hash>,
+    /// The non-empty nodes, where `nodes[h]` maps a position at height `depth - h` to its hash.
+    pub nodes: Vec<HashMap<u32, Poseidon31Hash>>,
+    pub empty_hashes: Vec<Poseidon31Hash>,
+}
+
+/// The authentication path of a key in a [`SparseMerkleTree`], from the leaf to the root.
+#[derive(Clone, Debug)]
+pub struct SparseMerkleProof {
+    pub key: u32,
+    pub value: Option<Poseidon31Hash>,
+    pub siblings: Vec<Poseidon31Hash>,
+}
+
+impl SparseMerkleTree {
+    pub fn new(depth: usize) -> Self {
+        assert!(depth <= SMT_MAX_DEPTH);
+        Self {
+            depth,
+            values: HashMap::new(),
+            nodes: vec![HashMap::new(); depth + 1],
+            empty_hashes: smt_empty_hashes(depth),
+        }
+    }
+
+    fn node(&self, h: usize, position: u32) -> Poseidon31Hash {
+        *self.nodes[h]
+            .get(&position)
+            .unwrap_or(&self.empty_hashes[h])
+    }
+
+    pub fn root(&self) -> Poseidon31Hash {
+        self.node(self.depth, 0)
+    }
+
+    pub fn get(&self, key: u32) -> Option<Poseidon31Hash> {
+        self.values.get(&key).copied()
+    }
+
+    pub fn insert(&mut self, key: u32, value: Poseidon31Hash) {
+        assert!((key as u64) < 1u64 << self.depth);
+        self.values.insert(key, value);
+
+        let mut position = key;
+        let mut cur = smt_leaf_hash(&value);
+        self.nodes[0].insert(position, cur);
+        for h in 0..self.depth {
+            let sibling = self.node(h, position ^ 1);
+            cur = if position & 1 == 0 {
+                Poseidon31MerkleHasher::hash_node(Some((cur, sibling)), &[])
+            } else {
+                Poseidon31MerkleHasher::hash_node(Some((sibling, cur)), &[])
+            };
+            position >>= 1;
+            self.nodes[h + 1].insert(position, cur);
+        }
+    }
+
+    pub fn prove(&self, key: u32) -> SparseMerkleProof {
+        let mut siblings = Vec::with_capacity(self.depth);
+        let mut position = key;
+        for h in 0..self.depth {
+            siblings.push(self.node(h, position ^ 1));
+            position >>= 1;
+        }
+        SparseMerkleProof {
+            key,
+            value: self.get(key),
+            siblings,
+        }
+    }
+}
+
+impl SparseMerkleProof {
+    pub fn compute_root(&self, leaf: Poseidon31Hash) -> Poseidon31Hash {
+        let mut cur = leaf;
+        for (i, sibling) in self.siblings.iter().enumerate() {
+            cur = if (self.key >> i) & 1 == 0 {
+                Poseidon31MerkleHasher::hash_node(Some((cur, *sibling)), &[])
+            } else {
+                Poseidon31MerkleHasher::hash_node(Some((*sibling, cur)), &[])
+            };
+        }
+        cur
+    }
+
+    pub fn verify(&self, root: Poseidon31Hash) {
+        let leaf = self
+            .value
+            .as_ref()
+            .map_or(Poseidon31Hash::default(), smt_leaf_hash);
+        assert_eq!(self.compute_root(leaf), root);
+    }
+}
+
+/// The in-circuit authentication path of a key in a sparse Merkle tree, from the leaf to the root.
+///
+/// The siblings are regular witnesses (not single-use) because an update proof hashes them twice,
+/// once for the old root and once for the new root.
+#[derive(Debug, Clone)]
+pub struct SparseMerkleProofVar {
+    pub cs: ConstraintSystemRef,
+    pub siblings: Vec<Poseidon2HalfVar>,
+}
+
+impl Var for SparseMerkleProofVar {
+    type Value = SparseMerkleProof;
+
+    fn cs(&self) -> ConstraintSystemRef {
+        self.cs.clone()
+    }
+}
+
+impl AllocVar for SparseMerkleProofVar {
+    fn new_variables(cs: &ConstraintSystemRef, value: &Self::Value, mode: AllocationMode) -> Self {
+        let siblings = value
+            .siblings
+            .iter()
+            .map(|sibling| Poseidon2HalfVar::new_variables(cs, sibling, mode))
+            .collect();
+        Self {
+            cs: cs.clone(),
+            siblings,
+        }
+    }
+}
+
+impl SparseMerkleProofVar {
+    pub fn depth(&self) -> usize {
+        self.siblings.len()
+    }
+
+    pub fn leaf_hash(value: &Poseidon2HalfVar) -> Poseidon2HalfVar {
+        let cs = value.cs();
+        Poseidon31MerkleHasherVar::hash_tree(value, &Poseidon2HalfVar::zero(&cs))
+    }
+
+    /// Decompose the key into its path bits, which also enforces that the key fits in the depth.
+    pub fn key_bits(&self, key: &M31Var) -> BitsVar {
+        assert!(self.depth() <= SMT_MAX_DEPTH);
+        BitsVar::from_m31(key, self.depth())
+    }
+
+    pub fn compute_root(&self, key_bits: &BitsVar, leaf: &Poseidon2HalfVar) -> Poseidon2HalfVar {
+        assert_eq!(key_bits.0.len(), self.depth());
+
+        let mut cur = leaf.clone();
+        for (sibling, bit) in self.siblings.iter().zip(key_bits.0.iter()) {
+            cur = Poseidon31MerkleHasherVar::hash_tree_with_swap(&cur, sibling, bit);
+        }
+        cur
+    }
+
+    pub fn verify_membership(
+        &self,
+        root: &Poseidon2HalfVar,
+        key: &M31Var,
+        value: &Poseidon2HalfVar,
+    ) {
+        let key_bits = self.key_bits(key);
+        let computed_root = self.compute_root(&key_bits, &Self::leaf_hash(value));
+        computed_root.equalverify(root);
+    }
+
+    pub fn verify_non_membership(&self, root: &Poseidon2HalfVar, key: &M31Var) {
+        let cs = self.cs().and(&key.cs());
+        let key_bits = self.key_bits(key);
+        let computed_root = self.compute_root(&key_bits, &Poseidon2HalfVar::zero(&cs));
+        computed_root.equalverify(root);
+    }
+
+    /// Verify that replacing the value at `key` by `new_value` turns `old_root` into `new_root`.
+    pub fn verify_update(
+        &self,
+        old_root: &Poseidon2HalfVar,
+        new_root: &Poseidon2HalfVar,
+        key: &M31Var,
+        old_value: &Poseidon2HalfVar,
+        new_value: &Poseidon2HalfVar,
+    ) {
+        let key_bits = self.key_bits(key);
+        self.compute_root(&key_bits, &Self::leaf_hash(old_value))
+            .equalverify(old_root);
+        self.compute_root(&key_bits, &Self::leaf_hash(new_value))
+            .equalverify(new_root);
+    }
+
+    /// Verify that inserting `value` at a previously empty `key` turns `old_root` into `new_root`.
+    pub fn verify_insert(
+        &self,
+        old_root: &Poseidon2HalfVar,
+        new_root: &Poseidon2HalfVar,
+        key: &M31Var,
+        value: &Poseidon2HalfVar,
+    ) {
+        let cs = self.cs().and(&key.cs());
+        let key_bits = self.key_bits(key);
+        self.compute_root(&key_bits, &Poseidon2HalfVar::zero(&cs))
+            .equalverify(old_root);
+        self.compute_root(&key_bits, &Self::leaf_hash(value))
+            .equalverify(new_root);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::smt::{SparseMerkleProof, SparseMerkleProofVar, SparseMerkleTree, SMT_MAX_DEPTH};
+    use crate::{M31Var, Poseidon2HalfVar};
+    use circle_plonk_dsl_constraint_system::var::AllocVar;
+    use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+    use stwo::core::fields::m31::M31;
+    use stwo::core::vcs::poseidon31_hash::Poseidon31Hash;
+
+    #[test]
+    fn test_sparse_merkle_tree() {
+        let mut prng = SmallRng::seed_from_u64(0);
+        let mut tree = SparseMerkleTree::new(20);
+
+        for key in [3u32, 1000, 65536] {
+            tree.insert(key, Poseidon31Hash(prng.gen::<[M31; 8]>()));
+        }
+
+        let cs = ConstraintSystemRef::new();
+
+        // membership
+        let root = Poseidon2HalfVar::new_witness(&cs, &tree.root());
+        let proof = tree.prove(1000);
+        proof.verify(tree.root());
+        let proof_var = SparseMerkleProofVar::new_witness(&cs, &proof);
+        proof_var.verify_membership(
+            &root,
+            &M31Var::new_witness(&cs, &M31::from(1000)),
+            &Poseidon2HalfVar::new_witness(&cs, &proof.value.unwrap()),
+        );
+
+        // non-membership, followed by an insertion
+        let key = 12345u32;
+        let proof = tree.prove(key);
+        assert!(proof.value.is_none());
+        proof.verify(tree.root());
+        let proof_var = SparseMerkleProofVar::new_witness(&cs, &proof);
+        let key_var = M31Var::new_witness(&cs, &M31::from(key));
+        proof_var.verify_non_membership(&root, &key_var);
+
+        let value = Poseidon31Hash(prng.gen::<[M31; 8]>());
+        tree.insert(key, value);
+        let new_root = Poseidon2HalfVar::new_witness(&cs, &tree.root());
+        proof_var.verify_insert(
+            &root,
+            &new_root,
+            &key_var,
+            &Poseidon2HalfVar::new_witness(&cs, &value),
+        );
+
+        // update of an existing key
+        let proof = tree.prove(3);
+        let old_value = proof.value.unwrap();
+        let proof_var = SparseMerkleProofVar::new_witness(&cs, &proof);
+        let new_value = Poseidon31Hash(prng.gen::<[M31; 8]>());
+        tree.insert(3, new_value);
+        proof_var.verify_update(
+            &new_root,
+            &Poseidon2HalfVar::new_witness(&cs, &tree.root()),
+            &M31Var::new_witness(&cs, &M31::from(3)),
+            &Poseidon2HalfVar::new_witness(&cs, &old_value),
+            &Poseidon2HalfVar::new_witness(&cs, &new_value),
+        );
+
+        cs.pad();
+        cs.check_arithmetics();
+        cs.populate_logup_arguments();
+        cs.check_poseidon_invocations();
+    }
+
+    #[test]
+    fn test_sparse_merkle_tree_largest_key() {
+        let mut prng = SmallRng::seed_from_u64(0);
+        for depth in [4, SMT_MAX_DEPTH] {
+            let mut tree = SparseMerkleTree::new(depth);
+            let key = ((1u64 << depth) - 1) as u32;
+            let value = Poseidon31Hash(prng.gen::<[M31; 8]>());
+            tree.insert(key, value);
+            let proof = tree.prove(key);
+            proof.verify(tree.root());
+
+            let cs = ConstraintSystemRef::new();
+            let proof_var = SparseMerkleProofVar::new_witness(&cs, &proof);
+            proof_var.verify_membership(
+                &Poseidon2HalfVar::new_witness(&cs, &tree.root()),
+                &M31Var::new_witness(&cs, &M31::from(key)),
+                &Poseidon2HalfVar::new_witness(&cs, &value),
+            );
+
+            cs.pad();
+            cs.check_arithmetics();
+            cs.populate_logup_arguments();
+            cs.check_poseidon_invocations();
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sparse_merkle_proof_var_key_out_of_range() {
+        let tree = SparseMerkleTree::new(SMT_MAX_DEPTH);
+        let proof = tree.prove(0);
+
+        let cs = ConstraintSystemRef::new();
+        let proof_var = SparseMerkleProofVar::new_witness(&cs, &proof);
+        proof_var.verify_non_membership(
+            &Poseidon2HalfVar::new_witness(&cs, &tree.root()),
+            &M31Var::new_witness(&cs, &M31::from(1u32 << SMT_MAX_DEPTH)),
+        );
+
+        cs.pad();
+        cs.check_arithmetics();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sparse_merkle_tree_depth_out_of_range() {
+        SparseMerkleTree::new(SMT_MAX_DEPTH + 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sparse_merkle_proof_var_depth_out_of_range() {
+        let cs = ConstraintSystemRef::new();
+        let proof = SparseMerkleProof {
+            key: 0,
+            value: None,
+            siblings: vec![Poseidon31Hash::default(); SMT_MAX_DEPTH + 1],
+        };
+        let proof_var = SparseMerkleProofVar::new_witness(&cs, &proof);
+        proof_var.key_bits(&M31Var::new_witness(&cs, &M31::from(0)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sparse_merkle_tree_key_out_of_range() {
+        let mut tree = SparseMerkleTree::new(4);
+        tree.insert(16, Poseidon31Hash::default());
+    }
+}
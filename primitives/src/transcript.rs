@@ -0,0 +1,308 @@
+use std::fmt::{Display, Formatter};
+use stwo::core::channel::{Channel, MerkleChannel, Poseidon31Channel};
+use stwo::core::fields::m31::M31;
+use stwo::core::fields::qm31::QM31;
+use stwo::core::vcs::poseidon31_hash::Poseidon31Hash;
+use stwo::core::vcs::poseidon31_merkle::Poseidon31MerkleChannel;
+
+/// An operation on a Fiat-Shamir channel, as seen by the transcript log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TranscriptOp {
+    MixRoot([M31; 8]),
+    MixOneFelt(QM31),
+    MixTwoFelts(QM31, QM31),
+    /// A draw, identified by the number of draws since the last mix.
+    DrawFelts(usize),
+}
+
+/// A logged channel operation together with the scope it happened in and the digest after it.
+#[derive(Clone, Debug)]
+pub struct TranscriptEntry {
+    pub scope: String,
+    pub op: TranscriptOp,
+    pub digest: [M31; 8],
+}
+
+/// The log of channel operations. A clone of a channel continues from a copy of it.
+#[derive(Clone, Debug, Default)]
+pub struct TranscriptLog {
+    pub scopes: Vec<String>,
+    pub entries: Vec<TranscriptEntry>,
+}
+
+impl TranscriptLog {
+    pub fn enter_scope(&mut self, scope: &str) {
+        self.scopes.push(scope.to_string());
+    }
+
+    pub fn exit_scope(&mut self) {
+        self.scopes.pop().expect("no scope to exit");
+    }
+
+    pub fn record(&mut self, op: TranscriptOp, digest: [M31; 8]) {
+        self.entries.push(TranscriptEntry {
+            scope: self.scopes.join("/"),
+            op,
+            digest,
+        });
+    }
+}
+
+/// The first position at which the in-circuit and the native transcripts disagree.
+#[derive(Clone, Debug)]
+pub struct TranscriptDivergence {
+    pub index: usize,
+    pub circuit: Option<TranscriptEntry>,
+    pub native: Option<TranscriptEntry>,
+}
+
+impl Display for TranscriptDivergence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "transcripts diverge at operation {}", self.index)?;
+        match &self.circuit {
+            Some(entry) => writeln!(
+                f,
+                "  circuit [{}]: {:?}, digest {:?}",
+                entry.scope, entry.op, entry.digest
+            )?,
+            None => writeln!(f, "  circuit: <end of transcript>")?,
+        }
+        match &self.native {
+            Some(entry) => write!(
+                f,
+                "  native  [{}]: {:?}, digest {:?}",
+                entry.scope, entry.op, entry.digest
+            ),
+            None => write!(f, "  native: <end of transcript>"),
+        }
+    }
+}
+
+/// Compare the operations and the digests of two transcripts, ignoring the scopes.
+pub fn compare_transcripts(
+    circuit: &TranscriptLog,
+    native: &TranscriptLog,
+) -> Result<(), TranscriptDivergence> {
+    let len = circuit.entries.len().max(native.entries.len());
+    for index in 0..len {
+        let a = circuit.entries.get(index);
+        let b = native.entries.get(index);
+        let matches = match (a, b) {
+            (Some(a), Some(b)) => a.op == b.op && a.digest == b.digest,
+            _ => false,
+        };
+        if !matches {
+            return Err(TranscriptDivergence {
+                index,
+                circuit: a.cloned(),
+                native: b.cloned(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// A native Poseidon31 channel that logs the operations that have an in-circuit counterpart in
+/// [`crate::ChannelVar`].
+#[derive(Clone, Debug, Default)]
+pub struct LoggedPoseidon31Channel {
+    pub channel: Poseidon31Channel,
+    pub n_draws: usize,
+    pub log: TranscriptLog,
+}
+
+impl LoggedPoseidon31Channel {
+    pub fn new(channel: Poseidon31Channel) -> Self {
+        Self {
+            channel,
+            n_draws: 0,
+            log: TranscriptLog::default(),
+        }
+    }
+
+    pub fn enter_scope(&mut self, scope: &str) {
+        self.log.enter_scope(scope);
+    }
+
+    pub fn exit_scope(&mut self) {
+        self.log.exit_scope();
+    }
+
+    pub fn mix_root(&mut self, root: Poseidon31Hash) {
+        Poseidon31MerkleChannel::mix_root(&mut self.channel, root);
+        self.n_draws = 0;
+        self.log
+            .record(TranscriptOp::MixRoot(root.0), self.channel.digest());
+    }
+
+    /// Mix the felts two by two, in the same way as the verifier circuits do.
+    pub fn mix_felts(&mut self, felts: &[QM31]) {
+        for chunk in felts.chunks(2) {
+            self.channel.mix_felts(chunk);
+            self.n_draws = 0;
+            let op = if chunk.len() == 1 {
+                TranscriptOp::MixOneFelt(chunk[0])
+            } else {
+                TranscriptOp::MixTwoFelts(chunk[0], chunk[1])
+            };
+            self.log.record(op, self.channel.digest());
+        }
+    }
+
+    /// Mix the proof-of-work nonce as a single felt of three limbs, as the verifier circuits do.
+    pub fn mix_u64(&mut self, nonce: u64) {
+        self.channel.mix_u64(nonce);
+        self.n_draws = 0;
+        let felt = QM31::from_m31(
+            M31::from((nonce & ((1 << 22) - 1)) as u32),
+            M31::from(((nonce >> 22) & ((1 << 21) - 1)) as u32),
+            M31::from(((nonce >> 43) & ((1 << 21) - 1)) as u32),
+            M31::from(0),
+        );
+        self.log
+            .record(TranscriptOp::MixOneFelt(felt), self.channel.digest());
+    }
+
+    pub fn draw_secure_felt(&mut self) -> QM31 {
+        let res = self.channel.draw_secure_felt();
+        self.record_draw();
+        res
+    }
+
+    pub fn draw_u32s(&mut self) -> Vec<u32> {
+        let res = self.channel.draw_u32s();
+        self.record_draw();
+        res
+    }
+
+    fn record_draw(&mut self) {
+        self.log
+            .record(TranscriptOp::DrawFelts(self.n_draws), self.channel.digest());
+        self.n_draws += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::transcript::{compare_transcripts, LoggedPoseidon31Channel, TranscriptOp};
+    use crate::{ChannelVar, LogSizeVar, ObliviousMapVar, Poseidon2HalfVar, QM31Var};
+    use circle_plonk_dsl_constraint_system::var::AllocVar;
+    use circle_plonk_dsl_constraint_system::ConstraintSystemRef;
+    use indexmap::IndexMap;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+    use stwo::core::fields::m31::M31;
+    use stwo::core::fields::qm31::QM31;
+    use stwo::core::vcs::poseidon31_hash::Poseidon31Hash;
+
+    #[test]
+    fn test_transcript_comparison() {
+        let mut prng = SmallRng::seed_from_u64(0);
+        let root = Poseidon31Hash(prng.gen::<[M31; 8]>());
+        let felts: [QM31; 3] = prng.gen();
+
+        let cs = ConstraintSystemRef::new();
+        let mut channel_var = ChannelVar::default(&cs);
+        channel_var.enable_transcript_log();
+        let mut native = LoggedPoseidon31Channel::default();
+
+        channel_var.enter_scope("commitments");
+        channel_var.mix_root(&Poseidon2HalfVar::new_witness(&cs, &root));
+        let _ = channel_var.draw_felts();
+        channel_var.exit_scope();
+        native.enter_scope("commitments");
+        native.mix_root(root);
+        let _ = native.draw_secure_felt();
+        native.exit_scope();
+
+        let felt_vars = felts
+            .iter()
+            .map(|felt| QM31Var::new_witness(&cs, felt))
+            .collect::<Vec<_>>();
+        channel_var.mix_two_felts(&felt_vars[0], &felt_vars[1]);
+        channel_var.mix_one_felt(&felt_vars[2]);
+        native.mix_felts(&felts);
+
+        let circuit_log = channel_var.transcript_log().unwrap();
+        assert_eq!(circuit_log.entries.len(), 4);
+        assert_eq!(circuit_log.entries[0].scope, "commitments");
+        compare_transcripts(&circuit_log, &native.log).unwrap();
+
+        // an extra native mix is reported as the first divergence
+        native.mix_felts(&felts[0..1]);
+        let divergence = compare_transcripts(&circuit_log, &native.log).unwrap_err();
+        assert_eq!(divergence.index, 4);
+        assert!(divergence.circuit.is_none());
+
+        // so is a mix of a different felt
+        channel_var.mix_one_felt(&felt_vars[1]);
+        let circuit_log = channel_var.transcript_log().unwrap();
+        let divergence = compare_transcripts(&circuit_log, &native.log).unwrap_err();
+        assert_eq!(divergence.index, 4);
+        assert!(divergence.circuit.is_some() && divergence.native.is_some());
+    }
+
+    #[test]
+    fn test_transcript_after_select() {
+        let mut prng = SmallRng::seed_from_u64(0);
+        let felts: [QM31; 3] = prng.gen();
+
+        let cs = ConstraintSystemRef::new();
+        let felt_vars = felts
+            .iter()
+            .map(|felt| QM31Var::new_witness(&cs, felt))
+            .collect::<Vec<_>>();
+
+        let mut map = IndexMap::new();
+        for (log_size, felt_var) in [(5, &felt_vars[0]), (6, &felt_vars[1])] {
+            let mut channel_var = ChannelVar::default(&cs);
+            channel_var.enable_transcript_log();
+            channel_var.mix_one_felt(felt_var);
+            map.insert(log_size, channel_var);
+        }
+
+        let key = LogSizeVar::new_witness(&cs, &6);
+        let mut selected = ObliviousMapVar::new(map).select(&key);
+        selected.mix_one_felt(&felt_vars[2]);
+
+        let mut native = LoggedPoseidon31Channel::default();
+        native.mix_felts(&felts[1..2]);
+        native.mix_felts(&felts[2..3]);
+
+        // the selected channel keeps logging into the transcript of the channel at the key
+        let circuit_log = selected.transcript_log().unwrap();
+        assert_eq!(circuit_log.entries.len(), 2);
+        compare_transcripts(&circuit_log, &native.log).unwrap();
+    }
+
+    #[test]
+    fn test_transcript_of_a_clone() {
+        let mut prng = SmallRng::seed_from_u64(0);
+        let felts: [QM31; 2] = prng.gen();
+
+        let cs = ConstraintSystemRef::new();
+        let felt_vars = felts
+            .iter()
+            .map(|felt| QM31Var::new_witness(&cs, felt))
+            .collect::<Vec<_>>();
+
+        let mut channel_var = ChannelVar::default(&cs);
+        channel_var.enable_transcript_log();
+        channel_var.mix_one_felt(&felt_vars[0]);
+
+        // a lookahead draw on a clone is not part of the transcript of the original
+        let mut lookahead = channel_var.clone();
+        let _ = lookahead.draw_felts();
+        channel_var.mix_one_felt(&felt_vars[1]);
+
+        let mut native = LoggedPoseidon31Channel::default();
+        native.mix_felts(&felts[0..1]);
+        native.mix_felts(&felts[1..2]);
+        compare_transcripts(&channel_var.transcript_log().unwrap(), &native.log).unwrap();
+
+        // and the clone keeps the operations before the clone
+        let lookahead_log = lookahead.transcript_log().unwrap();
+        assert_eq!(lookahead_log.entries.len(), 2);
+        assert_eq!(lookahead_log.entries[1].op, TranscriptOp::DrawFelts(0));
+    }
+}